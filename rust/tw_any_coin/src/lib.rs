@@ -9,6 +9,7 @@ pub mod message_signer;
 pub mod transaction_compiler;
 pub mod transaction_decoder;
 pub mod wallet_connect_request;
+pub mod watch_only;
 
 #[cfg(feature = "test-utils")]
 pub mod test_utils;
@@ -0,0 +1,286 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Watch-only derivation of receive and change addresses from an account-level extended public key,
+//! e.g. `m/44'/60'/0'`, so deposit addresses can be generated without any private keys:
+//!
+//! ```rust,ignore
+//! let account = WatchOnlyAccount::with_xpub(CoinType::Ethereum, ACCOUNT_XPUB)?;
+//! let deposit_addresses = account.derive_addresses(AddressChain::Receive, 0..1000)?;
+//! ```
+//!
+//! Only the coins of curves that support non-hardened derivation, i.e. `secp256k1` and `nist256p1`, are supported.
+//! Bitcoin accounts may also be given as SLIP-132 `ypub` or `zpub` keys, which derive P2SH-P2WPKH or P2WPKH addresses.
+//! The testnet keys, e.g. `tpub` or `vpub`, derive the testnet addresses of Bitcoin.
+//!
+//! When an account is restored, the used addresses are discovered as specified in
+//! [BIP44](https://github.com/bitcoin/bips/blob/master/bip-0044.mediawiki#address-gap-limit),
+//! i.e. the addresses are scanned until `gap_limit` consecutive addresses have no activity:
+//!
+//! ```rust,ignore
+//! let receive = account.discover(AddressChain::Receive, DEFAULT_GAP_LIMIT, |address| has_transactions(address.description()))?;
+//! let next_receive_address = account.derive_address(AddressChain::Receive, receive.next_index())?;
+//! ```
+
+use crate::any_address::AnyAddress;
+use std::ops::Range;
+use tw_coin_entry::derivation::Derivation;
+use tw_coin_entry::error::prelude::*;
+use tw_coin_entry::prefix::AddressPrefix;
+use tw_coin_registry::blockchain_type::BlockchainType;
+use tw_coin_registry::coin_type::CoinType;
+use tw_coin_registry::registry::get_coin_item;
+use tw_encoding::base58::{self, Alphabet};
+use tw_hash::ripemd::ripemd_160;
+use tw_hash::sha2::sha256;
+use tw_keypair::bip32::slip132::{Network, ScriptType};
+use tw_keypair::bip32::{
+    Bip32Curve, ExtendedPublicKey, Nist256p1, Secp256k1, TPUB_VERSION, XPUB_VERSION,
+};
+use tw_keypair::tw::{PublicKey, PublicKeyType};
+
+/// The gap limit that BIP44 recommends.
+pub const DEFAULT_GAP_LIMIT: u32 = 20;
+
+/// The bech32 prefix of the Bitcoin testnet addresses.
+const BITCOIN_TESTNET_HRP: &str = "tb";
+/// The version bytes of the Bitcoin P2SH addresses.
+const BITCOIN_P2SH_VERSION: u8 = 0x05;
+const BITCOIN_TESTNET_P2SH_VERSION: u8 = 0xc4;
+
+/// The `change` level of a BIP44 path.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AddressChain {
+    /// Addresses that are given out to receive funds.
+    Receive = 0,
+    /// Addresses that receive the change of outgoing transactions.
+    Change = 1,
+}
+
+/// The result of the discovery of a chain of addresses.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ChainActivity {
+    /// The indices of the addresses that have activity, in ascending order.
+    pub used: Vec<u32>,
+    /// The indices of the scanned addresses that have no activity, in ascending order,
+    /// i.e. the gaps between the used addresses and the trailing `gap_limit` addresses.
+    pub unused: Vec<u32>,
+}
+
+impl ChainActivity {
+    /// Returns whether any address of the chain has activity.
+    pub fn is_used(&self) -> bool {
+        !self.used.is_empty()
+    }
+
+    /// Returns the index that follows the last used address, i.e. of the next address to give out.
+    pub fn next_index(&self) -> u32 {
+        self.used.last().map_or(0, |last| last + 1)
+    }
+}
+
+/// An account-level extended public key of a specific coin.
+pub struct WatchOnlyAccount {
+    coin: CoinType,
+    blockchain: BlockchainType,
+    public_key_type: PublicKeyType,
+    script_type: ScriptType,
+    network: Network,
+    key: AccountKey,
+}
+
+enum AccountKey {
+    Secp256k1(ExtendedPublicKey<Secp256k1>),
+    Nist256p1(ExtendedPublicKey<Nist256p1>),
+}
+
+impl WatchOnlyAccount {
+    /// Creates an account from a base58 `xpub` or `tpub` extended public key,
+    /// or any other SLIP-132 extended public key of a Bitcoin account.
+    pub fn with_xpub(coin: CoinType, xpub: &str) -> AddressResult<WatchOnlyAccount> {
+        let item = get_coin_item(coin).map_err(|_| AddressError::UnknownCoinType)?;
+        if !item.blockchain.is_supported() {
+            return Err(AddressError::Unsupported);
+        }
+
+        let (key, script_type, network) = match item.public_key_type {
+            PublicKeyType::Secp256k1 | PublicKeyType::Secp256k1Extended => {
+                let (key, version) = ExtendedPublicKey::from_slip132(xpub)
+                    .map_err(|_| AddressError::InvalidInput)?;
+                // SegWit keys are specific to Bitcoin.
+                if version.script_type != ScriptType::P2pkh
+                    && item.blockchain != BlockchainType::Bitcoin
+                {
+                    return Err(AddressError::Unsupported);
+                }
+                (
+                    AccountKey::Secp256k1(key),
+                    version.script_type,
+                    version.network,
+                )
+            },
+            PublicKeyType::Nist256p1 | PublicKeyType::Nist256p1Extended => (
+                AccountKey::Nist256p1(decode_xpub(xpub)?),
+                ScriptType::P2pkh,
+                Network::Mainnet,
+            ),
+            // Hardened derivation only.
+            _ => return Err(AddressError::Unsupported),
+        };
+        Ok(WatchOnlyAccount {
+            coin,
+            blockchain: item.blockchain,
+            public_key_type: item.public_key_type,
+            script_type,
+            network,
+            key,
+        })
+    }
+
+    /// Derives the address at `account/chain/index`.
+    pub fn derive_address(&self, chain: AddressChain, index: u32) -> AddressResult<AnyAddress> {
+        let end = index.checked_add(1).ok_or(AddressError::InvalidInput)?;
+        self.derive_addresses(chain, index..end)?
+            .pop()
+            .ok_or(AddressError::InvalidInput)
+    }
+
+    /// Derives the addresses at `account/chain/index` for every index in the given range.
+    pub fn derive_addresses(
+        &self,
+        chain: AddressChain,
+        indices: Range<u32>,
+    ) -> AddressResult<Vec<AnyAddress>> {
+        match self.key {
+            AccountKey::Secp256k1(ref account) => {
+                self.derive_addresses_impl(account, chain, indices, |public| {
+                    match self.public_key_type {
+                        PublicKeyType::Secp256k1Extended => PublicKey::Secp256k1Extended(public),
+                        _ => PublicKey::Secp256k1(public),
+                    }
+                })
+            },
+            AccountKey::Nist256p1(ref account) => {
+                self.derive_addresses_impl(account, chain, indices, |public| {
+                    match self.public_key_type {
+                        PublicKeyType::Nist256p1Extended => PublicKey::Nist256p1Extended(public),
+                        _ => PublicKey::Nist256p1(public),
+                    }
+                })
+            },
+        }
+    }
+
+    /// Scans the addresses of the `chain` from index 0 until `gap_limit` consecutive addresses
+    /// have no activity according to `has_activity`, which is called once per address in order.
+    pub fn discover<F>(
+        &self,
+        chain: AddressChain,
+        gap_limit: u32,
+        mut has_activity: F,
+    ) -> AddressResult<ChainActivity>
+    where
+        F: FnMut(&AnyAddress) -> bool,
+    {
+        if gap_limit == 0 {
+            return Err(AddressError::InvalidInput);
+        }
+
+        let mut activity = ChainActivity::default();
+        let mut gap = 0;
+        let mut start = 0;
+        while gap < gap_limit {
+            // The addresses are derived in batches, but never beyond the gap limit.
+            let end = start
+                .checked_add(gap_limit - gap)
+                .ok_or(AddressError::InvalidInput)?;
+            let addresses = self.derive_addresses(chain, start..end)?;
+            for (index, address) in (start..end).zip(addresses.iter()) {
+                if has_activity(address) {
+                    activity.used.push(index);
+                    gap = 0;
+                } else {
+                    activity.unused.push(index);
+                    gap += 1;
+                }
+            }
+            start = end;
+        }
+        Ok(activity)
+    }
+
+    fn derive_addresses_impl<C, F>(
+        &self,
+        account: &ExtendedPublicKey<C>,
+        chain: AddressChain,
+        indices: Range<u32>,
+        to_public_key: F,
+    ) -> AddressResult<Vec<AnyAddress>>
+    where
+        C: Bip32Curve,
+        F: Fn(C::PublicKey) -> PublicKey,
+    {
+        // The chain key is derived once for all the indices.
+        let chain_key = account
+            .derive_child(chain as u32)
+            .map_err(|_| AddressError::InvalidInput)?;
+        indices
+            .map(|index| {
+                let child = chain_key
+                    .derive_child(index)
+                    .map_err(|_| AddressError::InvalidInput)?;
+                self.address(to_public_key(child.public().clone()))
+            })
+            .collect()
+    }
+
+    fn address(&self, public_key: PublicKey) -> AddressResult<AnyAddress> {
+        let derivation = match self.script_type {
+            ScriptType::P2pkh => Derivation::Default,
+            ScriptType::P2wpkh => Derivation::BitcoinSegwit,
+            // There is no such derivation in the registry, so the address is encoded here.
+            ScriptType::P2shP2wpkh => {
+                let address = p2sh_p2wpkh_address(&public_key, self.network)?;
+                return AnyAddress::with_string_unchecked(self.coin, &address);
+            },
+        };
+        AnyAddress::with_public_key(self.coin, public_key, derivation, self.address_prefix())
+    }
+
+    /// Returns the prefix of the testnet addresses, which only Bitcoin distinguishes.
+    fn address_prefix(&self) -> Option<AddressPrefix> {
+        match (self.blockchain, self.network) {
+            (BlockchainType::Bitcoin, Network::Testnet) => {
+                Some(AddressPrefix::Hrp(BITCOIN_TESTNET_HRP.to_string()))
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Returns the base58 P2SH address of the P2WPKH script of the `public_key`.
+fn p2sh_p2wpkh_address(public_key: &PublicKey, network: Network) -> AddressResult<String> {
+    let public_key = public_key
+        .to_secp256k1()
+        .ok_or(AddressError::InvalidInput)?;
+    // `OP_0 <20-byte public key hash>`.
+    let mut witness_program = vec![0x00, 0x14];
+    witness_program.extend(ripemd_160(&sha256(public_key.compressed().as_slice())));
+
+    let mut payload = vec![match network {
+        Network::Mainnet => BITCOIN_P2SH_VERSION,
+        Network::Testnet => BITCOIN_TESTNET_P2SH_VERSION,
+    }];
+    payload.extend(ripemd_160(&sha256(&witness_program)));
+    let checksum = sha256(&sha256(&payload));
+    payload.extend_from_slice(&checksum[..4]);
+    Ok(base58::encode(&payload, Alphabet::BITCOIN))
+}
+
+fn decode_xpub<C: Bip32Curve>(xpub: &str) -> AddressResult<ExtendedPublicKey<C>> {
+    [XPUB_VERSION, TPUB_VERSION]
+        .into_iter()
+        .find_map(|version| ExtendedPublicKey::from_xpub(xpub, version).ok())
+        .ok_or(AddressError::InvalidInput)
+}
@@ -0,0 +1,238 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use tw_any_coin::watch_only::{AddressChain, ChainActivity, WatchOnlyAccount, DEFAULT_GAP_LIMIT};
+use tw_coin_entry::error::prelude::*;
+use tw_coin_registry::coin_type::CoinType;
+use tw_keypair::bip32::HARDENED_OFFSET;
+
+/// `m/44'/0'/0'` of the "abandon ... about" mnemonic.
+const BITCOIN_XPUB: &str = "xpub6BosfCnifzxcFwrSzQiqu2DBVTshkCXacvNsWGYJVVhhawA7d4R5WSWGFNbi8Aw6ZRc1brxMyWMzG3DSSSSoekkudhUd9yLb6qx39T9nMdj";
+/// `m/44'/60'/0'` of the "abandon ... about" mnemonic.
+const ETHEREUM_XPUB: &str = "xpub6DCoCpSuQZB2jawqnGMEPS63ePKWkwWPH4TU45Q7LPXWuNd8TMtVxRrgjtEshuqpK3mdhaWHPFsBngh5GFZaM6si3yZdUsT8ddYM3PwnATt";
+
+#[test]
+fn test_watch_only_bitcoin() {
+    let account = WatchOnlyAccount::with_xpub(CoinType::Bitcoin, BITCOIN_XPUB).unwrap();
+
+    let addresses = account
+        .derive_addresses(AddressChain::Receive, 0..2)
+        .unwrap();
+    let addresses: Vec<_> = addresses
+        .iter()
+        .map(|address| address.description())
+        .collect();
+    assert_eq!(
+        addresses,
+        [
+            "1LqBGSKuX5yYUonjxT5qGfpUsXKYYWeabA",
+            "1Ak8PffB2meyfYnbXZR9EGfLfFZVpzJvQP"
+        ]
+    );
+
+    let change = account.derive_address(AddressChain::Change, 0).unwrap();
+    assert_eq!(change.description(), "1J3J6EvPrv8q6AC3VCjWV45Uf3nssNMRtH");
+}
+
+#[test]
+fn test_watch_only_bitcoin_slip132() {
+    // `m/49'/0'/0'`, P2SH-P2WPKH.
+    let ypub = "ypub6Ww3ibxVfGzLrAH1PNcjyAWenMTbbAosGNB6VvmSEgytSER9azLDWCxoJwW7Ke7icmizBMXrzBx9979FfaHxHcrArf3zbeJJJUZPf663zsP";
+    let account = WatchOnlyAccount::with_xpub(CoinType::Bitcoin, ypub).unwrap();
+    let receive = account.derive_address(AddressChain::Receive, 0).unwrap();
+    assert_eq!(receive.description(), "37VucYSaXLCAsxYyAPfbSi9eh4iEcbShgf");
+    let receive = account.derive_address(AddressChain::Receive, 1).unwrap();
+    assert_eq!(receive.description(), "3LtMnn87fqUeHBUG414p9CWwnoV6E2pNKS");
+    let change = account.derive_address(AddressChain::Change, 0).unwrap();
+    assert_eq!(change.description(), "34K56kSjgUCUSD8GTtuF7c9Zzwokbs6uZ7");
+
+    // `m/84'/0'/0'`, P2WPKH.
+    let zpub = "zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs";
+    let account = WatchOnlyAccount::with_xpub(CoinType::Bitcoin, zpub).unwrap();
+    let receive = account.derive_address(AddressChain::Receive, 0).unwrap();
+    assert_eq!(
+        receive.description(),
+        "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"
+    );
+    let receive = account.derive_address(AddressChain::Receive, 1).unwrap();
+    assert_eq!(
+        receive.description(),
+        "bc1qnjg0jd8228aq7egyzacy8cys3knf9xvrerkf9g"
+    );
+    let change = account.derive_address(AddressChain::Change, 0).unwrap();
+    assert_eq!(
+        change.description(),
+        "bc1q8c6fshw2dlwun7ekn9qwf37cu2rn755upcp6el"
+    );
+
+    // SegWit keys are specific to Bitcoin.
+    let err = WatchOnlyAccount::with_xpub(CoinType::Ethereum, zpub)
+        .err()
+        .unwrap();
+    assert_eq!(err, AddressError::Unsupported);
+}
+
+#[test]
+fn test_watch_only_bitcoin_testnet() {
+    // `m/84'/1'/0'`, P2WPKH.
+    let vpub = "vpub5Y6cjg78GGuNLsaPhmYsiw4gYX3HoQiRBiSwDaBXKUafCt9bNwWQiitDk5VZ5BVxYnQdwoTyXSs2JHRPAgjAvtbBrf8ZhDYe2jWAqvZVnsc";
+    let account = WatchOnlyAccount::with_xpub(CoinType::Bitcoin, vpub).unwrap();
+    let receive = account.derive_address(AddressChain::Receive, 0).unwrap();
+    assert_eq!(
+        receive.description(),
+        "tb1q6rz28mcfaxtmd6v789l9rrlrusdprr9pqcpvkl"
+    );
+    let receive = account.derive_address(AddressChain::Receive, 1).unwrap();
+    assert_eq!(
+        receive.description(),
+        "tb1qd7spv5q28348xl4myc8zmh983w5jx32cjhkn97"
+    );
+    let change = account.derive_address(AddressChain::Change, 0).unwrap();
+    assert_eq!(
+        change.description(),
+        "tb1q9u62588spffmq4dzjxsr5l297znf3z6j5p2688"
+    );
+
+    // `m/49'/1'/0'`, P2SH-P2WPKH.
+    let upub = "upub5EFU65HtV5TeiSHmZZm7FUffBGy8UKeqp7vw43jYbvZPpoVsgU93oac7Wk3u6moKegAEWtGNF8DehrnHtv21XXEMYRUocHqguyjknFHYfgY";
+    let account = WatchOnlyAccount::with_xpub(CoinType::Bitcoin, upub).unwrap();
+    let receive = account.derive_address(AddressChain::Receive, 0).unwrap();
+    assert_eq!(receive.description(), "2Mww8dCYPUpKHofjgcXcBCEGmniw9CoaiD2");
+    let receive = account.derive_address(AddressChain::Receive, 1).unwrap();
+    assert_eq!(receive.description(), "2N55m54k8vr95ggehfUcNkdbUuQvaqG2GxK");
+    let change = account.derive_address(AddressChain::Change, 0).unwrap();
+    assert_eq!(change.description(), "2MvdUi5o3f2tnEFh9yGvta6FzptTZtkPJC8");
+
+    // `m/44'/1'/0'`, P2PKH.
+    let tpub = "tpubDC5FSnBiZDMmhiuCmWAYsLwgLYrrT9rAqvTySfuCCrgsWz8wxMXUS9Tb9iVMvcRbvFcAHGkMD5Kx8koh4GquNGNTfohfk7pgjhaPCdXpoba";
+    let account = WatchOnlyAccount::with_xpub(CoinType::Bitcoin, tpub).unwrap();
+    let receive = account.derive_address(AddressChain::Receive, 0).unwrap();
+    assert_eq!(receive.description(), "mkpZhYtJu2r87Js3pDiWJDmPte2NRZ8bJV");
+    let receive = account.derive_address(AddressChain::Receive, 1).unwrap();
+    assert_eq!(receive.description(), "mzpbWabUQm1w8ijuJnAof5eiSTep27deVH");
+    let change = account.derive_address(AddressChain::Change, 0).unwrap();
+    assert_eq!(change.description(), "mi8nhzZgGZQthq6DQHbru9crMDerUdTKva");
+}
+
+#[test]
+fn test_watch_only_ethereum() {
+    let account = WatchOnlyAccount::with_xpub(CoinType::Ethereum, ETHEREUM_XPUB).unwrap();
+
+    let receive = account.derive_address(AddressChain::Receive, 0).unwrap();
+    assert_eq!(
+        receive.description(),
+        "0x9858EfFD232B4033E47d90003D41EC34EcaEda94"
+    );
+    let receive = account.derive_address(AddressChain::Receive, 1).unwrap();
+    assert_eq!(
+        receive.description(),
+        "0x6Fac4D18c912343BF86fa7049364Dd4E424Ab9C0"
+    );
+    let change = account.derive_address(AddressChain::Change, 0).unwrap();
+    assert_eq!(
+        change.description(),
+        "0x399Db6Ed32539fbDF44c3e7678b5b428e378F666"
+    );
+
+    let addresses = account
+        .derive_addresses(AddressChain::Receive, 0..100)
+        .unwrap();
+    assert_eq!(addresses.len(), 100);
+    assert_eq!(
+        addresses[1],
+        account.derive_address(AddressChain::Receive, 1).unwrap()
+    );
+}
+
+#[test]
+fn test_watch_only_discover() {
+    let account = WatchOnlyAccount::with_xpub(CoinType::Bitcoin, BITCOIN_XPUB).unwrap();
+    let used_address = account.derive_address(AddressChain::Receive, 4).unwrap();
+
+    let mut scanned = Vec::new();
+    let activity = account
+        .discover(AddressChain::Receive, 5, |address| {
+            scanned.push(address.description().to_string());
+            *address == used_address
+        })
+        .unwrap();
+    assert_eq!(
+        activity,
+        ChainActivity {
+            used: vec![4],
+            unused: vec![0, 1, 2, 3, 5, 6, 7, 8, 9],
+        }
+    );
+    assert!(activity.is_used());
+    assert_eq!(activity.next_index(), 5);
+    // Every address is checked once, in order.
+    let expected: Vec<_> = account
+        .derive_addresses(AddressChain::Receive, 0..10)
+        .unwrap()
+        .iter()
+        .map(|address| address.description().to_string())
+        .collect();
+    assert_eq!(scanned, expected);
+
+    // The used address is beyond the gap limit.
+    let activity = account
+        .discover(AddressChain::Receive, 4, |address| *address == used_address)
+        .unwrap();
+    assert_eq!(activity.used, Vec::<u32>::new());
+    assert_eq!(activity.unused, [0, 1, 2, 3]);
+    assert!(!activity.is_used());
+    assert_eq!(activity.next_index(), 0);
+
+    let used = [
+        "1LqBGSKuX5yYUonjxT5qGfpUsXKYYWeabA",
+        "1J3J6EvPrv8q6AC3VCjWV45Uf3nssNMRtH",
+    ];
+    let receive = account
+        .discover(AddressChain::Receive, DEFAULT_GAP_LIMIT, |address| {
+            used.contains(&address.description())
+        })
+        .unwrap();
+    assert_eq!(receive.used, [0]);
+    assert_eq!(receive.unused, (1..=20).collect::<Vec<_>>());
+    let change = account
+        .discover(AddressChain::Change, DEFAULT_GAP_LIMIT, |address| {
+            used.contains(&address.description())
+        })
+        .unwrap();
+    assert_eq!(change.used, [0]);
+    assert_eq!(change.next_index(), 1);
+
+    // At least one unused address must be scanned.
+    let err = account
+        .discover(AddressChain::Receive, 0, |_| true)
+        .unwrap_err();
+    assert_eq!(err, AddressError::InvalidInput);
+}
+
+#[test]
+fn test_watch_only_invalid() {
+    // Hardened derivation only.
+    let err = WatchOnlyAccount::with_xpub(CoinType::Solana, ETHEREUM_XPUB)
+        .err()
+        .unwrap();
+    assert_eq!(err, AddressError::Unsupported);
+
+    // An extended private key.
+    let xprv = "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi";
+    let err = WatchOnlyAccount::with_xpub(CoinType::Ethereum, xprv)
+        .err()
+        .unwrap();
+    assert_eq!(err, AddressError::InvalidInput);
+
+    // Hardened indices can't be derived from a public key.
+    let account = WatchOnlyAccount::with_xpub(CoinType::Ethereum, ETHEREUM_XPUB).unwrap();
+    let err = account
+        .derive_address(AddressChain::Receive, HARDENED_OFFSET)
+        .unwrap_err();
+    assert_eq!(err, AddressError::InvalidInput);
+    let err = account
+        .derive_address(AddressChain::Receive, u32::MAX)
+        .unwrap_err();
+    assert_eq!(err, AddressError::InvalidInput);
+}
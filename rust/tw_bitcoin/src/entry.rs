@@ -1,4 +1,10 @@
+use crate::aliases::*;
+use crate::modules::network::{network_from_proto, NetworkPrefix};
 use crate::modules::signer::Signer;
+use crate::modules::transaction_decoder::BitcoinTransactionDecoder;
+use crate::modules::transactions::{
+    timelock_from_proto, Timelock, LOCKTIME_THRESHOLD, SEQUENCE_ENABLE_LOCKTIME,
+};
 use crate::{bitcoin_output_error, Error, Result};
 use bitcoin::address::NetworkChecked;
 use std::borrow::Cow;
@@ -11,9 +17,7 @@ use tw_coin_entry::error::prelude::*;
 use tw_coin_entry::modules::json_signer::NoJsonSigner;
 use tw_coin_entry::modules::message_signer::NoMessageSigner;
 use tw_coin_entry::modules::plan_builder::NoPlanBuilder;
-use tw_coin_entry::modules::transaction_decoder::NoTransactionDecoder;
 use tw_coin_entry::modules::wallet_connector::NoWalletConnector;
-use tw_coin_entry::prefix::NoPrefix;
 use tw_keypair::tw::PublicKey;
 use tw_misc::traits::ToBytesVec;
 use tw_proto::BitcoinV2::Proto;
@@ -36,7 +40,7 @@ impl CoinAddress for Address {
 pub struct BitcoinEntry;
 
 impl CoinEntry for BitcoinEntry {
-    type AddressPrefix = NoPrefix;
+    type AddressPrefix = NetworkPrefix;
     type Address = Address;
     type SigningInput<'a> = Proto::SigningInput<'a>;
     type SigningOutput = Proto::SigningOutput<'static>;
@@ -47,16 +51,23 @@ impl CoinEntry for BitcoinEntry {
     type PlanBuilder = NoPlanBuilder;
     type MessageSigner = NoMessageSigner;
     type WalletConnector = NoWalletConnector;
-    type TransactionDecoder = NoTransactionDecoder;
+    type TransactionDecoder = BitcoinTransactionDecoder;
 
     #[inline]
     fn parse_address(
         &self,
-        coin: &dyn CoinContext,
+        _coin: &dyn CoinContext,
         address: &str,
-        _prefix: Option<Self::AddressPrefix>,
+        prefix: Option<Self::AddressPrefix>,
     ) -> AddressResult<Self::Address> {
-        self.parse_address_unchecked(coin, address)
+        // Mainnet by default.
+        let network = prefix.map_or(bitcoin::Network::Bitcoin, |prefix| prefix.0);
+        let address = bitcoin::address::Address::from_str(address)
+            .map_err(|_| AddressError::FromHexError)?
+            .require_network(network)
+            .map_err(|_| AddressError::InvalidInput)?;
+
+        Ok(Address(address))
     }
 
     #[inline]
@@ -65,12 +76,10 @@ impl CoinEntry for BitcoinEntry {
         _coin: &dyn CoinContext,
         address: &str,
     ) -> AddressResult<Self::Address> {
+        // The address of any network.
         let address = bitcoin::address::Address::from_str(address)
             .map_err(|_| AddressError::FromHexError)?
-            // At this moment, we support mainnet only.
-            // This check will be removed in coming PRs.
-            .require_network(bitcoin::Network::Bitcoin)
-            .map_err(|_| AddressError::InvalidInput)?;
+            .assume_checked();
 
         Ok(Address(address))
     }
@@ -80,8 +89,8 @@ impl CoinEntry for BitcoinEntry {
         &self,
         _coin: &dyn CoinContext,
         public_key: PublicKey,
-        _derivation: Derivation,
-        _prefix: Option<Self::AddressPrefix>,
+        derivation: Derivation,
+        prefix: Option<Self::AddressPrefix>,
     ) -> AddressResult<Self::Address> {
         let pubkey = match public_key {
             PublicKey::Secp256k1(pubkey) | PublicKey::Secp256k1Extended(pubkey) => pubkey,
@@ -91,10 +100,17 @@ impl CoinEntry for BitcoinEntry {
         let pubkey = bitcoin::PublicKey::from_slice(pubkey.to_vec().as_ref())
             .map_err(|_| AddressError::InvalidInput)?;
 
-        let address: bitcoin::address::Address<NetworkChecked> = bitcoin::address::Address::new(
-            bitcoin::Network::Bitcoin,
-            bitcoin::address::Payload::PubkeyHash(pubkey.pubkey_hash()),
-        );
+        let payload = match derivation {
+            Derivation::BitcoinSegwit => bitcoin::address::Payload::p2wpkh(&pubkey),
+            _ => Ok(bitcoin::address::Payload::PubkeyHash(pubkey.pubkey_hash())),
+        }
+        // Only compressed public keys have SegWit addresses.
+        .map_err(|_| AddressError::InvalidInput)?;
+
+        let network = prefix.map_or(bitcoin::Network::Bitcoin, |prefix| prefix.0);
+
+        let address: bitcoin::address::Address<NetworkChecked> =
+            bitcoin::address::Address::new(network, payload);
 
         Ok(Address(address))
     }
@@ -131,6 +147,11 @@ impl CoinEntry for BitcoinEntry {
     fn plan_builder(&self) -> Option<Self::PlanBuilder> {
         None
     }
+
+    #[inline]
+    fn transaction_decoder(&self) -> Option<Self::TransactionDecoder> {
+        Some(BitcoinTransactionDecoder)
+    }
 }
 
 impl BitcoinEntry {
@@ -148,11 +169,17 @@ impl BitcoinEntry {
             .map(crate::modules::transactions::InputBuilder::utxo_from_proto)
             .collect::<Result<Vec<_>>>()?;
 
+        let network = network_from_proto(proto.network);
+
         // Convert output builders into Utxo outputs (does not contain the change output).
         let mut utxo_outputs = proto
             .outputs
             .iter()
-            .map(crate::modules::transactions::OutputBuilder::utxo_from_proto)
+            .map(|output| {
+                crate::modules::transactions::OutputBuilder::utxo_from_proto_with_network(
+                    output, network,
+                )
+            })
             .collect::<Result<Vec<_>>>()?;
 
         // If automatic change output creation is enabled (by default), a change
@@ -161,10 +188,11 @@ impl BitcoinEntry {
             Cow::default()
         } else {
             // Convert output builder to Utxo output.
-            let output = crate::modules::transactions::OutputBuilder::utxo_from_proto(
+            let output = crate::modules::transactions::OutputBuilder::utxo_from_proto_with_network(
                 &proto
                     .change_output
                     .ok_or_else(|| Error::from(Proto::Error::Error_invalid_change_output))?,
+                network,
             )?;
 
             output.script_pubkey
@@ -186,6 +214,10 @@ impl BitcoinEntry {
             weight_base: proto.fee_per_vb,
             change_script_pubkey,
             disable_change_output: proto.disable_change_output,
+            dust_policy: proto.dust_policy,
+            dust_relay_fee: proto.dust_relay_fee,
+            fixed_dust_threshold: proto.fixed_dust_threshold,
+            change_to_fee_threshold: proto.change_to_fee_threshold,
         };
 
         // Generate the sighashes to be signed. This also selects the inputs
@@ -194,8 +226,9 @@ impl BitcoinEntry {
         let utxo_presigning = tw_utxo::compiler::Compiler::preimage_hashes(utxo_signing);
         handle_utxo_error(&utxo_presigning.error)?;
 
-        // Check whether the change output is present.
-        if !proto.disable_change_output {
+        // Check whether the change output is present. It's not added if it's
+        // dropped as dust, or if the selected inputs don't require it.
+        if utxo_presigning.outputs.len() > utxo_outputs.len() {
             // Change output has been added.
             debug_assert_eq!(utxo_presigning.outputs.len(), utxo_outputs.len() + 1);
 
@@ -227,6 +260,8 @@ impl BitcoinEntry {
             utxo_outputs,
             weight_estimate: utxo_presigning.weight_estimate,
             fee_estimate: utxo_presigning.fee_estimate,
+            change_absorbed: utxo_presigning.change_absorbed,
+            absorbed_change: utxo_presigning.absorbed_change,
         })
     }
 
@@ -235,7 +270,7 @@ impl BitcoinEntry {
         _coin: &dyn CoinContext,
         proto: Proto::SigningInput<'_>,
         signatures: Vec<SignatureBytes>,
-        _public_keys: Vec<PublicKeyBytes>,
+        public_keys: Vec<PublicKeyBytes>,
     ) -> Result<Proto::SigningOutput<'static>> {
         let proto = pre_processor(proto);
 
@@ -246,21 +281,32 @@ impl BitcoinEntry {
             ));
         }
 
+        let lock_time = lock_time_value(&proto.lock_time);
+
         // Generate claims for all the inputs.
         let mut utxo_input_claims: Vec<UtxoProto::TxInClaim> = vec![];
-        for (input, signature) in proto.inputs.iter().zip(signatures.into_iter()) {
+        for (index, (input, signature)) in proto.inputs.iter().zip(signatures).enumerate() {
+            // The public key of the signature, either given or derived from the private key.
+            let signer = match public_keys.get(index).filter(|key| !key.is_empty()) {
+                Some(public_key) => bitcoin::PublicKey::from_slice(public_key).ok(),
+                None => signer_public_key(&proto, input),
+            };
+
             let utxo_claim =
                 crate::modules::transactions::InputClaimBuilder::utxo_claim_from_proto(
-                    input, signature,
+                    input, signature, signer, lock_time,
                 )?;
 
             utxo_input_claims.push(utxo_claim);
         }
 
         // Prepare all the outputs.
+        let network = network_from_proto(proto.network);
         let mut utxo_outputs = vec![];
         for output in &proto.outputs {
-            let utxo = crate::modules::transactions::OutputBuilder::utxo_from_proto(output)?;
+            let utxo = crate::modules::transactions::OutputBuilder::utxo_from_proto_with_network(
+                output, network,
+            )?;
 
             utxo_outputs.push(utxo);
         }
@@ -348,6 +394,9 @@ impl BitcoinEntry {
             txid: utxo_serialized.txid,
             weight: utxo_serialized.weight,
             fee: utxo_serialized.fee,
+            // Known from the input selection only, see `Signer::sign_proto`.
+            change_absorbed: false,
+            absorbed_change: 0,
         })
     }
 }
@@ -369,9 +418,76 @@ pub(crate) fn pre_processor(mut proto: Proto::SigningInput<'_>) -> Proto::Signin
         }
     });
 
+    // The timelocks of the timelock inputs and of the refunded HTLC inputs are
+    // enabled by their sequences and the lock time of the transaction, unless these are set explicitly.
+    let mut required_lock_time = None;
+    for txin in proto.inputs.iter_mut() {
+        let timelock = match &txin.to_recipient {
+            ProtoInputRecipient::builder(builder) => match &builder.variant {
+                ProtoInputBuilder::p2sh_timelock(timelock)
+                | ProtoInputBuilder::p2wsh_timelock(timelock) => timelock_from_proto(timelock).ok(),
+                // The timeout only restricts the refund.
+                ProtoInputBuilder::p2sh_htlc(htlc) | ProtoInputBuilder::p2wsh_htlc(htlc)
+                    if htlc.secret.is_empty() =>
+                {
+                    htlc.refund
+                        .as_ref()
+                        .and_then(|refund| timelock_from_proto(refund).ok())
+                },
+                _ => None,
+            },
+            _ => None,
+        };
+        match timelock {
+            Some(Timelock::Absolute(lock_time)) => {
+                if txin.sequence == u32::MAX {
+                    txin.sequence = SEQUENCE_ENABLE_LOCKTIME;
+                }
+                required_lock_time = required_lock_time.max(Some(lock_time));
+            },
+            Some(Timelock::Relative(sequence)) if txin.sequence == u32::MAX => {
+                txin.sequence = sequence;
+            },
+            _ => (),
+        }
+    }
+    if let (0, Some(lock_time)) = (lock_time_value(&proto.lock_time), required_lock_time) {
+        let variant = if lock_time < LOCKTIME_THRESHOLD {
+            UtxoProto::mod_LockTime::OneOfvariant::blocks(lock_time)
+        } else {
+            UtxoProto::mod_LockTime::OneOfvariant::seconds(lock_time)
+        };
+        proto.lock_time = Some(UtxoProto::LockTime { variant });
+    }
+
     proto
 }
 
+/// Returns the public key of the individual private key of the input, or of the primary one.
+fn signer_public_key(
+    proto: &Proto::SigningInput<'_>,
+    input: &Proto::Input<'_>,
+) -> Option<bitcoin::PublicKey> {
+    let private_key = if input.private_key.is_empty() {
+        proto.private_key.as_ref()
+    } else {
+        input.private_key.as_ref()
+    };
+    let secret_key = secp256k1::SecretKey::from_slice(private_key).ok()?;
+    Some(bitcoin::PublicKey::new(
+        secp256k1::PublicKey::from_secret_key_global(&secret_key),
+    ))
+}
+
+/// Returns the consensus value of the lock time, `0` if not set.
+fn lock_time_value(lock_time: &Option<UtxoProto::LockTime>) -> u32 {
+    match lock_time.as_ref().map(|lock_time| &lock_time.variant) {
+        Some(UtxoProto::mod_LockTime::OneOfvariant::blocks(blocks)) => *blocks,
+        Some(UtxoProto::mod_LockTime::OneOfvariant::seconds(seconds)) => *seconds,
+        _ => 0,
+    }
+}
+
 #[rustfmt::skip]
 /// Convert `Utxo.proto` error type to `BitcoinV2.proto` error type.
 fn handle_utxo_error(utxo_err: &UtxoProto::Error) -> Result<()> {
@@ -387,6 +503,8 @@ fn handle_utxo_error(utxo_err: &UtxoProto::Error) -> Result<()> {
         UtxoProto::Error::Error_insufficient_inputs => Proto::Error::Error_utxo_insufficient_inputs,
         UtxoProto::Error::Error_no_outputs_specified => Proto::Error::Error_utxo_no_outputs_specified,
         UtxoProto::Error::Error_missing_change_script_pubkey => Proto::Error::Error_utxo_missing_change_script_pubkey,
+        UtxoProto::Error::Error_dust_output => Proto::Error::Error_utxo_dust_output,
+        UtxoProto::Error::Error_invalid_annex => Proto::Error::Error_utxo_invalid_annex,
     };
 
     Err(Error::from(bitcoin_err))
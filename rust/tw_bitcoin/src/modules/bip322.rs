@@ -0,0 +1,320 @@
+//! Generic signed messages as specified in [BIP322](https://github.com/bitcoin/bips/blob/master/bip-0322.mediawiki),
+//! i.e. the proofs that the signer controls an address, e.g. as requested by exchanges:
+//!
+//! ```rust,ignore
+//! let signature = sign_message(PRIVATE_KEY, &address, b"Hello World")?;
+//! let encoded = signature.to_base64();
+//! verify_message(&address, b"Hello World", &MessageSignature::from_base64(&encoded)?)?;
+//! ```
+//!
+//! The message is signed by the virtual `to_sign` transaction that spends the output of the address
+//! of the virtual `to_spend` transaction, which commits to the message.
+//! The supported addresses are P2PKH, P2SH-P2WPKH, P2WPKH and P2TR key path.
+//! Their signatures are encoded in the _simple_ format, i.e. the witness of `to_sign`,
+//! if the script signature is empty, and in the _full_ format, i.e. the whole `to_sign` transaction, otherwise.
+
+use crate::modules::psbt::Psbt;
+use crate::{Error, Result};
+use bitcoin::blockdata::locktime::absolute::LockTime;
+use bitcoin::blockdata::opcodes::all::OP_RETURN;
+use bitcoin::blockdata::script::{Builder, Instruction};
+use bitcoin::hashes::{sha256, Hash, HashEngine};
+use bitcoin::sighash::{EcdsaSighashType, Prevouts, SighashCache, TapSighashType};
+use bitcoin::{
+    Address, OutPoint, PublicKey, Script, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Txid,
+    Witness,
+};
+use secp256k1::{Message, Secp256k1, XOnlyPublicKey};
+use tw_encoding::base64;
+use tw_proto::BitcoinV2::Proto;
+
+/// The tag of the tagged hash of the message.
+const MESSAGE_TAG: &[u8] = b"BIP0322-signed-message";
+
+/// The signature of a message.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MessageSignature {
+    /// The witness of the `to_sign` transaction that has an empty script signature.
+    Simple(Witness),
+    /// The signed `to_sign` transaction.
+    Full(Transaction),
+}
+
+impl MessageSignature {
+    pub fn serialize(&self) -> Vec<u8> {
+        match self {
+            MessageSignature::Simple(witness) => bitcoin::consensus::serialize(witness),
+            MessageSignature::Full(tx) => bitcoin::consensus::serialize(tx),
+        }
+    }
+
+    /// Parses a signature of either format.
+    pub fn deserialize(bytes: &[u8]) -> Result<MessageSignature> {
+        // A transaction starts with its version, which is never a complete witness.
+        if let Ok(witness) = bitcoin::consensus::deserialize::<Witness>(bytes) {
+            return Ok(MessageSignature::Simple(witness));
+        }
+        bitcoin::consensus::deserialize::<Transaction>(bytes)
+            .map(MessageSignature::Full)
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_message_signature))
+    }
+
+    /// Parses a base64 encoded signature, i.e. the format that is usually exchanged.
+    pub fn from_base64(signature: &str) -> Result<MessageSignature> {
+        let bytes = base64::decode(signature.trim(), false)
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_message_signature))?;
+        MessageSignature::deserialize(&bytes)
+    }
+
+    pub fn to_base64(&self) -> String {
+        base64::encode(&self.serialize(), false)
+    }
+}
+
+/// Returns the tagged hash of the `message` that the `to_spend` transaction commits to.
+pub fn message_hash(message: &[u8]) -> [u8; 32] {
+    let tag = sha256::Hash::hash(MESSAGE_TAG);
+    let mut engine = sha256::Hash::engine();
+    engine.input(tag.as_byte_array());
+    engine.input(tag.as_byte_array());
+    engine.input(message);
+    sha256::Hash::from_engine(engine).to_byte_array()
+}
+
+/// Returns the virtual transaction that commits to the `message` and pays to the `script_pubkey` of the signer.
+pub fn to_spend(script_pubkey: &Script, message: &[u8]) -> Transaction {
+    Transaction {
+        version: 0,
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint {
+                txid: Txid::all_zeros(),
+                vout: u32::MAX,
+            },
+            script_sig: Builder::new()
+                .push_int(0)
+                .push_slice(message_hash(message))
+                .into_script(),
+            sequence: Sequence::ZERO,
+            witness: Witness::new(),
+        }],
+        output: vec![TxOut {
+            value: 0,
+            script_pubkey: script_pubkey.to_owned(),
+        }],
+    }
+}
+
+/// Returns the unsigned virtual transaction that spends the output of `to_spend`.
+pub fn to_sign(to_spend: &Transaction) -> Transaction {
+    Transaction {
+        version: 0,
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint {
+                txid: to_spend.txid(),
+                vout: 0,
+            },
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::ZERO,
+            witness: Witness::new(),
+        }],
+        output: vec![TxOut {
+            value: 0,
+            script_pubkey: Builder::new().push_opcode(OP_RETURN).into_script(),
+        }],
+    }
+}
+
+/// Signs the `message` with the secp256k1 `private_key` that controls the `address`.
+pub fn sign_message(
+    private_key: &[u8],
+    address: &Address,
+    message: &[u8],
+) -> Result<MessageSignature> {
+    let to_spend = to_spend(&address.script_pubkey(), message);
+    let mut psbt = Psbt::from_unsigned_tx(to_sign(&to_spend))?;
+    psbt.inner_mut().inputs[0].witness_utxo = Some(to_spend.output[0].clone());
+
+    if psbt.sign(private_key)?.is_empty() {
+        return Err(Error::from(Proto::Error::Error_unsupported_message_address));
+    }
+    psbt.finalize()?;
+    let tx = psbt.extract_tx()?;
+
+    if tx.input[0].script_sig.is_empty() {
+        Ok(MessageSignature::Simple(tx.input[0].witness.clone()))
+    } else {
+        Ok(MessageSignature::Full(tx))
+    }
+}
+
+/// Verifies the `signature` of the `message` by the owner of the `address`.
+pub fn verify_message(
+    address: &Address,
+    message: &[u8],
+    signature: &MessageSignature,
+) -> Result<()> {
+    let script_pubkey = address.script_pubkey();
+    let to_spend = to_spend(&script_pubkey, message);
+    let expected = to_sign(&to_spend);
+
+    let tx = match signature {
+        MessageSignature::Simple(witness) => {
+            let mut tx = expected.clone();
+            tx.input[0].witness = witness.clone();
+            tx
+        },
+        // The version, the lock time and the sequence are committed to by the signature.
+        MessageSignature::Full(tx) => tx.clone(),
+    };
+    if tx.input.len() != 1
+        || tx.input[0].previous_output != expected.input[0].previous_output
+        || tx.output != expected.output
+    {
+        return Err(Error::from(Proto::Error::Error_invalid_message_signature));
+    }
+
+    let txin = &tx.input[0];
+    let valid = if script_pubkey.is_p2pkh() {
+        txin.witness.is_empty() && verify_p2pkh(&tx, &script_pubkey)
+    } else if script_pubkey.is_v0_p2wpkh() {
+        txin.script_sig.is_empty() && verify_p2wpkh(&tx, &script_pubkey)
+    } else if script_pubkey.is_p2sh() {
+        // The script signature pushes the P2WPKH redeem script only.
+        match single_push(&txin.script_sig) {
+            Some(redeem_script)
+                if redeem_script.is_v0_p2wpkh()
+                    && script_pubkey == ScriptBuf::new_p2sh(&redeem_script.script_hash()) =>
+            {
+                verify_p2wpkh(&tx, &redeem_script)
+            },
+            _ => false,
+        }
+    } else if script_pubkey.is_v1_p2tr() {
+        txin.script_sig.is_empty() && verify_p2tr(&tx, &to_spend.output[0])
+    } else {
+        return Err(Error::from(Proto::Error::Error_unsupported_message_address));
+    };
+
+    if !valid {
+        return Err(Error::from(Proto::Error::Error_invalid_message_signature));
+    }
+    Ok(())
+}
+
+/// Verifies the script signature `<signature> <public key>` of the `to_sign` transaction.
+fn verify_p2pkh(tx: &Transaction, script_pubkey: &Script) -> bool {
+    let pushes = tx.input[0]
+        .script_sig
+        .instructions()
+        .map(|instruction| match instruction {
+            Ok(Instruction::PushBytes(bytes)) => Some(bytes.as_bytes().to_vec()),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>();
+    let Some([signature, public_key]) = pushes.as_deref() else {
+        return false;
+    };
+    let Ok(public_key) = PublicKey::from_slice(public_key) else {
+        return false;
+    };
+    if *script_pubkey != ScriptBuf::new_p2pkh(&public_key.pubkey_hash()) {
+        return false;
+    }
+
+    match SighashCache::new(tx).legacy_signature_hash(
+        0,
+        script_pubkey,
+        EcdsaSighashType::All.to_u32(),
+    ) {
+        Ok(sighash) => verify_ecdsa(sighash.as_byte_array(), signature, &public_key),
+        Err(_) => false,
+    }
+}
+
+/// Verifies the witness `<signature> <public key>` of the `to_sign` transaction
+/// that spends the `p2wpkh` script, either directly or nested in P2SH.
+fn verify_p2wpkh(tx: &Transaction, p2wpkh: &Script) -> bool {
+    let witness = &tx.input[0].witness;
+    let (Some(signature), Some(public_key), 2) = (witness.nth(0), witness.nth(1), witness.len())
+    else {
+        return false;
+    };
+    let Ok(public_key) = PublicKey::from_slice(public_key) else {
+        return false;
+    };
+    // Uncompressed keys have no P2WPKH script.
+    match public_key.wpubkey_hash() {
+        Some(hash) if *p2wpkh == ScriptBuf::new_v0_p2wpkh(&hash) => (),
+        _ => return false,
+    }
+
+    // The script code of P2WPKH is the P2PKH script of the key, see BIP-143.
+    let script_code = ScriptBuf::new_p2pkh(&public_key.pubkey_hash());
+    match SighashCache::new(tx).segwit_signature_hash(0, &script_code, 0, EcdsaSighashType::All) {
+        Ok(sighash) => verify_ecdsa(sighash.as_byte_array(), signature, &public_key),
+        Err(_) => false,
+    }
+}
+
+/// Verifies the key path witness `<signature>` of the `to_sign` transaction that spends the P2TR `utxo`.
+fn verify_p2tr(tx: &Transaction, utxo: &TxOut) -> bool {
+    let witness = &tx.input[0].witness;
+    let (Some(signature), 1) = (witness.nth(0), witness.len()) else {
+        return false;
+    };
+    let Ok(signature) = bitcoin::taproot::Signature::from_slice(signature) else {
+        return false;
+    };
+    if !matches!(
+        signature.hash_ty,
+        TapSighashType::Default | TapSighashType::All
+    ) {
+        return false;
+    }
+    // The witness program is the tweaked output key.
+    let Ok(output_key) = XOnlyPublicKey::from_slice(&utxo.script_pubkey.as_bytes()[2..]) else {
+        return false;
+    };
+
+    let sighash = SighashCache::new(tx).taproot_key_spend_signature_hash(
+        0,
+        &Prevouts::All(&[utxo.clone()]),
+        signature.hash_ty,
+    );
+    let Ok(sighash) = sighash else {
+        return false;
+    };
+    let Ok(message) = Message::from_slice(sighash.as_byte_array()) else {
+        return false;
+    };
+    Secp256k1::verification_only()
+        .verify_schnorr(&signature.sig, &message, &output_key)
+        .is_ok()
+}
+
+/// Verifies the ECDSA `SIGHASH_ALL` `signature` of the `public_key` over the `sighash`.
+fn verify_ecdsa(sighash: &[u8; 32], signature: &[u8], public_key: &PublicKey) -> bool {
+    let Ok(signature) = bitcoin::ecdsa::Signature::from_slice(signature) else {
+        return false;
+    };
+    let Ok(message) = Message::from_slice(sighash) else {
+        return false;
+    };
+    signature.hash_ty == EcdsaSighashType::All
+        && Secp256k1::verification_only()
+            .verify_ecdsa(&message, &signature.sig, &public_key.inner)
+            .is_ok()
+}
+
+/// Returns the script that the `script_sig` pushes, if it's the only instruction.
+fn single_push(script_sig: &Script) -> Option<ScriptBuf> {
+    let mut instructions = script_sig.instructions();
+    match (instructions.next(), instructions.next()) {
+        (Some(Ok(Instruction::PushBytes(bytes))), None) => {
+            Some(ScriptBuf::from_bytes(bytes.as_bytes().to_vec()))
+        },
+        _ => None,
+    }
+}
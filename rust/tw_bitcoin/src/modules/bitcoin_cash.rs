@@ -0,0 +1,628 @@
+//! Bitcoin Cash transactions, i.e. the [CashAddr](https://github.com/bitcoincashorg/bitcoincash.org/blob/master/spec/cashaddr.md)
+//! addresses, the BIP143 sighash with `SIGHASH_FORKID`, and the
+//! [CashTokens](https://github.com/cashtokens/cashtokens) of the outputs:
+//!
+//! ```rust,ignore
+//! let token = CashToken::fungible(category, 1000);
+//! let tx = CashTransactionBuilder::new()
+//!     .add_input(outpoint, token_utxo)
+//!     .add_input(outpoint, utxo)
+//!     .add_token_output(800, &CashAddress::from_str("bitcoincash:z...")?, token)
+//!     .add_output(change, &change_address)
+//!     .sign(PRIVATE_KEY)?;
+//! ```
+//!
+//! The token prefix is a part of the script pubkey of an output, so the transactions are encoded as the Bitcoin ones.
+
+use crate::modules::context::with_context;
+use crate::modules::signer::split_sighash_type;
+use crate::{Error, Result};
+use bitcoin::absolute::LockTime;
+use bitcoin::blockdata::opcodes::all::{OP_EQUAL, OP_HASH160, OP_HASH256};
+use bitcoin::blockdata::script::PushBytesBuf;
+use bitcoin::consensus::encode::VarInt;
+use bitcoin::consensus::Decodable;
+use bitcoin::hashes::{sha256d, Hash};
+use bitcoin::sighash::EcdsaSighashType;
+use bitcoin::{
+    OutPoint, PubkeyHash, PublicKey, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Txid,
+};
+use bitcoin::{Script, Witness};
+use secp256k1::{Message, SecretKey};
+use std::fmt;
+use std::str::FromStr;
+use tw_proto::BitcoinV2::Proto;
+
+pub const MAINNET_PREFIX: &str = "bitcoincash";
+pub const TESTNET_PREFIX: &str = "bchtest";
+pub const REGTEST_PREFIX: &str = "bchreg";
+/// The flag of the sighash type that selects the BIP143 sighash, with the fork ID `0`.
+pub const SIGHASH_FORKID: u32 = 0x40;
+
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const CHECKSUM_LEN: usize = 8;
+const GENERATORS: [u64; 5] = [
+    0x98_f2bc_8e61,
+    0x79_b76d_99e2,
+    0xf3_3e5f_b3c4,
+    0xae_2eab_e2a8,
+    0x1e_4f43_e470,
+];
+/// The size bits of the version byte of the 160 and 256-bit hashes.
+const HASH_160_SIZE: u8 = 0;
+const HASH_256_SIZE: u8 = 3;
+
+const PREFIX_TOKEN: u8 = 0xef;
+const RESERVED_BIT: u8 = 0x80;
+const HAS_COMMITMENT_LENGTH: u8 = 0x40;
+const HAS_NFT: u8 = 0x20;
+const HAS_AMOUNT: u8 = 0x10;
+const CAPABILITY_MASK: u8 = 0x0f;
+pub const MAX_COMMITMENT_LEN: usize = 40;
+pub const MAX_TOKEN_AMOUNT: u64 = i64::MAX as u64;
+
+/// The type of a CashAddr, where the token-aware types signal that the wallet of the recipient supports CashTokens.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CashAddressType {
+    P2pkh,
+    P2sh,
+    TokenP2pkh,
+    TokenP2sh,
+}
+
+impl CashAddressType {
+    fn from_bits(bits: u8) -> Option<Self> {
+        match bits {
+            0 => Some(CashAddressType::P2pkh),
+            1 => Some(CashAddressType::P2sh),
+            2 => Some(CashAddressType::TokenP2pkh),
+            3 => Some(CashAddressType::TokenP2sh),
+            _ => None,
+        }
+    }
+
+    fn bits(&self) -> u8 {
+        match self {
+            CashAddressType::P2pkh => 0,
+            CashAddressType::P2sh => 1,
+            CashAddressType::TokenP2pkh => 2,
+            CashAddressType::TokenP2sh => 3,
+        }
+    }
+
+    fn is_p2sh(&self) -> bool {
+        matches!(self, CashAddressType::P2sh | CashAddressType::TokenP2sh)
+    }
+}
+
+/// A CashAddr of the 20-byte P2PKH or P2SH hash, or the 32-byte P2SH32 hash.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CashAddress {
+    prefix: String,
+    address_type: CashAddressType,
+    hash: Vec<u8>,
+}
+
+impl CashAddress {
+    pub fn new(prefix: &str, address_type: CashAddressType, hash: &[u8]) -> Result<Self> {
+        let valid_len = match hash.len() {
+            20 => true,
+            32 => address_type.is_p2sh(),
+            _ => false,
+        };
+        if !valid_len || prefix.is_empty() || prefix.bytes().any(|c| !c.is_ascii_lowercase()) {
+            return Err(Error::from(Proto::Error::Error_bad_address_recipient));
+        }
+        Ok(CashAddress {
+            prefix: prefix.to_string(),
+            address_type,
+            hash: hash.to_vec(),
+        })
+    }
+
+    /// The P2PKH address of the `public_key`, token-aware or not.
+    pub fn p2pkh(public_key: &PublicKey, prefix: &str, token_aware: bool) -> Result<Self> {
+        let address_type = if token_aware {
+            CashAddressType::TokenP2pkh
+        } else {
+            CashAddressType::P2pkh
+        };
+        Self::new(
+            prefix,
+            address_type,
+            public_key.pubkey_hash().as_byte_array(),
+        )
+    }
+
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    pub fn address_type(&self) -> CashAddressType {
+        self.address_type
+    }
+
+    pub fn hash(&self) -> &[u8] {
+        &self.hash
+    }
+
+    pub fn is_token_aware(&self) -> bool {
+        matches!(
+            self.address_type,
+            CashAddressType::TokenP2pkh | CashAddressType::TokenP2sh
+        )
+    }
+
+    pub fn script_pubkey(&self) -> ScriptBuf {
+        if !self.address_type.is_p2sh() {
+            let hash = PubkeyHash::from_slice(&self.hash).expect("The P2PKH hash is 20 bytes");
+            return ScriptBuf::new_p2pkh(&hash);
+        }
+        let hash = PushBytesBuf::try_from(self.hash.clone()).expect("The hash is 20 or 32 bytes");
+        let opcode = if self.hash.len() == 20 {
+            OP_HASH160
+        } else {
+            OP_HASH256
+        };
+        ScriptBuf::builder()
+            .push_opcode(opcode)
+            .push_slice(hash)
+            .push_opcode(OP_EQUAL)
+            .into_script()
+    }
+}
+
+impl FromStr for CashAddress {
+    type Err = Error;
+
+    /// Parses the address with the prefix, or without it on the mainnet.
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::from(Proto::Error::Error_bad_address_recipient);
+
+        // Either lowercase or uppercase.
+        if s.bytes().any(|c| c.is_ascii_lowercase()) && s.bytes().any(|c| c.is_ascii_uppercase()) {
+            return Err(invalid());
+        }
+        let s = s.to_ascii_lowercase();
+        let (prefix, payload) = s.split_once(':').unwrap_or((MAINNET_PREFIX, &s));
+
+        let data = payload
+            .bytes()
+            .map(|c| CHARSET.iter().position(|x| *x == c).map(|i| i as u8))
+            .collect::<Option<Vec<u8>>>()
+            .ok_or_else(invalid)?;
+        if data.len() <= CHECKSUM_LEN || polymod(prefix, &data) != 0 {
+            return Err(invalid());
+        }
+
+        let bytes =
+            convert_bits(&data[..data.len() - CHECKSUM_LEN], 5, 8, false).ok_or_else(invalid)?;
+        let (version, hash) = bytes.split_first().ok_or_else(invalid)?;
+        let address_type = CashAddressType::from_bits(version >> 3).ok_or_else(invalid)?;
+        let size_matches = match version & 0x07 {
+            HASH_160_SIZE => hash.len() == 20,
+            HASH_256_SIZE => hash.len() == 32,
+            _ => false,
+        };
+        if !size_matches {
+            return Err(invalid());
+        }
+        CashAddress::new(prefix, address_type, hash)
+    }
+}
+
+impl fmt::Display for CashAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let size = if self.hash.len() == 20 {
+            HASH_160_SIZE
+        } else {
+            HASH_256_SIZE
+        };
+        let mut bytes = vec![(self.address_type.bits() << 3) | size];
+        bytes.extend_from_slice(&self.hash);
+
+        let mut data = convert_bits(&bytes, 8, 5, true).ok_or(fmt::Error)?;
+        let checksum = polymod(
+            &self.prefix,
+            &[data.as_slice(), &[0; CHECKSUM_LEN]].concat(),
+        );
+        data.extend((0..CHECKSUM_LEN).map(|i| ((checksum >> (5 * (7 - i))) & 0x1f) as u8));
+
+        let payload: String = data.iter().map(|d| CHARSET[*d as usize] as char).collect();
+        write!(f, "{}:{}", self.prefix, payload)
+    }
+}
+
+/// The capability of a non-fungible token.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NftCapability {
+    /// The commitment can't be changed.
+    Immutable,
+    /// The commitment can be changed once, when the token is spent.
+    Mutable,
+    /// Any tokens of the category can be created.
+    Minting,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NonFungibleToken {
+    pub capability: NftCapability,
+    /// At most [`MAX_COMMITMENT_LEN`] bytes.
+    pub commitment: Vec<u8>,
+}
+
+/// The tokens of an output, i.e. the amount of the fungible tokens and/or a non-fungible token of the category.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CashToken {
+    /// The ID of the category, i.e. the ID of the transaction whose first input created it.
+    pub category: Txid,
+    /// The amount of the fungible tokens, `0` if none.
+    pub amount: u64,
+    pub nft: Option<NonFungibleToken>,
+}
+
+impl CashToken {
+    pub fn fungible(category: Txid, amount: u64) -> Self {
+        CashToken {
+            category,
+            amount,
+            nft: None,
+        }
+    }
+
+    pub fn non_fungible(category: Txid, capability: NftCapability, commitment: Vec<u8>) -> Self {
+        CashToken {
+            category,
+            amount: 0,
+            nft: Some(NonFungibleToken {
+                capability,
+                commitment,
+            }),
+        }
+    }
+
+    /// Returns the token prefix of the script pubkey.
+    pub fn prefix(&self) -> Result<Vec<u8>> {
+        let invalid = || Error::from(Proto::Error::Error_invalid_cash_token);
+        if self.amount > MAX_TOKEN_AMOUNT || (self.amount == 0 && self.nft.is_none()) {
+            return Err(invalid());
+        }
+
+        let mut bitfield = 0;
+        let mut fields = Vec::new();
+        if let Some(nft) = &self.nft {
+            if nft.commitment.len() > MAX_COMMITMENT_LEN {
+                return Err(invalid());
+            }
+            bitfield |= HAS_NFT
+                | match nft.capability {
+                    NftCapability::Immutable => 0,
+                    NftCapability::Mutable => 1,
+                    NftCapability::Minting => 2,
+                };
+            if !nft.commitment.is_empty() {
+                bitfield |= HAS_COMMITMENT_LENGTH;
+                fields.extend(bitcoin::consensus::serialize(&VarInt(
+                    nft.commitment.len() as u64
+                )));
+                fields.extend_from_slice(&nft.commitment);
+            }
+        }
+        if self.amount != 0 {
+            bitfield |= HAS_AMOUNT;
+            fields.extend(bitcoin::consensus::serialize(&VarInt(self.amount)));
+        }
+
+        let mut prefix = vec![PREFIX_TOKEN];
+        prefix.extend_from_slice(self.category.as_byte_array());
+        prefix.push(bitfield);
+        prefix.extend(fields);
+        Ok(prefix)
+    }
+
+    /// Splits the `script_pubkey` of an output into its tokens, if any, and the locking script.
+    pub fn split_script_pubkey(script_pubkey: &Script) -> Result<(Option<CashToken>, &Script)> {
+        let invalid = || Error::from(Proto::Error::Error_invalid_cash_token);
+
+        let bytes = script_pubkey.as_bytes();
+        let Some((&PREFIX_TOKEN, mut reader)) = bytes.split_first() else {
+            return Ok((None, script_pubkey));
+        };
+
+        let category = Txid::consensus_decode(&mut reader).map_err(|_| invalid())?;
+        let (&bitfield, mut reader) = reader.split_first().ok_or_else(invalid)?;
+        let capability = match bitfield & CAPABILITY_MASK {
+            0 => NftCapability::Immutable,
+            1 => NftCapability::Mutable,
+            2 => NftCapability::Minting,
+            _ => return Err(invalid()),
+        };
+        let has_nft = bitfield & HAS_NFT != 0;
+        let has_commitment = bitfield & HAS_COMMITMENT_LENGTH != 0;
+        let has_amount = bitfield & HAS_AMOUNT != 0;
+        if bitfield & RESERVED_BIT != 0
+            || (!has_nft && bitfield & (HAS_COMMITMENT_LENGTH | CAPABILITY_MASK) != 0)
+            || (!has_nft && !has_amount)
+        {
+            return Err(invalid());
+        }
+
+        let commitment = if has_commitment {
+            let len = VarInt::consensus_decode(&mut reader)
+                .map_err(|_| invalid())?
+                .0 as usize;
+            if len == 0 || len > MAX_COMMITMENT_LEN || len > reader.len() {
+                return Err(invalid());
+            }
+            let (commitment, rest) = reader.split_at(len);
+            reader = rest;
+            commitment.to_vec()
+        } else {
+            Vec::new()
+        };
+        let amount = if has_amount {
+            let amount = VarInt::consensus_decode(&mut reader)
+                .map_err(|_| invalid())?
+                .0;
+            if amount == 0 || amount > MAX_TOKEN_AMOUNT {
+                return Err(invalid());
+            }
+            amount
+        } else {
+            0
+        };
+
+        let token = CashToken {
+            category,
+            amount,
+            nft: has_nft.then_some(NonFungibleToken {
+                capability,
+                commitment,
+            }),
+        };
+        Ok((Some(token), Script::from_bytes(reader)))
+    }
+}
+
+/// Returns the BIP143 sighash with `SIGHASH_FORKID` of the input at `index` that spends a P2PKH output.
+/// The `prevouts` are the outputs that the inputs spend, in the same order.
+/// The token prefix of the spent output, if any, is committed before the script code.
+pub fn signature_hash(
+    tx: &Transaction,
+    index: usize,
+    prevouts: &[TxOut],
+    sighash_type: EcdsaSighashType,
+) -> Result<[u8; 32]> {
+    if index >= tx.input.len() || prevouts.len() != tx.input.len() {
+        return Err(Error::from(Proto::Error::Error_invalid_prevouts));
+    }
+    let (anyone_can_pay, base_type) = split_sighash_type(sighash_type);
+    if base_type == EcdsaSighashType::Single && index >= tx.output.len() {
+        return Err(Error::from(Proto::Error::Error_utxo_invalid_sighash_type));
+    }
+    let zero = [0; 32];
+
+    let mut data = Vec::new();
+    data.extend(tx.version.to_le_bytes());
+    if anyone_can_pay {
+        data.extend(zero);
+    } else {
+        let prevouts: Vec<u8> = tx
+            .input
+            .iter()
+            .flat_map(|input| bitcoin::consensus::serialize(&input.previous_output))
+            .collect();
+        data.extend(sha256d::Hash::hash(&prevouts).to_byte_array());
+    }
+    if anyone_can_pay || base_type != EcdsaSighashType::All {
+        data.extend(zero);
+    } else {
+        let sequences: Vec<u8> = tx
+            .input
+            .iter()
+            .flat_map(|input| input.sequence.0.to_le_bytes())
+            .collect();
+        data.extend(sha256d::Hash::hash(&sequences).to_byte_array());
+    }
+
+    let input = &tx.input[index];
+    let (_, script_code) = CashToken::split_script_pubkey(&prevouts[index].script_pubkey)?;
+    let token_prefix_len = prevouts[index].script_pubkey.len() - script_code.len();
+    data.extend(bitcoin::consensus::serialize(&input.previous_output));
+    data.extend_from_slice(&prevouts[index].script_pubkey.as_bytes()[..token_prefix_len]);
+    data.extend(bitcoin::consensus::serialize(script_code));
+    data.extend(prevouts[index].value.to_le_bytes());
+    data.extend(input.sequence.0.to_le_bytes());
+
+    match base_type {
+        EcdsaSighashType::Single => {
+            let output = bitcoin::consensus::serialize(&tx.output[index]);
+            data.extend(sha256d::Hash::hash(&output).to_byte_array());
+        },
+        EcdsaSighashType::None => data.extend(zero),
+        _ => {
+            let outputs: Vec<u8> = tx
+                .output
+                .iter()
+                .flat_map(bitcoin::consensus::serialize)
+                .collect();
+            data.extend(sha256d::Hash::hash(&outputs).to_byte_array());
+        },
+    }
+
+    data.extend(tx.lock_time.to_consensus_u32().to_le_bytes());
+    data.extend((sighash_type.to_u32() | SIGHASH_FORKID).to_le_bytes());
+    Ok(sha256d::Hash::hash(&data).to_byte_array())
+}
+
+/// Builds and signs a transaction that spends the P2PKH outputs of a key, with or without tokens.
+/// The fee is the difference of the inputs and the outputs, so the change is to be added as an output.
+pub struct CashTransactionBuilder {
+    version: i32,
+    lock_time: u32,
+    sighash_type: EcdsaSighashType,
+    inputs: Vec<(OutPoint, TxOut)>,
+    outputs: Vec<(u64, CashAddress, Option<CashToken>)>,
+}
+
+impl Default for CashTransactionBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CashTransactionBuilder {
+    pub fn new() -> Self {
+        CashTransactionBuilder {
+            version: 2,
+            lock_time: 0,
+            sighash_type: EcdsaSighashType::All,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+        }
+    }
+
+    /// Sets the version, `2` by default.
+    pub fn version(mut self, version: i32) -> Self {
+        self.version = version;
+        self
+    }
+
+    pub fn lock_time(mut self, lock_time: u32) -> Self {
+        self.lock_time = lock_time;
+        self
+    }
+
+    /// Sets the sighash type of the inputs, `SIGHASH_ALL` by default, to which `SIGHASH_FORKID` is added.
+    pub fn sighash_type(mut self, sighash_type: EcdsaSighashType) -> Self {
+        self.sighash_type = sighash_type;
+        self
+    }
+
+    /// Adds the input that spends the `utxo`, whose script pubkey includes the token prefix if it holds tokens.
+    pub fn add_input(mut self, outpoint: OutPoint, utxo: TxOut) -> Self {
+        self.inputs.push((outpoint, utxo));
+        self
+    }
+
+    pub fn add_output(mut self, value: u64, address: &CashAddress) -> Self {
+        self.outputs.push((value, address.clone(), None));
+        self
+    }
+
+    /// Adds the output of the `token`, which requires a token-aware `address`.
+    pub fn add_token_output(mut self, value: u64, address: &CashAddress, token: CashToken) -> Self {
+        self.outputs.push((value, address.clone(), Some(token)));
+        self
+    }
+
+    /// Returns the transaction whose inputs are signed with the `private_key`.
+    pub fn sign(&self, private_key: &[u8]) -> Result<Transaction> {
+        let secret = SecretKey::from_slice(private_key)
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_private_key))?;
+        let public_key = PublicKey::new(with_context(|secp| secret.public_key(secp)));
+        let own_script = ScriptBuf::new_p2pkh(&public_key.pubkey_hash());
+
+        let output = self
+            .outputs
+            .iter()
+            .map(|(value, address, token)| {
+                let mut script_pubkey = match token {
+                    Some(_) if !address.is_token_aware() => {
+                        return Err(Error::from(Proto::Error::Error_invalid_cash_token))
+                    },
+                    Some(token) => token.prefix()?,
+                    None => Vec::new(),
+                };
+                script_pubkey.extend_from_slice(address.script_pubkey().as_bytes());
+                Ok(TxOut {
+                    value: *value,
+                    script_pubkey: ScriptBuf::from_bytes(script_pubkey),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let mut tx = Transaction {
+            version: self.version,
+            lock_time: LockTime::from_consensus(self.lock_time),
+            input: self
+                .inputs
+                .iter()
+                .map(|(outpoint, _)| TxIn {
+                    previous_output: *outpoint,
+                    script_sig: ScriptBuf::new(),
+                    sequence: Sequence::MAX,
+                    witness: Witness::new(),
+                })
+                .collect(),
+            output,
+        };
+        let prevouts: Vec<TxOut> = self.inputs.iter().map(|(_, utxo)| utxo.clone()).collect();
+
+        for (index, prevout) in prevouts.iter().enumerate() {
+            let (_, script_code) = CashToken::split_script_pubkey(&prevout.script_pubkey)?;
+            if script_code != own_script.as_script() {
+                return Err(Error::from(Proto::Error::Error_invalid_prevouts));
+            }
+
+            let sighash = signature_hash(&tx, index, &prevouts, self.sighash_type)?;
+            let message = Message::from_slice(&sighash)
+                .map_err(|_| Error::from(Proto::Error::Error_invalid_sighash))?;
+            let sig = with_context(|secp| secp.sign_ecdsa(&message, &secret));
+            let mut signature = sig.serialize_der().to_vec();
+            signature.push((self.sighash_type.to_u32() | SIGHASH_FORKID) as u8);
+            let signature = PushBytesBuf::try_from(signature)
+                .map_err(|_| Error::from(Proto::Error::Error_invalid_ecdsa_signature))?;
+
+            tx.input[index].script_sig = ScriptBuf::builder()
+                .push_slice(signature)
+                .push_key(&public_key)
+                .into_script();
+        }
+        Ok(tx)
+    }
+}
+
+/// The 40-bit BCH checksum of the `data` with the lower 5 bits of the `prefix` characters.
+fn polymod(prefix: &str, data: &[u8]) -> u64 {
+    let values = prefix
+        .bytes()
+        .map(|c| c & 0x1f)
+        .chain(std::iter::once(0))
+        .chain(data.iter().copied());
+
+    let mut checksum: u64 = 1;
+    for value in values {
+        let top = checksum >> 35;
+        checksum = ((checksum & 0x07_ffff_ffff) << 5) ^ value as u64;
+        for (i, generator) in GENERATORS.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+    }
+    checksum ^ 1
+}
+
+fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Option<Vec<u8>> {
+    let mut acc: u32 = 0;
+    let mut bits = 0;
+    let mut result = Vec::new();
+    let max = (1 << to) - 1;
+    let max_acc = (1 << (from + to - 1)) - 1;
+    for value in data {
+        acc = ((acc << from) | *value as u32) & max_acc;
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            result.push(((acc >> bits) & max) as u8);
+        }
+    }
+    if pad {
+        if bits > 0 {
+            result.push(((acc << (to - bits)) & max) as u8);
+        }
+    } else if bits >= from || ((acc << (to - bits)) & max) != 0 {
+        return None;
+    }
+    Some(result)
+}
@@ -0,0 +1,310 @@
+//! Lightning invoices as specified in [BOLT11](https://github.com/lightning/bolts/blob/master/11-payment-encoding.md),
+//! decoded so that an invoice can be displayed and validated before it's handed off to a Lightning node:
+//!
+//! ```rust,ignore
+//! let invoice = Invoice::from_str("lnbc1pvjluezsp5zyg3zyg3...")?;
+//! println!("{:?} msat to {}", invoice.amount_msat, invoice.payee);
+//! assert!(!invoice.is_expired(now));
+//! ```
+//!
+//! The signature of the invoice is verified by the public key of the `n` field if any,
+//! otherwise the public key of the payee is recovered from the signature.
+//! The fields of an unknown type or of an unexpected length are skipped, as the specification requires.
+
+use crate::{Error, Result};
+use bitcoin::bech32::{self, u5, Variant};
+use bitcoin::hashes::{sha256, Hash};
+use bitcoin::{Network, PublicKey};
+use secp256k1::ecdsa::Signature;
+use secp256k1::{Message, Secp256k1};
+use std::str::FromStr;
+use tw_hash::H256;
+use tw_keypair::ecdsa::secp256k1 as tw_secp256k1;
+use tw_proto::BitcoinV2::Proto;
+
+/// The seconds after the timestamp that an invoice expires, unless the `x` field specifies it.
+pub const DEFAULT_EXPIRY: u64 = 3600;
+/// The `min_final_cltv_expiry` in blocks, unless the `c` field specifies it.
+pub const DEFAULT_MIN_FINAL_CLTV_EXPIRY: u64 = 18;
+const MSAT_PER_BTC: u64 = 100_000_000_000;
+/// The lengths in 5-bit groups.
+const TIMESTAMP_LEN: usize = 7;
+const SIGNATURE_LEN: usize = 104;
+const HASH_LEN: usize = 52;
+const PUBLIC_KEY_LEN: usize = 53;
+/// The node ID, the short channel ID, the base fee, the proportional fee and the CLTV expiry delta.
+const ROUTE_HOP_LEN: usize = 33 + 8 + 4 + 4 + 2;
+
+// The types of the tagged fields.
+const PAYMENT_HASH_TAG: u8 = 1;
+const ROUTE_HINT_TAG: u8 = 3;
+const EXPIRY_TAG: u8 = 6;
+const DESCRIPTION_TAG: u8 = 13;
+const PAYMENT_SECRET_TAG: u8 = 16;
+const PAYEE_TAG: u8 = 19;
+const DESCRIPTION_HASH_TAG: u8 = 23;
+const MIN_FINAL_CLTV_EXPIRY_TAG: u8 = 24;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Description {
+    /// The purpose of the payment, shown to the payer.
+    Direct(String),
+    /// The SHA-256 hash of the description that's too long for the invoice, provided by other means.
+    Hash([u8; 32]),
+}
+
+/// A hop of a private route to the payee, i.e. the channel from the `node_id` to the next hop.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RouteHop {
+    pub node_id: PublicKey,
+    pub short_channel_id: u64,
+    pub fee_base_msat: u32,
+    pub fee_proportional_millionths: u32,
+    pub cltv_expiry_delta: u16,
+}
+
+/// A decoded invoice, whose signature is verified.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Invoice {
+    pub network: Network,
+    /// The amount in millisatoshis, or any amount if `None`.
+    pub amount_msat: Option<u64>,
+    /// The UNIX timestamp of the creation.
+    pub timestamp: u64,
+    pub payment_hash: [u8; 32],
+    pub payment_secret: Option<[u8; 32]>,
+    pub description: Description,
+    /// The public key of the node that signed the invoice.
+    pub payee: PublicKey,
+    /// The seconds after the timestamp that the invoice expires.
+    pub expiry: u64,
+    pub min_final_cltv_expiry: u64,
+    /// The private routes to the payee, each a list of hops from the first one.
+    pub route_hints: Vec<Vec<RouteHop>>,
+}
+
+impl Invoice {
+    /// Returns the UNIX timestamp that the invoice expires.
+    pub fn expires_at(&self) -> u64 {
+        self.timestamp.saturating_add(self.expiry)
+    }
+
+    /// Returns whether the invoice has expired at the UNIX timestamp `now`.
+    pub fn is_expired(&self, now: u64) -> bool {
+        now >= self.expires_at()
+    }
+}
+
+impl FromStr for Invoice {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (hrp, data, variant) = bech32::decode(s).map_err(|_| invalid())?;
+        if variant != Variant::Bech32 || data.len() < TIMESTAMP_LEN + SIGNATURE_LEN {
+            return Err(invalid());
+        }
+        let (network, amount_msat) = parse_hrp(&hrp)?;
+        let (data, signature) = data.split_at(data.len() - SIGNATURE_LEN);
+        let timestamp = parse_int(&data[..TIMESTAMP_LEN])?;
+
+        let mut payment_hash = None;
+        let mut payment_secret = None;
+        let mut description = None;
+        let mut payee = None;
+        let mut expiry = None;
+        let mut min_final_cltv_expiry = None;
+        let mut route_hints = Vec::new();
+
+        let mut fields = &data[TIMESTAMP_LEN..];
+        while !fields.is_empty() {
+            let (tag, len) = match fields {
+                [tag, len_high, len_low, ..] => (
+                    tag.to_u8(),
+                    (len_high.to_u8() as usize) << 5 | len_low.to_u8() as usize,
+                ),
+                _ => return Err(invalid()),
+            };
+            let value = fields.get(3..3 + len).ok_or_else(invalid)?;
+            fields = &fields[3 + len..];
+
+            // The first field of a type is used.
+            match (tag, len) {
+                (PAYMENT_HASH_TAG, HASH_LEN) if payment_hash.is_none() => {
+                    payment_hash = Some(to_hash(value));
+                },
+                (PAYMENT_SECRET_TAG, HASH_LEN) if payment_secret.is_none() => {
+                    payment_secret = Some(to_hash(value));
+                },
+                (DESCRIPTION_TAG, _) if description.is_none() => {
+                    let text = String::from_utf8(to_bytes(value, false)).map_err(|_| invalid())?;
+                    description = Some(Description::Direct(text));
+                },
+                (DESCRIPTION_HASH_TAG, HASH_LEN) if description.is_none() => {
+                    description = Some(Description::Hash(to_hash(value)));
+                },
+                (PAYEE_TAG, PUBLIC_KEY_LEN) if payee.is_none() => {
+                    let public_key =
+                        PublicKey::from_slice(&to_bytes(value, false)).map_err(|_| invalid())?;
+                    payee = Some(public_key);
+                },
+                (EXPIRY_TAG, _) if expiry.is_none() => expiry = Some(parse_int(value)?),
+                (MIN_FINAL_CLTV_EXPIRY_TAG, _) if min_final_cltv_expiry.is_none() => {
+                    min_final_cltv_expiry = Some(parse_int(value)?);
+                },
+                (ROUTE_HINT_TAG, _) => route_hints.push(parse_route_hint(&to_bytes(value, false))?),
+                _ => (),
+            }
+        }
+
+        // The signature commits to the human-readable part and the data, padded to bytes.
+        let mut preimage = hrp.into_bytes();
+        preimage.extend(to_bytes(data, true));
+        let hash = sha256::Hash::hash(&preimage).to_byte_array();
+        let signature = to_bytes(signature, false);
+        let payee = match payee {
+            Some(payee) => {
+                verify_signature(&hash, &signature, &payee)?;
+                payee
+            },
+            None => recover_payee(&hash, &signature)?,
+        };
+
+        Ok(Invoice {
+            network,
+            amount_msat,
+            timestamp,
+            payment_hash: payment_hash.ok_or_else(invalid)?,
+            payment_secret,
+            description: description.ok_or_else(invalid)?,
+            payee,
+            expiry: expiry.unwrap_or(DEFAULT_EXPIRY),
+            min_final_cltv_expiry: min_final_cltv_expiry.unwrap_or(DEFAULT_MIN_FINAL_CLTV_EXPIRY),
+            route_hints,
+        })
+    }
+}
+
+fn invalid() -> Error {
+    Error::from(Proto::Error::Error_invalid_invoice)
+}
+
+/// Returns the network of the currency prefix and the amount in millisatoshis, if any, of the `ln` prefixed `hrp`.
+fn parse_hrp(hrp: &str) -> Result<(Network, Option<u64>)> {
+    let hrp = hrp.strip_prefix("ln").ok_or_else(invalid)?;
+    // The longer prefixes first, since the amount starts with a digit.
+    let (network, amount) = [
+        ("bcrt", Network::Regtest),
+        ("bc", Network::Bitcoin),
+        ("tbs", Network::Signet),
+        ("tb", Network::Testnet),
+    ]
+    .into_iter()
+    .find_map(|(prefix, network)| hrp.strip_prefix(prefix).map(|amount| (network, amount)))
+    .ok_or_else(invalid)?;
+    if amount.is_empty() {
+        return Ok((network, None));
+    }
+
+    let (digits, multiplier) = match amount.as_bytes().last() {
+        Some(b'm' | b'u' | b'n' | b'p') => amount.split_at(amount.len() - 1),
+        _ => (amount, ""),
+    };
+    if digits.is_empty() || !digits.bytes().all(|digit| digit.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    let value: u64 = digits.parse().map_err(|_| invalid())?;
+    let amount_msat = match multiplier {
+        "m" => value.checked_mul(MSAT_PER_BTC / 1_000),
+        "u" => value.checked_mul(MSAT_PER_BTC / 1_000_000),
+        "n" => value.checked_mul(MSAT_PER_BTC / 1_000_000_000),
+        // A picobitcoin is a tenth of a millisatoshi.
+        "p" => (value % 10 == 0).then_some(value / 10),
+        _ => value.checked_mul(MSAT_PER_BTC),
+    };
+    Ok((network, Some(amount_msat.ok_or_else(invalid)?)))
+}
+
+/// Returns the big-endian integer of the 5-bit groups.
+fn parse_int(data: &[u5]) -> Result<u64> {
+    if data.len() > 12 {
+        return Err(invalid());
+    }
+    Ok(data
+        .iter()
+        .fold(0, |value, group| value << 5 | group.to_u8() as u64))
+}
+
+fn parse_route_hint(bytes: &[u8]) -> Result<Vec<RouteHop>> {
+    if bytes.is_empty() || bytes.len() % ROUTE_HOP_LEN != 0 {
+        return Err(invalid());
+    }
+    bytes
+        .chunks_exact(ROUTE_HOP_LEN)
+        .map(|hop| {
+            let (node_id, hop) = hop.split_at(33);
+            let (short_channel_id, hop) = hop.split_at(8);
+            let (fee_base_msat, hop) = hop.split_at(4);
+            let (fee_proportional_millionths, cltv_expiry_delta) = hop.split_at(4);
+            Ok(RouteHop {
+                node_id: PublicKey::from_slice(node_id).map_err(|_| invalid())?,
+                short_channel_id: u64::from_be_bytes(short_channel_id.try_into().expect("8 bytes")),
+                fee_base_msat: u32::from_be_bytes(fee_base_msat.try_into().expect("4 bytes")),
+                fee_proportional_millionths: u32::from_be_bytes(
+                    fee_proportional_millionths.try_into().expect("4 bytes"),
+                ),
+                cltv_expiry_delta: u16::from_be_bytes(
+                    cltv_expiry_delta.try_into().expect("2 bytes"),
+                ),
+            })
+        })
+        .collect()
+}
+
+/// Returns the bytes of the 5-bit groups. The remaining bits are padded with zeros to a byte if `pad`,
+/// or dropped otherwise.
+fn to_bytes(data: &[u5], pad: bool) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(data.len() * 5 / 8 + 1);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for group in data {
+        buffer = (buffer << 5 | group.to_u8() as u32) & 0xfff;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    if pad && bits > 0 {
+        bytes.push((buffer << (8 - bits)) as u8);
+    }
+    bytes
+}
+
+/// Returns the 32 bytes hash of the 52 5-bit groups.
+fn to_hash(data: &[u5]) -> [u8; 32] {
+    to_bytes(data, false)
+        .try_into()
+        .expect("52 5-bit groups are 32 bytes and 4 bits")
+}
+
+/// Verifies the (r, s) `signature`, followed by the recovery ID, of the `payee` over the `hash`.
+fn verify_signature(hash: &[u8; 32], signature: &[u8], payee: &PublicKey) -> Result<()> {
+    let invalid_signature = || Error::from(Proto::Error::Error_invalid_invoice_signature);
+
+    let signature = Signature::from_compact(&signature[..64]).map_err(|_| invalid_signature())?;
+    let message = Message::from_slice(hash).map_err(|_| invalid_signature())?;
+    Secp256k1::verification_only()
+        .verify_ecdsa(&message, &signature, &payee.inner)
+        .map_err(|_| invalid_signature())
+}
+
+/// Returns the public key that signed the `hash` with the (r, s) `signature`, followed by the recovery ID.
+fn recover_payee(hash: &[u8; 32], signature: &[u8]) -> Result<PublicKey> {
+    let recovered = H256::try_from(&signature[..32])
+        .and_then(|r| H256::try_from(&signature[32..64]).map(|s| (r, s)))
+        .ok()
+        .and_then(|(r, s)| tw_secp256k1::Signature::try_from_parts(r, s, signature[64]).ok())
+        .and_then(|signature| tw_secp256k1::PublicKey::recover(signature, H256::from(*hash)).ok())
+        .ok_or_else(|| Error::from(Proto::Error::Error_invalid_invoice_signature))?;
+    Ok(PublicKey::from_slice(recovered.compressed().as_slice())?)
+}
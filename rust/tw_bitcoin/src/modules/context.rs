@@ -0,0 +1,111 @@
+//! The shared `secp256k1` context that is used for the secret-dependent operations.
+//!
+//! By default, the global [`secp256k1::SECP256K1`] context is used.
+//! It's randomized once on first use and lives for the process lifetime.
+//!
+//! Enable the `rerandomize-context` feature to use a context that is re-randomized
+//! every [`RERANDOMIZE_INTERVAL`] uses and on [`rerandomize`] call, e.g. when a mobile app is resumed.
+//! Re-randomization refreshes the blinding of the secret-dependent operations,
+//! which hardens them against side-channel attacks.
+
+use secp256k1::{All, Secp256k1};
+
+/// The number of uses after which the context is re-randomized.
+pub const RERANDOMIZE_INTERVAL: usize = 1000;
+
+/// Calls `f` with the shared context.
+pub fn with_context<F, R>(f: F) -> R
+where
+    F: FnOnce(&Secp256k1<All>) -> R,
+{
+    imp::with_context(f)
+}
+
+/// Re-randomizes the shared context immediately.
+/// Does nothing if the `rerandomize-context` feature is disabled.
+pub fn rerandomize() {
+    imp::rerandomize()
+}
+
+#[cfg(feature = "rerandomize-context")]
+mod imp {
+    use super::RERANDOMIZE_INTERVAL;
+    use secp256k1::rand::thread_rng;
+    use secp256k1::{All, Secp256k1};
+    use std::sync::{Mutex, MutexGuard, OnceLock, PoisonError};
+
+    struct Context {
+        secp: Secp256k1<All>,
+        uses: usize,
+    }
+
+    impl Context {
+        fn rerandomize(&mut self) {
+            self.secp.randomize(&mut thread_rng());
+            self.uses = 0;
+        }
+    }
+
+    fn lock_context() -> MutexGuard<'static, Context> {
+        static CONTEXT: OnceLock<Mutex<Context>> = OnceLock::new();
+        CONTEXT
+            .get_or_init(|| {
+                // `Secp256k1::new` randomizes the context if the `rand-std` feature is enabled.
+                Mutex::new(Context {
+                    secp: Secp256k1::new(),
+                    uses: 0,
+                })
+            })
+            .lock()
+            // The context remains valid even if another thread panicked while holding the lock.
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn with_context<F, R>(f: F) -> R
+    where
+        F: FnOnce(&Secp256k1<All>) -> R,
+    {
+        let mut context = lock_context();
+        context.uses += 1;
+        if context.uses >= RERANDOMIZE_INTERVAL {
+            context.rerandomize();
+        }
+        f(&context.secp)
+    }
+
+    pub fn rerandomize() {
+        lock_context().rerandomize();
+    }
+}
+
+#[cfg(not(feature = "rerandomize-context"))]
+mod imp {
+    use secp256k1::{All, Secp256k1};
+
+    pub fn with_context<F, R>(f: F) -> R
+    where
+        F: FnOnce(&Secp256k1<All>) -> R,
+    {
+        f(secp256k1::SECP256K1)
+    }
+
+    pub fn rerandomize() {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use secp256k1::{PublicKey, SecretKey};
+
+    #[test]
+    fn test_with_context_rerandomize() {
+        let secret = SecretKey::from_slice(&[1; 32]).unwrap();
+        let expected = with_context(|secp| PublicKey::from_secret_key(secp, &secret));
+
+        rerandomize();
+        for _ in 0..RERANDOMIZE_INTERVAL + 1 {
+            let actual = with_context(|secp| PublicKey::from_secret_key(secp, &secret));
+            assert_eq!(actual, expected);
+        }
+    }
+}
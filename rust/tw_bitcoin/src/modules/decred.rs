@@ -0,0 +1,411 @@
+//! Decred transactions, which separate the prefix of the inputs and the outputs from the witness of the signature scripts,
+//! and hash with BLAKE-256 instead of the double SHA-256:
+//!
+//! ```rust,ignore
+//! let mut tx = DecredTransaction::new();
+//! tx.inputs.push(DecredTxIn::new(previous_output, value_in));
+//! tx.outputs.push(DecredTxOut::new(value, DecredAddress::from_str("Ds...")?.script_pubkey()));
+//! tx.sign_p2pkh(PRIVATE_KEY, &prevout_scripts, EcdsaSighashType::All)?;
+//! let encoded = tx.encode();
+//! ```
+
+use crate::modules::context::with_context;
+use crate::modules::signer::split_sighash_type;
+use crate::{Error, Result};
+use bitcoin::blockdata::script::PushBytesBuf;
+use bitcoin::consensus::encode::VarInt;
+use bitcoin::hashes::Hash;
+use bitcoin::sighash::EcdsaSighashType;
+use bitcoin::{Network, OutPoint, PubkeyHash, PublicKey, Script, ScriptBuf, ScriptHash, Txid};
+use secp256k1::{Message, SecretKey};
+use std::fmt;
+use std::str::FromStr;
+use tw_encoding::base58::{self, Alphabet};
+use tw_hash::blake::blake_256;
+use tw_hash::ripemd::ripemd_160;
+use tw_proto::BitcoinV2::Proto;
+
+/// The two-byte prefixes of the base58 encoded addresses.
+const MAINNET_P2PKH_PREFIX: [u8; 2] = [0x07, 0x3f];
+const MAINNET_P2SH_PREFIX: [u8; 2] = [0x07, 0x1a];
+const TESTNET_P2PKH_PREFIX: [u8; 2] = [0x0f, 0x21];
+const TESTNET_P2SH_PREFIX: [u8; 2] = [0x0e, 0xfc];
+const CHECKSUM_LEN: usize = 4;
+
+/// The tree of the regular transactions, as opposed to the stake ones.
+pub const REGULAR_TREE: u8 = 0;
+/// The block index of an input whose funding transaction isn't known to be mined.
+pub const NO_BLOCK_INDEX: u32 = u32::MAX;
+
+/// The serialization type of a transaction, i.e. the upper 16 bits of the version field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SerializeType {
+    /// The prefix followed by the witness.
+    Full,
+    /// The prefix only, which the transaction ID is the hash of.
+    NoWitness,
+    OnlyWitness,
+}
+
+impl SerializeType {
+    fn bits(&self) -> u32 {
+        match self {
+            SerializeType::Full => 0,
+            SerializeType::NoWitness => 1,
+            SerializeType::OnlyWitness => 2,
+        }
+    }
+}
+
+/// The serialization type of the witness that is committed by the sighash.
+const SIGHASH_WITNESS_TYPE: u32 = 3;
+
+/// A P2PKH or P2SH address, i.e. the base58 encoded hash with a two-byte prefix and a BLAKE-256 checksum.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecredAddress {
+    /// The `Ds` address on the mainnet, or `Ts` on the testnet.
+    P2pkh { hash: PubkeyHash, network: Network },
+    /// The `Dc` address on the mainnet, or `Tc` on the testnet.
+    P2sh { hash: ScriptHash, network: Network },
+}
+
+impl DecredAddress {
+    /// Creates the P2PKH address of the `public_key`, where any network but the mainnet uses the testnet prefix.
+    pub fn p2pkh(public_key: &PublicKey, network: Network) -> Self {
+        DecredAddress::P2pkh {
+            hash: pubkey_hash(public_key),
+            network,
+        }
+    }
+
+    pub fn script_pubkey(&self) -> ScriptBuf {
+        match self {
+            DecredAddress::P2pkh { hash, .. } => ScriptBuf::new_p2pkh(hash),
+            DecredAddress::P2sh { hash, .. } => ScriptBuf::new_p2sh(hash),
+        }
+    }
+}
+
+impl FromStr for DecredAddress {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::from(Proto::Error::Error_bad_address_recipient);
+
+        let data = base58::decode(s, Alphabet::BITCOIN).map_err(|_| invalid())?;
+        if data.len() != 22 + CHECKSUM_LEN {
+            return Err(invalid());
+        }
+        let (payload, checksum) = data.split_at(22);
+        if self::checksum(payload) != checksum {
+            return Err(invalid());
+        }
+        let (prefix, hash) = payload.split_at(2);
+        let address = match [prefix[0], prefix[1]] {
+            MAINNET_P2PKH_PREFIX => DecredAddress::P2pkh {
+                hash: PubkeyHash::from_slice(hash).map_err(|_| invalid())?,
+                network: Network::Bitcoin,
+            },
+            TESTNET_P2PKH_PREFIX => DecredAddress::P2pkh {
+                hash: PubkeyHash::from_slice(hash).map_err(|_| invalid())?,
+                network: Network::Testnet,
+            },
+            MAINNET_P2SH_PREFIX => DecredAddress::P2sh {
+                hash: ScriptHash::from_slice(hash).map_err(|_| invalid())?,
+                network: Network::Bitcoin,
+            },
+            TESTNET_P2SH_PREFIX => DecredAddress::P2sh {
+                hash: ScriptHash::from_slice(hash).map_err(|_| invalid())?,
+                network: Network::Testnet,
+            },
+            _ => return Err(invalid()),
+        };
+        Ok(address)
+    }
+}
+
+impl fmt::Display for DecredAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (prefix, hash) = match self {
+            DecredAddress::P2pkh {
+                hash,
+                network: Network::Bitcoin,
+            } => (MAINNET_P2PKH_PREFIX, hash.as_byte_array()),
+            DecredAddress::P2pkh { hash, .. } => (TESTNET_P2PKH_PREFIX, hash.as_byte_array()),
+            DecredAddress::P2sh {
+                hash,
+                network: Network::Bitcoin,
+            } => (MAINNET_P2SH_PREFIX, hash.as_byte_array()),
+            DecredAddress::P2sh { hash, .. } => (TESTNET_P2SH_PREFIX, hash.as_byte_array()),
+        };
+        let mut data = prefix.to_vec();
+        data.extend_from_slice(hash);
+        data.extend(checksum(&data));
+        f.write_str(&base58::encode(&data, Alphabet::BITCOIN))
+    }
+}
+
+/// An input, whose outpoint and sequence are of the prefix, while the rest is of the witness.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecredTxIn {
+    pub previous_output: OutPoint,
+    pub tree: u8,
+    pub sequence: u32,
+    /// The value of the spent output.
+    pub value_in: u64,
+    pub block_height: u32,
+    pub block_index: u32,
+    pub signature_script: ScriptBuf,
+}
+
+impl DecredTxIn {
+    /// Creates the unsigned input of the regular tree, of the unknown block.
+    pub fn new(previous_output: OutPoint, value_in: u64) -> Self {
+        DecredTxIn {
+            previous_output,
+            tree: REGULAR_TREE,
+            sequence: u32::MAX,
+            value_in,
+            block_height: 0,
+            block_index: NO_BLOCK_INDEX,
+            signature_script: ScriptBuf::new(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecredTxOut {
+    pub value: u64,
+    /// The version of the script, `0` for the standard scripts.
+    pub version: u16,
+    pub script_pubkey: ScriptBuf,
+}
+
+impl DecredTxOut {
+    pub fn new(value: u64, script_pubkey: ScriptBuf) -> Self {
+        DecredTxOut {
+            value,
+            version: 0,
+            script_pubkey,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecredTransaction {
+    pub version: u16,
+    pub inputs: Vec<DecredTxIn>,
+    pub outputs: Vec<DecredTxOut>,
+    pub lock_time: u32,
+    /// The block height after which the transaction can't be mined, `0` to disable.
+    pub expiry: u32,
+}
+
+impl Default for DecredTransaction {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DecredTransaction {
+    pub fn new() -> Self {
+        DecredTransaction {
+            version: 1,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            lock_time: 0,
+            expiry: 0,
+        }
+    }
+
+    /// Returns the full serialization.
+    pub fn encode(&self) -> Vec<u8> {
+        self.encode_as(SerializeType::Full)
+    }
+
+    pub fn encode_as(&self, serialize_type: SerializeType) -> Vec<u8> {
+        let mut data = self.version_field(serialize_type.bits()).to_vec();
+        if serialize_type != SerializeType::OnlyWitness {
+            self.encode_prefix(&mut data);
+        }
+        if serialize_type != SerializeType::NoWitness {
+            data.extend(var_int(self.inputs.len()));
+            for input in &self.inputs {
+                data.extend(input.value_in.to_le_bytes());
+                data.extend(input.block_height.to_le_bytes());
+                data.extend(input.block_index.to_le_bytes());
+                data.extend(bitcoin::consensus::serialize(&input.signature_script));
+            }
+        }
+        data
+    }
+
+    /// Returns the transaction ID, i.e. the BLAKE-256 of the prefix, so it doesn't commit to the signatures.
+    pub fn txid(&self) -> Txid {
+        let hash = blake_256(&self.encode_as(SerializeType::NoWitness));
+        Txid::from_slice(&hash).expect("BLAKE-256 is 32 bytes")
+    }
+
+    /// Returns the sighash of the input at `index`, which spends the output of the `script_pubkey`.
+    pub fn signature_hash(
+        &self,
+        index: usize,
+        script_pubkey: &Script,
+        sighash_type: EcdsaSighashType,
+    ) -> Result<[u8; 32]> {
+        if index >= self.inputs.len() {
+            return Err(Error::from(Proto::Error::Error_invalid_prevouts));
+        }
+        let (anyone_can_pay, base_type) = split_sighash_type(sighash_type);
+        if base_type == EcdsaSighashType::Single && index >= self.outputs.len() {
+            return Err(Error::from(Proto::Error::Error_utxo_invalid_sighash_type));
+        }
+
+        // Only the signed input is committed with `SIGHASH_ANYONECANPAY`.
+        let (inputs, sign_index) = if anyone_can_pay {
+            (&self.inputs[index..=index], 0)
+        } else {
+            (self.inputs.as_slice(), index)
+        };
+        let outputs = match base_type {
+            EcdsaSighashType::None => &self.outputs[..0],
+            EcdsaSighashType::Single => &self.outputs[..=index],
+            _ => self.outputs.as_slice(),
+        };
+
+        let mut prefix = self.version_field(SerializeType::NoWitness.bits()).to_vec();
+        prefix.extend(var_int(inputs.len()));
+        for (i, input) in inputs.iter().enumerate() {
+            prefix.extend(outpoint(input));
+            // The other inputs may update their sequences with `SIGHASH_NONE` and `SIGHASH_SINGLE`.
+            let sequence = if base_type != EcdsaSighashType::All && i != sign_index {
+                0
+            } else {
+                input.sequence
+            };
+            prefix.extend(sequence.to_le_bytes());
+        }
+        prefix.extend(var_int(outputs.len()));
+        for (i, output) in outputs.iter().enumerate() {
+            // The preceding outputs are blanked with `SIGHASH_SINGLE`.
+            if base_type == EcdsaSighashType::Single && i != index {
+                prefix.extend(u64::MAX.to_le_bytes());
+                prefix.extend(output.version.to_le_bytes());
+                prefix.extend(var_int(0));
+            } else {
+                prefix.extend(encode_output(output));
+            }
+        }
+        prefix.extend(self.lock_time.to_le_bytes());
+        prefix.extend(self.expiry.to_le_bytes());
+
+        // The script pubkey replaces the signature script of the signed input only.
+        let mut witness = self.version_field(SIGHASH_WITNESS_TYPE).to_vec();
+        witness.extend(var_int(inputs.len()));
+        for i in 0..inputs.len() {
+            if i == sign_index {
+                witness.extend(bitcoin::consensus::serialize(script_pubkey));
+            } else {
+                witness.extend(var_int(0));
+            }
+        }
+
+        let mut preimage = sighash_type.to_u32().to_le_bytes().to_vec();
+        preimage.extend(blake_256(&prefix));
+        preimage.extend(blake_256(&witness));
+        let mut sighash = [0; 32];
+        sighash.copy_from_slice(&blake_256(&preimage));
+        Ok(sighash)
+    }
+
+    /// Signs the inputs that spend the P2PKH outputs of the compressed public key of the `private_key`.
+    /// The `prevout_scripts` are the script pubkeys of the outputs that the inputs spend, in the same order.
+    pub fn sign_p2pkh(
+        &mut self,
+        private_key: &[u8],
+        prevout_scripts: &[ScriptBuf],
+        sighash_type: EcdsaSighashType,
+    ) -> Result<()> {
+        if prevout_scripts.len() != self.inputs.len() {
+            return Err(Error::from(Proto::Error::Error_invalid_prevouts));
+        }
+        let secret = SecretKey::from_slice(private_key)
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_private_key))?;
+        let public_key = PublicKey::new(with_context(|secp| secret.public_key(secp)));
+        let own_script = ScriptBuf::new_p2pkh(&pubkey_hash(&public_key));
+
+        let mut signed = false;
+        for (index, script_pubkey) in prevout_scripts.iter().enumerate() {
+            if *script_pubkey != own_script {
+                continue;
+            }
+            let sighash = self.signature_hash(index, script_pubkey, sighash_type)?;
+            let message = Message::from_slice(&sighash)
+                .map_err(|_| Error::from(Proto::Error::Error_invalid_sighash))?;
+            let sig = bitcoin::ecdsa::Signature {
+                sig: with_context(|secp| secp.sign_ecdsa(&message, &secret)),
+                hash_ty: sighash_type,
+            };
+            let signature = PushBytesBuf::try_from(sig.to_vec())
+                .map_err(|_| Error::from(Proto::Error::Error_invalid_ecdsa_signature))?;
+
+            self.inputs[index].signature_script = ScriptBuf::builder()
+                .push_slice(signature)
+                .push_key(&public_key)
+                .into_script();
+            signed = true;
+        }
+
+        if !signed {
+            return Err(Error::from(Proto::Error::Error_invalid_prevouts));
+        }
+        Ok(())
+    }
+
+    fn version_field(&self, serialize_type: u32) -> [u8; 4] {
+        (self.version as u32 | (serialize_type << 16)).to_le_bytes()
+    }
+
+    fn encode_prefix(&self, data: &mut Vec<u8>) {
+        data.extend(var_int(self.inputs.len()));
+        for input in &self.inputs {
+            data.extend(outpoint(input));
+            data.extend(input.sequence.to_le_bytes());
+        }
+        data.extend(var_int(self.outputs.len()));
+        for output in &self.outputs {
+            data.extend(encode_output(output));
+        }
+        data.extend(self.lock_time.to_le_bytes());
+        data.extend(self.expiry.to_le_bytes());
+    }
+}
+
+/// Returns the RIPEMD-160 of the BLAKE-256 of the `public_key`.
+pub fn pubkey_hash(public_key: &PublicKey) -> PubkeyHash {
+    let hash = ripemd_160(&blake_256(&public_key.to_bytes()));
+    PubkeyHash::from_slice(&hash).expect("RIPEMD-160 is 20 bytes")
+}
+
+/// The hash, the index and the tree of the spent output.
+fn outpoint(input: &DecredTxIn) -> Vec<u8> {
+    let mut data = bitcoin::consensus::serialize(&input.previous_output);
+    data.push(input.tree);
+    data
+}
+
+fn encode_output(output: &DecredTxOut) -> Vec<u8> {
+    let mut data = output.value.to_le_bytes().to_vec();
+    data.extend(output.version.to_le_bytes());
+    data.extend(bitcoin::consensus::serialize(&output.script_pubkey));
+    data
+}
+
+fn var_int(len: usize) -> Vec<u8> {
+    bitcoin::consensus::serialize(&VarInt(len as u64))
+}
+
+/// The first four bytes of the double BLAKE-256.
+fn checksum(data: &[u8]) -> Vec<u8> {
+    blake_256(&blake_256(data))[..CHECKSUM_LEN].to_vec()
+}
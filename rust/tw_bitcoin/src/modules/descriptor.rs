@@ -0,0 +1,551 @@
+//! Output script descriptors as specified in [BIP380](https://github.com/bitcoin/bips/blob/master/bip-0380.mediawiki),
+//! i.e. the watch-only description of the outputs of a wallet:
+//!
+//! ```rust,ignore
+//! let descriptor = Descriptor::from_str("wpkh([73c5da0a/84'/0'/0']xpub6CatWdiZ.../0/*)#wc3n3van")?;
+//! let derived = descriptor.derive(0)?;
+//! let address = derived.address(Network::Bitcoin)?;
+//! ```
+//!
+//! The supported descriptors are `pkh`, `wpkh`, `sh(wpkh)`, `tr` without script paths,
+//! and `multi` or `sortedmulti` within `sh`, `wsh` or `sh(wsh)`.
+//! A key is either a hex public key, or an `xpub` followed by unhardened derivation steps and an optional `/*` wildcard,
+//! and may be preceded by its origin, e.g. `[d34db33f/48'/0'/0'/2']`.
+
+use crate::{Error, Result};
+use bitcoin::bip32::{ChildNumber, ExtendedPubKey, Fingerprint};
+use bitcoin::blockdata::opcodes::all::OP_CHECKMULTISIG;
+use bitcoin::blockdata::script::Builder;
+use bitcoin::{Address, Network, PublicKey, ScriptBuf};
+use secp256k1::XOnlyPublicKey;
+use std::str::FromStr;
+use tw_encoding::hex;
+use tw_proto::BitcoinV2::Proto;
+
+/// The characters of a descriptor that the checksum is computed over.
+const INPUT_CHARSET: &str =
+    "0123456789()[],'/*abcdefgh@:$%{}IJKLMNOPQRSTUVWXYZ&+-.;<=>?!^_|~ijklmnopqrstuvwxyzABCDEFGH`#\"\\ ";
+const CHECKSUM_CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const CHECKSUM_LEN: usize = 8;
+const CHECKSUM_GENERATOR: [u64; 5] = [
+    0xf5dee51989,
+    0xa9fdca3312,
+    0x1bab10e32d,
+    0x3706b1677a,
+    0x644d626ffd,
+];
+const HARDENED_MARKERS: [char; 2] = ['\'', 'h'];
+const WILDCARD: &str = "*";
+/// The maximum number of keys of a `multi` within `sh`, limited by the size of the redeem script.
+const MAX_SH_MULTI_KEYS: usize = 15;
+/// The maximum number of keys of a `multi` within `wsh`.
+const MAX_WSH_MULTI_KEYS: usize = 20;
+
+/// The origin of a key, i.e. the fingerprint of the master key and the derivation path of the key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyOrigin {
+    pub fingerprint: Fingerprint,
+    pub path: Vec<ChildNumber>,
+}
+
+/// A key of a descriptor.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DescriptorKey {
+    pub origin: Option<KeyOrigin>,
+    pub key: KeySource,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum KeySource {
+    /// A hex encoded public key.
+    Single(PublicKey),
+    /// A hex encoded x-only public key, allowed within `tr` only.
+    XOnly(XOnlyPublicKey),
+    /// The keys derived from an extended public key at `path`, followed by the index if `wildcard` is set.
+    Extended {
+        xpub: ExtendedPubKey,
+        path: Vec<ChildNumber>,
+        wildcard: bool,
+    },
+}
+
+/// A `multi` or `sortedmulti` multisig script.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Multisig {
+    pub threshold: usize,
+    pub keys: Vec<DescriptorKey>,
+    /// Whether the keys are sorted in the script, i.e. `sortedmulti`.
+    pub sorted: bool,
+}
+
+/// A parsed output script descriptor.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Descriptor {
+    Pkh(DescriptorKey),
+    Wpkh(DescriptorKey),
+    ShWpkh(DescriptorKey),
+    Tr(DescriptorKey),
+    ShMulti(Multisig),
+    WshMulti(Multisig),
+    ShWshMulti(Multisig),
+}
+
+/// The scripts and keys of the output of a descriptor at a specific index,
+/// which are needed to sign the spending inputs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DerivedDescriptor {
+    pub script_pubkey: ScriptBuf,
+    /// The redeem script of a P2SH output.
+    pub redeem_script: Option<ScriptBuf>,
+    /// The witness script of a P2WSH output.
+    pub witness_script: Option<ScriptBuf>,
+    /// The internal key of a P2TR output.
+    pub tap_internal_key: Option<XOnlyPublicKey>,
+    /// The derived public keys along with their full origins, in the order of the descriptor.
+    pub keys: Vec<DerivedKey>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DerivedKey {
+    pub public_key: PublicKey,
+    pub origin: Option<KeyOrigin>,
+}
+
+impl DerivedDescriptor {
+    pub fn address(&self, network: Network) -> Result<Address> {
+        Address::from_script(&self.script_pubkey, network)
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_descriptor))
+    }
+}
+
+impl Descriptor {
+    /// Returns whether the descriptor has a key with a wildcard,
+    /// i.e. whether it describes a range of outputs rather than a single one.
+    pub fn has_wildcard(&self) -> bool {
+        self.keys().iter().any(|key| key.has_wildcard())
+    }
+
+    /// Derives the output at the given (unhardened) `index`.
+    /// The index is ignored for the keys without a wildcard.
+    pub fn derive(&self, index: u32) -> Result<DerivedDescriptor> {
+        let secp = secp256k1::Secp256k1::verification_only();
+        let keys = self
+            .keys()
+            .iter()
+            .map(|key| key.derive(&secp, index))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut derived = DerivedDescriptor {
+            script_pubkey: ScriptBuf::new(),
+            redeem_script: None,
+            witness_script: None,
+            tap_internal_key: None,
+            keys,
+        };
+        let public_key = derived.keys[0].public_key;
+        match self {
+            Descriptor::Pkh(_) => {
+                derived.script_pubkey = ScriptBuf::new_p2pkh(&public_key.pubkey_hash());
+            },
+            Descriptor::Wpkh(_) => {
+                derived.script_pubkey = ScriptBuf::new_v0_p2wpkh(&wpubkey_hash(&public_key)?);
+            },
+            Descriptor::ShWpkh(_) => {
+                let redeem_script = ScriptBuf::new_v0_p2wpkh(&wpubkey_hash(&public_key)?);
+                derived.script_pubkey = ScriptBuf::new_p2sh(&redeem_script.script_hash());
+                derived.redeem_script = Some(redeem_script);
+            },
+            Descriptor::Tr(_) => {
+                let internal_key = XOnlyPublicKey::from(public_key.inner);
+                derived.script_pubkey = ScriptBuf::new_v1_p2tr(&secp, internal_key, None);
+                derived.tap_internal_key = Some(internal_key);
+            },
+            Descriptor::ShMulti(multisig) => {
+                let redeem_script = multisig.script(&derived.keys);
+                derived.script_pubkey = ScriptBuf::new_p2sh(&redeem_script.script_hash());
+                derived.redeem_script = Some(redeem_script);
+            },
+            Descriptor::WshMulti(multisig) => {
+                let witness_script = multisig.script(&derived.keys);
+                derived.script_pubkey = ScriptBuf::new_v0_p2wsh(&witness_script.wscript_hash());
+                derived.witness_script = Some(witness_script);
+            },
+            Descriptor::ShWshMulti(multisig) => {
+                let witness_script = multisig.script(&derived.keys);
+                let redeem_script = ScriptBuf::new_v0_p2wsh(&witness_script.wscript_hash());
+                derived.script_pubkey = ScriptBuf::new_p2sh(&redeem_script.script_hash());
+                derived.redeem_script = Some(redeem_script);
+                derived.witness_script = Some(witness_script);
+            },
+        }
+        Ok(derived)
+    }
+
+    fn keys(&self) -> &[DescriptorKey] {
+        match self {
+            Descriptor::Pkh(key)
+            | Descriptor::Wpkh(key)
+            | Descriptor::ShWpkh(key)
+            | Descriptor::Tr(key) => std::slice::from_ref(key),
+            Descriptor::ShMulti(multisig)
+            | Descriptor::WshMulti(multisig)
+            | Descriptor::ShWshMulti(multisig) => &multisig.keys,
+        }
+    }
+
+    fn parse(s: &str) -> Result<Descriptor> {
+        let mut parser = Parser::new(s);
+        let descriptor = match parser.function()? {
+            "pkh" => Descriptor::Pkh(parser.key(KeyContext::Legacy)?),
+            "wpkh" => Descriptor::Wpkh(parser.key(KeyContext::Segwit)?),
+            "tr" => Descriptor::Tr(parser.key(KeyContext::Taproot)?),
+            "sh" => match parser.function()? {
+                "wpkh" => {
+                    let key = parser.key(KeyContext::Segwit)?;
+                    Descriptor::ShWpkh(parser.end(key)?)
+                },
+                "wsh" => {
+                    let multisig = parser.multisig(KeyContext::Segwit, MAX_WSH_MULTI_KEYS)?;
+                    Descriptor::ShWshMulti(parser.end(multisig)?)
+                },
+                name => Descriptor::ShMulti(parser.multisig_body(
+                    name,
+                    KeyContext::Legacy,
+                    MAX_SH_MULTI_KEYS,
+                )?),
+            },
+            "wsh" => Descriptor::WshMulti(parser.multisig(KeyContext::Segwit, MAX_WSH_MULTI_KEYS)?),
+            _ => return Err(invalid_descriptor()),
+        };
+        parser.finish(descriptor)
+    }
+}
+
+impl FromStr for Descriptor {
+    type Err = Error;
+
+    /// Parses the descriptor, verifying the checksum if any.
+    fn from_str(s: &str) -> Result<Self> {
+        let descriptor = match s.split_once('#') {
+            Some((descriptor, checksum)) => {
+                if checksum != descriptor_checksum(descriptor)? {
+                    return Err(invalid_descriptor());
+                }
+                descriptor
+            },
+            None => s,
+        };
+        Descriptor::parse(descriptor)
+    }
+}
+
+impl Multisig {
+    fn script(&self, keys: &[DerivedKey]) -> ScriptBuf {
+        let mut public_keys: Vec<_> = keys.iter().map(|key| key.public_key).collect();
+        if self.sorted {
+            public_keys.sort_by_key(|key| key.to_bytes());
+        }
+        let builder = public_keys.iter().fold(
+            Builder::new().push_int(self.threshold as i64),
+            |builder, key| builder.push_key(key),
+        );
+        builder
+            .push_int(public_keys.len() as i64)
+            .push_opcode(OP_CHECKMULTISIG)
+            .into_script()
+    }
+}
+
+impl DescriptorKey {
+    fn has_wildcard(&self) -> bool {
+        matches!(self.key, KeySource::Extended { wildcard: true, .. })
+    }
+
+    fn derive<C: secp256k1::Verification>(
+        &self,
+        secp: &secp256k1::Secp256k1<C>,
+        index: u32,
+    ) -> Result<DerivedKey> {
+        let (xpub, path, wildcard) = match self.key {
+            KeySource::Single(public_key) => {
+                return Ok(DerivedKey {
+                    public_key,
+                    origin: self.origin.clone(),
+                })
+            },
+            KeySource::XOnly(xonly) => {
+                return Ok(DerivedKey {
+                    public_key: PublicKey::new(xonly.public_key(secp256k1::Parity::Even)),
+                    origin: self.origin.clone(),
+                })
+            },
+            KeySource::Extended {
+                ref xpub,
+                ref path,
+                wildcard,
+            } => (xpub, path, wildcard),
+        };
+
+        let mut full_path = path.clone();
+        if wildcard {
+            let index = ChildNumber::from_normal_idx(index).map_err(|_| invalid_descriptor())?;
+            full_path.push(index);
+        }
+        let derived = xpub
+            .derive_pub(secp, &full_path)
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_public_key))?;
+
+        // The origin of a key without one is the extended key itself.
+        let origin = match self.origin {
+            Some(ref origin) => KeyOrigin {
+                fingerprint: origin.fingerprint,
+                path: origin
+                    .path
+                    .iter()
+                    .chain(full_path.iter())
+                    .copied()
+                    .collect(),
+            },
+            None => KeyOrigin {
+                fingerprint: xpub.fingerprint(),
+                path: full_path,
+            },
+        };
+        Ok(DerivedKey {
+            public_key: derived.to_pub(),
+            origin: Some(origin),
+        })
+    }
+}
+
+/// The script context of a key, which determines the allowed key formats.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum KeyContext {
+    /// Uncompressed keys are allowed.
+    Legacy,
+    Segwit,
+    /// X-only keys are allowed.
+    Taproot,
+}
+
+/// A recursive descent parser of the descriptor syntax.
+struct Parser<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    fn new(s: &'a str) -> Parser<'a> {
+        Parser { rest: s }
+    }
+
+    /// Parses `name(` and returns the name.
+    fn function(&mut self) -> Result<&'a str> {
+        let (name, rest) = self.rest.split_once('(').ok_or_else(invalid_descriptor)?;
+        self.rest = rest;
+        Ok(name)
+    }
+
+    /// Parses the closing `)` of a function.
+    fn end<T>(&mut self, value: T) -> Result<T> {
+        self.rest = self.rest.strip_prefix(')').ok_or_else(invalid_descriptor)?;
+        Ok(value)
+    }
+
+    /// Parses the closing `)` of the top-level function, which must be the end of the descriptor.
+    fn finish<T>(&mut self, value: T) -> Result<T> {
+        let value = self.end(value)?;
+        if !self.rest.is_empty() {
+            return Err(invalid_descriptor());
+        }
+        Ok(value)
+    }
+
+    /// Parses `multi(...)` or `sortedmulti(...)`.
+    fn multisig(&mut self, context: KeyContext, max_keys: usize) -> Result<Multisig> {
+        let name = self.function()?;
+        self.multisig_body(name, context, max_keys)
+    }
+
+    /// Parses the arguments of the `name` multisig function and its closing `)`.
+    fn multisig_body(
+        &mut self,
+        name: &str,
+        context: KeyContext,
+        max_keys: usize,
+    ) -> Result<Multisig> {
+        let sorted = match name {
+            "multi" => false,
+            "sortedmulti" => true,
+            _ => return Err(invalid_descriptor()),
+        };
+
+        let (threshold, rest) = self.rest.split_once(',').ok_or_else(invalid_descriptor)?;
+        self.rest = rest;
+        let threshold: usize = threshold.parse().map_err(|_| invalid_descriptor())?;
+
+        let mut keys = vec![self.key(context)?];
+        while let Some(rest) = self.rest.strip_prefix(',') {
+            self.rest = rest;
+            keys.push(self.key(context)?);
+        }
+        if threshold == 0 || threshold > keys.len() || keys.len() > max_keys {
+            return Err(invalid_descriptor());
+        }
+        self.end(Multisig {
+            threshold,
+            keys,
+            sorted,
+        })
+    }
+
+    /// Parses a key, which is followed by either the closing `)` of the function or `,` of the next key.
+    fn key(&mut self, context: KeyContext) -> Result<DescriptorKey> {
+        let end = self.rest.find([',', ')']).ok_or_else(invalid_descriptor)?;
+        let (key, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        parse_key(key, context)
+    }
+}
+
+fn parse_key(s: &str, context: KeyContext) -> Result<DescriptorKey> {
+    let (origin, key) = match s.strip_prefix('[') {
+        Some(s) => {
+            let (origin, key) = s.split_once(']').ok_or_else(invalid_descriptor)?;
+            (Some(parse_origin(origin)?), key)
+        },
+        None => (None, s),
+    };
+
+    let mut steps = key.split('/');
+    let key = steps.next().ok_or_else(invalid_descriptor)?;
+    let steps: Vec<&str> = steps.collect();
+
+    let key = if key.starts_with("xpub") || key.starts_with("tpub") {
+        let xpub = ExtendedPubKey::from_str(key)
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_public_key))?;
+        let wildcard = steps.last() == Some(&WILDCARD);
+        let path_steps = if wildcard {
+            &steps[..steps.len() - 1]
+        } else {
+            &steps[..]
+        };
+        let path = path_steps
+            .iter()
+            .map(|step| parse_child_number(step))
+            .collect::<Result<Vec<_>>>()?;
+        // Hardened keys can't be derived from an extended public key.
+        if path.iter().any(ChildNumber::is_hardened) {
+            return Err(invalid_descriptor());
+        }
+        KeySource::Extended {
+            xpub,
+            path,
+            wildcard,
+        }
+    } else {
+        if !steps.is_empty() {
+            return Err(invalid_descriptor());
+        }
+        let bytes = hex::decode(key).map_err(|_| invalid_descriptor())?;
+        if context == KeyContext::Taproot && bytes.len() == 32 {
+            let xonly = XOnlyPublicKey::from_slice(&bytes)
+                .map_err(|_| Error::from(Proto::Error::Error_invalid_public_key))?;
+            KeySource::XOnly(xonly)
+        } else {
+            let public_key = PublicKey::from_slice(&bytes)?;
+            // Only compressed public keys are standard in SegWit scripts.
+            if context != KeyContext::Legacy && !public_key.compressed {
+                return Err(Error::from(Proto::Error::Error_invalid_public_key));
+            }
+            KeySource::Single(public_key)
+        }
+    };
+    Ok(DescriptorKey { origin, key })
+}
+
+/// Parses the key origin, e.g. `d34db33f/44'/0'/0'`.
+fn parse_origin(s: &str) -> Result<KeyOrigin> {
+    let mut steps = s.split('/');
+    let fingerprint = steps.next().ok_or_else(invalid_descriptor)?;
+    let fingerprint: [u8; 4] = hex::decode(fingerprint)
+        .ok()
+        .and_then(|fingerprint| fingerprint.try_into().ok())
+        .ok_or_else(invalid_descriptor)?;
+    let path = steps.map(parse_child_number).collect::<Result<Vec<_>>>()?;
+    Ok(KeyOrigin {
+        fingerprint: Fingerprint::from(fingerprint),
+        path,
+    })
+}
+
+/// Parses a derivation step, e.g. `0`, `44'` or `44h`.
+fn parse_child_number(s: &str) -> Result<ChildNumber> {
+    let (index, hardened) = match s.strip_suffix(HARDENED_MARKERS) {
+        Some(index) => (index, true),
+        None => (s, false),
+    };
+    // Leading signs are not allowed.
+    if !index.bytes().all(|c| c.is_ascii_digit()) {
+        return Err(invalid_descriptor());
+    }
+    let index: u32 = index.parse().map_err(|_| invalid_descriptor())?;
+    if hardened {
+        ChildNumber::from_hardened_idx(index)
+    } else {
+        ChildNumber::from_normal_idx(index)
+    }
+    .map_err(|_| invalid_descriptor())
+}
+
+fn wpubkey_hash(public_key: &PublicKey) -> Result<bitcoin::WPubkeyHash> {
+    public_key
+        .wpubkey_hash()
+        .ok_or_else(|| Error::from(Proto::Error::Error_invalid_public_key))
+}
+
+/// Returns the 8 character checksum of the descriptor.
+pub fn descriptor_checksum(descriptor: &str) -> Result<String> {
+    let mut symbols = Vec::with_capacity(descriptor.len() * 2);
+    let mut groups = Vec::with_capacity(3);
+    for c in descriptor.chars() {
+        let value = INPUT_CHARSET.find(c).ok_or_else(invalid_descriptor)? as u64;
+        symbols.push(value & 31);
+        groups.push(value >> 5);
+        if groups.len() == 3 {
+            symbols.push(groups[0] * 9 + groups[1] * 3 + groups[2]);
+            groups.clear();
+        }
+    }
+    match groups[..] {
+        [group] => symbols.push(group),
+        [first, second] => symbols.push(first * 3 + second),
+        _ => (),
+    }
+    symbols.extend([0; CHECKSUM_LEN]);
+
+    let checksum = checksum_polymod(&symbols) ^ 1;
+    Ok((0..CHECKSUM_LEN)
+        .map(|i| {
+            let value = (checksum >> (5 * (CHECKSUM_LEN - 1 - i))) & 31;
+            char::from(CHECKSUM_CHARSET[value as usize])
+        })
+        .collect())
+}
+
+fn checksum_polymod(symbols: &[u64]) -> u64 {
+    let mut checksum = 1;
+    for value in symbols {
+        let top = checksum >> 35;
+        checksum = ((checksum & 0x7_ffff_ffff) << 5) ^ value;
+        for (i, generator) in CHECKSUM_GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 != 0 {
+                checksum ^= generator;
+            }
+        }
+    }
+    checksum
+}
+
+fn invalid_descriptor() -> Error {
+    Error::from(Proto::Error::Error_invalid_descriptor)
+}
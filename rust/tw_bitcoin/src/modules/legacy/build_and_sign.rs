@@ -1,4 +1,5 @@
 use crate::aliases::*;
+use crate::modules::context::with_context;
 use crate::{Error, Result};
 use bitcoin::absolute::LockTime;
 use bitcoin::taproot::{LeafVersion, NodeInfo, TaprootSpendInfo};
@@ -49,7 +50,7 @@ pub fn taproot_build_and_sign_transaction(
         };
 
         let private_key = PrivateKey::from_slice(private_key, Network::Bitcoin)?;
-        let my_pubkey = private_key.public_key(&secp256k1::Secp256k1::new());
+        let my_pubkey = with_context(|secp| private_key.public_key(secp));
 
         let mut input = input_from_legacy_utxo(my_pubkey, utxo, legacy.hash_type)?;
         input.private_key = private_key.to_bytes().into();
@@ -90,6 +91,7 @@ pub fn taproot_build_and_sign_transaction(
         change_output: None,
         disable_change_output: true,
         dangerous_use_fixed_schnorr_rng: false,
+        ..Default::default()
     };
 
     // Build and sign the Bitcoin transaction.
@@ -202,6 +204,7 @@ fn input_from_legacy_utxo(
                         one_prevout: false,
                         payload: utxo.spendingScript.to_vec().into(),
                         control_block: control_block.serialize().into(),
+                        ..Default::default()
                     },
                 ),
             }
@@ -244,5 +247,7 @@ fn input_from_legacy_utxo(
         sequence_enable_zero: false,
         sighash_type,
         to_recipient: ProtoInputRecipient::builder(input_builder),
+        annex: Default::default(),
+        coin_control: Default::default(),
     })
 }
@@ -0,0 +1,1177 @@
+//! [Miniscript](https://bitcoin.sipa.be/miniscript/), the structured representation of Bitcoin scripts
+//! that can be analyzed, compiled from a spending policy and satisfied generically:
+//!
+//! ```rust,ignore
+//! let policy = Policy::from_str("or(pk(<alice>),and(pk(<bob>),older(144)))")?;
+//! let miniscript = policy.compile()?;
+//! let address = miniscript.address(Network::Bitcoin);
+//! // The smallest witness of the available signatures, preimages and timelocks.
+//! let witness = miniscript.satisfy(&Satisfier::new().signature(bob, signature).sequence(144))?;
+//! ```
+//!
+//! Only the P2WSH context is supported, with hex encoded compressed public keys,
+//! and the digests of the hash fragments are hex encoded in the script byte order.
+//! The policy compiler is a direct translation of the policy rather than the optimizing compiler of Bitcoin Core.
+
+use crate::modules::transactions::{absolute_lock_satisfied, relative_lock_satisfied};
+use crate::modules::ECDSA_SIGNATURE_LEN;
+use crate::{Error, Result};
+use bitcoin::blockdata::opcodes::all::{
+    OP_0NOTEQUAL, OP_ADD, OP_BOOLAND, OP_BOOLOR, OP_CHECKMULTISIG, OP_CHECKSIG, OP_CLTV, OP_CSV,
+    OP_DUP, OP_ELSE, OP_ENDIF, OP_EQUAL, OP_EQUALVERIFY, OP_FROMALTSTACK, OP_HASH160, OP_HASH256,
+    OP_IF, OP_IFDUP, OP_NOTIF, OP_RIPEMD160, OP_SHA256, OP_SIZE, OP_SWAP, OP_TOALTSTACK,
+};
+use bitcoin::blockdata::opcodes::All as Opcode;
+use bitcoin::blockdata::script::Builder;
+use bitcoin::hashes::{hash160, ripemd160, sha256, sha256d, Hash};
+use bitcoin::{Address, Network, PublicKey, ScriptBuf, Witness};
+use std::fmt;
+use std::str::FromStr;
+use tw_encoding::hex;
+use tw_proto::BitcoinV2::Proto;
+
+/// The maximum number of the keys of `multi`.
+const MAX_MULTI_KEYS: usize = 20;
+/// The maximum length of a standard P2WSH witness script.
+const MAX_SCRIPT_LEN: usize = 3600;
+/// The maximum nesting of the expressions, to limit the recursion of the parser.
+const MAX_DEPTH: usize = 128;
+/// The length of the preimages of the hash fragments.
+const PREIMAGE_LEN: usize = 32;
+/// The timelocks are limited to the positive script numbers of 4 bytes.
+const MAX_TIMELOCK: u32 = 0x8000_0000;
+
+/// A hash lock, i.e. the digest whose preimage of 32 bytes is required.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HashLock {
+    Sha256([u8; 32]),
+    Hash256([u8; 32]),
+    Ripemd160([u8; 20]),
+    Hash160([u8; 20]),
+}
+
+/// A fragment of a miniscript, named after its expression.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Fragment {
+    /// `0`
+    False,
+    /// `1`
+    True,
+    PkK(PublicKey),
+    PkH(PublicKey),
+    /// The relative timelock in the BIP68 encoding.
+    Older(u32),
+    /// The absolute timelock, either a block height or a timestamp.
+    After(u32),
+    Hash(HashLock),
+    AndOr(Box<Fragment>, Box<Fragment>, Box<Fragment>),
+    AndV(Box<Fragment>, Box<Fragment>),
+    AndB(Box<Fragment>, Box<Fragment>),
+    OrB(Box<Fragment>, Box<Fragment>),
+    OrC(Box<Fragment>, Box<Fragment>),
+    OrD(Box<Fragment>, Box<Fragment>),
+    OrI(Box<Fragment>, Box<Fragment>),
+    Thresh(usize, Vec<Fragment>),
+    Multi(usize, Vec<PublicKey>),
+    /// The `a:` wrapper.
+    Alt(Box<Fragment>),
+    /// The `s:` wrapper.
+    Swap(Box<Fragment>),
+    /// The `c:` wrapper.
+    Check(Box<Fragment>),
+    /// The `d:` wrapper.
+    DupIf(Box<Fragment>),
+    /// The `v:` wrapper.
+    Verify(Box<Fragment>),
+    /// The `j:` wrapper.
+    NonZero(Box<Fragment>),
+    /// The `n:` wrapper.
+    ZeroNotEqual(Box<Fragment>),
+}
+
+/// A miniscript of the P2WSH context, i.e. a well-typed fragment of the `B` type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Miniscript {
+    fragment: Fragment,
+    script: ScriptBuf,
+}
+
+/// A spending policy, to be compiled into a miniscript.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Policy {
+    Key(PublicKey),
+    After(u32),
+    Older(u32),
+    Hash(HashLock),
+    And(Box<Policy>, Box<Policy>),
+    Or(Box<Policy>, Box<Policy>),
+    Thresh(usize, Vec<Policy>),
+}
+
+/// The signatures, preimages and timelocks available to satisfy a miniscript.
+#[derive(Clone, Debug, Default)]
+pub struct Satisfier {
+    signatures: Vec<(PublicKey, Vec<u8>)>,
+    preimages: Vec<Vec<u8>>,
+    sequence: u32,
+    lock_time: u32,
+    /// Whether everything is assumed to be available, to estimate the length of the witness.
+    placeholder: bool,
+}
+
+impl Miniscript {
+    /// Creates the miniscript of the `fragment`, checking its type and the length of its script.
+    pub fn new(fragment: Fragment) -> Result<Self> {
+        if fragment.ty()?.base != Base::B {
+            return Err(invalid_miniscript());
+        }
+        let script = fragment.encode(Builder::new()).into_script();
+        if script.len() > MAX_SCRIPT_LEN {
+            return Err(invalid_miniscript());
+        }
+        Ok(Miniscript { fragment, script })
+    }
+
+    pub fn fragment(&self) -> &Fragment {
+        &self.fragment
+    }
+
+    /// Returns the witness script.
+    pub fn script(&self) -> &ScriptBuf {
+        &self.script
+    }
+
+    /// Returns the P2WSH script of the witness script.
+    pub fn script_pubkey(&self) -> ScriptBuf {
+        ScriptBuf::new_v0_p2wsh(&self.script.wscript_hash())
+    }
+
+    pub fn address(&self, network: Network) -> Address {
+        Address::p2wsh(&self.script, network)
+    }
+
+    /// Returns the smallest witness that satisfies the miniscript with the `satisfier`,
+    /// followed by the witness script.
+    pub fn satisfy(&self, satisfier: &Satisfier) -> Result<Witness> {
+        let stack = self
+            .fragment
+            .satisfactions(satisfier, Preference::Smallest)
+            .sat
+            .ok_or_else(|| Error::from(Proto::Error::Error_unsatisfiable_miniscript))?;
+        let mut witness = Witness::from_slice(&stack);
+        witness.push(self.script.as_bytes());
+        Ok(witness)
+    }
+
+    /// Returns the maximum length of the satisfying witness without the witness script,
+    /// including the lengths of its elements.
+    pub fn max_satisfaction_len(&self) -> u64 {
+        let satisfier = Satisfier {
+            placeholder: true,
+            ..Satisfier::default()
+        };
+        let stack = self
+            .fragment
+            .satisfactions(&satisfier, Preference::Largest)
+            .sat
+            .unwrap_or_default();
+        stack_len(&stack) as u64
+    }
+}
+
+impl FromStr for Miniscript {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let expression = Expression::parse(s)?;
+        Miniscript::new(Fragment::from_expression(&expression)?)
+    }
+}
+
+impl fmt::Display for Miniscript {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.fragment)
+    }
+}
+
+impl Policy {
+    /// Compiles the policy into a miniscript. The keys are checked with `pk()`,
+    /// the conjunctions are `and_v` and the disjunctions are `or_d` if either branch
+    /// is dissatisfiable, `or_i` otherwise. The thresholds of keys only are `multi`.
+    pub fn compile(&self) -> Result<Miniscript> {
+        Miniscript::new(self.fragment()?)
+    }
+
+    fn fragment(&self) -> Result<Fragment> {
+        let fragment = match self {
+            Policy::Key(key) => Fragment::Check(Box::new(Fragment::PkK(*key))),
+            Policy::After(n) => Fragment::After(*n),
+            Policy::Older(n) => Fragment::Older(*n),
+            Policy::Hash(hash) => Fragment::Hash(*hash),
+            Policy::And(x, y) => Fragment::AndV(
+                Box::new(Fragment::Verify(Box::new(x.fragment()?))),
+                Box::new(y.fragment()?),
+            ),
+            Policy::Or(x, y) => {
+                let (x, y) = (x.fragment()?, y.fragment()?);
+                let (x_ty, y_ty) = (x.ty()?, y.ty()?);
+                if x_ty.d && x_ty.u {
+                    Fragment::OrD(Box::new(x), Box::new(y))
+                } else if y_ty.d && y_ty.u {
+                    Fragment::OrD(Box::new(y), Box::new(x))
+                } else {
+                    Fragment::OrI(Box::new(x), Box::new(y))
+                }
+            },
+            Policy::Thresh(k, subs) => {
+                if *k == 0 || *k > subs.len() {
+                    return Err(invalid_miniscript());
+                }
+                let keys = subs
+                    .iter()
+                    .map(|sub| match sub {
+                        Policy::Key(key) => Some(*key),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<_>>>();
+                match keys {
+                    Some(keys) if keys.len() <= MAX_MULTI_KEYS => Fragment::Multi(*k, keys),
+                    _ if *k == subs.len() => Policy::fold(subs, Policy::And)?,
+                    _ if *k == 1 => Policy::fold(subs, Policy::Or)?,
+                    _ => {
+                        let mut fragments = Vec::with_capacity(subs.len());
+                        for sub in subs {
+                            let fragment = threshold_sub(sub.fragment()?)?;
+                            // All but the first are wrapped to take the sum from the stack.
+                            let fragment = if fragments.is_empty() {
+                                fragment
+                            } else if fragment.ty()?.o {
+                                Fragment::Swap(Box::new(fragment))
+                            } else {
+                                Fragment::Alt(Box::new(fragment))
+                            };
+                            fragments.push(fragment);
+                        }
+                        Fragment::Thresh(*k, fragments)
+                    },
+                }
+            },
+        };
+        Ok(fragment)
+    }
+
+    /// Returns the fragment of the `subs` combined pairwise with `combine`.
+    fn fold(subs: &[Policy], combine: fn(Box<Policy>, Box<Policy>) -> Policy) -> Result<Fragment> {
+        subs.iter()
+            .cloned()
+            .reduce(|x, y| combine(Box::new(x), Box::new(y)))
+            .ok_or_else(invalid_miniscript)?
+            .fragment()
+    }
+
+    fn from_expression(expression: &Expression<'_>) -> Result<Policy> {
+        let args = &expression.args;
+        let policy = match (expression.name, args.len()) {
+            ("pk", 1) => Policy::Key(parse_key(args[0].leaf()?)?),
+            ("after", 1) => Policy::After(parse_timelock(args[0].leaf()?)?),
+            ("older", 1) => Policy::Older(parse_timelock(args[0].leaf()?)?),
+            (name @ ("sha256" | "hash256" | "ripemd160" | "hash160"), 1) => {
+                Policy::Hash(HashLock::from_hex(name, args[0].leaf()?)?)
+            },
+            ("and", 2) => Policy::And(
+                Box::new(Policy::from_expression(&args[0])?),
+                Box::new(Policy::from_expression(&args[1])?),
+            ),
+            ("or", 2) => Policy::Or(
+                Box::new(Policy::from_expression(&args[0])?),
+                Box::new(Policy::from_expression(&args[1])?),
+            ),
+            ("thresh", 2..) => Policy::Thresh(
+                parse_number(args[0].leaf()?)?,
+                args[1..]
+                    .iter()
+                    .map(Policy::from_expression)
+                    .collect::<Result<Vec<_>>>()?,
+            ),
+            _ => return Err(invalid_miniscript()),
+        };
+        Ok(policy)
+    }
+}
+
+impl FromStr for Policy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Policy::from_expression(&Expression::parse(s)?)
+    }
+}
+
+/// Wraps the sub of `thresh`, so that it's dissatisfiable and leaves exactly `1` on the stack when satisfied.
+fn threshold_sub(fragment: Fragment) -> Result<Fragment> {
+    let mut fragment = fragment;
+    if !fragment.ty()?.d {
+        // `l:X`
+        fragment = Fragment::OrI(Box::new(Fragment::False), Box::new(fragment));
+    }
+    if !fragment.ty()?.u {
+        fragment = Fragment::ZeroNotEqual(Box::new(fragment));
+    }
+    Ok(fragment)
+}
+
+impl Satisfier {
+    pub fn new() -> Self {
+        Satisfier::default()
+    }
+
+    /// Adds the DER encoded ECDSA signature of the `public_key` with the sighash type appended.
+    pub fn signature(mut self, public_key: PublicKey, signature: Vec<u8>) -> Self {
+        self.signatures.push((public_key, signature));
+        self
+    }
+
+    /// Adds the preimage of a hash lock.
+    pub fn preimage(mut self, preimage: Vec<u8>) -> Self {
+        self.preimages.push(preimage);
+        self
+    }
+
+    /// Sets the sequence of the spending input, which satisfies the relative timelocks.
+    pub fn sequence(mut self, sequence: u32) -> Self {
+        self.sequence = sequence;
+        self
+    }
+
+    /// Sets the lock time of the spending transaction, which satisfies the absolute timelocks.
+    pub fn lock_time(mut self, lock_time: u32) -> Self {
+        self.lock_time = lock_time;
+        self
+    }
+
+    fn find_signature(&self, public_key: &PublicKey) -> Option<Vec<u8>> {
+        if self.placeholder {
+            return Some(vec![0; ECDSA_SIGNATURE_LEN as usize]);
+        }
+        self.signatures
+            .iter()
+            .find(|(signer, _)| signer == public_key)
+            .map(|(_, signature)| signature.clone())
+    }
+
+    fn find_preimage(&self, hash: &HashLock) -> Option<Vec<u8>> {
+        if self.placeholder {
+            return Some(vec![0; PREIMAGE_LEN]);
+        }
+        self.preimages
+            .iter()
+            .find(|preimage| hash.is_preimage(preimage))
+            .cloned()
+    }
+
+    fn is_older(&self, older: u32) -> bool {
+        self.placeholder || relative_lock_satisfied(self.sequence, older)
+    }
+
+    fn is_after(&self, after: u32) -> bool {
+        self.placeholder || absolute_lock_satisfied(self.lock_time, after)
+    }
+}
+
+impl HashLock {
+    fn from_hex(name: &str, digest: &str) -> Result<HashLock> {
+        let digest = hex::decode(digest).map_err(|_| invalid_miniscript())?;
+        match name {
+            "sha256" => digest.try_into().map(HashLock::Sha256),
+            "hash256" => digest.try_into().map(HashLock::Hash256),
+            "ripemd160" => digest.try_into().map(HashLock::Ripemd160),
+            "hash160" => digest.try_into().map(HashLock::Hash160),
+            _ => return Err(invalid_miniscript()),
+        }
+        .map_err(|_| invalid_miniscript())
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            HashLock::Sha256(_) => "sha256",
+            HashLock::Hash256(_) => "hash256",
+            HashLock::Ripemd160(_) => "ripemd160",
+            HashLock::Hash160(_) => "hash160",
+        }
+    }
+
+    fn digest(&self) -> &[u8] {
+        match self {
+            HashLock::Sha256(digest) | HashLock::Hash256(digest) => digest,
+            HashLock::Ripemd160(digest) | HashLock::Hash160(digest) => digest,
+        }
+    }
+
+    fn opcode(&self) -> Opcode {
+        match self {
+            HashLock::Sha256(_) => OP_SHA256,
+            HashLock::Hash256(_) => OP_HASH256,
+            HashLock::Ripemd160(_) => OP_RIPEMD160,
+            HashLock::Hash160(_) => OP_HASH160,
+        }
+    }
+
+    fn is_preimage(&self, preimage: &[u8]) -> bool {
+        preimage.len() == PREIMAGE_LEN
+            && match self {
+                HashLock::Sha256(digest) => sha256::Hash::hash(preimage).to_byte_array() == *digest,
+                HashLock::Hash256(digest) => {
+                    sha256d::Hash::hash(preimage).to_byte_array() == *digest
+                },
+                HashLock::Ripemd160(digest) => {
+                    ripemd160::Hash::hash(preimage).to_byte_array() == *digest
+                },
+                HashLock::Hash160(digest) => {
+                    hash160::Hash::hash(preimage).to_byte_array() == *digest
+                },
+            }
+    }
+}
+
+/// The basic type of a fragment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Base {
+    /// Pushes a nonzero value if satisfied, zero otherwise.
+    B,
+    /// Continues if satisfied, aborts otherwise.
+    V,
+    /// Pushes a key that is checked by a signature.
+    K,
+    /// Takes the top element of the stack, and pushes the result of `B` under it or over it.
+    W,
+}
+
+/// The type of a fragment, i.e. its basic type and properties as defined by the specification.
+#[derive(Clone, Copy, Debug)]
+struct Type {
+    base: Base,
+    /// Consumes no elements of the stack.
+    z: bool,
+    /// Consumes exactly one element of the stack.
+    o: bool,
+    /// Requires the top element of the stack to be nonzero when satisfied.
+    n: bool,
+    /// Has a dissatisfaction.
+    d: bool,
+    /// Pushes exactly `1` when satisfied.
+    u: bool,
+}
+
+impl Type {
+    /// Returns the type with the properties named by their letters.
+    fn new(base: Base, properties: &str) -> Type {
+        Type {
+            base,
+            z: properties.contains('z'),
+            o: properties.contains('o'),
+            n: properties.contains('n'),
+            d: properties.contains('d'),
+            u: properties.contains('u'),
+        }
+    }
+}
+
+impl Fragment {
+    /// Returns the type of the fragment, checking that it's well-formed.
+    fn ty(&self) -> Result<Type> {
+        let ty = match self {
+            Fragment::False => Type::new(Base::B, "zud"),
+            Fragment::True => Type::new(Base::B, "zu"),
+            Fragment::PkK(key) => {
+                check(key.compressed)?;
+                Type::new(Base::K, "ondu")
+            },
+            Fragment::PkH(key) => {
+                check(key.compressed)?;
+                Type::new(Base::K, "ndu")
+            },
+            Fragment::Older(n) | Fragment::After(n) => {
+                check(*n > 0 && *n < MAX_TIMELOCK)?;
+                Type::new(Base::B, "z")
+            },
+            Fragment::Hash(_) => Type::new(Base::B, "ondu"),
+            Fragment::Multi(k, keys) => {
+                check(*k > 0 && *k <= keys.len() && keys.len() <= MAX_MULTI_KEYS)?;
+                check(keys.iter().all(|key| key.compressed))?;
+                Type::new(Base::B, "ndu")
+            },
+            Fragment::AndOr(x, y, z) => {
+                let (x, y, z) = (x.ty()?, y.ty()?, z.ty()?);
+                check(x.base == Base::B && x.d && x.u)?;
+                check(y.base == z.base && y.base != Base::W)?;
+                Type {
+                    base: y.base,
+                    z: x.z && y.z && z.z,
+                    o: (x.z && y.o && z.o) || (x.o && y.z && z.z),
+                    n: false,
+                    d: z.d,
+                    u: y.u && z.u,
+                }
+            },
+            Fragment::AndV(x, y) => {
+                let (x, y) = (x.ty()?, y.ty()?);
+                check(x.base == Base::V && y.base != Base::W)?;
+                Type {
+                    base: y.base,
+                    z: x.z && y.z,
+                    o: (x.z && y.o) || (x.o && y.z),
+                    n: x.n || (x.z && y.n),
+                    d: false,
+                    u: y.u,
+                }
+            },
+            Fragment::AndB(x, y) => {
+                let (x, y) = (x.ty()?, y.ty()?);
+                check(x.base == Base::B && y.base == Base::W)?;
+                Type {
+                    base: Base::B,
+                    z: x.z && y.z,
+                    o: (x.z && y.o) || (x.o && y.z),
+                    n: x.n || (x.z && y.n),
+                    d: x.d && y.d,
+                    u: true,
+                }
+            },
+            Fragment::OrB(x, z) => {
+                let (x, z) = (x.ty()?, z.ty()?);
+                check(x.base == Base::B && x.d && z.base == Base::W && z.d)?;
+                Type {
+                    base: Base::B,
+                    z: x.z && z.z,
+                    o: (x.z && z.o) || (x.o && z.z),
+                    n: false,
+                    d: true,
+                    u: true,
+                }
+            },
+            Fragment::OrC(x, z) => {
+                let (x, z) = (x.ty()?, z.ty()?);
+                check(x.base == Base::B && x.d && x.u && z.base == Base::V)?;
+                Type {
+                    base: Base::V,
+                    z: x.z && z.z,
+                    o: x.o && z.z,
+                    n: false,
+                    d: false,
+                    u: false,
+                }
+            },
+            Fragment::OrD(x, z) => {
+                let (x, z) = (x.ty()?, z.ty()?);
+                check(x.base == Base::B && x.d && x.u && z.base == Base::B)?;
+                Type {
+                    base: Base::B,
+                    z: x.z && z.z,
+                    o: x.o && z.z,
+                    n: false,
+                    d: z.d,
+                    u: z.u,
+                }
+            },
+            Fragment::OrI(x, z) => {
+                let (x, z) = (x.ty()?, z.ty()?);
+                check(x.base == z.base && x.base != Base::W)?;
+                Type {
+                    base: x.base,
+                    z: false,
+                    o: x.z && z.z,
+                    n: false,
+                    d: x.d || z.d,
+                    u: x.u && z.u,
+                }
+            },
+            Fragment::Thresh(k, subs) => {
+                check(*k > 0 && *k <= subs.len())?;
+                let mut zero_count = 0;
+                let mut one_count = 0;
+                for (index, sub) in subs.iter().enumerate() {
+                    let sub = sub.ty()?;
+                    let base = if index == 0 { Base::B } else { Base::W };
+                    check(sub.base == base && sub.d && sub.u)?;
+                    zero_count += sub.z as usize;
+                    one_count += sub.o as usize;
+                }
+                Type {
+                    base: Base::B,
+                    z: zero_count == subs.len(),
+                    o: zero_count + 1 == subs.len() && one_count == 1,
+                    n: false,
+                    d: true,
+                    u: true,
+                }
+            },
+            Fragment::Alt(x) => {
+                let x = x.ty()?;
+                check(x.base == Base::B)?;
+                Type {
+                    base: Base::W,
+                    z: false,
+                    o: false,
+                    n: false,
+                    d: x.d,
+                    u: x.u,
+                }
+            },
+            Fragment::Swap(x) => {
+                let x = x.ty()?;
+                check(x.base == Base::B && x.o)?;
+                Type {
+                    base: Base::W,
+                    z: false,
+                    o: false,
+                    n: false,
+                    d: x.d,
+                    u: x.u,
+                }
+            },
+            Fragment::Check(x) => {
+                let x = x.ty()?;
+                check(x.base == Base::K)?;
+                Type {
+                    base: Base::B,
+                    z: false,
+                    o: x.o,
+                    n: x.n,
+                    d: x.d,
+                    u: true,
+                }
+            },
+            Fragment::DupIf(x) => {
+                let x = x.ty()?;
+                check(x.base == Base::V && x.z)?;
+                // `u` only holds with `MINIMALIF`, which is a consensus rule of Tapscript only.
+                Type::new(Base::B, "ond")
+            },
+            Fragment::Verify(x) => {
+                let x = x.ty()?;
+                check(x.base == Base::B)?;
+                Type {
+                    base: Base::V,
+                    z: x.z,
+                    o: x.o,
+                    n: x.n,
+                    d: false,
+                    u: false,
+                }
+            },
+            Fragment::NonZero(x) => {
+                let x = x.ty()?;
+                check(x.base == Base::B && x.n)?;
+                Type {
+                    base: Base::B,
+                    z: false,
+                    o: x.o,
+                    n: true,
+                    d: true,
+                    u: x.u,
+                }
+            },
+            Fragment::ZeroNotEqual(x) => {
+                let x = x.ty()?;
+                check(x.base == Base::B)?;
+                Type {
+                    base: Base::B,
+                    z: x.z,
+                    o: x.o,
+                    n: x.n,
+                    d: x.d,
+                    u: true,
+                }
+            },
+        };
+        Ok(ty)
+    }
+
+    fn encode(&self, builder: Builder) -> Builder {
+        match self {
+            Fragment::False => builder.push_int(0),
+            Fragment::True => builder.push_int(1),
+            Fragment::PkK(key) => builder.push_key(key),
+            Fragment::PkH(key) => builder
+                .push_opcode(OP_DUP)
+                .push_opcode(OP_HASH160)
+                .push_slice(key.pubkey_hash().to_byte_array())
+                .push_opcode(OP_EQUALVERIFY),
+            Fragment::Older(n) => builder.push_int(*n as i64).push_opcode(OP_CSV),
+            Fragment::After(n) => builder.push_int(*n as i64).push_opcode(OP_CLTV),
+            Fragment::Hash(hash) => {
+                let builder = builder
+                    .push_opcode(OP_SIZE)
+                    .push_int(PREIMAGE_LEN as i64)
+                    .push_opcode(OP_EQUALVERIFY)
+                    .push_opcode(hash.opcode());
+                match hash {
+                    HashLock::Sha256(digest) | HashLock::Hash256(digest) => {
+                        builder.push_slice(digest)
+                    },
+                    HashLock::Ripemd160(digest) | HashLock::Hash160(digest) => {
+                        builder.push_slice(digest)
+                    },
+                }
+                .push_opcode(OP_EQUAL)
+            },
+            Fragment::AndOr(x, y, z) => {
+                let builder = x.encode(builder).push_opcode(OP_NOTIF);
+                let builder = z.encode(builder).push_opcode(OP_ELSE);
+                y.encode(builder).push_opcode(OP_ENDIF)
+            },
+            Fragment::AndV(x, y) => y.encode(x.encode(builder)),
+            Fragment::AndB(x, y) => y.encode(x.encode(builder)).push_opcode(OP_BOOLAND),
+            Fragment::OrB(x, z) => z.encode(x.encode(builder)).push_opcode(OP_BOOLOR),
+            Fragment::OrC(x, z) => z
+                .encode(x.encode(builder).push_opcode(OP_NOTIF))
+                .push_opcode(OP_ENDIF),
+            Fragment::OrD(x, z) => z
+                .encode(
+                    x.encode(builder)
+                        .push_opcode(OP_IFDUP)
+                        .push_opcode(OP_NOTIF),
+                )
+                .push_opcode(OP_ENDIF),
+            Fragment::OrI(x, z) => z
+                .encode(x.encode(builder.push_opcode(OP_IF)).push_opcode(OP_ELSE))
+                .push_opcode(OP_ENDIF),
+            Fragment::Thresh(k, subs) => {
+                let mut builder = subs[0].encode(builder);
+                for sub in &subs[1..] {
+                    builder = sub.encode(builder).push_opcode(OP_ADD);
+                }
+                builder.push_int(*k as i64).push_opcode(OP_EQUAL)
+            },
+            Fragment::Multi(k, keys) => keys
+                .iter()
+                .fold(builder.push_int(*k as i64), |builder, key| {
+                    builder.push_key(key)
+                })
+                .push_int(keys.len() as i64)
+                .push_opcode(OP_CHECKMULTISIG),
+            Fragment::Alt(x) => x
+                .encode(builder.push_opcode(OP_TOALTSTACK))
+                .push_opcode(OP_FROMALTSTACK),
+            Fragment::Swap(x) => x.encode(builder.push_opcode(OP_SWAP)),
+            Fragment::Check(x) => x.encode(builder).push_opcode(OP_CHECKSIG),
+            Fragment::DupIf(x) => x
+                .encode(builder.push_opcode(OP_DUP).push_opcode(OP_IF))
+                .push_opcode(OP_ENDIF),
+            // `OP_VERIFY`, or the `VERIFY` form of the last opcode.
+            Fragment::Verify(x) => x.encode(builder).push_verify(),
+            Fragment::NonZero(x) => x
+                .encode(
+                    builder
+                        .push_opcode(OP_SIZE)
+                        .push_opcode(OP_0NOTEQUAL)
+                        .push_opcode(OP_IF),
+                )
+                .push_opcode(OP_ENDIF),
+            Fragment::ZeroNotEqual(x) => x.encode(builder).push_opcode(OP_0NOTEQUAL),
+        }
+    }
+
+    /// Returns the satisfaction and the dissatisfaction of the fragment if any,
+    /// as the witness elements from the bottom of the stack to the top.
+    fn satisfactions(&self, satisfier: &Satisfier, preference: Preference) -> Satisfactions {
+        let empty = || Some(vec![Vec::new()]);
+        let one = || Some(vec![vec![1]]);
+
+        let (sat, dissat) = match self {
+            Fragment::False => (None, Some(Vec::new())),
+            Fragment::True => (Some(Vec::new()), None),
+            Fragment::PkK(key) => (
+                satisfier
+                    .find_signature(key)
+                    .map(|signature| vec![signature]),
+                empty(),
+            ),
+            Fragment::PkH(key) => (
+                satisfier
+                    .find_signature(key)
+                    .map(|signature| vec![signature, key.to_bytes()]),
+                Some(vec![Vec::new(), key.to_bytes()]),
+            ),
+            Fragment::Older(n) => (satisfier.is_older(*n).then(Vec::new), None),
+            Fragment::After(n) => (satisfier.is_after(*n).then(Vec::new), None),
+            // Any other value of the same length dissatisfies the hash lock.
+            Fragment::Hash(hash) => (
+                satisfier.find_preimage(hash).map(|preimage| vec![preimage]),
+                Some(vec![vec![0; PREIMAGE_LEN]]),
+            ),
+            Fragment::AndOr(x, y, z) => {
+                let x = x.satisfactions(satisfier, preference);
+                let y = y.satisfactions(satisfier, preference);
+                let z = z.satisfactions(satisfier, preference);
+                (
+                    preference.pick(join(y.sat, x.sat), join(z.sat, x.dissat.clone())),
+                    join(z.dissat, x.dissat),
+                )
+            },
+            Fragment::AndV(x, y) => {
+                let x = x.satisfactions(satisfier, preference);
+                let y = y.satisfactions(satisfier, preference);
+                (join(y.sat, x.sat), None)
+            },
+            Fragment::AndB(x, y) => {
+                let x = x.satisfactions(satisfier, preference);
+                let y = y.satisfactions(satisfier, preference);
+                (join(y.sat, x.sat), join(y.dissat, x.dissat))
+            },
+            Fragment::OrB(x, z) => {
+                let x = x.satisfactions(satisfier, preference);
+                let z = z.satisfactions(satisfier, preference);
+                (
+                    preference.pick(join(z.dissat.clone(), x.sat), join(z.sat, x.dissat.clone())),
+                    join(z.dissat, x.dissat),
+                )
+            },
+            Fragment::OrC(x, z) => {
+                let x = x.satisfactions(satisfier, preference);
+                let z = z.satisfactions(satisfier, preference);
+                (preference.pick(x.sat, join(z.sat, x.dissat)), None)
+            },
+            Fragment::OrD(x, z) => {
+                let x = x.satisfactions(satisfier, preference);
+                let z = z.satisfactions(satisfier, preference);
+                (
+                    preference.pick(x.sat, join(z.sat, x.dissat.clone())),
+                    join(z.dissat, x.dissat),
+                )
+            },
+            // The top element selects the branch.
+            Fragment::OrI(x, z) => {
+                let x = x.satisfactions(satisfier, preference);
+                let z = z.satisfactions(satisfier, preference);
+                (
+                    preference.pick(join(x.sat, one()), join(z.sat, empty())),
+                    preference.pick(join(x.dissat, one()), join(z.dissat, empty())),
+                )
+            },
+            Fragment::Thresh(k, subs) => {
+                // The witness of the subs so far with exactly `count` of them satisfied, at `count`.
+                let mut witnesses: Vec<Option<Stack>> = vec![Some(Vec::new())];
+                for sub in subs {
+                    let sub = sub.satisfactions(satisfier, preference);
+                    let mut next = Vec::with_capacity(witnesses.len() + 1);
+                    for count in 0..=witnesses.len() {
+                        // The later subs are executed later, so their elements are deeper in the stack.
+                        let dissatisfied = match witnesses.get(count) {
+                            Some(witness) => join(sub.dissat.clone(), witness.clone()),
+                            None => None,
+                        };
+                        let satisfied = match count.checked_sub(1) {
+                            Some(previous) => join(sub.sat.clone(), witnesses[previous].clone()),
+                            None => None,
+                        };
+                        next.push(preference.pick(dissatisfied, satisfied));
+                    }
+                    witnesses = next;
+                }
+                (witnesses[*k].clone(), witnesses[0].clone())
+            },
+            // The dummy element followed by the signatures in the order of the keys.
+            Fragment::Multi(k, keys) => {
+                let signatures: Vec<Vec<u8>> = keys
+                    .iter()
+                    .filter_map(|key| satisfier.find_signature(key))
+                    .take(*k)
+                    .collect();
+                let sat: Option<Stack> = (signatures.len() == *k)
+                    .then(|| std::iter::once(Vec::new()).chain(signatures).collect());
+                (sat, Some(vec![Vec::new(); k + 1]))
+            },
+            Fragment::Alt(x)
+            | Fragment::Swap(x)
+            | Fragment::Check(x)
+            | Fragment::ZeroNotEqual(x) => {
+                let x = x.satisfactions(satisfier, preference);
+                (x.sat, x.dissat)
+            },
+            Fragment::DupIf(x) => {
+                let x = x.satisfactions(satisfier, preference);
+                (join(x.sat, one()), empty())
+            },
+            Fragment::Verify(x) => (x.satisfactions(satisfier, preference).sat, None),
+            Fragment::NonZero(x) => (x.satisfactions(satisfier, preference).sat, empty()),
+        };
+        Satisfactions { sat, dissat }
+    }
+
+    fn from_expression(expression: &Expression<'_>) -> Result<Fragment> {
+        let (wrappers, name) = match expression.name.split_once(':') {
+            Some(("", _)) => return Err(invalid_miniscript()),
+            Some((wrappers, name)) => (wrappers, name),
+            None => ("", expression.name),
+        };
+
+        let args = &expression.args;
+        let sub = |index: usize| Fragment::from_expression(&args[index]).map(Box::new);
+        let mut fragment = match (name, args.len()) {
+            ("0", 0) => Fragment::False,
+            ("1", 0) => Fragment::True,
+            ("pk_k", 1) => Fragment::PkK(parse_key(args[0].leaf()?)?),
+            ("pk_h", 1) => Fragment::PkH(parse_key(args[0].leaf()?)?),
+            ("pk", 1) => Fragment::Check(Box::new(Fragment::PkK(parse_key(args[0].leaf()?)?))),
+            ("pkh", 1) => Fragment::Check(Box::new(Fragment::PkH(parse_key(args[0].leaf()?)?))),
+            ("older", 1) => Fragment::Older(parse_timelock(args[0].leaf()?)?),
+            ("after", 1) => Fragment::After(parse_timelock(args[0].leaf()?)?),
+            (name @ ("sha256" | "hash256" | "ripemd160" | "hash160"), 1) => {
+                Fragment::Hash(HashLock::from_hex(name, args[0].leaf()?)?)
+            },
+            ("andor", 3) => Fragment::AndOr(sub(0)?, sub(1)?, sub(2)?),
+            ("and_n", 2) => Fragment::AndOr(sub(0)?, sub(1)?, Box::new(Fragment::False)),
+            ("and_v", 2) => Fragment::AndV(sub(0)?, sub(1)?),
+            ("and_b", 2) => Fragment::AndB(sub(0)?, sub(1)?),
+            ("or_b", 2) => Fragment::OrB(sub(0)?, sub(1)?),
+            ("or_c", 2) => Fragment::OrC(sub(0)?, sub(1)?),
+            ("or_d", 2) => Fragment::OrD(sub(0)?, sub(1)?),
+            ("or_i", 2) => Fragment::OrI(sub(0)?, sub(1)?),
+            ("thresh", 2..) => Fragment::Thresh(
+                parse_number(args[0].leaf()?)?,
+                args[1..]
+                    .iter()
+                    .map(Fragment::from_expression)
+                    .collect::<Result<Vec<_>>>()?,
+            ),
+            ("multi", 2..) => Fragment::Multi(
+                parse_number(args[0].leaf()?)?,
+                args[1..]
+                    .iter()
+                    .map(|arg| parse_key(arg.leaf()?))
+                    .collect::<Result<Vec<_>>>()?,
+            ),
+            _ => return Err(invalid_miniscript()),
+        };
+
+        // The innermost wrapper is the last one.
+        for wrapper in wrappers.chars().rev() {
+            let inner = Box::new(fragment);
+            fragment = match wrapper {
+                'a' => Fragment::Alt(inner),
+                's' => Fragment::Swap(inner),
+                'c' => Fragment::Check(inner),
+                'd' => Fragment::DupIf(inner),
+                'v' => Fragment::Verify(inner),
+                'j' => Fragment::NonZero(inner),
+                'n' => Fragment::ZeroNotEqual(inner),
+                't' => Fragment::AndV(inner, Box::new(Fragment::True)),
+                'l' => Fragment::OrI(Box::new(Fragment::False), inner),
+                'u' => Fragment::OrI(inner, Box::new(Fragment::False)),
+                _ => return Err(invalid_miniscript()),
+            };
+        }
+        Ok(fragment)
+    }
+
+    /// Returns the letter of the wrapper and the wrapped fragment, if the fragment is written as a wrapper.
+    fn wrapper(&self) -> Option<(char, &Fragment)> {
+        match self {
+            Fragment::Alt(x) => Some(('a', x)),
+            Fragment::Swap(x) => Some(('s', x)),
+            // `pk()` and `pkh()` are written as such.
+            Fragment::Check(x) if !matches!(**x, Fragment::PkK(_) | Fragment::PkH(_)) => {
+                Some(('c', x))
+            },
+            Fragment::DupIf(x) => Some(('d', x)),
+            Fragment::Verify(x) => Some(('v', x)),
+            Fragment::NonZero(x) => Some(('j', x)),
+            Fragment::ZeroNotEqual(x) => Some(('n', x)),
+            Fragment::AndV(x, y) if **y == Fragment::True => Some(('t', x)),
+            Fragment::OrI(x, z) if **x == Fragment::False => Some(('l', z)),
+            Fragment::OrI(x, z) if **z == Fragment::False => Some(('u', x)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Fragment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut wrappers = String::new();
+        let mut fragment = self;
+        while let Some((wrapper, inner)) = fragment.wrapper() {
+            wrappers.push(wrapper);
+            fragment = inner;
+        }
+        if !wrappers.is_empty() {
+            write!(f, "{wrappers}:")?;
+        }
+
+        match fragment {
+            Fragment::False => write!(f, "0"),
+            Fragment::True => write!(f, "1"),
+            Fragment::PkK(key) => write!(f, "pk_k({key})"),
+            Fragment::PkH(key) => write!(f, "pk_h({key})"),
+            Fragment::Older(n) => write!(f, "older({n})"),
+            Fragment::After(n) => write!(f, "after({n})"),
+            Fragment::Hash(hash) => {
+                write!(f, "{}({})", hash.name(), hex::encode(hash.digest(), false))
+            },
+            Fragment::AndOr(x, y, z) => write!(f, "andor({x},{y},{z})"),
+            Fragment::AndV(x, y) => write!(f, "and_v({x},{y})"),
+            Fragment::AndB(x, y) => write!(f, "and_b({x},{y})"),
+            Fragment::OrB(x, z) => write!(f, "or_b({x},{z})"),
+            Fragment::OrC(x, z) => write!(f, "or_c({x},{z})"),
+            Fragment::OrD(x, z) => write!(f, "or_d({x},{z})"),
+            Fragment::OrI(x, z) => write!(f, "or_i({x},{z})"),
+            Fragment::Thresh(k, subs) => {
+                write!(f, "thresh({k}")?;
+                for sub in subs {
+                    write!(f, ",{sub}")?;
+                }
+                write!(f, ")")
+            },
+            Fragment::Multi(k, keys) => {
+                write!(f, "multi({k}")?;
+                for key in keys {
+                    write!(f, ",{key}")?;
+                }
+                write!(f, ")")
+            },
+            Fragment::Check(x) => match x.as_ref() {
+                Fragment::PkK(key) => write!(f, "pk({key})"),
+                Fragment::PkH(key) => write!(f, "pkh({key})"),
+                _ => unreachable!("The other checks are written as wrappers"),
+            },
+            _ => unreachable!("The wrappers are written as prefixes"),
+        }
+    }
+}
+
+/// The witness elements from the bottom of the stack to the top.
+type Stack = Vec<Vec<u8>>;
+
+struct Satisfactions {
+    sat: Option<Stack>,
+    dissat: Option<Stack>,
+}
+
+/// Which of the alternative witnesses is picked.
+#[derive(Clone, Copy)]
+enum Preference {
+    Smallest,
+    /// To estimate the maximum length of the witness.
+    Largest,
+}
+
+impl Preference {
+    fn pick(self, a: Option<Stack>, b: Option<Stack>) -> Option<Stack> {
+        match (a, b) {
+            (Some(a), Some(b)) => {
+                let a_is_smaller = stack_len(&a) <= stack_len(&b);
+                match (self, a_is_smaller) {
+                    (Preference::Smallest, true) | (Preference::Largest, false) => Some(a),
+                    _ => Some(b),
+                }
+            },
+            (a, None) => a,
+            (None, b) => b,
+        }
+    }
+}
+
+/// Returns the `bottom` elements followed by the `top` ones, if both exist.
+fn join(bottom: Option<Stack>, top: Option<Stack>) -> Option<Stack> {
+    let mut stack = bottom?;
+    stack.extend(top?);
+    Some(stack)
+}
+
+/// Returns the serialized length of the elements, including their lengths.
+fn stack_len(stack: &Stack) -> usize {
+    stack
+        .iter()
+        .map(|element| match element.len() {
+            len if len < 0xfd => 1 + len,
+            len => 3 + len,
+        })
+        .sum()
+}
+
+/// A parsed `name(args...)` expression, where a leaf has no arguments.
+#[derive(Debug)]
+struct Expression<'a> {
+    name: &'a str,
+    args: Vec<Expression<'a>>,
+}
+
+impl<'a> Expression<'a> {
+    fn parse(s: &'a str) -> Result<Expression<'a>> {
+        let (expression, rest) = Expression::parse_prefix(s, 0)?;
+        if !rest.is_empty() {
+            return Err(invalid_miniscript());
+        }
+        Ok(expression)
+    }
+
+    /// Parses the expression at the start of `s`, and returns it along with the rest of `s`.
+    fn parse_prefix(s: &'a str, depth: usize) -> Result<(Expression<'a>, &'a str)> {
+        if depth > MAX_DEPTH {
+            return Err(invalid_miniscript());
+        }
+        let end = s.find(['(', ',', ')']).unwrap_or(s.len());
+        let (name, mut rest) = s.split_at(end);
+
+        let mut args = Vec::new();
+        if let Some(mut inner) = rest.strip_prefix('(') {
+            loop {
+                let (arg, after) = Expression::parse_prefix(inner, depth + 1)?;
+                args.push(arg);
+                match after.strip_prefix(',') {
+                    Some(after) => inner = after,
+                    None => {
+                        rest = after.strip_prefix(')').ok_or_else(invalid_miniscript)?;
+                        break;
+                    },
+                }
+            }
+        }
+        Ok((Expression { name, args }, rest))
+    }
+
+    /// Returns the name of a leaf, e.g. a key or a number.
+    fn leaf(&self) -> Result<&'a str> {
+        if !self.args.is_empty() {
+            return Err(invalid_miniscript());
+        }
+        Ok(self.name)
+    }
+}
+
+/// Parses a hex encoded compressed public key.
+fn parse_key(s: &str) -> Result<PublicKey> {
+    let bytes = hex::decode(s).map_err(|_| invalid_miniscript())?;
+    let public_key = PublicKey::from_slice(&bytes)?;
+    if !public_key.compressed {
+        return Err(Error::from(Proto::Error::Error_invalid_public_key));
+    }
+    Ok(public_key)
+}
+
+fn parse_number(s: &str) -> Result<usize> {
+    // Leading signs are not allowed.
+    if !s.bytes().all(|c| c.is_ascii_digit()) {
+        return Err(invalid_miniscript());
+    }
+    s.parse().map_err(|_| invalid_miniscript())
+}
+
+fn parse_timelock(s: &str) -> Result<u32> {
+    let n = u32::try_from(parse_number(s)?).map_err(|_| invalid_miniscript())?;
+    if n == 0 || n >= MAX_TIMELOCK {
+        return Err(invalid_miniscript());
+    }
+    Ok(n)
+}
+
+fn check(condition: bool) -> Result<()> {
+    if !condition {
+        return Err(invalid_miniscript());
+    }
+    Ok(())
+}
+
+fn invalid_miniscript() -> Error {
+    Error::from(Proto::Error::Error_invalid_miniscript)
+}
@@ -1,3 +1,20 @@
+pub mod bip322;
+pub mod bitcoin_cash;
+pub mod bolt11;
+pub mod context;
+pub mod decred;
+pub mod descriptor;
 pub mod legacy;
+pub mod miniscript;
+pub mod network;
+pub mod psbt;
+pub mod runes;
+pub mod signed_message;
 pub mod signer;
+pub mod silent_payments;
+pub mod transaction_decoder;
 pub mod transactions;
+pub mod zcash;
+
+/// The maximum length of a DER encoded low-S ECDSA signature with the sighash type.
+pub(crate) const ECDSA_SIGNATURE_LEN: u64 = 72;
@@ -0,0 +1,52 @@
+//! The networks that the addresses are encoded for, e.g. to sign the transactions
+//! of integration tests on the regtest or the signet without a patched build:
+//!
+//! ```rust,ignore
+//! let signing = Proto::SigningInput {
+//!     network: Proto::Network::Regtest,
+//!     outputs: vec![output_to("bcrt1q...")],
+//!     ..Default::default()
+//! };
+//! ```
+//!
+//! The testnet and the signet share the same addresses.
+
+use bitcoin::Network;
+use tw_coin_entry::error::prelude::*;
+use tw_coin_entry::prefix::AddressPrefix;
+use tw_proto::BitcoinV2::Proto;
+
+/// The bech32 prefixes of the segwit addresses.
+const MAINNET_HRP: &str = "bc";
+const TESTNET_HRP: &str = "tb";
+const REGTEST_HRP: &str = "bcrt";
+
+pub fn network_from_proto(network: Proto::Network) -> Network {
+    match network {
+        Proto::Network::Mainnet => Network::Bitcoin,
+        Proto::Network::Testnet => Network::Testnet,
+        Proto::Network::Signet => Network::Signet,
+        Proto::Network::Regtest => Network::Regtest,
+    }
+}
+
+/// The network of an address, specified by the bech32 prefix of its segwit addresses,
+/// e.g. `bcrt` for the regtest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NetworkPrefix(pub Network);
+
+impl TryFrom<AddressPrefix> for NetworkPrefix {
+    type Error = AddressError;
+
+    fn try_from(prefix: AddressPrefix) -> Result<Self, Self::Error> {
+        match prefix {
+            AddressPrefix::Hrp(hrp) => match hrp.as_str() {
+                MAINNET_HRP => Ok(NetworkPrefix(Network::Bitcoin)),
+                // The signet addresses are the same.
+                TESTNET_HRP => Ok(NetworkPrefix(Network::Testnet)),
+                REGTEST_HRP => Ok(NetworkPrefix(Network::Regtest)),
+                _ => Err(AddressError::UnexpectedAddressPrefix),
+            },
+        }
+    }
+}
@@ -0,0 +1,602 @@
+//! Partially signed transactions as specified in [BIP174](https://github.com/bitcoin/bips/blob/master/bip-0174.mediawiki),
+//! so the wallet can co-sign the transactions of hardware wallets and multisig coordinators:
+//!
+//! ```rust,ignore
+//! let mut psbt = Psbt::from_base64(PSBT)?;
+//! let signed_inputs = psbt.sign(PRIVATE_KEY)?;
+//! psbt.finalize()?;
+//! let tx = psbt.extract_tx()?;
+//! ```
+//!
+//! An input is signed if it spends a P2PKH, P2WPKH, P2SH-P2WPKH or P2TR key path output of the key,
+//! or a P2SH, P2WSH or P2SH-P2WSH script that contains the key.
+//! Inputs are finalized if they spend a single key output, a `multi` script or a P2A anchor, which is never signed.
+//!
+//! The PSBTs of a transaction that several devices sign separately are merged with [`Psbt::combine`].
+//!
+//! [`PsbtV2`] supports the version 2 documents of [BIP370](https://github.com/bitcoin/bips/blob/master/bip-0370.mediawiki).
+
+use crate::modules::transactions::is_p2a;
+use crate::{Error, Result};
+use bitcoin::blockdata::opcodes::all::{OP_CHECKMULTISIG, OP_PUSHNUM_1, OP_PUSHNUM_16};
+use bitcoin::blockdata::script::{Builder, Instruction, PushBytesBuf};
+use bitcoin::hashes::Hash;
+use bitcoin::psbt::{Input, Output, PartiallySignedTransaction};
+use bitcoin::sighash::{EcdsaSighashType, Prevouts, SighashCache, TapSighashType};
+use bitcoin::taproot::TapNodeHash;
+use bitcoin::{PublicKey, Script, ScriptBuf, Transaction, TxOut, Witness};
+use secp256k1::{Message, Secp256k1, SecretKey, Verification};
+use std::collections::BTreeMap;
+use tw_encoding::base64;
+use tw_hash::H256;
+use tw_keypair::schnorr::{self, TaprootSpendPath};
+use tw_proto::BitcoinV2::Proto;
+
+mod v2;
+
+// Re-exports
+pub use v2::{PsbtV2, RequiredLockTime};
+
+/// How an input that the key controls is signed.
+enum SpendingPath {
+    /// ECDSA over the legacy sighash of the script code.
+    Legacy { script_code: ScriptBuf },
+    /// ECDSA over the BIP143 sighash of the script code.
+    Segwit { script_code: ScriptBuf },
+    /// Schnorr with the key tweaked with the merkle root.
+    TaprootKeyPath { merkle_root: Option<TapNodeHash> },
+}
+
+/// A BIP174 partially signed transaction, version 0.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Psbt {
+    inner: PartiallySignedTransaction,
+}
+
+impl Psbt {
+    /// Creates a PSBT of the given transaction that has empty script signatures and witnesses.
+    /// The spent outputs should be set via [`Psbt::inner_mut`] before signing.
+    pub fn from_unsigned_tx(tx: Transaction) -> Result<Psbt> {
+        let inner = PartiallySignedTransaction::from_unsigned_tx(tx)
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_psbt))?;
+        Ok(Psbt { inner })
+    }
+
+    /// Parses a binary PSBT.
+    pub fn deserialize(bytes: &[u8]) -> Result<Psbt> {
+        let inner = PartiallySignedTransaction::deserialize(bytes)
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_psbt))?;
+        Ok(Psbt { inner })
+    }
+
+    /// Parses a base64 encoded PSBT, i.e. the format that is usually exchanged between wallets.
+    pub fn from_base64(psbt: &str) -> Result<Psbt> {
+        let bytes = base64::decode(psbt.trim(), false)
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_psbt))?;
+        Psbt::deserialize(&bytes)
+    }
+
+    pub fn serialize(&self) -> Vec<u8> {
+        self.inner.serialize()
+    }
+
+    pub fn to_base64(&self) -> String {
+        base64::encode(&self.serialize(), false)
+    }
+
+    pub fn inner(&self) -> &PartiallySignedTransaction {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut PartiallySignedTransaction {
+        &mut self.inner
+    }
+
+    /// Signs the inputs that the given secp256k1 `private_key` controls with their sighash type, `ALL` by default.
+    /// The inputs that are finalized already or don't specify the spent output are skipped.
+    /// Returns the indices of the signed inputs.
+    pub fn sign(&mut self, private_key: &[u8]) -> Result<Vec<usize>> {
+        let secret = SecretKey::from_slice(private_key)
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_private_key))?;
+        let public_key = PublicKey::new(secp256k1::PublicKey::from_secret_key_global(&secret));
+        let secp = Secp256k1::verification_only();
+
+        let spent = self.spent_outputs()?;
+        let tx = self.inner.unsigned_tx.clone();
+        let mut cache = SighashCache::new(&tx);
+        let mut signed = Vec::new();
+
+        for (index, input) in self.inner.inputs.iter_mut().enumerate() {
+            let Some(utxo) = spent[index].as_ref() else {
+                continue;
+            };
+            if is_finalized(input) {
+                continue;
+            }
+            let Some(path) = spending_path(&secp, input, utxo, &public_key) else {
+                continue;
+            };
+
+            match path {
+                SpendingPath::Legacy { script_code } => {
+                    let hash_ty = ecdsa_sighash_type(input)?;
+                    let sighash = cache
+                        .legacy_signature_hash(index, &script_code, hash_ty.to_u32())
+                        .map_err(|_| Error::from(Proto::Error::Error_utxo_sighash_failed))?;
+                    let sig = sign_ecdsa(&secret, sighash.as_byte_array(), hash_ty)?;
+                    input.partial_sigs.insert(public_key, sig);
+                },
+                SpendingPath::Segwit { script_code } => {
+                    let hash_ty = ecdsa_sighash_type(input)?;
+                    let sighash = cache
+                        .segwit_signature_hash(index, &script_code, utxo.value, hash_ty)
+                        .map_err(|_| Error::from(Proto::Error::Error_utxo_sighash_failed))?;
+                    let sig = sign_ecdsa(&secret, sighash.as_byte_array(), hash_ty)?;
+                    input.partial_sigs.insert(public_key, sig);
+                },
+                SpendingPath::TaprootKeyPath { merkle_root } => {
+                    let hash_ty = tap_sighash_type(input)?;
+                    let sighash = match hash_ty {
+                        TapSighashType::AllPlusAnyoneCanPay
+                        | TapSighashType::NonePlusAnyoneCanPay
+                        | TapSighashType::SinglePlusAnyoneCanPay => cache
+                            .taproot_key_spend_signature_hash(
+                                index,
+                                &Prevouts::One(index, utxo.clone()),
+                                hash_ty,
+                            ),
+                        _ => {
+                            // The sighash commits to all the spent outputs.
+                            let prevouts = spent
+                                .iter()
+                                .cloned()
+                                .collect::<Option<Vec<TxOut>>>()
+                                .ok_or_else(|| Error::from(Proto::Error::Error_invalid_psbt))?;
+                            cache.taproot_key_spend_signature_hash(
+                                index,
+                                &Prevouts::All(&prevouts),
+                                hash_ty,
+                            )
+                        },
+                    }
+                    .map_err(|_| Error::from(Proto::Error::Error_utxo_sighash_failed))?;
+
+                    let private = schnorr::PrivateKey::try_from(private_key)
+                        .map_err(|_| Error::from(Proto::Error::Error_invalid_private_key))?;
+                    let spend_path = TaprootSpendPath::KeyPath {
+                        merkle_root: merkle_root.map(|root| H256::from(root.to_byte_array())),
+                    };
+                    let schnorr = private
+                        .sign_taproot(H256::from(sighash.to_byte_array()), &spend_path)
+                        .map_err(|_| Error::from(Proto::Error::Error_invalid_schnorr_signature))?;
+
+                    input.tap_key_sig = Some(bitcoin::taproot::Signature {
+                        sig: secp256k1::schnorr::Signature::from_slice(
+                            schnorr.to_bytes().as_slice(),
+                        )
+                        .map_err(|_| Error::from(Proto::Error::Error_invalid_schnorr_signature))?,
+                        hash_ty,
+                    });
+                    input
+                        .tap_internal_key
+                        .get_or_insert(public_key.inner.x_only_public_key().0);
+                },
+            }
+            signed.push(index);
+        }
+
+        Ok(signed)
+    }
+
+    /// Constructs the final script signatures and witnesses of the inputs,
+    /// and removes the other fields of the inputs as required by BIP174.
+    /// Returns an error if an input is not signed, or spends an unsupported script.
+    pub fn finalize(&mut self) -> Result<()> {
+        let spent = self.spent_outputs()?;
+        for (input, utxo) in self.inner.inputs.iter_mut().zip(spent) {
+            if is_finalized(input) {
+                continue;
+            }
+            let utxo = utxo.ok_or_else(|| Error::from(Proto::Error::Error_invalid_psbt))?;
+            finalize_input(input, &utxo.script_pubkey)?;
+        }
+        Ok(())
+    }
+
+    /// Returns whether every input is finalized.
+    pub fn is_finalized(&self) -> bool {
+        self.inner.inputs.iter().all(is_finalized)
+    }
+
+    /// Returns the network serializable transaction.
+    /// Returns an error if an input is not finalized.
+    pub fn extract_tx(&self) -> Result<Transaction> {
+        if !self.is_finalized() {
+            return Err(Error::from(Proto::Error::Error_invalid_psbt));
+        }
+        Ok(self.inner.clone().extract_tx())
+    }
+
+    /// Merges the PSBT of the same transaction that another signer has updated, as the BIP174 combiner does:
+    /// the union of the signatures, the scripts, the key derivations and the other fields of every input and output.
+    /// Returns [`Proto::Error::Error_psbt_conflict`] if the transactions differ,
+    /// or a field is set to different values, e.g. two signatures of the same key.
+    pub fn combine(&mut self, other: Psbt) -> Result<()> {
+        if self.inner.unsigned_tx != other.inner.unsigned_tx {
+            return Err(Error::from(Proto::Error::Error_psbt_conflict));
+        }
+        for (input, other) in self.inner.inputs.iter().zip(other.inner.inputs.iter()) {
+            check_input_conflicts(input, other)?;
+        }
+        for (output, other) in self.inner.outputs.iter().zip(other.inner.outputs.iter()) {
+            check_output_conflicts(output, other)?;
+        }
+        check_map_conflicts(&self.inner.proprietary, &other.inner.proprietary)?;
+        check_map_conflicts(&self.inner.unknown, &other.inner.unknown)?;
+
+        // Conflicting extended public keys are detected by the combiner.
+        self.inner
+            .combine(other.inner)
+            .map_err(|_| Error::from(Proto::Error::Error_psbt_conflict))
+    }
+
+    /// Returns the outputs that the inputs spend, if specified.
+    fn spent_outputs(&self) -> Result<Vec<Option<TxOut>>> {
+        self.inner
+            .unsigned_tx
+            .input
+            .iter()
+            .zip(self.inner.inputs.iter())
+            .map(|(txin, input)| {
+                if let Some(utxo) = &input.witness_utxo {
+                    return Ok(Some(utxo.clone()));
+                }
+                let Some(prev_tx) = &input.non_witness_utxo else {
+                    return Ok(None);
+                };
+                if prev_tx.txid() != txin.previous_output.txid {
+                    return Err(Error::from(Proto::Error::Error_invalid_psbt));
+                }
+                prev_tx
+                    .output
+                    .get(txin.previous_output.vout as usize)
+                    .cloned()
+                    .map(Some)
+                    .ok_or_else(|| Error::from(Proto::Error::Error_invalid_psbt))
+            })
+            .collect()
+    }
+}
+
+impl From<PartiallySignedTransaction> for Psbt {
+    fn from(inner: PartiallySignedTransaction) -> Self {
+        Psbt { inner }
+    }
+}
+
+impl From<Psbt> for PartiallySignedTransaction {
+    fn from(psbt: Psbt) -> Self {
+        psbt.inner
+    }
+}
+
+/// Returns how the `public_key` signs the `input` that spends `utxo`, or `None` if it doesn't control the input.
+/// Sets the redeem script of a P2SH-P2WPKH input if it's missing.
+fn spending_path<C: Verification>(
+    secp: &Secp256k1<C>,
+    input: &mut Input,
+    utxo: &TxOut,
+    public_key: &PublicKey,
+) -> Option<SpendingPath> {
+    let script_pubkey = &utxo.script_pubkey;
+    let p2wpkh = ScriptBuf::new_v0_p2wpkh(&public_key.wpubkey_hash()?);
+    // Special script code requirement for claiming P2WPKH outputs.
+    let p2wpkh_script_code = p2wpkh.p2wpkh_script_code()?;
+
+    if *script_pubkey == ScriptBuf::new_p2pkh(&public_key.pubkey_hash()) {
+        return Some(SpendingPath::Legacy {
+            script_code: script_pubkey.clone(),
+        });
+    }
+    if *script_pubkey == p2wpkh {
+        return Some(SpendingPath::Segwit {
+            script_code: p2wpkh_script_code,
+        });
+    }
+    if *script_pubkey == ScriptBuf::new_p2sh(&p2wpkh.script_hash()) {
+        input.redeem_script.get_or_insert(p2wpkh);
+        return Some(SpendingPath::Segwit {
+            script_code: p2wpkh_script_code,
+        });
+    }
+    if script_pubkey.is_v1_p2tr() {
+        let internal_key = public_key.inner.x_only_public_key().0;
+        let merkle_root = input.tap_merkle_root;
+        let matches_internal_key = input
+            .tap_internal_key
+            .map_or(true, |key| key == internal_key);
+        let output = ScriptBuf::new_v1_p2tr(secp, internal_key, merkle_root);
+        return (matches_internal_key && *script_pubkey == output)
+            .then_some(SpendingPath::TaprootKeyPath { merkle_root });
+    }
+
+    // The scripts that contain the key, e.g. multisig.
+    let witness_script = input
+        .witness_script
+        .clone()
+        .filter(|script| contains_key(script, public_key));
+    if script_pubkey.is_v0_p2wsh() {
+        let witness_script = witness_script?;
+        return (*script_pubkey == ScriptBuf::new_v0_p2wsh(&witness_script.wscript_hash()))
+            .then_some(SpendingPath::Segwit {
+                script_code: witness_script,
+            });
+    }
+    if script_pubkey.is_p2sh() {
+        let redeem_script = input.redeem_script.as_ref()?;
+        if *script_pubkey != ScriptBuf::new_p2sh(&redeem_script.script_hash()) {
+            return None;
+        }
+        if redeem_script.is_v0_p2wsh() {
+            let witness_script = witness_script?;
+            return (*redeem_script == ScriptBuf::new_v0_p2wsh(&witness_script.wscript_hash()))
+                .then_some(SpendingPath::Segwit {
+                    script_code: witness_script,
+                });
+        }
+        return contains_key(redeem_script, public_key).then(|| SpendingPath::Legacy {
+            script_code: redeem_script.clone(),
+        });
+    }
+    None
+}
+
+/// Sets the final script signature and witness of the `input` that spends `script_pubkey`.
+fn finalize_input(input: &mut Input, script_pubkey: &Script) -> Result<()> {
+    let mut script_sig = None;
+    let mut witness = None;
+
+    if script_pubkey.is_v1_p2tr() {
+        let sig = input
+            .tap_key_sig
+            .as_ref()
+            .ok_or_else(|| Error::from(Proto::Error::Error_invalid_psbt))?;
+        witness = Some(Witness::from_slice(&[sig.to_vec()]));
+    } else if script_pubkey.is_p2pkh() {
+        let (public_key, sig) = single_signature(input)?;
+        script_sig = Some(
+            Builder::new()
+                .push_slice(push_bytes(sig)?)
+                .push_key(&public_key)
+                .into_script(),
+        );
+    } else if script_pubkey.is_v0_p2wpkh() {
+        witness = Some(p2wpkh_witness(input)?);
+    } else if script_pubkey.is_v0_p2wsh() {
+        witness = Some(multisig_witness(input)?);
+    } else if script_pubkey.is_p2sh() {
+        let redeem_script = input
+            .redeem_script
+            .clone()
+            .ok_or_else(|| Error::from(Proto::Error::Error_invalid_redeem_script))?;
+        let builder = if redeem_script.is_v0_p2wpkh() {
+            witness = Some(p2wpkh_witness(input)?);
+            Builder::new()
+        } else if redeem_script.is_v0_p2wsh() {
+            witness = Some(multisig_witness(input)?);
+            Builder::new()
+        } else {
+            multisig_signatures(input, &redeem_script)?
+                .into_iter()
+                .try_fold(Builder::new().push_int(0), |builder, sig| {
+                    Ok::<_, Error>(builder.push_slice(push_bytes(sig)?))
+                })?
+        };
+        script_sig = Some(
+            builder
+                .push_slice(push_bytes(redeem_script.to_bytes())?)
+                .into_script(),
+        );
+    } else if is_p2a(script_pubkey) {
+        // Anyone can spend the anchor without a signature.
+        witness = Some(Witness::new());
+    } else {
+        return Err(Error::from(Proto::Error::Error_invalid_psbt));
+    }
+
+    // The finalizer removes everything but the spent outputs, the final script signature and witness.
+    *input = Input {
+        non_witness_utxo: input.non_witness_utxo.take(),
+        witness_utxo: input.witness_utxo.take(),
+        final_script_sig: script_sig,
+        final_script_witness: witness,
+        unknown: std::mem::take(&mut input.unknown),
+        ..Input::default()
+    };
+    Ok(())
+}
+
+/// Returns the only signature of a single key input.
+fn single_signature(input: &Input) -> Result<(PublicKey, Vec<u8>)> {
+    let (public_key, sig) = input
+        .partial_sigs
+        .iter()
+        .next()
+        .ok_or_else(|| Error::from(Proto::Error::Error_invalid_psbt))?;
+    Ok((*public_key, sig.to_vec()))
+}
+
+fn p2wpkh_witness(input: &Input) -> Result<Witness> {
+    let (public_key, sig) = single_signature(input)?;
+    Ok(Witness::from_slice(&[sig, public_key.to_bytes()]))
+}
+
+/// Returns the witness `<empty> <signatures> <witness script>` of a `multi` witness script.
+fn multisig_witness(input: &Input) -> Result<Witness> {
+    let witness_script = input
+        .witness_script
+        .as_ref()
+        .ok_or_else(|| Error::from(Proto::Error::Error_invalid_redeem_script))?;
+
+    // `OP_CHECKMULTISIG` pops an extra item.
+    let mut items = vec![Vec::new()];
+    items.extend(multisig_signatures(input, witness_script)?);
+    items.push(witness_script.to_bytes());
+    Ok(Witness::from_slice(&items))
+}
+
+/// Returns the threshold number of signatures of a `multi` script in the order of their keys.
+fn multisig_signatures(input: &Input, script: &Script) -> Result<Vec<Vec<u8>>> {
+    let (threshold, keys) =
+        multisig_keys(script).ok_or_else(|| Error::from(Proto::Error::Error_invalid_psbt))?;
+    let signatures: Vec<Vec<u8>> = keys
+        .iter()
+        .filter_map(|key| input.partial_sigs.get(key))
+        .take(threshold)
+        .map(|sig| sig.to_vec())
+        .collect();
+    if signatures.len() < threshold {
+        return Err(Error::from(Proto::Error::Error_invalid_psbt));
+    }
+    Ok(signatures)
+}
+
+/// Parses the threshold and the keys of a script `<m> <keys> <n> OP_CHECKMULTISIG`.
+fn multisig_keys(script: &Script) -> Option<(usize, Vec<PublicKey>)> {
+    let instructions = script
+        .instructions()
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .ok()?;
+    let (first, rest) = instructions.split_first()?;
+    let (last, rest) = rest.split_last()?;
+    let (key_count, keys) = rest.split_last()?;
+    if !matches!(last, Instruction::Op(op) if *op == OP_CHECKMULTISIG) {
+        return None;
+    }
+
+    let threshold = small_number(first)?;
+    let keys = keys
+        .iter()
+        .map(|instruction| match instruction {
+            Instruction::PushBytes(bytes) => PublicKey::from_slice(bytes.as_bytes()).ok(),
+            Instruction::Op(_) => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    if small_number(key_count)? != keys.len() || threshold > keys.len() {
+        return None;
+    }
+    Some((threshold, keys))
+}
+
+/// Returns the number that is pushed by `OP_1` to `OP_16`.
+fn small_number(instruction: &Instruction) -> Option<usize> {
+    let Instruction::Op(op) = instruction else {
+        return None;
+    };
+    let range = OP_PUSHNUM_1.to_u8()..=OP_PUSHNUM_16.to_u8();
+    range
+        .contains(&op.to_u8())
+        .then(|| (op.to_u8() - OP_PUSHNUM_1.to_u8() + 1) as usize)
+}
+
+fn contains_key(script: &Script, public_key: &PublicKey) -> bool {
+    let key = public_key.to_bytes();
+    script.instructions().any(|instruction| {
+        matches!(instruction, Ok(Instruction::PushBytes(bytes)) if bytes.as_bytes() == key.as_slice())
+    })
+}
+
+fn check_input_conflicts(input: &Input, other: &Input) -> Result<()> {
+    check_option_conflict(&input.non_witness_utxo, &other.non_witness_utxo)?;
+    check_option_conflict(&input.witness_utxo, &other.witness_utxo)?;
+    check_map_conflicts(&input.partial_sigs, &other.partial_sigs)?;
+    check_option_conflict(&input.sighash_type, &other.sighash_type)?;
+    check_option_conflict(&input.redeem_script, &other.redeem_script)?;
+    check_option_conflict(&input.witness_script, &other.witness_script)?;
+    check_map_conflicts(&input.bip32_derivation, &other.bip32_derivation)?;
+    check_option_conflict(&input.final_script_sig, &other.final_script_sig)?;
+    check_option_conflict(&input.final_script_witness, &other.final_script_witness)?;
+    check_map_conflicts(&input.ripemd160_preimages, &other.ripemd160_preimages)?;
+    check_map_conflicts(&input.sha256_preimages, &other.sha256_preimages)?;
+    check_map_conflicts(&input.hash160_preimages, &other.hash160_preimages)?;
+    check_map_conflicts(&input.hash256_preimages, &other.hash256_preimages)?;
+    check_option_conflict(&input.tap_key_sig, &other.tap_key_sig)?;
+    check_map_conflicts(&input.tap_script_sigs, &other.tap_script_sigs)?;
+    check_map_conflicts(&input.tap_scripts, &other.tap_scripts)?;
+    check_map_conflicts(&input.tap_key_origins, &other.tap_key_origins)?;
+    check_option_conflict(&input.tap_internal_key, &other.tap_internal_key)?;
+    check_option_conflict(&input.tap_merkle_root, &other.tap_merkle_root)?;
+    check_map_conflicts(&input.proprietary, &other.proprietary)?;
+    check_map_conflicts(&input.unknown, &other.unknown)
+}
+
+fn check_output_conflicts(output: &Output, other: &Output) -> Result<()> {
+    check_option_conflict(&output.redeem_script, &other.redeem_script)?;
+    check_option_conflict(&output.witness_script, &other.witness_script)?;
+    check_map_conflicts(&output.bip32_derivation, &other.bip32_derivation)?;
+    check_option_conflict(&output.tap_internal_key, &other.tap_internal_key)?;
+    check_option_conflict(&output.tap_tree, &other.tap_tree)?;
+    check_map_conflicts(&output.tap_key_origins, &other.tap_key_origins)?;
+    check_map_conflicts(&output.proprietary, &other.proprietary)?;
+    check_map_conflicts(&output.unknown, &other.unknown)
+}
+
+/// Returns an error if both fields are set to different values.
+fn check_option_conflict<T: PartialEq>(value: &Option<T>, other: &Option<T>) -> Result<()> {
+    match (value, other) {
+        (Some(value), Some(other)) if value != other => {
+            Err(Error::from(Proto::Error::Error_psbt_conflict))
+        },
+        _ => Ok(()),
+    }
+}
+
+/// Returns an error if a key of both maps has different values.
+fn check_map_conflicts<K: Ord, V: PartialEq>(
+    map: &BTreeMap<K, V>,
+    other: &BTreeMap<K, V>,
+) -> Result<()> {
+    for (key, value) in map.iter() {
+        check_option_conflict(&Some(value), &other.get(key))?;
+    }
+    Ok(())
+}
+
+fn is_finalized(input: &Input) -> bool {
+    input.final_script_sig.is_some() || input.final_script_witness.is_some()
+}
+
+fn ecdsa_sighash_type(input: &Input) -> Result<EcdsaSighashType> {
+    input
+        .sighash_type
+        .map(|hash_ty| hash_ty.ecdsa_hash_ty())
+        .transpose()
+        .map(|hash_ty| hash_ty.unwrap_or(EcdsaSighashType::All))
+        .map_err(|_| Error::from(Proto::Error::Error_utxo_invalid_sighash_type))
+}
+
+fn tap_sighash_type(input: &Input) -> Result<TapSighashType> {
+    input
+        .sighash_type
+        .map(|hash_ty| hash_ty.taproot_hash_ty())
+        .transpose()
+        .map(|hash_ty| hash_ty.unwrap_or(TapSighashType::Default))
+        .map_err(|_| Error::from(Proto::Error::Error_utxo_invalid_sighash_type))
+}
+
+fn sign_ecdsa(
+    secret: &SecretKey,
+    sighash: &[u8; 32],
+    hash_ty: EcdsaSighashType,
+) -> Result<bitcoin::ecdsa::Signature> {
+    let sighash = Message::from_slice(sighash)
+        .map_err(|_| Error::from(Proto::Error::Error_invalid_sighash))?;
+    Ok(bitcoin::ecdsa::Signature {
+        sig: secret.sign_ecdsa(sighash),
+        hash_ty,
+    })
+}
+
+fn push_bytes(bytes: Vec<u8>) -> Result<PushBytesBuf> {
+    PushBytesBuf::try_from(bytes).map_err(|_| Error::from(Proto::Error::Error_invalid_psbt))
+}
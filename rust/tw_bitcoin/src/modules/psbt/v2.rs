@@ -0,0 +1,596 @@
+use super::Psbt;
+use crate::{Error, Result};
+use bitcoin::blockdata::locktime::absolute::LockTime;
+use bitcoin::consensus::encode::serialize;
+use bitcoin::hashes::Hash;
+use bitcoin::psbt::{Input, Output};
+use bitcoin::{OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Txid, Witness};
+use std::collections::BTreeMap;
+use tw_encoding::base64;
+use tw_proto::BitcoinV2::Proto;
+
+const MAGIC: &[u8] = b"psbt\xff";
+const VERSION: u32 = 2;
+/// The first version of the transaction that supports relative lock times.
+const MIN_TX_VERSION: i32 = 2;
+/// Lock times below the threshold are block heights, and timestamps otherwise.
+const LOCKTIME_THRESHOLD: u32 = 500_000_000;
+
+const PSBT_GLOBAL_UNSIGNED_TX: u8 = 0x00;
+const PSBT_GLOBAL_TX_VERSION: u8 = 0x02;
+const PSBT_GLOBAL_FALLBACK_LOCKTIME: u8 = 0x03;
+const PSBT_GLOBAL_INPUT_COUNT: u8 = 0x04;
+const PSBT_GLOBAL_OUTPUT_COUNT: u8 = 0x05;
+const PSBT_GLOBAL_TX_MODIFIABLE: u8 = 0x06;
+const PSBT_GLOBAL_VERSION: u8 = 0xfb;
+const PSBT_IN_PREVIOUS_TXID: u8 = 0x0e;
+const PSBT_IN_OUTPUT_INDEX: u8 = 0x0f;
+const PSBT_IN_SEQUENCE: u8 = 0x10;
+const PSBT_IN_REQUIRED_TIME_LOCKTIME: u8 = 0x11;
+const PSBT_IN_REQUIRED_HEIGHT_LOCKTIME: u8 = 0x12;
+const PSBT_OUT_AMOUNT: u8 = 0x03;
+const PSBT_OUT_SCRIPT: u8 = 0x04;
+
+/// The bits of `PSBT_GLOBAL_TX_MODIFIABLE`.
+const INPUTS_MODIFIABLE: u8 = 0x01;
+const OUTPUTS_MODIFIABLE: u8 = 0x02;
+const HAS_SIGHASH_SINGLE: u8 = 0x04;
+
+const SIGHASH_NONE: u32 = 0x02;
+const SIGHASH_SINGLE: u32 = 0x03;
+const SIGHASH_ANYONECANPAY: u32 = 0x80;
+
+/// The key-value pairs of a PSBT map by the key, i.e. the key type and the key data.
+type RawMap = BTreeMap<Vec<u8>, Vec<u8>>;
+
+/// The lock time that an input requires, the block height and/or the timestamp.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RequiredLockTime {
+    /// The timestamp, at least `500000000`.
+    pub time: Option<u32>,
+    /// The block height, below `500000000`.
+    pub height: Option<u32>,
+}
+
+impl RequiredLockTime {
+    fn is_some(&self) -> bool {
+        self.time.is_some() || self.height.is_some()
+    }
+
+    fn validate(&self) -> Result<()> {
+        let valid_time = self.time.map_or(true, |time| time >= LOCKTIME_THRESHOLD);
+        let valid_height = self
+            .height
+            .map_or(true, |height| height < LOCKTIME_THRESHOLD);
+        if !valid_time || !valid_height {
+            return Err(Error::from(Proto::Error::Error_utxo_invalid_lock_time));
+        }
+        Ok(())
+    }
+}
+
+/// A BIP370 partially signed transaction, version 2.
+/// Unlike version 0, the transaction is not fixed, so the inputs and the outputs can be added
+/// one by one while the document is passed between the parties, as long as they are modifiable.
+///
+/// The document is kept as a version 0 PSBT of the transaction it currently describes,
+/// so it's updated and signed the same way and converted to version 0 without loss.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PsbtV2 {
+    psbt: Psbt,
+    fallback_lock_time: Option<u32>,
+    /// `PSBT_GLOBAL_TX_MODIFIABLE`.
+    tx_modifiable: u8,
+    /// The required lock times of the inputs.
+    required_lock_times: Vec<RequiredLockTime>,
+}
+
+impl PsbtV2 {
+    /// Creates an empty PSBT that allows to add inputs and outputs.
+    /// The `fallback_lock_time` is used if none of the inputs requires a lock time, `0` by default.
+    pub fn new(tx_version: i32, fallback_lock_time: Option<u32>) -> Result<PsbtV2> {
+        if tx_version < MIN_TX_VERSION {
+            return Err(Error::from(Proto::Error::Error_invalid_psbt));
+        }
+        let tx = Transaction {
+            version: tx_version,
+            lock_time: LockTime::from_consensus(fallback_lock_time.unwrap_or_default()),
+            input: Vec::new(),
+            output: Vec::new(),
+        };
+        Ok(PsbtV2 {
+            psbt: Psbt::from_unsigned_tx(tx)?,
+            fallback_lock_time,
+            tx_modifiable: INPUTS_MODIFIABLE | OUTPUTS_MODIFIABLE,
+            required_lock_times: Vec::new(),
+        })
+    }
+
+    /// Converts a version 0 PSBT. Its transaction is signed or about to be signed,
+    /// so the inputs and the outputs are not modifiable, while its lock time becomes the fallback lock time.
+    pub fn from_v0(psbt: Psbt) -> Result<PsbtV2> {
+        let tx = &psbt.inner().unsigned_tx;
+        if tx.version < MIN_TX_VERSION {
+            return Err(Error::from(Proto::Error::Error_invalid_psbt));
+        }
+        Ok(PsbtV2 {
+            fallback_lock_time: Some(tx.lock_time.to_consensus_u32()),
+            tx_modifiable: 0,
+            required_lock_times: vec![RequiredLockTime::default(); tx.input.len()],
+            psbt,
+        })
+    }
+
+    /// Converts to a version 0 PSBT of the transaction with the lock time that the inputs require.
+    pub fn into_v0(self) -> Psbt {
+        self.psbt
+    }
+
+    /// Parses a binary PSBT of version 2.
+    pub fn deserialize(bytes: &[u8]) -> Result<PsbtV2> {
+        let maps = parse_maps(bytes).ok_or_else(invalid_psbt)?;
+        let (global, maps) = maps.split_first().ok_or_else(invalid_psbt)?;
+        let mut global = global.clone();
+
+        if take_u32(&mut global, PSBT_GLOBAL_VERSION)? != Some(VERSION)
+            || global.contains_key([PSBT_GLOBAL_UNSIGNED_TX].as_slice())
+        {
+            return Err(invalid_psbt());
+        }
+        let tx_version = take_u32(&mut global, PSBT_GLOBAL_TX_VERSION)?.ok_or_else(invalid_psbt)?;
+        let fallback_lock_time = take_u32(&mut global, PSBT_GLOBAL_FALLBACK_LOCKTIME)?;
+        let input_count = take_count(&mut global, PSBT_GLOBAL_INPUT_COUNT)?;
+        let output_count = take_count(&mut global, PSBT_GLOBAL_OUTPUT_COUNT)?;
+        let tx_modifiable = match global.remove([PSBT_GLOBAL_TX_MODIFIABLE].as_slice()) {
+            Some(value) => <[u8; 1]>::try_from(value.as_slice()).map_err(|_| invalid_psbt())?[0],
+            None => 0,
+        };
+        if input_count.checked_add(output_count) != Some(maps.len()) {
+            return Err(invalid_psbt());
+        }
+        let (input_maps, output_maps) = maps.split_at(input_count);
+
+        let mut inputs = Vec::with_capacity(input_count);
+        let mut required_lock_times = Vec::with_capacity(input_count);
+        let mut input_maps = input_maps.to_vec();
+        for map in input_maps.iter_mut() {
+            let txid = map
+                .remove([PSBT_IN_PREVIOUS_TXID].as_slice())
+                .and_then(|txid| Txid::from_slice(&txid).ok())
+                .ok_or_else(invalid_psbt)?;
+            let vout = take_u32(map, PSBT_IN_OUTPUT_INDEX)?.ok_or_else(invalid_psbt)?;
+            let sequence = take_u32(map, PSBT_IN_SEQUENCE)?.map_or(Sequence::MAX, Sequence);
+            let required = RequiredLockTime {
+                time: take_u32(map, PSBT_IN_REQUIRED_TIME_LOCKTIME)?,
+                height: take_u32(map, PSBT_IN_REQUIRED_HEIGHT_LOCKTIME)?,
+            };
+            required.validate().map_err(|_| invalid_psbt())?;
+
+            inputs.push(tx_input(OutPoint { txid, vout }, sequence));
+            required_lock_times.push(required);
+        }
+
+        let mut outputs = Vec::with_capacity(output_count);
+        let mut output_maps = output_maps.to_vec();
+        for map in output_maps.iter_mut() {
+            let value = map
+                .remove([PSBT_OUT_AMOUNT].as_slice())
+                .and_then(|amount| <[u8; 8]>::try_from(amount.as_slice()).ok())
+                .and_then(|amount| u64::try_from(i64::from_le_bytes(amount)).ok())
+                .ok_or_else(invalid_psbt)?;
+            let script_pubkey = map
+                .remove([PSBT_OUT_SCRIPT].as_slice())
+                .map(ScriptBuf::from_bytes)
+                .ok_or_else(invalid_psbt)?;
+            outputs.push(TxOut {
+                value,
+                script_pubkey,
+            });
+        }
+
+        let mut tx = Transaction {
+            version: tx_version as i32,
+            lock_time: lock_time(&required_lock_times, fallback_lock_time)
+                .map_err(|_| invalid_psbt())?,
+            input: inputs,
+            output: outputs,
+        };
+        if tx.version < MIN_TX_VERSION {
+            return Err(invalid_psbt());
+        }
+
+        // A transaction without inputs is ambiguous with the segwit serialization,
+        // so the version 0 document is parsed with a placeholder input that is removed afterwards.
+        let placeholder = tx.input.is_empty();
+        if placeholder {
+            tx.input.push(tx_input(OutPoint::null(), Sequence::MAX));
+            input_maps.push(RawMap::new());
+        }
+        global.insert(vec![PSBT_GLOBAL_UNSIGNED_TX], serialize(&tx));
+
+        let v0 = std::iter::once(global)
+            .chain(input_maps)
+            .chain(output_maps)
+            .collect::<Vec<_>>();
+        let mut psbt = Psbt::deserialize(&serialize_maps(&v0))?;
+        if placeholder {
+            psbt.inner_mut().unsigned_tx.input.clear();
+            psbt.inner_mut().inputs.clear();
+        }
+
+        Ok(PsbtV2 {
+            psbt,
+            fallback_lock_time,
+            tx_modifiable,
+            required_lock_times,
+        })
+    }
+
+    /// Parses a base64 encoded PSBT of version 2.
+    pub fn from_base64(psbt: &str) -> Result<PsbtV2> {
+        let bytes = base64::decode(psbt.trim(), false).map_err(|_| invalid_psbt())?;
+        PsbtV2::deserialize(&bytes)
+    }
+
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut maps = parse_maps(&self.psbt.serialize()).expect("Expected a valid PSBT");
+        let tx = self.unsigned_tx();
+        let (global, maps_rest) = maps.split_first_mut().expect("Expected a global map");
+
+        global.remove([PSBT_GLOBAL_UNSIGNED_TX].as_slice());
+        insert(
+            global,
+            PSBT_GLOBAL_TX_VERSION,
+            tx.version.to_le_bytes().to_vec(),
+        );
+        if let Some(fallback_lock_time) = self.fallback_lock_time {
+            insert(
+                global,
+                PSBT_GLOBAL_FALLBACK_LOCKTIME,
+                fallback_lock_time.to_le_bytes().to_vec(),
+            );
+        }
+        insert(
+            global,
+            PSBT_GLOBAL_INPUT_COUNT,
+            compact_size(tx.input.len()),
+        );
+        insert(
+            global,
+            PSBT_GLOBAL_OUTPUT_COUNT,
+            compact_size(tx.output.len()),
+        );
+        if self.tx_modifiable != 0 {
+            insert(global, PSBT_GLOBAL_TX_MODIFIABLE, vec![self.tx_modifiable]);
+        }
+        insert(global, PSBT_GLOBAL_VERSION, VERSION.to_le_bytes().to_vec());
+
+        let (input_maps, output_maps) = maps_rest.split_at_mut(tx.input.len());
+        for ((map, txin), required) in input_maps
+            .iter_mut()
+            .zip(tx.input.iter())
+            .zip(self.required_lock_times.iter())
+        {
+            let outpoint = txin.previous_output;
+            insert(
+                map,
+                PSBT_IN_PREVIOUS_TXID,
+                outpoint.txid.to_byte_array().to_vec(),
+            );
+            insert(
+                map,
+                PSBT_IN_OUTPUT_INDEX,
+                outpoint.vout.to_le_bytes().to_vec(),
+            );
+            insert(
+                map,
+                PSBT_IN_SEQUENCE,
+                txin.sequence.0.to_le_bytes().to_vec(),
+            );
+            if let Some(time) = required.time {
+                insert(
+                    map,
+                    PSBT_IN_REQUIRED_TIME_LOCKTIME,
+                    time.to_le_bytes().to_vec(),
+                );
+            }
+            if let Some(height) = required.height {
+                insert(
+                    map,
+                    PSBT_IN_REQUIRED_HEIGHT_LOCKTIME,
+                    height.to_le_bytes().to_vec(),
+                );
+            }
+        }
+        for (map, txout) in output_maps.iter_mut().zip(tx.output.iter()) {
+            insert(
+                map,
+                PSBT_OUT_AMOUNT,
+                (txout.value as i64).to_le_bytes().to_vec(),
+            );
+            insert(map, PSBT_OUT_SCRIPT, txout.script_pubkey.to_bytes());
+        }
+
+        serialize_maps(&maps)
+    }
+
+    pub fn to_base64(&self) -> String {
+        base64::encode(&self.serialize(), false)
+    }
+
+    /// Returns the transaction that the document currently describes.
+    pub fn unsigned_tx(&self) -> &Transaction {
+        &self.psbt.inner().unsigned_tx
+    }
+
+    pub fn fallback_lock_time(&self) -> Option<u32> {
+        self.fallback_lock_time
+    }
+
+    pub fn required_lock_times(&self) -> &[RequiredLockTime] {
+        &self.required_lock_times
+    }
+
+    pub fn inputs_modifiable(&self) -> bool {
+        self.tx_modifiable & INPUTS_MODIFIABLE != 0
+    }
+
+    pub fn outputs_modifiable(&self) -> bool {
+        self.tx_modifiable & OUTPUTS_MODIFIABLE != 0
+    }
+
+    /// Returns whether an input is signed with `SIGHASH_SINGLE`,
+    /// so the inputs and the outputs should be added in pairs at the same index.
+    pub fn has_sighash_single(&self) -> bool {
+        self.tx_modifiable & HAS_SIGHASH_SINGLE != 0
+    }
+
+    /// Allows or forbids adding the inputs and the outputs, e.g. by the creator once the transaction is complete.
+    pub fn set_modifiable(&mut self, inputs: bool, outputs: bool) {
+        self.tx_modifiable &= HAS_SIGHASH_SINGLE;
+        if inputs {
+            self.tx_modifiable |= INPUTS_MODIFIABLE;
+        }
+        if outputs {
+            self.tx_modifiable |= OUTPUTS_MODIFIABLE;
+        }
+    }
+
+    /// Returns the PSBT inputs to update, e.g. to set the spent outputs.
+    pub fn inputs_mut(&mut self) -> &mut [Input] {
+        &mut self.psbt.inner_mut().inputs
+    }
+
+    /// Returns the PSBT outputs to update, e.g. to set the derivation paths of the change.
+    pub fn outputs_mut(&mut self) -> &mut [Output] {
+        &mut self.psbt.inner_mut().outputs
+    }
+
+    /// Adds an input that spends `previous_output`, and returns its index.
+    /// Returns an error if the inputs are not modifiable,
+    /// or `required_lock_time` conflicts with the lock times that the other inputs require.
+    pub fn add_input(
+        &mut self,
+        previous_output: OutPoint,
+        sequence: Sequence,
+        required_lock_time: RequiredLockTime,
+        input: Input,
+    ) -> Result<usize> {
+        if !self.inputs_modifiable() {
+            return Err(invalid_psbt());
+        }
+        required_lock_time.validate()?;
+
+        let mut required_lock_times = self.required_lock_times.clone();
+        required_lock_times.push(required_lock_time);
+        let lock_time = lock_time(&required_lock_times, self.fallback_lock_time)?;
+
+        let inner = self.psbt.inner_mut();
+        inner.unsigned_tx.lock_time = lock_time;
+        inner
+            .unsigned_tx
+            .input
+            .push(tx_input(previous_output, sequence));
+        inner.inputs.push(input);
+        self.required_lock_times = required_lock_times;
+        Ok(self.required_lock_times.len() - 1)
+    }
+
+    /// Adds an output, and returns its index.
+    /// Returns an error if the outputs are not modifiable.
+    pub fn add_output(&mut self, txout: TxOut, output: Output) -> Result<usize> {
+        if !self.outputs_modifiable() {
+            return Err(invalid_psbt());
+        }
+        let inner = self.psbt.inner_mut();
+        inner.unsigned_tx.output.push(txout);
+        inner.outputs.push(output);
+        Ok(inner.outputs.len() - 1)
+    }
+
+    /// Signs the inputs as [`Psbt::sign`] does, and then forbids adding the inputs and the outputs
+    /// that the signatures commit to, i.e. the inputs unless `ANYONECANPAY`, and the outputs unless `NONE`.
+    pub fn sign(&mut self, private_key: &[u8]) -> Result<Vec<usize>> {
+        let signed = self.psbt.sign(private_key)?;
+        for index in signed.iter() {
+            // `SIGHASH_DEFAULT` of Taproot commits to the same data as `SIGHASH_ALL`.
+            let sighash_type = self.psbt.inner().inputs[*index]
+                .sighash_type
+                .map_or(0, |sighash_type| sighash_type.to_u32());
+            if sighash_type & SIGHASH_ANYONECANPAY == 0 {
+                self.tx_modifiable &= !INPUTS_MODIFIABLE;
+            }
+            match sighash_type & !SIGHASH_ANYONECANPAY {
+                SIGHASH_NONE => (),
+                SIGHASH_SINGLE => self.tx_modifiable |= HAS_SIGHASH_SINGLE,
+                _ => self.tx_modifiable &= !OUTPUTS_MODIFIABLE,
+            }
+        }
+        Ok(signed)
+    }
+
+    /// Finalizes the inputs as [`Psbt::finalize`] does.
+    pub fn finalize(&mut self) -> Result<()> {
+        self.psbt.finalize()
+    }
+
+    pub fn is_finalized(&self) -> bool {
+        self.psbt.is_finalized()
+    }
+
+    /// Merges the PSBT of the same transaction as [`Psbt::combine`] does. The inputs and the outputs remain
+    /// modifiable only if both PSBTs allow to modify them, since the other signer might commit to them.
+    pub fn combine(&mut self, other: PsbtV2) -> Result<()> {
+        if self.fallback_lock_time != other.fallback_lock_time
+            || self.required_lock_times != other.required_lock_times
+        {
+            return Err(Error::from(Proto::Error::Error_psbt_conflict));
+        }
+        self.psbt.combine(other.psbt)?;
+
+        let modifiable = INPUTS_MODIFIABLE | OUTPUTS_MODIFIABLE;
+        self.tx_modifiable = (self.tx_modifiable & other.tx_modifiable & modifiable)
+            | ((self.tx_modifiable | other.tx_modifiable) & HAS_SIGHASH_SINGLE);
+        Ok(())
+    }
+
+    /// Returns the network serializable transaction.
+    /// Returns an error if an input is not finalized.
+    pub fn extract_tx(&self) -> Result<Transaction> {
+        self.psbt.extract_tx()
+    }
+}
+
+/// Determines the lock time of the transaction as specified in BIP370:
+/// the maximum of the required heights if every input that requires a lock time supports a height,
+/// or the maximum of the required timestamps otherwise, or the fallback if no input requires a lock time.
+fn lock_time(required: &[RequiredLockTime], fallback: Option<u32>) -> Result<LockTime> {
+    let required: Vec<_> = required.iter().filter(|r| r.is_some()).collect();
+    if required.is_empty() {
+        return Ok(LockTime::from_consensus(fallback.unwrap_or_default()));
+    }
+
+    let max_height = required
+        .iter()
+        .map(|r| r.height)
+        .collect::<Option<Vec<_>>>()
+        .and_then(|heights| heights.into_iter().max());
+    let max_time = required
+        .iter()
+        .map(|r| r.time)
+        .collect::<Option<Vec<_>>>()
+        .and_then(|times| times.into_iter().max());
+    max_height
+        .or(max_time)
+        .map(LockTime::from_consensus)
+        .ok_or_else(|| Error::from(Proto::Error::Error_utxo_invalid_lock_time))
+}
+
+fn tx_input(previous_output: OutPoint, sequence: Sequence) -> TxIn {
+    TxIn {
+        previous_output,
+        script_sig: ScriptBuf::new(),
+        sequence,
+        witness: Witness::new(),
+    }
+}
+
+fn invalid_psbt() -> Error {
+    Error::from(Proto::Error::Error_invalid_psbt)
+}
+
+fn insert(map: &mut RawMap, key_type: u8, value: Vec<u8>) {
+    map.insert(vec![key_type], value);
+}
+
+fn take_u32(map: &mut RawMap, key_type: u8) -> Result<Option<u32>> {
+    map.remove([key_type].as_slice())
+        .map(|value| {
+            <[u8; 4]>::try_from(value.as_slice())
+                .map(u32::from_le_bytes)
+                .map_err(|_| invalid_psbt())
+        })
+        .transpose()
+}
+
+fn take_count(map: &mut RawMap, key_type: u8) -> Result<usize> {
+    let value = map.remove([key_type].as_slice()).ok_or_else(invalid_psbt)?;
+    let mut rest = value.as_slice();
+    let count = read_compact_size(&mut rest).ok_or_else(invalid_psbt)?;
+    if !rest.is_empty() {
+        return Err(invalid_psbt());
+    }
+    usize::try_from(count).map_err(|_| invalid_psbt())
+}
+
+/// Parses the magic bytes followed by the maps of the key-value pairs.
+/// Returns `None` if a key repeats within a map.
+fn parse_maps(bytes: &[u8]) -> Option<Vec<RawMap>> {
+    let mut rest = bytes.strip_prefix(MAGIC)?;
+    let mut maps = Vec::new();
+    while !rest.is_empty() {
+        let mut map = RawMap::new();
+        loop {
+            let key_len = usize::try_from(read_compact_size(&mut rest)?).ok()?;
+            // The zero length key is the separator of the maps.
+            if key_len == 0 {
+                break;
+            }
+            let key = take(&mut rest, key_len)?;
+            let value_len = usize::try_from(read_compact_size(&mut rest)?).ok()?;
+            let value = take(&mut rest, value_len)?;
+            if map.insert(key.to_vec(), value.to_vec()).is_some() {
+                return None;
+            }
+        }
+        maps.push(map);
+    }
+    Some(maps)
+}
+
+fn serialize_maps(maps: &[RawMap]) -> Vec<u8> {
+    let mut bytes = MAGIC.to_vec();
+    for map in maps {
+        for (key, value) in map {
+            bytes.extend(compact_size(key.len()));
+            bytes.extend_from_slice(key);
+            bytes.extend(compact_size(value.len()));
+            bytes.extend_from_slice(value);
+        }
+        bytes.push(0);
+    }
+    bytes
+}
+
+fn take<'a>(rest: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+    if rest.len() < len {
+        return None;
+    }
+    let (taken, tail) = rest.split_at(len);
+    *rest = tail;
+    Some(taken)
+}
+
+fn read_compact_size(rest: &mut &[u8]) -> Option<u64> {
+    let (first, tail) = rest.split_first()?;
+    *rest = tail;
+    let len = match first {
+        0xfd => 2,
+        0xfe => 4,
+        0xff => 8,
+        n => return Some(*n as u64),
+    };
+    let mut buf = [0; 8];
+    buf[..len].copy_from_slice(take(rest, len)?);
+    Some(u64::from_le_bytes(buf))
+}
+
+fn compact_size(n: usize) -> Vec<u8> {
+    let n = n as u64;
+    match n {
+        0..=0xfc => vec![n as u8],
+        0xfd..=0xffff => [vec![0xfd], (n as u16).to_le_bytes().to_vec()].concat(),
+        0x10000..=0xffff_ffff => [vec![0xfe], (n as u32).to_le_bytes().to_vec()].concat(),
+        _ => [vec![0xff], n.to_le_bytes().to_vec()].concat(),
+    }
+}
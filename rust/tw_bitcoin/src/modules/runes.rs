@@ -0,0 +1,603 @@
+//! [Runes](https://docs.ordinals.com/runes.html) runestones, i.e. the `OP_RETURN` outputs
+//! that etch, mint and transfer runes:
+//!
+//! ```rust,ignore
+//! let runestone = Runestone {
+//!     edicts: vec![Edict { id: RuneId { block: 840000, tx: 3 }, amount: 1000, output: 1 }],
+//!     ..Runestone::default()
+//! };
+//! let output = TxOut { value: 0, script_pubkey: runestone.encipher() };
+//! // ...
+//! match Runestone::decipher(&tx) {
+//!     Some(Artifact::Runestone(runestone)) => credit(runestone.edicts),
+//!     // The input runes are burned.
+//!     Some(Artifact::Cenotaph(_)) => (),
+//!     None => (),
+//! }
+//! ```
+//!
+//! The message is a sequence of LEB128 integers pushed after `OP_RETURN OP_13`:
+//! the tag and value pairs of the fields, followed by the edicts after the `Body` tag.
+//! A malformed runestone is a cenotaph that burns the runes of the inputs.
+
+use crate::{Error, Result};
+use bitcoin::blockdata::opcodes::all::{OP_PUSHNUM_13, OP_RETURN};
+use bitcoin::blockdata::script::{Instruction, PushBytesBuf};
+use bitcoin::{ScriptBuf, Transaction};
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+use tw_proto::BitcoinV2::Proto;
+
+/// The maximum length of a push, the payload is split into pushes of this length.
+const MAX_PUSH_LEN: usize = 520;
+/// The maximum length of a LEB128 encoded `u128`.
+const MAX_VARINT_LEN: usize = 19;
+pub const MAX_DIVISIBILITY: u8 = 38;
+/// The spacers of the longest rune name, 28 letters.
+pub const MAX_SPACERS: u32 = 0b0000_0111_1111_1111_1111_1111_1111_1111;
+const SPACER: char = '•';
+/// The name of `u128::MAX`, the longest one.
+const MAX_RUNE_NAME: &str = "BCGDENLQRQWDSLRUGSNLBTMFIJAV";
+
+const TAG_BODY: u128 = 0;
+const TAG_DIVISIBILITY: u128 = 1;
+const TAG_FLAGS: u128 = 2;
+const TAG_SPACERS: u128 = 3;
+const TAG_RUNE: u128 = 4;
+const TAG_SYMBOL: u128 = 5;
+const TAG_PREMINE: u128 = 6;
+const TAG_CAP: u128 = 8;
+const TAG_AMOUNT: u128 = 10;
+const TAG_HEIGHT_START: u128 = 12;
+const TAG_HEIGHT_END: u128 = 14;
+const TAG_OFFSET_START: u128 = 16;
+const TAG_OFFSET_END: u128 = 18;
+const TAG_MINT: u128 = 20;
+const TAG_POINTER: u128 = 22;
+
+const FLAG_ETCHING: u128 = 1 << 0;
+const FLAG_TERMS: u128 = 1 << 1;
+const FLAG_TURBO: u128 = 1 << 2;
+
+/// The name of a rune, the modified base-26 number of the letters, e.g. `A` is 0 and `AA` is 26.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Rune(pub u128);
+
+impl Rune {
+    /// Returns the data that the input of the etching transaction must push in its tapscript,
+    /// i.e. the little-endian bytes of the rune without the trailing zeros.
+    pub fn commitment(&self) -> Vec<u8> {
+        let bytes = self.0.to_le_bytes();
+        let len = bytes
+            .iter()
+            .rposition(|byte| *byte != 0)
+            .map_or(0, |i| i + 1);
+        bytes[..len].to_vec()
+    }
+}
+
+impl FromStr for Rune {
+    type Err = Error;
+
+    /// Parses the uppercase letters of the name.
+    fn from_str(s: &str) -> Result<Self> {
+        if s.is_empty() {
+            return Err(Error::from(Proto::Error::Error_invalid_rune));
+        }
+        let mut rune: u128 = 0;
+        for (i, c) in s.chars().enumerate() {
+            if !c.is_ascii_uppercase() {
+                return Err(Error::from(Proto::Error::Error_invalid_rune));
+            }
+            if i > 0 {
+                rune = rune
+                    .checked_add(1)
+                    .ok_or_else(|| Error::from(Proto::Error::Error_invalid_rune))?;
+            }
+            rune = rune
+                .checked_mul(26)
+                .and_then(|rune| rune.checked_add((c as u8 - b'A') as u128))
+                .ok_or_else(|| Error::from(Proto::Error::Error_invalid_rune))?;
+        }
+        Ok(Rune(rune))
+    }
+}
+
+impl fmt::Display for Rune {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0 == u128::MAX {
+            return f.write_str(MAX_RUNE_NAME);
+        }
+        let mut n = self.0 + 1;
+        let mut letters = Vec::new();
+        while n > 0 {
+            letters.push(b'A' + ((n - 1) % 26) as u8);
+            n = (n - 1) / 26;
+        }
+        letters.reverse();
+        f.write_str(&String::from_utf8(letters).expect("Expected ASCII letters"))
+    }
+}
+
+/// A rune with the spacers between its letters, e.g. `UNCOMMON•GOODS`.
+/// The bit `i` of the spacers is a spacer after the letter `i`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SpacedRune {
+    pub rune: Rune,
+    pub spacers: u32,
+}
+
+impl FromStr for SpacedRune {
+    type Err = Error;
+
+    /// Parses the name with the `•` or `.` spacers, that may only be between the letters.
+    fn from_str(s: &str) -> Result<Self> {
+        let mut letters = String::new();
+        let mut spacers = 0u32;
+        for c in s.chars() {
+            if c == SPACER || c == '.' {
+                if letters.is_empty() {
+                    return Err(Error::from(Proto::Error::Error_invalid_rune));
+                }
+                let bit = 1u32
+                    .checked_shl(letters.len() as u32 - 1)
+                    .ok_or_else(|| Error::from(Proto::Error::Error_invalid_rune))?;
+                if spacers & bit != 0 {
+                    return Err(Error::from(Proto::Error::Error_invalid_rune));
+                }
+                spacers |= bit;
+            } else {
+                letters.push(c);
+            }
+        }
+        // A trailing spacer.
+        let trailing = spacers
+            .checked_shr(letters.len().saturating_sub(1) as u32)
+            .unwrap_or_default();
+        if trailing != 0 || spacers > MAX_SPACERS {
+            return Err(Error::from(Proto::Error::Error_invalid_rune));
+        }
+        Ok(SpacedRune {
+            rune: Rune::from_str(&letters)?,
+            spacers,
+        })
+    }
+}
+
+impl fmt::Display for SpacedRune {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rune = self.rune.to_string();
+        for (i, c) in rune.chars().enumerate() {
+            write!(f, "{c}")?;
+            if i + 1 < rune.len() && self.spacers & (1 << i) != 0 {
+                write!(f, "{SPACER}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The ID of a rune, the block height and the index of the etching transaction, e.g. `840000:3`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct RuneId {
+    pub block: u64,
+    pub tx: u32,
+}
+
+impl RuneId {
+    /// Returns the delta encoding of the next ID, given that the IDs are sorted.
+    fn delta(&self, next: RuneId) -> (u128, u128) {
+        let block = next.block - self.block;
+        let tx = if block == 0 {
+            next.tx - self.tx
+        } else {
+            next.tx
+        };
+        (block as u128, tx as u128)
+    }
+
+    /// Returns the ID of the delta encoded `block` and `tx` that follow this ID.
+    fn next(&self, block: u128, tx: u128) -> Option<RuneId> {
+        let block = u64::try_from(block).ok()?;
+        let tx = u32::try_from(tx).ok()?;
+        Some(if block == 0 {
+            RuneId {
+                block: self.block,
+                tx: self.tx.checked_add(tx)?,
+            }
+        } else {
+            RuneId {
+                block: self.block.checked_add(block)?,
+                tx,
+            }
+        })
+    }
+}
+
+impl FromStr for RuneId {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (block, tx) = s
+            .split_once(':')
+            .ok_or_else(|| Error::from(Proto::Error::Error_invalid_rune))?;
+        Ok(RuneId {
+            block: block
+                .parse()
+                .map_err(|_| Error::from(Proto::Error::Error_invalid_rune))?,
+            tx: tx
+                .parse()
+                .map_err(|_| Error::from(Proto::Error::Error_invalid_rune))?,
+        })
+    }
+}
+
+impl fmt::Display for RuneId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.block, self.tx)
+    }
+}
+
+/// The open mint terms of an etched rune.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Terms {
+    /// The amount of every mint.
+    pub amount: Option<u128>,
+    /// The maximum number of the mints.
+    pub cap: Option<u128>,
+    /// The absolute block heights that the mints are open between.
+    pub height: (Option<u64>, Option<u64>),
+    /// The block heights relative to the etching that the mints are open between.
+    pub offset: (Option<u64>, Option<u64>),
+}
+
+/// The creation of a rune.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Etching {
+    pub divisibility: Option<u8>,
+    /// The amount that is allocated to the outputs of the etching transaction.
+    pub premine: Option<u128>,
+    /// The name, a reserved one is assigned if `None`.
+    pub rune: Option<Rune>,
+    pub spacers: Option<u32>,
+    /// The currency symbol, `¤` by default.
+    pub symbol: Option<char>,
+    pub terms: Option<Terms>,
+    /// Whether the rune opts in to the future protocol changes.
+    pub turbo: bool,
+}
+
+impl Etching {
+    /// Returns the maximum supply, or `None` if it overflows.
+    pub fn supply(&self) -> Option<u128> {
+        let terms = self.terms.unwrap_or_default();
+        terms
+            .cap
+            .unwrap_or_default()
+            .checked_mul(terms.amount.unwrap_or_default())?
+            .checked_add(self.premine.unwrap_or_default())
+    }
+}
+
+/// The transfer of the `amount` of the rune to the `output`.
+/// The amount of zero transfers all the remaining runes,
+/// and the `output` equal to the number of the outputs splits the amount between the non-`OP_RETURN` outputs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Edict {
+    pub id: RuneId,
+    pub amount: u128,
+    pub output: u32,
+}
+
+/// A valid runestone.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Runestone {
+    pub edicts: Vec<Edict>,
+    pub etching: Option<Etching>,
+    /// The rune to mint according to its terms.
+    pub mint: Option<RuneId>,
+    /// The output that the unallocated runes are transferred to,
+    /// the first non-`OP_RETURN` output by default.
+    pub pointer: Option<u32>,
+}
+
+/// A malformed runestone. The runes of the inputs are burned,
+/// as well as the etched rune, while the mint is counted against the cap.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cenotaph {
+    pub etching: Option<Rune>,
+    pub mint: Option<RuneId>,
+}
+
+// The runestone is the common case, so it isn't boxed.
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Artifact {
+    Runestone(Runestone),
+    Cenotaph(Cenotaph),
+}
+
+impl Runestone {
+    /// Returns the `OP_RETURN` script of the runestone.
+    pub fn encipher(&self) -> ScriptBuf {
+        let mut payload = Vec::new();
+
+        if let Some(etching) = &self.etching {
+            let mut flags = FLAG_ETCHING;
+            if etching.terms.is_some() {
+                flags |= FLAG_TERMS;
+            }
+            if etching.turbo {
+                flags |= FLAG_TURBO;
+            }
+            encode_field(&mut payload, TAG_FLAGS, Some(flags));
+            encode_field(&mut payload, TAG_RUNE, etching.rune.map(|rune| rune.0));
+            encode_field(&mut payload, TAG_DIVISIBILITY, etching.divisibility);
+            encode_field(&mut payload, TAG_SPACERS, etching.spacers);
+            encode_field(&mut payload, TAG_SYMBOL, etching.symbol.map(u32::from));
+            encode_field(&mut payload, TAG_PREMINE, etching.premine);
+            if let Some(terms) = &etching.terms {
+                encode_field(&mut payload, TAG_AMOUNT, terms.amount);
+                encode_field(&mut payload, TAG_CAP, terms.cap);
+                encode_field(&mut payload, TAG_HEIGHT_START, terms.height.0);
+                encode_field(&mut payload, TAG_HEIGHT_END, terms.height.1);
+                encode_field(&mut payload, TAG_OFFSET_START, terms.offset.0);
+                encode_field(&mut payload, TAG_OFFSET_END, terms.offset.1);
+            }
+        }
+
+        if let Some(mint) = self.mint {
+            encode_field(&mut payload, TAG_MINT, Some(mint.block));
+            encode_field(&mut payload, TAG_MINT, Some(mint.tx));
+        }
+        encode_field(&mut payload, TAG_POINTER, self.pointer);
+
+        if !self.edicts.is_empty() {
+            encode_varint(&mut payload, TAG_BODY);
+            let mut edicts = self.edicts.clone();
+            edicts.sort_by_key(|edict| edict.id);
+
+            let mut previous = RuneId::default();
+            for edict in edicts {
+                let (block, tx) = previous.delta(edict.id);
+                encode_varint(&mut payload, block);
+                encode_varint(&mut payload, tx);
+                encode_varint(&mut payload, edict.amount);
+                encode_varint(&mut payload, edict.output as u128);
+                previous = edict.id;
+            }
+        }
+
+        let mut builder = ScriptBuf::builder()
+            .push_opcode(OP_RETURN)
+            .push_opcode(OP_PUSHNUM_13);
+        for chunk in payload.chunks(MAX_PUSH_LEN) {
+            let push = PushBytesBuf::try_from(chunk.to_vec()).expect("Expected a valid push");
+            builder = builder.push_slice(push);
+        }
+        builder.into_script()
+    }
+
+    /// Returns the runestone of the first `OP_RETURN OP_13` output, or `None` if there is none.
+    pub fn decipher(tx: &Transaction) -> Option<Artifact> {
+        let Some(payload) = payload(tx)? else {
+            return Some(Artifact::Cenotaph(Cenotaph {
+                etching: None,
+                mint: None,
+            }));
+        };
+        let mut flaw = false;
+
+        let mut integers = Vec::new();
+        let mut rest = payload.as_slice();
+        while !rest.is_empty() {
+            match decode_varint(&mut rest) {
+                Some(integer) => integers.push(integer),
+                None => {
+                    flaw = true;
+                    break;
+                },
+            }
+        }
+
+        let mut fields: BTreeMap<u128, VecDeque<u128>> = BTreeMap::new();
+        let mut edicts = Vec::new();
+        let mut i = 0;
+        while i < integers.len() {
+            let tag = integers[i];
+            if tag == TAG_BODY {
+                let mut id = RuneId::default();
+                for chunk in integers[i + 1..].chunks(4) {
+                    let [block, tx_delta, amount, output] = chunk else {
+                        // The trailing integers.
+                        flaw = true;
+                        break;
+                    };
+                    let (Some(next), Ok(output)) =
+                        (id.next(*block, *tx_delta), u32::try_from(*output))
+                    else {
+                        flaw = true;
+                        break;
+                    };
+                    if output as usize > tx.output.len() {
+                        flaw = true;
+                        break;
+                    }
+                    id = next;
+                    edicts.push(Edict {
+                        id,
+                        amount: *amount,
+                        output,
+                    });
+                }
+                break;
+            }
+            let Some(value) = integers.get(i + 1) else {
+                // The truncated field.
+                flaw = true;
+                break;
+            };
+            fields.entry(tag).or_default().push_back(*value);
+            i += 2;
+        }
+
+        let mut flags = take(&mut fields, TAG_FLAGS, |[flags]| Some(flags)).unwrap_or_default();
+        let etching = take_flag(&mut flags, FLAG_ETCHING).then(|| Etching {
+            divisibility: take(&mut fields, TAG_DIVISIBILITY, |[divisibility]| {
+                u8::try_from(divisibility)
+                    .ok()
+                    .filter(|divisibility| *divisibility <= MAX_DIVISIBILITY)
+            }),
+            premine: take(&mut fields, TAG_PREMINE, |[premine]| Some(premine)),
+            rune: take(&mut fields, TAG_RUNE, |[rune]| Some(Rune(rune))),
+            spacers: take(&mut fields, TAG_SPACERS, |[spacers]| {
+                u32::try_from(spacers)
+                    .ok()
+                    .filter(|spacers| *spacers <= MAX_SPACERS)
+            }),
+            symbol: take(&mut fields, TAG_SYMBOL, |[symbol]| {
+                char::from_u32(u32::try_from(symbol).ok()?)
+            }),
+            terms: take_flag(&mut flags, FLAG_TERMS).then(|| Terms {
+                cap: take(&mut fields, TAG_CAP, |[cap]| Some(cap)),
+                height: (
+                    take(&mut fields, TAG_HEIGHT_START, |[height]| {
+                        u64::try_from(height).ok()
+                    }),
+                    take(&mut fields, TAG_HEIGHT_END, |[height]| {
+                        u64::try_from(height).ok()
+                    }),
+                ),
+                amount: take(&mut fields, TAG_AMOUNT, |[amount]| Some(amount)),
+                offset: (
+                    take(&mut fields, TAG_OFFSET_START, |[offset]| {
+                        u64::try_from(offset).ok()
+                    }),
+                    take(&mut fields, TAG_OFFSET_END, |[offset]| {
+                        u64::try_from(offset).ok()
+                    }),
+                ),
+            }),
+            turbo: take_flag(&mut flags, FLAG_TURBO),
+        });
+        let mint = take(&mut fields, TAG_MINT, |[block, tx]| {
+            Some(RuneId {
+                block: u64::try_from(block).ok()?,
+                tx: u32::try_from(tx).ok()?,
+            })
+        });
+        let pointer = take(&mut fields, TAG_POINTER, |[pointer]| {
+            u32::try_from(pointer)
+                .ok()
+                .filter(|pointer| (*pointer as usize) < tx.output.len())
+        });
+
+        // The supply overflows, a flag is unknown, or an even tag is unknown or invalid.
+        flaw |= matches!(etching, Some(etching) if etching.supply().is_none());
+        flaw |= flags != 0;
+        flaw |= fields.keys().any(|tag| tag % 2 == 0);
+
+        if flaw {
+            return Some(Artifact::Cenotaph(Cenotaph {
+                etching: etching.and_then(|etching| etching.rune),
+                mint,
+            }));
+        }
+        Some(Artifact::Runestone(Runestone {
+            edicts,
+            etching,
+            mint,
+            pointer,
+        }))
+    }
+}
+
+/// Returns the concatenated pushes of the first runestone output,
+/// or `Some(None)` if the runestone contains an opcode or is not a valid script.
+fn payload(tx: &Transaction) -> Option<Option<Vec<u8>>> {
+    for output in tx.output.iter() {
+        let mut instructions = output.script_pubkey.instructions();
+        if instructions.next() != Some(Ok(Instruction::Op(OP_RETURN))) {
+            continue;
+        }
+        if instructions.next() != Some(Ok(Instruction::Op(OP_PUSHNUM_13))) {
+            continue;
+        }
+
+        let mut payload = Vec::new();
+        for instruction in instructions {
+            match instruction {
+                Ok(Instruction::PushBytes(push)) => payload.extend_from_slice(push.as_bytes()),
+                Ok(Instruction::Op(_)) | Err(_) => return Some(None),
+            }
+        }
+        return Some(Some(payload));
+    }
+    None
+}
+
+/// Takes the `N` values of the `tag` if they are valid according to `with`.
+/// The invalid values are left, so an even tag makes a cenotaph.
+fn take<const N: usize, T>(
+    fields: &mut BTreeMap<u128, VecDeque<u128>>,
+    tag: u128,
+    with: impl Fn([u128; N]) -> Option<T>,
+) -> Option<T> {
+    let values = fields.get_mut(&tag)?;
+    if values.len() < N {
+        return None;
+    }
+    let mut array = [0; N];
+    for (item, value) in array.iter_mut().zip(values.iter()) {
+        *item = *value;
+    }
+    let result = with(array)?;
+    values.drain(..N);
+    if values.is_empty() {
+        fields.remove(&tag);
+    }
+    Some(result)
+}
+
+fn take_flag(flags: &mut u128, flag: u128) -> bool {
+    let set = *flags & flag != 0;
+    *flags &= !flag;
+    set
+}
+
+fn encode_field<T: Into<u128>>(payload: &mut Vec<u8>, tag: u128, value: Option<T>) {
+    if let Some(value) = value {
+        encode_varint(payload, tag);
+        encode_varint(payload, value.into());
+    }
+}
+
+fn encode_varint(payload: &mut Vec<u8>, mut n: u128) {
+    while n >> 7 > 0 {
+        payload.push((n as u8 & 0x7f) | 0x80);
+        n >>= 7;
+    }
+    payload.push(n as u8);
+}
+
+/// Returns `None` if the integer is unterminated or overflows `u128`.
+fn decode_varint(rest: &mut &[u8]) -> Option<u128> {
+    let mut n: u128 = 0;
+    for (i, byte) in rest.iter().enumerate() {
+        if i >= MAX_VARINT_LEN {
+            return None;
+        }
+        let value = (byte & 0x7f) as u128;
+        // Only the two lowest bits of the last byte fit.
+        if i == MAX_VARINT_LEN - 1 && value & 0b0111_1100 != 0 {
+            return None;
+        }
+        n |= value << (7 * i);
+        if byte & 0x80 == 0 {
+            *rest = &rest[i + 1..];
+            return Some(n);
+        }
+    }
+    None
+}
@@ -0,0 +1,132 @@
+//! Legacy signed messages of the Bitcoin Core `signmessage` RPC, i.e. the compact recoverable signatures
+//! of the hash of the `\x18Bitcoin Signed Message:\n` prefixed message:
+//!
+//! ```rust,ignore
+//! let signature = sign_message(PRIVATE_KEY, &address, b"test signature")?;
+//! verify_message(&address, b"test signature", &signature)?;
+//! ```
+//!
+//! The signature is base64 encoded, and its first byte is the header of the recovery ID and the type of
+//! the address as specified in [BIP137](https://github.com/bitcoin/bips/blob/master/bip-0137.mediawiki),
+//! so the P2PKH, P2SH-P2WPKH and P2WPKH addresses can be recovered from the signature.
+
+use crate::{Error, Result};
+use bitcoin::consensus::encode::VarInt;
+use bitcoin::hashes::{sha256d, Hash};
+use bitcoin::{Address, Network, PublicKey};
+use tw_encoding::base64;
+use tw_hash::H256;
+use tw_keypair::ecdsa::secp256k1;
+use tw_keypair::traits::SigningKeyTrait;
+use tw_proto::BitcoinV2::Proto;
+
+/// The prefix of the signed message, including the length of the text.
+pub const MESSAGE_PREFIX: &[u8] = b"\x18Bitcoin Signed Message:\n";
+/// The length of the header and the (r, s) signature.
+const SIGNATURE_LEN: usize = 65;
+/// The headers of the address types, followed by the recovery ID.
+const P2PKH_UNCOMPRESSED_HEADER: u8 = 27;
+const P2PKH_HEADER: u8 = 31;
+const P2SH_P2WPKH_HEADER: u8 = 35;
+const P2WPKH_HEADER: u8 = 39;
+const MAX_HEADER: u8 = P2WPKH_HEADER + 3;
+
+/// Returns the hash of the prefixed `message` that is signed.
+pub fn message_hash(message: &[u8]) -> [u8; 32] {
+    let mut data = MESSAGE_PREFIX.to_vec();
+    data.extend(bitcoin::consensus::serialize(&VarInt(message.len() as u64)));
+    data.extend_from_slice(message);
+    sha256d::Hash::hash(&data).to_byte_array()
+}
+
+/// Signs the `message` with the secp256k1 `private_key` that controls the `address`,
+/// which is a P2PKH address of either the compressed or uncompressed public key, or a P2SH-P2WPKH or P2WPKH address.
+/// Returns the base64 encoded signature.
+pub fn sign_message(private_key: &[u8], address: &Address, message: &[u8]) -> Result<String> {
+    let private = secp256k1::PrivateKey::try_from(private_key)
+        .map_err(|_| Error::from(Proto::Error::Error_invalid_private_key))?;
+    let public_key = PublicKey::from_slice(private.public().compressed().as_slice())?;
+    let uncompressed = PublicKey {
+        compressed: false,
+        inner: public_key.inner,
+    };
+    let network = address.network;
+
+    let header = if *address == Address::p2pkh(&public_key, network) {
+        P2PKH_HEADER
+    } else if *address == Address::p2pkh(&uncompressed, network) {
+        P2PKH_UNCOMPRESSED_HEADER
+    } else if Address::p2shwpkh(&public_key, network).ok().as_ref() == Some(address) {
+        P2SH_P2WPKH_HEADER
+    } else if Address::p2wpkh(&public_key, network).ok().as_ref() == Some(address) {
+        P2WPKH_HEADER
+    } else {
+        return Err(Error::from(Proto::Error::Error_unsupported_message_address));
+    };
+
+    let signature = private
+        .sign(H256::from(message_hash(message)))
+        .map_err(|_| Error::from(Proto::Error::Error_invalid_message_signature))?;
+    let mut bytes = vec![header + signature.v()];
+    bytes.extend_from_slice(signature.r().as_slice());
+    bytes.extend_from_slice(signature.s().as_slice());
+    Ok(base64::encode(&bytes, false))
+}
+
+/// Returns the address of the `network` that signed the `message`, of the type that the header of the `signature` specifies.
+pub fn recover_address(message: &[u8], signature: &str, network: Network) -> Result<Address> {
+    let (header, public_key) = recover_public_key(message, signature)?;
+    if header < P2SH_P2WPKH_HEADER {
+        return Ok(Address::p2pkh(&public_key, network));
+    }
+    // The segwit headers specify a compressed public key.
+    let address = if header < P2WPKH_HEADER {
+        Address::p2shwpkh(&public_key, network)
+    } else {
+        Address::p2wpkh(&public_key, network)
+    };
+    address.map_err(|_| Error::from(Proto::Error::Error_invalid_message_signature))
+}
+
+/// Verifies the `signature` of the `message` by the owner of the `address`.
+/// Since many wallets sign with the P2PKH header for any address type, a compressed public key
+/// is accepted for the P2PKH, P2SH-P2WPKH and P2WPKH addresses regardless of the header.
+pub fn verify_message(address: &Address, message: &[u8], signature: &str) -> Result<()> {
+    let (_, public_key) = recover_public_key(message, signature)?;
+    let network = address.network;
+
+    // The segwit addresses of an uncompressed public key are never equal.
+    let matches = *address == Address::p2pkh(&public_key, network)
+        || Address::p2shwpkh(&public_key, network).ok().as_ref() == Some(address)
+        || Address::p2wpkh(&public_key, network).ok().as_ref() == Some(address);
+    if !matches {
+        return Err(Error::from(Proto::Error::Error_invalid_message_signature));
+    }
+    Ok(())
+}
+
+/// Returns the header of the base64 encoded `signature` and the public key that signed the `message`,
+/// which is uncompressed if the header specifies so.
+fn recover_public_key(message: &[u8], signature: &str) -> Result<(u8, PublicKey)> {
+    let bytes = base64::decode(signature.trim(), false)
+        .map_err(|_| Error::from(Proto::Error::Error_invalid_message_signature))?;
+    if bytes.len() != SIGNATURE_LEN || !(P2PKH_UNCOMPRESSED_HEADER..=MAX_HEADER).contains(&bytes[0])
+    {
+        return Err(Error::from(Proto::Error::Error_invalid_message_signature));
+    }
+    let header = bytes[0];
+    let recovery_id = (header - P2PKH_UNCOMPRESSED_HEADER) % 4;
+
+    let recovered = H256::try_from(&bytes[1..33])
+        .and_then(|r| H256::try_from(&bytes[33..]).map(|s| (r, s)))
+        .ok()
+        .and_then(|(r, s)| secp256k1::Signature::try_from_parts(r, s, recovery_id).ok())
+        .and_then(|signature| {
+            secp256k1::PublicKey::recover(signature, H256::from(message_hash(message))).ok()
+        })
+        .ok_or_else(|| Error::from(Proto::Error::Error_invalid_message_signature))?;
+
+    let mut public_key = PublicKey::from_slice(recovered.compressed().as_slice())?;
+    public_key.compressed = header >= P2PKH_HEADER;
+    Ok((header, public_key))
+}
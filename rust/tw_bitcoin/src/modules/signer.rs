@@ -1,14 +1,21 @@
+use crate::modules::silent_payments;
 use crate::{BitcoinEntry, Error, Result};
-use bitcoin::key::{TapTweak, TweakedKeyPair};
 use bitcoin::sighash::{EcdsaSighashType, TapSighashType};
-use secp256k1::{KeyPair, Message, Secp256k1};
+use secp256k1::{Message, SecretKey};
 use std::collections::HashMap;
 use tw_coin_entry::coin_context::CoinContext;
 use tw_coin_entry::coin_entry::{PrivateKeyBytes, SignatureBytes};
+use tw_hash::H256;
+use tw_keypair::ecdsa::secp256k1 as tw_secp256k1;
+use tw_keypair::schnorr::{self, TaprootSpendPath};
+use tw_keypair::traits::AsyncSigner;
 use tw_misc::traits::ToBytesVec;
 use tw_proto::BitcoinV2::Proto;
 use tw_proto::Utxo::Proto as UtxoProto;
 
+/// The length of an ECDSA signature without the recovery ID.
+const ECDSA_COMPACT_LEN: usize = 64;
+
 pub struct Signer;
 
 impl Signer {
@@ -16,11 +23,54 @@ impl Signer {
         _coin: &dyn CoinContext,
         proto: Proto::SigningInput<'_>,
     ) -> Result<Proto::SigningOutput<'static>> {
+        let (proto, pre_signed) = Self::prepare_proto(_coin, proto)?;
+
+        // Collect individual private keys per input, if there are any.
+        let mut individual_keys = HashMap::new();
+        for (index, txin) in proto.inputs.iter().enumerate() {
+            if !txin.private_key.is_empty() {
+                individual_keys.insert(index, txin.private_key.to_vec());
+            }
+        }
+
+        // Sign the sighashes.
+        let signatures = crate::modules::signer::Signer::signatures_from_proto(
+            &pre_signed,
+            proto.private_key.to_vec(),
+            individual_keys,
+            proto.dangerous_use_fixed_schnorr_rng,
+        )?;
+
+        Self::compile_signed(_coin, proto, &pre_signed, signatures)
+    }
+
+    /// Signs the transaction with the given external `signer` instead of the private keys
+    /// specified in `proto`, e.g. an HSM, a remote KMS or a hardware wallet.
+    pub async fn sign_proto_with_signer<S>(
+        _coin: &dyn CoinContext,
+        proto: Proto::SigningInput<'_>,
+        signer: &S,
+    ) -> Result<Proto::SigningOutput<'static>>
+    where
+        S: AsyncSigner<SigningMessage = SighashToSign, Signature = ExternalSignature>,
+    {
+        let (proto, pre_signed) = Self::prepare_proto(_coin, proto)?;
+        let signatures = Self::signatures_with_signer(&pre_signed, signer).await?;
+        Self::compile_signed(_coin, proto, &pre_signed, signatures)
+    }
+
+    /// Generates the sighashes and updates `proto` with the selected inputs and the change output.
+    fn prepare_proto<'a>(
+        _coin: &dyn CoinContext,
+        proto: Proto::SigningInput<'a>,
+    ) -> Result<(Proto::SigningInput<'a>, Proto::PreSigningOutput<'static>)> {
         // Technically not required here, since this gets called by
         // `preimage_hashes_impl` and `compile_impl`. But we're leaving this
         // here in case this methods gets extended and the pre-processing does
         // not get accidentally forgotten.
         let mut proto = crate::entry::pre_processor(proto);
+        // The silent payment outputs are derived from the inputs.
+        silent_payments::resolve_proto_outputs(&mut proto)?;
 
         // Generate the sighashes.
         let pre_signed = BitcoinEntry.preimage_hashes_impl(_coin, proto.clone())?;
@@ -32,10 +82,10 @@ impl Signer {
         debug_assert!(proto.inputs.len() >= pre_signed.utxo_inputs.len());
         debug_assert_eq!(pre_signed.utxo_inputs.len(), pre_signed.sighashes.len());
 
-        if proto.disable_change_output {
-            debug_assert_eq!(proto.outputs.len(), pre_signed.utxo_outputs.len());
-        } else {
-            // If a change output was generated...
+        // If a change output was generated, i.e. it's neither disabled, dropped
+        // as dust nor unnecessary for the selected inputs...
+        if pre_signed.utxo_outputs.len() > proto.outputs.len() {
+            debug_assert!(!proto.disable_change_output);
             debug_assert_eq!(proto.outputs.len() + 1, pre_signed.utxo_outputs.len()); // plus change output.
 
             // Update the given change output with the specified amount and push
@@ -73,22 +123,16 @@ impl Signer {
         debug_assert_eq!(proto.outputs.len(), pre_signed.utxo_outputs.len());
         debug_assert_eq!(proto.inputs.len(), pre_signed.utxo_inputs.len());
 
-        // Collect individual private keys per input, if there are any.
-        let mut individual_keys = HashMap::new();
-        for (index, txin) in proto.inputs.iter().enumerate() {
-            if !txin.private_key.is_empty() {
-                individual_keys.insert(index, txin.private_key.to_vec());
-            }
-        }
-
-        // Sign the sighashes.
-        let signatures = crate::modules::signer::Signer::signatures_from_proto(
-            &pre_signed,
-            proto.private_key.to_vec(),
-            individual_keys,
-            proto.dangerous_use_fixed_schnorr_rng,
-        )?;
+        Ok((proto, pre_signed))
+    }
 
+    /// Constructs the final transaction from the prepared `proto` and the `signatures`.
+    fn compile_signed(
+        _coin: &dyn CoinContext,
+        proto: Proto::SigningInput<'_>,
+        pre_signed: &Proto::PreSigningOutput<'_>,
+        signatures: Vec<SignatureBytes>,
+    ) -> Result<Proto::SigningOutput<'static>> {
         // Sanity check.
         debug_assert_eq!(signatures.len(), proto.inputs.len());
         debug_assert_eq!(signatures.len(), pre_signed.sighashes.len());
@@ -108,6 +152,8 @@ impl Signer {
         // one weight unit. Hence, we overwrite the final fee with the
         // estimated fee.
         compiled.weight = pre_signed.weight_estimate;
+        compiled.change_absorbed = pre_signed.change_absorbed;
+        compiled.absorbed_change = pre_signed.absorbed_change;
 
         // Sanity check.
         let compiled_total_output_amount = compiled
@@ -125,14 +171,13 @@ impl Signer {
 
         Ok(compiled)
     }
+
     pub fn signatures_from_proto(
         input: &Proto::PreSigningOutput<'_>,
         private_key: PrivateKeyBytes,
         individual_keys: HashMap<usize, PrivateKeyBytes>,
         dangerous_use_fixed_schnorr_rng: bool,
     ) -> Result<Vec<SignatureBytes>> {
-        let secp = Secp256k1::new();
-
         let mut signatures = vec![];
 
         for (index, (entry, utxo)) in input
@@ -142,32 +187,22 @@ impl Signer {
             .enumerate()
         {
             // Check if there's an individual private key for the given input. If not, use the primary one.
-            let keypair = if let Some(slice) = individual_keys.get(&index) {
-                KeyPair::from_seckey_slice(&secp, slice)
-                    .map_err(|_| Error::from(Proto::Error::Error_invalid_private_key))?
-            } else {
-                KeyPair::from_seckey_slice(&secp, private_key.as_ref())
-                    .map_err(|_| Error::from(Proto::Error::Error_invalid_private_key))?
-            };
-
-            // Create signable message from sighash.
-            let sighash = Message::from_slice(entry.sighash.as_ref())
-                .map_err(|_| Error::from(Proto::Error::Error_invalid_sighash))?;
+            let private_key = individual_keys.get(&index).unwrap_or(&private_key);
 
             // Sign the sighash depending on signing method.
             match entry.signing_method {
                 // Create a ECDSA signature for legacy and segwit transaction.
                 UtxoProto::SigningMethod::Legacy | UtxoProto::SigningMethod::Segwit => {
-                    let sighash_type =
-                        if let UtxoProto::SighashType::UseDefault = entry.sighash_type {
-                            EcdsaSighashType::All
-                        } else {
-                            EcdsaSighashType::from_consensus(entry.sighash_type as u32)
-                        };
+                    let secret_key = SecretKey::from_slice(private_key)
+                        .map_err(|_| Error::from(Proto::Error::Error_invalid_private_key))?;
+
+                    // Create signable message from sighash.
+                    let sighash = Message::from_slice(entry.sighash.as_ref())
+                        .map_err(|_| Error::from(Proto::Error::Error_invalid_sighash))?;
 
                     let sig = bitcoin::ecdsa::Signature {
-                        sig: keypair.secret_key().sign_ecdsa(sighash),
-                        hash_ty: sighash_type,
+                        sig: secret_key.sign_ecdsa(sighash),
+                        hash_ty: ecdsa_sighash_type(entry),
                     };
 
                     signatures.push(sig.serialize().to_vec());
@@ -175,57 +210,91 @@ impl Signer {
                 // Create a Schnorr signature for taproot transactions.
                 UtxoProto::SigningMethod::TaprootAll
                 | UtxoProto::SigningMethod::TaprootOnePrevout => {
-                    // Note that `input.sighash_type = 0` is handled by the underlying library.
-                    let sighash_type = TapSighashType::from_consensus_u8(entry.sighash_type as u8)
-                        .map_err(|_| Error::from(Proto::Error::Error_utxo_invalid_sighash_type))?;
-
-                    // Any empty leaf hash implies P2TR key-path (balance transfer)
-                    if utxo.leaf_hash.is_empty() {
-                        // Tweak keypair for P2TR key-path (ie. zeroed Merkle root).
-                        let tapped: TweakedKeyPair = keypair.tap_tweak(&secp, None);
-                        let tweaked = KeyPair::from(tapped);
-
-                        // Construct the Schnorr signature.
-                        let schnorr = if dangerous_use_fixed_schnorr_rng {
-                            // For tests, we disable the included randomness in order to create
-                            // reproducible signatures. Randomness should ALWAYS be used in
-                            // production.
-                            secp.sign_schnorr_no_aux_rand(&sighash, &tweaked)
-                        } else {
-                            secp.sign_schnorr(&sighash, &tweaked)
-                        };
-
-                        let sig = bitcoin::taproot::Signature {
-                            sig: schnorr,
-                            hash_ty: sighash_type,
-                        };
-
-                        signatures.push(sig.to_vec());
+                    let sighash_type = tap_sighash_type(entry)?;
+
+                    let mut private = schnorr::PrivateKey::try_from(private_key.as_slice())
+                        .map_err(|_| Error::from(Proto::Error::Error_invalid_private_key))?;
+                    if dangerous_use_fixed_schnorr_rng {
+                        // For tests, we disable the included randomness in order to create
+                        // reproducible signatures. Randomness should ALWAYS be used in
+                        // production.
+                        private = private.no_aux_rand();
                     }
-                    // If it has a leaf hash, then it's a P2TR script-path (complex transaction)
+
+                    let sighash = H256::try_from(entry.sighash.as_ref())
+                        .map_err(|_| Error::from(Proto::Error::Error_invalid_sighash))?;
+
+                    let spend_path = taproot_spend_path(utxo)?;
+
+                    // Construct the Schnorr signature.
+                    let schnorr = private
+                        .sign_taproot(sighash, &spend_path)
+                        .map_err(|_| Error::from(Proto::Error::Error_invalid_schnorr_signature))?;
+
+                    signatures.push(taproot_signature_bytes(&schnorr, sighash_type)?);
+                },
+            }
+        }
+
+        Ok(signatures)
+    }
+
+    /// Signs the sighashes with the external `signer` one by one.
+    async fn signatures_with_signer<S>(
+        input: &Proto::PreSigningOutput<'_>,
+        signer: &S,
+    ) -> Result<Vec<SignatureBytes>>
+    where
+        S: AsyncSigner<SigningMessage = SighashToSign, Signature = ExternalSignature>,
+    {
+        let mut signatures = vec![];
+
+        for (entry, utxo) in input.sighashes.iter().zip(input.utxo_inputs.iter()) {
+            let sighash = H256::try_from(entry.sighash.as_ref())
+                .map_err(|_| Error::from(Proto::Error::Error_invalid_sighash))?;
+
+            match entry.signing_method {
+                UtxoProto::SigningMethod::Legacy | UtxoProto::SigningMethod::Segwit => {
+                    let ExternalSignature::Ecdsa(ecdsa) = signer
+                        .sign(SighashToSign::Ecdsa(sighash))
+                        .await
+                        .map_err(|_| Error::from(Proto::Error::Error_invalid_ecdsa_signature))?
                     else {
-                        // NOTE: We do not tweak the key here since the complex
-                        // spending condition(s) must take into account on who
-                        // is allowed to spend the input, hence this signing
-                        // process is simpler than for P2TR key-path.
-
-                        // Construct the Schnorr signature.
-                        let schnorr = if dangerous_use_fixed_schnorr_rng {
-                            // For tests, we disable the included randomness in order to create
-                            // reproducible signatures. Randomness should ALWAYS be used in
-                            // production.
-                            secp.sign_schnorr_no_aux_rand(&sighash, &keypair)
-                        } else {
-                            secp.sign_schnorr(&sighash, &keypair)
-                        };
-
-                        let sig = bitcoin::taproot::Signature {
-                            sig: schnorr,
-                            hash_ty: sighash_type,
-                        };
-
-                        signatures.push(sig.to_vec());
-                    }
+                        return Err(Error::from(Proto::Error::Error_invalid_ecdsa_signature));
+                    };
+
+                    let mut sig = secp256k1::ecdsa::Signature::from_compact(
+                        &ecdsa.to_bytes()[..ECDSA_COMPACT_LEN],
+                    )
+                    .map_err(|_| Error::from(Proto::Error::Error_invalid_ecdsa_signature))?;
+                    // Bitcoin consensus rules require low-S signatures.
+                    sig.normalize_s();
+
+                    let sig = bitcoin::ecdsa::Signature {
+                        sig,
+                        hash_ty: ecdsa_sighash_type(entry),
+                    };
+
+                    signatures.push(sig.serialize().to_vec());
+                },
+                UtxoProto::SigningMethod::TaprootAll
+                | UtxoProto::SigningMethod::TaprootOnePrevout => {
+                    let sighash_type = tap_sighash_type(entry)?;
+                    let spend_path = taproot_spend_path(utxo)?;
+
+                    let to_sign = SighashToSign::Taproot {
+                        sighash,
+                        spend_path,
+                    };
+                    let ExternalSignature::Schnorr(schnorr) = signer
+                        .sign(to_sign)
+                        .await
+                        .map_err(|_| Error::from(Proto::Error::Error_invalid_schnorr_signature))?
+                    else {
+                        return Err(Error::from(Proto::Error::Error_invalid_schnorr_signature));
+                    };
+
+                    signatures.push(taproot_signature_bytes(&schnorr, sighash_type)?);
                 },
             }
         }
@@ -233,3 +302,78 @@ impl Signer {
         Ok(signatures)
     }
 }
+
+/// A sighash that is passed to an external [`AsyncSigner`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum SighashToSign {
+    /// A legacy or segwit input sighash that should be signed with ECDSA.
+    Ecdsa(H256),
+    /// A taproot input sighash that should be signed with Schnorr.
+    /// The signer is responsible for tweaking the key according to the `spend_path`,
+    /// see [`schnorr::PrivateKey::sign_taproot`].
+    Taproot {
+        sighash: H256,
+        spend_path: TaprootSpendPath,
+    },
+}
+
+/// A signature that is produced by an external [`AsyncSigner`].
+pub enum ExternalSignature {
+    /// Should be produced for [`SighashToSign::Ecdsa`].
+    Ecdsa(tw_secp256k1::Signature),
+    /// Should be produced for [`SighashToSign::Taproot`].
+    Schnorr(schnorr::Signature),
+}
+
+fn ecdsa_sighash_type(entry: &UtxoProto::Sighash<'_>) -> EcdsaSighashType {
+    if let UtxoProto::SighashType::UseDefault = entry.sighash_type {
+        EcdsaSighashType::All
+    } else {
+        EcdsaSighashType::from_consensus(entry.sighash_type as u32)
+    }
+}
+
+/// Returns whether the `SIGHASH_ANYONECANPAY` flag is set, and the type without the flag.
+pub(crate) fn split_sighash_type(sighash_type: EcdsaSighashType) -> (bool, EcdsaSighashType) {
+    match sighash_type {
+        EcdsaSighashType::AllPlusAnyoneCanPay => (true, EcdsaSighashType::All),
+        EcdsaSighashType::NonePlusAnyoneCanPay => (true, EcdsaSighashType::None),
+        EcdsaSighashType::SinglePlusAnyoneCanPay => (true, EcdsaSighashType::Single),
+        sighash_type => (false, sighash_type),
+    }
+}
+
+fn tap_sighash_type(entry: &UtxoProto::Sighash<'_>) -> Result<TapSighashType> {
+    // Note that `input.sighash_type = 0` is handled by the underlying library.
+    TapSighashType::from_consensus_u8(entry.sighash_type as u8)
+        .map_err(|_| Error::from(Proto::Error::Error_utxo_invalid_sighash_type))
+}
+
+fn taproot_spend_path(utxo: &UtxoProto::TxIn<'_>) -> Result<TaprootSpendPath> {
+    // Any empty leaf hash implies P2TR key-path (balance transfer),
+    // so the key gets tweaked (ie. zeroed Merkle root).
+    //
+    // If it has a leaf hash, then it's a P2TR script-path (complex transaction).
+    // We do not tweak the key here since the complex spending condition(s)
+    // must take into account on who is allowed to spend the input.
+    // The leaf hash itself is committed to by the sighash.
+    if utxo.leaf_hash.is_empty() {
+        Ok(TaprootSpendPath::KeyPath { merkle_root: None })
+    } else if utxo.leaf_hash.len() == H256::LEN {
+        Ok(TaprootSpendPath::ScriptPath)
+    } else {
+        Err(Error::from(Proto::Error::Error_utxo_invalid_leaf_hash))
+    }
+}
+
+fn taproot_signature_bytes(
+    schnorr: &schnorr::Signature,
+    sighash_type: TapSighashType,
+) -> Result<SignatureBytes> {
+    let sig = bitcoin::taproot::Signature {
+        sig: secp256k1::schnorr::Signature::from_slice(schnorr.to_bytes().as_slice())
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_schnorr_signature))?,
+        hash_ty: sighash_type,
+    };
+    Ok(sig.to_vec())
+}
@@ -0,0 +1,471 @@
+//! Silent payments as specified in [BIP352](https://github.com/bitcoin/bips/blob/master/bip-0352.mediawiki),
+//! i.e. the reusable addresses whose payments are sent to unique Taproot outputs that only the recipient can detect:
+//!
+//! ```rust,ignore
+//! let address = SilentPaymentAddress::from_str("sp1qq...")?;
+//! // The sender derives the output keys from the private keys and the outpoints of the inputs.
+//! let output_keys = derive_outputs(&outpoints, &input_keys, &[address])?;
+//! // The recipient scans the transactions with the scan key.
+//! let found = scan(&scan_key, &spend_key, &tx, &prevouts)?;
+//! let private_key = spend_private_key(&spend_private, &found[0].tweak)?;
+//! ```
+//!
+//! The eligible inputs are P2PKH, P2SH-P2WPKH, P2WPKH and P2TR key path spends of compressed keys.
+//! Labels are not supported.
+
+use crate::aliases::*;
+use crate::modules::network::network_from_proto;
+use crate::{Error, Result};
+use bitcoin::bech32::{self, FromBase32, ToBase32, Variant};
+use bitcoin::blockdata::script::Instruction;
+use bitcoin::hashes::{sha256, Hash, HashEngine};
+use bitcoin::key::TapTweak;
+use bitcoin::{Network, OutPoint, ScriptBuf, Transaction, TxIn, TxOut, Txid};
+use secp256k1::{KeyPair, Parity, PublicKey, Scalar, Secp256k1, SecretKey, XOnlyPublicKey};
+use std::fmt;
+use std::str::FromStr;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+use tw_utxo::compiler::taproot_annex;
+
+const INPUTS_TAG: &[u8] = b"BIP0352/Inputs";
+const SHARED_SECRET_TAG: &[u8] = b"BIP0352/SharedSecret";
+/// The length of the scan and the spend public keys.
+const ADDRESS_PAYLOAD_LEN: usize = 66;
+/// The future versions are parsed as the version 0, the version 31 signals an incompatible change.
+const MAX_ADDRESS_VERSION: u8 = 30;
+/// The x coordinate of the NUMS point `H` of BIP341, the internal key of the script path only outputs.
+const NUMS_INTERNAL_KEY: [u8; 32] = [
+    0x50, 0x92, 0x9b, 0x74, 0xc1, 0xa0, 0x49, 0x54, 0xb7, 0x8b, 0x4b, 0x60, 0x35, 0xe9, 0x7a, 0x5e,
+    0x07, 0x8a, 0x5a, 0x0f, 0x28, 0xec, 0x96, 0xd5, 0x47, 0xbf, 0xee, 0x9a, 0xce, 0x80, 0x3a, 0xc0,
+];
+
+/// A silent payment address, i.e. the scan and the spend public keys of the recipient.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SilentPaymentAddress {
+    scan_key: PublicKey,
+    spend_key: PublicKey,
+    network: Network,
+}
+
+impl SilentPaymentAddress {
+    /// Creates the address of the `network`, where the testnet and the signet share the prefix.
+    pub fn new(scan_key: PublicKey, spend_key: PublicKey, network: Network) -> Self {
+        SilentPaymentAddress {
+            scan_key,
+            spend_key,
+            network,
+        }
+    }
+
+    pub fn scan_key(&self) -> &PublicKey {
+        &self.scan_key
+    }
+
+    pub fn spend_key(&self) -> &PublicKey {
+        &self.spend_key
+    }
+
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    /// Returns whether the address belongs to the `network`, where the testnet and the signet share the addresses.
+    pub fn is_valid_for_network(&self, network: Network) -> bool {
+        hrp(self.network) == hrp(network)
+    }
+}
+
+fn hrp(network: Network) -> &'static str {
+    match network {
+        Network::Bitcoin => "sp",
+        Network::Regtest => "sprt",
+        _ => "tsp",
+    }
+}
+
+impl FromStr for SilentPaymentAddress {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::from(Proto::Error::Error_invalid_silent_payment);
+
+        let (hrp, data, variant) = bech32::decode(s).map_err(|_| invalid())?;
+        let network = match hrp.as_str() {
+            "sp" => Network::Bitcoin,
+            "tsp" => Network::Testnet,
+            "sprt" => Network::Regtest,
+            _ => return Err(invalid()),
+        };
+        let (version, data) = data.split_first().ok_or_else(invalid)?;
+        let payload = Vec::<u8>::from_base32(data).map_err(|_| invalid())?;
+        let valid_len = match version.to_u8() {
+            0 => payload.len() == ADDRESS_PAYLOAD_LEN,
+            // The future versions may append data.
+            version => version <= MAX_ADDRESS_VERSION && payload.len() >= ADDRESS_PAYLOAD_LEN,
+        };
+        if variant != Variant::Bech32m || !valid_len {
+            return Err(invalid());
+        }
+
+        let scan_key = PublicKey::from_slice(&payload[..33]).map_err(|_| invalid())?;
+        let spend_key = PublicKey::from_slice(&payload[33..66]).map_err(|_| invalid())?;
+        Ok(SilentPaymentAddress::new(scan_key, spend_key, network))
+    }
+}
+
+impl fmt::Display for SilentPaymentAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let hrp = hrp(self.network);
+        let mut payload = self.scan_key.serialize().to_vec();
+        payload.extend_from_slice(&self.spend_key.serialize());
+
+        let mut data = vec![bech32::u5::try_from_u8(0).expect("The version is a 5-bit value")];
+        data.extend(payload.to_base32());
+        let address = bech32::encode(hrp, data, Variant::Bech32m).map_err(|_| fmt::Error)?;
+        f.write_str(&address)
+    }
+}
+
+/// The private key of an eligible input of the sender.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputKey {
+    /// The key of a P2PKH, P2SH-P2WPKH or P2WPKH input.
+    Ecdsa(SecretKey),
+    /// The tweaked key of a P2TR key path input, i.e. the key of the output key.
+    Taproot(SecretKey),
+}
+
+/// An output of a silent payment that is found by the recipient.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScannedOutput {
+    pub vout: u32,
+    pub output_key: XOnlyPublicKey,
+    /// The tweak of the spend key, see [`spend_private_key`].
+    pub tweak: [u8; 32],
+}
+
+/// Returns the Taproot output keys that pay to the `recipients` in the same order.
+/// The `outpoints` are of all the inputs of the transaction,
+/// while the `keys` are the private keys of the eligible inputs only.
+pub fn derive_outputs(
+    outpoints: &[OutPoint],
+    keys: &[InputKey],
+    recipients: &[SilentPaymentAddress],
+) -> Result<Vec<XOnlyPublicKey>> {
+    let secp = Secp256k1::new();
+    let invalid = || Error::from(Proto::Error::Error_invalid_silent_payment);
+
+    // The sum of the private keys, negated to the even public keys of Taproot.
+    let mut sum: Option<SecretKey> = None;
+    for key in keys {
+        let key = match *key {
+            InputKey::Ecdsa(key) => key,
+            InputKey::Taproot(key) => match key.x_only_public_key(&secp).1 {
+                Parity::Even => key,
+                Parity::Odd => key.negate(),
+            },
+        };
+        sum = Some(match sum {
+            Some(sum) => sum.add_tweak(&Scalar::from(key)).map_err(|_| invalid())?,
+            None => key,
+        });
+    }
+    let sum = sum.ok_or_else(invalid)?;
+
+    let input_hash = input_hash(outpoints, &sum.public_key(&secp))?;
+    let sum = sum.mul_tweak(&input_hash).map_err(|_| invalid())?;
+
+    // The outputs of the same scan key are numbered in the order of the recipients.
+    let mut counters: Vec<(PublicKey, u32)> = Vec::new();
+    recipients
+        .iter()
+        .map(|recipient| {
+            let k = match counters
+                .iter_mut()
+                .find(|(scan_key, _)| scan_key == recipient.scan_key())
+            {
+                Some((_, k)) => {
+                    *k += 1;
+                    *k
+                },
+                None => {
+                    counters.push((recipient.scan_key, 0));
+                    0
+                },
+            };
+            let shared_secret = recipient
+                .scan_key
+                .mul_tweak(&secp, &Scalar::from(sum))
+                .map_err(|_| invalid())?;
+            output_key(&secp, &shared_secret, &recipient.spend_key, k).map(|(key, _)| key)
+        })
+        .collect()
+}
+
+/// Returns the outputs of the `tx` that pay to the recipient of the private `scan_key` and the public `spend_key`.
+/// The `prevouts` are the outputs that the inputs spend, in the same order.
+pub fn scan(
+    scan_key: &SecretKey,
+    spend_key: &PublicKey,
+    tx: &Transaction,
+    prevouts: &[TxOut],
+) -> Result<Vec<ScannedOutput>> {
+    let secp = Secp256k1::new();
+    let invalid = || Error::from(Proto::Error::Error_invalid_silent_payment);
+    if tx.input.len() != prevouts.len() {
+        return Err(invalid());
+    }
+
+    let public_keys: Vec<PublicKey> = tx
+        .input
+        .iter()
+        .zip(prevouts)
+        .filter_map(|(txin, prevout)| input_public_key(txin, prevout))
+        .collect();
+    // Not a silent payment.
+    if public_keys.is_empty() {
+        return Ok(Vec::new());
+    }
+    let sum =
+        PublicKey::combine_keys(&public_keys.iter().collect::<Vec<_>>()).map_err(|_| invalid())?;
+
+    let outpoints: Vec<OutPoint> = tx.input.iter().map(|txin| txin.previous_output).collect();
+    let input_hash = input_hash(&outpoints, &sum)?;
+    let shared_secret = sum
+        .mul_tweak(&secp, &input_hash)
+        .and_then(|point| point.mul_tweak(&secp, &Scalar::from(*scan_key)))
+        .map_err(|_| invalid())?;
+
+    let taproot_outputs: Vec<(u32, XOnlyPublicKey)> = tx
+        .output
+        .iter()
+        .enumerate()
+        .filter(|(_, output)| output.script_pubkey.is_v1_p2tr())
+        .filter_map(|(vout, output)| {
+            XOnlyPublicKey::from_slice(&output.script_pubkey.as_bytes()[2..])
+                .ok()
+                .map(|key| (vout as u32, key))
+        })
+        .collect();
+
+    // The outputs are numbered until one is not found.
+    let mut found = Vec::new();
+    for k in 0.. {
+        let (output_key, tweak) = output_key(&secp, &shared_secret, spend_key, k)?;
+        let Some((vout, _)) = taproot_outputs.iter().find(|(_, key)| *key == output_key) else {
+            break;
+        };
+        found.push(ScannedOutput {
+            vout: *vout,
+            output_key,
+            tweak: tweak.to_be_bytes(),
+        });
+    }
+    Ok(found)
+}
+
+/// Returns the private key of the output key of a [`ScannedOutput`], to sign the Taproot key path spend of the output
+/// without the BIP86 tweak.
+pub fn spend_private_key(spend_key: &SecretKey, tweak: &[u8; 32]) -> Result<SecretKey> {
+    Scalar::from_be_bytes(*tweak)
+        .ok()
+        .and_then(|tweak| spend_key.add_tweak(&tweak).ok())
+        .ok_or_else(|| Error::from(Proto::Error::Error_invalid_silent_payment))
+}
+
+/// Returns the public key of the eligible `txin` that spends the `prevout`, or `None` if the input is not eligible.
+pub fn input_public_key(txin: &TxIn, prevout: &TxOut) -> Option<PublicKey> {
+    let script_pubkey = &prevout.script_pubkey;
+
+    if script_pubkey.is_p2pkh() {
+        // The script signature might be malleated, so any push of the key counts.
+        return txin
+            .script_sig
+            .instructions()
+            .filter_map(|instruction| match instruction {
+                Ok(Instruction::PushBytes(bytes)) => Some(bytes.as_bytes().to_vec()),
+                _ => None,
+            })
+            .filter_map(|bytes| compressed_key(&bytes))
+            .find(|key| {
+                *script_pubkey == ScriptBuf::new_p2pkh(&bitcoin::PublicKey::new(*key).pubkey_hash())
+            });
+    }
+    if script_pubkey.is_v0_p2wpkh() {
+        return witness_key(txin, script_pubkey);
+    }
+    if script_pubkey.is_p2sh() {
+        let mut instructions = txin.script_sig.instructions();
+        let (Some(Ok(Instruction::PushBytes(redeem_script))), None) =
+            (instructions.next(), instructions.next())
+        else {
+            return None;
+        };
+        let redeem_script = ScriptBuf::from_bytes(redeem_script.as_bytes().to_vec());
+        if !redeem_script.is_v0_p2wpkh()
+            || *script_pubkey != ScriptBuf::new_p2sh(&redeem_script.script_hash())
+        {
+            return None;
+        }
+        return witness_key(txin, &redeem_script);
+    }
+    if script_pubkey.is_v1_p2tr() {
+        let mut items: Vec<&[u8]> = txin.witness.iter().collect();
+        if taproot_annex(&txin.witness).is_some() {
+            items.pop();
+        }
+        // The script path spends of the outputs without a key path are not eligible.
+        if items.len() > 1 {
+            let control_block = items.last()?;
+            if control_block.get(1..33) == Some(NUMS_INTERNAL_KEY.as_slice()) {
+                return None;
+            }
+        }
+        let output_key = XOnlyPublicKey::from_slice(&script_pubkey.as_bytes()[2..]).ok()?;
+        return Some(PublicKey::from_x_only_public_key(output_key, Parity::Even));
+    }
+    None
+}
+
+/// Replaces the outputs of `proto` to the silent payment addresses with the derived Taproot outputs.
+/// The outputs commit to the spent inputs, so all the inputs must be used.
+pub(crate) fn resolve_proto_outputs(proto: &mut Proto::SigningInput<'_>) -> Result<()> {
+    let recipients: Vec<(usize, SilentPaymentAddress)> = proto
+        .outputs
+        .iter()
+        .enumerate()
+        .filter_map(|(index, output)| match &output.to_recipient {
+            ProtoOutputRecipient::from_address(address) => SilentPaymentAddress::from_str(address)
+                .ok()
+                .map(|address| (index, address)),
+            _ => None,
+        })
+        .collect();
+    if recipients.is_empty() {
+        return Ok(());
+    }
+    let network = network_from_proto(proto.network);
+    if proto.input_selector != UtxoProto::InputSelector::UseAll
+        || recipients
+            .iter()
+            .any(|(_, address)| !address.is_valid_for_network(network))
+    {
+        return Err(Error::from(Proto::Error::Error_invalid_silent_payment));
+    }
+
+    let secp = Secp256k1::new();
+    let mut outpoints = Vec::new();
+    let mut keys = Vec::new();
+    for input in &proto.inputs {
+        let txid = Txid::from_slice(&input.txid)
+            .map_err(|_| Error::from(Proto::Error::Error_utxo_invalid_txid))?;
+        outpoints.push(OutPoint {
+            txid,
+            vout: input.vout,
+        });
+
+        let private_key = if input.private_key.is_empty() {
+            proto.private_key.as_ref()
+        } else {
+            input.private_key.as_ref()
+        };
+        let ProtoInputRecipient::builder(builder) = &input.to_recipient else {
+            continue;
+        };
+        let eligible = matches!(
+            builder.variant,
+            ProtoInputBuilder::p2pkh(_)
+                | ProtoInputBuilder::p2wpkh(_)
+                | ProtoInputBuilder::p2sh_p2wpkh(_)
+                | ProtoInputBuilder::p2tr_key_path(_)
+        );
+        if !eligible {
+            continue;
+        }
+        let private_key = SecretKey::from_slice(private_key)
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_private_key))?;
+        keys.push(match builder.variant {
+            ProtoInputBuilder::p2tr_key_path(_) => {
+                // The key path is signed by the BIP86 tweaked key.
+                let tweaked = KeyPair::from_secret_key(&secp, &private_key).tap_tweak(&secp, None);
+                InputKey::Taproot(SecretKey::from_keypair(&tweaked.to_inner()))
+            },
+            _ => InputKey::Ecdsa(private_key),
+        });
+    }
+
+    let output_keys = derive_outputs(
+        &outpoints,
+        &keys,
+        &recipients
+            .iter()
+            .map(|(_, address)| *address)
+            .collect::<Vec<_>>(),
+    )?;
+    for ((index, _), output_key) in recipients.iter().zip(output_keys) {
+        proto.outputs[*index].to_recipient =
+            ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+                variant: ProtoOutputBuilder::p2tr_dangerous_assume_tweaked(
+                    output_key.serialize().to_vec().into(),
+                ),
+            });
+    }
+    Ok(())
+}
+
+/// Returns `hash_BIP0352/Inputs(outpoint_L || A)`, where `outpoint_L` is the lowest outpoint of the transaction
+/// and `A` is the sum of the public keys of the eligible inputs.
+fn input_hash(outpoints: &[OutPoint], sum: &PublicKey) -> Result<Scalar> {
+    let lowest = outpoints
+        .iter()
+        .map(bitcoin::consensus::serialize)
+        .min()
+        .ok_or_else(|| Error::from(Proto::Error::Error_invalid_silent_payment))?;
+    let hash = tagged_hash(INPUTS_TAG, &[&lowest, &sum.serialize()]);
+    Scalar::from_be_bytes(hash).map_err(|_| Error::from(Proto::Error::Error_invalid_silent_payment))
+}
+
+/// Returns the `k`-th output key `B_spend + t_k * G` and its tweak `t_k`.
+fn output_key(
+    secp: &Secp256k1<secp256k1::All>,
+    shared_secret: &PublicKey,
+    spend_key: &PublicKey,
+    k: u32,
+) -> Result<(XOnlyPublicKey, Scalar)> {
+    let hash = tagged_hash(
+        SHARED_SECRET_TAG,
+        &[&shared_secret.serialize(), &k.to_be_bytes()],
+    );
+    let tweak = Scalar::from_be_bytes(hash)
+        .map_err(|_| Error::from(Proto::Error::Error_invalid_silent_payment))?;
+    let output_key = spend_key
+        .add_exp_tweak(secp, &tweak)
+        .map_err(|_| Error::from(Proto::Error::Error_invalid_silent_payment))?;
+    Ok((output_key.x_only_public_key().0, tweak))
+}
+
+/// Returns the compressed key of the P2WPKH witness `<signature> <public key>` of the `p2wpkh` script.
+fn witness_key(txin: &TxIn, p2wpkh: &ScriptBuf) -> Option<PublicKey> {
+    let key = compressed_key(txin.witness.last()?)?;
+    let hash = bitcoin::PublicKey::new(key).wpubkey_hash()?;
+    (*p2wpkh == ScriptBuf::new_v0_p2wpkh(&hash)).then_some(key)
+}
+
+fn compressed_key(bytes: &[u8]) -> Option<PublicKey> {
+    if bytes.len() != 33 {
+        return None;
+    }
+    PublicKey::from_slice(bytes).ok()
+}
+
+fn tagged_hash(tag: &[u8], data: &[&[u8]]) -> [u8; 32] {
+    let tag = sha256::Hash::hash(tag);
+    let mut engine = sha256::Hash::engine();
+    engine.input(tag.as_byte_array());
+    engine.input(tag.as_byte_array());
+    for data in data {
+        engine.input(data);
+    }
+    sha256::Hash::from_engine(engine).to_byte_array()
+}
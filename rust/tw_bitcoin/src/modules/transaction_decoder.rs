@@ -0,0 +1,234 @@
+use crate::modules::transactions::{is_p2a, LOCKTIME_THRESHOLD};
+use crate::{bitcoin_output_error, Error, Result};
+use bitcoin::blockdata::script::Instruction;
+use bitcoin::hashes::Hash;
+use bitcoin::taproot::{
+    TAPROOT_ANNEX_PREFIX, TAPROOT_CONTROL_BASE_SIZE, TAPROOT_CONTROL_NODE_SIZE,
+};
+use bitcoin::{Address, Network, Script, Transaction, TxIn, TxOut, Witness};
+use tw_coin_entry::coin_context::CoinContext;
+use tw_coin_entry::modules::transaction_decoder::TransactionDecoder;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+/// Decodes a raw signed or unsigned transaction, e.g. to display it before it's signed or broadcast.
+/// The types of the inputs are detected from their script signatures and witnesses,
+/// or from the spent outputs if they're known.
+pub struct BitcoinTransactionDecoder;
+
+impl TransactionDecoder for BitcoinTransactionDecoder {
+    type Output = Proto::DecodingTransactionOutput<'static>;
+
+    fn decode_transaction(&self, _coin: &dyn CoinContext, tx: &[u8]) -> Self::Output {
+        Self::decode(tx, &[], Network::Bitcoin)
+            .map(|transaction| Proto::DecodingTransactionOutput {
+                transaction: Some(transaction),
+                ..Default::default()
+            })
+            .unwrap_or_else(|err| bitcoin_output_error!(Proto::DecodingTransactionOutput, err))
+    }
+}
+
+impl BitcoinTransactionDecoder {
+    /// Decodes the raw `tx` with the addresses of the `network`. The `prevouts` are the spent outputs
+    /// of all the inputs in order, which specify the values, the addresses and the fee, or empty if unknown.
+    pub fn decode(
+        tx: &[u8],
+        prevouts: &[TxOut],
+        network: Network,
+    ) -> Result<Proto::DecodedTransaction<'static>> {
+        let tx: Transaction = bitcoin::consensus::deserialize(tx)
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_raw_transaction))?;
+        if !prevouts.is_empty() && prevouts.len() != tx.input.len() {
+            return Err(Error::from(Proto::Error::Error_invalid_prevouts));
+        }
+
+        let inputs = tx
+            .input
+            .iter()
+            .enumerate()
+            .map(|(index, txin)| decode_input(txin, prevouts.get(index), network))
+            .collect();
+        let outputs = tx
+            .output
+            .iter()
+            .map(|txout| Proto::DecodedOutput {
+                script_pubkey: txout.script_pubkey.to_bytes().into(),
+                value: txout.value,
+                script_type: output_script_type(&txout.script_pubkey),
+                address: address(&txout.script_pubkey, network).into(),
+            })
+            .collect();
+
+        let fee = if prevouts.is_empty() {
+            0
+        } else {
+            let spent: u64 = prevouts.iter().map(|prevout| prevout.value).sum();
+            let sent: u64 = tx.output.iter().map(|txout| txout.value).sum();
+            // The prevouts don't fund the outputs, so they're not the spent ones.
+            spent
+                .checked_sub(sent)
+                .ok_or_else(|| Error::from(Proto::Error::Error_invalid_prevouts))?
+        };
+
+        let lock_time = tx.lock_time.to_consensus_u32();
+        let variant = if lock_time < LOCKTIME_THRESHOLD {
+            UtxoProto::mod_LockTime::OneOfvariant::blocks(lock_time)
+        } else {
+            UtxoProto::mod_LockTime::OneOfvariant::seconds(lock_time)
+        };
+        let txid: Vec<u8> = tx.txid().as_byte_array().iter().copied().rev().collect();
+
+        Ok(Proto::DecodedTransaction {
+            txid: txid.into(),
+            version: tx.version,
+            lock_time: Some(UtxoProto::LockTime { variant }),
+            inputs,
+            outputs,
+            weight: tx.weight().to_wu(),
+            vsize: tx.vsize() as u64,
+            fee,
+        })
+    }
+}
+
+fn decode_input(
+    txin: &TxIn,
+    prevout: Option<&TxOut>,
+    network: Network,
+) -> Proto::DecodedInput<'static> {
+    let is_signed = !txin.script_sig.is_empty() || !txin.witness.is_empty();
+    let script_type = match prevout {
+        Some(prevout) => spent_script_type(txin, &prevout.script_pubkey, is_signed),
+        None => claimed_script_type(txin),
+    };
+
+    Proto::DecodedInput {
+        txid: txin.previous_output.txid.as_byte_array().to_vec().into(),
+        vout: txin.previous_output.vout,
+        sequence: txin.sequence.0,
+        script_sig: txin.script_sig.to_bytes().into(),
+        witness_items: txin.witness.to_vec().into_iter().map(Into::into).collect(),
+        script_type,
+        is_signed,
+        value: prevout.map_or(0, |prevout| prevout.value),
+        address: prevout
+            .map(|prevout| address(&prevout.script_pubkey, network))
+            .unwrap_or_default()
+            .into(),
+    }
+}
+
+fn output_script_type(script_pubkey: &Script) -> Proto::ScriptType {
+    if script_pubkey.is_p2pkh() {
+        Proto::ScriptType::P2pkh
+    } else if script_pubkey.is_p2sh() {
+        Proto::ScriptType::P2sh
+    } else if script_pubkey.is_v0_p2wpkh() {
+        Proto::ScriptType::P2wpkh
+    } else if script_pubkey.is_v0_p2wsh() {
+        Proto::ScriptType::P2wsh
+    } else if script_pubkey.is_v1_p2tr() {
+        Proto::ScriptType::P2tr
+    } else if script_pubkey.is_op_return() {
+        Proto::ScriptType::OpReturn
+    } else if is_p2a(script_pubkey) {
+        Proto::ScriptType::P2a
+    } else {
+        Proto::ScriptType::UnknownScript
+    }
+}
+
+/// Returns the type of the input that spends the `script_pubkey`, refined by its claim if it's signed.
+fn spent_script_type(txin: &TxIn, script_pubkey: &Script, is_signed: bool) -> Proto::ScriptType {
+    match output_script_type(script_pubkey) {
+        Proto::ScriptType::P2sh if is_signed => match nested_witness_program(txin) {
+            Some(program) => program,
+            None => Proto::ScriptType::P2sh,
+        },
+        Proto::ScriptType::P2tr if is_signed => taproot_path(&txin.witness),
+        script_type => script_type,
+    }
+}
+
+/// Returns the type of the input detected from its script signature and witness only.
+fn claimed_script_type(txin: &TxIn) -> Proto::ScriptType {
+    if let Some(program) = nested_witness_program(txin) {
+        return program;
+    }
+    if !txin.script_sig.is_empty() {
+        let pushes: Option<Vec<_>> = txin
+            .script_sig
+            .instructions()
+            .map(|instruction| match instruction {
+                Ok(Instruction::PushBytes(push)) => Some(push.len()),
+                _ => None,
+            })
+            .collect();
+        // A signature and a compressed or uncompressed public key, or the redeem script last.
+        return match pushes.as_deref() {
+            Some([70..=73, 33 | 65]) => Proto::ScriptType::P2pkh,
+            Some([.., _]) => Proto::ScriptType::P2sh,
+            _ => Proto::ScriptType::UnknownScript,
+        };
+    }
+
+    match witness_without_annex(&txin.witness).as_slice() {
+        [] => Proto::ScriptType::UnknownScript,
+        [signature] if signature.len() == 64 || signature.len() == 65 => {
+            Proto::ScriptType::P2trKeyPath
+        },
+        [_, public_key] if public_key.len() == 33 => Proto::ScriptType::P2wpkh,
+        [.., control_block] if is_control_block(control_block) => Proto::ScriptType::P2trScriptPath,
+        _ => Proto::ScriptType::P2wsh,
+    }
+}
+
+/// Returns the type of the witness program that the script signature of a P2SH input pushes, if any.
+fn nested_witness_program(txin: &TxIn) -> Option<Proto::ScriptType> {
+    let mut instructions = txin.script_sig.instructions();
+    let (Some(Ok(Instruction::PushBytes(push))), None) = (instructions.next(), instructions.next())
+    else {
+        return None;
+    };
+    let redeem_script = Script::from_bytes(push.as_bytes());
+    if txin.witness.is_empty() {
+        None
+    } else if redeem_script.is_v0_p2wpkh() {
+        Some(Proto::ScriptType::P2shP2wpkh)
+    } else if redeem_script.is_v0_p2wsh() {
+        Some(Proto::ScriptType::P2shP2wsh)
+    } else {
+        None
+    }
+}
+
+fn taproot_path(witness: &Witness) -> Proto::ScriptType {
+    if witness_without_annex(witness).len() == 1 {
+        Proto::ScriptType::P2trKeyPath
+    } else {
+        Proto::ScriptType::P2trScriptPath
+    }
+}
+
+/// Returns the witness items without the Taproot annex, i.e. the last of at least two items with the annex prefix.
+fn witness_without_annex(witness: &Witness) -> Vec<&[u8]> {
+    let mut items: Vec<&[u8]> = witness.iter().collect();
+    if items.len() >= 2 && items.last().and_then(|item| item.first()) == Some(&TAPROOT_ANNEX_PREFIX)
+    {
+        items.pop();
+    }
+    items
+}
+
+fn is_control_block(item: &[u8]) -> bool {
+    item.len() >= TAPROOT_CONTROL_BASE_SIZE
+        && (item.len() - TAPROOT_CONTROL_BASE_SIZE) % TAPROOT_CONTROL_NODE_SIZE == 0
+        && item[0] & 0xfe == 0xc0
+}
+
+fn address(script_pubkey: &Script, network: Network) -> String {
+    Address::from_script(script_pubkey, network)
+        .map(|address| address.to_string())
+        .unwrap_or_default()
+}
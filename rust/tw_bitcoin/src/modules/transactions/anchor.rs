@@ -0,0 +1,24 @@
+use bitcoin::blockdata::opcodes::all::OP_PUSHNUM_1;
+use bitcoin::blockdata::script::Builder;
+use bitcoin::{Script, ScriptBuf};
+
+/// The witness program of a pay-to-anchor output.
+const P2A_PROGRAM: [u8; 2] = [0x4e, 0x73];
+
+/// The dust threshold of a pay-to-anchor output at the default dust relay fee.
+/// A zero value anchor is only relayed in a zero fee transaction, i.e. as ephemeral dust,
+/// so the dust policies never reject an anchor.
+pub const P2A_DUST_LIMIT: u64 = 240;
+
+/// Returns the pay-to-anchor (P2A) script `OP_1 <0x4e73>`, i.e. a keyless output that anyone can spend
+/// with an empty witness, so that any party can bump the fee of the transaction with a child (CPFP).
+pub fn p2a_script() -> ScriptBuf {
+    Builder::new()
+        .push_opcode(OP_PUSHNUM_1)
+        .push_slice(P2A_PROGRAM)
+        .into_script()
+}
+
+pub fn is_p2a(script_pubkey: &Script) -> bool {
+    script_pubkey.as_bytes() == p2a_script().as_bytes()
+}
@@ -0,0 +1,248 @@
+use super::inscription_builder::{placeholder_witness, DUST_LIMIT};
+use crate::modules::psbt::Psbt;
+use crate::{Error, Result};
+use bitcoin::blockdata::locktime::absolute::LockTime;
+use bitcoin::consensus::encode::VarInt;
+use bitcoin::hashes::Hash;
+use bitcoin::{OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness};
+use tw_proto::BitcoinV2::Proto;
+
+/// The version and the lock time of a transaction.
+const TX_BASE_WEIGHT: u64 = 4 * (4 + 4);
+/// The segwit marker and flag.
+const SEGWIT_WEIGHT: u64 = 2;
+/// The outpoint, the sequence and the empty script signature of an input.
+const TXIN_BASE_WEIGHT: u64 = 4 * (32 + 4 + 4 + 1);
+
+/// Builds a single transaction that pays many recipients, e.g. to batch the withdrawals of an exchange:
+///
+/// ```rust,ignore
+/// let batch = BatchPaymentBuilder::new(change)
+///     .fee_rate(10)
+///     .add_payment(alice, 50_000)
+///     .add_payment(bob, 20_000)
+///     .add_utxo(outpoint, utxo)
+///     .bip69(true)
+///     .build()?;
+/// println!("Bob pays {} sats of fees at vout {}", batch.fee_shares[1], batch.payment_vouts[1]);
+/// let mut psbt = batch.psbt;
+/// psbt.sign(PRIVATE_KEY)?;
+/// psbt.finalize()?;
+/// ```
+///
+/// The UTXOs are added in order until they cover the payments and the fee, the rest is sent to the change
+/// unless it's dust. The outputs follow the order of the payments, or the [BIP69](https://github.com/bitcoin/bips/blob/master/bip-0069.mediawiki)
+/// order of the inputs and the outputs if enabled.
+pub struct BatchPaymentBuilder {
+    change: ScriptBuf,
+    /// Satoshis per vbyte.
+    fee_rate: u64,
+    bip69: bool,
+    payments: Vec<TxOut>,
+    utxos: Vec<(OutPoint, TxOut)>,
+}
+
+/// The batch transaction and the accounting of its outputs.
+#[derive(Debug)]
+pub struct BatchPayment {
+    /// The transaction to be signed by the owner of the UTXOs.
+    pub psbt: Psbt,
+    pub fee: u64,
+    /// The share of the fee of each payment, in the order of the payments, which add up to the fee.
+    /// A payment pays for its own output, and the rest of the fee is split evenly.
+    pub fee_shares: Vec<u64>,
+    /// The output index of each payment, in the order of the payments.
+    pub payment_vouts: Vec<u32>,
+    /// The output index and the value of the change, if any.
+    pub change: Option<(u32, u64)>,
+}
+
+impl BatchPaymentBuilder {
+    /// Creates a builder of the transaction that returns the change to the `change` script.
+    pub fn new(change: ScriptBuf) -> Self {
+        BatchPaymentBuilder {
+            change,
+            fee_rate: 1,
+            bip69: false,
+            payments: Vec::new(),
+            utxos: Vec::new(),
+        }
+    }
+
+    /// Sets the fee rate in satoshis per vbyte, `1` by default.
+    pub fn fee_rate(mut self, fee_rate: u64) -> Self {
+        self.fee_rate = fee_rate;
+        self
+    }
+
+    /// Sorts the inputs and the outputs as specified in BIP69, so that their order doesn't reveal the payments.
+    pub fn bip69(mut self, bip69: bool) -> Self {
+        self.bip69 = bip69;
+        self
+    }
+
+    /// Adds a payment of the `value` to the `script_pubkey`.
+    pub fn add_payment(mut self, script_pubkey: ScriptBuf, value: u64) -> Self {
+        self.payments.push(TxOut {
+            value,
+            script_pubkey,
+        });
+        self
+    }
+
+    /// Adds a P2WPKH or P2TR key-path UTXO to fund the payments.
+    pub fn add_utxo(mut self, outpoint: OutPoint, utxo: TxOut) -> Self {
+        self.utxos.push((outpoint, utxo));
+        self
+    }
+
+    pub fn build(&self) -> Result<BatchPayment> {
+        if self.payments.is_empty() {
+            return Err(Error::from(Proto::Error::Error_utxo_no_outputs_specified));
+        }
+        if self
+            .payments
+            .iter()
+            .any(|payment| payment.value < DUST_LIMIT)
+        {
+            return Err(Error::from(Proto::Error::Error_utxo_dust_output));
+        }
+        let payments_value = self
+            .payments
+            .iter()
+            .try_fold(0u64, |total, payment| total.checked_add(payment.value))
+            .ok_or_else(|| Error::from(Proto::Error::Error_utxo_insufficient_inputs))?;
+
+        // The weight is accumulated instead of serializing the transaction for each UTXO,
+        // since a batch may have hundreds of outputs.
+        let payments_len: u64 = self.payments.iter().map(serialized_len).sum();
+        let change_len = serialized_len(&TxOut {
+            value: 0,
+            script_pubkey: self.change.clone(),
+        });
+        let mut inputs = Vec::new();
+        let mut inputs_weight = 0;
+        let mut inputs_value = 0u64;
+        let mut utxos = self.utxos.iter();
+        let (fee, change) = loop {
+            let Some((outpoint, utxo)) = utxos.next() else {
+                return Err(Error::from(Proto::Error::Error_utxo_insufficient_inputs));
+            };
+            // The fee is estimated with the placeholder witnesses of the maximum length.
+            let witness = placeholder_witness(&utxo.script_pubkey)?;
+            inputs_weight += TXIN_BASE_WEIGHT + witness.serialized_len() as u64;
+            inputs_value = inputs_value.saturating_add(utxo.value);
+            inputs.push((*outpoint, utxo.clone()));
+
+            let fee_of = |outputs: usize, outputs_len: u64| {
+                let weight = TX_BASE_WEIGHT
+                    + SEGWIT_WEIGHT
+                    + 4 * (varint_len(inputs.len()) + varint_len(outputs) + outputs_len)
+                    + inputs_weight;
+                self.fee_rate * ((weight + 3) / 4)
+            };
+            let outputs = self.payments.len();
+            let fee = fee_of(outputs + 1, payments_len + change_len);
+            if let Some(change) = inputs_value
+                .saturating_sub(payments_value)
+                .checked_sub(fee)
+                .filter(|change| *change >= DUST_LIMIT)
+            {
+                break (fee, Some(change));
+            }
+            // The dust change is left to the fee.
+            let fee = fee_of(outputs, payments_len);
+            if inputs_value >= payments_value + fee {
+                break (inputs_value - payments_value, None);
+            }
+        };
+
+        // The index of each output among the payments, or `None` for the change.
+        let mut output: Vec<(Option<usize>, TxOut)> = self
+            .payments
+            .iter()
+            .cloned()
+            .enumerate()
+            .map(|(index, payment)| (Some(index), payment))
+            .collect();
+        if let Some(value) = change {
+            output.push((
+                None,
+                TxOut {
+                    value,
+                    script_pubkey: self.change.clone(),
+                },
+            ));
+        }
+        if self.bip69 {
+            // The txids in the reversed byte order, i.e. as displayed.
+            inputs.sort_by_key(|(outpoint, _)| {
+                let mut txid = outpoint.txid.to_byte_array();
+                txid.reverse();
+                (txid, outpoint.vout)
+            });
+            output.sort_by(|(_, a), (_, b)| {
+                (a.value, a.script_pubkey.as_bytes()).cmp(&(b.value, b.script_pubkey.as_bytes()))
+            });
+        }
+
+        let mut payment_vouts = vec![0; self.payments.len()];
+        let mut change_output = None;
+        for (vout, (payment, txout)) in output.iter().enumerate() {
+            match payment {
+                Some(index) => payment_vouts[*index] = vout as u32,
+                None => change_output = Some((vout as u32, txout.value)),
+            }
+        }
+
+        let tx = Transaction {
+            version: 2,
+            lock_time: LockTime::ZERO,
+            input: inputs
+                .iter()
+                .map(|(outpoint, _)| TxIn {
+                    previous_output: *outpoint,
+                    script_sig: ScriptBuf::new(),
+                    sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                    witness: Witness::new(),
+                })
+                .collect(),
+            output: output.into_iter().map(|(_, txout)| txout).collect(),
+        };
+        let mut psbt = Psbt::from_unsigned_tx(tx)?;
+        for (input, (_, utxo)) in psbt.inner_mut().inputs.iter_mut().zip(inputs) {
+            input.witness_utxo = Some(utxo);
+        }
+
+        Ok(BatchPayment {
+            psbt,
+            fee,
+            fee_shares: self.fee_shares(fee),
+            payment_vouts,
+            change: change_output,
+        })
+    }
+
+    /// Splits the `fee` among the payments, the remainder of the even split to the first ones.
+    fn fee_shares(&self, fee: u64) -> Vec<u64> {
+        let own: Vec<u64> = self
+            .payments
+            .iter()
+            .map(|payment| self.fee_rate * serialized_len(payment))
+            .collect();
+        let shared = fee - own.iter().sum::<u64>();
+        let count = self.payments.len() as u64;
+        own.into_iter()
+            .zip(0..)
+            .map(|(own, index)| own + shared / count + u64::from(index < shared % count))
+            .collect()
+    }
+}
+
+fn serialized_len(txout: &TxOut) -> u64 {
+    bitcoin::consensus::serialize(txout).len() as u64
+}
+
+fn varint_len(count: usize) -> u64 {
+    bitcoin::consensus::serialize(&VarInt(count as u64)).len() as u64
+}
@@ -1,6 +1,8 @@
+use super::inscription_builder::{funded_psbt, InscriptionBuilder};
 use super::ordinals::OrdinalsInscription;
+use crate::modules::psbt::Psbt;
 use crate::{Error, Result};
-use bitcoin::PublicKey;
+use bitcoin::{OutPoint, PublicKey, ScriptBuf, TxOut};
 use serde::Serialize;
 use tw_proto::BitcoinV2::Proto;
 
@@ -18,32 +20,135 @@ impl Brc20Ticker {
     }
 }
 
-#[derive(Serialize)]
-struct BRC20TransferPayload {
+/// A positive decimal amount of BRC-20 tokens, e.g. `1000` or `0.5`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Brc20Amount(String);
+
+impl Brc20Amount {
+    /// The maximum number of the decimal places, and the default of a deployed token.
+    pub const MAX_DECIMALS: u8 = 18;
+
+    /// Validates the amount: the integer part must fit `u64`,
+    /// and the fractional part, if any, must have at most 18 digits.
+    pub fn new(string: String) -> Result<Self> {
+        let (integer, fraction) = string.split_once('.').unwrap_or((&string, ""));
+        let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+        let valid = !integer.is_empty()
+            && is_digits(integer)
+            && is_digits(fraction)
+            && fraction.len() <= Self::MAX_DECIMALS as usize
+            && !string.ends_with('.')
+            && integer.parse::<u64>().is_ok();
+        let amount = Brc20Amount(string);
+        if !valid || amount.scaled() == 0 {
+            return Err(Error::from(Proto::Error::Error_invalid_brc20_amount));
+        }
+        Ok(amount)
+    }
+
+    /// Returns the number of the fractional digits.
+    fn decimals(&self) -> usize {
+        self.0
+            .split_once('.')
+            .map_or(0, |(_, fraction)| fraction.len())
+    }
+
+    /// Returns the amount scaled by `10^18`, so the amounts are compared exactly.
+    fn scaled(&self) -> u128 {
+        let (integer, fraction) = self.0.split_once('.').unwrap_or((&self.0, ""));
+        let integer: u128 = integer.parse().expect("Expected a valid amount");
+        let fraction: u128 = format!("{fraction:0<18}")
+            .parse()
+            .expect("Expected a valid amount");
+        integer * 10u128.pow(Self::MAX_DECIMALS as u32) + fraction
+    }
+}
+
+/// The JSON payload of a [BRC-20](https://domo-2.gitbook.io/brc-20-experiment/) operation.
+#[derive(Debug, Clone, Serialize)]
+pub struct Brc20Payload {
     #[serde(rename = "p")]
-    protocol: String,
+    protocol: &'static str,
     #[serde(rename = "op")]
-    operation: String,
+    operation: &'static str,
     #[serde(rename = "tick")]
     ticker: Brc20Ticker,
-    #[serde(rename = "amt")]
-    amount: String,
+    #[serde(rename = "max", skip_serializing_if = "Option::is_none")]
+    max_supply: Option<Brc20Amount>,
+    #[serde(rename = "lim", skip_serializing_if = "Option::is_none")]
+    mint_limit: Option<Brc20Amount>,
+    #[serde(rename = "dec", skip_serializing_if = "Option::is_none")]
+    decimals: Option<String>,
+    #[serde(rename = "amt", skip_serializing_if = "Option::is_none")]
+    amount: Option<Brc20Amount>,
 }
 
-impl BRC20TransferPayload {
+impl Brc20Payload {
     const PROTOCOL_ID: &'static str = "brc-20";
-    const MIME: &'static [u8] = b"text/plain;charset=utf-8";
-}
+    pub const MIME: &'static [u8] = b"text/plain;charset=utf-8";
+
+    /// Deploys the token of the `ticker`. The `mint_limit` per mint is unlimited
+    /// and the `decimals` are 18 if not set.
+    pub fn deploy(
+        ticker: Brc20Ticker,
+        max_supply: Brc20Amount,
+        mint_limit: Option<Brc20Amount>,
+        decimals: Option<u8>,
+    ) -> Result<Self> {
+        let max_decimals = decimals.unwrap_or(Brc20Amount::MAX_DECIMALS);
+        let valid_amount = |amount: &Brc20Amount| amount.decimals() <= max_decimals as usize;
+        let valid = max_decimals <= Brc20Amount::MAX_DECIMALS
+            && valid_amount(&max_supply)
+            && mint_limit.as_ref().map_or(true, |limit| {
+                valid_amount(limit) && limit.scaled() <= max_supply.scaled()
+            });
+        if !valid {
+            return Err(Error::from(Proto::Error::Error_invalid_brc20_amount));
+        }
+
+        Ok(Brc20Payload {
+            max_supply: Some(max_supply),
+            mint_limit,
+            decimals: decimals.map(|decimals| decimals.to_string()),
+            ..Self::new("deploy", ticker)
+        })
+    }
+
+    /// Mints the `amount` of the deployed token, up to its mint limit.
+    pub fn mint(ticker: Brc20Ticker, amount: Brc20Amount) -> Self {
+        Brc20Payload {
+            amount: Some(amount),
+            ..Self::new("mint", ticker)
+        }
+    }
+
+    /// Inscribes the transfer of the `amount`. The tokens are transferred once
+    /// the inscription is sent to the receiver, see [`Brc20TransferBuilder`].
+    pub fn transfer(ticker: Brc20Ticker, amount: Brc20Amount) -> Self {
+        Brc20Payload {
+            amount: Some(amount),
+            ..Self::new("transfer", ticker)
+        }
+    }
+
+    pub fn to_json(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("badly constructed BRC20 payload")
+    }
 
-impl BRC20TransferPayload {
-    const OPERATION: &'static str = "transfer";
+    /// Returns the builder of the commit and reveal transactions that inscribe the payload for the `recipient`.
+    pub fn inscription_builder(&self, recipient: ScriptBuf) -> InscriptionBuilder {
+        InscriptionBuilder::new(Self::MIME, &self.to_json(), recipient)
+    }
 
-    fn new(ticker: Brc20Ticker, amount: String) -> Self {
-        BRC20TransferPayload {
-            protocol: Self::PROTOCOL_ID.to_string(),
-            operation: Self::OPERATION.to_string(),
+    fn new(operation: &'static str, ticker: Brc20Ticker) -> Self {
+        Brc20Payload {
+            protocol: Self::PROTOCOL_ID,
+            operation,
             ticker,
-            amount,
+            max_supply: None,
+            mint_limit: None,
+            decimals: None,
+            amount: None,
         }
     }
 }
@@ -56,13 +161,9 @@ impl BRC20TransferInscription {
         ticker: Brc20Ticker,
         value: String,
     ) -> Result<BRC20TransferInscription> {
-        let data: BRC20TransferPayload = BRC20TransferPayload::new(ticker, value);
+        let data = Brc20Payload::transfer(ticker, Brc20Amount::new(value)?);
 
-        let inscription = OrdinalsInscription::new(
-            BRC20TransferPayload::MIME,
-            &serde_json::to_vec(&data).expect("badly constructed BRC20 payload"),
-            recipient,
-        )?;
+        let inscription = OrdinalsInscription::new(Brc20Payload::MIME, &data.to_json(), recipient)?;
 
         Ok(BRC20TransferInscription(inscription))
     }
@@ -71,6 +172,64 @@ impl BRC20TransferInscription {
     }
 }
 
+/// Builds the transaction that sends an inscribed transfer to the receiver, which completes the BRC-20 transfer.
+/// The inscribed output is the first input, so the inscription is assigned to the first output of the receiver,
+/// while the fee is paid by the other UTXOs.
+pub struct Brc20TransferBuilder {
+    inscription: (OutPoint, TxOut),
+    receiver: ScriptBuf,
+    fee_rate: u64,
+    utxos: Vec<(OutPoint, TxOut)>,
+    change_script: Option<ScriptBuf>,
+}
+
+impl Brc20TransferBuilder {
+    /// Creates a builder that sends the inscribed `utxo`, i.e. the output of the reveal transaction, to the `receiver`.
+    pub fn new(outpoint: OutPoint, utxo: TxOut, receiver: ScriptBuf) -> Self {
+        Brc20TransferBuilder {
+            inscription: (outpoint, utxo),
+            receiver,
+            fee_rate: 1,
+            utxos: Vec::new(),
+            change_script: None,
+        }
+    }
+
+    /// Sets the fee rate in satoshis per vbyte, `1` by default.
+    pub fn fee_rate(mut self, fee_rate: u64) -> Self {
+        self.fee_rate = fee_rate;
+        self
+    }
+
+    /// Adds a P2WPKH or P2TR key-path UTXO to pay the fee.
+    pub fn add_utxo(mut self, outpoint: OutPoint, utxo: TxOut) -> Self {
+        self.utxos.push((outpoint, utxo));
+        self
+    }
+
+    pub fn change_script(mut self, change_script: ScriptBuf) -> Self {
+        self.change_script = Some(change_script);
+        self
+    }
+
+    /// Returns the PSBT to sign by the owner of the inscription and the UTXOs, and its fee.
+    pub fn build(&self) -> Result<(Psbt, u64)> {
+        let utxos: Vec<_> = std::iter::once(self.inscription.clone())
+            .chain(self.utxos.iter().cloned())
+            .collect();
+        let output = TxOut {
+            value: self.inscription.1.value,
+            script_pubkey: self.receiver.clone(),
+        };
+        funded_psbt(
+            &utxos,
+            vec![output],
+            self.fee_rate,
+            self.change_script.as_ref(),
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -90,4 +249,76 @@ mod tests {
         let _cloned = ticker.clone();
         let _ticker = ticker;
     }
+
+    #[test]
+    fn brc20_amount_validity() {
+        for amount in [
+            "1",
+            "1000",
+            "0.5",
+            "18446744073709551615",
+            "1.000000000000000001",
+        ] {
+            assert!(Brc20Amount::new(amount.to_string()).is_ok(), "{amount}");
+        }
+        for amount in [
+            "",
+            "0",
+            "0.000",
+            "-1",
+            "+1",
+            ".5",
+            "5.",
+            "1e3",
+            "1,5",
+            "1.2.3",
+            "18446744073709551616",
+            "1.0000000000000000001",
+        ] {
+            assert!(Brc20Amount::new(amount.to_string()).is_err(), "{amount}");
+        }
+    }
+
+    #[test]
+    fn brc20_payload_json() {
+        let ticker = Brc20Ticker::new("ordi".to_string()).unwrap();
+        let amount = |amount: &str| Brc20Amount::new(amount.to_string()).unwrap();
+
+        let deploy = Brc20Payload::deploy(
+            ticker.clone(),
+            amount("21000000"),
+            Some(amount("1000")),
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            deploy.to_json(),
+            br#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000","lim":"1000"}"#
+        );
+        let deploy =
+            Brc20Payload::deploy(ticker.clone(), amount("21000000.5"), None, Some(1)).unwrap();
+        assert_eq!(
+            deploy.to_json(),
+            br#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000.5","dec":"1"}"#
+        );
+
+        let mint = Brc20Payload::mint(ticker.clone(), amount("1000"));
+        assert_eq!(
+            mint.to_json(),
+            br#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"1000"}"#
+        );
+        let transfer = Brc20Payload::transfer(ticker.clone(), amount("0.5"));
+        assert_eq!(
+            transfer.to_json(),
+            br#"{"p":"brc-20","op":"transfer","tick":"ordi","amt":"0.5"}"#
+        );
+
+        // The amounts must have at most `dec` decimals.
+        assert!(Brc20Payload::deploy(ticker.clone(), amount("1.05"), None, Some(1)).is_err());
+        assert!(Brc20Payload::deploy(ticker.clone(), amount("1"), None, Some(19)).is_err());
+        // The mint limit must not exceed the supply.
+        assert!(
+            Brc20Payload::deploy(ticker, amount("1000"), Some(amount("1000.1")), None).is_err()
+        );
+    }
 }
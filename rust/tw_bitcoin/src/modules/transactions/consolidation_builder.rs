@@ -0,0 +1,148 @@
+use super::inscription_builder::{placeholder_witness, DUST_LIMIT};
+use crate::modules::psbt::Psbt;
+use crate::{Error, Result};
+use bitcoin::blockdata::locktime::absolute::LockTime;
+use bitcoin::{OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut};
+use tw_proto::BitcoinV2::Proto;
+
+/// The outpoint, the sequence and the empty script signature of an input.
+const TXIN_BASE_WEIGHT: u64 = 4 * (32 + 4 + 4 + 1);
+
+/// Builds the transaction that spends the UTXOs to a single output, e.g. to sweep a wallet
+/// or to consolidate its UTXOs while the fees are low:
+///
+/// ```rust,ignore
+/// let consolidation = ConsolidationBuilder::new(destination)
+///     .fee_rate(5)
+///     .add_utxo(outpoint, utxo)
+///     .min_value(10_000)
+///     .build()?;
+/// println!("{} sats after {} sats of fees", consolidation.amount, consolidation.fee);
+/// let mut psbt = consolidation.psbt;
+/// psbt.sign(PRIVATE_KEY)?;
+/// psbt.finalize()?;
+/// ```
+///
+/// The UTXOs that cost more to spend at the fee rate than they're worth are skipped,
+/// unless [`ConsolidationBuilder::include_uneconomical`] is set.
+pub struct ConsolidationBuilder {
+    destination: ScriptBuf,
+    /// Satoshis per vbyte.
+    fee_rate: u64,
+    min_value: u64,
+    include_uneconomical: bool,
+    utxos: Vec<(OutPoint, TxOut)>,
+}
+
+/// The consolidation transaction and the UTXOs left out of it.
+#[derive(Debug)]
+pub struct Consolidation {
+    /// The transaction to be signed by the owner of the UTXOs.
+    pub psbt: Psbt,
+    /// The value of the output, i.e. the total of the spent UTXOs minus the fee.
+    pub amount: u64,
+    pub fee: u64,
+    /// The UTXOs that aren't spent, since they're below the minimum value or uneconomical.
+    pub skipped: Vec<OutPoint>,
+}
+
+impl ConsolidationBuilder {
+    /// Creates a builder of the transaction that pays to the `destination`.
+    pub fn new(destination: ScriptBuf) -> Self {
+        ConsolidationBuilder {
+            destination,
+            fee_rate: 1,
+            min_value: 0,
+            include_uneconomical: false,
+            utxos: Vec::new(),
+        }
+    }
+
+    /// Sets the fee rate in satoshis per vbyte, `1` by default.
+    pub fn fee_rate(mut self, fee_rate: u64) -> Self {
+        self.fee_rate = fee_rate;
+        self
+    }
+
+    /// Skips the UTXOs below the `min_value`, e.g. to leave the dust attacks unspent.
+    pub fn min_value(mut self, min_value: u64) -> Self {
+        self.min_value = min_value;
+        self
+    }
+
+    /// Spends the UTXOs that cost more than their value at the fee rate too, e.g. to sweep a wallet
+    /// completely. They reduce the amount of the output.
+    pub fn include_uneconomical(mut self, include: bool) -> Self {
+        self.include_uneconomical = include;
+        self
+    }
+
+    /// Adds a P2WPKH or P2TR key-path UTXO to be spent.
+    pub fn add_utxo(mut self, outpoint: OutPoint, utxo: TxOut) -> Self {
+        self.utxos.push((outpoint, utxo));
+        self
+    }
+
+    pub fn build(&self) -> Result<Consolidation> {
+        let mut spent = Vec::new();
+        let mut skipped = Vec::new();
+        let mut input = Vec::new();
+        for (outpoint, utxo) in &self.utxos {
+            // The fee is estimated with the placeholder witnesses of the maximum length.
+            let witness = placeholder_witness(&utxo.script_pubkey)?;
+            let input_weight = TXIN_BASE_WEIGHT + witness.serialized_len() as u64;
+            let input_fee = (input_weight + 3) / 4 * self.fee_rate;
+
+            let uneconomical = utxo.value <= input_fee;
+            if utxo.value < self.min_value || (uneconomical && !self.include_uneconomical) {
+                skipped.push(*outpoint);
+                continue;
+            }
+
+            input.push(TxIn {
+                previous_output: *outpoint,
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                witness,
+            });
+            spent.push(utxo.clone());
+        }
+        if spent.is_empty() {
+            return Err(Error::from(Proto::Error::Error_utxo_insufficient_inputs));
+        }
+
+        let mut tx = Transaction {
+            version: 2,
+            lock_time: LockTime::ZERO,
+            input,
+            output: vec![TxOut {
+                value: 0,
+                script_pubkey: self.destination.clone(),
+            }],
+        };
+        let fee = self.fee_rate * tx.vsize() as u64;
+        let total: u64 = spent.iter().map(|utxo| utxo.value).sum();
+        let amount = total
+            .checked_sub(fee)
+            .ok_or_else(|| Error::from(Proto::Error::Error_utxo_insufficient_inputs))?;
+        if amount < DUST_LIMIT {
+            return Err(Error::from(Proto::Error::Error_utxo_dust_output));
+        }
+
+        tx.output[0].value = amount;
+        for txin in tx.input.iter_mut() {
+            txin.witness.clear();
+        }
+        let mut psbt = Psbt::from_unsigned_tx(tx)?;
+        for (input, utxo) in psbt.inner_mut().inputs.iter_mut().zip(spent) {
+            input.witness_utxo = Some(utxo);
+        }
+
+        Ok(Consolidation {
+            psbt,
+            amount,
+            fee,
+            skipped,
+        })
+    }
+}
@@ -0,0 +1,97 @@
+use crate::{Error, Result};
+use bitcoin::hashes::Hash;
+use bitcoin::key::TapTweak;
+use bitcoin::taproot::{ControlBlock, LeafVersion, TapLeafHash, TapNodeHash, TaprootBuilder};
+use bitcoin::{Script, ScriptBuf};
+use secp256k1::XOnlyPublicKey;
+use tw_proto::BitcoinV2::Proto;
+
+pub struct ControlBlockBuilder;
+
+impl ControlBlockBuilder {
+    /// Returns the control block that proves the `payload` leaf is committed to by the output:
+    /// the given one, or the one constructed of the internal key and the script tree or merkle path.
+    pub fn from_proto(
+        taproot: &Proto::mod_Input::InputTaprootScriptPath<'_>,
+    ) -> Result<ControlBlock> {
+        if !taproot.control_block.is_empty() {
+            return ControlBlock::decode(taproot.control_block.as_ref())
+                .map_err(|_| Error::from(Proto::Error::Error_invalid_control_block));
+        }
+
+        let pubkey = bitcoin::PublicKey::from_slice(taproot.internal_key.as_ref())?;
+        let internal_key = XOnlyPublicKey::from(pubkey.inner);
+        let script = Script::from_bytes(taproot.payload.as_ref());
+
+        if !taproot.script_tree.is_empty() {
+            let leaves = taproot
+                .script_tree
+                .iter()
+                .map(|leaf| {
+                    let depth = u8::try_from(leaf.depth)
+                        .map_err(|_| Error::from(Proto::Error::Error_invalid_control_block))?;
+                    Ok((depth, ScriptBuf::from_bytes(leaf.script.to_vec())))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            return Self::from_script_tree(internal_key, &leaves, script);
+        }
+
+        let merkle_path = taproot
+            .merkle_path
+            .iter()
+            .map(|hash| {
+                TapNodeHash::from_slice(hash.as_ref())
+                    .map_err(|_| Error::from(Proto::Error::Error_invalid_control_block))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Self::from_merkle_path(internal_key, &merkle_path, script)
+    }
+
+    /// Constructs the control block of the `script` leaf of the tree,
+    /// given as the leaves with their depths in depth-first order.
+    pub fn from_script_tree(
+        internal_key: XOnlyPublicKey,
+        leaves: &[(u8, ScriptBuf)],
+        script: &Script,
+    ) -> Result<ControlBlock> {
+        let mut builder = TaprootBuilder::new();
+        for (depth, leaf) in leaves {
+            builder = builder
+                .add_leaf(*depth, leaf.clone())
+                .map_err(|_| Error::from(Proto::Error::Error_invalid_control_block))?;
+        }
+        let spend_info = builder
+            .finalize(&secp256k1::Secp256k1::new(), internal_key)
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_control_block))?;
+
+        // Returns `None` if the script is not a leaf of the tree.
+        spend_info
+            .control_block(&(script.to_owned(), LeafVersion::TapScript))
+            .ok_or_else(|| Error::from(Proto::Error::Error_invalid_control_block))
+    }
+
+    /// Constructs the control block of the `script` leaf given the hashes
+    /// from its sibling up to the root of the tree.
+    pub fn from_merkle_path(
+        internal_key: XOnlyPublicKey,
+        merkle_path: &[TapNodeHash],
+        script: &Script,
+    ) -> Result<ControlBlock> {
+        let leaf_hash = TapLeafHash::from_script(script, LeafVersion::TapScript);
+        let merkle_root = merkle_path
+            .iter()
+            .fold(TapNodeHash::from(leaf_hash), |node, sibling| {
+                TapNodeHash::from_node_hashes(node, *sibling)
+            });
+        // The parity of the output key is required to verify the tweak.
+        let (_, parity) = internal_key.tap_tweak(&secp256k1::Secp256k1::new(), Some(merkle_root));
+
+        let mut encoded = vec![LeafVersion::TapScript.to_consensus() | parity.to_u8()];
+        encoded.extend_from_slice(&internal_key.serialize());
+        for hash in merkle_path {
+            encoded.extend_from_slice(hash.as_byte_array());
+        }
+        ControlBlock::decode(&encoded)
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_control_block))
+    }
+}
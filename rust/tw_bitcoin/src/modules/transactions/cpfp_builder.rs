@@ -0,0 +1,148 @@
+use super::inscription_builder::{placeholder_witness, DUST_LIMIT};
+use crate::modules::psbt::Psbt;
+use crate::{Error, Result};
+use bitcoin::{OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut};
+use tw_proto::BitcoinV2::Proto;
+
+/// Builds the child transaction that bumps the fee of an unconfirmed parent transaction (Child Pays For Parent):
+///
+/// ```rust,ignore
+/// let cpfp = CpfpBuilder::new(parent, parent_fee, change_vout, destination)
+///     .fee_rate(20)
+///     .build()?;
+/// println!("{:.1} sat/vB", cpfp.package_fee_rate());
+/// let mut child = cpfp.child;
+/// child.sign(PRIVATE_KEY)?;
+/// child.finalize()?;
+/// ```
+///
+/// The child spends the change output of the parent to the destination,
+/// with the fee that pulls the fee rate of both transactions, i.e. the package, to the target.
+/// If the change doesn't cover the fee, the UTXOs are added in order until they do.
+pub struct CpfpBuilder {
+    /// The signed parent transaction.
+    parent: Transaction,
+    parent_fee: u64,
+    vout: u32,
+    destination: ScriptBuf,
+    /// Satoshis per vbyte of the package.
+    fee_rate: u64,
+    utxos: Vec<(OutPoint, TxOut)>,
+}
+
+/// The child transaction and the resulting package.
+#[derive(Debug)]
+pub struct CpfpTransaction {
+    /// The child transaction to be signed by the owner of the change output and the UTXOs.
+    pub child: Psbt,
+    pub fee: u64,
+    /// The fee of the parent and the child transactions.
+    pub package_fee: u64,
+    /// The virtual size of the parent and the child transactions.
+    pub package_vsize: u64,
+}
+
+impl CpfpTransaction {
+    /// Returns the fee rate of the package in satoshis per vbyte, e.g. to display it.
+    pub fn package_fee_rate(&self) -> f64 {
+        self.package_fee as f64 / self.package_vsize as f64
+    }
+}
+
+impl CpfpBuilder {
+    /// Creates a builder of the child of the signed `parent` that pays the `parent_fee`,
+    /// which spends its P2WPKH, P2TR key-path or P2A anchor output at `vout` to the `destination`.
+    pub fn new(parent: Transaction, parent_fee: u64, vout: u32, destination: ScriptBuf) -> Self {
+        CpfpBuilder {
+            parent,
+            parent_fee,
+            vout,
+            destination,
+            fee_rate: 1,
+            utxos: Vec::new(),
+        }
+    }
+
+    /// Sets the target fee rate of the package in satoshis per vbyte, `1` by default.
+    /// The child pays at least the fee rate itself, even if the parent exceeds the target.
+    pub fn fee_rate(mut self, fee_rate: u64) -> Self {
+        self.fee_rate = fee_rate;
+        self
+    }
+
+    /// Adds a P2WPKH or P2TR key-path UTXO to pay the fee, if the change doesn't cover it.
+    pub fn add_utxo(mut self, outpoint: OutPoint, utxo: TxOut) -> Self {
+        self.utxos.push((outpoint, utxo));
+        self
+    }
+
+    pub fn build(&self) -> Result<CpfpTransaction> {
+        let change = self
+            .parent
+            .output
+            .get(self.vout as usize)
+            .ok_or_else(|| Error::from(Proto::Error::Error_invalid_change_output))?;
+        let change_outpoint = OutPoint {
+            txid: self.parent.txid(),
+            vout: self.vout,
+        };
+        let parent_vsize = self.parent.vsize() as u64;
+
+        let mut utxos = vec![(change_outpoint, change.clone())];
+        let mut additional = self.utxos.iter();
+        loop {
+            // The fee is estimated with the placeholder witnesses of the maximum length.
+            let input = utxos
+                .iter()
+                .map(|(outpoint, utxo)| {
+                    Ok(TxIn {
+                        previous_output: *outpoint,
+                        script_sig: ScriptBuf::new(),
+                        sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                        witness: placeholder_witness(&utxo.script_pubkey)?,
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let mut child = Transaction {
+                version: 2,
+                lock_time: self.parent.lock_time,
+                input,
+                output: vec![TxOut {
+                    value: 0,
+                    script_pubkey: self.destination.clone(),
+                }],
+            };
+
+            let total: u64 = utxos.iter().map(|(_, utxo)| utxo.value).sum();
+            let child_vsize = child.vsize() as u64;
+            let package_vsize = parent_vsize + child_vsize;
+            let fee = std::cmp::max(
+                (self.fee_rate * package_vsize).saturating_sub(self.parent_fee),
+                self.fee_rate * child_vsize,
+            );
+
+            if let Some(value) = total.checked_sub(fee).filter(|value| *value >= DUST_LIMIT) {
+                child.output[0].value = value;
+                for txin in child.input.iter_mut() {
+                    txin.witness.clear();
+                }
+
+                let mut psbt = Psbt::from_unsigned_tx(child)?;
+                for (input, (_, utxo)) in psbt.inner_mut().inputs.iter_mut().zip(utxos) {
+                    input.witness_utxo = Some(utxo);
+                }
+                return Ok(CpfpTransaction {
+                    child: psbt,
+                    fee,
+                    package_fee: self.parent_fee + fee,
+                    package_vsize,
+                });
+            }
+
+            let Some(utxo) = additional.next() else {
+                return Err(Error::from(Proto::Error::Error_utxo_insufficient_inputs));
+            };
+            utxos.push(utxo.clone());
+        }
+    }
+}
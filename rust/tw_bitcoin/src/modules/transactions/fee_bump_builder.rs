@@ -0,0 +1,175 @@
+use super::inscription_builder::{placeholder_witness, DUST_LIMIT};
+use crate::modules::psbt::Psbt;
+use crate::{Error, Result};
+use bitcoin::consensus::deserialize;
+use bitcoin::{OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness};
+use tw_proto::BitcoinV2::Proto;
+
+/// The minimum increase of the fee rate of a replacement, the default incremental relay fee of Bitcoin Core.
+const INCREMENTAL_RELAY_FEE_RATE: u64 = 1;
+
+/// Builds the [BIP-125](https://github.com/bitcoin/bips/blob/master/bip-0125.mediawiki) replacement
+/// of a stuck transaction that pays a higher fee:
+///
+/// ```rust,ignore
+/// let (mut psbt, fee) = FeeBumpBuilder::from_raw(&stuck_tx, prevouts, change_index)?
+///     .fee_rate(20)
+///     .add_utxo(outpoint, utxo)
+///     .build()?;
+/// psbt.sign(PRIVATE_KEY)?;
+/// psbt.finalize()?;
+/// ```
+///
+/// The outputs are the same, except for the change output that pays the increase of the fee.
+/// If the change doesn't cover it, the UTXOs are added in order until they do.
+/// Note that the added UTXOs must be confirmed, as a replacement may not spend new unconfirmed outputs.
+pub struct FeeBumpBuilder {
+    tx: Transaction,
+    /// The outputs spent by the inputs of `tx`, in order.
+    prevouts: Vec<TxOut>,
+    change_index: usize,
+    /// Satoshis per vbyte of the replacement.
+    fee_rate: u64,
+    utxos: Vec<(OutPoint, TxOut)>,
+}
+
+impl FeeBumpBuilder {
+    /// Creates a builder of the replacement of the signed or unsigned `tx` that spends the `prevouts`,
+    /// and whose output at `change_index` returns the change to the sender.
+    pub fn new(tx: Transaction, prevouts: Vec<TxOut>, change_index: usize) -> Self {
+        FeeBumpBuilder {
+            tx,
+            prevouts,
+            change_index,
+            fee_rate: 1,
+            utxos: Vec::new(),
+        }
+    }
+
+    /// Creates a builder of the replacement of the consensus encoded `tx`.
+    pub fn from_raw(tx: &[u8], prevouts: Vec<TxOut>, change_index: usize) -> Result<Self> {
+        let tx = deserialize(tx)
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_replaced_transaction))?;
+        Ok(Self::new(tx, prevouts, change_index))
+    }
+
+    /// Sets the fee rate of the replacement in satoshis per vbyte, `1` by default.
+    /// The fee is increased by the incremental relay fee at least, as BIP-125 requires.
+    pub fn fee_rate(mut self, fee_rate: u64) -> Self {
+        self.fee_rate = fee_rate;
+        self
+    }
+
+    /// Adds a P2WPKH or P2TR key-path UTXO to pay the fee, if the change doesn't cover it.
+    pub fn add_utxo(mut self, outpoint: OutPoint, utxo: TxOut) -> Self {
+        self.utxos.push((outpoint, utxo));
+        self
+    }
+
+    /// Returns the PSBT of the replacement to sign, and its fee.
+    /// The inputs signal the replaceability, and the witness UTXOs are set for the Segwit and Taproot inputs,
+    /// while the previous transactions of the legacy inputs are to be set by the caller.
+    pub fn build(&self) -> Result<(Psbt, u64)> {
+        if self.prevouts.len() != self.tx.input.len() {
+            return Err(Error::from(
+                Proto::Error::Error_invalid_replaced_transaction,
+            ));
+        }
+        if self.change_index >= self.tx.output.len() {
+            return Err(Error::from(Proto::Error::Error_invalid_change_output));
+        }
+
+        let original_input: u64 = self.prevouts.iter().map(|prevout| prevout.value).sum();
+        let original_output: u64 = self.tx.output.iter().map(|output| output.value).sum();
+        let original_fee = original_input
+            .checked_sub(original_output)
+            .ok_or_else(|| Error::from(Proto::Error::Error_invalid_replaced_transaction))?;
+        let recipients: u64 = original_output - self.tx.output[self.change_index].value;
+
+        // The fee is estimated with the scripts and witnesses of the signed inputs,
+        // and the placeholder witnesses of the unsigned ones.
+        let mut input = self
+            .tx
+            .input
+            .iter()
+            .zip(self.prevouts.iter())
+            .map(|(txin, prevout)| {
+                let witness = if txin.script_sig.is_empty() && txin.witness.is_empty() {
+                    placeholder_witness(&prevout.script_pubkey)?
+                } else {
+                    txin.witness.clone()
+                };
+                Ok(TxIn {
+                    previous_output: txin.previous_output,
+                    script_sig: txin.script_sig.clone(),
+                    sequence: replaceable(txin.sequence),
+                    witness,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let mut prevouts = self.prevouts.clone();
+        let mut utxos = self.utxos.iter();
+
+        loop {
+            let mut tx = Transaction {
+                version: self.tx.version,
+                lock_time: self.tx.lock_time,
+                input: input.clone(),
+                output: self.tx.output.clone(),
+            };
+            let total: u64 = prevouts.iter().map(|prevout| prevout.value).sum();
+            let vsize = tx.vsize() as u64;
+            let fee = std::cmp::max(
+                self.fee_rate * vsize,
+                original_fee + INCREMENTAL_RELAY_FEE_RATE * vsize,
+            );
+
+            match total.checked_sub(recipients + fee) {
+                Some(change) if change >= DUST_LIMIT => {
+                    tx.output[self.change_index].value = change;
+                    return Self::psbt(tx, &prevouts, fee);
+                },
+                // The change below the dust limit is left to the miners, unless it's the only output.
+                Some(change) if tx.output.len() > 1 => {
+                    tx.output.remove(self.change_index);
+                    return Self::psbt(tx, &prevouts, fee + change);
+                },
+                _ => (),
+            }
+
+            let Some((outpoint, utxo)) = utxos.next() else {
+                return Err(Error::from(Proto::Error::Error_utxo_insufficient_inputs));
+            };
+            input.push(TxIn {
+                previous_output: *outpoint,
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                witness: placeholder_witness(&utxo.script_pubkey)?,
+            });
+            prevouts.push(utxo.clone());
+        }
+    }
+
+    fn psbt(mut tx: Transaction, prevouts: &[TxOut], fee: u64) -> Result<(Psbt, u64)> {
+        for txin in tx.input.iter_mut() {
+            txin.script_sig = ScriptBuf::new();
+            txin.witness = Witness::new();
+        }
+        let mut psbt = Psbt::from_unsigned_tx(tx)?;
+        for (input, prevout) in psbt.inner_mut().inputs.iter_mut().zip(prevouts) {
+            if prevout.script_pubkey.is_witness_program() {
+                input.witness_utxo = Some(prevout.clone());
+            }
+        }
+        Ok((psbt, fee))
+    }
+}
+
+/// Returns the sequence that signals the replaceability, keeping the relative lock time if any.
+fn replaceable(sequence: Sequence) -> Sequence {
+    if sequence.is_rbf() {
+        sequence
+    } else {
+        Sequence::ENABLE_RBF_NO_LOCKTIME
+    }
+}
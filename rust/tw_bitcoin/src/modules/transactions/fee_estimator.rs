@@ -0,0 +1,172 @@
+use super::multisig::MAX_PUBLIC_KEYS;
+use crate::modules::ECDSA_SIGNATURE_LEN;
+use crate::{Error, Result};
+use bitcoin::consensus::encode::VarInt;
+use bitcoin::Script;
+use tw_proto::BitcoinV2::Proto;
+
+/// The version and the lock time.
+const TX_BASE_LEN: u64 = 4 + 4;
+/// The segwit marker and flag, which are witness data.
+const SEGWIT_MARKER_WEIGHT: u64 = 2;
+/// The outpoint and the sequence of an input.
+const TXIN_BASE_LEN: u64 = 32 + 4 + 4;
+/// The value of an output.
+const TXOUT_BASE_LEN: u64 = 8;
+/// The length of a Schnorr signature with `SIGHASH_DEFAULT`.
+const SCHNORR_SIGNATURE_LEN: u64 = 64;
+const COMPRESSED_PUBKEY_LEN: u64 = 33;
+const P2WPKH_SCRIPT_LEN: u64 = 22;
+
+/// The type of an input that determines the length of its script signature and witness.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputType {
+    /// P2PKH of a compressed public key.
+    P2pkh,
+    /// P2WPKH nested in P2SH.
+    P2shP2wpkh,
+    P2wpkh,
+    /// P2TR key path, signed with `SIGHASH_DEFAULT`.
+    P2trKeyPath,
+    /// The m-of-n [`Multisig`](super::Multisig) witness script of compressed public keys.
+    P2wshMultisig {
+        required: usize,
+        total: usize,
+    },
+    /// The P2A anchor, which is spent with an empty witness.
+    P2a,
+}
+
+/// The estimated size and fee of a transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeeEstimate {
+    pub weight: u64,
+    /// The virtual size, i.e. the weight divided by four and rounded up.
+    pub vsize: u64,
+    pub fee: u64,
+}
+
+/// Estimates the weight and the fee of a transaction before signing, e.g. to preview the fee:
+///
+/// ```rust,ignore
+/// let estimate = FeeEstimator::new()
+///     .add_input(InputType::P2wpkh)
+///     .add_input(InputType::P2wshMultisig { required: 2, total: 3 })
+///     .add_output(&recipient)
+///     .add_output(&change)
+///     .fee_rate(10)
+///     .estimate()?;
+/// println!("{} vB, {} sats", estimate.vsize, estimate.fee);
+/// ```
+///
+/// The ECDSA signatures are assumed to be of the maximum length, so the estimate is an upper bound
+/// that usually exceeds the signed transaction by at most one byte per ECDSA signature.
+/// The other parts are exact.
+#[derive(Clone, Debug, Default)]
+pub struct FeeEstimator {
+    inputs: Vec<InputType>,
+    /// The lengths of the script pubkeys.
+    outputs: Vec<u64>,
+    /// Satoshis per vbyte.
+    fee_rate: u64,
+}
+
+impl FeeEstimator {
+    pub fn new() -> Self {
+        FeeEstimator {
+            fee_rate: 1,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the fee rate in satoshis per vbyte, `1` by default.
+    pub fn fee_rate(mut self, fee_rate: u64) -> Self {
+        self.fee_rate = fee_rate;
+        self
+    }
+
+    pub fn add_input(mut self, input: InputType) -> Self {
+        self.inputs.push(input);
+        self
+    }
+
+    /// Adds an output that pays to the `script_pubkey`, whose value doesn't affect the size.
+    pub fn add_output(mut self, script_pubkey: &Script) -> Self {
+        self.outputs.push(script_pubkey.len() as u64);
+        self
+    }
+
+    pub fn estimate(&self) -> Result<FeeEstimate> {
+        let mut base = TX_BASE_LEN
+            + var_int_len(self.inputs.len() as u64)
+            + var_int_len(self.outputs.len() as u64);
+        let mut witness = 0;
+
+        for input in &self.inputs {
+            let (script_sig_len, witness_items) = input_lengths(*input)?;
+            base += TXIN_BASE_LEN + var_int_len(script_sig_len) + script_sig_len;
+            // The number of the witness items, which is zero for the legacy inputs.
+            witness += var_int_len(witness_items.len() as u64)
+                + witness_items
+                    .iter()
+                    .map(|len| var_int_len(*len) + len)
+                    .sum::<u64>();
+        }
+        for script_len in &self.outputs {
+            base += TXOUT_BASE_LEN + var_int_len(*script_len) + script_len;
+        }
+
+        // The transaction is serialized with the witnesses if any input has a non-empty witness.
+        let is_segwit = self
+            .inputs
+            .iter()
+            .any(|input| !matches!(input, InputType::P2pkh | InputType::P2a));
+        let weight = if is_segwit {
+            4 * base + SEGWIT_MARKER_WEIGHT + witness
+        } else {
+            4 * base
+        };
+        let vsize = (weight + 3) / 4;
+
+        Ok(FeeEstimate {
+            weight,
+            vsize,
+            fee: vsize * self.fee_rate,
+        })
+    }
+}
+
+/// Returns the length of the script signature and the lengths of the witness items of the `input`.
+fn input_lengths(input: InputType) -> Result<(u64, Vec<u64>)> {
+    let p2wpkh_witness = vec![ECDSA_SIGNATURE_LEN, COMPRESSED_PUBKEY_LEN];
+
+    let lengths = match input {
+        // `<signature> <public key>`.
+        InputType::P2pkh => (
+            1 + ECDSA_SIGNATURE_LEN + 1 + COMPRESSED_PUBKEY_LEN,
+            Vec::new(),
+        ),
+        // The push of the P2WPKH redeem script.
+        InputType::P2shP2wpkh => (1 + P2WPKH_SCRIPT_LEN, p2wpkh_witness),
+        InputType::P2wpkh => (0, p2wpkh_witness),
+        InputType::P2trKeyPath => (0, vec![SCHNORR_SIGNATURE_LEN]),
+        InputType::P2a => (0, Vec::new()),
+        InputType::P2wshMultisig { required, total } => {
+            if required == 0 || required > total || total > MAX_PUBLIC_KEYS {
+                return Err(Error::from(Proto::Error::Error_invalid_multisig));
+            }
+            // `OP_m <public key>... OP_n OP_CHECKMULTISIG`.
+            let script_len = 3 + total as u64 * (1 + COMPRESSED_PUBKEY_LEN);
+            // The empty dummy element, the signatures and the witness script.
+            let mut items = vec![0];
+            items.extend(std::iter::repeat(ECDSA_SIGNATURE_LEN).take(required));
+            items.push(script_len);
+            (0, items)
+        },
+    };
+    Ok(lengths)
+}
+
+fn var_int_len(value: u64) -> u64 {
+    VarInt(value).len() as u64
+}
@@ -0,0 +1,148 @@
+use crate::{Error, Result};
+use std::cmp::Reverse;
+use tw_proto::BitcoinV2::Proto;
+
+/// The maximum virtual size of a block.
+pub const MAX_BLOCK_VSIZE: u64 = 1_000_000;
+
+/// The transactions that pay the same fee rate, e.g. an entry of the `mempool.get_fee_histogram`
+/// response of an Electrum server.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeeRateBucket {
+    /// Satoshis per vbyte.
+    pub fee_rate: u64,
+    /// The total virtual size of the transactions.
+    pub vsize: u64,
+}
+
+/// Recommends the fee rates to confirm a transaction within a number of blocks, from a mempool histogram
+/// or the fee rates of recent blocks supplied by the caller, e.g. to offer the fee options of a wallet:
+///
+/// ```rust,ignore
+/// let recommender = FeeRateRecommender::new()
+///     .mempool_histogram(histogram)
+///     .add_block(previous_block)
+///     .add_block(last_block);
+/// let priority = recommender.recommend(1, 50)?;
+/// let economy = recommender.recommend(6, 0)?;
+/// ```
+///
+/// The mempool is projected into blocks in the order of the fee rates, so that the rate of a target of `n` blocks
+/// is the `percentile` of the fee rates of the `n`th projected block, i.e. `0` for the lowest rate that enters it.
+/// The rate of the recent blocks is the lowest that would have been confirmed within `n` blocks at any time,
+/// given the `percentile` of the fee rates of each block. If both are supplied, the rate is the higher of the two.
+#[derive(Clone, Debug)]
+pub struct FeeRateRecommender {
+    mempool: Vec<FeeRateBucket>,
+    /// The fee rates of the recent blocks, the oldest first.
+    blocks: Vec<Vec<FeeRateBucket>>,
+    block_vsize: u64,
+    /// Satoshis per vbyte.
+    min_fee_rate: u64,
+}
+
+impl Default for FeeRateRecommender {
+    fn default() -> Self {
+        FeeRateRecommender::new()
+    }
+}
+
+impl FeeRateRecommender {
+    pub fn new() -> Self {
+        FeeRateRecommender {
+            mempool: Vec::new(),
+            blocks: Vec::new(),
+            block_vsize: MAX_BLOCK_VSIZE,
+            min_fee_rate: 1,
+        }
+    }
+
+    /// Sets the fee rates of the unconfirmed transactions, in any order.
+    pub fn mempool_histogram(mut self, histogram: Vec<FeeRateBucket>) -> Self {
+        self.mempool = histogram;
+        self
+    }
+
+    /// Adds the fee rates of the transactions of the block that follows the previously added ones.
+    pub fn add_block(mut self, fee_rates: Vec<FeeRateBucket>) -> Self {
+        self.blocks.push(fee_rates);
+        self
+    }
+
+    /// Sets the virtual size of the projected blocks, [`MAX_BLOCK_VSIZE`] by default.
+    pub fn block_vsize(mut self, block_vsize: u64) -> Self {
+        self.block_vsize = block_vsize;
+        self
+    }
+
+    /// Sets the minimum relay fee rate in satoshis per vbyte, `1` by default,
+    /// which is recommended if the blocks aren't full.
+    pub fn min_fee_rate(mut self, min_fee_rate: u64) -> Self {
+        self.min_fee_rate = min_fee_rate;
+        self
+    }
+
+    /// Returns the fee rate in satoshis per vbyte to confirm within `target_blocks`, at the `percentile`
+    /// from `0` to `100` of the fee rates of a block.
+    pub fn recommend(&self, target_blocks: u32, percentile: u8) -> Result<u64> {
+        if target_blocks == 0
+            || percentile > 100
+            || self.block_vsize == 0
+            || (self.mempool.is_empty() && self.blocks.is_empty())
+        {
+            return Err(Error::from(Proto::Error::Error_invalid_fee_rate_histogram));
+        }
+
+        let mut fee_rate = self.min_fee_rate;
+        if !self.mempool.is_empty() {
+            fee_rate = fee_rate.max(self.mempool_fee_rate(target_blocks, percentile));
+        }
+        if !self.blocks.is_empty() {
+            fee_rate = fee_rate.max(self.blocks_fee_rate(target_blocks, percentile));
+        }
+        Ok(fee_rate)
+    }
+
+    /// Returns the fee rate of the vbyte at the `percentile` of the `target_blocks`th projected block,
+    /// or zero if the mempool doesn't fill it.
+    fn mempool_fee_rate(&self, target_blocks: u32, percentile: u8) -> u64 {
+        let mut mempool = self.mempool.clone();
+        mempool.sort_by_key(|bucket| Reverse(bucket.fee_rate));
+
+        // The offset from the top of the mempool, where the higher percentiles are closer to the top of the block.
+        let offset = (target_blocks as u64 - 1).saturating_mul(self.block_vsize)
+            + (self.block_vsize - 1) * (100 - percentile as u64) / 100;
+        bucket_at(&mempool, offset).unwrap_or(0)
+    }
+
+    fn blocks_fee_rate(&self, target_blocks: u32, percentile: u8) -> u64 {
+        let block_rates: Vec<u64> = self
+            .blocks
+            .iter()
+            .map(|block| {
+                let mut block = block.clone();
+                block.sort_by_key(|bucket| bucket.fee_rate);
+                let vsize: u64 = block.iter().map(|bucket| bucket.vsize).sum();
+                // An empty block confirms any transaction.
+                bucket_at(&block, vsize.saturating_sub(1) * percentile as u64 / 100).unwrap_or(0)
+            })
+            .collect();
+
+        // A transaction waits at most `target_blocks` for a block that accepts its fee rate.
+        let window = (target_blocks as usize).min(block_rates.len());
+        block_rates
+            .windows(window)
+            .map(|blocks| blocks.iter().copied().min().unwrap_or(0))
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// Returns the fee rate of the vbyte at the `offset` of the sorted `buckets`, if any.
+fn bucket_at(buckets: &[FeeRateBucket], offset: u64) -> Option<u64> {
+    let mut cumulative = 0_u64;
+    buckets.iter().find_map(|bucket| {
+        cumulative = cumulative.saturating_add(bucket.vsize);
+        (cumulative > offset).then_some(bucket.fee_rate)
+    })
+}
@@ -0,0 +1,142 @@
+use super::TimelockScript;
+use crate::modules::ECDSA_SIGNATURE_LEN;
+use crate::{Error, Result};
+use bitcoin::blockdata::opcodes::all::{
+    OP_CHECKSIG, OP_ELSE, OP_ENDIF, OP_EQUALVERIFY, OP_IF, OP_SHA256, OP_SIZE,
+};
+use bitcoin::hashes::{sha256, Hash};
+use bitcoin::{PublicKey, ScriptBuf};
+use tw_proto::BitcoinV2::Proto;
+
+/// The length of the secret, enforced by the script so that the secret is revealed
+/// on a chain with the different limits on the stack elements too.
+pub const HTLC_SECRET_LEN: usize = 32;
+
+/// The path to spend a [`HtlcScript`] with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HtlcSpend {
+    /// The recipient reveals the secret.
+    Redeem([u8; HTLC_SECRET_LEN]),
+    /// The sender takes the output back once the timeout expires.
+    Refund,
+}
+
+/// The hash time-locked contract of an atomic swap:
+///
+/// ```text
+/// OP_IF
+///     OP_SIZE <32> OP_EQUALVERIFY OP_SHA256 <secret hash> OP_EQUALVERIFY <recipient> OP_CHECKSIG
+/// OP_ELSE
+///     <timeout> OP_CHECKLOCKTIMEVERIFY|OP_CHECKSEQUENCEVERIFY OP_DROP <refund> OP_CHECKSIG
+/// OP_ENDIF
+/// ```
+///
+/// i.e. the recipient can spend the output with the secret at any time, and the sender
+/// once the timeout expires. It's used as the redeem script of P2SH or the witness script of P2WSH.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HtlcScript {
+    secret_hash: [u8; 32],
+    recipient: PublicKey,
+    refund: TimelockScript,
+}
+
+impl HtlcScript {
+    /// The `secret_hash` is the SHA-256 hash of the secret, the `refund` is the timelocked key of the sender.
+    pub fn new(secret_hash: [u8; 32], recipient: PublicKey, refund: TimelockScript) -> Self {
+        HtlcScript {
+            secret_hash,
+            recipient,
+            refund,
+        }
+    }
+
+    pub fn from_proto(proto: &Proto::mod_Input::InputHtlc<'_>) -> Result<Self> {
+        let secret_hash = proto
+            .secret_hash
+            .as_ref()
+            .try_into()
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_htlc))?;
+        let recipient = PublicKey::from_slice(proto.recipient_public_key.as_ref())?;
+        let refund = proto
+            .refund
+            .as_ref()
+            .ok_or_else(|| Error::from(Proto::Error::Error_invalid_htlc))?;
+        Ok(Self::new(
+            secret_hash,
+            recipient,
+            TimelockScript::from_proto(refund)?,
+        ))
+    }
+
+    pub fn secret_hash(&self) -> &[u8; 32] {
+        &self.secret_hash
+    }
+
+    pub fn recipient(&self) -> &PublicKey {
+        &self.recipient
+    }
+
+    pub fn refund(&self) -> &TimelockScript {
+        &self.refund
+    }
+
+    pub fn script(&self) -> ScriptBuf {
+        let mut script = ScriptBuf::builder()
+            .push_opcode(OP_IF)
+            .push_opcode(OP_SIZE)
+            .push_int(HTLC_SECRET_LEN as i64)
+            .push_opcode(OP_EQUALVERIFY)
+            .push_opcode(OP_SHA256)
+            .push_slice(self.secret_hash)
+            .push_opcode(OP_EQUALVERIFY)
+            .push_key(&self.recipient)
+            .push_opcode(OP_CHECKSIG)
+            .push_opcode(OP_ELSE)
+            .into_script()
+            .into_bytes();
+        script.extend_from_slice(self.refund.script().as_bytes());
+        script.push(OP_ENDIF.to_u8());
+        ScriptBuf::from_bytes(script)
+    }
+
+    /// Returns the spending path of the `secret`, the refund if it's empty.
+    pub fn spend_from_secret(&self, secret: &[u8]) -> Result<HtlcSpend> {
+        if secret.is_empty() {
+            return Ok(HtlcSpend::Refund);
+        }
+        let secret: [u8; HTLC_SECRET_LEN] = secret
+            .try_into()
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_htlc_secret))?;
+        if sha256::Hash::hash(&secret).to_byte_array() != self.secret_hash {
+            return Err(Error::from(Proto::Error::Error_invalid_htlc_secret));
+        }
+        Ok(HtlcSpend::Redeem(secret))
+    }
+
+    /// Returns the public key that signs the `spend`.
+    pub fn signer(&self, spend: &HtlcSpend) -> &PublicKey {
+        match spend {
+            HtlcSpend::Redeem(_) => &self.recipient,
+            HtlcSpend::Refund => self.refund.public_key(),
+        }
+    }
+
+    /// Returns the stack that satisfies the script for the `spend` with the `signature`, without the script.
+    pub fn claim_stack(&self, signature: Vec<u8>, spend: &HtlcSpend) -> Vec<Vec<u8>> {
+        match spend {
+            // The `OP_IF` branch is selected by the minimal true value.
+            HtlcSpend::Redeem(secret) => vec![signature, secret.to_vec(), vec![1]],
+            HtlcSpend::Refund => vec![signature, Vec::new()],
+        }
+    }
+
+    /// Returns the maximum length of the claim stack without the script, including the lengths of its elements.
+    pub fn claim_len(&self, spend: &HtlcSpend) -> u64 {
+        match spend {
+            // length + ECDSA signature, length + secret, length + branch selector.
+            HtlcSpend::Redeem(_) => 1 + ECDSA_SIGNATURE_LEN + 1 + HTLC_SECRET_LEN as u64 + 2,
+            // length + ECDSA signature, empty branch selector.
+            HtlcSpend::Refund => 1 + ECDSA_SIGNATURE_LEN + 1,
+        }
+    }
+}
@@ -1,10 +1,14 @@
+use super::anchor::p2a_script;
 use super::brc20::{BRC20TransferInscription, Brc20Ticker};
 use crate::aliases::*;
-use crate::modules::transactions::OrdinalNftInscription;
+use crate::modules::miniscript::Miniscript;
+use crate::modules::transactions::{HtlcScript, Multisig, OrdinalNftInscription, TimelockScript};
 use crate::{Error, Result};
+use bitcoin::consensus::encode::VarInt;
 use bitcoin::taproot::{LeafVersion, TapLeafHash};
-use bitcoin::ScriptBuf;
+use bitcoin::{Script, ScriptBuf, WPubkeyHash};
 use secp256k1::XOnlyPublicKey;
+use std::str::FromStr;
 use tw_misc::traits::ToBytesVec;
 use tw_proto::BitcoinV2::Proto;
 use tw_proto::Utxo::Proto as UtxoProto;
@@ -103,6 +107,34 @@ impl InputBuilder {
                         ),
                     )
                 },
+                ProtoInputBuilder::p2sh_p2wpkh(pubkey) => {
+                    let pubkey = bitcoin::PublicKey::from_slice(pubkey.as_ref())?;
+
+                    // Signed as P2WPKH, the redeem script is revealed in the scriptSig.
+                    let script_pubkey = ScriptBuf::new_v0_p2wpkh(&wpubkey_hash(&pubkey)?)
+                        .p2wpkh_script_code()
+                        .ok_or_else(|| Error::from(Proto::Error::Error_invalid_wpkh_script_code))?;
+
+                    (
+                        UtxoProto::SigningMethod::Segwit,
+                        script_pubkey,
+                        NO_LEAF_HASH,
+                        // scale factor applied to non-witness bytes
+                        4 * (
+                            // length + P2WPKH redeem script
+                            1 + 22
+                        ) +
+                        // witness bytes, scale factor NOT applied.
+                        (
+                            // indicator of witness item (2)
+                            1 +
+                            // length + ECDSA signature (can be 71 or 72)
+                            1 + 72 +
+                            // length + compressed public key
+                            1 + 33
+                        ),
+                    )
+                },
                 ProtoInputBuilder::p2tr_key_path(key_path) => {
                     let pubkey = bitcoin::PublicKey::from_slice(key_path.public_key.as_ref())?;
                     let xonly = XOnlyPublicKey::from(pubkey.inner);
@@ -261,6 +293,158 @@ impl InputBuilder {
                         ),
                     )
                 },
+                ProtoInputBuilder::p2sh_multisig(multisig) => {
+                    let multisig = Multisig::from_proto(multisig)?;
+                    // The scriptPubkey is the redeem script directly.
+                    let script_pubkey = multisig.script();
+                    let script_len = script_pubkey.len() as u64;
+
+                    (
+                        UtxoProto::SigningMethod::Legacy,
+                        script_pubkey,
+                        NO_LEAF_HASH,
+                        // scale factor applied to non-witness bytes
+                        4 * (
+                            // dummy + signatures
+                            multisig.claim_len() +
+                            // length (up to `OP_PUSHDATA2`) + redeem script.
+                            3 + script_len
+                        ),
+                    )
+                },
+                ProtoInputBuilder::p2wsh_multisig(multisig) => {
+                    let multisig = Multisig::from_proto(multisig)?;
+                    // The scriptPubkey is the witness script directly.
+                    let script_pubkey = multisig.script();
+                    let script_len = script_pubkey.len() as u64;
+
+                    (
+                        UtxoProto::SigningMethod::Segwit,
+                        script_pubkey,
+                        NO_LEAF_HASH,
+                        // witness bytes, scale factor NOT applied.
+                        (
+                            // indicator of witness item count
+                            1 +
+                            // dummy + signatures
+                            multisig.claim_len() +
+                            // length + witness script.
+                            3 + script_len
+                        ),
+                    )
+                },
+                ProtoInputBuilder::p2wsh_miniscript(miniscript) => {
+                    let miniscript = Miniscript::from_str(&miniscript.miniscript)?;
+                    // The scriptPubkey is the witness script directly.
+                    let script_pubkey = miniscript.script().clone();
+                    let script_len = script_pubkey.len() as u64;
+
+                    (
+                        UtxoProto::SigningMethod::Segwit,
+                        script_pubkey,
+                        NO_LEAF_HASH,
+                        // witness bytes, scale factor NOT applied.
+                        (
+                            // indicator of witness item count
+                            1 +
+                            // the largest satisfaction
+                            miniscript.max_satisfaction_len() +
+                            // length + witness script.
+                            3 + script_len
+                        ),
+                    )
+                },
+                ProtoInputBuilder::p2sh_timelock(timelock) => {
+                    let timelock = TimelockScript::from_proto(timelock)?;
+                    // The scriptPubkey is the redeem script directly.
+                    let script_pubkey = timelock.script();
+                    let script_len = script_pubkey.len() as u64;
+
+                    (
+                        UtxoProto::SigningMethod::Legacy,
+                        script_pubkey,
+                        NO_LEAF_HASH,
+                        // scale factor applied to non-witness bytes
+                        4 * (
+                            // signature
+                            timelock.claim_len() +
+                            // length + redeem script.
+                            1 + script_len
+                        ),
+                    )
+                },
+                ProtoInputBuilder::p2wsh_timelock(timelock) => {
+                    let timelock = TimelockScript::from_proto(timelock)?;
+                    // The scriptPubkey is the witness script directly.
+                    let script_pubkey = timelock.script();
+                    let script_len = script_pubkey.len() as u64;
+
+                    (
+                        UtxoProto::SigningMethod::Segwit,
+                        script_pubkey,
+                        NO_LEAF_HASH,
+                        // witness bytes, scale factor NOT applied.
+                        (
+                            // indicator of witness item count
+                            1 +
+                            // signature
+                            timelock.claim_len() +
+                            // length + witness script.
+                            1 + script_len
+                        ),
+                    )
+                },
+                ProtoInputBuilder::p2sh_htlc(proto_htlc) => {
+                    let htlc = HtlcScript::from_proto(proto_htlc)?;
+                    let spend = htlc.spend_from_secret(&proto_htlc.secret)?;
+                    // The scriptPubkey is the redeem script directly.
+                    let script_pubkey = htlc.script();
+                    let script_len = script_pubkey.len() as u64;
+
+                    (
+                        UtxoProto::SigningMethod::Legacy,
+                        script_pubkey,
+                        NO_LEAF_HASH,
+                        // scale factor applied to non-witness bytes
+                        4 * (
+                            // signature, secret and branch selector
+                            htlc.claim_len(&spend) +
+                            // length + redeem script.
+                            2 + script_len
+                        ),
+                    )
+                },
+                ProtoInputBuilder::p2wsh_htlc(proto_htlc) => {
+                    let htlc = HtlcScript::from_proto(proto_htlc)?;
+                    let spend = htlc.spend_from_secret(&proto_htlc.secret)?;
+                    // The scriptPubkey is the witness script directly.
+                    let script_pubkey = htlc.script();
+                    let script_len = script_pubkey.len() as u64;
+
+                    (
+                        UtxoProto::SigningMethod::Segwit,
+                        script_pubkey,
+                        NO_LEAF_HASH,
+                        // witness bytes, scale factor NOT applied.
+                        (
+                            // indicator of witness item count
+                            1 +
+                            // signature, secret and branch selector
+                            htlc.claim_len(&spend) +
+                            // length + witness script.
+                            1 + script_len
+                        ),
+                    )
+                },
+                ProtoInputBuilder::p2a(_) => (
+                    // Signed as any other segwit input, but the signature is not revealed.
+                    UtxoProto::SigningMethod::Segwit,
+                    p2a_script(),
+                    NO_LEAF_HASH,
+                    // witness bytes, scale factor NOT applied:
+                    // indicator of witness item count (0)
+                    1,
+                ),
                 ProtoInputBuilder::None => {
                     return Err(Error::from(Proto::Error::Error_missing_input_builder))
                 },
@@ -303,6 +487,21 @@ impl InputBuilder {
             },
         };
 
+        let prevout_script_pubkey = match &input.to_recipient {
+            ProtoInputRecipient::builder(builder) => {
+                prevout_script_pubkey(&builder.variant, &script_pubkey)?
+            },
+            _ => None,
+        };
+
+        // The annex is appended to the witness, scale factor NOT applied.
+        let weight = if input.annex.is_empty() {
+            weight
+        } else {
+            let annex_len = input.annex.len() as u64;
+            weight + bitcoin::consensus::serialize(&VarInt(annex_len)).len() as u64 + annex_len
+        };
+
         // Create Utxo.proto structure.
         let utxo = UtxoProto::TxIn {
             txid: input.txid.to_vec().into(),
@@ -316,8 +515,51 @@ impl InputBuilder {
             leaf_hash: leaf_hash
                 .map(|hash| hash.to_vec().into())
                 .unwrap_or_default(),
+            annex: input.annex.to_vec().into(),
+            coin_control: input.coin_control,
+            prevout_script_pubkey: prevout_script_pubkey
+                .map(|script| script.to_vec().into())
+                .unwrap_or_default(),
         };
 
         Ok(utxo)
     }
 }
+
+/// Returns the script pubkey of the output spent by the input of the `variant`, if it differs from
+/// the script code in its `script_pubkey`, e.g. the P2SH output of a redeem script.
+/// The P2WSH input of a raw witness and the Taproot script path inputs keep the script they sign.
+fn prevout_script_pubkey(
+    variant: &ProtoInputBuilder<'_>,
+    script_pubkey: &Script,
+) -> Result<Option<ScriptBuf>> {
+    let prevout = match variant {
+        ProtoInputBuilder::p2sh(_)
+        | ProtoInputBuilder::p2sh_multisig(_)
+        | ProtoInputBuilder::p2sh_timelock(_)
+        | ProtoInputBuilder::p2sh_htlc(_) => ScriptBuf::new_p2sh(&script_pubkey.script_hash()),
+        ProtoInputBuilder::p2wsh_multisig(_)
+        | ProtoInputBuilder::p2wsh_miniscript(_)
+        | ProtoInputBuilder::p2wsh_timelock(_)
+        | ProtoInputBuilder::p2wsh_htlc(_) => {
+            ScriptBuf::new_v0_p2wsh(&script_pubkey.wscript_hash())
+        },
+        ProtoInputBuilder::p2wpkh(pubkey) => {
+            let pubkey = bitcoin::PublicKey::from_slice(pubkey.as_ref())?;
+            ScriptBuf::new_v0_p2wpkh(&wpubkey_hash(&pubkey)?)
+        },
+        ProtoInputBuilder::p2sh_p2wpkh(pubkey) => {
+            let pubkey = bitcoin::PublicKey::from_slice(pubkey.as_ref())?;
+            let redeem_script = ScriptBuf::new_v0_p2wpkh(&wpubkey_hash(&pubkey)?);
+            ScriptBuf::new_p2sh(&redeem_script.script_hash())
+        },
+        _ => return Ok(None),
+    };
+    Ok(Some(prevout))
+}
+
+fn wpubkey_hash(pubkey: &bitcoin::PublicKey) -> Result<WPubkeyHash> {
+    pubkey
+        .wpubkey_hash()
+        .ok_or_else(|| Error::from(Proto::Error::Error_invalid_witness_pubkey_hash))
+}
@@ -1,11 +1,16 @@
 use super::brc20::{BRC20TransferInscription, Brc20Ticker};
-use super::OrdinalNftInscription;
+use super::{
+    ControlBlockBuilder, HtlcScript, HtlcSpend, Multisig, OrdinalNftInscription, TimelockScript,
+};
 use crate::aliases::*;
+use crate::modules::miniscript::{Miniscript, Satisfier};
 use crate::{Error, Result};
+use bitcoin::blockdata::script::PushBytesBuf;
 use bitcoin::consensus::Decodable;
-use bitcoin::taproot::{ControlBlock, LeafVersion};
+use bitcoin::taproot::{LeafVersion, TAPROOT_ANNEX_PREFIX};
 use bitcoin::{ScriptBuf, Witness};
 use std::borrow::Cow;
+use std::str::FromStr;
 use tw_coin_entry::coin_entry::SignatureBytes;
 use tw_misc::traits::ToBytesVec;
 use tw_proto::BitcoinV2::Proto;
@@ -15,12 +20,16 @@ pub struct InputClaimBuilder;
 
 impl InputClaimBuilder {
     /// Creates the claim script (_scriptSig_ or _Witness_) to be revealed
-    /// on-chain for a given input.
+    /// on-chain for a given input. The `signer` is the public key of the
+    /// `signature`, required to order the signatures of a multisig input.
+    /// The `lock_time` of the transaction satisfies the absolute timelocks of a miniscript, timelock or HTLC input.
     pub fn utxo_claim_from_proto(
         input: &Proto::Input<'_>,
         signature: SignatureBytes,
+        signer: Option<bitcoin::PublicKey>,
+        lock_time: u32,
     ) -> Result<UtxoProto::TxInClaim<'static>> {
-        let (script_sig, witness) = match &input.to_recipient {
+        let (script_sig, mut witness) = match &input.to_recipient {
             ProtoInputRecipient::builder(variant) => match &variant.variant {
                 ProtoInputBuilder::p2sh(redeem_script) => (
                     ScriptBuf::from_bytes(redeem_script.to_vec()),
@@ -57,6 +66,29 @@ impl InputClaimBuilder {
                         w
                     })
                 },
+                ProtoInputBuilder::p2sh_p2wpkh(pubkey) => {
+                    let sig = bitcoin::ecdsa::Signature::from_slice(signature.as_ref())?;
+                    let pubkey = bitcoin::PublicKey::from_slice(pubkey.as_ref())?;
+                    let wpubkey_hash = pubkey.wpubkey_hash().ok_or_else(|| {
+                        Error::from(Proto::Error::Error_invalid_witness_pubkey_hash)
+                    })?;
+
+                    let redeem_script = PushBytesBuf::try_from(
+                        ScriptBuf::new_v0_p2wpkh(&wpubkey_hash).into_bytes(),
+                    )
+                    .map_err(|_| Error::from(Proto::Error::Error_invalid_redeem_script))?;
+
+                    // The P2WPKH redeem script, and the signature and the public key in the witness.
+                    (
+                        ScriptBuf::builder().push_slice(redeem_script).into_script(),
+                        {
+                            let mut w = Witness::new();
+                            w.push(sig.serialize());
+                            w.push(pubkey.to_bytes());
+                            w
+                        },
+                    )
+                },
                 ProtoInputBuilder::p2tr_key_path(_) => {
                     let sig = bitcoin::taproot::Signature::from_slice(signature.as_ref())?;
 
@@ -68,8 +100,7 @@ impl InputClaimBuilder {
                     })
                 },
                 ProtoInputBuilder::p2tr_script_path(taproot) => {
-                    let control_block = ControlBlock::decode(taproot.control_block.as_ref())
-                        .map_err(|_| Error::from(Proto::Error::Error_invalid_control_block))?;
+                    let control_block = ControlBlockBuilder::from_proto(taproot)?;
 
                     let sig = bitcoin::taproot::Signature::from_slice(signature.as_ref())?;
 
@@ -144,6 +175,117 @@ impl InputClaimBuilder {
                         w
                     })
                 },
+                ProtoInputBuilder::p2sh_multisig(proto_multisig) => {
+                    let multisig = Multisig::from_proto(proto_multisig)?;
+                    let signatures =
+                        collected_signatures(&proto_multisig.signatures, signer, signature)?;
+                    let stack = multisig.claim_stack(&signatures)?;
+
+                    // The dummy, the signatures and the redeem script.
+                    let mut builder = ScriptBuf::builder();
+                    for item in stack
+                        .into_iter()
+                        .chain(std::iter::once(multisig.script().into_bytes()))
+                    {
+                        let push = PushBytesBuf::try_from(item)
+                            .map_err(|_| Error::from(Proto::Error::Error_invalid_multisig))?;
+                        builder = builder.push_slice(push);
+                    }
+                    (builder.into_script(), Witness::new())
+                },
+                ProtoInputBuilder::p2wsh_multisig(proto_multisig) => {
+                    let multisig = Multisig::from_proto(proto_multisig)?;
+                    let signatures =
+                        collected_signatures(&proto_multisig.signatures, signer, signature)?;
+                    let stack = multisig.claim_stack(&signatures)?;
+
+                    // The dummy, the signatures and the witness script.
+                    (ScriptBuf::new(), {
+                        let mut w = Witness::from_slice(&stack);
+                        w.push(multisig.script().as_bytes());
+                        w
+                    })
+                },
+                ProtoInputBuilder::p2wsh_miniscript(proto_miniscript) => {
+                    let miniscript = Miniscript::from_str(&proto_miniscript.miniscript)?;
+                    let mut satisfier = Satisfier::new()
+                        .sequence(input.sequence)
+                        .lock_time(lock_time);
+                    for (public_key, signature) in
+                        collected_signatures(&proto_miniscript.signatures, signer, signature)?
+                    {
+                        bitcoin::ecdsa::Signature::from_slice(&signature)?;
+                        satisfier = satisfier.signature(public_key, signature);
+                    }
+                    for preimage in &proto_miniscript.preimages {
+                        satisfier = satisfier.preimage(preimage.to_vec());
+                    }
+
+                    // The cheapest satisfaction and the witness script.
+                    (ScriptBuf::new(), miniscript.satisfy(&satisfier)?)
+                },
+                ProtoInputBuilder::p2sh_timelock(timelock) => {
+                    let timelock = TimelockScript::from_proto(timelock)?;
+                    timelock.check_spend(lock_time, input.sequence)?;
+                    let sig = bitcoin::ecdsa::Signature::from_slice(signature.as_ref())?;
+                    let redeem_script = PushBytesBuf::try_from(timelock.script().into_bytes())
+                        .map_err(|_| Error::from(Proto::Error::Error_invalid_redeem_script))?;
+
+                    // The signature and the redeem script.
+                    (
+                        ScriptBuf::builder()
+                            .push_slice(sig.serialize())
+                            .push_slice(redeem_script)
+                            .into_script(),
+                        Witness::new(),
+                    )
+                },
+                ProtoInputBuilder::p2wsh_timelock(timelock) => {
+                    let timelock = TimelockScript::from_proto(timelock)?;
+                    timelock.check_spend(lock_time, input.sequence)?;
+                    let sig = bitcoin::ecdsa::Signature::from_slice(signature.as_ref())?;
+
+                    // The signature and the witness script.
+                    (ScriptBuf::new(), {
+                        let mut w = Witness::new();
+                        w.push(sig.serialize());
+                        w.push(timelock.script().as_bytes());
+                        w
+                    })
+                },
+                ProtoInputBuilder::p2sh_htlc(proto_htlc) => {
+                    let htlc = HtlcScript::from_proto(proto_htlc)?;
+                    let spend = htlc_spend(&htlc, &proto_htlc.secret, signer, input, lock_time)?;
+                    let sig = bitcoin::ecdsa::Signature::from_slice(signature.as_ref())?;
+                    let redeem_script = PushBytesBuf::try_from(htlc.script().into_bytes())
+                        .map_err(|_| Error::from(Proto::Error::Error_invalid_redeem_script))?;
+
+                    // The signature, the secret and the branch selector, and the redeem script.
+                    let builder = ScriptBuf::builder().push_slice(sig.serialize());
+                    let builder = match spend {
+                        HtlcSpend::Redeem(secret) => builder.push_slice(secret).push_int(1),
+                        HtlcSpend::Refund => builder.push_int(0),
+                    };
+                    (
+                        builder.push_slice(redeem_script).into_script(),
+                        Witness::new(),
+                    )
+                },
+                ProtoInputBuilder::p2wsh_htlc(proto_htlc) => {
+                    let htlc = HtlcScript::from_proto(proto_htlc)?;
+                    let spend = htlc_spend(&htlc, &proto_htlc.secret, signer, input, lock_time)?;
+                    let sig = bitcoin::ecdsa::Signature::from_slice(signature.as_ref())?;
+
+                    // The signature, the secret and the branch selector, and the witness script.
+                    (ScriptBuf::new(), {
+                        let stack = htlc.claim_stack(sig.serialize().to_vec(), &spend);
+                        let mut w = Witness::from_slice(&stack);
+                        w.push(htlc.script().as_bytes());
+                        w
+                    })
+                },
+                // Anyone can spend the anchor, so the signature is ignored.
+                ProtoInputBuilder::p2a(_) => (ScriptBuf::new(), Witness::new()),
                 ProtoInputBuilder::None => {
                     return Err(Error::from(Proto::Error::Error_missing_input_builder))
                 },
@@ -157,6 +299,14 @@ impl InputClaimBuilder {
             },
         };
 
+        // The annex is the last item of a Taproot witness.
+        if !input.annex.is_empty() {
+            if !is_taproot(input) || input.annex.first() != Some(&TAPROOT_ANNEX_PREFIX) {
+                return Err(Error::from(Proto::Error::Error_utxo_invalid_annex));
+            }
+            witness.push(input.annex.as_ref());
+        }
+
         // Create Utxo.proto structure.
         let claim = UtxoProto::TxInClaim {
             txid: input.txid.to_vec().into(),
@@ -174,3 +324,62 @@ impl InputClaimBuilder {
         Ok(claim)
     }
 }
+
+/// Whether the `input` is spent with a Taproot key path or script path.
+fn is_taproot(input: &Proto::Input<'_>) -> bool {
+    match &input.to_recipient {
+        ProtoInputRecipient::builder(builder) => matches!(
+            builder.variant,
+            ProtoInputBuilder::p2tr_key_path(_)
+                | ProtoInputBuilder::p2tr_script_path(_)
+                | ProtoInputBuilder::brc20_inscribe(_)
+                | ProtoInputBuilder::ordinal_inscribe(_)
+        ),
+        ProtoInputRecipient::custom_script(custom) => matches!(
+            custom.signing_method,
+            UtxoProto::SigningMethod::TaprootAll | UtxoProto::SigningMethod::TaprootOnePrevout
+        ),
+        ProtoInputRecipient::None => false,
+    }
+}
+
+/// Returns the spending path of the HTLC input, checking that the `signer` is the key of the path
+/// and that the timeout has expired for a refund.
+fn htlc_spend(
+    htlc: &HtlcScript,
+    secret: &[u8],
+    signer: Option<bitcoin::PublicKey>,
+    input: &Proto::Input<'_>,
+    lock_time: u32,
+) -> Result<HtlcSpend> {
+    let spend = htlc.spend_from_secret(secret)?;
+    if signer.map_or(false, |signer| signer != *htlc.signer(&spend)) {
+        return Err(Error::from(Proto::Error::Error_invalid_htlc));
+    }
+    if spend == HtlcSpend::Refund {
+        htlc.refund().check_spend(lock_time, input.sequence)?;
+    }
+    Ok(spend)
+}
+
+/// Returns the collected signatures of the multisig or miniscript input, and the `signature` of the `signer` if any.
+fn collected_signatures(
+    collected: &[Proto::mod_Input::MultisigSignature<'_>],
+    signer: Option<bitcoin::PublicKey>,
+    signature: SignatureBytes,
+) -> Result<Vec<(bitcoin::PublicKey, Vec<u8>)>> {
+    let mut signatures = collected
+        .iter()
+        .map(|partial| {
+            let public_key = bitcoin::PublicKey::from_slice(partial.public_key.as_ref())?;
+            Ok((public_key, partial.signature.to_vec()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    if !signature.is_empty() {
+        let signer = signer.ok_or_else(|| Error::from(Proto::Error::Error_invalid_multisig))?;
+        signatures.retain(|(public_key, _)| *public_key != signer);
+        signatures.push((signer, signature));
+    }
+    Ok(signatures)
+}
@@ -0,0 +1,291 @@
+use super::anchor::is_p2a;
+use super::OrdinalsInscription;
+use crate::modules::psbt::Psbt;
+use crate::modules::ECDSA_SIGNATURE_LEN;
+use crate::{Error, Result};
+use bitcoin::blockdata::locktime::absolute::LockTime;
+use bitcoin::blockdata::opcodes::all::OP_CHECKSIG;
+use bitcoin::hashes::Hash;
+use bitcoin::sighash::{Prevouts, SighashCache, TapSighashType};
+use bitcoin::taproot::{LeafVersion, TapLeafHash, TaprootBuilder};
+use bitcoin::{OutPoint, Script, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness};
+use secp256k1::{Secp256k1, SecretKey};
+use tw_hash::H256;
+use tw_keypair::schnorr::{self, TaprootSpendPath};
+use tw_proto::BitcoinV2::Proto;
+
+/// The value of the inscription output by default, the same as `ord` uses.
+const DEFAULT_POSTAGE: u64 = 10_000;
+/// The change below the limit is left to the miners.
+pub(super) const DUST_LIMIT: u64 = 546;
+/// The length of a Schnorr signature with `SIGHASH_DEFAULT`.
+const SCHNORR_SIGNATURE_LEN: usize = 64;
+const COMPRESSED_PUBKEY_LEN: usize = 33;
+
+/// Builds the commit and reveal transactions of an [Ordinals inscription](https://docs.ordinals.com/inscriptions.html):
+///
+/// ```rust,ignore
+/// let txs = InscriptionBuilder::new(b"text/plain;charset=utf-8", b"Hello", recipient)
+///     .fee_rate(10)
+///     .add_utxo(outpoint, utxo)
+///     .change_script(change)
+///     .build()?;
+/// let mut commit = txs.commit;
+/// commit.sign(PRIVATE_KEY)?;
+/// commit.finalize()?;
+/// // Broadcast the commit transaction, and then the reveal transaction.
+/// ```
+///
+/// The commit transaction pays to a Taproot output of an ephemeral key with the inscription script as the only leaf.
+/// The reveal transaction spends it with the ephemeral key and reveals the inscription,
+/// so it's signed on build, while the key is discarded.
+pub struct InscriptionBuilder {
+    content_type: Vec<u8>,
+    body: Vec<u8>,
+    recipient: ScriptBuf,
+    postage: u64,
+    /// Satoshis per vbyte of both transactions.
+    fee_rate: u64,
+    utxos: Vec<(OutPoint, TxOut)>,
+    change_script: Option<ScriptBuf>,
+}
+
+/// The transactions of an inscription, to be broadcast in order.
+#[derive(Debug)]
+pub struct InscriptionTransactions {
+    /// The transaction that funds the inscription output, to be signed by the owner of the UTXOs.
+    pub commit: Psbt,
+    /// The signed transaction that reveals the inscription and sends its postage output to the recipient.
+    /// Note that the commit output can't be spent otherwise, so the transaction should be kept until it's confirmed.
+    pub reveal: Transaction,
+    pub commit_fee: u64,
+    pub reveal_fee: u64,
+}
+
+impl InscriptionBuilder {
+    /// Creates a builder of an inscription of the `body` with the MIME `content_type`,
+    /// e.g. `text/plain;charset=utf-8` or `image/png`, for the `recipient` script.
+    pub fn new(content_type: &[u8], body: &[u8], recipient: ScriptBuf) -> InscriptionBuilder {
+        InscriptionBuilder {
+            content_type: content_type.to_vec(),
+            body: body.to_vec(),
+            recipient,
+            postage: DEFAULT_POSTAGE,
+            fee_rate: 1,
+            utxos: Vec::new(),
+            change_script: None,
+        }
+    }
+
+    /// Sets the value of the output that holds the inscription, `10000` satoshis by default.
+    pub fn postage(mut self, postage: u64) -> Self {
+        self.postage = postage;
+        self
+    }
+
+    /// Sets the fee rate of both transactions in satoshis per vbyte, `1` by default.
+    pub fn fee_rate(mut self, fee_rate: u64) -> Self {
+        self.fee_rate = fee_rate;
+        self
+    }
+
+    /// Adds a P2WPKH or P2TR key-path UTXO to fund the commit transaction.
+    /// Legacy outputs are not supported, as their signatures would change the commit txid that the reveal spends.
+    pub fn add_utxo(mut self, outpoint: OutPoint, utxo: TxOut) -> Self {
+        self.utxos.push((outpoint, utxo));
+        self
+    }
+
+    /// Sets the script of the change output of the commit transaction.
+    /// The change is left to the miners if not set.
+    pub fn change_script(mut self, change_script: ScriptBuf) -> Self {
+        self.change_script = Some(change_script);
+        self
+    }
+
+    /// Builds the transactions with a random ephemeral key.
+    pub fn build(&self) -> Result<InscriptionTransactions> {
+        let ephemeral_key = SecretKey::new(&mut secp256k1::rand::thread_rng());
+        self.build_with_key(&ephemeral_key.secret_bytes())
+    }
+
+    /// Builds the transactions with the given ephemeral key, e.g. to reproduce them.
+    pub fn build_with_key(&self, ephemeral_key: &[u8]) -> Result<InscriptionTransactions> {
+        let secp = Secp256k1::new();
+        let secret = SecretKey::from_slice(ephemeral_key)
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_private_key))?;
+        let public = secp256k1::PublicKey::from_secret_key(&secp, &secret);
+        let xonly = public.x_only_public_key().0;
+
+        // `<pubkey> OP_CHECKSIG` followed by the envelope of the inscription.
+        let envelope = OrdinalsInscription::new(
+            &self.content_type,
+            &self.body,
+            bitcoin::PublicKey::new(public),
+        )?;
+        let mut script = ScriptBuf::builder()
+            .push_x_only_key(&xonly)
+            .push_opcode(OP_CHECKSIG)
+            .into_script()
+            .into_bytes();
+        script.extend_from_slice(envelope.taproot_program().as_bytes());
+        let script = ScriptBuf::from_bytes(script);
+
+        let spend_info = TaprootBuilder::new()
+            .add_leaf(0, script.clone())
+            .expect("Inscription spending info must always build")
+            .finalize(&secp, xonly)
+            .expect("Inscription spending info must always build");
+        let control_block = spend_info
+            .control_block(&(script.clone(), LeafVersion::TapScript))
+            .expect("The script is the leaf of the tree");
+
+        // The reveal fee is estimated with the placeholder signature of the same length.
+        let mut reveal = Transaction {
+            version: 2,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::MAX,
+                witness: Witness::from_slice(&[
+                    vec![0; SCHNORR_SIGNATURE_LEN],
+                    script.to_bytes(),
+                    control_block.serialize(),
+                ]),
+            }],
+            output: vec![TxOut {
+                value: self.postage,
+                script_pubkey: self.recipient.clone(),
+            }],
+        };
+        let reveal_fee = self.fee_rate * reveal.vsize() as u64;
+        let commit_output = TxOut {
+            value: self.postage + reveal_fee,
+            script_pubkey: ScriptBuf::new_v1_p2tr_tweaked(spend_info.output_key()),
+        };
+
+        let (commit_psbt, commit_fee) = funded_psbt(
+            &self.utxos,
+            vec![commit_output.clone()],
+            self.fee_rate,
+            self.change_script.as_ref(),
+        )?;
+        let commit = commit_psbt.inner().unsigned_tx.clone();
+
+        // The commit txid doesn't depend on the witnesses, so the reveal is signed right away.
+        reveal.input[0].previous_output = OutPoint {
+            txid: commit.txid(),
+            vout: 0,
+        };
+        let sighash = SighashCache::new(&reveal)
+            .taproot_script_spend_signature_hash(
+                0,
+                &Prevouts::All(&[commit_output]),
+                TapLeafHash::from_script(&script, LeafVersion::TapScript),
+                TapSighashType::Default,
+            )
+            .map_err(|_| Error::from(Proto::Error::Error_utxo_sighash_failed))?;
+        let private = schnorr::PrivateKey::try_from(ephemeral_key)
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_private_key))?;
+        let signature = private
+            .sign_taproot(
+                H256::from(sighash.to_byte_array()),
+                &TaprootSpendPath::ScriptPath,
+            )
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_schnorr_signature))?;
+        reveal.input[0].witness = Witness::from_slice(&[
+            signature.to_bytes().as_slice().to_vec(),
+            script.into_bytes(),
+            control_block.serialize(),
+        ]);
+
+        Ok(InscriptionTransactions {
+            commit: commit_psbt,
+            reveal,
+            commit_fee,
+            reveal_fee,
+        })
+    }
+}
+
+/// Returns the PSBT that spends the P2WPKH or P2TR key-path `utxos` to the `outputs` and the change if any,
+/// and its fee at the `fee_rate`. The change below the dust limit is left to the miners.
+pub(super) fn funded_psbt(
+    utxos: &[(OutPoint, TxOut)],
+    outputs: Vec<TxOut>,
+    fee_rate: u64,
+    change_script: Option<&ScriptBuf>,
+) -> Result<(Psbt, u64)> {
+    // The fee is estimated with the placeholder witnesses of the maximum length.
+    let input = utxos
+        .iter()
+        .map(|(outpoint, utxo)| {
+            Ok(TxIn {
+                previous_output: *outpoint,
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::MAX,
+                witness: placeholder_witness(&utxo.script_pubkey)?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let total: u64 = utxos.iter().map(|(_, utxo)| utxo.value).sum();
+    let required: u64 = outputs.iter().map(|output| output.value).sum();
+
+    let mut tx = Transaction {
+        version: 2,
+        lock_time: LockTime::ZERO,
+        input,
+        output: outputs,
+    };
+    let fee = fee_rate * tx.vsize() as u64;
+    if total < required + fee {
+        return Err(Error::from(Proto::Error::Error_utxo_insufficient_inputs));
+    }
+
+    if let Some(change_script) = change_script {
+        tx.output.push(TxOut {
+            value: 0,
+            script_pubkey: change_script.clone(),
+        });
+        let fee = fee_rate * tx.vsize() as u64;
+        match total.checked_sub(required + fee) {
+            Some(change) if change >= DUST_LIMIT => {
+                tx.output
+                    .last_mut()
+                    .expect("The change output is pushed")
+                    .value = change;
+            },
+            _ => {
+                tx.output.pop();
+            },
+        }
+    }
+
+    let spent: u64 = tx.output.iter().map(|output| output.value).sum();
+    for txin in tx.input.iter_mut() {
+        txin.witness.clear();
+    }
+    let mut psbt = Psbt::from_unsigned_tx(tx)?;
+    for (input, (_, utxo)) in psbt.inner_mut().inputs.iter_mut().zip(utxos) {
+        input.witness_utxo = Some(utxo.clone());
+    }
+    Ok((psbt, total - spent))
+}
+
+/// Returns the witness of the maximum length that spends the P2WPKH, P2TR key-path or P2A `script_pubkey`,
+/// to estimate the fee before signing.
+pub(super) fn placeholder_witness(script_pubkey: &Script) -> Result<Witness> {
+    if script_pubkey.is_v0_p2wpkh() {
+        Ok(Witness::from_slice(&[
+            vec![0; ECDSA_SIGNATURE_LEN as usize],
+            vec![0; COMPRESSED_PUBKEY_LEN],
+        ]))
+    } else if script_pubkey.is_v1_p2tr() {
+        Ok(Witness::from_slice(&[vec![0; SCHNORR_SIGNATURE_LEN]]))
+    } else if is_p2a(script_pubkey) {
+        Ok(Witness::new())
+    } else {
+        Err(Error::from(Proto::Error::Error_unsupported_funding_utxo))
+    }
+}
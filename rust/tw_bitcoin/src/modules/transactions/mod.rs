@@ -2,18 +2,48 @@ use bitcoin::key::PublicKey;
 use bitcoin::script::ScriptBuf;
 use bitcoin::taproot::{TapNodeHash, TaprootSpendInfo};
 
+mod anchor;
+mod batch_payment_builder;
 mod brc20;
+mod consolidation_builder;
+mod control_block_builder;
+mod cpfp_builder;
+mod fee_bump_builder;
+mod fee_estimator;
+mod fee_rate_recommender;
+mod htlc;
 mod input_builder;
 mod input_claim_builder;
+mod inscription_builder;
+mod multisig;
 mod ordinals;
 mod output_builder;
+mod timelock;
 
 // Re-exports
-pub use brc20::{BRC20TransferInscription, Brc20Ticker};
+pub use anchor::{is_p2a, p2a_script, P2A_DUST_LIMIT};
+pub use batch_payment_builder::{BatchPayment, BatchPaymentBuilder};
+pub use brc20::{
+    BRC20TransferInscription, Brc20Amount, Brc20Payload, Brc20Ticker, Brc20TransferBuilder,
+};
+pub use consolidation_builder::{Consolidation, ConsolidationBuilder};
+pub use control_block_builder::ControlBlockBuilder;
+pub use cpfp_builder::{CpfpBuilder, CpfpTransaction};
+pub use fee_bump_builder::FeeBumpBuilder;
+pub use fee_estimator::{FeeEstimate, FeeEstimator, InputType};
+pub use fee_rate_recommender::{FeeRateBucket, FeeRateRecommender, MAX_BLOCK_VSIZE};
+pub use htlc::{HtlcScript, HtlcSpend, HTLC_SECRET_LEN};
 pub use input_builder::InputBuilder;
 pub use input_claim_builder::InputClaimBuilder;
+pub use inscription_builder::{InscriptionBuilder, InscriptionTransactions};
+pub use multisig::Multisig;
 pub use ordinals::{OrdinalNftInscription, OrdinalsInscription};
 pub use output_builder::OutputBuilder;
+pub(crate) use timelock::{
+    absolute_lock_satisfied, relative_lock_satisfied, timelock_from_proto, LOCKTIME_THRESHOLD,
+    SEQUENCE_ENABLE_LOCKTIME,
+};
+pub use timelock::{Timelock, TimelockScript};
 
 pub struct TaprootScript {
     pub pubkey: PublicKey,
@@ -0,0 +1,92 @@
+use crate::modules::ECDSA_SIGNATURE_LEN;
+use crate::{Error, Result};
+use bitcoin::blockdata::opcodes::all::OP_CHECKMULTISIG;
+use bitcoin::{PublicKey, ScriptBuf};
+use tw_proto::BitcoinV2::Proto;
+
+/// The maximum number of the public keys, so that the redeem script fits in a P2SH push of 520 bytes.
+pub(super) const MAX_PUBLIC_KEYS: usize = 15;
+
+/// The m-of-n `OP_CHECKMULTISIG` script, used as the redeem script of P2SH or the witness script of P2WSH.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Multisig {
+    required: usize,
+    public_keys: Vec<PublicKey>,
+}
+
+impl Multisig {
+    /// Creates the script that requires the signatures of `required` of the `public_keys`,
+    /// which are kept in the given order.
+    pub fn new(required: usize, public_keys: Vec<PublicKey>) -> Result<Self> {
+        if required == 0 || required > public_keys.len() || public_keys.len() > MAX_PUBLIC_KEYS {
+            return Err(Error::from(Proto::Error::Error_invalid_multisig));
+        }
+        Ok(Multisig {
+            required,
+            public_keys,
+        })
+    }
+
+    pub fn from_proto(multisig: &Proto::mod_Input::InputMultisig<'_>) -> Result<Self> {
+        let public_keys = multisig
+            .public_keys
+            .iter()
+            .map(|public_key| PublicKey::from_slice(public_key.as_ref()))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Self::new(multisig.required as usize, public_keys)
+    }
+
+    pub fn required(&self) -> usize {
+        self.required
+    }
+
+    pub fn public_keys(&self) -> &[PublicKey] {
+        &self.public_keys
+    }
+
+    /// Returns `OP_m <public key>... OP_n OP_CHECKMULTISIG`.
+    pub fn script(&self) -> ScriptBuf {
+        let mut builder = ScriptBuf::builder().push_int(self.required as i64);
+        for public_key in &self.public_keys {
+            builder = builder.push_key(public_key);
+        }
+        builder
+            .push_int(self.public_keys.len() as i64)
+            .push_opcode(OP_CHECKMULTISIG)
+            .into_script()
+    }
+
+    /// Returns the stack that satisfies the script, i.e. the signatures of the first `required` signers
+    /// in the order of their public keys. The stack starts with the empty dummy element,
+    /// since `OP_CHECKMULTISIG` pops one more element than it checks.
+    pub fn claim_stack(&self, signatures: &[(PublicKey, Vec<u8>)]) -> Result<Vec<Vec<u8>>> {
+        for (public_key, signature) in signatures {
+            if !self.public_keys.contains(public_key) {
+                return Err(Error::from(Proto::Error::Error_invalid_multisig));
+            }
+            bitcoin::ecdsa::Signature::from_slice(signature)?;
+        }
+
+        let mut stack = vec![Vec::new()];
+        for public_key in &self.public_keys {
+            if stack.len() > self.required {
+                break;
+            }
+            if let Some((_, signature)) = signatures.iter().find(|(signer, _)| signer == public_key)
+            {
+                stack.push(signature.clone());
+            }
+        }
+
+        if stack.len() <= self.required {
+            return Err(Error::from(Proto::Error::Error_missing_multisig_signatures));
+        }
+        Ok(stack)
+    }
+
+    /// Returns the maximum length of the claim stack, including the lengths of its elements.
+    pub fn claim_len(&self) -> u64 {
+        // length of the dummy + (length + ECDSA signature) of each signer.
+        1 + self.required as u64 * (1 + ECDSA_SIGNATURE_LEN)
+    }
+}
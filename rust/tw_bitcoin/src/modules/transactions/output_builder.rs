@@ -1,13 +1,19 @@
 use std::str::FromStr;
 
+use super::anchor::p2a_script;
 use super::brc20::{BRC20TransferInscription, Brc20Ticker};
 use super::OrdinalNftInscription;
 use crate::aliases::*;
+use crate::modules::silent_payments::SilentPaymentAddress;
 use crate::{Error, Result};
 use bitcoin::address::{Payload, WitnessVersion};
+use bitcoin::blockdata::opcodes::all::OP_PUSHNUM_16;
+use bitcoin::blockdata::script::{Instruction, PushBytesBuf};
 use bitcoin::key::TweakedPublicKey;
 use bitcoin::taproot::{LeafVersion, TapNodeHash};
-use bitcoin::{Address, PubkeyHash, ScriptBuf, ScriptHash, WPubkeyHash, WScriptHash};
+use bitcoin::{
+    Address, Network, PubkeyHash, Script, ScriptBuf, ScriptHash, WPubkeyHash, WScriptHash,
+};
 use secp256k1::hashes::Hash;
 use secp256k1::XOnlyPublicKey;
 use tw_misc::traits::ToBytesVec;
@@ -19,10 +25,24 @@ pub struct OutputBuilder;
 const NO_CONTROL_BLOCK: Option<Vec<u8>> = None;
 const NO_TAPROOT_PAYLOAD: Option<Vec<u8>> = None;
 
+/// The maximum length of the data of a standard `OP_RETURN` output.
+const MAX_OP_RETURN_DATA_LEN: usize = 80;
+/// The `OP_RETURN` and the push of the data of the maximum length.
+const MAX_OP_RETURN_SCRIPT_LEN: usize = 1 + 2 + MAX_OP_RETURN_DATA_LEN;
+
 impl OutputBuilder {
-    /// Creates the spending condition (_scriptPubkey_) for a given output.
+    /// Creates the spending condition (_scriptPubkey_) for a given output to a mainnet address.
     pub fn utxo_from_proto(
         output: &Proto::Output<'_>,
+    ) -> Result<Proto::mod_PreSigningOutput::TxOut<'static>> {
+        Self::utxo_from_proto_with_network(output, Network::Bitcoin)
+    }
+
+    /// Creates the spending condition (_scriptPubkey_) for a given output,
+    /// whose address must belong to the `network`.
+    pub fn utxo_from_proto_with_network(
+        output: &Proto::Output<'_>,
+        network: Network,
     ) -> Result<Proto::mod_PreSigningOutput::TxOut<'static>> {
         let secp = secp256k1::Secp256k1::new();
 
@@ -170,22 +190,39 @@ impl OutputBuilder {
                         Some(transfer.inscription().taproot_program().to_vec()),
                     )
                 },
+                ProtoOutputBuilder::op_return(data) => {
+                    if data.len() > MAX_OP_RETURN_DATA_LEN {
+                        return Err(Error::from(Proto::Error::Error_invalid_op_return));
+                    }
+                    let data = PushBytesBuf::try_from(data.to_vec())
+                        .map_err(|_| Error::from(Proto::Error::Error_invalid_op_return))?;
+                    (
+                        ScriptBuf::new_op_return(&data),
+                        NO_CONTROL_BLOCK,
+                        NO_TAPROOT_PAYLOAD,
+                    )
+                },
+                ProtoOutputBuilder::p2a(_) => (p2a_script(), NO_CONTROL_BLOCK, NO_TAPROOT_PAYLOAD),
                 ProtoOutputBuilder::None => {
                     return Err(Error::from(Proto::Error::Error_missing_output_builder))
                 },
             },
             // We derive the transaction type from the address.
             ProtoOutputRecipient::from_address(addr) => {
-                let proto = output_from_address(output.value, addr.as_ref())?;
+                let proto = output_from_address(output.value, addr.as_ref(), network)?;
 
                 // Recursive call, will initiate the appropraite builder.
-                return Self::utxo_from_proto(&proto);
+                return Self::utxo_from_proto_with_network(&proto, network);
             },
             ProtoOutputRecipient::None => {
                 return Err(Error::from(Proto::Error::Error_missing_recipient))
             },
         };
 
+        if script_pubkey.is_op_return() {
+            check_op_return(output.value, &script_pubkey)?;
+        }
+
         let utxo = Proto::mod_PreSigningOutput::TxOut {
             value: output.value,
             script_pubkey: script_pubkey.to_vec().into(),
@@ -197,6 +234,24 @@ impl OutputBuilder {
     }
 }
 
+/// Checks that the `OP_RETURN` output is standard, i.e. of zero value, since the value is burned,
+/// and of the data pushes of at most 80 bytes.
+fn check_op_return(value: u64, script_pubkey: &Script) -> Result<()> {
+    let invalid = || Error::from(Proto::Error::Error_invalid_op_return);
+
+    if value != 0 || script_pubkey.len() > MAX_OP_RETURN_SCRIPT_LEN {
+        return Err(invalid());
+    }
+    for instruction in script_pubkey.instructions().skip(1) {
+        match instruction.map_err(|_| invalid())? {
+            Instruction::PushBytes(_) => (),
+            Instruction::Op(op) if op.to_u8() <= OP_PUSHNUM_16.to_u8() => (),
+            Instruction::Op(_) => return Err(invalid()),
+        }
+    }
+    Ok(())
+}
+
 // Convenience helper function.
 fn redeem_script_or_hash(
     script_or_hash: &Proto::mod_Output::OutputRedeemScriptOrHash,
@@ -264,13 +319,18 @@ fn witness_pubkey_hash_from_proto(
 }
 
 // Derives the P2* output from the given address.
-fn output_from_address(value: u64, addr: &str) -> Result<Proto::Output<'static>> {
+fn output_from_address(value: u64, addr: &str, network: Network) -> Result<Proto::Output<'static>> {
     let string = String::from_utf8(addr.to_vec())
         .map_err(|_| Error::from(Proto::Error::Error_bad_address_recipient))?;
 
+    // The silent payment outputs must be derived from the inputs by the signer.
+    if SilentPaymentAddress::from_str(&string).is_ok() {
+        return Err(Error::from(Proto::Error::Error_invalid_silent_payment));
+    }
+
     let addr = Address::from_str(&string)
         .map_err(|_| Error::from(Proto::Error::Error_bad_address_recipient))?
-        .require_network(bitcoin::Network::Bitcoin)
+        .require_network(network)
         .map_err(|_| Error::from(Proto::Error::Error_bad_address_recipient))?;
 
     let proto = match addr.payload {
@@ -0,0 +1,125 @@
+use crate::modules::ECDSA_SIGNATURE_LEN;
+use crate::{Error, Result};
+use bitcoin::blockdata::opcodes::all::{OP_CHECKSIG, OP_CLTV, OP_CSV, OP_DROP};
+use bitcoin::{PublicKey, ScriptBuf};
+use tw_proto::BitcoinV2::Proto;
+
+/// The lock times below the threshold are block heights, the others are UNIX timestamps.
+pub(crate) const LOCKTIME_THRESHOLD: u32 = 500_000_000;
+/// The sequence that enables the lock time of the transaction without signaling replaceability.
+pub(crate) const SEQUENCE_ENABLE_LOCKTIME: u32 = 0xffff_fffe;
+const SEQUENCE_DISABLE_FLAG: u32 = 1 << 31;
+/// The relative lock time of the sequence is a number of 512 seconds intervals if set, a number of blocks otherwise.
+const SEQUENCE_TYPE_FLAG: u32 = 1 << 22;
+const SEQUENCE_LOCKTIME_MASK: u32 = 0xffff;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Timelock {
+    /// The lock time of the transaction, either a block height or a UNIX timestamp,
+    /// checked by `OP_CHECKLOCKTIMEVERIFY`.
+    Absolute(u32),
+    /// The sequence of the input in the BIP68 encoding, either a number of blocks
+    /// or of 512 seconds intervals, checked by `OP_CHECKSEQUENCEVERIFY`.
+    Relative(u32),
+}
+
+/// The `<timelock> OP_CHECKLOCKTIMEVERIFY|OP_CHECKSEQUENCEVERIFY OP_DROP <public key> OP_CHECKSIG` script,
+/// i.e. the output can be spent by the key once the timelock expires.
+/// It's used as the redeem script of P2SH or the witness script of P2WSH.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TimelockScript {
+    timelock: Timelock,
+    public_key: PublicKey,
+}
+
+impl TimelockScript {
+    pub fn new(timelock: Timelock, public_key: PublicKey) -> Result<Self> {
+        let valid = match timelock {
+            Timelock::Absolute(lock_time) => lock_time > 0,
+            // The disabled relative lock time would pass `OP_CHECKSEQUENCEVERIFY` unconditionally.
+            Timelock::Relative(sequence) => {
+                sequence & !(SEQUENCE_TYPE_FLAG | SEQUENCE_LOCKTIME_MASK) == 0
+                    && sequence & SEQUENCE_LOCKTIME_MASK > 0
+            },
+        };
+        if !valid {
+            return Err(Error::from(Proto::Error::Error_invalid_timelock));
+        }
+        Ok(TimelockScript {
+            timelock,
+            public_key,
+        })
+    }
+
+    pub fn from_proto(proto: &Proto::mod_Input::InputTimelock<'_>) -> Result<Self> {
+        let public_key = PublicKey::from_slice(proto.public_key.as_ref())?;
+        Self::new(timelock_from_proto(proto)?, public_key)
+    }
+
+    pub fn timelock(&self) -> Timelock {
+        self.timelock
+    }
+
+    pub fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    pub fn script(&self) -> ScriptBuf {
+        let (value, opcode) = match self.timelock {
+            Timelock::Absolute(lock_time) => (lock_time, OP_CLTV),
+            Timelock::Relative(sequence) => (sequence, OP_CSV),
+        };
+        ScriptBuf::builder()
+            .push_int(value as i64)
+            .push_opcode(opcode)
+            .push_opcode(OP_DROP)
+            .push_key(&self.public_key)
+            .push_opcode(OP_CHECKSIG)
+            .into_script()
+    }
+
+    /// Checks that the timelock has expired for the `lock_time` of the spending transaction
+    /// and the `sequence` of the spending input, which would fail the script otherwise.
+    pub fn check_spend(&self, lock_time: u32, sequence: u32) -> Result<()> {
+        let expired = match self.timelock {
+            // The lock time is disabled by the final sequence.
+            Timelock::Absolute(required) => {
+                sequence != u32::MAX && absolute_lock_satisfied(lock_time, required)
+            },
+            Timelock::Relative(required) => relative_lock_satisfied(sequence, required),
+        };
+        if !expired {
+            return Err(Error::from(Proto::Error::Error_unsatisfied_timelock));
+        }
+        Ok(())
+    }
+
+    /// Returns the maximum length of the claim stack without the script, including the lengths of its elements.
+    pub fn claim_len(&self) -> u64 {
+        // length + ECDSA signature.
+        1 + ECDSA_SIGNATURE_LEN
+    }
+}
+
+/// Returns the timelock of the input, without checking it.
+pub(crate) fn timelock_from_proto(proto: &Proto::mod_Input::InputTimelock<'_>) -> Result<Timelock> {
+    use Proto::mod_Input::mod_InputTimelock::OneOftimelock as ProtoTimelock;
+
+    match proto.timelock {
+        ProtoTimelock::lock_time(lock_time) => Ok(Timelock::Absolute(lock_time)),
+        ProtoTimelock::sequence(sequence) => Ok(Timelock::Relative(sequence)),
+        ProtoTimelock::None => Err(Error::from(Proto::Error::Error_invalid_timelock)),
+    }
+}
+
+/// Returns whether the `lock_time` of the transaction satisfies the `required` lock time of the same type.
+pub(crate) fn absolute_lock_satisfied(lock_time: u32, required: u32) -> bool {
+    (lock_time < LOCKTIME_THRESHOLD) == (required < LOCKTIME_THRESHOLD) && lock_time >= required
+}
+
+/// Returns whether the `sequence` of the input satisfies the `required` relative lock time of the same type.
+pub(crate) fn relative_lock_satisfied(sequence: u32, required: u32) -> bool {
+    sequence & SEQUENCE_DISABLE_FLAG == 0
+        && sequence & SEQUENCE_TYPE_FLAG == required & SEQUENCE_TYPE_FLAG
+        && sequence & SEQUENCE_LOCKTIME_MASK >= required & SEQUENCE_LOCKTIME_MASK
+}
@@ -0,0 +1,497 @@
+//! Transparent Zcash transactions, i.e. the transactions that only spend from and pay to t-addresses:
+//!
+//! ```rust,ignore
+//! let mut tx = ZcashTransaction::new(NU5_BRANCH_ID);
+//! tx.inputs.push(TxIn { previous_output, sequence: Sequence::MAX, ..Default::default() });
+//! tx.outputs.push(TxOut { value, script_pubkey: TAddress::from_str("t1...")?.script_pubkey() });
+//! tx.sign_p2pkh(PRIVATE_KEY, &prevouts, EcdsaSighashType::All)?;
+//! let encoded = tx.encode();
+//! ```
+//!
+//! The v4 transactions of the Sapling to Canopy upgrades are signed with the sighash of
+//! [ZIP-243](https://zips.z.cash/zip-0243), the v5 transactions since NU5 with the sighash of
+//! [ZIP-244](https://zips.z.cash/zip-0244). Both commit to the consensus branch ID of the network upgrade,
+//! which is not hardcoded, so the transactions of a future upgrade are signed by passing its branch ID.
+
+use crate::modules::context::with_context;
+use crate::modules::signer::split_sighash_type;
+use crate::{Error, Result};
+use bitcoin::consensus::encode::VarInt;
+use bitcoin::hashes::{sha256d, Hash};
+use bitcoin::sighash::EcdsaSighashType;
+use bitcoin::{base58, Network, PubkeyHash, PublicKey, ScriptBuf, ScriptHash, TxIn, TxOut, Txid};
+use secp256k1::{Message, SecretKey};
+use std::fmt;
+use std::str::FromStr;
+use tw_proto::BitcoinV2::Proto;
+
+/// The consensus branch IDs of the network upgrades, see [ZIP-200](https://zips.z.cash/zip-0200).
+pub const SAPLING_BRANCH_ID: u32 = 0x76b8_09bb;
+pub const BLOSSOM_BRANCH_ID: u32 = 0x2bb4_0e60;
+pub const HEARTWOOD_BRANCH_ID: u32 = 0xf5b9_230b;
+pub const CANOPY_BRANCH_ID: u32 = 0xe9ff_75a6;
+pub const NU5_BRANCH_ID: u32 = 0xc2d6_d0b4;
+pub const NU6_BRANCH_ID: u32 = 0xc8e7_1055;
+
+/// The bit of the header that marks the transactions since Overwinter.
+const OVERWINTERED_FLAG: u32 = 0x8000_0000;
+const V4_VERSION_GROUP_ID: u32 = 0x892f_2085;
+const V5_VERSION_GROUP_ID: u32 = 0x26a7_270a;
+
+/// The two-byte prefixes of the base58check encoded t-addresses.
+const MAINNET_P2PKH_PREFIX: [u8; 2] = [0x1c, 0xb8];
+const MAINNET_P2SH_PREFIX: [u8; 2] = [0x1c, 0xbd];
+const TESTNET_P2PKH_PREFIX: [u8; 2] = [0x1d, 0x25];
+const TESTNET_P2SH_PREFIX: [u8; 2] = [0x1c, 0xba];
+
+/// The BLAKE2b personalizations of ZIP-243.
+const V4_PREVOUTS_PERSONAL: &[u8] = b"ZcashPrevoutHash";
+const V4_SEQUENCE_PERSONAL: &[u8] = b"ZcashSequencHash";
+const V4_OUTPUTS_PERSONAL: &[u8] = b"ZcashOutputsHash";
+/// Followed by the consensus branch ID.
+const V4_SIGHASH_PERSONAL: &[u8] = b"ZcashSigHash";
+
+/// The BLAKE2b personalizations of ZIP-244.
+const V5_HEADER_PERSONAL: &[u8] = b"ZTxIdHeadersHash";
+const V5_TRANSPARENT_PERSONAL: &[u8] = b"ZTxIdTranspaHash";
+const V5_PREVOUTS_PERSONAL: &[u8] = b"ZTxIdPrevoutHash";
+const V5_AMOUNTS_PERSONAL: &[u8] = b"ZTxTrAmountsHash";
+const V5_SCRIPTS_PERSONAL: &[u8] = b"ZTxTrScriptsHash";
+const V5_SEQUENCE_PERSONAL: &[u8] = b"ZTxIdSequencHash";
+const V5_OUTPUTS_PERSONAL: &[u8] = b"ZTxIdOutputsHash";
+const V5_TXIN_PERSONAL: &[u8] = b"Zcash___TxInHash";
+const V5_SAPLING_PERSONAL: &[u8] = b"ZTxIdSaplingHash";
+const V5_ORCHARD_PERSONAL: &[u8] = b"ZTxIdOrchardHash";
+/// Followed by the consensus branch ID.
+const V5_TXID_PERSONAL: &[u8] = b"ZcashTxHash_";
+
+/// The version of a transparent transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ZcashTxVersion {
+    /// The v4 transaction of the Sapling, Blossom, Heartwood and Canopy upgrades.
+    V4,
+    /// The v5 transaction since NU5.
+    V5,
+}
+
+impl ZcashTxVersion {
+    /// Returns the version of the upgrade of the `branch_id`, where the unknown upgrades are assumed to be after NU5.
+    pub fn for_branch_id(branch_id: u32) -> Self {
+        match branch_id {
+            SAPLING_BRANCH_ID | BLOSSOM_BRANCH_ID | HEARTWOOD_BRANCH_ID | CANOPY_BRANCH_ID => {
+                ZcashTxVersion::V4
+            },
+            _ => ZcashTxVersion::V5,
+        }
+    }
+
+    fn header(&self) -> u32 {
+        match self {
+            ZcashTxVersion::V4 => OVERWINTERED_FLAG | 4,
+            ZcashTxVersion::V5 => OVERWINTERED_FLAG | 5,
+        }
+    }
+
+    fn version_group_id(&self) -> u32 {
+        match self {
+            ZcashTxVersion::V4 => V4_VERSION_GROUP_ID,
+            ZcashTxVersion::V5 => V5_VERSION_GROUP_ID,
+        }
+    }
+}
+
+/// A transparent address, i.e. the base58check encoded P2PKH or P2SH hash with a two-byte prefix.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TAddress {
+    /// The `t1` address on the mainnet, or `tm` on the testnet.
+    P2pkh { hash: PubkeyHash, network: Network },
+    /// The `t3` address on the mainnet, or `t2` on the testnet.
+    P2sh { hash: ScriptHash, network: Network },
+}
+
+impl TAddress {
+    /// Creates the P2PKH address of the `public_key`, where any network but the mainnet uses the testnet prefix.
+    pub fn p2pkh(public_key: &PublicKey, network: Network) -> Self {
+        TAddress::P2pkh {
+            hash: public_key.pubkey_hash(),
+            network,
+        }
+    }
+
+    pub fn script_pubkey(&self) -> ScriptBuf {
+        match self {
+            TAddress::P2pkh { hash, .. } => ScriptBuf::new_p2pkh(hash),
+            TAddress::P2sh { hash, .. } => ScriptBuf::new_p2sh(hash),
+        }
+    }
+}
+
+impl FromStr for TAddress {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::from(Proto::Error::Error_bad_address_recipient);
+
+        let data = base58::decode_check(s).map_err(|_| invalid())?;
+        if data.len() != 22 {
+            return Err(invalid());
+        }
+        let (prefix, hash) = data.split_at(2);
+        let address = match [prefix[0], prefix[1]] {
+            MAINNET_P2PKH_PREFIX => TAddress::P2pkh {
+                hash: PubkeyHash::from_slice(hash).map_err(|_| invalid())?,
+                network: Network::Bitcoin,
+            },
+            TESTNET_P2PKH_PREFIX => TAddress::P2pkh {
+                hash: PubkeyHash::from_slice(hash).map_err(|_| invalid())?,
+                network: Network::Testnet,
+            },
+            MAINNET_P2SH_PREFIX => TAddress::P2sh {
+                hash: ScriptHash::from_slice(hash).map_err(|_| invalid())?,
+                network: Network::Bitcoin,
+            },
+            TESTNET_P2SH_PREFIX => TAddress::P2sh {
+                hash: ScriptHash::from_slice(hash).map_err(|_| invalid())?,
+                network: Network::Testnet,
+            },
+            _ => return Err(invalid()),
+        };
+        Ok(address)
+    }
+}
+
+impl fmt::Display for TAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (prefix, hash) = match self {
+            TAddress::P2pkh {
+                hash,
+                network: Network::Bitcoin,
+            } => (MAINNET_P2PKH_PREFIX, hash.as_byte_array()),
+            TAddress::P2pkh { hash, .. } => (TESTNET_P2PKH_PREFIX, hash.as_byte_array()),
+            TAddress::P2sh {
+                hash,
+                network: Network::Bitcoin,
+            } => (MAINNET_P2SH_PREFIX, hash.as_byte_array()),
+            TAddress::P2sh { hash, .. } => (TESTNET_P2SH_PREFIX, hash.as_byte_array()),
+        };
+        let mut data = prefix.to_vec();
+        data.extend_from_slice(hash);
+        f.write_str(&base58::encode_check(&data))
+    }
+}
+
+/// A transaction without the shielded parts.
+/// The script signatures of the inputs are ignored by the sighash, so they can be set in any order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ZcashTransaction {
+    pub version: ZcashTxVersion,
+    pub consensus_branch_id: u32,
+    pub lock_time: u32,
+    /// The block height after which the transaction can't be mined, `0` to disable.
+    pub expiry_height: u32,
+    pub inputs: Vec<TxIn>,
+    pub outputs: Vec<TxOut>,
+}
+
+impl ZcashTransaction {
+    /// Creates an empty transaction of the upgrade of the `consensus_branch_id`, see [`ZcashTxVersion::for_branch_id`].
+    pub fn new(consensus_branch_id: u32) -> Self {
+        ZcashTransaction {
+            version: ZcashTxVersion::for_branch_id(consensus_branch_id),
+            consensus_branch_id,
+            lock_time: 0,
+            expiry_height: 0,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend(self.version.header().to_le_bytes());
+        data.extend(self.version.version_group_id().to_le_bytes());
+        if self.version == ZcashTxVersion::V5 {
+            data.extend(self.consensus_branch_id.to_le_bytes());
+            data.extend(self.lock_time.to_le_bytes());
+            data.extend(self.expiry_height.to_le_bytes());
+        }
+
+        data.extend(serialize(&VarInt(self.inputs.len() as u64)));
+        for input in &self.inputs {
+            data.extend(serialize(input));
+        }
+        data.extend(serialize(&VarInt(self.outputs.len() as u64)));
+        for output in &self.outputs {
+            data.extend(serialize(output));
+        }
+
+        match self.version {
+            ZcashTxVersion::V4 => {
+                data.extend(self.lock_time.to_le_bytes());
+                data.extend(self.expiry_height.to_le_bytes());
+                // The value balance, the empty Sapling spends and outputs, and the empty JoinSplits.
+                data.extend(0_u64.to_le_bytes());
+                data.extend([0; 3]);
+            },
+            // The empty Sapling spends and outputs, and the empty Orchard actions.
+            ZcashTxVersion::V5 => data.extend([0; 3]),
+        }
+        data
+    }
+
+    /// Returns the transaction ID, which is the double SHA-256 of the v4 transaction,
+    /// or the digest of ZIP-244 that doesn't commit to the script signatures of the v5 transaction.
+    pub fn txid(&self) -> Txid {
+        match self.version {
+            ZcashTxVersion::V4 => {
+                Txid::from_byte_array(sha256d::Hash::hash(&self.encode()).to_byte_array())
+            },
+            ZcashTxVersion::V5 => {
+                // The digest of the empty transparent bundle is of no data.
+                let transparent = if self.inputs.is_empty() && self.outputs.is_empty() {
+                    Vec::new()
+                } else {
+                    [
+                        self.prevouts_digest(V5_PREVOUTS_PERSONAL),
+                        self.sequence_digest(V5_SEQUENCE_PERSONAL),
+                        self.outputs_digest(V5_OUTPUTS_PERSONAL),
+                    ]
+                    .concat()
+                };
+                Txid::from_byte_array(
+                    self.v5_digest(&blake2b(V5_TRANSPARENT_PERSONAL, &transparent)),
+                )
+            },
+        }
+    }
+
+    /// Returns the sighash of the P2PKH input at `index`.
+    /// The `prevouts` are the outputs that the inputs spend, in the same order.
+    pub fn signature_hash(
+        &self,
+        index: usize,
+        prevouts: &[TxOut],
+        sighash_type: EcdsaSighashType,
+    ) -> Result<[u8; 32]> {
+        if index >= self.inputs.len() || prevouts.len() != self.inputs.len() {
+            return Err(Error::from(Proto::Error::Error_invalid_zcash_transaction));
+        }
+        // Refuse to sign a `SIGHASH_SINGLE` input that would commit to no output.
+        let is_single = matches!(
+            sighash_type,
+            EcdsaSighashType::Single | EcdsaSighashType::SinglePlusAnyoneCanPay
+        );
+        if is_single && index >= self.outputs.len() {
+            return Err(Error::from(Proto::Error::Error_utxo_invalid_sighash_type));
+        }
+
+        let sighash = match self.version {
+            ZcashTxVersion::V4 => self.v4_signature_hash(index, &prevouts[index], sighash_type),
+            ZcashTxVersion::V5 => self.v5_signature_hash(index, prevouts, sighash_type),
+        };
+        Ok(sighash)
+    }
+
+    /// Signs the inputs that spend the P2PKH outputs of the compressed public key of the `private_key`,
+    /// so the inputs of several keys are signed by calling it with each key.
+    pub fn sign_p2pkh(
+        &mut self,
+        private_key: &[u8],
+        prevouts: &[TxOut],
+        sighash_type: EcdsaSighashType,
+    ) -> Result<()> {
+        let secret = SecretKey::from_slice(private_key)
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_private_key))?;
+        let public_key = PublicKey::new(with_context(|secp| secret.public_key(secp)));
+        let script_pubkey = ScriptBuf::new_p2pkh(&public_key.pubkey_hash());
+
+        let mut signed = false;
+        for index in 0..self.inputs.len() {
+            if prevouts.get(index).map(|prevout| &prevout.script_pubkey) != Some(&script_pubkey) {
+                continue;
+            }
+            let sighash = self.signature_hash(index, prevouts, sighash_type)?;
+            let message = Message::from_slice(&sighash)
+                .map_err(|_| Error::from(Proto::Error::Error_invalid_sighash))?;
+            let sig = bitcoin::ecdsa::Signature {
+                sig: with_context(|secp| secp.sign_ecdsa(&message, &secret)),
+                hash_ty: sighash_type,
+            };
+
+            self.inputs[index].script_sig = ScriptBuf::builder()
+                .push_slice(sig.serialize())
+                .push_key(&public_key)
+                .into_script();
+            signed = true;
+        }
+
+        if !signed {
+            return Err(Error::from(Proto::Error::Error_invalid_zcash_transaction));
+        }
+        Ok(())
+    }
+
+    /// The sighash of ZIP-243, where the script code is the P2PKH script.
+    fn v4_signature_hash(
+        &self,
+        index: usize,
+        prevout: &TxOut,
+        sighash_type: EcdsaSighashType,
+    ) -> [u8; 32] {
+        let (anyone_can_pay, base_type) = split_sighash_type(sighash_type);
+        let zero = [0; 32];
+
+        let mut data = Vec::new();
+        data.extend(self.version.header().to_le_bytes());
+        data.extend(self.version.version_group_id().to_le_bytes());
+
+        if anyone_can_pay {
+            data.extend(zero);
+        } else {
+            data.extend(self.prevouts_digest(V4_PREVOUTS_PERSONAL));
+        }
+        if anyone_can_pay || base_type != EcdsaSighashType::All {
+            data.extend(zero);
+        } else {
+            data.extend(self.sequence_digest(V4_SEQUENCE_PERSONAL));
+        }
+        match base_type {
+            EcdsaSighashType::Single => data.extend(blake2b(
+                V4_OUTPUTS_PERSONAL,
+                &serialize(&self.outputs[index]),
+            )),
+            EcdsaSighashType::None => data.extend(zero),
+            _ => data.extend(self.outputs_digest(V4_OUTPUTS_PERSONAL)),
+        }
+
+        // The empty JoinSplits, Sapling spends and Sapling outputs.
+        data.extend([0; 3 * 32]);
+        data.extend(self.lock_time.to_le_bytes());
+        data.extend(self.expiry_height.to_le_bytes());
+        // The value balance.
+        data.extend(0_u64.to_le_bytes());
+        data.extend(sighash_type.to_u32().to_le_bytes());
+
+        let input = &self.inputs[index];
+        data.extend(serialize(&input.previous_output));
+        data.extend(serialize(&prevout.script_pubkey));
+        data.extend(prevout.value.to_le_bytes());
+        data.extend(input.sequence.0.to_le_bytes());
+
+        blake2b(
+            &branch_personal(V4_SIGHASH_PERSONAL, self.consensus_branch_id),
+            &data,
+        )
+    }
+
+    /// The signature digest of ZIP-244 for a transparent input.
+    fn v5_signature_hash(
+        &self,
+        index: usize,
+        prevouts: &[TxOut],
+        sighash_type: EcdsaSighashType,
+    ) -> [u8; 32] {
+        let (anyone_can_pay, base_type) = split_sighash_type(sighash_type);
+
+        let mut data = vec![sighash_type.to_u32() as u8];
+        if anyone_can_pay {
+            data.extend(blake2b(V5_PREVOUTS_PERSONAL, &[]));
+            data.extend(blake2b(V5_AMOUNTS_PERSONAL, &[]));
+            data.extend(blake2b(V5_SCRIPTS_PERSONAL, &[]));
+            data.extend(blake2b(V5_SEQUENCE_PERSONAL, &[]));
+        } else {
+            let amounts: Vec<u8> = prevouts
+                .iter()
+                .flat_map(|prevout| prevout.value.to_le_bytes())
+                .collect();
+            let scripts: Vec<u8> = prevouts
+                .iter()
+                .flat_map(|prevout| serialize(&prevout.script_pubkey))
+                .collect();
+            data.extend(self.prevouts_digest(V5_PREVOUTS_PERSONAL));
+            data.extend(blake2b(V5_AMOUNTS_PERSONAL, &amounts));
+            data.extend(blake2b(V5_SCRIPTS_PERSONAL, &scripts));
+            data.extend(self.sequence_digest(V5_SEQUENCE_PERSONAL));
+        }
+        match base_type {
+            EcdsaSighashType::Single => data.extend(blake2b(
+                V5_OUTPUTS_PERSONAL,
+                &serialize(&self.outputs[index]),
+            )),
+            EcdsaSighashType::None => data.extend(blake2b(V5_OUTPUTS_PERSONAL, &[])),
+            _ => data.extend(self.outputs_digest(V5_OUTPUTS_PERSONAL)),
+        }
+
+        let input = &self.inputs[index];
+        let mut txin = serialize(&input.previous_output);
+        txin.extend(prevouts[index].value.to_le_bytes());
+        txin.extend(serialize(&prevouts[index].script_pubkey));
+        txin.extend(input.sequence.0.to_le_bytes());
+        data.extend(blake2b(V5_TXIN_PERSONAL, &txin));
+
+        self.v5_digest(&blake2b(V5_TRANSPARENT_PERSONAL, &data))
+    }
+
+    /// The digest of the header, the `transparent` digest and the empty shielded bundles of ZIP-244.
+    fn v5_digest(&self, transparent: &[u8; 32]) -> [u8; 32] {
+        let mut header = Vec::new();
+        header.extend(self.version.header().to_le_bytes());
+        header.extend(self.version.version_group_id().to_le_bytes());
+        header.extend(self.consensus_branch_id.to_le_bytes());
+        header.extend(self.lock_time.to_le_bytes());
+        header.extend(self.expiry_height.to_le_bytes());
+
+        let data = [
+            blake2b(V5_HEADER_PERSONAL, &header),
+            *transparent,
+            blake2b(V5_SAPLING_PERSONAL, &[]),
+            blake2b(V5_ORCHARD_PERSONAL, &[]),
+        ]
+        .concat();
+        blake2b(
+            &branch_personal(V5_TXID_PERSONAL, self.consensus_branch_id),
+            &data,
+        )
+    }
+
+    fn prevouts_digest(&self, personal: &[u8]) -> [u8; 32] {
+        let data: Vec<u8> = self
+            .inputs
+            .iter()
+            .flat_map(|input| serialize(&input.previous_output))
+            .collect();
+        blake2b(personal, &data)
+    }
+
+    fn sequence_digest(&self, personal: &[u8]) -> [u8; 32] {
+        let data: Vec<u8> = self
+            .inputs
+            .iter()
+            .flat_map(|input| input.sequence.0.to_le_bytes())
+            .collect();
+        blake2b(personal, &data)
+    }
+
+    fn outputs_digest(&self, personal: &[u8]) -> [u8; 32] {
+        let data: Vec<u8> = self.outputs.iter().flat_map(serialize).collect();
+        blake2b(personal, &data)
+    }
+}
+
+fn branch_personal(prefix: &[u8], consensus_branch_id: u32) -> Vec<u8> {
+    let mut personal = prefix.to_vec();
+    personal.extend(consensus_branch_id.to_le_bytes());
+    personal
+}
+
+fn blake2b(personal: &[u8], data: &[u8]) -> [u8; 32] {
+    let hash = tw_hash::blake2::blake2_b_personal(data, 32, personal)
+        .expect("The personalization is at most 16 bytes");
+    let mut output = [0; 32];
+    output.copy_from_slice(&hash);
+    output
+}
+
+fn serialize<T: bitcoin::consensus::Encodable + ?Sized>(data: &T) -> Vec<u8> {
+    bitcoin::consensus::serialize(data)
+}
@@ -0,0 +1,186 @@
+mod common;
+
+use bitcoin::hashes::Hash;
+use bitcoin::{OutPoint, PublicKey, ScriptBuf, Transaction, TxOut, Txid, WPubkeyHash};
+use common::hex;
+use std::str::FromStr;
+use tw_bitcoin::modules::psbt::Psbt;
+use tw_bitcoin::modules::transactions::BatchPaymentBuilder;
+use tw_proto::BitcoinV2::Proto;
+
+const ALICE_PRIVATE_KEY: &str = "e253373989199da27c48680e3a3fc0f648d50f9a727ef17a7fe6a4dc3b159129";
+const ALICE_PUBKEY: &str = "030f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb";
+const TXID: &str = "8ec895b4d30adb01e38471ca1019bfc8c3e5fbd1f28d9e7b5653260d89989008";
+
+fn alice() -> ScriptBuf {
+    let pubkey = PublicKey::from_slice(&hex(ALICE_PUBKEY)).unwrap();
+    ScriptBuf::new_v0_p2wpkh(&pubkey.wpubkey_hash().unwrap())
+}
+
+/// The P2WPKH script of a recipient.
+fn recipient(index: u16) -> ScriptBuf {
+    let mut hash = [0; 20];
+    hash[..2].copy_from_slice(&index.to_be_bytes());
+    ScriptBuf::new_v0_p2wpkh(&WPubkeyHash::from_byte_array(hash))
+}
+
+fn alice_utxo(txid: &str, vout: u32, value: u64) -> (OutPoint, TxOut) {
+    let outpoint = OutPoint {
+        txid: Txid::from_str(txid).unwrap(),
+        vout,
+    };
+    let utxo = TxOut {
+        value,
+        script_pubkey: alice(),
+    };
+    (outpoint, utxo)
+}
+
+fn sign(mut psbt: Psbt) -> Transaction {
+    psbt.sign(&hex(ALICE_PRIVATE_KEY)).unwrap();
+    psbt.finalize().unwrap();
+    psbt.extract_tx().unwrap()
+}
+
+#[test]
+fn batch_payment_many_recipients() {
+    let builder = (0..300).fold(
+        BatchPaymentBuilder::new(alice()).fee_rate(10),
+        |builder, i| builder.add_payment(recipient(i), 10_000 + i as u64),
+    );
+    let (outpoint1, utxo1) = alice_utxo(TXID, 0, 2_000_000);
+    let (outpoint2, utxo2) = alice_utxo(TXID, 1, 2_000_000);
+    let (outpoint3, utxo3) = alice_utxo(TXID, 2, 2_000_000);
+    let batch = builder
+        .add_utxo(outpoint1, utxo1)
+        .add_utxo(outpoint2, utxo2)
+        // Not required.
+        .add_utxo(outpoint3, utxo3)
+        .build()
+        .unwrap();
+
+    let payments_value: u64 = (0..300).map(|i| 10_000 + i).sum();
+    let (change_vout, change) = batch.change.unwrap();
+    assert_eq!(change_vout, 300);
+    assert_eq!(change, 4_000_000 - payments_value - batch.fee);
+    assert_eq!(batch.payment_vouts, (0..300).collect::<Vec<u32>>());
+    assert_eq!(batch.fee_shares.len(), 300);
+    assert_eq!(batch.fee_shares.iter().sum::<u64>(), batch.fee);
+
+    let unsigned = &batch.psbt.inner().unsigned_tx;
+    assert_eq!(unsigned.input.len(), 2);
+    assert_eq!(unsigned.output.len(), 301);
+    for i in 0..300 {
+        assert_eq!(unsigned.output[i].script_pubkey, recipient(i as u16));
+        assert_eq!(unsigned.output[i].value, 10_000 + i as u64);
+    }
+
+    // The fee covers the signed transaction at the fee rate.
+    let tx = sign(batch.psbt);
+    assert!(10 * tx.vsize() as u64 <= batch.fee);
+}
+
+#[test]
+fn batch_payment_bip69() {
+    let txid2 = "0ec895b4d30adb01e38471ca1019bfc8c3e5fbd1f28d9e7b5653260d89989008";
+    let (outpoint1, utxo1) = alice_utxo(TXID, 0, 30_000);
+    let (outpoint2, utxo2) = alice_utxo(txid2, 1, 30_000);
+    let (outpoint3, utxo3) = alice_utxo(txid2, 0, 9_000);
+    let batch = BatchPaymentBuilder::new(alice())
+        .fee_rate(10)
+        .add_payment(recipient(2), 25_000)
+        .add_payment(recipient(1), 25_000)
+        .add_payment(recipient(3), 15_000)
+        .add_utxo(outpoint1, utxo1)
+        .add_utxo(outpoint2, utxo2)
+        .add_utxo(outpoint3, utxo3)
+        .bip69(true)
+        .build()
+        .unwrap();
+
+    // Sorted by the displayed txid, then the vout.
+    let unsigned = &batch.psbt.inner().unsigned_tx;
+    let inputs: Vec<OutPoint> = unsigned
+        .input
+        .iter()
+        .map(|txin| txin.previous_output)
+        .collect();
+    assert_eq!(inputs, vec![outpoint3, outpoint2, outpoint1]);
+    assert_eq!(
+        batch.psbt.inner().inputs[0].witness_utxo,
+        Some(alice_utxo(txid2, 0, 9_000).1)
+    );
+
+    // 339 vbytes with the change.
+    assert_eq!(batch.fee, 3_390);
+
+    // Sorted by the value, then the script.
+    assert_eq!(batch.change, Some((0, 69_000 - 65_000 - 3_390)));
+    assert_eq!(batch.payment_vouts, vec![3, 2, 1]);
+    assert_eq!(unsigned.output[1].script_pubkey, recipient(3));
+    assert_eq!(unsigned.output[2].script_pubkey, recipient(1));
+    assert_eq!(unsigned.output[3].script_pubkey, recipient(2));
+
+    let tx = sign(batch.psbt);
+    assert!(10 * tx.vsize() as u64 <= batch.fee);
+}
+
+#[test]
+fn batch_payment_dust_change() {
+    let (outpoint, utxo) = alice_utxo(TXID, 0, 100_000);
+    let builder = |value| {
+        BatchPaymentBuilder::new(alice())
+            .fee_rate(10)
+            .add_payment(recipient(1), value)
+            .add_utxo(outpoint, utxo.clone())
+    };
+
+    // 141 vbytes with the change, 110 vbytes without it.
+    let batch = builder(98_000).build().unwrap();
+    assert_eq!(batch.fee, 1_410);
+    assert_eq!(batch.change, Some((1, 590)));
+    assert_eq!(batch.fee_shares, vec![1_410]);
+
+    // The change would be dust, so it's left to the fee.
+    let batch = builder(98_100).build().unwrap();
+    assert_eq!(batch.fee, 1_900);
+    assert_eq!(batch.change, None);
+    assert_eq!(batch.psbt.inner().unsigned_tx.output.len(), 1);
+}
+
+#[test]
+fn batch_payment_invalid() {
+    let (outpoint, utxo) = alice_utxo(TXID, 0, 100_000);
+
+    let error = BatchPaymentBuilder::new(alice())
+        .add_utxo(outpoint, utxo.clone())
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        Proto::Error::from(error),
+        Proto::Error::Error_utxo_no_outputs_specified
+    );
+
+    let error = BatchPaymentBuilder::new(alice())
+        .add_payment(recipient(1), 50_000)
+        .add_payment(recipient(2), 545)
+        .add_utxo(outpoint, utxo.clone())
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        Proto::Error::from(error),
+        Proto::Error::Error_utxo_dust_output
+    );
+
+    // The payments and the fee exceed the UTXO.
+    let error = BatchPaymentBuilder::new(alice())
+        .fee_rate(10)
+        .add_payment(recipient(1), 99_000)
+        .add_utxo(outpoint, utxo)
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        Proto::Error::from(error),
+        Proto::Error::Error_utxo_insufficient_inputs
+    );
+}
@@ -0,0 +1,165 @@
+mod common;
+
+use bitcoin::{Address, Network, PublicKey, ScriptBuf};
+use common::hex;
+use secp256k1::Secp256k1;
+use std::str::FromStr;
+use tw_bitcoin::modules::bip322::{
+    message_hash, sign_message, to_sign, to_spend, verify_message, MessageSignature,
+};
+use tw_proto::BitcoinV2::Proto;
+
+/// The private key of the test vectors of BIP322.
+const PRIVATE_KEY: &str = "bb051cd0dda0246f33c5a9e133ebd8e7bc02a92af6c41adc131ccd7826c5b004";
+const PUBLIC_KEY: &str = "02c7f12003196442943d8588e01aee840423cc54fc1521526a3b85c2b0cbd58872";
+const P2WPKH_ADDRESS: &str = "bc1q9vza2e8x573nczrlzms0wvx3gsqjx7vavgkx0l";
+const P2TR_ADDRESS: &str = "bc1ppv609nr0vr25u07u95waq5lucwfm6tde4nydujnu8npg4q75mr5sxq8lt3";
+
+fn address(address: &str) -> Address {
+    Address::from_str(address)
+        .unwrap()
+        .require_network(Network::Bitcoin)
+        .unwrap()
+}
+
+fn public_key() -> PublicKey {
+    PublicKey::from_slice(&hex(PUBLIC_KEY)).unwrap()
+}
+
+#[test]
+fn bip322_message_hash_and_transactions() {
+    assert_eq!(
+        message_hash(b""),
+        hex("c90c269c4f8fcbe6880f72a721ddfbf1914268a794cbb21cfafee13770ae19f1").as_slice()
+    );
+    assert_eq!(
+        message_hash(b"Hello World"),
+        hex("f0eb03b1a75ac6d9847f55c624a99169b5dccba2a31f5b23bea77ba270de0a7a").as_slice()
+    );
+
+    let script_pubkey = address(P2WPKH_ADDRESS).script_pubkey();
+    for (message, to_spend_txid, to_sign_txid) in [
+        (
+            b"".as_slice(),
+            "c5680aa69bb8d860bf82d4e9cd3504b55dde018de765a91bb566283c545a99a7",
+            "1e9654e951a5ba44c8604c4de6c67fd78a27e81dcadcfe1edf638ba3aaebaed6",
+        ),
+        (
+            b"Hello World".as_slice(),
+            "b79d196740ad5217771c1098fc4a4b51e0535c32236c71f1ea4d61a2d603352b",
+            "88737ae86f2077145f93cc4b153ae9a1cb8d56afa511988c149c5c8c9d93bddf",
+        ),
+    ] {
+        let to_spend = to_spend(&script_pubkey, message);
+        assert_eq!(to_spend.txid().to_string(), to_spend_txid);
+        assert_eq!(to_sign(&to_spend).txid().to_string(), to_sign_txid);
+    }
+}
+
+#[test]
+fn bip322_verify_vectors() {
+    let p2wpkh = address(P2WPKH_ADDRESS);
+    let p2tr = address(P2TR_ADDRESS);
+    for (address, message, signature) in [
+        (
+            &p2wpkh,
+            b"".as_slice(),
+            "AkcwRAIgM2gBAQqvZX15ZiysmKmQpDrG83avLIT492QBzLnQIxYCIBaTpOaD20qRlEylyxFSeEA2ba9YOixpX8z46TSDtS40ASECx/EgAxlkQpQ9hYjgGu6EBCPMVPwVIVJqO4XCsMvViHI=",
+        ),
+        (
+            &p2wpkh,
+            b"Hello World".as_slice(),
+            "AkcwRAIgZRfIY3p7/DoVTty6YZbWS71bc5Vct9p9Fia83eRmw2QCICK/ENGfwLtptFluMGs2KsqoNSk89pO7F29zJLUx9a/sASECx/EgAxlkQpQ9hYjgGu6EBCPMVPwVIVJqO4XCsMvViHI=",
+        ),
+        (
+            &p2tr,
+            b"Hello World".as_slice(),
+            "AUHd69PrJQEv+oKTfZ8l+WROBHuy9HKrbFCJu7U1iK2iiEy1vMU5EfMtjc+VSHM7aU0SDbak5IUZRVno2P5mjSafAQ==",
+        ),
+    ] {
+        let signature = MessageSignature::from_base64(signature).unwrap();
+        assert!(matches!(signature, MessageSignature::Simple(_)));
+        verify_message(address, message, &signature).unwrap();
+
+        // Another message, or another address.
+        assert_eq!(
+            Proto::Error::from(verify_message(address, b"Hello", &signature).unwrap_err()),
+            Proto::Error::Error_invalid_message_signature
+        );
+        let other = if *address == p2wpkh { &p2tr } else { &p2wpkh };
+        assert_eq!(
+            Proto::Error::from(verify_message(other, message, &signature).unwrap_err()),
+            Proto::Error::Error_invalid_message_signature
+        );
+    }
+
+    assert_eq!(
+        Proto::Error::from(MessageSignature::from_base64("AkcwRAIg").unwrap_err()),
+        Proto::Error::Error_invalid_message_signature
+    );
+}
+
+#[test]
+fn bip322_sign_and_verify() {
+    let private_key = hex(PRIVATE_KEY);
+    let secp = Secp256k1::new();
+    let key = public_key();
+    let p2tr = Address::p2tr(
+        &secp,
+        key.inner.x_only_public_key().0,
+        None,
+        Network::Bitcoin,
+    );
+    assert_eq!(p2tr.to_string(), P2TR_ADDRESS);
+
+    // The script signature of the legacy and nested addresses requires the full format.
+    for (address, full) in [
+        (Address::p2pkh(&key, Network::Bitcoin), true),
+        (Address::p2shwpkh(&key, Network::Bitcoin).unwrap(), true),
+        (Address::p2wpkh(&key, Network::Bitcoin).unwrap(), false),
+        (p2tr, false),
+    ] {
+        let signature = sign_message(&private_key, &address, b"Hello World").unwrap();
+        assert_eq!(
+            matches!(signature, MessageSignature::Full(_)),
+            full,
+            "{address}"
+        );
+
+        let decoded = MessageSignature::from_base64(&signature.to_base64()).unwrap();
+        assert_eq!(decoded, signature);
+        verify_message(&address, b"Hello World", &decoded).unwrap();
+        assert_eq!(
+            Proto::Error::from(verify_message(&address, b"Hello", &decoded).unwrap_err()),
+            Proto::Error::Error_invalid_message_signature
+        );
+    }
+    assert_eq!(
+        address(P2WPKH_ADDRESS),
+        Address::p2wpkh(&key, Network::Bitcoin).unwrap()
+    );
+
+    // The key doesn't control the address, or the address is not supported.
+    let other = "036666dd712e05a487916384bfcd5973eb53e8038eccbbf97f7eed775b87389536";
+    let other = Address::p2wpkh(
+        &PublicKey::from_slice(&hex(other)).unwrap(),
+        Network::Bitcoin,
+    );
+    let p2wsh = Address::p2wsh(
+        &ScriptBuf::builder().push_key(&key).into_script(),
+        Network::Bitcoin,
+    );
+    for address in [other.unwrap(), p2wsh] {
+        assert_eq!(
+            Proto::Error::from(sign_message(&private_key, &address, b"").unwrap_err()),
+            Proto::Error::Error_unsupported_message_address
+        );
+    }
+
+    let signature = sign_message(&private_key, &address(P2WPKH_ADDRESS), b"").unwrap();
+    let p2wsh = Address::p2wsh(&ScriptBuf::new(), Network::Bitcoin);
+    assert_eq!(
+        Proto::Error::from(verify_message(&p2wsh, b"", &signature).unwrap_err()),
+        Proto::Error::Error_unsupported_message_address
+    );
+}
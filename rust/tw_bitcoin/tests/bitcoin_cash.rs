@@ -0,0 +1,284 @@
+mod common;
+
+use bitcoin::hashes::Hash;
+use bitcoin::sighash::EcdsaSighashType;
+use bitcoin::{OutPoint, PublicKey, ScriptBuf, TxOut, Txid};
+use common::hex;
+use secp256k1::{Secp256k1, SecretKey};
+use std::str::FromStr;
+use tw_bitcoin::modules::bitcoin_cash::{
+    signature_hash, CashAddress, CashAddressType, CashToken, CashTransactionBuilder, NftCapability,
+    MAINNET_PREFIX, TESTNET_PREFIX,
+};
+use tw_encoding::hex::ToHex;
+use tw_proto::BitcoinV2::Proto;
+
+const PRIVATE_KEY: &str = "7fdafb9db5bc501f2096e7d13d331dc7a75d9594af3d251313ba8b6200f4e384";
+const OWN_SCRIPT: &str = "76a914aff1e0789e5fe316b729577665aa0a04d5b0f8c788ac";
+const TOKEN_RECIPIENT: &str = "bitcoincash:zr6m7j9njldwwzlg9v7v53unlr4jkmx6eycnjehshe";
+
+fn public_key() -> PublicKey {
+    let secret = SecretKey::from_slice(&hex(PRIVATE_KEY)).unwrap();
+    PublicKey::new(secret.public_key(&Secp256k1::new()))
+}
+
+#[test]
+fn cash_address() {
+    let address =
+        CashAddress::from_str("bitcoincash:qpm2qsznhks23z7629mms6s4cwef74vcwvy22gdx6a").unwrap();
+    assert_eq!(address.address_type(), CashAddressType::P2pkh);
+    assert_eq!(
+        address.hash().to_hex(),
+        "76a04053bda0a88bda5177b86a15c3b29f559873"
+    );
+    // The prefix is optional on the mainnet, and the address may be uppercase.
+    assert_eq!(
+        CashAddress::from_str("qpm2qsznhks23z7629mms6s4cwef74vcwvy22gdx6a").unwrap(),
+        address
+    );
+    assert_eq!(
+        CashAddress::from_str("BITCOINCASH:QPM2QSZNHKS23Z7629MMS6S4CWEF74VCWVY22GDX6A").unwrap(),
+        address
+    );
+
+    let address = CashAddress::p2pkh(&public_key(), MAINNET_PREFIX, false).unwrap();
+    assert_eq!(
+        address.to_string(),
+        "bitcoincash:qzhlrcrcne07x94h99thved2pgzdtv8ccujjy73xya"
+    );
+    assert_eq!(address.script_pubkey().as_bytes().to_hex(), OWN_SCRIPT);
+    let address = CashAddress::p2pkh(&public_key(), MAINNET_PREFIX, true).unwrap();
+    assert!(address.is_token_aware());
+    assert_eq!(
+        address.to_string(),
+        "bitcoincash:zzhlrcrcne07x94h99thved2pgzdtv8ccu4chqlqmw"
+    );
+    assert_eq!(address.script_pubkey().as_bytes().to_hex(), OWN_SCRIPT);
+
+    // The token-aware address pays to the same script.
+    let token_aware = CashAddress::from_str(TOKEN_RECIPIENT).unwrap();
+    let legacy =
+        CashAddress::from_str("bitcoincash:qr6m7j9njldwwzlg9v7v53unlr4jkmx6eylep8ekg2").unwrap();
+    assert_eq!(token_aware.address_type(), CashAddressType::TokenP2pkh);
+    assert_eq!(token_aware.script_pubkey(), legacy.script_pubkey());
+
+    let p2sh =
+        CashAddress::from_str("bitcoincash:pqx578nanz2h2estzmkr53zqdg6qt8xyqvwhn6qeyc").unwrap();
+    assert_eq!(
+        p2sh.script_pubkey().as_bytes().to_hex(),
+        "a9140d4f1e7d989575660b16ec3a44406a34059cc40387"
+    );
+    let p2sh32 = CashAddress::new(MAINNET_PREFIX, CashAddressType::P2sh, &[0xaa; 32]).unwrap();
+    assert_eq!(CashAddress::from_str(&p2sh32.to_string()).unwrap(), p2sh32);
+    assert_eq!(
+        p2sh32.script_pubkey().as_bytes().to_hex(),
+        format!("aa20{}87", [0xaa; 32].to_hex())
+    );
+
+    let testnet =
+        CashAddress::from_str("bchtest:qr6m7j9njldwwzlg9v7v53unlr4jkmx6eymt9qmp0k").unwrap();
+    assert_eq!(testnet.prefix(), TESTNET_PREFIX);
+    assert_eq!(testnet.hash(), legacy.hash());
+
+    for invalid in [
+        // Bad checksum.
+        "bitcoincash:pqx578nanz2h2estzmkr53zqdg6qt8xyqvffffffff",
+        // The checksum of another prefix.
+        "bchtest:qpm2qsznhks23z7629mms6s4cwef74vcwvy22gdx6a",
+        // Mixed case.
+        "bitcoincash:Qpm2qsznhks23z7629mms6s4cwef74vcwvy22gdx6a",
+        // A legacy address.
+        "1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2",
+    ] {
+        let error = CashAddress::from_str(invalid).unwrap_err();
+        assert_eq!(
+            Proto::Error::from(error),
+            Proto::Error::Error_bad_address_recipient
+        );
+    }
+    // A 32-byte P2PKH hash.
+    CashAddress::new(MAINNET_PREFIX, CashAddressType::P2pkh, &[0xaa; 32]).unwrap_err();
+}
+
+#[test]
+fn cash_token_prefix() {
+    let category = Txid::from_byte_array([0xbb; 32]);
+    let category_hex = [0xbb; 32].to_hex();
+
+    for (token, expected) in [
+        (CashToken::fungible(category, 1), "1001"),
+        (CashToken::fungible(category, 253), "10fdfd00"),
+        (
+            CashToken::non_fungible(category, NftCapability::Immutable, Vec::new()),
+            "20",
+        ),
+        (
+            CashToken::non_fungible(category, NftCapability::Mutable, Vec::new()),
+            "21",
+        ),
+        (
+            CashToken::non_fungible(category, NftCapability::Minting, vec![0xcc]),
+            "6201cc",
+        ),
+        (
+            CashToken {
+                amount: 1,
+                ..CashToken::non_fungible(category, NftCapability::Immutable, vec![0xcc])
+            },
+            "7001cc01",
+        ),
+    ] {
+        let prefix = token.prefix().unwrap();
+        assert_eq!(prefix.to_hex(), format!("ef{category_hex}{expected}"));
+
+        // The prefix is split from the locking script.
+        let mut script_pubkey = prefix;
+        script_pubkey.extend(hex(OWN_SCRIPT));
+        let script_pubkey = ScriptBuf::from_bytes(script_pubkey);
+        let (parsed, locking_script) = CashToken::split_script_pubkey(&script_pubkey).unwrap();
+        assert_eq!(parsed, Some(token));
+        assert_eq!(locking_script.as_bytes().to_hex(), OWN_SCRIPT);
+    }
+
+    let script_pubkey = ScriptBuf::from_bytes(hex(OWN_SCRIPT));
+    let (parsed, locking_script) = CashToken::split_script_pubkey(&script_pubkey).unwrap();
+    assert_eq!(parsed, None);
+    assert_eq!(locking_script, script_pubkey.as_script());
+
+    // No tokens, too large amount or commitment.
+    for token in [
+        CashToken::fungible(category, 0),
+        CashToken::fungible(category, u64::MAX),
+        CashToken::non_fungible(category, NftCapability::Immutable, vec![0; 41]),
+    ] {
+        let error = token.prefix().unwrap_err();
+        assert_eq!(
+            Proto::Error::from(error),
+            Proto::Error::Error_invalid_cash_token
+        );
+    }
+    // The reserved bit, a commitment without an NFT, a zero amount, and an invalid capability.
+    for bitfield in ["9001", "4001cc", "1000", "23"] {
+        let script_pubkey = ScriptBuf::from_bytes(hex(&format!("ef{category_hex}{bitfield}")));
+        let error = CashToken::split_script_pubkey(&script_pubkey).unwrap_err();
+        assert_eq!(
+            Proto::Error::from(error),
+            Proto::Error::Error_invalid_cash_token
+        );
+    }
+}
+
+#[test]
+fn cash_sign_transaction() {
+    // https://blockchair.com/bitcoin-cash/transaction/96ee20002b34e468f9d3c5ee54f6a8ddaa61c118889c4f35395c2cd93ba5bbb4
+    let outpoint = OutPoint {
+        txid: Txid::from_slice(&hex(
+            "e28c2b955293159898e34c6840d99bf4d390e2ee1c6f606939f18ee1e2000d05",
+        ))
+        .unwrap(),
+        vout: 2,
+    };
+    let utxo = TxOut {
+        value: 5_151,
+        script_pubkey: ScriptBuf::from_bytes(hex(OWN_SCRIPT)),
+    };
+    let recipient = CashAddress::new(
+        MAINNET_PREFIX,
+        CashAddressType::P2pkh,
+        &hex("769bdff96a02f9135a1d19b749db6a78fe07dc90"),
+    )
+    .unwrap();
+    let change = CashAddress::new(
+        MAINNET_PREFIX,
+        CashAddressType::P2pkh,
+        &hex("9e089b6889e032d46e3b915a3392edfd616fb1c4"),
+    )
+    .unwrap();
+
+    let tx = CashTransactionBuilder::new()
+        .version(1)
+        .add_input(outpoint, utxo)
+        .add_output(600, &recipient)
+        .add_output(4_325, &change)
+        .sign(&hex(PRIVATE_KEY))
+        .unwrap();
+    assert_eq!(
+        bitcoin::consensus::serialize(&tx).to_hex(),
+        "0100000001e28c2b955293159898e34c6840d99bf4d390e2ee1c6f606939f18ee1e2000d05020000006b483045022100b70d158b43cbcded60e6977e93f9a84966bc0cec6f2dfd1463d1223a90563f0d02207548d081069de570a494d0967ba388ff02641d91cadb060587ead95a98d4e3534121038eab72ec78e639d02758e7860cdec018b49498c307791f785aa3019622f4ea5bffffffff0258020000000000001976a914769bdff96a02f9135a1d19b749db6a78fe07dc9088ace5100000000000001976a9149e089b6889e032d46e3b915a3392edfd616fb1c488ac00000000"
+    );
+}
+
+#[test]
+fn cash_sign_token_transfer() {
+    let category = Txid::from_byte_array([0xab; 32]);
+    let token = CashToken {
+        amount: 1_000,
+        ..CashToken::non_fungible(category, NftCapability::Immutable, vec![0x01, 0x02])
+    };
+    let mut token_script = token.prefix().unwrap();
+    token_script.extend(hex(OWN_SCRIPT));
+    let prevouts = vec![
+        TxOut {
+            value: 1_000,
+            script_pubkey: ScriptBuf::from_bytes(token_script),
+        },
+        TxOut {
+            value: 50_000,
+            script_pubkey: ScriptBuf::from_bytes(hex(OWN_SCRIPT)),
+        },
+    ];
+    let recipient = CashAddress::from_str(TOKEN_RECIPIENT).unwrap();
+    let change = CashAddress::p2pkh(&public_key(), MAINNET_PREFIX, false).unwrap();
+
+    let builder = CashTransactionBuilder::new()
+        .add_input(
+            OutPoint::new(Txid::from_byte_array([0x11; 32]), 0),
+            prevouts[0].clone(),
+        )
+        .add_input(
+            OutPoint::new(Txid::from_byte_array([0x22; 32]), 1),
+            prevouts[1].clone(),
+        )
+        .add_token_output(800, &recipient, token.clone())
+        .add_output(49_000, &change);
+    let tx = builder.sign(&hex(PRIVATE_KEY)).unwrap();
+
+    // The token prefix of the spent output is committed.
+    let sighash = signature_hash(&tx, 0, &prevouts, EcdsaSighashType::All).unwrap();
+    assert_eq!(
+        sighash.to_hex(),
+        "b8487a18c5cf6b329e4d91ce4f0e9f9c54cf89ede9c07991d3c99daf1d3da6b3"
+    );
+    let sighash = signature_hash(&tx, 1, &prevouts, EcdsaSighashType::All).unwrap();
+    assert_eq!(
+        sighash.to_hex(),
+        "517e980ff82f86274286590af32f297906de027ea9e6fc8f10ba5317e2d3c6bf"
+    );
+    assert_eq!(
+        bitcoin::consensus::serialize(&tx).to_hex(),
+        "02000000021111111111111111111111111111111111111111111111111111111111111111000000006b483045022100da369efbe5a3a148078c621f1da50c05a4344139f96fa80af2ac885c8b5aa91702203eb3b561faab19242d169a0737d060bd5c9c56d0b5b8a27c33d282fe5d82f6bf4121038eab72ec78e639d02758e7860cdec018b49498c307791f785aa3019622f4ea5bffffffff2222222222222222222222222222222222222222222222222222222222222222010000006a47304402203239e9dabb2fb3c7c9800752f791da2287752ae224cca79f85f33ca98826991a022072616cacb1cd878e970f2e3588c61754d01c2dd7ce174392d9217add968b47d24121038eab72ec78e639d02758e7860cdec018b49498c307791f785aa3019622f4ea5bffffffff02200300000000000041efabababababababababababababababababababababababababababababababab70020102fde80376a914f5bf48b397dae70be82b3cca4793f8eb2b6cdac988ac68bf0000000000001976a914aff1e0789e5fe316b729577665aa0a04d5b0f8c788ac00000000"
+    );
+    let (output_token, _) = CashToken::split_script_pubkey(&tx.output[0].script_pubkey).unwrap();
+    assert_eq!(output_token, Some(token.clone()));
+
+    // The tokens can't be sent to an address that isn't token-aware.
+    let error = CashTransactionBuilder::new()
+        .add_input(
+            OutPoint::new(Txid::from_byte_array([0x11; 32]), 0),
+            prevouts[0].clone(),
+        )
+        .add_token_output(800, &change, token)
+        .sign(&hex(PRIVATE_KEY))
+        .unwrap_err();
+    assert_eq!(
+        Proto::Error::from(error),
+        Proto::Error::Error_invalid_cash_token
+    );
+
+    // The input doesn't spend an output of the key.
+    let error = builder.sign(&[0x01; 32]).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(error),
+        Proto::Error::Error_invalid_prevouts
+    );
+}
@@ -0,0 +1,138 @@
+mod common;
+
+use bitcoin::{Network, PublicKey};
+use common::hex;
+use std::str::FromStr;
+use tw_bitcoin::modules::bolt11::{Description, Invoice, RouteHop};
+use tw_proto::BitcoinV2::Proto;
+
+const PAYEE_PUBKEY: &str = "03e7156ae33b0a208d0744199163177e909e80176e55d97a2f221ede0f934dd9ad";
+const HOP_PUBKEY: &str = "034f355bdcb7cc0af728ef3cceb9615d90684bb5b2ca5f859ab0f0b704075871aa";
+
+fn public_key(public_key: &str) -> PublicKey {
+    PublicKey::from_slice(&hex(public_key)).unwrap()
+}
+
+#[test]
+fn bolt11_decode_donation() {
+    // The first example of the BOLT11 specification, of any amount.
+    let invoice = Invoice::from_str(
+        "lnbc1pvjluezsp5zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zygspp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqdpl2pkx2ctnv5sxxmmwwd5kgetjypeh2ursdae8g6twvus8g6rfwvs8qun0dfjkxaq9qrsgq357wnc5r2ueh7ck6q93dj32dlqnls087fxdwk8qakdyafkq3yap9us6v52vjjsrvywa6rt52cm9r9zqt8r2t7mlcwspyetp5h2tztugp9lfyql",
+    )
+    .unwrap();
+
+    assert_eq!(invoice.network, Network::Bitcoin);
+    assert_eq!(invoice.amount_msat, None);
+    assert_eq!(invoice.timestamp, 1496314658);
+    assert_eq!(
+        invoice.payment_hash.to_vec(),
+        hex("0001020304050607080900010203040506070809000102030405060708090102")
+    );
+    assert_eq!(invoice.payment_secret, Some([0x11; 32]));
+    assert_eq!(
+        invoice.description,
+        Description::Direct("Please consider supporting this project".to_string())
+    );
+    // Recovered from the signature.
+    assert_eq!(invoice.payee, public_key(PAYEE_PUBKEY));
+    assert_eq!(invoice.expiry, 3600);
+    assert_eq!(invoice.min_final_cltv_expiry, 18);
+    assert!(invoice.route_hints.is_empty());
+
+    assert_eq!(invoice.expires_at(), 1496314658 + 3600);
+    assert!(!invoice.is_expired(1496314658 + 3599));
+    assert!(invoice.is_expired(1496314658 + 3600));
+}
+
+#[test]
+fn bolt11_decode_route_hints() {
+    // With the `n` field and a fallback address, which is skipped.
+    let invoice = Invoice::from_str(
+        "lntb2500u1pj48ugqpp5qqqsyqcyq5rqwzqfpg9scrgwpugpzysnzs23v9ccrydpk8qarc0ssp5yg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3qhp58yjmdan79s6qqdhdzgynm4zwqd5d7xmw5fk98klysy043l2ahrqsnp4q0n326hr8v9zprg8gsvezcch06gfaqqhde2aj730yg0durunfhv66xqzpucqzpgfpp3x9et2e20v6pu37c5d9vax37wxq72un98r9yqd8n2k7uklxq4aegau7vawtptkgxsja4kt99lpv6krctwpq8tpc65qgzqvzq2ps8pqqqqqlgqqqqqeqqjqp7w9t2uvas5gydqazpnytrzalfp85qzah9tkt69u3pahs0jdxantgqqqqqqqqqqq4qqqqqqqqqqzwyqq5q2evz7h8es4yuzwm5gaage32fq3w66plzc8e8a0xgq0k8eugpxwlxs3p2ua785evnnje0rrgavlmlftq0xpg6afhqgnfaf2t5fc7u92spqfaw2d",
+    )
+    .unwrap();
+
+    assert_eq!(invoice.network, Network::Testnet);
+    // 2500 µBTC.
+    assert_eq!(invoice.amount_msat, Some(250_000_000));
+    assert_eq!(invoice.timestamp, 1_700_000_000);
+    assert_eq!(
+        invoice.payment_hash.to_vec(),
+        hex("000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f")
+    );
+    assert_eq!(invoice.payment_secret, Some([0x22; 32]));
+    assert_eq!(
+        invoice.description,
+        Description::Hash(
+            hex("3925b6f67e2c340036ed12093dd44e0368df1b6ea26c53dbe4811f58fd5db8c1")
+                .try_into()
+                .unwrap()
+        )
+    );
+    assert_eq!(invoice.payee, public_key(PAYEE_PUBKEY));
+    assert_eq!(invoice.expiry, 60);
+    assert_eq!(invoice.min_final_cltv_expiry, 40);
+    assert_eq!(
+        invoice.route_hints,
+        vec![vec![
+            RouteHop {
+                node_id: public_key(HOP_PUBKEY),
+                short_channel_id: 0x0102030405060708,
+                fee_base_msat: 1000,
+                fee_proportional_millionths: 100,
+                cltv_expiry_delta: 144,
+            },
+            RouteHop {
+                node_id: public_key(PAYEE_PUBKEY),
+                short_channel_id: 42,
+                fee_base_msat: 0,
+                fee_proportional_millionths: 2500,
+                cltv_expiry_delta: 40,
+            },
+        ]]
+    );
+}
+
+#[test]
+fn bolt11_decode_pico_amount() {
+    let invoice = Invoice::from_str(
+        "lnbc250p1pj48ugqpp5qqqsyqcyq5rqwzqfpg9scrgwpugpzysnzs23v9ccrydpk8qarc0sdq2vdhkven9v58ex8690tuga4g8rugkgh9d25tm8yn7546gc8n07d9v9jtxejgczx7fuusml2furqp2q2jt4wgr3y6hg0y36uat0j8hfdd6m9m847v5qqaj4vw0",
+    )
+    .unwrap();
+    assert_eq!(invoice.amount_msat, Some(25));
+    assert_eq!(
+        invoice.description,
+        Description::Direct("coffee".to_string())
+    );
+}
+
+#[test]
+fn bolt11_decode_invalid() {
+    for invalid in [
+        // Bad checksum.
+        "lnbc250p1pj48ugqpp5qqqsyqcyq5rqwzqfpg9scrgwpugpzysnzs23v9ccrydpk8qarc0sdq2vdhkven9v58ex8690tuga4g8rugkgh9d25tm8yn7546gc8n07d9v9jtxejgczx7fuusml2furqp2q2jt4wgr3y6hg0y36uat0j8hfdd6m9m847v5qqaj4vw9",
+        // The amount isn't a whole number of millisatoshis.
+        "lnbc25p1pj48ugqpp5qqqsyqcyq5rqwzqfpg9scrgwpugpzysnzs23v9ccrydpk8qarc0sdq2vdhkven9v5adyms9lum59w8k7us5cvgsykupzqdzm4ktgvnznn5dh3xvqfazhkrwjwhuwq08slhqrrhv9j8pmc840fevknu4dg9tv46jlh56h54xqpxm778q",
+        // No payment hash.
+        "lnbcrt11pj48ugqdq2vdhkven9v5jg4tvrrv9vangpt66y78jm64te4qhzsafk5mnkdyd2g406kj0jvy2uy22as2nf95veh0n6qswvmg4nlxvfqxtckxf7cjjlfm53lfmhgqxd25pl",
+        // A segwit address.
+        "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+    ] {
+        let error = Invoice::from_str(invalid).unwrap_err();
+        assert_eq!(
+            Proto::Error::from(error),
+            Proto::Error::Error_invalid_invoice,
+            "{invalid}"
+        );
+    }
+
+    // The `n` field isn't the public key that signed the invoice.
+    let error = Invoice::from_str(
+        "lnbc1m1pj48ugqpp5qqqsyqcyq5rqwzqfpg9scrgwpugpzysnzs23v9ccrydpk8qarc0sdq2vdhkven9v5np4qd8n2k7uklxq4aegau7vawtptkgxsja4kt99lpv6krctwpq8tpc6586dalr08a5wzqdy0eunlxwctujk6u497u47r6htxfrdd8h4l97ypgla5sljrvg05gyyuxy8u6stp4egn2xdryrd6lf2r45296gdtmwgqupu484",
+    )
+    .unwrap_err();
+    assert_eq!(
+        Proto::Error::from(error),
+        Proto::Error::Error_invalid_invoice_signature
+    );
+}
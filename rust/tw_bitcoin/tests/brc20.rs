@@ -1,7 +1,12 @@
 mod common;
 
+use bitcoin::{OutPoint, PublicKey, ScriptBuf, TxOut, Txid};
 use common::hex;
+use std::str::FromStr;
 use tw_bitcoin::aliases::*;
+use tw_bitcoin::modules::transactions::{
+    Brc20Amount, Brc20Payload, Brc20Ticker, Brc20TransferBuilder,
+};
 use tw_bitcoin::BitcoinEntry;
 use tw_coin_entry::coin_entry::CoinEntry;
 use tw_coin_entry::test_utils::test_context::TestCoinContext;
@@ -135,3 +140,81 @@ fn coin_entry_sign_brc20_commit_reveal_transfer() {
     assert_eq!(transaction.inputs.len(), 1);
     assert_eq!(transaction.outputs.len(), 1);
 }
+
+#[test]
+fn brc20_transfer_inscription_flow() {
+    let alice_private_key = hex("e253373989199da27c48680e3a3fc0f648d50f9a727ef17a7fe6a4dc3b159129");
+    let alice_pubkey = PublicKey::from_slice(&hex(
+        "030f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb",
+    ))
+    .unwrap();
+    let alice = ScriptBuf::new_v0_p2wpkh(&alice_pubkey.wpubkey_hash().unwrap());
+    let bob = ScriptBuf::new_v0_p2wpkh(
+        &PublicKey::from_slice(&hex(
+            "025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f",
+        ))
+        .unwrap()
+        .wpubkey_hash()
+        .unwrap(),
+    );
+    let funding = OutPoint {
+        txid: Txid::from_str("8ec895b4d30adb01e38471ca1019bfc8c3e5fbd1f28d9e7b5653260d89989008")
+            .unwrap(),
+        vout: 1,
+    };
+
+    // 1. Alice inscribes the transfer for herself.
+    let ticker = Brc20Ticker::new("oadf".to_string()).unwrap();
+    let payload = Brc20Payload::transfer(ticker, Brc20Amount::new("20".to_string()).unwrap());
+    let txs = payload
+        .inscription_builder(alice.clone())
+        .postage(546)
+        .fee_rate(2)
+        .add_utxo(
+            funding,
+            TxOut {
+                value: 26_400,
+                script_pubkey: alice.clone(),
+            },
+        )
+        .change_script(alice.clone())
+        .build()
+        .unwrap();
+    let mut commit = txs.commit;
+    commit.sign(&alice_private_key).unwrap();
+    commit.finalize().unwrap();
+    let commit = commit.extract_tx().unwrap();
+    let change = OutPoint {
+        txid: commit.txid(),
+        vout: 1,
+    };
+
+    // 2. Alice sends the inscription to Bob, and pays the fee with the change.
+    let inscription = OutPoint {
+        txid: txs.reveal.txid(),
+        vout: 0,
+    };
+    let (mut psbt, fee) =
+        Brc20TransferBuilder::new(inscription, txs.reveal.output[0].clone(), bob.clone())
+            .fee_rate(2)
+            .add_utxo(change, commit.output[1].clone())
+            .change_script(alice.clone())
+            .build()
+            .unwrap();
+    assert_eq!(psbt.sign(&alice_private_key).unwrap(), [0, 1]);
+    psbt.finalize().unwrap();
+    let transfer = psbt.extract_tx().unwrap();
+
+    // The inscription is the first input, and is assigned to Bob's first output.
+    assert_eq!(transfer.input[0].previous_output, inscription);
+    assert_eq!(transfer.output[0].script_pubkey, bob);
+    assert_eq!(transfer.output[0].value, 546);
+    assert_eq!(transfer.output[1].script_pubkey, alice);
+    assert_eq!(transfer.output[1].value, commit.output[1].value - fee,);
+    assert!(fee >= 2 * transfer.vsize() as u64);
+
+    // The fee can't be paid without the other UTXOs.
+    Brc20TransferBuilder::new(inscription, txs.reveal.output[0].clone(), bob)
+        .build()
+        .unwrap_err();
+}
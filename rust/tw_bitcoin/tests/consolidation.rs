@@ -0,0 +1,124 @@
+mod common;
+
+use bitcoin::{OutPoint, PublicKey, ScriptBuf, Transaction, TxOut, Txid};
+use common::hex;
+use std::str::FromStr;
+use tw_bitcoin::modules::psbt::Psbt;
+use tw_bitcoin::modules::transactions::ConsolidationBuilder;
+use tw_proto::BitcoinV2::Proto;
+
+const ALICE_PRIVATE_KEY: &str = "e253373989199da27c48680e3a3fc0f648d50f9a727ef17a7fe6a4dc3b159129";
+const ALICE_PUBKEY: &str = "030f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb";
+const BOB_PUBKEY: &str = "037ed9a436e11ec4947ac4b7823787e24ba73180f1edd2857bff19c9f4d62b65bf";
+
+fn p2wpkh(pubkey: &str) -> ScriptBuf {
+    let pubkey = PublicKey::from_slice(&hex(pubkey)).unwrap();
+    ScriptBuf::new_v0_p2wpkh(&pubkey.wpubkey_hash().unwrap())
+}
+
+fn alice_utxo(vout: u32, value: u64) -> (OutPoint, TxOut) {
+    let outpoint = OutPoint {
+        txid: Txid::from_str("8ec895b4d30adb01e38471ca1019bfc8c3e5fbd1f28d9e7b5653260d89989008")
+            .unwrap(),
+        vout,
+    };
+    let utxo = TxOut {
+        value,
+        script_pubkey: p2wpkh(ALICE_PUBKEY),
+    };
+    (outpoint, utxo)
+}
+
+fn sign(mut psbt: Psbt) -> Transaction {
+    psbt.sign(&hex(ALICE_PRIVATE_KEY)).unwrap();
+    psbt.finalize().unwrap();
+    psbt.extract_tx().unwrap()
+}
+
+/// The UTXOs of 50_000, 30_000 and 500 satoshis, the last one costs more to spend at 10 sat/vB.
+fn builder() -> ConsolidationBuilder {
+    [(0, 50_000), (1, 30_000), (2, 500)].into_iter().fold(
+        ConsolidationBuilder::new(p2wpkh(BOB_PUBKEY)).fee_rate(10),
+        |builder, (vout, value)| {
+            let (outpoint, utxo) = alice_utxo(vout, value);
+            builder.add_utxo(outpoint, utxo)
+        },
+    )
+}
+
+#[test]
+fn consolidation_skips_uneconomical() {
+    let consolidation = builder().build().unwrap();
+
+    assert_eq!(consolidation.skipped, vec![alice_utxo(2, 500).0]);
+    assert_eq!(consolidation.amount, 80_000 - consolidation.fee);
+
+    let unsigned = &consolidation.psbt.inner().unsigned_tx;
+    assert_eq!(unsigned.input.len(), 2);
+    assert_eq!(unsigned.output.len(), 1);
+    assert_eq!(unsigned.output[0].value, consolidation.amount);
+    assert_eq!(unsigned.output[0].script_pubkey, p2wpkh(BOB_PUBKEY));
+
+    // The fee covers the signed transaction at the fee rate.
+    let tx = sign(consolidation.psbt);
+    assert!(10 * tx.vsize() as u64 <= consolidation.fee);
+}
+
+#[test]
+fn consolidation_sweep_all() {
+    let skipping = builder().build().unwrap();
+    let consolidation = builder().include_uneconomical(true).build().unwrap();
+
+    // The uneconomical UTXO reduces the amount.
+    assert!(consolidation.skipped.is_empty());
+    assert_eq!(consolidation.amount, 80_500 - consolidation.fee);
+    assert!(consolidation.amount < skipping.amount);
+
+    let tx = sign(consolidation.psbt);
+    assert_eq!(tx.input.len(), 3);
+    assert!(10 * tx.vsize() as u64 <= consolidation.fee);
+}
+
+#[test]
+fn consolidation_min_value() {
+    let consolidation = builder().min_value(40_000).build().unwrap();
+
+    assert_eq!(
+        consolidation.skipped,
+        vec![alice_utxo(1, 30_000).0, alice_utxo(2, 500).0]
+    );
+    assert_eq!(consolidation.amount, 50_000 - consolidation.fee);
+}
+
+#[test]
+fn consolidation_invalid() {
+    // All the UTXOs are skipped.
+    let builder = builder().min_value(100_000);
+    assert_eq!(
+        Proto::Error::from(builder.build().unwrap_err()),
+        Proto::Error::Error_utxo_insufficient_inputs
+    );
+
+    // The amount after the fee is dust.
+    let (outpoint, utxo) = alice_utxo(0, 1_500);
+    let builder = ConsolidationBuilder::new(p2wpkh(BOB_PUBKEY))
+        .fee_rate(10)
+        .add_utxo(outpoint, utxo);
+    assert_eq!(
+        Proto::Error::from(builder.build().unwrap_err()),
+        Proto::Error::Error_utxo_dust_output
+    );
+
+    // Only the P2WPKH and P2TR key-path UTXOs are supported.
+    let (outpoint, mut utxo) = alice_utxo(0, 50_000);
+    utxo.script_pubkey = ScriptBuf::new_p2pkh(
+        &PublicKey::from_slice(&hex(ALICE_PUBKEY))
+            .unwrap()
+            .pubkey_hash(),
+    );
+    let builder = ConsolidationBuilder::new(p2wpkh(BOB_PUBKEY)).add_utxo(outpoint, utxo);
+    assert_eq!(
+        Proto::Error::from(builder.build().unwrap_err()),
+        Proto::Error::Error_unsupported_funding_utxo
+    );
+}
@@ -0,0 +1,151 @@
+mod common;
+
+use bitcoin::blockdata::locktime::absolute::LockTime;
+use bitcoin::{OutPoint, PublicKey, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Txid, Witness};
+use common::hex;
+use std::str::FromStr;
+use tw_bitcoin::modules::psbt::Psbt;
+use tw_bitcoin::modules::transactions::CpfpBuilder;
+use tw_proto::BitcoinV2::Proto;
+
+const ALICE_PRIVATE_KEY: &str = "e253373989199da27c48680e3a3fc0f648d50f9a727ef17a7fe6a4dc3b159129";
+const ALICE_PUBKEY: &str = "030f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb";
+const BOB_PUBKEY: &str = "037ed9a436e11ec4947ac4b7823787e24ba73180f1edd2857bff19c9f4d62b65bf";
+
+fn p2wpkh(pubkey: &str) -> ScriptBuf {
+    let pubkey = PublicKey::from_slice(&hex(pubkey)).unwrap();
+    ScriptBuf::new_v0_p2wpkh(&pubkey.wpubkey_hash().unwrap())
+}
+
+fn alice_utxo(vout: u32, value: u64) -> (OutPoint, TxOut) {
+    let outpoint = OutPoint {
+        txid: Txid::from_str("8ec895b4d30adb01e38471ca1019bfc8c3e5fbd1f28d9e7b5653260d89989008")
+            .unwrap(),
+        vout,
+    };
+    let utxo = TxOut {
+        value,
+        script_pubkey: p2wpkh(ALICE_PUBKEY),
+    };
+    (outpoint, utxo)
+}
+
+fn sign(mut psbt: Psbt) -> Transaction {
+    psbt.sign(&hex(ALICE_PRIVATE_KEY)).unwrap();
+    psbt.finalize().unwrap();
+    psbt.extract_tx().unwrap()
+}
+
+/// Returns the signed parent transaction that spends 100_000 satoshis to Bob and the change to Alice.
+fn parent_tx(change: u64) -> Transaction {
+    let (outpoint, utxo) = alice_utxo(1, 100_000);
+    let tx = Transaction {
+        version: 2,
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: outpoint,
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::MAX,
+            witness: Witness::new(),
+        }],
+        output: vec![
+            TxOut {
+                value: 100_000 - change - 1_000,
+                script_pubkey: p2wpkh(BOB_PUBKEY),
+            },
+            TxOut {
+                value: change,
+                script_pubkey: p2wpkh(ALICE_PUBKEY),
+            },
+        ],
+    };
+    let mut psbt = Psbt::from_unsigned_tx(tx).unwrap();
+    psbt.inner_mut().inputs[0].witness_utxo = Some(utxo);
+    sign(psbt)
+}
+
+#[test]
+fn cpfp_package_fee_rate() {
+    let parent = parent_tx(49_000);
+    let cpfp = CpfpBuilder::new(parent.clone(), 1_000, 1, p2wpkh(ALICE_PUBKEY))
+        .fee_rate(20)
+        .build()
+        .unwrap();
+
+    let unsigned = &cpfp.child.inner().unsigned_tx;
+    assert_eq!(unsigned.input.len(), 1);
+    assert_eq!(unsigned.input[0].previous_output.txid, parent.txid());
+    assert_eq!(unsigned.input[0].previous_output.vout, 1);
+    assert_eq!(unsigned.output.len(), 1);
+    assert_eq!(unsigned.output[0].value, 49_000 - cpfp.fee);
+
+    assert_eq!(cpfp.package_fee, 1_000 + cpfp.fee);
+    assert_eq!(cpfp.package_fee, 20 * cpfp.package_vsize);
+    assert_eq!(cpfp.package_fee_rate(), 20.0);
+
+    let child = sign(cpfp.child);
+    assert!(parent.vsize() + child.vsize() <= cpfp.package_vsize as usize);
+}
+
+#[test]
+fn cpfp_parent_above_target() {
+    let parent = parent_tx(49_000);
+    let cpfp = CpfpBuilder::new(parent, 10_000, 1, p2wpkh(ALICE_PUBKEY))
+        .fee_rate(5)
+        .build()
+        .unwrap();
+
+    // The child pays the target fee rate itself.
+    let child = sign(cpfp.child.clone());
+    assert_eq!(cpfp.fee, 5 * child.vsize() as u64);
+    assert!(cpfp.package_fee_rate() > 5.0);
+}
+
+#[test]
+fn cpfp_additional_inputs() {
+    let parent = parent_tx(1_000);
+    let (outpoint, utxo) = alice_utxo(2, 100_000);
+    let cpfp = CpfpBuilder::new(parent, 1_000, 1, p2wpkh(ALICE_PUBKEY))
+        .fee_rate(50)
+        .add_utxo(outpoint, utxo.clone())
+        .build()
+        .unwrap();
+
+    let unsigned = &cpfp.child.inner().unsigned_tx;
+    assert_eq!(unsigned.input.len(), 2);
+    assert_eq!(unsigned.input[1].previous_output, outpoint);
+    assert_eq!(cpfp.child.inner().inputs[1].witness_utxo, Some(utxo));
+    assert_eq!(unsigned.output[0].value, 101_000 - cpfp.fee);
+    assert!(cpfp.package_fee_rate() >= 50.0);
+}
+
+#[test]
+fn cpfp_invalid() {
+    let parent = parent_tx(1_000);
+
+    let builder = CpfpBuilder::new(parent.clone(), 1_000, 2, p2wpkh(ALICE_PUBKEY));
+    assert_eq!(
+        Proto::Error::from(builder.build().unwrap_err()),
+        Proto::Error::Error_invalid_change_output
+    );
+
+    let builder = CpfpBuilder::new(parent.clone(), 1_000, 1, p2wpkh(ALICE_PUBKEY)).fee_rate(50);
+    assert_eq!(
+        Proto::Error::from(builder.build().unwrap_err()),
+        Proto::Error::Error_utxo_insufficient_inputs
+    );
+
+    let (outpoint, mut utxo) = alice_utxo(2, 100_000);
+    utxo.script_pubkey = ScriptBuf::new_p2pkh(
+        &PublicKey::from_slice(&hex(ALICE_PUBKEY))
+            .unwrap()
+            .pubkey_hash(),
+    );
+    let builder = CpfpBuilder::new(parent, 1_000, 1, p2wpkh(ALICE_PUBKEY))
+        .fee_rate(50)
+        .add_utxo(outpoint, utxo);
+    assert_eq!(
+        Proto::Error::from(builder.build().unwrap_err()),
+        Proto::Error::Error_unsupported_funding_utxo
+    );
+}
@@ -0,0 +1,162 @@
+mod common;
+
+use bitcoin::hashes::Hash;
+use bitcoin::sighash::EcdsaSighashType;
+use bitcoin::{Network, OutPoint, PublicKey, ScriptBuf, Txid};
+use common::hex;
+use secp256k1::{Secp256k1, SecretKey};
+use std::str::FromStr;
+use tw_bitcoin::modules::decred::{DecredAddress, DecredTransaction, DecredTxIn, DecredTxOut};
+use tw_encoding::hex::ToHex;
+use tw_proto::BitcoinV2::Proto;
+
+const PRIVATE_KEY: &str = "22a47fa09a223f2aa079edf85a7c2d4f8720ee63e502ee2869afab7de234b80c";
+const ORIGIN_TX_HASH: &str = "0ff6ff7c6774a56ccc51598b11724c9c441cadc52978ddb5f08f3511a0cc777a";
+
+fn own_address() -> DecredAddress {
+    let secret = SecretKey::from_slice(&hex(PRIVATE_KEY)).unwrap();
+    let public_key = PublicKey::new(secret.public_key(&Secp256k1::new()));
+    DecredAddress::p2pkh(&public_key, Network::Bitcoin)
+}
+
+fn redeem_transaction() -> DecredTransaction {
+    let origin = OutPoint {
+        txid: Txid::from_slice(&hex(ORIGIN_TX_HASH)).unwrap(),
+        vout: 0,
+    };
+    let mut tx = DecredTransaction::new();
+    tx.inputs.push(DecredTxIn::new(origin, 100_000_000));
+    tx.outputs.push(DecredTxOut::new(0, ScriptBuf::new()));
+    tx
+}
+
+#[test]
+fn decred_address() {
+    let public_key = PublicKey::from_slice(&hex(
+        "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+    ))
+    .unwrap();
+    let address = DecredAddress::p2pkh(&public_key, Network::Bitcoin);
+    assert_eq!(address.to_string(), "DsmcYVbP1Nmag2H4AS17UTvmWXmGeA7nLDx");
+    assert_eq!(
+        own_address().to_string(),
+        "DsoPDLh462ULTy1QMSvBGLqGKQENerrdZDH"
+    );
+
+    for address in [
+        "DsmcYVbP1Nmag2H4AS17UTvmWXmGeA7nLDx",
+        "Dcur2mcGjmENx4DhNqDctW5wJCVyT3Qeqkx",
+    ] {
+        assert_eq!(
+            DecredAddress::from_str(address).unwrap().to_string(),
+            address
+        );
+    }
+    let address = DecredAddress::from_str("Dcur2mcGjmENx4DhNqDctW5wJCVyT3Qeqkx").unwrap();
+    assert!(address.script_pubkey().is_p2sh());
+
+    for invalid in [
+        // Bad checksum.
+        "DsmcYVbP1Nmag2H4AS17UTvmWXmGeA7nLDy",
+        // A Ripple address.
+        "rnBFvgZphmN39GWzUJeUitaP22Fr9be75H",
+        // A Zcash t-address, whose checksum is the double SHA-256.
+        "t3gQDEavk5VzAAHK8TrQu2BWDLxEiF1unBm",
+    ] {
+        let error = DecredAddress::from_str(invalid).unwrap_err();
+        assert_eq!(
+            Proto::Error::from(error),
+            Proto::Error::Error_bad_address_recipient
+        );
+    }
+}
+
+#[test]
+fn decred_transaction_hash() {
+    let coinbase = OutPoint {
+        txid: Txid::all_zeros(),
+        vout: u32::MAX,
+    };
+    let mut input = DecredTxIn::new(coinbase, 100_000_000);
+    input.signature_script = ScriptBuf::from_bytes(hex("0000"));
+
+    let mut tx = DecredTransaction::new();
+    tx.inputs.push(input);
+    tx.outputs
+        .push(DecredTxOut::new(100_000_000, own_address().script_pubkey()));
+
+    // The hash of the prefix is in the byte order of the serialization.
+    assert_eq!(tx.txid().as_byte_array().to_hex(), ORIGIN_TX_HASH);
+
+    // The signature script isn't committed.
+    tx.inputs[0].signature_script = ScriptBuf::new();
+    assert_eq!(tx.txid().as_byte_array().to_hex(), ORIGIN_TX_HASH);
+}
+
+#[test]
+fn decred_sign_p2pkh() {
+    let mut tx = redeem_transaction();
+    let prevout_scripts = [own_address().script_pubkey()];
+
+    let sighash = tx
+        .signature_hash(0, &prevout_scripts[0], EcdsaSighashType::All)
+        .unwrap();
+    assert_eq!(
+        sighash.to_hex(),
+        "5b7dc4f6fd55809be39a06d0336103fea942428d5296cbfc9bf66f832d74e4a6"
+    );
+
+    tx.sign_p2pkh(&hex(PRIVATE_KEY), &prevout_scripts, EcdsaSighashType::All)
+        .unwrap();
+    assert_eq!(
+        tx.encode().to_hex(),
+        "0100000001\
+         0ff6ff7c6774a56ccc51598b11724c9c441cadc52978ddb5f08f3511a0cc777a0000000000ffffffff\
+         010000000000000000000000000000000000000001\
+         00e1f5050000000000000000ffffffff\
+         6a47304402201ac7bdf56a9d12f3bc09cf7b47cdfafc1348628f659e37b455d497cb6e7a748802202b3630eedee1bbc9248424e4a1b8671e14631a069f36ac8860dee0bb9ea1541f012102a673638cb9587cb68ea08dbef685c6f2d2a751a8b3c6f2a7e9a4999e6e4bfaf5"
+    );
+    assert_eq!(
+        tx.txid().as_byte_array().to_hex(),
+        "5dce245241e7753ae5dca891ecf07b21d9fe48fd5742789c492fd8c302ab9b80"
+    );
+}
+
+#[test]
+fn decred_invalid_signing() {
+    let mut tx = redeem_transaction();
+    let prevout_scripts = [own_address().script_pubkey()];
+
+    // The prevout scripts of all the inputs are required.
+    let error = tx
+        .sign_p2pkh(&hex(PRIVATE_KEY), &[], EcdsaSighashType::All)
+        .unwrap_err();
+    assert_eq!(
+        Proto::Error::from(error),
+        Proto::Error::Error_invalid_prevouts
+    );
+
+    // No input spends the outputs of the key.
+    let other_key = [0x01; 32];
+    let error = tx
+        .sign_p2pkh(&other_key, &prevout_scripts, EcdsaSighashType::All)
+        .unwrap_err();
+    assert_eq!(
+        Proto::Error::from(error),
+        Proto::Error::Error_invalid_prevouts
+    );
+
+    // The input has no output of the same index.
+    tx.outputs.clear();
+    let error = tx
+        .sign_p2pkh(
+            &hex(PRIVATE_KEY),
+            &prevout_scripts,
+            EcdsaSighashType::Single,
+        )
+        .unwrap_err();
+    assert_eq!(
+        Proto::Error::from(error),
+        Proto::Error::Error_utxo_invalid_sighash_type
+    );
+}
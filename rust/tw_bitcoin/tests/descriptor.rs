@@ -0,0 +1,241 @@
+use bitcoin::bip32::{ChildNumber, Fingerprint};
+use bitcoin::{Network, ScriptBuf};
+use std::str::FromStr;
+use tw_bitcoin::modules::descriptor::{descriptor_checksum, Descriptor, KeyOrigin};
+use tw_encoding::hex;
+
+/// The fingerprint of the "abandon ... about" master key.
+const FINGERPRINT: &str = "73c5da0a";
+/// `m/84'/0'/0'` of the "abandon ... about" mnemonic.
+const BIP84_XPUB: &str = "xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V";
+/// `m/48'/0'/{0,1,2}'/2'` of the "abandon ... about" mnemonic.
+const BIP48_XPUBS: [&str; 3] = [
+    "xpub6DkFAXWQ2dHxq2vatrt9qyA3bXYU4ToWQwCHbf5XB2mSTexcHZCeKS1VZYcPoBd5X8yVcbXFHJR9R8UCVpt82VX1VhR28mCyxUFL4r6KFrf",
+    "xpub6DzhyrnFFYQ1HimDiM388xHnDiRPNdZJFBmmxge3Y1WWcHLtMJLfRuhRHqnQCPbTj3fGKTuKFLHzzwpJkp5Dtc3UtLKZKaVZe1yqMBXd6Vk",
+    "xpub6EGx8sPr9FxPPE1rbZazhqWwpMXA3Hf5DYKtZbL7c4BSddzmQktp96UaTvecEkoCZysuaj79GMCFZYT1KKk7Ph2M3Kf5g8B82KZ8TZ9SKQR",
+];
+
+fn address_at(descriptor: &str, index: u32) -> String {
+    let descriptor = Descriptor::from_str(descriptor).unwrap();
+    let derived = descriptor.derive(index).unwrap();
+    derived.address(Network::Bitcoin).unwrap().to_string()
+}
+
+fn multisig_descriptor(script: &str) -> String {
+    let keys: Vec<_> = BIP48_XPUBS
+        .iter()
+        .enumerate()
+        .map(|(account, xpub)| format!("[{FINGERPRINT}/48'/0'/{account}'/2']{xpub}/0/*"))
+        .collect();
+    script.replace("KEYS", &keys.join(","))
+}
+
+#[test]
+fn descriptor_checksum_vectors() {
+    assert_eq!(descriptor_checksum("raw(deadbeef)").unwrap(), "89f8spxm");
+
+    let descriptor = format!("wpkh([{FINGERPRINT}/84'/0'/0']{BIP84_XPUB}/0/*)");
+    assert_eq!(descriptor_checksum(&descriptor).unwrap(), "wc3n3van");
+    Descriptor::from_str(&format!("{descriptor}#wc3n3van")).unwrap();
+    Descriptor::from_str(&format!("{descriptor}#wc3n3vaa")).unwrap_err();
+}
+
+#[test]
+fn descriptor_wpkh() {
+    let descriptor = format!("wpkh([{FINGERPRINT}/84'/0'/0']{BIP84_XPUB}/0/*)");
+    assert_eq!(
+        address_at(&descriptor, 0),
+        "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"
+    );
+
+    let change = format!("wpkh([{FINGERPRINT}/84h/0h/0h]{BIP84_XPUB}/1/*)");
+    assert_eq!(
+        address_at(&change, 3),
+        "bc1qv6vaedpeke2lxr3q0wek8dd7nzhut9w0eqkz9z"
+    );
+
+    // The full origin of the derived key.
+    let derived = Descriptor::from_str(&change).unwrap().derive(3).unwrap();
+    let hardened = |index| ChildNumber::from_hardened_idx(index).unwrap();
+    let normal = |index| ChildNumber::from_normal_idx(index).unwrap();
+    assert_eq!(
+        derived.keys[0].origin,
+        Some(KeyOrigin {
+            fingerprint: Fingerprint::from_str(FINGERPRINT).unwrap(),
+            path: vec![hardened(84), hardened(0), hardened(0), normal(1), normal(3)],
+        })
+    );
+    assert_eq!(derived.redeem_script, None);
+    assert_eq!(derived.witness_script, None);
+}
+
+#[test]
+fn descriptor_sh_wpkh() {
+    let xpub = "xpub6C6nQwHaWbSrzs5tZ1q7m5R9cPK9eYpNMFesiXsYrgc1P8bvLLAet9JfHjYXKjToD8cBRswJXXbbFpXgwsswVPAZzKMa1jUp2kVkGVUaJa7";
+    let descriptor = format!("sh(wpkh([{FINGERPRINT}/49h/0h/0h]{xpub}/0/*))#vu666hnq");
+    assert_eq!(
+        address_at(&descriptor, 1),
+        "3LtMnn87fqUeHBUG414p9CWwnoV6E2pNKS"
+    );
+
+    let derived = Descriptor::from_str(&descriptor)
+        .unwrap()
+        .derive(1)
+        .unwrap();
+    let redeem_script = derived.redeem_script.unwrap();
+    assert!(redeem_script.is_v0_p2wpkh());
+    assert_eq!(
+        derived.script_pubkey,
+        ScriptBuf::new_p2sh(&redeem_script.script_hash())
+    );
+}
+
+#[test]
+fn descriptor_pkh() {
+    let xpub = "xpub6BosfCnifzxcFwrSzQiqu2DBVTshkCXacvNsWGYJVVhhawA7d4R5WSWGFNbi8Aw6ZRc1brxMyWMzG3DSSSSoekkudhUd9yLb6qx39T9nMdj";
+    let descriptor = format!("pkh([{FINGERPRINT}/44'/0'/0']{xpub}/0/*)#8w4z8fed");
+    assert_eq!(
+        address_at(&descriptor, 0),
+        "1LqBGSKuX5yYUonjxT5qGfpUsXKYYWeabA"
+    );
+    assert_eq!(
+        address_at(&descriptor, 1),
+        "1Ak8PffB2meyfYnbXZR9EGfLfFZVpzJvQP"
+    );
+
+    // Without a wildcard.
+    let descriptor = format!("pkh({xpub}/1/0)");
+    let descriptor = Descriptor::from_str(&descriptor).unwrap();
+    assert!(!descriptor.has_wildcard());
+    let derived = descriptor.derive(7).unwrap();
+    assert_eq!(
+        derived.address(Network::Bitcoin).unwrap().to_string(),
+        "1J3J6EvPrv8q6AC3VCjWV45Uf3nssNMRtH"
+    );
+}
+
+#[test]
+fn descriptor_tr() {
+    // BIP86 test vectors.
+    let xpub = "xpub6BgBgsespWvERF3LHQu6CnqdvfEvtMcQjYrcRzx53QJjSxarj2afYWcLteoGVky7D3UKDP9QyrLprQ3VCECoY49yfdDEHGCtMMj92pReUsQ";
+    let descriptor = format!("tr([{FINGERPRINT}/86'/0'/0']{xpub}/0/*)#rg247h69");
+    assert_eq!(
+        address_at(&descriptor, 0),
+        "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr"
+    );
+    assert_eq!(
+        address_at(&descriptor, 1),
+        "bc1p4qhjn9zdvkux4e44uhx8tc55attvtyu358kutcqkudyccelu0was9fqzwh"
+    );
+
+    let derived = Descriptor::from_str(&descriptor)
+        .unwrap()
+        .derive(0)
+        .unwrap();
+    assert_eq!(
+        hex::encode(derived.tap_internal_key.unwrap().serialize(), false),
+        hex::encode(&derived.keys[0].public_key.to_bytes()[1..], false)
+    );
+
+    // An x-only key.
+    let xonly = hex::encode(derived.tap_internal_key.unwrap().serialize(), false);
+    assert_eq!(
+        address_at(&format!("tr({xonly})"), 0),
+        "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr"
+    );
+}
+
+#[test]
+fn descriptor_multisig() {
+    let descriptor = multisig_descriptor("wsh(sortedmulti(2,KEYS))");
+    assert_eq!(descriptor_checksum(&descriptor).unwrap(), "sm5z0p2s");
+    assert_eq!(
+        address_at(&descriptor, 0),
+        "bc1q2sz6vvu6k7y9gtc6kfgfe0p6xkhmvmdlu97eecjkykpdktvps08scdjgr5"
+    );
+    assert_eq!(
+        address_at(&descriptor, 1),
+        "bc1qg8fpeqrl9uf3w5vawye5s235xylqhyxd7gjs4hq78crn6sm5w3asar4472"
+    );
+
+    let derived = Descriptor::from_str(&descriptor)
+        .unwrap()
+        .derive(0)
+        .unwrap();
+    assert_eq!(
+        hex::encode(derived.witness_script.unwrap().as_bytes(), false),
+        "52210299e0abe1239f349e6dc3525dc5ad84cce6e63b61c6253d64731feefd501cc6892102cdc49e39ddebe2a8b82f8c3f90c7a5ee2cf0534aca856661575c0b01ecf2a0a22103dc1953c2756c7c58d4f48ca1bbba767f414fd236bf4d662b67721ac626c514e053ae"
+    );
+    assert_eq!(derived.keys.len(), 3);
+
+    // The keys are not sorted.
+    let descriptor = multisig_descriptor("wsh(multi(2,KEYS))");
+    assert_eq!(
+        address_at(&descriptor, 0),
+        "bc1q8z8kvwnpeqy79hfkggrtfm26hkgq2tu708a86tcwtm5gy5wrc85s99fe24"
+    );
+
+    let descriptor = multisig_descriptor("sh(multi(2,KEYS))");
+    assert_eq!(
+        address_at(&descriptor, 0),
+        "3KRuCyCPYm3WK19MdicVQewsi5ZL89wRQn"
+    );
+    assert_eq!(
+        address_at(&descriptor, 1),
+        "32M713NJHBR8raAHntfxRhpmk6FF6C9gcu"
+    );
+
+    let descriptor = multisig_descriptor("sh(wsh(sortedmulti(2,KEYS)))");
+    assert_eq!(
+        address_at(&descriptor, 0),
+        "3HFYRu1FmRu1oCZ2gLXvtrR2RYw2ynnWy1"
+    );
+    let derived = Descriptor::from_str(&descriptor)
+        .unwrap()
+        .derive(1)
+        .unwrap();
+    assert!(derived.redeem_script.as_ref().unwrap().is_v0_p2wsh());
+    assert_eq!(
+        derived.address(Network::Bitcoin).unwrap().to_string(),
+        "3BkwNX3nmb6nE5toEDAG4BDeSxVr2y3EDE"
+    );
+}
+
+#[test]
+fn descriptor_invalid() {
+    let invalid = [
+        // Unknown or unsupported scripts.
+        format!("wsh(pkh({BIP84_XPUB}/0/*))"),
+        format!("raw({BIP84_XPUB})"),
+        format!("tr({BIP84_XPUB}/0/*,pk({BIP84_XPUB}))"),
+        // Hardened derivation from an extended public key.
+        format!("wpkh({BIP84_XPUB}/0'/*)"),
+        format!("wpkh({BIP84_XPUB}/0/*')"),
+        // Invalid origins.
+        format!("wpkh([73c5da/84'/0'/0']{BIP84_XPUB}/0/*)"),
+        format!("wpkh([{FINGERPRINT}/84'/x]{BIP84_XPUB}/0/*)"),
+        // An unclosed or trailing parenthesis.
+        format!("wpkh({BIP84_XPUB}/0/*"),
+        format!("wpkh({BIP84_XPUB}/0/*))"),
+        // Uncompressed keys are not allowed in SegWit scripts.
+        "wpkh(04a34b99f22c790c4e36b2b3c2c35a36db06226e41c692fc82b8b56ac1c540c5bd5b8dec5235a0fa8722476c7709c02559e3aa73aa03918ba2d492eea75abea235)".to_string(),
+        // Invalid thresholds.
+        multisig_descriptor("wsh(multi(0,KEYS))"),
+        multisig_descriptor("wsh(multi(4,KEYS))"),
+    ];
+    for descriptor in invalid {
+        assert!(Descriptor::from_str(&descriptor).is_err(), "{descriptor}");
+    }
+
+    // Uncompressed keys are allowed in legacy scripts.
+    let descriptor = "pkh(04a34b99f22c790c4e36b2b3c2c35a36db06226e41c692fc82b8b56ac1c540c5bd5b8dec5235a0fa8722476c7709c02559e3aa73aa03918ba2d492eea75abea235)";
+    assert_eq!(
+        address_at(descriptor, 0),
+        "1HZwkjkeaoZfTSaJxDw6aKkxp45agDiEzN"
+    );
+
+    // Hardened indices can't be derived.
+    let descriptor = Descriptor::from_str(&format!("wpkh({BIP84_XPUB}/0/*)")).unwrap();
+    assert!(descriptor.has_wildcard());
+    descriptor.derive(0x8000_0000).unwrap_err();
+}
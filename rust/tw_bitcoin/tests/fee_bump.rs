@@ -0,0 +1,194 @@
+mod common;
+
+use bitcoin::blockdata::locktime::absolute::LockTime;
+use bitcoin::consensus::serialize;
+use bitcoin::{OutPoint, PublicKey, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Txid, Witness};
+use common::hex;
+use std::str::FromStr;
+use tw_bitcoin::modules::psbt::Psbt;
+use tw_bitcoin::modules::transactions::FeeBumpBuilder;
+use tw_proto::BitcoinV2::Proto;
+
+const ALICE_PRIVATE_KEY: &str = "e253373989199da27c48680e3a3fc0f648d50f9a727ef17a7fe6a4dc3b159129";
+const ALICE_PUBKEY: &str = "030f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb";
+const BOB_PUBKEY: &str = "037ed9a436e11ec4947ac4b7823787e24ba73180f1edd2857bff19c9f4d62b65bf";
+
+fn p2wpkh(pubkey: &str) -> ScriptBuf {
+    let pubkey = PublicKey::from_slice(&hex(pubkey)).unwrap();
+    ScriptBuf::new_v0_p2wpkh(&pubkey.wpubkey_hash().unwrap())
+}
+
+fn alice_utxo(vout: u32, value: u64) -> (OutPoint, TxOut) {
+    let outpoint = OutPoint {
+        txid: Txid::from_str("8ec895b4d30adb01e38471ca1019bfc8c3e5fbd1f28d9e7b5653260d89989008")
+            .unwrap(),
+        vout,
+    };
+    let utxo = TxOut {
+        value,
+        script_pubkey: p2wpkh(ALICE_PUBKEY),
+    };
+    (outpoint, utxo)
+}
+
+/// Returns the signed transaction that sends 50_000 satoshis to Bob, and the change to Alice.
+fn stuck_tx(change: u64) -> (Transaction, Vec<TxOut>) {
+    let (outpoint, utxo) = alice_utxo(1, 100_000);
+    let tx = Transaction {
+        version: 2,
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: outpoint,
+            script_sig: ScriptBuf::new(),
+            // Doesn't signal the replaceability.
+            sequence: Sequence::MAX,
+            witness: Witness::new(),
+        }],
+        output: vec![
+            TxOut {
+                value: 50_000,
+                script_pubkey: p2wpkh(BOB_PUBKEY),
+            },
+            TxOut {
+                value: change,
+                script_pubkey: p2wpkh(ALICE_PUBKEY),
+            },
+        ],
+    };
+    let mut psbt = Psbt::from_unsigned_tx(tx).unwrap();
+    psbt.inner_mut().inputs[0].witness_utxo = Some(utxo.clone());
+    psbt.sign(&hex(ALICE_PRIVATE_KEY)).unwrap();
+    psbt.finalize().unwrap();
+    (psbt.extract_tx().unwrap(), vec![utxo])
+}
+
+fn sign(mut psbt: Psbt) -> Transaction {
+    psbt.sign(&hex(ALICE_PRIVATE_KEY)).unwrap();
+    psbt.finalize().unwrap();
+    psbt.extract_tx().unwrap()
+}
+
+#[test]
+fn fee_bump_from_change() {
+    let (stuck, prevouts) = stuck_tx(49_000);
+    let (psbt, fee) = FeeBumpBuilder::from_raw(&serialize(&stuck), prevouts, 1)
+        .unwrap()
+        .fee_rate(20)
+        .build()
+        .unwrap();
+
+    let unsigned = &psbt.inner().unsigned_tx;
+    assert_eq!(unsigned.input.len(), 1);
+    assert_eq!(
+        unsigned.input[0].previous_output,
+        stuck.input[0].previous_output
+    );
+    assert_eq!(unsigned.input[0].sequence, Sequence::ENABLE_RBF_NO_LOCKTIME);
+
+    // The recipient output is the same, while the change pays the fee.
+    assert_eq!(unsigned.output[0], stuck.output[0]);
+    assert_eq!(
+        unsigned.output[1].script_pubkey,
+        stuck.output[1].script_pubkey
+    );
+    assert_eq!(unsigned.output[1].value, 100_000 - 50_000 - fee);
+
+    let replacement = sign(psbt);
+    assert_ne!(replacement.txid(), stuck.txid());
+    assert_eq!(fee, 20 * replacement.vsize() as u64);
+}
+
+#[test]
+fn fee_bump_incremental_relay_fee() {
+    // The fee rate of the stuck transaction is higher than the requested one.
+    let (stuck, prevouts) = stuck_tx(40_000);
+    let (psbt, fee) = FeeBumpBuilder::new(stuck.clone(), prevouts, 1)
+        .fee_rate(1)
+        .build()
+        .unwrap();
+
+    let replacement = sign(psbt);
+    assert_eq!(fee, 10_000 + replacement.vsize() as u64);
+    assert_eq!(
+        replacement.output[1].value,
+        40_000 - replacement.vsize() as u64
+    );
+}
+
+#[test]
+fn fee_bump_additional_inputs() {
+    let (stuck, prevouts) = stuck_tx(49_000);
+    let (outpoint, utxo) = alice_utxo(2, 200_000);
+    let (psbt, fee) = FeeBumpBuilder::new(stuck.clone(), prevouts, 1)
+        .fee_rate(400)
+        .add_utxo(outpoint, utxo.clone())
+        .build()
+        .unwrap();
+
+    let unsigned = &psbt.inner().unsigned_tx;
+    assert_eq!(unsigned.input.len(), 2);
+    assert_eq!(unsigned.input[1].previous_output, outpoint);
+    assert_eq!(unsigned.input[1].sequence, Sequence::ENABLE_RBF_NO_LOCKTIME);
+    assert_eq!(psbt.inner().inputs[1].witness_utxo, Some(utxo));
+    assert_eq!(unsigned.output[0], stuck.output[0]);
+    assert_eq!(unsigned.output[1].value, 300_000 - 50_000 - fee);
+
+    let replacement = sign(psbt);
+    assert!(fee >= 400 * replacement.vsize() as u64);
+}
+
+#[test]
+fn fee_bump_dust_change() {
+    // The change below the dust limit is left to the miners.
+    let (stuck, prevouts) = stuck_tx(49_000);
+    let (psbt, fee) = FeeBumpBuilder::new(stuck.clone(), prevouts, 1)
+        .fee_rate(351)
+        .build()
+        .unwrap();
+
+    let unsigned = &psbt.inner().unsigned_tx;
+    assert_eq!(unsigned.output.len(), 1);
+    assert_eq!(unsigned.output[0], stuck.output[0]);
+    assert_eq!(fee, 50_000);
+}
+
+#[test]
+fn fee_bump_invalid() {
+    let (stuck, prevouts) = stuck_tx(49_000);
+
+    let builder = FeeBumpBuilder::new(stuck.clone(), Vec::new(), 1);
+    assert_eq!(
+        Proto::Error::from(builder.build().unwrap_err()),
+        Proto::Error::Error_invalid_replaced_transaction
+    );
+
+    let builder = FeeBumpBuilder::new(stuck.clone(), prevouts.clone(), 2);
+    assert_eq!(
+        Proto::Error::from(builder.build().unwrap_err()),
+        Proto::Error::Error_invalid_change_output
+    );
+
+    // The change can't pay the fee, and no UTXOs are added.
+    let builder = FeeBumpBuilder::new(stuck.clone(), prevouts, 1).fee_rate(1_000);
+    assert_eq!(
+        Proto::Error::from(builder.build().unwrap_err()),
+        Proto::Error::Error_utxo_insufficient_inputs
+    );
+
+    // The outputs exceed the inputs.
+    let (_, utxo) = alice_utxo(1, 10_000);
+    let builder = FeeBumpBuilder::new(stuck, vec![utxo], 1);
+    assert_eq!(
+        Proto::Error::from(builder.build().unwrap_err()),
+        Proto::Error::Error_invalid_replaced_transaction
+    );
+
+    assert_eq!(
+        Proto::Error::from(
+            FeeBumpBuilder::from_raw(&[0x02], Vec::new(), 0)
+                .err()
+                .unwrap()
+        ),
+        Proto::Error::Error_invalid_replaced_transaction
+    );
+}
@@ -0,0 +1,125 @@
+mod common;
+
+use bitcoin::{PublicKey, ScriptBuf, Transaction, WScriptHash};
+use common::{hex, MINER_FEE, ONE_BTC};
+use secp256k1::hashes::Hash;
+use secp256k1::Secp256k1;
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::transactions::{FeeEstimator, InputType};
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+const ALICE_PRIVATE_KEY: &str = "56429688a1a6b00b90ccd22a0de0a376b6569d8684022ae92229a28478bfb657";
+const ALICE_PUBKEY: &str = "036666dd712e05a487916384bfcd5973eb53e8038eccbbf97f7eed775b87389536";
+
+fn public_key() -> PublicKey {
+    PublicKey::from_slice(&hex(ALICE_PUBKEY)).unwrap()
+}
+
+#[test]
+fn fee_estimator_known_sizes() {
+    let key = public_key();
+    let p2pkh = ScriptBuf::new_p2pkh(&key.pubkey_hash());
+    let p2wpkh = ScriptBuf::new_v0_p2wpkh(&key.wpubkey_hash().unwrap());
+    let p2wsh = ScriptBuf::new_v0_p2wsh(&WScriptHash::all_zeros());
+    let p2tr = ScriptBuf::new_v1_p2tr(&Secp256k1::new(), key.inner.x_only_public_key().0, None);
+
+    for (input, output, outputs, weight, vsize) in [
+        (InputType::P2pkh, &p2pkh, 1, 768, 192),
+        (InputType::P2wpkh, &p2wpkh, 2, 562, 141),
+        (InputType::P2shP2wpkh, &p2wpkh, 1, 530, 133),
+        (InputType::P2trKeyPath, &p2tr, 1, 444, 111),
+        (
+            InputType::P2wshMultisig {
+                required: 2,
+                total: 3,
+            },
+            &p2wsh,
+            1,
+            632,
+            158,
+        ),
+    ] {
+        let mut estimator = FeeEstimator::new().add_input(input).fee_rate(10);
+        for _ in 0..outputs {
+            estimator = estimator.add_output(output);
+        }
+        let estimate = estimator.estimate().unwrap();
+        assert_eq!(estimate.weight, weight, "{input:?}");
+        assert_eq!(estimate.vsize, vsize, "{input:?}");
+        assert_eq!(estimate.fee, 10 * vsize, "{input:?}");
+    }
+
+    for (required, total) in [(0, 1), (3, 2), (1, 16)] {
+        let estimate = FeeEstimator::new()
+            .add_input(InputType::P2wshMultisig { required, total })
+            .add_output(&p2wsh)
+            .estimate();
+        assert_eq!(
+            Proto::Error::from(estimate.unwrap_err()),
+            Proto::Error::Error_invalid_multisig
+        );
+    }
+}
+
+#[test]
+fn fee_estimator_signed_transaction() {
+    let variants = [
+        ProtoInputBuilder::p2pkh(hex(ALICE_PUBKEY).into()),
+        ProtoInputBuilder::p2wpkh(hex(ALICE_PUBKEY).into()),
+        ProtoInputBuilder::p2tr_key_path(Proto::mod_Input::InputTaprootKeyPath {
+            public_key: hex(ALICE_PUBKEY).into(),
+            one_prevout: false,
+        }),
+    ];
+    let inputs = variants
+        .into_iter()
+        .enumerate()
+        .map(|(vout, variant)| Proto::Input {
+            txid: vec![0x11; 32].into(),
+            vout: vout as u32,
+            value: ONE_BTC,
+            sighash_type: UtxoProto::SighashType::UseDefault,
+            to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder { variant }),
+            ..Default::default()
+        })
+        .collect();
+    let output = Proto::Output {
+        value: 3 * ONE_BTC - MINER_FEE,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(hex(ALICE_PUBKEY).into()),
+            }),
+        }),
+    };
+    let signing = Proto::SigningInput {
+        private_key: hex(ALICE_PRIVATE_KEY).into(),
+        inputs,
+        outputs: vec![output],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        disable_change_output: true,
+        ..Default::default()
+    };
+
+    let signed = BitcoinEntry.sign(&TestCoinContext::default(), signing);
+    assert_eq!(signed.error, Proto::Error::OK);
+    let tx: Transaction = bitcoin::consensus::deserialize(&signed.encoded).unwrap();
+
+    let key = public_key();
+    let estimate = FeeEstimator::new()
+        .add_input(InputType::P2pkh)
+        .add_input(InputType::P2wpkh)
+        .add_input(InputType::P2trKeyPath)
+        .add_output(&ScriptBuf::new_v0_p2wpkh(&key.wpubkey_hash().unwrap()))
+        .estimate()
+        .unwrap();
+
+    // The ECDSA signatures might be shorter than the maximum.
+    let weight = tx.weight().to_wu();
+    assert!(estimate.weight >= weight);
+    assert!(estimate.weight - weight <= 2 * 4 + 2);
+    assert!(estimate.vsize - tx.vsize() as u64 <= 3);
+}
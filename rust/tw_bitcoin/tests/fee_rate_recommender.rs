@@ -0,0 +1,103 @@
+use tw_bitcoin::modules::transactions::{FeeRateBucket, FeeRateRecommender};
+use tw_proto::BitcoinV2::Proto;
+
+fn buckets(buckets: &[(u64, u64)]) -> Vec<FeeRateBucket> {
+    buckets
+        .iter()
+        .map(|(fee_rate, vsize)| FeeRateBucket {
+            fee_rate: *fee_rate,
+            vsize: *vsize,
+        })
+        .collect()
+}
+
+#[test]
+fn fee_rate_recommender_mempool() {
+    // The projected blocks of 1000 vbytes pay [50, 20, 10], [10, 5], [5] and a part of [5].
+    let recommender = FeeRateRecommender::new()
+        .mempool_histogram(buckets(&[(20, 500), (50, 300), (10, 1_000), (5, 2_000)]))
+        .block_vsize(1_000);
+
+    for (target_blocks, percentile, fee_rate) in [
+        (1, 0, 10),
+        (1, 50, 20),
+        (1, 100, 50),
+        (2, 0, 5),
+        (2, 100, 10),
+        (3, 0, 5),
+        // The blocks aren't full.
+        (4, 0, 1),
+        (100, 50, 1),
+    ] {
+        assert_eq!(
+            recommender.recommend(target_blocks, percentile).unwrap(),
+            fee_rate,
+            "{target_blocks} blocks at {percentile}%"
+        );
+    }
+
+    let recommender = recommender.min_fee_rate(2);
+    assert_eq!(recommender.recommend(4, 0).unwrap(), 2);
+    assert_eq!(recommender.recommend(1, 50).unwrap(), 20);
+}
+
+#[test]
+fn fee_rate_recommender_recent_blocks() {
+    // The medians of the blocks are 10, 40 and 15.
+    let recommender = FeeRateRecommender::new()
+        .add_block(buckets(&[(30, 500), (10, 500)]))
+        .add_block(buckets(&[(40, 1_000)]))
+        .add_block(buckets(&[(8, 200), (15, 800)]));
+
+    for (target_blocks, percentile, fee_rate) in [
+        (1, 50, 40),
+        (2, 50, 15),
+        (3, 50, 10),
+        (10, 50, 10),
+        (1, 0, 40),
+        (2, 0, 10),
+        (3, 0, 8),
+        (2, 100, 30),
+    ] {
+        assert_eq!(
+            recommender.recommend(target_blocks, percentile).unwrap(),
+            fee_rate,
+            "{target_blocks} blocks at {percentile}%"
+        );
+    }
+
+    // The higher rate of the mempool and the recent blocks.
+    let recommender = recommender
+        .mempool_histogram(buckets(&[(60, 100), (20, 100)]))
+        .block_vsize(1_000);
+    assert_eq!(recommender.recommend(1, 100).unwrap(), 60);
+    assert_eq!(recommender.recommend(1, 50).unwrap(), 40);
+
+    // An empty block confirms any transaction.
+    let recommender = FeeRateRecommender::new().add_block(Vec::new());
+    assert_eq!(recommender.recommend(1, 50).unwrap(), 1);
+}
+
+#[test]
+fn fee_rate_recommender_invalid() {
+    let recommender = FeeRateRecommender::new()
+        .mempool_histogram(buckets(&[(10, 1_000)]))
+        .block_vsize(1_000);
+    assert_eq!(recommender.recommend(1, 50).unwrap(), 10);
+
+    for (recommender, target_blocks, percentile) in [
+        (recommender.clone(), 0, 50),
+        (recommender.clone(), 1, 101),
+        (recommender.block_vsize(0), 1, 50),
+        (FeeRateRecommender::new(), 1, 50),
+    ] {
+        assert_eq!(
+            Proto::Error::from(
+                recommender
+                    .recommend(target_blocks, percentile)
+                    .unwrap_err()
+            ),
+            Proto::Error::Error_invalid_fee_rate_histogram
+        );
+    }
+}
@@ -0,0 +1,245 @@
+mod common;
+
+use bitcoin::blockdata::script::Instruction;
+use bitcoin::hashes::Hash;
+use bitcoin::sighash::{EcdsaSighashType, SighashCache};
+use bitcoin::{PublicKey, Transaction};
+use common::{hex, MINER_FEE, ONE_BTC};
+use secp256k1::{ecdsa, Message, Secp256k1};
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::transactions::{HtlcScript, HtlcSpend, Timelock, TimelockScript};
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+const SENDER_PRIVATE_KEY: &str = "56429688a1a6b00b90ccd22a0de0a376b6569d8684022ae92229a28478bfb657";
+const SENDER_PUBKEY: &str = "036666dd712e05a487916384bfcd5973eb53e8038eccbbf97f7eed775b87389536";
+const RECIPIENT_PRIVATE_KEY: &str =
+    "57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a";
+const RECIPIENT_PUBKEY: &str = "028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f";
+const SECRET: [u8; 32] = [0x11; 32];
+const SECRET_HASH: &str = "02d449a31fbb267c8f352e9968a79e3e5fc95c1bbeaa502fd6454ebde5a4bedc";
+const TIMEOUT: u32 = 800_000;
+const INPUT_VALUE: u64 = 50 * ONE_BTC - 3 * MINER_FEE;
+
+fn public_key(public_key: &str) -> PublicKey {
+    PublicKey::from_slice(&hex(public_key)).unwrap()
+}
+
+fn htlc_script() -> HtlcScript {
+    let refund =
+        TimelockScript::new(Timelock::Absolute(TIMEOUT), public_key(SENDER_PUBKEY)).unwrap();
+    HtlcScript::new(
+        hex(SECRET_HASH).try_into().unwrap(),
+        public_key(RECIPIENT_PUBKEY),
+        refund,
+    )
+}
+
+fn signing_input(
+    private_key: &str,
+    secret: &[u8],
+    witness: bool,
+    lock_time: Option<UtxoProto::LockTime>,
+) -> Proto::SigningInput<'static> {
+    let txid: Vec<u8> = hex("dd9d4ca23532f5c89d016e1aacef1210ab5b9d00527c633969841daca7dd17c7")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let htlc = Proto::mod_Input::InputHtlc {
+        secret_hash: hex(SECRET_HASH).into(),
+        recipient_public_key: hex(RECIPIENT_PUBKEY).into(),
+        refund: Some(Proto::mod_Input::InputTimelock {
+            public_key: hex(SENDER_PUBKEY).into(),
+            timelock: Proto::mod_Input::mod_InputTimelock::OneOftimelock::lock_time(TIMEOUT),
+        }),
+        secret: secret.to_vec().into(),
+    };
+    let variant = if witness {
+        ProtoInputBuilder::p2wsh_htlc(htlc)
+    } else {
+        ProtoInputBuilder::p2sh_htlc(htlc)
+    };
+    let tx1 = Proto::Input {
+        txid: txid.into(),
+        vout: 0,
+        value: INPUT_VALUE,
+        sighash_type: UtxoProto::SighashType::All,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder { variant }),
+        ..Default::default()
+    };
+
+    let out1 = Proto::Output {
+        value: 50 * ONE_BTC - 4 * MINER_FEE,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(hex(RECIPIENT_PUBKEY).into()),
+            }),
+        }),
+    };
+
+    Proto::SigningInput {
+        private_key: hex(private_key).into(),
+        inputs: vec![tx1],
+        outputs: vec![out1],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        disable_change_output: true,
+        lock_time,
+        ..Default::default()
+    }
+}
+
+fn verify(sighash: &[u8], signature: &[u8], public_key: &PublicKey) {
+    // The last byte is the sighash type.
+    let signature = ecdsa::Signature::from_der(&signature[..signature.len() - 1]).unwrap();
+    Secp256k1::new()
+        .verify_ecdsa(
+            &Message::from_slice(sighash).unwrap(),
+            &signature,
+            &public_key.inner,
+        )
+        .unwrap();
+}
+
+#[test]
+fn htlc_script_template() {
+    let htlc = htlc_script();
+    assert_eq!(
+        htlc.script().as_bytes(),
+        hex(&format!(
+            "6382012088a820{SECRET_HASH}8821{RECIPIENT_PUBKEY}ac\
+             67\
+             0300350cb17521{SENDER_PUBKEY}ac\
+             68"
+        ))
+    );
+
+    assert_eq!(
+        htlc.spend_from_secret(&SECRET).unwrap(),
+        HtlcSpend::Redeem(SECRET)
+    );
+    assert_eq!(htlc.spend_from_secret(&[]).unwrap(), HtlcSpend::Refund);
+    assert_eq!(htlc.signer(&HtlcSpend::Refund), &public_key(SENDER_PUBKEY));
+
+    // Neither the secret nor of the required length.
+    for secret in [vec![0x22; 32], vec![0x11; 31]] {
+        assert_eq!(
+            Proto::Error::from(htlc.spend_from_secret(&secret).unwrap_err()),
+            Proto::Error::Error_invalid_htlc_secret
+        );
+    }
+}
+
+#[test]
+fn htlc_p2wsh_redeem_sign() {
+    let coin = TestCoinContext::default();
+    let script = htlc_script().script();
+
+    // The secret can be revealed before the timeout.
+    let input = signing_input(RECIPIENT_PRIVATE_KEY, &SECRET, true, None);
+    let signed = BitcoinEntry.sign(&coin, input);
+    assert_eq!(signed.error, Proto::Error::OK);
+    let tx: Transaction = bitcoin::consensus::deserialize(&signed.encoded).unwrap();
+    assert_eq!(tx.lock_time.to_consensus_u32(), 0);
+    assert_eq!(tx.input[0].sequence.0, u32::MAX);
+    assert!(tx.input[0].script_sig.is_empty());
+
+    let witness: Vec<Vec<u8>> = tx.input[0].witness.iter().map(|e| e.to_vec()).collect();
+    assert_eq!(witness.len(), 4);
+    assert_eq!(witness[1], SECRET);
+    assert_eq!(witness[2], [1]);
+    assert_eq!(witness[3], script.to_bytes());
+    let sighash = SighashCache::new(&tx)
+        .segwit_signature_hash(0, &script, INPUT_VALUE, EcdsaSighashType::All)
+        .unwrap();
+    verify(
+        sighash.as_byte_array(),
+        &witness[0],
+        &public_key(RECIPIENT_PUBKEY),
+    );
+
+    // A wrong secret.
+    let input = signing_input(RECIPIENT_PRIVATE_KEY, &[0x22; 32], true, None);
+    let signed = BitcoinEntry.sign(&coin, input);
+    assert_eq!(signed.error, Proto::Error::Error_invalid_htlc_secret);
+
+    // The sender can't redeem.
+    let input = signing_input(SENDER_PRIVATE_KEY, &SECRET, true, None);
+    let signed = BitcoinEntry.sign(&coin, input);
+    assert_eq!(signed.error, Proto::Error::Error_invalid_htlc);
+}
+
+#[test]
+fn htlc_p2sh_refund_sign() {
+    let coin = TestCoinContext::default();
+    let script = htlc_script().script();
+
+    // The lock time and the sequence are set to enable the timeout.
+    let input = signing_input(SENDER_PRIVATE_KEY, &[], false, None);
+    let signed = BitcoinEntry.sign(&coin, input);
+    assert_eq!(signed.error, Proto::Error::OK);
+    let tx: Transaction = bitcoin::consensus::deserialize(&signed.encoded).unwrap();
+    assert_eq!(tx.lock_time.to_consensus_u32(), TIMEOUT);
+    assert_eq!(tx.input[0].sequence.0, 0xffff_fffe);
+    assert!(tx.input[0].witness.is_empty());
+
+    // The signature, `OP_0` and the redeem script.
+    let pushes: Vec<Vec<u8>> = tx.input[0]
+        .script_sig
+        .instructions()
+        .map(|instruction| match instruction.unwrap() {
+            Instruction::PushBytes(push) => push.as_bytes().to_vec(),
+            Instruction::Op(op) => panic!("unexpected {op:?}"),
+        })
+        .collect();
+    assert_eq!(pushes.len(), 3);
+    assert!(pushes[1].is_empty());
+    assert_eq!(pushes[2], script.to_bytes());
+    let sighash = SighashCache::new(&tx)
+        .legacy_signature_hash(0, &script, EcdsaSighashType::All.to_u32())
+        .unwrap();
+    verify(
+        sighash.as_byte_array(),
+        &pushes[0],
+        &public_key(SENDER_PUBKEY),
+    );
+
+    // The timeout has not expired.
+    let lock_time = Some(UtxoProto::LockTime {
+        variant: UtxoProto::mod_LockTime::OneOfvariant::blocks(TIMEOUT - 1),
+    });
+    let input = signing_input(SENDER_PRIVATE_KEY, &[], false, lock_time);
+    let signed = BitcoinEntry.sign(&coin, input);
+    assert_eq!(signed.error, Proto::Error::Error_unsatisfied_timelock);
+
+    // The recipient can't refund.
+    let input = signing_input(RECIPIENT_PRIVATE_KEY, &[], false, None);
+    let signed = BitcoinEntry.sign(&coin, input);
+    assert_eq!(signed.error, Proto::Error::Error_invalid_htlc);
+}
+
+#[test]
+fn htlc_p2sh_redeem_script_sig() {
+    let coin = TestCoinContext::default();
+
+    let input = signing_input(RECIPIENT_PRIVATE_KEY, &SECRET, false, None);
+    let signed = BitcoinEntry.sign(&coin, input);
+    assert_eq!(signed.error, Proto::Error::OK);
+    let tx: Transaction = bitcoin::consensus::deserialize(&signed.encoded).unwrap();
+
+    // The signature, the secret, `OP_1` and the redeem script.
+    let instructions: Vec<_> = tx.input[0]
+        .script_sig
+        .instructions()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(instructions.len(), 4);
+    assert_eq!(instructions[1].push_bytes().unwrap().as_bytes(), SECRET);
+    assert_eq!(
+        instructions[2].opcode(),
+        Some(bitcoin::blockdata::opcodes::all::OP_PUSHNUM_1)
+    );
+}
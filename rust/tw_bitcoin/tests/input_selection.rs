@@ -872,3 +872,157 @@ fn input_selection_use_all_without_change_output() {
     assert!(tx.outputs[0].taproot_payload.is_empty());
     assert!(tx.outputs[0].control_block.is_empty());
 }
+
+#[test]
+fn input_selection_dust_change_dropped_to_fee() {
+    let coin = TestCoinContext::default();
+
+    let alice_pubkey = hex(ALICE_PUBKEY);
+    let bob_pubkey = hex(BOB_PUBKEY);
+
+    let tx1 = Proto::Input {
+        txid: vec![1; 32].into(),
+        vout: 0,
+        value: ONE_BTC,
+        sighash_type: UtxoProto::SighashType::All,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2wpkh(alice_pubkey.as_slice().into()),
+        }),
+        ..Default::default()
+    };
+
+    // The fee is 7_000 with the change output, so the change would be 100.
+    let out1 = Proto::Output {
+        value: ONE_BTC - 7_000 - 100,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(bob_pubkey.as_slice().into()),
+            }),
+        }),
+    };
+
+    let change_output = Proto::Output {
+        value: 0,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.as_slice().into()),
+            }),
+        }),
+    };
+
+    let signing = |dust_policy| Proto::SigningInput {
+        private_key: hex(ALICE_PRIVATE_KEY).into(),
+        input_selector: UtxoProto::InputSelector::UseAll,
+        inputs: vec![tx1.clone()],
+        outputs: vec![out1.clone()],
+        change_output: Some(change_output.clone()),
+        fee_per_vb: SAT_VBYTE,
+        dust_policy,
+        ..Default::default()
+    };
+
+    // The dust change output is created by default.
+    let signed = BitcoinEntry.sign(&coin, signing(UtxoProto::DustPolicy::DustInclude));
+    assert_eq!(signed.error, Proto::Error::OK);
+    let tx = signed.transaction.unwrap();
+    assert_eq!(tx.outputs.len(), 2);
+    assert_eq!(tx.outputs[1].value, 100);
+
+    let signed = BitcoinEntry.sign(&coin, signing(UtxoProto::DustPolicy::DustReject));
+    assert_eq!(signed.error, Proto::Error::Error_utxo_dust_output);
+
+    // The change is added to the fee.
+    let signed = BitcoinEntry.sign(&coin, signing(UtxoProto::DustPolicy::DustDropChangeToFee));
+    assert_eq!(signed.error, Proto::Error::OK);
+    assert_eq!(signed.fee, 7_100);
+    assert!(signed.change_absorbed);
+    assert_eq!(signed.absorbed_change, 100);
+    let tx = signed.transaction.unwrap();
+    assert_eq!(tx.outputs.len(), 1);
+    assert_eq!(tx.outputs[0].value, out1.value);
+}
+
+#[test]
+fn input_selection_change_to_fee_threshold() {
+    let coin = TestCoinContext::default();
+
+    let tx1 = Proto::Input {
+        txid: vec![1; 32].into(),
+        vout: 0,
+        value: ONE_BTC,
+        sighash_type: UtxoProto::SighashType::All,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2wpkh(hex(ALICE_PUBKEY).into()),
+        }),
+        ..Default::default()
+    };
+
+    // The fee is 7_000 with the change output, so the change would be 10_000.
+    let out1 = Proto::Output {
+        value: ONE_BTC - 7_000 - 10_000,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(hex(BOB_PUBKEY).into()),
+            }),
+        }),
+    };
+
+    let change_output = Proto::Output {
+        value: 0,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(hex(ALICE_PUBKEY).into()),
+            }),
+        }),
+    };
+
+    let signing = |change_to_fee_threshold, dust_policy| Proto::SigningInput {
+        private_key: hex(ALICE_PRIVATE_KEY).into(),
+        input_selector: UtxoProto::InputSelector::UseAll,
+        inputs: vec![tx1.clone()],
+        outputs: vec![out1.clone()],
+        change_output: Some(change_output.clone()),
+        fee_per_vb: SAT_VBYTE,
+        dust_policy,
+        change_to_fee_threshold,
+        ..Default::default()
+    };
+
+    // The change isn't below the threshold.
+    for threshold in [0, 10_000] {
+        let signed = BitcoinEntry.sign(
+            &coin,
+            signing(threshold, UtxoProto::DustPolicy::DustInclude),
+        );
+        assert_eq!(signed.error, Proto::Error::OK);
+        assert!(!signed.change_absorbed);
+        assert_eq!(signed.absorbed_change, 0);
+        assert_eq!(signed.fee, 7_000);
+        let tx = signed.transaction.unwrap();
+        assert_eq!(tx.outputs.len(), 2);
+        assert_eq!(tx.outputs[1].value, 10_000);
+    }
+
+    // The change is added to the fee, which is reported before signing as well.
+    let presigned =
+        BitcoinEntry.preimage_hashes(&coin, signing(10_001, UtxoProto::DustPolicy::DustInclude));
+    assert_eq!(presigned.error, Proto::Error::OK);
+    assert!(presigned.change_absorbed);
+    assert_eq!(presigned.absorbed_change, 10_000);
+    assert_eq!(presigned.fee_estimate, 17_000);
+    assert_eq!(presigned.utxo_outputs.len(), 1);
+
+    let signed = BitcoinEntry.sign(&coin, signing(10_001, UtxoProto::DustPolicy::DustInclude));
+    assert_eq!(signed.error, Proto::Error::OK);
+    assert!(signed.change_absorbed);
+    assert_eq!(signed.absorbed_change, 10_000);
+    assert_eq!(signed.fee, 17_000);
+    let tx = signed.transaction.unwrap();
+    assert_eq!(tx.outputs.len(), 1);
+    assert_eq!(tx.outputs[0].value, out1.value);
+
+    // The absorbed change is never rejected as dust.
+    let signed = BitcoinEntry.sign(&coin, signing(10_001, UtxoProto::DustPolicy::DustReject));
+    assert_eq!(signed.error, Proto::Error::OK);
+    assert!(signed.change_absorbed);
+}
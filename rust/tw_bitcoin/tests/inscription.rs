@@ -0,0 +1,157 @@
+mod common;
+
+use bitcoin::blockdata::script::Instruction;
+use bitcoin::hashes::Hash;
+use bitcoin::sighash::{Prevouts, SighashCache, TapSighashType};
+use bitcoin::taproot::{ControlBlock, LeafVersion, TapLeafHash};
+use bitcoin::{OutPoint, PublicKey, Script, ScriptBuf, TxOut, Txid};
+use common::hex;
+use secp256k1::{Message, Secp256k1, XOnlyPublicKey};
+use std::str::FromStr;
+use tw_bitcoin::modules::transactions::InscriptionBuilder;
+use tw_proto::BitcoinV2::Proto;
+
+const ALICE_PRIVATE_KEY: &str = "e253373989199da27c48680e3a3fc0f648d50f9a727ef17a7fe6a4dc3b159129";
+const ALICE_PUBKEY: &str = "030f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb";
+const EPHEMERAL_KEY: &str = "3cbe6a3c13b3a437a7c2ca45e8a6cfc38c1cd7fbc23e0e2a5cbdfd391a83a57f";
+const CONTENT_TYPE: &[u8] = b"text/plain;charset=utf-8";
+
+fn alice_p2wpkh() -> ScriptBuf {
+    let pubkey = PublicKey::from_slice(&hex(ALICE_PUBKEY)).unwrap();
+    ScriptBuf::new_v0_p2wpkh(&pubkey.wpubkey_hash().unwrap())
+}
+
+fn alice_utxo(value: u64) -> (OutPoint, TxOut) {
+    let outpoint = OutPoint {
+        txid: Txid::from_str("8ec895b4d30adb01e38471ca1019bfc8c3e5fbd1f28d9e7b5653260d89989008")
+            .unwrap(),
+        vout: 1,
+    };
+    let utxo = TxOut {
+        value,
+        script_pubkey: alice_p2wpkh(),
+    };
+    (outpoint, utxo)
+}
+
+#[test]
+fn inscription_commit_reveal() {
+    // The body is pushed in chunks of 520 bytes.
+    let body = vec![0xab; 1000];
+    let (outpoint, utxo) = alice_utxo(100_000);
+    let txs = InscriptionBuilder::new(CONTENT_TYPE, &body, alice_p2wpkh())
+        .postage(546)
+        .fee_rate(5)
+        .add_utxo(outpoint, utxo.clone())
+        .change_script(alice_p2wpkh())
+        .build_with_key(&hex(EPHEMERAL_KEY))
+        .unwrap();
+
+    let commit = txs.commit.inner().unsigned_tx.clone();
+    let commit_output = commit.output[0].clone();
+    assert!(commit_output.script_pubkey.is_v1_p2tr());
+    assert_eq!(commit_output.value, 546 + txs.reveal_fee);
+    assert_eq!(commit.output[1].script_pubkey, alice_p2wpkh());
+    assert_eq!(
+        commit.output[0].value + commit.output[1].value + txs.commit_fee,
+        utxo.value
+    );
+    assert_eq!(txs.reveal_fee, 5 * txs.reveal.vsize() as u64);
+
+    // The signatures don't change the commit txid that the reveal spends.
+    let mut psbt = txs.commit.clone();
+    assert_eq!(psbt.sign(&hex(ALICE_PRIVATE_KEY)).unwrap(), [0]);
+    psbt.finalize().unwrap();
+    let signed_commit = psbt.extract_tx().unwrap();
+    assert_eq!(signed_commit.txid(), commit.txid());
+    assert!(txs.commit_fee >= 5 * signed_commit.vsize() as u64);
+
+    let reveal = &txs.reveal;
+    assert_eq!(reveal.input[0].previous_output.txid, commit.txid());
+    assert_eq!(reveal.input[0].previous_output.vout, 0);
+    assert_eq!(reveal.output[0].value, 546);
+    assert_eq!(reveal.output[0].script_pubkey, alice_p2wpkh());
+
+    // The witness is the signature, the script and the control block.
+    let witness: Vec<&[u8]> = reveal.input[0].witness.iter().collect();
+    assert_eq!(witness.len(), 3);
+    let script = Script::from_bytes(witness[1]);
+    let control_block = ControlBlock::decode(witness[2]).unwrap();
+    let secp = Secp256k1::new();
+    let output_key =
+        XOnlyPublicKey::from_slice(&commit_output.script_pubkey.as_bytes()[2..]).unwrap();
+    assert!(control_block.verify_taproot_commitment(&secp, output_key, script));
+
+    // The script starts with `<ephemeral key> OP_CHECKSIG`, followed by the envelope.
+    let pushes: Vec<Vec<u8>> = script
+        .instructions()
+        .filter_map(|instruction| match instruction.unwrap() {
+            Instruction::PushBytes(bytes) => Some(bytes.as_bytes().to_vec()),
+            Instruction::Op(_) => None,
+        })
+        .collect();
+    assert_eq!(pushes[0], control_block.internal_key.serialize());
+    assert_eq!(pushes[2], b"ord");
+    assert_eq!(pushes[4], CONTENT_TYPE);
+    assert_eq!(pushes[6].len(), 520);
+    assert_eq!(pushes[7].len(), 480);
+
+    let sighash = SighashCache::new(reveal)
+        .taproot_script_spend_signature_hash(
+            0,
+            &Prevouts::All(&[commit_output]),
+            TapLeafHash::from_script(script, LeafVersion::TapScript),
+            TapSighashType::Default,
+        )
+        .unwrap();
+    let message = Message::from_slice(sighash.as_byte_array()).unwrap();
+    let sig = secp256k1::schnorr::Signature::from_slice(witness[0]).unwrap();
+    secp.verify_schnorr(&sig, &message, &control_block.internal_key)
+        .unwrap();
+}
+
+#[test]
+fn inscription_random_key_no_change() {
+    let (outpoint, utxo) = alice_utxo(10_500);
+    let builder = InscriptionBuilder::new(CONTENT_TYPE, b"Hello", alice_p2wpkh())
+        .add_utxo(outpoint, utxo)
+        .change_script(alice_p2wpkh());
+
+    let first = builder.build().unwrap();
+    let second = builder.build().unwrap();
+    assert_ne!(first.reveal.txid(), second.reveal.txid());
+
+    // The change is below the dust limit, so it's left to the miners.
+    let commit = &first.commit.inner().unsigned_tx;
+    assert_eq!(commit.output.len(), 1);
+    assert_eq!(commit.output[0].value, 10_000 + first.reveal_fee);
+    assert_eq!(first.commit_fee, 10_500 - commit.output[0].value);
+}
+
+#[test]
+fn inscription_invalid() {
+    let builder = InscriptionBuilder::new(CONTENT_TYPE, b"Hello", alice_p2wpkh());
+    assert_eq!(
+        Proto::Error::from(builder.build().unwrap_err()),
+        Proto::Error::Error_utxo_insufficient_inputs
+    );
+
+    let (outpoint, utxo) = alice_utxo(10_000);
+    assert_eq!(
+        Proto::Error::from(builder.add_utxo(outpoint, utxo).build().unwrap_err()),
+        Proto::Error::Error_utxo_insufficient_inputs
+    );
+
+    // The signature of a legacy output would change the commit txid.
+    let (outpoint, mut utxo) = alice_utxo(100_000);
+    utxo.script_pubkey = ScriptBuf::new_p2pkh(
+        &PublicKey::from_slice(&hex(ALICE_PUBKEY))
+            .unwrap()
+            .pubkey_hash(),
+    );
+    let builder = InscriptionBuilder::new(CONTENT_TYPE, b"Hello", alice_p2wpkh());
+    assert_eq!(
+        Proto::Error::from(builder.add_utxo(outpoint, utxo).build().unwrap_err()),
+        Proto::Error::Error_unsupported_funding_utxo
+    );
+}
@@ -0,0 +1,346 @@
+mod common;
+
+use bitcoin::hashes::{sha256, Hash};
+use bitcoin::sighash::{EcdsaSighashType, SighashCache};
+use bitcoin::{Address, Network, PublicKey, ScriptBuf, Transaction};
+use common::{hex, MINER_FEE, ONE_BTC};
+use secp256k1::{ecdsa, Message, Secp256k1};
+use std::str::FromStr;
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::miniscript::{Miniscript, Policy, Satisfier};
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+const ALICE_PRIVATE_KEY: &str = "56429688a1a6b00b90ccd22a0de0a376b6569d8684022ae92229a28478bfb657";
+const ALICE_PUBKEY: &str = "036666dd712e05a487916384bfcd5973eb53e8038eccbbf97f7eed775b87389536";
+const BOB_PRIVATE_KEY: &str = "b7da1ec42b19085fe09fec54b9d9eacd998ae4e6d2ad472be38d8393391b9ead";
+const BOB_PUBKEY: &str = "037ed9a436e11ec4947ac4b7823787e24ba73180f1edd2857bff19c9f4d62b65bf";
+const CAROL_PUBKEY: &str = "030f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb";
+const INPUT_VALUE: u64 = 50 * ONE_BTC - 3 * MINER_FEE;
+
+/// Alice can spend at any time, Bob after 144 blocks.
+fn recovery_miniscript() -> String {
+    format!("or_d(pk({ALICE_PUBKEY}),and_v(v:pk({BOB_PUBKEY}),older(144)))")
+}
+
+fn public_key(public_key: &str) -> PublicKey {
+    PublicKey::from_slice(&hex(public_key)).unwrap()
+}
+
+fn witness_of(witness: &bitcoin::Witness) -> Vec<Vec<u8>> {
+    witness.iter().map(|element| element.to_vec()).collect()
+}
+
+#[test]
+fn miniscript_script_and_address() {
+    let miniscript = Miniscript::from_str(&recovery_miniscript()).unwrap();
+    assert_eq!(miniscript.to_string(), recovery_miniscript());
+
+    // `<alice> OP_CHECKSIG OP_IFDUP OP_NOTIF <bob> OP_CHECKSIGVERIFY <144> OP_CSV OP_ENDIF`.
+    assert_eq!(
+        miniscript.script().as_bytes(),
+        hex(&format!("21{ALICE_PUBKEY}ac736421{BOB_PUBKEY}ad029000b268"))
+    );
+    assert_eq!(
+        miniscript.script_pubkey(),
+        ScriptBuf::new_v0_p2wsh(&miniscript.script().wscript_hash())
+    );
+    let address = miniscript.address(Network::Bitcoin);
+    assert!(address.to_string().starts_with("bc1q"));
+    assert_eq!(
+        address,
+        Address::from_script(&miniscript.script_pubkey(), Network::Bitcoin).unwrap()
+    );
+
+    // The wrappers and the aliases are written as parsed.
+    for s in [
+        format!("thresh(2,pk({ALICE_PUBKEY}),s:pk({BOB_PUBKEY}),snl:after(1000))"),
+        format!("andor(pk({ALICE_PUBKEY}),older(10),pkh({BOB_PUBKEY}))"),
+        format!("or_i(and_v(v:pkh({ALICE_PUBKEY}),tv:older(10)),u:pk({BOB_PUBKEY}))"),
+        format!("multi(2,{ALICE_PUBKEY},{BOB_PUBKEY},{CAROL_PUBKEY})"),
+    ] {
+        assert_eq!(Miniscript::from_str(&s).unwrap().to_string(), s);
+    }
+}
+
+#[test]
+fn miniscript_invalid() {
+    for s in [
+        // Not of the `B` type.
+        format!("pk_k({ALICE_PUBKEY})"),
+        format!("v:pk({ALICE_PUBKEY})"),
+        // The first argument of `and_v` must be of the `V` type.
+        format!("and_v(pk({ALICE_PUBKEY}),pk({BOB_PUBKEY}))"),
+        // The first argument of `or_d` must be dissatisfiable.
+        format!("or_d(older(1),pk({ALICE_PUBKEY}))"),
+        // All but the first sub of `thresh` must be of the `W` type.
+        format!("thresh(2,pk({ALICE_PUBKEY}),pk({BOB_PUBKEY}))"),
+        format!("thresh(0,pk({ALICE_PUBKEY}))"),
+        format!("multi(3,{ALICE_PUBKEY},{BOB_PUBKEY})"),
+        format!("x:pk({ALICE_PUBKEY})"),
+        format!(":pk({ALICE_PUBKEY})"),
+        format!("pk({ALICE_PUBKEY})x"),
+        format!("pk({ALICE_PUBKEY}"),
+        "older(0)".to_string(),
+        "older(2147483648)".to_string(),
+        "sha256(00)".to_string(),
+    ] {
+        assert_eq!(
+            Proto::Error::from(Miniscript::from_str(&s).unwrap_err()),
+            Proto::Error::Error_invalid_miniscript,
+            "{s}"
+        );
+    }
+
+    // Uncompressed keys are not standard in P2WSH.
+    let uncompressed = "0479be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8";
+    assert_eq!(
+        Proto::Error::from(Miniscript::from_str(&format!("pk({uncompressed})")).unwrap_err()),
+        Proto::Error::Error_invalid_public_key
+    );
+}
+
+#[test]
+fn miniscript_compile_policy() {
+    let digest = "9267d3dbed802941483f1afa2a6bc68de5f653128aca9bf1461c5d0a3ad36ed2";
+    for (policy, miniscript) in [
+        (
+            format!("or(pk({ALICE_PUBKEY}),and(pk({BOB_PUBKEY}),older(144)))"),
+            recovery_miniscript(),
+        ),
+        (
+            format!("thresh(2,pk({ALICE_PUBKEY}),pk({BOB_PUBKEY}),pk({CAROL_PUBKEY}))"),
+            format!("multi(2,{ALICE_PUBKEY},{BOB_PUBKEY},{CAROL_PUBKEY})"),
+        ),
+        (
+            format!("thresh(2,pk({ALICE_PUBKEY}),pk({BOB_PUBKEY}),after(1000))"),
+            format!("thresh(2,pk({ALICE_PUBKEY}),s:pk({BOB_PUBKEY}),snl:after(1000))"),
+        ),
+        (
+            format!("and(pk({ALICE_PUBKEY}),or(pk({BOB_PUBKEY}),sha256({digest})))"),
+            format!("and_v(v:pk({ALICE_PUBKEY}),or_d(pk({BOB_PUBKEY}),sha256({digest})))"),
+        ),
+        // The dissatisfiable branch is the first one of `or_d`.
+        (
+            format!("or(older(10),pk({ALICE_PUBKEY}))"),
+            format!("or_d(pk({ALICE_PUBKEY}),older(10))"),
+        ),
+        (
+            "or(after(100),older(100))".to_string(),
+            "or_i(after(100),older(100))".to_string(),
+        ),
+    ] {
+        let compiled = Policy::from_str(&policy).unwrap().compile().unwrap();
+        assert_eq!(compiled.to_string(), miniscript);
+        assert_eq!(compiled, Miniscript::from_str(&miniscript).unwrap());
+    }
+
+    for policy in [
+        format!("thresh(0,pk({ALICE_PUBKEY}))"),
+        format!("thresh(3,pk({ALICE_PUBKEY}),pk({BOB_PUBKEY}))"),
+        format!("and(pk({ALICE_PUBKEY}))"),
+        format!("pk_k({ALICE_PUBKEY})"),
+        "after(0)".to_string(),
+    ] {
+        let error = Policy::from_str(&policy).and_then(|policy| policy.compile());
+        assert_eq!(
+            Proto::Error::from(error.unwrap_err()),
+            Proto::Error::Error_invalid_miniscript,
+            "{policy}"
+        );
+    }
+}
+
+#[test]
+fn miniscript_satisfy_cheapest() {
+    let miniscript = Miniscript::from_str(&recovery_miniscript()).unwrap();
+    let script = miniscript.script().to_bytes();
+    let (alice, bob) = (public_key(ALICE_PUBKEY), public_key(BOB_PUBKEY));
+    let (alice_sig, bob_sig) = (vec![0xaa; 71], vec![0xbb; 72]);
+
+    let satisfier = Satisfier::new().signature(alice, alice_sig.clone());
+    assert_eq!(
+        witness_of(&miniscript.satisfy(&satisfier).unwrap()),
+        [alice_sig.clone(), script.clone()]
+    );
+
+    // Bob's branch requires the relative timelock, and Alice's key to be dissatisfied.
+    let satisfier = Satisfier::new()
+        .signature(bob, bob_sig.clone())
+        .sequence(144);
+    assert_eq!(
+        witness_of(&miniscript.satisfy(&satisfier).unwrap()),
+        [bob_sig.clone(), Vec::new(), script.clone()]
+    );
+
+    // Alice's branch is cheaper.
+    let satisfier = satisfier.signature(alice, alice_sig.clone());
+    assert_eq!(
+        witness_of(&miniscript.satisfy(&satisfier).unwrap()),
+        [alice_sig, script]
+    );
+
+    // The timelock is not reached, or is of the other type.
+    for sequence in [143, 144 | (1 << 22), u32::MAX] {
+        let satisfier = Satisfier::new()
+            .signature(bob, bob_sig.clone())
+            .sequence(sequence);
+        assert_eq!(
+            Proto::Error::from(miniscript.satisfy(&satisfier).unwrap_err()),
+            Proto::Error::Error_unsatisfiable_miniscript
+        );
+    }
+
+    // Bob's signature and the dissatisfaction of Alice's key.
+    assert_eq!(miniscript.max_satisfaction_len(), 73 + 1);
+}
+
+#[test]
+fn miniscript_satisfy_thresh_hash_and_after() {
+    let (alice, carol) = (public_key(ALICE_PUBKEY), public_key(CAROL_PUBKEY));
+    let (alice_sig, carol_sig) = (vec![0xaa; 71], vec![0xcc; 71]);
+
+    // The later subs are deeper in the stack, and Bob's key is dissatisfied.
+    let miniscript = Miniscript::from_str(&format!(
+        "thresh(2,pk({ALICE_PUBKEY}),s:pk({BOB_PUBKEY}),s:pk({CAROL_PUBKEY}))"
+    ))
+    .unwrap();
+    let satisfier = Satisfier::new()
+        .signature(carol, carol_sig.clone())
+        .signature(alice, alice_sig.clone());
+    assert_eq!(
+        witness_of(&miniscript.satisfy(&satisfier).unwrap()),
+        [
+            carol_sig.clone(),
+            Vec::new(),
+            alice_sig.clone(),
+            miniscript.script().to_bytes()
+        ]
+    );
+
+    let preimage = vec![7; 32];
+    let digest = sha256::Hash::hash(&preimage).to_byte_array();
+    let miniscript = Miniscript::from_str(&format!(
+        "and_v(v:pk({ALICE_PUBKEY}),sha256({}))",
+        tw_encoding::hex::encode(digest, false)
+    ))
+    .unwrap();
+    let satisfier = Satisfier::new()
+        .signature(alice, alice_sig.clone())
+        .preimage(vec![8; 32]);
+    assert_eq!(
+        Proto::Error::from(miniscript.satisfy(&satisfier).unwrap_err()),
+        Proto::Error::Error_unsatisfiable_miniscript
+    );
+    let satisfier = satisfier.preimage(preimage.clone());
+    assert_eq!(
+        witness_of(&miniscript.satisfy(&satisfier).unwrap()),
+        [preimage, alice_sig.clone(), miniscript.script().to_bytes()]
+    );
+
+    let miniscript =
+        Miniscript::from_str(&format!("and_v(v:pk({ALICE_PUBKEY}),after(800000))")).unwrap();
+    let satisfier = Satisfier::new().signature(alice, alice_sig);
+    assert!(miniscript
+        .satisfy(&satisfier.clone().lock_time(800_000))
+        .is_ok());
+    // A lower height, or a timestamp.
+    for lock_time in [799_999, 1_700_000_000] {
+        assert_eq!(
+            Proto::Error::from(
+                miniscript
+                    .satisfy(&satisfier.clone().lock_time(lock_time))
+                    .unwrap_err()
+            ),
+            Proto::Error::Error_unsatisfiable_miniscript
+        );
+    }
+}
+
+fn signing_input(private_key: &str, sequence: u32) -> Proto::SigningInput<'static> {
+    let txid: Vec<u8> = hex("dd9d4ca23532f5c89d016e1aacef1210ab5b9d00527c633969841daca7dd17c7")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let tx1 = Proto::Input {
+        txid: txid.into(),
+        vout: 0,
+        sequence,
+        value: INPUT_VALUE,
+        sighash_type: UtxoProto::SighashType::All,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2wsh_miniscript(Proto::mod_Input::InputMiniscript {
+                miniscript: recovery_miniscript().into(),
+                ..Default::default()
+            }),
+        }),
+        ..Default::default()
+    };
+
+    let out1 = Proto::Output {
+        value: 50 * ONE_BTC - 4 * MINER_FEE,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(hex(ALICE_PUBKEY).into()),
+            }),
+        }),
+    };
+
+    Proto::SigningInput {
+        private_key: hex(private_key).into(),
+        inputs: vec![tx1],
+        outputs: vec![out1],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        disable_change_output: true,
+        ..Default::default()
+    }
+}
+
+/// Verifies the signature of the input with the BIP143 sighash of the witness script.
+fn verify(tx: &Transaction, script: &ScriptBuf, signature: &[u8], signer: &str) {
+    let sighash = SighashCache::new(tx)
+        .segwit_signature_hash(0, script, INPUT_VALUE, EcdsaSighashType::All)
+        .unwrap();
+    // The last byte is the sighash type.
+    let signature = ecdsa::Signature::from_der(&signature[..signature.len() - 1]).unwrap();
+    Secp256k1::new()
+        .verify_ecdsa(
+            &Message::from_slice(sighash.as_byte_array()).unwrap(),
+            &signature,
+            &public_key(signer).inner,
+        )
+        .unwrap();
+}
+
+#[test]
+fn miniscript_p2wsh_sign() {
+    let coin = TestCoinContext::default();
+    let miniscript = Miniscript::from_str(&recovery_miniscript()).unwrap();
+    let script = miniscript.script().clone();
+
+    let signed = BitcoinEntry.sign(&coin, signing_input(ALICE_PRIVATE_KEY, 0));
+    assert_eq!(signed.error, Proto::Error::OK);
+    let tx: Transaction = bitcoin::consensus::deserialize(&signed.encoded).unwrap();
+    let witness = witness_of(&tx.input[0].witness);
+    assert_eq!(witness.len(), 2);
+    assert_eq!(witness[1], script.to_bytes());
+    verify(&tx, &script, &witness[0], ALICE_PUBKEY);
+
+    // Bob can't spend before the timelock.
+    let signed = BitcoinEntry.sign(&coin, signing_input(BOB_PRIVATE_KEY, 0));
+    assert_eq!(signed.error, Proto::Error::Error_unsatisfiable_miniscript);
+
+    let signed = BitcoinEntry.sign(&coin, signing_input(BOB_PRIVATE_KEY, 144));
+    assert_eq!(signed.error, Proto::Error::OK);
+    let tx: Transaction = bitcoin::consensus::deserialize(&signed.encoded).unwrap();
+    assert_eq!(tx.input[0].sequence.0, 144);
+    let witness = witness_of(&tx.input[0].witness);
+    assert_eq!(witness.len(), 3);
+    assert!(witness[1].is_empty());
+    assert_eq!(witness[2], script.to_bytes());
+    verify(&tx, &script, &witness[0], BOB_PUBKEY);
+}
@@ -0,0 +1,211 @@
+mod common;
+
+use bitcoin::blockdata::script::Instruction;
+use bitcoin::hashes::Hash;
+use bitcoin::sighash::{EcdsaSighashType, Prevouts, SighashCache, TapSighashType};
+use bitcoin::{PublicKey, Script, ScriptBuf, Transaction, TxOut};
+use common::{hex, MINER_FEE, ONE_BTC};
+use secp256k1::{Message, Secp256k1, XOnlyPublicKey};
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::transactions::{FeeEstimator, InputType};
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+const ALICE_PRIVATE_KEY: &str = "56429688a1a6b00b90ccd22a0de0a376b6569d8684022ae92229a28478bfb657";
+const ALICE_PUBKEY: &str = "036666dd712e05a487916384bfcd5973eb53e8038eccbbf97f7eed775b87389536";
+
+fn public_key() -> PublicKey {
+    PublicKey::from_slice(&hex(ALICE_PUBKEY)).unwrap()
+}
+
+fn p2tr_script() -> ScriptBuf {
+    ScriptBuf::new_v1_p2tr(
+        &Secp256k1::new(),
+        public_key().inner.x_only_public_key().0,
+        None,
+    )
+}
+
+fn p2sh_redeem_script() -> ScriptBuf {
+    ScriptBuf::new_v0_p2wpkh(&public_key().wpubkey_hash().unwrap())
+}
+
+/// The outputs of an old wallet, spent by the P2PKH, P2SH-P2WPKH, P2WPKH and P2TR key path inputs in order.
+fn prevouts() -> Vec<TxOut> {
+    let key = public_key();
+    vec![
+        TxOut {
+            value: ONE_BTC,
+            script_pubkey: ScriptBuf::new_p2pkh(&key.pubkey_hash()),
+        },
+        TxOut {
+            value: ONE_BTC,
+            script_pubkey: ScriptBuf::new_p2sh(&p2sh_redeem_script().script_hash()),
+        },
+        TxOut {
+            value: ONE_BTC,
+            script_pubkey: ScriptBuf::new_v0_p2wpkh(&key.wpubkey_hash().unwrap()),
+        },
+        TxOut {
+            value: ONE_BTC,
+            script_pubkey: p2tr_script(),
+        },
+    ]
+}
+
+/// Sweeps all the inputs to a Taproot output.
+fn sweep() -> Proto::SigningOutput<'static> {
+    let variants = [
+        ProtoInputBuilder::p2pkh(hex(ALICE_PUBKEY).into()),
+        ProtoInputBuilder::p2sh_p2wpkh(hex(ALICE_PUBKEY).into()),
+        ProtoInputBuilder::p2wpkh(hex(ALICE_PUBKEY).into()),
+        ProtoInputBuilder::p2tr_key_path(Proto::mod_Input::InputTaprootKeyPath {
+            public_key: hex(ALICE_PUBKEY).into(),
+            one_prevout: false,
+        }),
+    ];
+    let inputs = variants
+        .into_iter()
+        .enumerate()
+        .map(|(vout, variant)| Proto::Input {
+            txid: vec![0x22; 32].into(),
+            vout: vout as u32,
+            value: ONE_BTC,
+            sighash_type: UtxoProto::SighashType::UseDefault,
+            to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder { variant }),
+            ..Default::default()
+        })
+        .collect();
+
+    let output = Proto::Output {
+        value: 4 * ONE_BTC - MINER_FEE,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2tr_key_path(hex(ALICE_PUBKEY).into()),
+        }),
+    };
+    let signing = Proto::SigningInput {
+        private_key: hex(ALICE_PRIVATE_KEY).into(),
+        inputs,
+        outputs: vec![output],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        disable_change_output: true,
+        ..Default::default()
+    };
+
+    let signed = BitcoinEntry.sign(&TestCoinContext::default(), signing);
+    assert_eq!(signed.error, Proto::Error::OK);
+    signed
+}
+
+fn pushes(script_sig: &Script) -> Vec<Vec<u8>> {
+    script_sig
+        .instructions()
+        .map(|instruction| match instruction.unwrap() {
+            Instruction::PushBytes(push) => push.as_bytes().to_vec(),
+            Instruction::Op(op) => panic!("unexpected {op:?}"),
+        })
+        .collect()
+}
+
+fn verify_ecdsa(sighash: &[u8], signature: &[u8]) {
+    let signature = bitcoin::ecdsa::Signature::from_slice(signature).unwrap();
+    assert_eq!(signature.hash_ty, EcdsaSighashType::All);
+    Secp256k1::new()
+        .verify_ecdsa(
+            &Message::from_slice(sighash).unwrap(),
+            &signature.sig,
+            &public_key().inner,
+        )
+        .unwrap();
+}
+
+#[test]
+fn mixed_inputs_sweep_to_taproot() {
+    let signed = sweep();
+    let tx: Transaction = bitcoin::consensus::deserialize(&signed.encoded).unwrap();
+    let prevouts = prevouts();
+    let mut cache = SighashCache::new(&tx);
+    assert_eq!(tx.input.len(), 4);
+    assert_eq!(tx.output[0].script_pubkey, p2tr_script());
+
+    // P2PKH: `<signature> <public key>` in the scriptSig, signed with the legacy sighash.
+    let script_sig = pushes(&tx.input[0].script_sig);
+    assert_eq!(script_sig.len(), 2);
+    assert_eq!(script_sig[1], hex(ALICE_PUBKEY));
+    assert!(tx.input[0].witness.is_empty());
+    let sighash = cache
+        .legacy_signature_hash(
+            0,
+            &prevouts[0].script_pubkey,
+            EcdsaSighashType::All.to_u32(),
+        )
+        .unwrap();
+    verify_ecdsa(sighash.as_byte_array(), &script_sig[0]);
+
+    // P2SH-P2WPKH: the redeem script in the scriptSig, and the P2WPKH witness signed with the segwit sighash.
+    assert_eq!(
+        pushes(&tx.input[1].script_sig),
+        vec![p2sh_redeem_script().to_bytes()]
+    );
+    assert_eq!(tx.input[1].witness.len(), 2);
+    assert_eq!(tx.input[1].witness.nth(1).unwrap(), hex(ALICE_PUBKEY));
+    let script_code = p2sh_redeem_script().p2wpkh_script_code().unwrap();
+    let sighash = cache
+        .segwit_signature_hash(1, &script_code, ONE_BTC, EcdsaSighashType::All)
+        .unwrap();
+    verify_ecdsa(sighash.as_byte_array(), tx.input[1].witness.nth(0).unwrap());
+
+    // P2WPKH: the empty scriptSig and the witness.
+    assert!(tx.input[2].script_sig.is_empty());
+    assert_eq!(tx.input[2].witness.len(), 2);
+    let script_code = prevouts[2].script_pubkey.p2wpkh_script_code().unwrap();
+    let sighash = cache
+        .segwit_signature_hash(2, &script_code, ONE_BTC, EcdsaSighashType::All)
+        .unwrap();
+    verify_ecdsa(sighash.as_byte_array(), tx.input[2].witness.nth(0).unwrap());
+
+    // P2TR key path: the Schnorr signature alone, which commits to the spent outputs of all the inputs.
+    assert!(tx.input[3].script_sig.is_empty());
+    assert_eq!(tx.input[3].witness.len(), 1);
+    let signature =
+        bitcoin::taproot::Signature::from_slice(tx.input[3].witness.nth(0).unwrap()).unwrap();
+    assert_eq!(signature.hash_ty, TapSighashType::Default);
+    let sighash = cache
+        .taproot_key_spend_signature_hash(3, &Prevouts::All(&prevouts), TapSighashType::Default)
+        .unwrap();
+    let output_key =
+        XOnlyPublicKey::from_slice(&prevouts[3].script_pubkey.as_bytes()[2..]).unwrap();
+    Secp256k1::new()
+        .verify_schnorr(
+            &signature.sig,
+            &Message::from_slice(sighash.as_byte_array()).unwrap(),
+            &output_key,
+        )
+        .unwrap();
+}
+
+#[test]
+fn mixed_inputs_fee_estimate() {
+    let signed = sweep();
+    let tx: Transaction = bitcoin::consensus::deserialize(&signed.encoded).unwrap();
+
+    let estimate = FeeEstimator::new()
+        .add_input(InputType::P2pkh)
+        .add_input(InputType::P2shP2wpkh)
+        .add_input(InputType::P2wpkh)
+        .add_input(InputType::P2trKeyPath)
+        .add_output(&p2tr_script())
+        .estimate()
+        .unwrap();
+
+    // The ECDSA signatures might be shorter than the maximum.
+    let weight = tx.weight().to_wu();
+    assert!(estimate.weight >= weight);
+    assert!(estimate.weight - weight <= 4 + 2);
+    // The weight reported by the signer is its own estimate, which budgets 72 bytes for the Schnorr
+    // signature, but doesn't count the segwit marker and the empty witness of the P2PKH input.
+    assert_eq!(signed.weight, estimate.weight + (72 - 64) - 2 - 1);
+}
@@ -0,0 +1,249 @@
+mod common;
+
+use bitcoin::blockdata::script::Instruction;
+use bitcoin::{PublicKey, Transaction};
+use common::{hex, MINER_FEE, ONE_BTC};
+use secp256k1::{ecdsa, Message, Secp256k1};
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::signer::Signer;
+use tw_bitcoin::modules::transactions::Multisig;
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+const ALICE_PRIVATE_KEY: &str = "56429688a1a6b00b90ccd22a0de0a376b6569d8684022ae92229a28478bfb657";
+const ALICE_PUBKEY: &str = "036666dd712e05a487916384bfcd5973eb53e8038eccbbf97f7eed775b87389536";
+const BOB_PRIVATE_KEY: &str = "b7da1ec42b19085fe09fec54b9d9eacd998ae4e6d2ad472be38d8393391b9ead";
+const BOB_PUBKEY: &str = "037ed9a436e11ec4947ac4b7823787e24ba73180f1edd2857bff19c9f4d62b65bf";
+const CAROL_PRIVATE_KEY: &str = "e253373989199da27c48680e3a3fc0f648d50f9a727ef17a7fe6a4dc3b159129";
+const CAROL_PUBKEY: &str = "030f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb";
+
+fn multisig_proto(signatures: Vec<(&str, Vec<u8>)>) -> Proto::mod_Input::InputMultisig<'static> {
+    Proto::mod_Input::InputMultisig {
+        required: 2,
+        public_keys: vec![
+            hex(ALICE_PUBKEY).into(),
+            hex(BOB_PUBKEY).into(),
+            hex(CAROL_PUBKEY).into(),
+        ],
+        signatures: signatures
+            .into_iter()
+            .map(
+                |(public_key, signature)| Proto::mod_Input::MultisigSignature {
+                    public_key: hex(public_key).into(),
+                    signature: signature.into(),
+                },
+            )
+            .collect(),
+    }
+}
+
+fn signing_input(variant: ProtoInputBuilder<'static>) -> Proto::SigningInput<'static> {
+    let txid: Vec<u8> = hex("dd9d4ca23532f5c89d016e1aacef1210ab5b9d00527c633969841daca7dd17c7")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let tx1 = Proto::Input {
+        txid: txid.into(),
+        vout: 0,
+        value: 50 * ONE_BTC - 3 * MINER_FEE,
+        sighash_type: UtxoProto::SighashType::All,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder { variant }),
+        ..Default::default()
+    };
+
+    let out1 = Proto::Output {
+        value: 50 * ONE_BTC - 4 * MINER_FEE,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2pkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(hex(ALICE_PUBKEY).into()),
+            }),
+        }),
+    };
+
+    Proto::SigningInput {
+        inputs: vec![tx1],
+        outputs: vec![out1],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        disable_change_output: true,
+        ..Default::default()
+    }
+}
+
+/// Returns the sighash of the only input, and the signature of it with the `private_key`.
+fn sign_sighash(signing: &Proto::SigningInput<'_>, private_key: &str) -> (Vec<u8>, Vec<u8>) {
+    let sighashes = BitcoinEntry.preimage_hashes(&TestCoinContext::default(), signing.clone());
+    assert_eq!(sighashes.error, Proto::Error::OK);
+    let signatures =
+        Signer::signatures_from_proto(&sighashes, hex(private_key), Default::default(), false)
+            .unwrap();
+    (
+        sighashes.sighashes[0].sighash.to_vec(),
+        signatures[0].clone(),
+    )
+}
+
+fn verify(sighash: &[u8], signature: &[u8], public_key: &str) {
+    // The last byte is the sighash type.
+    let signature = ecdsa::Signature::from_der(&signature[..signature.len() - 1]).unwrap();
+    let public_key = PublicKey::from_slice(&hex(public_key)).unwrap();
+    Secp256k1::new()
+        .verify_ecdsa(
+            &Message::from_slice(sighash).unwrap(),
+            &signature,
+            &public_key.inner,
+        )
+        .unwrap();
+}
+
+#[test]
+fn multisig_script() {
+    let public_keys: Vec<PublicKey> = [ALICE_PUBKEY, BOB_PUBKEY, CAROL_PUBKEY]
+        .iter()
+        .map(|public_key| PublicKey::from_slice(&hex(public_key)).unwrap())
+        .collect();
+
+    let multisig = Multisig::new(2, public_keys.clone()).unwrap();
+    assert_eq!(
+        multisig.script().as_bytes(),
+        hex(&format!(
+            "5221{ALICE_PUBKEY}21{BOB_PUBKEY}21{CAROL_PUBKEY}53ae"
+        ))
+    );
+
+    for (required, public_keys) in [
+        (0, public_keys.clone()),
+        (4, public_keys.clone()),
+        (1, public_keys.repeat(6)),
+    ] {
+        assert_eq!(
+            Proto::Error::from(Multisig::new(required, public_keys).unwrap_err()),
+            Proto::Error::Error_invalid_multisig
+        );
+    }
+}
+
+#[test]
+fn multisig_p2wsh_sign_with_partial_signature() {
+    let coin = TestCoinContext::default();
+
+    // Carol signs first and shares her signature.
+    let unsigned = signing_input(ProtoInputBuilder::p2wsh_multisig(multisig_proto(vec![])));
+    let (sighash, carol_sig) = sign_sighash(&unsigned, CAROL_PRIVATE_KEY);
+    let (_, alice_sig) = sign_sighash(&unsigned, ALICE_PRIVATE_KEY);
+
+    // Alice completes the transaction.
+    let mut signing = signing_input(ProtoInputBuilder::p2wsh_multisig(multisig_proto(vec![(
+        CAROL_PUBKEY,
+        carol_sig.clone(),
+    )])));
+    signing.private_key = hex(ALICE_PRIVATE_KEY).into();
+
+    let signed = BitcoinEntry.sign(&coin, signing);
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    // The dummy and the signatures in the order of the public keys.
+    let tx: Transaction = bitcoin::consensus::deserialize(&signed.encoded).unwrap();
+    let witness: Vec<&[u8]> = tx.input[0].witness.iter().collect();
+    assert_eq!(witness.len(), 4);
+    assert!(witness[0].is_empty());
+    assert_eq!(witness[1], alice_sig);
+    assert_eq!(witness[2], carol_sig);
+    assert_eq!(witness[3], multisig_script_bytes());
+    assert!(tx.input[0].script_sig.is_empty());
+
+    verify(&sighash, witness[1], ALICE_PUBKEY);
+    verify(&sighash, witness[2], CAROL_PUBKEY);
+}
+
+#[test]
+fn multisig_p2wsh_compile_external_signatures() {
+    let coin = TestCoinContext::default();
+
+    let unsigned = signing_input(ProtoInputBuilder::p2wsh_multisig(multisig_proto(vec![])));
+    let (sighash, bob_sig) = sign_sighash(&unsigned, BOB_PRIVATE_KEY);
+    let (_, carol_sig) = sign_sighash(&unsigned, CAROL_PRIVATE_KEY);
+
+    // Not enough signatures.
+    let signing = signing_input(ProtoInputBuilder::p2wsh_multisig(multisig_proto(vec![(
+        CAROL_PUBKEY,
+        carol_sig.clone(),
+    )])));
+    let signed = BitcoinEntry.compile(&coin, signing.clone(), vec![vec![]], vec![]);
+    assert_eq!(
+        signed.error,
+        Proto::Error::Error_missing_multisig_signatures
+    );
+
+    // The signature passed to `compile` requires its public key.
+    let signed = BitcoinEntry.compile(&coin, signing.clone(), vec![bob_sig.clone()], vec![]);
+    assert_eq!(signed.error, Proto::Error::Error_invalid_multisig);
+
+    let signed = BitcoinEntry.compile(&coin, signing, vec![bob_sig.clone()], vec![hex(BOB_PUBKEY)]);
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    let tx: Transaction = bitcoin::consensus::deserialize(&signed.encoded).unwrap();
+    let witness: Vec<&[u8]> = tx.input[0].witness.iter().collect();
+    assert_eq!(witness.len(), 4);
+    assert!(witness[0].is_empty());
+    assert_eq!(witness[1], bob_sig);
+    assert_eq!(witness[2], carol_sig);
+    verify(&sighash, witness[1], BOB_PUBKEY);
+    verify(&sighash, witness[2], CAROL_PUBKEY);
+}
+
+#[test]
+fn multisig_p2sh_sign_with_partial_signatures() {
+    let coin = TestCoinContext::default();
+
+    let unsigned = signing_input(ProtoInputBuilder::p2sh_multisig(multisig_proto(vec![])));
+    let (sighash, alice_sig) = sign_sighash(&unsigned, ALICE_PRIVATE_KEY);
+    let (_, bob_sig) = sign_sighash(&unsigned, BOB_PRIVATE_KEY);
+
+    // A signature of a key that is not in the script is rejected.
+    let mut signing = signing_input(ProtoInputBuilder::p2sh_multisig(multisig_proto(vec![(
+        "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+        bob_sig.clone(),
+    )])));
+    signing.private_key = hex(CAROL_PRIVATE_KEY).into();
+    let signed = BitcoinEntry.sign(&coin, signing);
+    assert_eq!(signed.error, Proto::Error::Error_invalid_multisig);
+
+    // Carol signs, while the signatures of Alice and Bob are collected. Only the first two are used.
+    let mut signing = signing_input(ProtoInputBuilder::p2sh_multisig(multisig_proto(vec![
+        (BOB_PUBKEY, bob_sig.clone()),
+        (ALICE_PUBKEY, alice_sig.clone()),
+    ])));
+    signing.private_key = hex(CAROL_PRIVATE_KEY).into();
+    let signed = BitcoinEntry.sign(&coin, signing);
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    // `OP_0 <alice signature> <bob signature> <redeem script>`.
+    let tx: Transaction = bitcoin::consensus::deserialize(&signed.encoded).unwrap();
+    assert!(tx.input[0].witness.is_empty());
+    let pushes: Vec<Vec<u8>> = tx.input[0]
+        .script_sig
+        .instructions()
+        .map(|instruction| match instruction.unwrap() {
+            Instruction::PushBytes(bytes) => bytes.as_bytes().to_vec(),
+            Instruction::Op(_) => panic!("Expected only pushes"),
+        })
+        .collect();
+    assert_eq!(pushes.len(), 4);
+    assert!(pushes[0].is_empty());
+    assert_eq!(pushes[1], alice_sig);
+    assert_eq!(pushes[2], bob_sig);
+    assert_eq!(pushes[3], multisig_script_bytes());
+
+    verify(&sighash, &pushes[1], ALICE_PUBKEY);
+    verify(&sighash, &pushes[2], BOB_PUBKEY);
+}
+
+fn multisig_script_bytes() -> Vec<u8> {
+    hex(&format!(
+        "5221{ALICE_PUBKEY}21{BOB_PUBKEY}21{CAROL_PUBKEY}53ae"
+    ))
+}
@@ -0,0 +1,167 @@
+mod common;
+use common::{hex, ONE_BTC};
+
+use bitcoin::{Address, Network};
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::network::NetworkPrefix;
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::derivation::Derivation;
+use tw_coin_entry::error::prelude::*;
+use tw_coin_entry::prefix::AddressPrefix;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_keypair::tw::{PublicKey, PublicKeyType};
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+const ALICE_PRIVATE_KEY: &str = "57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a";
+const ALICE_PUBKEY: &str = "028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f";
+const BOB_PUBKEY: &str = "025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f";
+
+fn p2wpkh_address(pubkey: &str, network: Network) -> Address {
+    let pubkey = bitcoin::PublicKey::from_slice(&hex(pubkey)).unwrap();
+    Address::p2wpkh(&pubkey, network).unwrap()
+}
+
+fn p2pkh_address(pubkey: &str, network: Network) -> Address {
+    let pubkey = bitcoin::PublicKey::from_slice(&hex(pubkey)).unwrap();
+    Address::p2pkh(&pubkey, network)
+}
+
+/// Spends one P2WPKH input of Alice to Bob's `address` on the `network`, with the change to Alice's `change` address.
+fn signing_input(
+    network: Proto::Network,
+    address: &Address,
+    change: &Address,
+) -> Proto::SigningInput<'static> {
+    let tx1 = Proto::Input {
+        txid: vec![1; 32].into(),
+        vout: 0,
+        value: ONE_BTC,
+        sighash_type: UtxoProto::SighashType::All,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2wpkh(hex(ALICE_PUBKEY).into()),
+        }),
+        ..Default::default()
+    };
+
+    Proto::SigningInput {
+        private_key: hex(ALICE_PRIVATE_KEY).into(),
+        inputs: vec![tx1],
+        outputs: vec![Proto::Output {
+            value: ONE_BTC / 2,
+            to_recipient: ProtoOutputRecipient::from_address(address.to_string().into()),
+        }],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        fee_per_vb: 10,
+        change_output: Some(Proto::Output {
+            value: 0,
+            to_recipient: ProtoOutputRecipient::from_address(change.to_string().into()),
+        }),
+        network,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn network_sign_to_addresses() {
+    let coin = TestCoinContext::default();
+
+    for (network, proto_network) in [
+        (Network::Bitcoin, Proto::Network::Mainnet),
+        (Network::Testnet, Proto::Network::Testnet),
+        (Network::Signet, Proto::Network::Signet),
+        (Network::Regtest, Proto::Network::Regtest),
+    ] {
+        let bob = p2pkh_address(BOB_PUBKEY, network);
+        let alice = p2wpkh_address(ALICE_PUBKEY, network);
+        let signed = BitcoinEntry.sign(&coin, signing_input(proto_network, &bob, &alice));
+        assert_eq!(signed.error, Proto::Error::OK, "{network}");
+
+        let outputs = signed.transaction.unwrap().outputs;
+        assert_eq!(outputs[0].script_pubkey, bob.script_pubkey().to_bytes());
+        assert_eq!(outputs[1].script_pubkey, alice.script_pubkey().to_bytes());
+    }
+
+    // The testnet and the signet share the same addresses, but not the regtest.
+    let alice = p2wpkh_address(ALICE_PUBKEY, Network::Testnet);
+    let signed = BitcoinEntry.sign(&coin, signing_input(Proto::Network::Signet, &alice, &alice));
+    assert_eq!(signed.error, Proto::Error::OK);
+    let signed = BitcoinEntry.sign(
+        &coin,
+        signing_input(Proto::Network::Regtest, &alice, &alice),
+    );
+    assert_eq!(signed.error, Proto::Error::Error_bad_address_recipient);
+
+    // A mainnet address by default.
+    let bob = p2wpkh_address(BOB_PUBKEY, Network::Regtest);
+    let alice = p2wpkh_address(ALICE_PUBKEY, Network::Bitcoin);
+    let signed = BitcoinEntry.sign(&coin, signing_input(Proto::Network::Mainnet, &bob, &alice));
+    assert_eq!(signed.error, Proto::Error::Error_bad_address_recipient);
+
+    // The change address is checked as well.
+    let bob = p2wpkh_address(BOB_PUBKEY, Network::Bitcoin);
+    let alice = p2wpkh_address(ALICE_PUBKEY, Network::Testnet);
+    let signed = BitcoinEntry.sign(&coin, signing_input(Proto::Network::Mainnet, &bob, &alice));
+    assert_eq!(signed.error, Proto::Error::Error_bad_address_recipient);
+}
+
+#[test]
+fn network_derive_and_parse_address() {
+    let coin = TestCoinContext::default();
+    let public_key = PublicKey::new(hex(ALICE_PUBKEY), PublicKeyType::Secp256k1).unwrap();
+
+    for (hrp, network) in [
+        ("bc", Network::Bitcoin),
+        ("tb", Network::Testnet),
+        ("bcrt", Network::Regtest),
+    ] {
+        let prefix = NetworkPrefix::try_from(AddressPrefix::Hrp(hrp.to_string())).unwrap();
+        assert_eq!(prefix, NetworkPrefix(network));
+
+        let address = BitcoinEntry
+            .derive_address(
+                &coin,
+                public_key.clone(),
+                Derivation::BitcoinSegwit,
+                Some(prefix),
+            )
+            .unwrap();
+        let expected = p2wpkh_address(ALICE_PUBKEY, network).to_string();
+        assert_eq!(address.to_string(), expected);
+        assert!(address.to_string().starts_with(&format!("{hrp}1")));
+
+        let parsed = BitcoinEntry
+            .parse_address(&coin, &expected, Some(prefix))
+            .unwrap();
+        assert_eq!(parsed.to_string(), expected);
+    }
+
+    // Mainnet by default.
+    let address = BitcoinEntry
+        .derive_address(&coin, public_key, Derivation::Default, None)
+        .unwrap();
+    assert_eq!(
+        address.to_string(),
+        p2pkh_address(ALICE_PUBKEY, Network::Bitcoin).to_string()
+    );
+
+    let regtest = p2wpkh_address(ALICE_PUBKEY, Network::Regtest).to_string();
+    assert!(BitcoinEntry.parse_address(&coin, &regtest, None).is_err());
+    assert!(BitcoinEntry
+        .parse_address(&coin, &regtest, Some(NetworkPrefix(Network::Testnet)))
+        .is_err());
+    // The address of any network.
+    assert_eq!(
+        BitcoinEntry
+            .parse_address_unchecked(&coin, &regtest)
+            .unwrap()
+            .to_string(),
+        regtest
+    );
+
+    assert_eq!(
+        NetworkPrefix::try_from(AddressPrefix::Hrp("ltc".to_string())).unwrap_err(),
+        AddressError::UnexpectedAddressPrefix
+    );
+}
@@ -0,0 +1,127 @@
+mod common;
+use common::{hex, ONE_BTC};
+
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+const SAT_VBYTE: u64 = 10;
+
+const ALICE_PRIVATE_KEY: &str = "57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a";
+const ALICE_PUBKEY: &str = "028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f";
+const BOB_PUBKEY: &str = "025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f";
+
+fn p2wpkh(pubkey: &str) -> ProtoOutputRecipient<'static> {
+    ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+        variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+            to_address: ProtoPubkeyOrHash::pubkey(hex(pubkey).into()),
+        }),
+    })
+}
+
+fn op_return(data: &str) -> Proto::Output<'static> {
+    Proto::Output {
+        value: 0,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::op_return(hex(data).into()),
+        }),
+    }
+}
+
+/// Spends one P2WPKH input of Alice to the `outputs` and the change.
+fn signing_input(outputs: Vec<Proto::Output<'static>>) -> Proto::SigningInput<'static> {
+    let tx1 = Proto::Input {
+        txid: vec![1; 32].into(),
+        vout: 0,
+        value: ONE_BTC,
+        sighash_type: UtxoProto::SighashType::All,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2wpkh(hex(ALICE_PUBKEY).into()),
+        }),
+        ..Default::default()
+    };
+
+    Proto::SigningInput {
+        private_key: hex(ALICE_PRIVATE_KEY).into(),
+        inputs: vec![tx1],
+        outputs,
+        input_selector: UtxoProto::InputSelector::UseAll,
+        fee_per_vb: SAT_VBYTE,
+        change_output: Some(Proto::Output {
+            value: 0,
+            to_recipient: p2wpkh(ALICE_PUBKEY),
+        }),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn op_return_outputs() {
+    let coin = TestCoinContext::default();
+
+    let outputs = vec![
+        Proto::Output {
+            value: ONE_BTC / 2,
+            to_recipient: p2wpkh(BOB_PUBKEY),
+        },
+        op_return("68656c6c6f"),
+        // `OP_RETURN OP_13 <data>`, e.g. a runestone.
+        Proto::Output {
+            value: 0,
+            to_recipient: ProtoOutputRecipient::custom_script_pubkey(hex("6a5d0401020304").into()),
+        },
+    ];
+    let signed = BitcoinEntry.sign(&coin, signing_input(outputs));
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    let outputs = signed.transaction.unwrap().outputs;
+    assert_eq!(outputs.len(), 4);
+    assert_eq!(outputs[1].value, 0);
+    assert_eq!(outputs[1].script_pubkey, hex("6a0568656c6c6f"));
+    assert_eq!(outputs[2].value, 0);
+    assert_eq!(outputs[2].script_pubkey, hex("6a5d0401020304"));
+    // The change.
+    assert_eq!(outputs[3].value, ONE_BTC / 2 - signed.fee);
+}
+
+#[test]
+fn op_return_only() {
+    let coin = TestCoinContext::default();
+
+    // The data of the maximum length, anchored with the change as the only valued output.
+    let data = "ab".repeat(80);
+    let signed = BitcoinEntry.sign(&coin, signing_input(vec![op_return(&data)]));
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    let outputs = signed.transaction.unwrap().outputs;
+    assert_eq!(outputs.len(), 2);
+    assert_eq!(outputs[0].value, 0);
+    assert_eq!(outputs[0].script_pubkey, hex(&format!("6a4c50{data}")));
+    assert_eq!(outputs[1].value, ONE_BTC - signed.fee);
+}
+
+#[test]
+fn op_return_invalid() {
+    let coin = TestCoinContext::default();
+
+    // The data exceeds 80 bytes.
+    let signed = BitcoinEntry.sign(&coin, signing_input(vec![op_return(&"ab".repeat(81))]));
+    assert_eq!(signed.error, Proto::Error::Error_invalid_op_return);
+
+    // The value would be burned.
+    let mut output = op_return("68656c6c6f");
+    output.value = 1_000;
+    let signed = BitcoinEntry.sign(&coin, signing_input(vec![output]));
+    assert_eq!(signed.error, Proto::Error::Error_invalid_op_return);
+
+    // Not only data pushes, `OP_RETURN <data> OP_CHECKSIG`.
+    let output = Proto::Output {
+        value: 0,
+        to_recipient: ProtoOutputRecipient::custom_script_pubkey(hex("6a0568656c6c6fac").into()),
+    };
+    let signed = BitcoinEntry.sign(&coin, signing_input(vec![output]));
+    assert_eq!(signed.error, Proto::Error::Error_invalid_op_return);
+}
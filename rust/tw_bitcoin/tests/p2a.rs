@@ -0,0 +1,256 @@
+mod common;
+use common::{hex, ONE_BTC};
+
+use bitcoin::blockdata::locktime::absolute::LockTime;
+use bitcoin::{OutPoint, PublicKey, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Txid, Witness};
+use std::str::FromStr;
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::transaction_decoder::BitcoinTransactionDecoder;
+use tw_bitcoin::modules::transactions::{
+    is_p2a, p2a_script, CpfpBuilder, FeeEstimator, InputType, P2A_DUST_LIMIT,
+};
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+const SAT_VBYTE: u64 = 10;
+
+const ALICE_PRIVATE_KEY: &str = "e253373989199da27c48680e3a3fc0f648d50f9a727ef17a7fe6a4dc3b159129";
+const ALICE_PUBKEY: &str = "030f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb";
+const BOB_PUBKEY: &str = "037ed9a436e11ec4947ac4b7823787e24ba73180f1edd2857bff19c9f4d62b65bf";
+
+fn p2wpkh_script(pubkey: &str) -> ScriptBuf {
+    let pubkey = PublicKey::from_slice(&hex(pubkey)).unwrap();
+    ScriptBuf::new_v0_p2wpkh(&pubkey.wpubkey_hash().unwrap())
+}
+
+fn p2wpkh(pubkey: &str) -> ProtoOutputRecipient<'static> {
+    ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+        variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+            to_address: ProtoPubkeyOrHash::pubkey(hex(pubkey).into()),
+        }),
+    })
+}
+
+fn p2a_output(value: u64) -> Proto::Output<'static> {
+    Proto::Output {
+        value,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2a(true),
+        }),
+    }
+}
+
+fn alice_input(vout: u32, value: u64) -> Proto::Input<'static> {
+    Proto::Input {
+        txid: vec![1; 32].into(),
+        vout,
+        value,
+        sighash_type: UtxoProto::SighashType::All,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2wpkh(hex(ALICE_PUBKEY).into()),
+        }),
+        ..Default::default()
+    }
+}
+
+fn alice_utxo() -> TxOut {
+    TxOut {
+        value: 50_000,
+        script_pubkey: p2wpkh_script(ALICE_PUBKEY),
+    }
+}
+
+/// Pays to Bob with an anchor output of `anchor` satoshis and the change to Alice.
+fn signing_input(anchor: u64) -> Proto::SigningInput<'static> {
+    Proto::SigningInput {
+        private_key: hex(ALICE_PRIVATE_KEY).into(),
+        inputs: vec![alice_input(0, ONE_BTC)],
+        outputs: vec![
+            Proto::Output {
+                value: ONE_BTC / 2,
+                to_recipient: p2wpkh(BOB_PUBKEY),
+            },
+            p2a_output(anchor),
+        ],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        fee_per_vb: SAT_VBYTE,
+        change_output: Some(Proto::Output {
+            value: 0,
+            to_recipient: p2wpkh(ALICE_PUBKEY),
+        }),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn p2a_sign_anchor_output() {
+    assert_eq!(p2a_script().to_bytes(), hex("51024e73"));
+    assert!(is_p2a(&p2a_script()));
+    assert!(!is_p2a(&p2wpkh_script(ALICE_PUBKEY)));
+
+    let signed = BitcoinEntry.sign(&TestCoinContext::default(), signing_input(P2A_DUST_LIMIT));
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    let outputs = signed.transaction.unwrap().outputs;
+    assert_eq!(outputs.len(), 3);
+    assert_eq!(outputs[1].value, P2A_DUST_LIMIT);
+    assert_eq!(outputs[1].script_pubkey, hex("51024e73"));
+
+    let decoded =
+        BitcoinTransactionDecoder::decode(&signed.encoded, &[], bitcoin::Network::Bitcoin).unwrap();
+    assert_eq!(decoded.outputs[1].script_type, Proto::ScriptType::P2a);
+}
+
+#[test]
+fn p2a_dust_anchor_output() {
+    let coin = TestCoinContext::default();
+
+    // The zero value anchor as ephemeral dust, which no dust policy rejects.
+    for dust_policy in [
+        UtxoProto::DustPolicy::DustInclude,
+        UtxoProto::DustPolicy::DustReject,
+        UtxoProto::DustPolicy::DustDropChangeToFee,
+    ] {
+        for value in [0, P2A_DUST_LIMIT - 1] {
+            let signing = Proto::SigningInput {
+                dust_policy,
+                ..signing_input(value)
+            };
+            let signed = BitcoinEntry.sign(&coin, signing);
+            assert_eq!(signed.error, Proto::Error::OK, "{dust_policy:?}");
+            assert_eq!(signed.transaction.unwrap().outputs[1].value, value);
+        }
+    }
+
+    // Any other output below the threshold is still rejected.
+    let mut signing = Proto::SigningInput {
+        dust_policy: UtxoProto::DustPolicy::DustReject,
+        ..signing_input(0)
+    };
+    signing.outputs[0].value = 100;
+    let signed = BitcoinEntry.sign(&coin, signing);
+    assert_eq!(signed.error, Proto::Error::Error_utxo_dust_output);
+}
+
+#[test]
+fn p2a_spend_anchor_input() {
+    let anchor = Proto::Input {
+        txid: vec![2; 32].into(),
+        vout: 1,
+        value: P2A_DUST_LIMIT,
+        sighash_type: UtxoProto::SighashType::All,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2a(true),
+        }),
+        ..Default::default()
+    };
+    let signing = Proto::SigningInput {
+        private_key: hex(ALICE_PRIVATE_KEY).into(),
+        inputs: vec![anchor, alice_input(0, ONE_BTC)],
+        outputs: vec![Proto::Output {
+            value: ONE_BTC / 2,
+            to_recipient: p2wpkh(BOB_PUBKEY),
+        }],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        fee_per_vb: SAT_VBYTE,
+        change_output: Some(Proto::Output {
+            value: 0,
+            to_recipient: p2wpkh(ALICE_PUBKEY),
+        }),
+        ..Default::default()
+    };
+
+    let signed = BitcoinEntry.sign(&TestCoinContext::default(), signing);
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    let tx: Transaction = bitcoin::consensus::deserialize(&signed.encoded).unwrap();
+    assert_eq!(tx.input.len(), 2);
+    let anchor = tx
+        .input
+        .iter()
+        .find(|txin| txin.previous_output.vout == 1)
+        .unwrap();
+    assert!(anchor.script_sig.is_empty());
+    assert!(anchor.witness.is_empty());
+
+    let estimate = FeeEstimator::new()
+        .add_input(InputType::P2a)
+        .add_input(InputType::P2wpkh)
+        .add_output(&p2wpkh_script(BOB_PUBKEY))
+        .add_output(&p2wpkh_script(ALICE_PUBKEY))
+        .estimate()
+        .unwrap();
+    // The ECDSA signature might be shorter than the maximum.
+    let weight = tx.weight().to_wu();
+    assert!(estimate.weight >= weight);
+    assert!(estimate.weight - weight <= 4);
+}
+
+#[test]
+fn p2a_cpfp_anchor() {
+    // The zero fee parent with the ephemeral anchor, which Alice spends with one of her outputs.
+    let parent = Transaction {
+        version: 3,
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint {
+                txid: Txid::from_str(
+                    "8ec895b4d30adb01e38471ca1019bfc8c3e5fbd1f28d9e7b5653260d89989008",
+                )
+                .unwrap(),
+                vout: 0,
+            },
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::MAX,
+            witness: Witness::new(),
+        }],
+        output: vec![
+            TxOut {
+                value: 100_000,
+                script_pubkey: p2wpkh_script(BOB_PUBKEY),
+            },
+            TxOut {
+                value: 0,
+                script_pubkey: p2a_script(),
+            },
+        ],
+    };
+    let outpoint = OutPoint {
+        txid: Txid::from_str("8ec895b4d30adb01e38471ca1019bfc8c3e5fbd1f28d9e7b5653260d89989008")
+            .unwrap(),
+        vout: 1,
+    };
+    let cpfp = CpfpBuilder::new(parent.clone(), 0, 1, p2wpkh_script(ALICE_PUBKEY))
+        .fee_rate(SAT_VBYTE)
+        .add_utxo(outpoint, alice_utxo())
+        .build()
+        .unwrap();
+    assert_eq!(cpfp.package_fee, cpfp.fee);
+    assert_eq!(cpfp.package_fee, SAT_VBYTE * cpfp.package_vsize);
+
+    let mut psbt = cpfp.child;
+    psbt.sign(&hex(ALICE_PRIVATE_KEY)).unwrap();
+    psbt.finalize().unwrap();
+    let child = psbt.extract_tx().unwrap();
+
+    assert_eq!(child.input.len(), 2);
+    assert_eq!(child.input[0].previous_output.txid, parent.txid());
+    assert!(child.input[0].witness.is_empty());
+    assert_eq!(child.input[1].witness.len(), 2);
+    assert_eq!(child.output[0].value, 50_000 - cpfp.fee);
+    assert!(parent.vsize() + child.vsize() <= cpfp.package_vsize as usize);
+
+    // The anchor input is detected from its prevout only.
+    let decoded = BitcoinTransactionDecoder::decode(
+        &bitcoin::consensus::serialize(&child),
+        &[parent.output[1].clone(), alice_utxo()],
+        bitcoin::Network::Bitcoin,
+    )
+    .unwrap();
+    assert_eq!(decoded.inputs[0].script_type, Proto::ScriptType::P2a);
+    assert!(!decoded.inputs[0].is_signed);
+    assert_eq!(decoded.fee, cpfp.fee);
+}
@@ -1,6 +1,8 @@
 mod common;
 
+use bitcoin::Transaction;
 use common::{hex, MINER_FEE, ONE_BTC};
+use secp256k1::{Message, Secp256k1, XOnlyPublicKey};
 use tw_bitcoin::aliases::*;
 use tw_bitcoin::entry::BitcoinEntry;
 use tw_coin_entry::coin_entry::CoinEntry;
@@ -96,3 +98,95 @@ fn coin_entry_sign_input_p2pkh_output_p2tr_key_path() {
     assert_eq!(signed.error, Proto::Error::OK);
     assert_eq!(&encoded, "02000000000101ac6058397e18c277e98defda1bc38bdf3ab304563d7df7afed0ca5f63220589a0000000000ffffffff01806de72901000000225120a5c027857e359d19f625e52a106b8ac6ca2d6a8728f6cf2107cd7958ee0787c20140ec2d3910d41506b60aaa20520bb72f15e2d2cbd97e3a8e26ee7bad5f4c56b0f2fb0ceaddac33cb2813a33ba017ba6b1d011bab74a0426f12a2bcf47b4ed5bc8600000000");
 }
+
+#[test]
+fn coin_entry_sign_p2tr_key_path_with_annex() {
+    let coin = TestCoinContext::default();
+
+    let alice_pubkey = hex("0351e003fdc48e7f31c9bc94996c91f6c3273b7ef4208a1686021bedf7673bb058");
+    let bob_private_key = hex("26c2566adcc030a1799213bfd546e615f6ab06f72085ec6806ff1761da48d227");
+    let bob_pubkey = hex("02c0938cf377023dfde55e9c96b3cff4ca8894fb6b5d2009006bd43c0bff69cac9");
+    let annex = hex("50deadbeef");
+
+    let txid: Vec<u8> = hex("9a582032f6a50cedaff77d3d5604b33adf8bc31bdaef8de977c2187e395860ac")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let tx1 = Proto::Input {
+        txid: txid.as_slice().into(),
+        vout: 0,
+        value: ONE_BTC * 50 - MINER_FEE,
+        sighash_type: UtxoProto::SighashType::UseDefault,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2tr_key_path(Proto::mod_Input::InputTaprootKeyPath {
+                public_key: bob_pubkey.as_slice().into(),
+                one_prevout: false,
+            }),
+        }),
+        annex: annex.as_slice().into(),
+        ..Default::default()
+    };
+
+    let out1 = Proto::Output {
+        value: ONE_BTC * 50 - MINER_FEE - MINER_FEE,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2tr_key_path(alice_pubkey.as_slice().into()),
+        }),
+    };
+
+    let signing = Proto::SigningInput {
+        private_key: bob_private_key.as_slice().into(),
+        inputs: vec![tx1],
+        outputs: vec![out1],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        disable_change_output: true,
+        dangerous_use_fixed_schnorr_rng: true,
+        ..Default::default()
+    };
+
+    // The sighash commits to the annex, unlike the one of the same transaction without it.
+    let expected_sighash = hex("648e12804cc6fa6c1af8a215228c62e556bd81b1b601377cccc37cf1e5908b55");
+    let presigning = BitcoinEntry.preimage_hashes(&coin, signing.clone());
+    assert_eq!(presigning.error, Proto::Error::OK);
+    assert_eq!(presigning.sighashes[0].sighash, expected_sighash);
+
+    let signed = BitcoinEntry.sign(&coin, signing.clone());
+    assert_eq!(signed.error, Proto::Error::OK);
+    let tx: Transaction = bitcoin::consensus::deserialize(&signed.encoded).unwrap();
+
+    // The annex follows the signature.
+    let witness = &tx.input[0].witness;
+    assert_eq!(witness.len(), 2);
+    assert_eq!(
+        tw_utxo::compiler::taproot_annex(witness),
+        Some(annex.as_slice())
+    );
+
+    let signature = bitcoin::taproot::Signature::from_slice(&witness[0]).unwrap();
+    let output_key = XOnlyPublicKey::from_slice(&hex(
+        "e01cfdd05da8fa1d71f987373f3790d45dea9861acb0525c86656fe50f4397a6",
+    ))
+    .unwrap();
+    Secp256k1::new()
+        .verify_schnorr(
+            &signature.sig,
+            &Message::from_slice(&expected_sighash).unwrap(),
+            &output_key,
+        )
+        .unwrap();
+
+    // The annex must start with `0x50`.
+    let mut invalid = signing.clone();
+    invalid.inputs[0].annex = hex("deadbeef").into();
+    let signed = BitcoinEntry.sign(&coin, invalid);
+    assert_eq!(signed.error, Proto::Error::Error_utxo_invalid_annex);
+
+    // Only the Taproot inputs can have an annex.
+    let mut invalid = signing;
+    invalid.inputs[0].to_recipient = ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+        variant: ProtoInputBuilder::p2wpkh(bob_pubkey.as_slice().into()),
+    });
+    let signed = BitcoinEntry.sign(&coin, invalid);
+    assert_eq!(signed.error, Proto::Error::Error_utxo_invalid_annex);
+}
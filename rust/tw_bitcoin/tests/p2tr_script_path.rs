@@ -1,11 +1,13 @@
 mod common;
 
-use bitcoin::taproot::LeafVersion;
-use bitcoin::PublicKey;
+use bitcoin::taproot::{LeafVersion, TapLeafHash, TapNodeHash, TaprootBuilder};
+use bitcoin::{PublicKey, ScriptBuf};
 use common::hex;
 use tw_bitcoin::aliases::*;
 use tw_bitcoin::entry::BitcoinEntry;
-use tw_bitcoin::modules::transactions::{BRC20TransferInscription, Brc20Ticker};
+use tw_bitcoin::modules::transactions::{
+    BRC20TransferInscription, Brc20Ticker, ControlBlockBuilder,
+};
 use tw_coin_entry::coin_entry::CoinEntry;
 use tw_coin_entry::test_utils::test_context::TestCoinContext;
 use tw_misc::traits::ToBytesVec;
@@ -118,6 +120,7 @@ fn coin_entry_custom_script_path() {
                     one_prevout: false,
                     payload: payload.to_vec().into(),
                     control_block: control_block.serialize().into(),
+                    ..Default::default()
                 },
             ),
         }),
@@ -160,3 +163,129 @@ fn coin_entry_custom_script_path() {
     assert_eq!(transaction.inputs.len(), 1);
     assert_eq!(transaction.outputs.len(), 1);
 }
+
+#[test]
+/// The same BRC20 reveal transaction as in `coin_entry_custom_script_path`,
+/// but the control block is constructed of the internal key and the script tree or the merkle path.
+fn coin_entry_script_path_control_block() {
+    let coin = TestCoinContext::default();
+
+    let alice_private_key = hex("e253373989199da27c48680e3a3fc0f648d50f9a727ef17a7fe6a4dc3b159129");
+    let alice_pubkey = hex("030f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb");
+
+    let ticker = Brc20Ticker::new("oadf".to_string()).unwrap();
+    let inscribe_to = PublicKey::from_slice(&alice_pubkey).unwrap();
+    let transfer = BRC20TransferInscription::new(inscribe_to, ticker, "20".to_string()).unwrap();
+    let payload = transfer.inscription().taproot_program().to_owned();
+
+    let txid: Vec<u8> = hex("797d17d47ae66e598341f9dfdea020b04d4017dcf9cc33f0e51f7a6082171fb1")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let script_tree = Proto::mod_Input::InputTaprootScriptPath {
+        payload: payload.to_vec().into(),
+        internal_key: alice_pubkey.as_slice().into(),
+        script_tree: vec![Proto::mod_Input::TaprootLeaf {
+            depth: 0,
+            script: payload.to_vec().into(),
+        }],
+        ..Default::default()
+    };
+    // The payload is the only leaf, so the merkle path is empty.
+    let merkle_path = Proto::mod_Input::InputTaprootScriptPath {
+        payload: payload.to_vec().into(),
+        internal_key: alice_pubkey.as_slice().into(),
+        ..Default::default()
+    };
+
+    for taproot in [script_tree, merkle_path] {
+        let tx1 = Proto::Input {
+            txid: txid.as_slice().into(),
+            vout: 0,
+            value: 7_000,
+            sighash_type: UtxoProto::SighashType::UseDefault,
+            to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+                variant: ProtoInputBuilder::p2tr_script_path(taproot),
+            }),
+            ..Default::default()
+        };
+
+        let out1 = Proto::Output {
+            value: 546,
+            to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+                variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                    to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.as_slice().into()),
+                }),
+            }),
+        };
+
+        let signing = Proto::SigningInput {
+            private_key: alice_private_key.as_slice().into(),
+            inputs: vec![tx1],
+            outputs: vec![out1],
+            input_selector: UtxoProto::InputSelector::UseAll,
+            disable_change_output: true,
+            dangerous_use_fixed_schnorr_rng: true,
+            ..Default::default()
+        };
+
+        let signed = BitcoinEntry.sign(&coin, signing);
+        assert_eq!(signed.error, Proto::Error::OK);
+        assert_eq!(
+            signed.txid,
+            hex("7046dc2689a27e143ea2ad1039710885147e9485ab6453fa7e87464aa7dd3eca")
+        );
+    }
+}
+
+#[test]
+fn control_block_builder_script_tree() {
+    let internal_key = PublicKey::from_slice(&hex(
+        "030f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb",
+    ))
+    .unwrap()
+    .inner
+    .x_only_public_key()
+    .0;
+    let leaves: Vec<(u8, ScriptBuf)> = [1, 2, 2]
+        .into_iter()
+        .enumerate()
+        .map(|(i, depth)| (depth, ScriptBuf::from_bytes(vec![0x51 + i as u8])))
+        .collect();
+
+    let mut builder = TaprootBuilder::new();
+    for (depth, script) in leaves.iter() {
+        builder = builder.add_leaf(*depth, script.clone()).unwrap();
+    }
+    let spend_info = builder
+        .finalize(&secp256k1::Secp256k1::new(), internal_key)
+        .unwrap();
+
+    let leaf_hash = |script: &ScriptBuf| {
+        TapNodeHash::from(TapLeafHash::from_script(script, LeafVersion::TapScript))
+    };
+    // The second leaf is the sibling of the third one, and the first leaf is the sibling of their branch.
+    let merkle_path = [leaf_hash(&leaves[2].1), leaf_hash(&leaves[0].1)];
+
+    let expected = spend_info
+        .control_block(&(leaves[1].1.clone(), LeafVersion::TapScript))
+        .unwrap();
+    let from_tree =
+        ControlBlockBuilder::from_script_tree(internal_key, &leaves, &leaves[1].1).unwrap();
+    let from_path =
+        ControlBlockBuilder::from_merkle_path(internal_key, &merkle_path, &leaves[1].1).unwrap();
+    assert_eq!(from_tree, expected);
+    assert_eq!(from_path, expected);
+    assert!(expected.verify_taproot_commitment(
+        &secp256k1::Secp256k1::new(),
+        spend_info.output_key().to_inner(),
+        &leaves[1].1,
+    ));
+
+    // Not a leaf of the tree.
+    let other = ScriptBuf::from_bytes(vec![0x60]);
+    ControlBlockBuilder::from_script_tree(internal_key, &leaves, &other).unwrap_err();
+    // Not a valid tree.
+    ControlBlockBuilder::from_script_tree(internal_key, &leaves[..2], &leaves[1].1).unwrap_err();
+}
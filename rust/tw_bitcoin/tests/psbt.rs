@@ -0,0 +1,641 @@
+mod common;
+
+use bitcoin::bip32::{DerivationPath, Fingerprint};
+use bitcoin::blockdata::locktime::absolute::LockTime;
+use bitcoin::consensus::encode::{deserialize, serialize_hex};
+use bitcoin::hashes::Hash;
+use bitcoin::psbt::{Input, Output, PsbtSighashType};
+use bitcoin::sighash::{Prevouts, SighashCache, TapSighashType};
+use bitcoin::{OutPoint, PublicKey, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Txid, Witness};
+use common::{hex, MINER_FEE, ONE_BTC};
+use secp256k1::{Message, Secp256k1, SecretKey, XOnlyPublicKey};
+use std::str::FromStr;
+use tw_bitcoin::modules::psbt::{Psbt, PsbtV2, RequiredLockTime};
+use tw_proto::BitcoinV2::Proto;
+
+const ALICE_PRIVATE_KEY: &str = "57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a";
+const ALICE_PUBKEY: &str = "028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f";
+const BOB_PRIVATE_KEY: &str = "05dead4689ec7d55de654771120866be83bf1b8e25c9a1b77fc58a336e1cd1a3";
+const BOB_PUBKEY: &str = "025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f";
+
+/// Alice's P2PKH output is spent to Bob's P2WPKH output, the same as in `tests/p2wpkh.rs`.
+const P2PKH_SPEND_TX: &str = "020000000111b9f62923af73e297abb69f749e7a1aa2735fbdfd32ac5f6aa89e5c96841c18000000006b483045022100df9ed0b662b759e68b89a42e7144cddf787782a7129d4df05642dd825930e6e6022051a08f577f11cc7390684bbad2951a6374072253ffcf2468d14035ed0d8cd6490121028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28fffffffff01c0aff629010000001600140d0e1cec6c2babe8badde5e9b3dea667da90036d00000000";
+/// Bob's P2WPKH output is spent to Alice's P2WPKH output.
+const P2WPKH_SPEND_TX: &str = "020000000001016e1f16dcfafbb3a83697f6c23c624cd71085a7f8a25ce0bd9743a41d0a458e850000000000ffffffff01806de7290100000016001460cda7b50f14c152d7401c28ae773c698db9237302483045022100a9b517de5a5e036d7133df499b5b751db6f9a01576a6c5dc38229ec08b6c45cd02200e42c9f8c707c9bf0ceab4f739ec8d683dc1f1f29e195a8da9bc183584d624a60121025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f00000000";
+
+fn public_key(hex_key: &str) -> PublicKey {
+    PublicKey::from_slice(&hex(hex_key)).unwrap()
+}
+
+fn p2wpkh(hex_key: &str) -> ScriptBuf {
+    ScriptBuf::new_v0_p2wpkh(&public_key(hex_key).wpubkey_hash().unwrap())
+}
+
+fn unsigned_tx(txid: &str, outputs: Vec<TxOut>) -> Transaction {
+    Transaction {
+        version: 2,
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint {
+                txid: Txid::from_str(txid).unwrap(),
+                vout: 0,
+            },
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::MAX,
+            witness: Witness::new(),
+        }],
+        output: outputs,
+    }
+}
+
+#[test]
+fn psbt_sign_p2pkh() {
+    let tx = unsigned_tx(
+        "181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911",
+        vec![TxOut {
+            value: ONE_BTC * 50 - MINER_FEE,
+            script_pubkey: p2wpkh(BOB_PUBKEY),
+        }],
+    );
+    let mut psbt = Psbt::from_unsigned_tx(tx).unwrap();
+    psbt.inner_mut().inputs[0].witness_utxo = Some(TxOut {
+        value: ONE_BTC * 50,
+        script_pubkey: ScriptBuf::new_p2pkh(&public_key(ALICE_PUBKEY).pubkey_hash()),
+    });
+
+    // The PSBT is exchanged in base64.
+    let mut psbt = Psbt::from_base64(&psbt.to_base64()).unwrap();
+
+    // Bob doesn't control the input.
+    assert!(psbt.sign(&hex(BOB_PRIVATE_KEY)).unwrap().is_empty());
+    assert_eq!(psbt.sign(&hex(ALICE_PRIVATE_KEY)).unwrap(), [0]);
+    assert_eq!(psbt.inner().inputs[0].partial_sigs.len(), 1);
+    psbt.extract_tx().unwrap_err();
+
+    psbt.finalize().unwrap();
+    assert!(psbt.is_finalized());
+    assert!(psbt.inner().inputs[0].partial_sigs.is_empty());
+    assert_eq!(serialize_hex(&psbt.extract_tx().unwrap()), P2PKH_SPEND_TX);
+}
+
+#[test]
+fn psbt_sign_p2wpkh_non_witness_utxo() {
+    let prev_tx: Transaction = deserialize(&hex(P2PKH_SPEND_TX)).unwrap();
+    let tx = unsigned_tx(
+        "858e450a1da44397bde05ca2f8a78510d74c623cc2f69736a8b3fbfadc161f6e",
+        vec![TxOut {
+            value: ONE_BTC * 50 - MINER_FEE - MINER_FEE,
+            script_pubkey: p2wpkh(ALICE_PUBKEY),
+        }],
+    );
+    assert_eq!(tx.input[0].previous_output.txid, prev_tx.txid());
+
+    let mut psbt = Psbt::from_unsigned_tx(tx.clone()).unwrap();
+    psbt.inner_mut().inputs[0].non_witness_utxo = Some(prev_tx);
+    psbt.inner_mut().inputs[0].sighash_type = Some(PsbtSighashType::from_u32(1));
+
+    assert_eq!(psbt.sign(&hex(BOB_PRIVATE_KEY)).unwrap(), [0]);
+    psbt.finalize().unwrap();
+    assert_eq!(serialize_hex(&psbt.extract_tx().unwrap()), P2WPKH_SPEND_TX);
+
+    // The previous transaction must match the outpoint.
+    let mut psbt = Psbt::from_unsigned_tx(tx).unwrap();
+    psbt.inner_mut().inputs[0].non_witness_utxo = Some(deserialize(&hex(P2WPKH_SPEND_TX)).unwrap());
+    psbt.sign(&hex(BOB_PRIVATE_KEY)).unwrap_err();
+}
+
+#[test]
+fn psbt_sign_p2sh_p2wpkh() {
+    let bob = public_key(BOB_PUBKEY);
+    let redeem_script = p2wpkh(BOB_PUBKEY);
+    let script_pubkey = ScriptBuf::new_p2sh(&redeem_script.script_hash());
+
+    let tx = unsigned_tx(
+        "858e450a1da44397bde05ca2f8a78510d74c623cc2f69736a8b3fbfadc161f6e",
+        vec![TxOut {
+            value: ONE_BTC - MINER_FEE,
+            script_pubkey: p2wpkh(ALICE_PUBKEY),
+        }],
+    );
+    let mut psbt = Psbt::from_unsigned_tx(tx).unwrap();
+    psbt.inner_mut().inputs[0].witness_utxo = Some(TxOut {
+        value: ONE_BTC,
+        script_pubkey,
+    });
+
+    assert_eq!(psbt.sign(&hex(BOB_PRIVATE_KEY)).unwrap(), [0]);
+    // The missing redeem script is set by the signer.
+    assert_eq!(
+        psbt.inner().inputs[0].redeem_script.as_ref(),
+        Some(&redeem_script)
+    );
+    psbt.finalize().unwrap();
+
+    let tx = psbt.extract_tx().unwrap();
+    let script_sig: Vec<_> = tx.input[0].script_sig.instructions().collect();
+    assert_eq!(script_sig.len(), 1);
+    assert_eq!(tx.input[0].witness.len(), 2);
+    assert_eq!(
+        tx.input[0].witness.last().unwrap(),
+        bob.to_bytes().as_slice()
+    );
+}
+
+#[test]
+fn psbt_sign_p2wsh_multisig() {
+    let secp = Secp256k1::verification_only();
+    let alice = public_key(ALICE_PUBKEY);
+    let bob = public_key(BOB_PUBKEY);
+    let witness_script = bitcoin::blockdata::script::Builder::new()
+        .push_int(2)
+        .push_key(&alice)
+        .push_key(&bob)
+        .push_int(2)
+        .push_opcode(bitcoin::blockdata::opcodes::all::OP_CHECKMULTISIG)
+        .into_script();
+    let utxo = TxOut {
+        value: ONE_BTC,
+        script_pubkey: ScriptBuf::new_v0_p2wsh(&witness_script.wscript_hash()),
+    };
+
+    let tx = unsigned_tx(
+        "858e450a1da44397bde05ca2f8a78510d74c623cc2f69736a8b3fbfadc161f6e",
+        vec![TxOut {
+            value: ONE_BTC - MINER_FEE,
+            script_pubkey: p2wpkh(ALICE_PUBKEY),
+        }],
+    );
+    let mut psbt = Psbt::from_unsigned_tx(tx).unwrap();
+    psbt.inner_mut().inputs[0].witness_utxo = Some(utxo.clone());
+    psbt.inner_mut().inputs[0].witness_script = Some(witness_script.clone());
+
+    // Bob signs first, e.g. on a hardware wallet, and passes the PSBT on.
+    assert_eq!(psbt.sign(&hex(BOB_PRIVATE_KEY)).unwrap(), [0]);
+    let mut psbt = Psbt::from_base64(&psbt.to_base64()).unwrap();
+    // One of two signatures is not enough.
+    psbt.clone().finalize().unwrap_err();
+
+    assert_eq!(psbt.sign(&hex(ALICE_PRIVATE_KEY)).unwrap(), [0]);
+    psbt.finalize().unwrap();
+    let tx = psbt.extract_tx().unwrap();
+
+    // `<empty> <alice signature> <bob signature> <witness script>`.
+    let witness: Vec<&[u8]> = tx.input[0].witness.iter().collect();
+    assert_eq!(witness.len(), 4);
+    assert!(witness[0].is_empty());
+    assert_eq!(witness[3], witness_script.as_bytes());
+
+    let sighash = SighashCache::new(&tx)
+        .segwit_signature_hash(
+            0,
+            &witness_script,
+            utxo.value,
+            bitcoin::sighash::EcdsaSighashType::All,
+        )
+        .unwrap();
+    let message = Message::from_slice(sighash.as_byte_array()).unwrap();
+    for (sig, key) in [(witness[1], alice), (witness[2], bob)] {
+        let sig = bitcoin::ecdsa::Signature::from_slice(sig).unwrap();
+        secp.verify_ecdsa(&message, &sig.sig, &key.inner).unwrap();
+    }
+}
+
+#[test]
+fn psbt_sign_p2tr_key_path() {
+    let secp = Secp256k1::new();
+    let secret = SecretKey::from_slice(&hex(ALICE_PRIVATE_KEY)).unwrap();
+    let (internal_key, _) = secret.x_only_public_key(&secp);
+    let utxo = TxOut {
+        value: ONE_BTC,
+        script_pubkey: ScriptBuf::new_v1_p2tr(&secp, internal_key, None),
+    };
+
+    let tx = unsigned_tx(
+        "858e450a1da44397bde05ca2f8a78510d74c623cc2f69736a8b3fbfadc161f6e",
+        vec![TxOut {
+            value: ONE_BTC - MINER_FEE,
+            script_pubkey: p2wpkh(BOB_PUBKEY),
+        }],
+    );
+    let mut psbt = Psbt::from_unsigned_tx(tx).unwrap();
+    psbt.inner_mut().inputs[0].witness_utxo = Some(utxo.clone());
+
+    assert!(psbt.sign(&hex(BOB_PRIVATE_KEY)).unwrap().is_empty());
+    assert_eq!(psbt.sign(&hex(ALICE_PRIVATE_KEY)).unwrap(), [0]);
+    assert_eq!(psbt.inner().inputs[0].tap_internal_key, Some(internal_key));
+    psbt.finalize().unwrap();
+    let tx = psbt.extract_tx().unwrap();
+
+    // The default sighash type is omitted from the signature.
+    let witness: Vec<&[u8]> = tx.input[0].witness.iter().collect();
+    assert_eq!(witness.len(), 1);
+    assert_eq!(witness[0].len(), 64);
+
+    let sighash = SighashCache::new(&tx)
+        .taproot_key_spend_signature_hash(
+            0,
+            &Prevouts::All(&[utxo.clone()]),
+            TapSighashType::Default,
+        )
+        .unwrap();
+    let message = Message::from_slice(sighash.as_byte_array()).unwrap();
+    let output_key = XOnlyPublicKey::from_slice(&utxo.script_pubkey.as_bytes()[2..]).unwrap();
+    let sig = secp256k1::schnorr::Signature::from_slice(witness[0]).unwrap();
+    secp.verify_schnorr(&sig, &message, &output_key).unwrap();
+}
+
+#[test]
+fn psbt_invalid() {
+    Psbt::from_base64("cHNidP8=").unwrap_err();
+    Psbt::from_base64("not a psbt").unwrap_err();
+    Psbt::deserialize(&hex("00")).unwrap_err();
+
+    // The spent output is unknown.
+    let tx = unsigned_tx(
+        "858e450a1da44397bde05ca2f8a78510d74c623cc2f69736a8b3fbfadc161f6e",
+        vec![],
+    );
+    let mut psbt = Psbt::from_unsigned_tx(tx).unwrap();
+    assert!(psbt.sign(&hex(ALICE_PRIVATE_KEY)).unwrap().is_empty());
+    psbt.finalize().unwrap_err();
+    psbt.sign(&[0; 32]).unwrap_err();
+
+    // The transaction must be unsigned.
+    let signed: Transaction = deserialize(&hex(P2PKH_SPEND_TX)).unwrap();
+    Psbt::from_unsigned_tx(signed).unwrap_err();
+}
+
+fn outpoint(txid: &str, vout: u32) -> OutPoint {
+    OutPoint {
+        txid: Txid::from_str(txid).unwrap(),
+        vout,
+    }
+}
+
+#[test]
+fn psbt_v2_construct_and_sign() {
+    let txout = TxOut {
+        value: ONE_BTC * 50 - MINER_FEE,
+        script_pubkey: p2wpkh(BOB_PUBKEY),
+    };
+    let mut psbt = PsbtV2::new(2, None).unwrap();
+    assert_eq!(
+        psbt.add_output(txout.clone(), Output::default()).unwrap(),
+        0
+    );
+
+    // The PSBT is passed to the party that adds the input.
+    let mut psbt = PsbtV2::from_base64(&psbt.to_base64()).unwrap();
+    assert_eq!(psbt.unsigned_tx().output, [txout.clone()]);
+    let index = psbt
+        .add_input(
+            outpoint(
+                "181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911",
+                0,
+            ),
+            Sequence::MAX,
+            RequiredLockTime::default(),
+            Input::default(),
+        )
+        .unwrap();
+    assert_eq!(index, 0);
+    psbt.inputs_mut()[0].witness_utxo = Some(TxOut {
+        value: ONE_BTC * 50,
+        script_pubkey: ScriptBuf::new_p2pkh(&public_key(ALICE_PUBKEY).pubkey_hash()),
+    });
+
+    let mut psbt = PsbtV2::deserialize(&psbt.serialize()).unwrap();
+    assert!(psbt.inputs_modifiable());
+    assert!(psbt.outputs_modifiable());
+    assert_eq!(psbt.sign(&hex(ALICE_PRIVATE_KEY)).unwrap(), [0]);
+
+    // `SIGHASH_ALL` commits to the inputs and the outputs.
+    assert!(!psbt.inputs_modifiable());
+    assert!(!psbt.outputs_modifiable());
+    psbt.add_output(txout, Output::default()).unwrap_err();
+
+    let mut psbt = PsbtV2::deserialize(&psbt.serialize()).unwrap();
+    psbt.finalize().unwrap();
+    assert_eq!(serialize_hex(&psbt.extract_tx().unwrap()), P2PKH_SPEND_TX);
+}
+
+#[test]
+fn psbt_v2_anyone_can_pay() {
+    let mut psbt = PsbtV2::new(2, None).unwrap();
+    psbt.add_input(
+        outpoint(
+            "181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911",
+            0,
+        ),
+        Sequence::MAX,
+        RequiredLockTime::default(),
+        Input {
+            witness_utxo: Some(TxOut {
+                value: ONE_BTC * 50,
+                script_pubkey: ScriptBuf::new_p2pkh(&public_key(ALICE_PUBKEY).pubkey_hash()),
+            }),
+            // `SIGHASH_SINGLE | SIGHASH_ANYONECANPAY`.
+            sighash_type: Some(PsbtSighashType::from_u32(0x83)),
+            ..Input::default()
+        },
+    )
+    .unwrap();
+    psbt.add_output(
+        TxOut {
+            value: ONE_BTC * 50 - MINER_FEE,
+            script_pubkey: p2wpkh(BOB_PUBKEY),
+        },
+        Output::default(),
+    )
+    .unwrap();
+
+    assert_eq!(psbt.sign(&hex(ALICE_PRIVATE_KEY)).unwrap(), [0]);
+    assert!(psbt.inputs_modifiable());
+    assert!(psbt.outputs_modifiable());
+    assert!(psbt.has_sighash_single());
+
+    let mut psbt = PsbtV2::from_base64(&psbt.to_base64()).unwrap();
+    assert!(psbt.has_sighash_single());
+    psbt.set_modifiable(false, false);
+    assert!(!psbt.inputs_modifiable());
+    assert!(!psbt.outputs_modifiable());
+    assert!(psbt.has_sighash_single());
+    psbt.add_input(
+        outpoint(
+            "858e450a1da44397bde05ca2f8a78510d74c623cc2f69736a8b3fbfadc161f6e",
+            0,
+        ),
+        Sequence::MAX,
+        RequiredLockTime::default(),
+        Input::default(),
+    )
+    .unwrap_err();
+}
+
+#[test]
+fn psbt_v2_lock_time() {
+    let txid = "181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911";
+    let mut psbt = PsbtV2::new(2, Some(10)).unwrap();
+    let lock_time = |psbt: &PsbtV2| psbt.unsigned_tx().lock_time.to_consensus_u32();
+    assert_eq!(lock_time(&psbt), 10);
+
+    let height = RequiredLockTime {
+        time: None,
+        height: Some(100),
+    };
+    psbt.add_input(outpoint(txid, 0), Sequence::ZERO, height, Input::default())
+        .unwrap();
+    assert_eq!(lock_time(&psbt), 100);
+
+    // The height is preferred if every input supports it.
+    let height_or_time = RequiredLockTime {
+        time: Some(600_000_000),
+        height: Some(200),
+    };
+    psbt.add_input(
+        outpoint(txid, 1),
+        Sequence::ZERO,
+        height_or_time,
+        Input::default(),
+    )
+    .unwrap();
+    assert_eq!(lock_time(&psbt), 200);
+
+    // A timestamp conflicts with the height of the first input.
+    let time = RequiredLockTime {
+        time: Some(550_000_000),
+        height: None,
+    };
+    psbt.add_input(outpoint(txid, 2), Sequence::ZERO, time, Input::default())
+        .unwrap_err();
+    // A height must be below the threshold.
+    let invalid = RequiredLockTime {
+        time: None,
+        height: Some(500_000_000),
+    };
+    psbt.add_input(outpoint(txid, 2), Sequence::ZERO, invalid, Input::default())
+        .unwrap_err();
+    assert_eq!(psbt.unsigned_tx().input.len(), 2);
+    assert_eq!(lock_time(&psbt), 200);
+
+    let psbt = PsbtV2::deserialize(&psbt.serialize()).unwrap();
+    assert_eq!(psbt.fallback_lock_time(), Some(10));
+    assert_eq!(psbt.required_lock_times(), [height, height_or_time]);
+    assert_eq!(lock_time(&psbt), 200);
+    assert_eq!(
+        psbt.into_v0().inner().unsigned_tx.lock_time,
+        LockTime::from_consensus(200)
+    );
+}
+
+#[test]
+fn psbt_v2_version_conversion() {
+    let tx = unsigned_tx(
+        "181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911",
+        vec![TxOut {
+            value: ONE_BTC * 50 - MINER_FEE,
+            script_pubkey: p2wpkh(BOB_PUBKEY),
+        }],
+    );
+    let mut v0 = Psbt::from_unsigned_tx(tx.clone()).unwrap();
+    v0.inner_mut().inputs[0].witness_utxo = Some(TxOut {
+        value: ONE_BTC * 50,
+        script_pubkey: ScriptBuf::new_p2pkh(&public_key(ALICE_PUBKEY).pubkey_hash()),
+    });
+
+    let v2 = PsbtV2::from_v0(v0.clone()).unwrap();
+    assert_eq!(v2.unsigned_tx(), &tx);
+    assert_eq!(v2.fallback_lock_time(), Some(0));
+    // The transaction of version 0 is fixed.
+    assert!(!v2.inputs_modifiable());
+    assert!(!v2.outputs_modifiable());
+
+    // The versions are not interchangeable.
+    Psbt::deserialize(&v2.serialize()).unwrap_err();
+    PsbtV2::deserialize(&v0.serialize()).unwrap_err();
+
+    let v2 = PsbtV2::from_base64(&v2.to_base64()).unwrap();
+    assert_eq!(v2.into_v0(), v0);
+}
+
+#[test]
+fn psbt_v2_invalid() {
+    // Version 2 requires the transaction version 2 at least.
+    PsbtV2::new(1, None).unwrap_err();
+    let mut tx = unsigned_tx(
+        "181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911",
+        vec![],
+    );
+    tx.version = 1;
+    PsbtV2::from_v0(Psbt::from_unsigned_tx(tx).unwrap()).unwrap_err();
+
+    PsbtV2::from_base64("cHNidP8=").unwrap_err();
+    PsbtV2::from_base64("not a psbt").unwrap_err();
+
+    let bytes = PsbtV2::new(2, None).unwrap().serialize();
+    PsbtV2::deserialize(&bytes).unwrap();
+    PsbtV2::deserialize(&bytes[..bytes.len() - 1]).unwrap_err();
+}
+
+/// Returns the PSBT of a 2-of-2 P2WSH multisig input of Alice and Bob, and the witness script.
+fn multisig_psbt() -> (Psbt, ScriptBuf) {
+    let witness_script = bitcoin::blockdata::script::Builder::new()
+        .push_int(2)
+        .push_key(&public_key(ALICE_PUBKEY))
+        .push_key(&public_key(BOB_PUBKEY))
+        .push_int(2)
+        .push_opcode(bitcoin::blockdata::opcodes::all::OP_CHECKMULTISIG)
+        .into_script();
+    let tx = unsigned_tx(
+        "858e450a1da44397bde05ca2f8a78510d74c623cc2f69736a8b3fbfadc161f6e",
+        vec![TxOut {
+            value: ONE_BTC - MINER_FEE,
+            script_pubkey: p2wpkh(ALICE_PUBKEY),
+        }],
+    );
+    let mut psbt = Psbt::from_unsigned_tx(tx).unwrap();
+    psbt.inner_mut().inputs[0].witness_utxo = Some(TxOut {
+        value: ONE_BTC,
+        script_pubkey: ScriptBuf::new_v0_p2wsh(&witness_script.wscript_hash()),
+    });
+    psbt.inner_mut().inputs[0].witness_script = Some(witness_script.clone());
+    (psbt, witness_script)
+}
+
+#[test]
+fn psbt_combine_multisig() {
+    let alice = public_key(ALICE_PUBKEY);
+    let bob = public_key(BOB_PUBKEY);
+    let (psbt, witness_script) = multisig_psbt();
+
+    // The coordinator sends the PSBT to both devices, which sign in parallel.
+    let mut alice_psbt = psbt.clone();
+    assert_eq!(alice_psbt.sign(&hex(ALICE_PRIVATE_KEY)).unwrap(), [0]);
+    let mut bob_psbt = Psbt::from_base64(&psbt.to_base64()).unwrap();
+    assert_eq!(bob_psbt.sign(&hex(BOB_PRIVATE_KEY)).unwrap(), [0]);
+    // Bob's device adds the origin of its key.
+    let origin = (
+        Fingerprint::from_str("d34db33f").unwrap(),
+        DerivationPath::from_str("m/48'/0'/0'/2'/0/0").unwrap(),
+    );
+    bob_psbt.inner_mut().inputs[0]
+        .bip32_derivation
+        .insert(bob.inner, origin.clone());
+
+    let mut combined = psbt.clone();
+    combined.combine(alice_psbt.clone()).unwrap();
+    // One of two signatures is not enough.
+    combined.clone().finalize().unwrap_err();
+    combined
+        .combine(Psbt::from_base64(&bob_psbt.to_base64()).unwrap())
+        .unwrap();
+
+    let input = &combined.inner().inputs[0];
+    assert_eq!(input.partial_sigs.len(), 2);
+    assert!(input.partial_sigs.contains_key(&alice));
+    assert!(input.partial_sigs.contains_key(&bob));
+    assert_eq!(input.bip32_derivation.get(&bob.inner), Some(&origin));
+
+    // Combining is commutative, and the same PSBT can be combined again.
+    let mut other = bob_psbt.clone();
+    other.combine(alice_psbt.clone()).unwrap();
+    other.combine(alice_psbt).unwrap();
+    assert_eq!(other, combined);
+
+    combined.finalize().unwrap();
+    let tx = combined.extract_tx().unwrap();
+    let witness: Vec<&[u8]> = tx.input[0].witness.iter().collect();
+    assert_eq!(witness.len(), 4);
+    assert_eq!(witness[3], witness_script.as_bytes());
+}
+
+#[test]
+fn psbt_combine_conflicts() {
+    let (psbt, _) = multisig_psbt();
+    let mut alice_psbt = psbt.clone();
+    alice_psbt.sign(&hex(ALICE_PRIVATE_KEY)).unwrap();
+    let mut bob_psbt = psbt.clone();
+    bob_psbt.sign(&hex(BOB_PRIVATE_KEY)).unwrap();
+
+    // Bob's signature under Alice's key.
+    let mut forged = psbt.clone();
+    let bob_sig = bob_psbt.inner().inputs[0].partial_sigs[&public_key(BOB_PUBKEY)];
+    forged.inner_mut().inputs[0]
+        .partial_sigs
+        .insert(public_key(ALICE_PUBKEY), bob_sig);
+
+    let mut all_anyone_can_pay = psbt.clone();
+    all_anyone_can_pay.inner_mut().inputs[0].sighash_type = Some(PsbtSighashType::from_u32(0x81));
+    let mut all = psbt.clone();
+    all.inner_mut().inputs[0].sighash_type = Some(PsbtSighashType::from_u32(0x01));
+
+    let mut output_script = psbt.clone();
+    output_script.inner_mut().outputs[0].witness_script = Some(ScriptBuf::new());
+    let mut other_output_script = psbt.clone();
+    other_output_script.inner_mut().outputs[0].witness_script = Some(p2wpkh(BOB_PUBKEY));
+
+    let mut tx = psbt.inner().unsigned_tx.clone();
+    tx.lock_time = LockTime::from_consensus(1);
+    let other_tx = Psbt::from_unsigned_tx(tx).unwrap();
+
+    for (mut psbt, other) in [
+        (alice_psbt, forged),
+        (all_anyone_can_pay, all),
+        (output_script, other_output_script),
+        (psbt, other_tx),
+    ] {
+        let original = psbt.clone();
+        assert_eq!(
+            Proto::Error::from(psbt.combine(other).unwrap_err()),
+            Proto::Error::Error_psbt_conflict
+        );
+        assert_eq!(psbt, original);
+    }
+}
+
+#[test]
+fn psbt_v2_combine() {
+    let txout = TxOut {
+        value: ONE_BTC * 50 - MINER_FEE,
+        script_pubkey: p2wpkh(BOB_PUBKEY),
+    };
+    let mut psbt = PsbtV2::new(2, None).unwrap();
+    psbt.add_output(txout, Output::default()).unwrap();
+    psbt.add_input(
+        outpoint(
+            "181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911",
+            0,
+        ),
+        Sequence::MAX,
+        RequiredLockTime::default(),
+        Input::default(),
+    )
+    .unwrap();
+
+    // The updater supplies the spent output, which Alice's signed copy omits.
+    let mut updated = psbt.clone();
+    updated.inputs_mut()[0].witness_utxo = Some(TxOut {
+        value: ONE_BTC * 50,
+        script_pubkey: ScriptBuf::new_p2pkh(&public_key(ALICE_PUBKEY).pubkey_hash()),
+    });
+    let mut signed = updated.clone();
+    assert_eq!(signed.sign(&hex(ALICE_PRIVATE_KEY)).unwrap(), [0]);
+    signed.inputs_mut()[0].witness_utxo = None;
+
+    // The signature forbids modifying the transaction.
+    updated.combine(signed).unwrap();
+    assert!(!updated.inputs_modifiable());
+    assert!(!updated.outputs_modifiable());
+    updated.finalize().unwrap();
+    assert_eq!(
+        serialize_hex(&updated.extract_tx().unwrap()),
+        P2PKH_SPEND_TX
+    );
+
+    // The PSBTs of different lock times don't describe the same transaction.
+    let other = PsbtV2::new(2, Some(800_000)).unwrap();
+    assert_eq!(
+        Proto::Error::from(PsbtV2::new(2, None).unwrap().combine(other).unwrap_err()),
+        Proto::Error::Error_psbt_conflict
+    );
+}
@@ -0,0 +1,283 @@
+mod common;
+
+use bitcoin::blockdata::locktime::absolute::LockTime;
+use bitcoin::blockdata::opcodes::all::{OP_PUSHNUM_13, OP_RETURN, OP_VERIFY};
+use bitcoin::blockdata::script::PushBytesBuf;
+use bitcoin::hashes::Hash;
+use bitcoin::{ScriptBuf, Transaction, TxOut, WPubkeyHash};
+use common::hex;
+use std::str::FromStr;
+use tw_bitcoin::modules::runes::{
+    Artifact, Cenotaph, Edict, Etching, Rune, RuneId, Runestone, SpacedRune, Terms,
+};
+use tw_proto::BitcoinV2::Proto;
+
+const UNCOMMON_GOODS: RuneId = RuneId { block: 1, tx: 0 };
+
+fn tx_with_runestone(script_pubkey: ScriptBuf, outputs: usize) -> Transaction {
+    let mut output = vec![TxOut {
+        value: 0,
+        script_pubkey,
+    }];
+    for _ in 1..outputs {
+        output.push(TxOut {
+            value: 546,
+            script_pubkey: ScriptBuf::new_v0_p2wpkh(&WPubkeyHash::all_zeros()),
+        });
+    }
+    Transaction {
+        version: 2,
+        lock_time: LockTime::ZERO,
+        input: Vec::new(),
+        output,
+    }
+}
+
+fn runestone_script(payload: &[u8]) -> ScriptBuf {
+    ScriptBuf::builder()
+        .push_opcode(OP_RETURN)
+        .push_opcode(OP_PUSHNUM_13)
+        .push_slice(PushBytesBuf::try_from(payload.to_vec()).unwrap())
+        .into_script()
+}
+
+fn cenotaph(etching: Option<Rune>, mint: Option<RuneId>) -> Option<Artifact> {
+    Some(Artifact::Cenotaph(Cenotaph { etching, mint }))
+}
+
+#[test]
+fn runestone_edict_transfer() {
+    let runestone = Runestone {
+        edicts: vec![Edict {
+            id: RuneId {
+                block: 840000,
+                tx: 3,
+            },
+            amount: 100,
+            output: 1,
+        }],
+        ..Runestone::default()
+    };
+    let script = runestone.encipher();
+    assert_eq!(script.as_bytes(), hex("6a5d0700c0a233036401"));
+
+    let tx = tx_with_runestone(script, 2);
+    assert_eq!(
+        Runestone::decipher(&tx),
+        Some(Artifact::Runestone(runestone))
+    );
+}
+
+#[test]
+fn runestone_etching_mint_round_trip() {
+    let runestone = Runestone {
+        edicts: vec![
+            // The edicts are sorted by the rune ID to be delta encoded.
+            Edict {
+                id: RuneId {
+                    block: 840000,
+                    tx: 3,
+                },
+                amount: 0,
+                output: 2,
+            },
+            Edict {
+                id: UNCOMMON_GOODS,
+                amount: 1000,
+                output: 1,
+            },
+            Edict {
+                id: RuneId {
+                    block: 840000,
+                    tx: 8,
+                },
+                amount: u128::MAX,
+                output: 3,
+            },
+        ],
+        etching: Some(Etching {
+            divisibility: Some(2),
+            premine: Some(1_000_000),
+            rune: Some(Rune::from_str("UNCOMMONGOODS").unwrap()),
+            spacers: Some(1 << 7),
+            symbol: Some('⧉'),
+            terms: Some(Terms {
+                amount: Some(1),
+                cap: Some(u64::MAX as u128),
+                height: (Some(840000), Some(1050000)),
+                offset: (None, Some(1000)),
+            }),
+            turbo: true,
+        }),
+        mint: Some(UNCOMMON_GOODS),
+        pointer: Some(2),
+    };
+
+    let tx = tx_with_runestone(runestone.encipher(), 3);
+    let mut expected = runestone.clone();
+    expected.edicts.sort_by_key(|edict| edict.id);
+    assert_eq!(
+        Runestone::decipher(&tx),
+        Some(Artifact::Runestone(expected))
+    );
+}
+
+#[test]
+fn runestone_long_payload_pushes() {
+    let edicts: Vec<Edict> = (0..100)
+        .map(|tx| Edict {
+            id: RuneId { block: 840000, tx },
+            amount: u128::MAX,
+            output: 0,
+        })
+        .collect();
+    let runestone = Runestone {
+        edicts,
+        ..Runestone::default()
+    };
+
+    // The payload is split into the pushes of 520 bytes.
+    let script = runestone.encipher();
+    assert_eq!(script.instructions().count(), 2 + 5);
+
+    let tx = tx_with_runestone(script, 1);
+    assert_eq!(
+        Runestone::decipher(&tx),
+        Some(Artifact::Runestone(runestone))
+    );
+}
+
+#[test]
+fn runestone_decipher_cenotaph() {
+    // No runestone output.
+    let tx = tx_with_runestone(ScriptBuf::builder().push_opcode(OP_RETURN).into_script(), 2);
+    assert_eq!(Runestone::decipher(&tx), None);
+
+    // An opcode in the payload.
+    let script = ScriptBuf::builder()
+        .push_opcode(OP_RETURN)
+        .push_opcode(OP_PUSHNUM_13)
+        .push_opcode(OP_VERIFY)
+        .into_script();
+    let tx = tx_with_runestone(script, 2);
+    assert_eq!(Runestone::decipher(&tx), cenotaph(None, None));
+
+    // The pointer tag without a value.
+    let tx = tx_with_runestone(runestone_script(&[22]), 2);
+    assert_eq!(Runestone::decipher(&tx), cenotaph(None, None));
+
+    // An unterminated integer.
+    let tx = tx_with_runestone(runestone_script(&[22, 0x80]), 2);
+    assert_eq!(Runestone::decipher(&tx), cenotaph(None, None));
+
+    // An unknown even tag makes a cenotaph, while an odd one is ignored.
+    let tx = tx_with_runestone(runestone_script(&[24, 1]), 2);
+    assert_eq!(Runestone::decipher(&tx), cenotaph(None, None));
+    let tx = tx_with_runestone(runestone_script(&[25, 1]), 2);
+    assert_eq!(
+        Runestone::decipher(&tx),
+        Some(Artifact::Runestone(Runestone::default()))
+    );
+
+    // An edict to the output that doesn't exist.
+    let runestone = Runestone {
+        edicts: vec![Edict {
+            id: UNCOMMON_GOODS,
+            amount: 1,
+            output: 3,
+        }],
+        mint: Some(UNCOMMON_GOODS),
+        ..Runestone::default()
+    };
+    let tx = tx_with_runestone(runestone.encipher(), 2);
+    assert_eq!(
+        Runestone::decipher(&tx),
+        cenotaph(None, Some(UNCOMMON_GOODS))
+    );
+
+    // The supply overflows, the etched rune is burned.
+    let rune = Rune::from_str("AAAAAAAAAAAAA").unwrap();
+    let runestone = Runestone {
+        etching: Some(Etching {
+            premine: Some(1),
+            rune: Some(rune),
+            terms: Some(Terms {
+                amount: Some(u128::MAX),
+                cap: Some(1),
+                ..Terms::default()
+            }),
+            ..Etching::default()
+        }),
+        ..Runestone::default()
+    };
+    let tx = tx_with_runestone(runestone.encipher(), 2);
+    assert_eq!(Runestone::decipher(&tx), cenotaph(Some(rune), None));
+}
+
+#[test]
+fn rune_names() {
+    for (name, rune) in [
+        ("A", 0),
+        ("B", 1),
+        ("Z", 25),
+        ("AA", 26),
+        ("AB", 27),
+        ("UNCOMMONGOODS", 2055900680524219742),
+        ("BCGDENLQRQWDSLRUGSNLBTMFIJAV", u128::MAX),
+    ] {
+        assert_eq!(Rune::from_str(name).unwrap(), Rune(rune));
+        assert_eq!(Rune(rune).to_string(), name);
+    }
+
+    for invalid in ["", "a", "A B", "BCGDENLQRQWDSLRUGSNLBTMFIJAW"] {
+        assert_eq!(
+            Proto::Error::from(Rune::from_str(invalid).unwrap_err()),
+            Proto::Error::Error_invalid_rune
+        );
+    }
+
+    // The little-endian bytes without the trailing zeros.
+    assert_eq!(Rune(0).commitment(), Vec::<u8>::new());
+    assert_eq!(Rune(0x0102).commitment(), vec![0x02, 0x01]);
+}
+
+#[test]
+fn spaced_rune_names() {
+    let spaced = SpacedRune::from_str("UNCOMMON•GOODS").unwrap();
+    assert_eq!(
+        spaced,
+        SpacedRune {
+            rune: Rune(2055900680524219742),
+            spacers: 1 << 7,
+        }
+    );
+    assert_eq!(spaced.to_string(), "UNCOMMON•GOODS");
+    assert_eq!(SpacedRune::from_str("A.B.C").unwrap().to_string(), "A•B•C");
+
+    for invalid in ["•A", "A•", "A••B", "A•b"] {
+        assert_eq!(
+            Proto::Error::from(SpacedRune::from_str(invalid).unwrap_err()),
+            Proto::Error::Error_invalid_rune
+        );
+    }
+}
+
+#[test]
+fn rune_id_str() {
+    let id = RuneId::from_str("840000:3").unwrap();
+    assert_eq!(
+        id,
+        RuneId {
+            block: 840000,
+            tx: 3
+        }
+    );
+    assert_eq!(id.to_string(), "840000:3");
+
+    for invalid in ["840000", "840000:", ":3", "840000:3:1", "a:3"] {
+        assert_eq!(
+            Proto::Error::from(RuneId::from_str(invalid).unwrap_err()),
+            Proto::Error::Error_invalid_rune
+        );
+    }
+}
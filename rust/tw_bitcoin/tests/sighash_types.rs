@@ -0,0 +1,210 @@
+mod common;
+
+use bitcoin::blockdata::script::Instruction;
+use bitcoin::hashes::Hash;
+use bitcoin::sighash::{EcdsaSighashType, Prevouts, SighashCache, TapSighashType};
+use bitcoin::{PublicKey, ScriptBuf, Transaction, TxOut};
+use common::{hex, MINER_FEE, ONE_BTC};
+use secp256k1::{Message, Secp256k1, XOnlyPublicKey};
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+const ALICE_PRIVATE_KEY: &str = "56429688a1a6b00b90ccd22a0de0a376b6569d8684022ae92229a28478bfb657";
+const ALICE_PUBKEY: &str = "036666dd712e05a487916384bfcd5973eb53e8038eccbbf97f7eed775b87389536";
+
+fn public_key() -> PublicKey {
+    PublicKey::from_slice(&hex(ALICE_PUBKEY)).unwrap()
+}
+
+/// The P2PKH, P2WPKH and P2TR key path inputs with the given sighash types, and two outputs.
+fn signing_input(sighash_types: [UtxoProto::SighashType; 3]) -> Proto::SigningInput<'static> {
+    let variants = [
+        ProtoInputBuilder::p2pkh(hex(ALICE_PUBKEY).into()),
+        ProtoInputBuilder::p2wpkh(hex(ALICE_PUBKEY).into()),
+        ProtoInputBuilder::p2tr_key_path(Proto::mod_Input::InputTaprootKeyPath {
+            public_key: hex(ALICE_PUBKEY).into(),
+            one_prevout: false,
+        }),
+    ];
+    let inputs = variants
+        .into_iter()
+        .zip(sighash_types)
+        .enumerate()
+        .map(|(vout, (variant, sighash_type))| Proto::Input {
+            txid: vec![0x11; 32].into(),
+            vout: vout as u32,
+            value: ONE_BTC,
+            sighash_type,
+            to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder { variant }),
+            ..Default::default()
+        })
+        .collect();
+
+    let output = Proto::Output {
+        value: ONE_BTC,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(hex(ALICE_PUBKEY).into()),
+            }),
+        }),
+    };
+    let mut second = output.clone();
+    second.value = 2 * ONE_BTC - MINER_FEE;
+
+    Proto::SigningInput {
+        private_key: hex(ALICE_PRIVATE_KEY).into(),
+        inputs,
+        outputs: vec![output, second],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        disable_change_output: true,
+        ..Default::default()
+    }
+}
+
+fn prevouts() -> Vec<TxOut> {
+    let key = public_key();
+    let secp = Secp256k1::new();
+    vec![
+        TxOut {
+            value: ONE_BTC,
+            script_pubkey: ScriptBuf::new_p2pkh(&key.pubkey_hash()),
+        },
+        TxOut {
+            value: ONE_BTC,
+            script_pubkey: ScriptBuf::new_v0_p2wpkh(&key.wpubkey_hash().unwrap()),
+        },
+        TxOut {
+            value: ONE_BTC,
+            script_pubkey: ScriptBuf::new_v1_p2tr(&secp, key.inner.x_only_public_key().0, None),
+        },
+    ]
+}
+
+fn verify_ecdsa(sighash: &[u8], signature: &[u8], sighash_type: EcdsaSighashType) {
+    let signature = bitcoin::ecdsa::Signature::from_slice(signature).unwrap();
+    assert_eq!(signature.hash_ty, sighash_type);
+    Secp256k1::new()
+        .verify_ecdsa(
+            &Message::from_slice(sighash).unwrap(),
+            &signature.sig,
+            &public_key().inner,
+        )
+        .unwrap();
+}
+
+/// Verifies the signatures of the inputs against the sighashes of the expected types.
+fn verify_tx(tx: &Transaction, ecdsa: [EcdsaSighashType; 2], taproot: TapSighashType) {
+    let prevouts = prevouts();
+    let mut cache = SighashCache::new(tx);
+
+    // `<signature> <public key>`.
+    let pushes: Vec<Vec<u8>> = tx.input[0]
+        .script_sig
+        .instructions()
+        .map(|instruction| match instruction.unwrap() {
+            Instruction::PushBytes(push) => push.as_bytes().to_vec(),
+            Instruction::Op(op) => panic!("unexpected {op:?}"),
+        })
+        .collect();
+    let sighash = cache
+        .legacy_signature_hash(0, &prevouts[0].script_pubkey, ecdsa[0].to_u32())
+        .unwrap();
+    verify_ecdsa(sighash.as_byte_array(), &pushes[0], ecdsa[0]);
+
+    let script_code = prevouts[1].script_pubkey.p2wpkh_script_code().unwrap();
+    let sighash = cache
+        .segwit_signature_hash(1, &script_code, ONE_BTC, ecdsa[1])
+        .unwrap();
+    verify_ecdsa(
+        sighash.as_byte_array(),
+        tx.input[1].witness.nth(0).unwrap(),
+        ecdsa[1],
+    );
+
+    let signature =
+        bitcoin::taproot::Signature::from_slice(tx.input[2].witness.nth(0).unwrap()).unwrap();
+    assert_eq!(signature.hash_ty, taproot);
+    let sighash = cache
+        .taproot_key_spend_signature_hash(2, &Prevouts::All(&prevouts), taproot)
+        .unwrap();
+    let output_key =
+        XOnlyPublicKey::from_slice(&prevouts[2].script_pubkey.as_bytes()[2..]).unwrap();
+    Secp256k1::new()
+        .verify_schnorr(
+            &signature.sig,
+            &Message::from_slice(sighash.as_byte_array()).unwrap(),
+            &output_key,
+        )
+        .unwrap();
+}
+
+fn sign(sighash_types: [UtxoProto::SighashType; 3]) -> Transaction {
+    let signed = BitcoinEntry.sign(&TestCoinContext::default(), signing_input(sighash_types));
+    assert_eq!(signed.error, Proto::Error::OK);
+    bitcoin::consensus::deserialize(&signed.encoded).unwrap()
+}
+
+#[test]
+fn sighash_types_default_and_all() {
+    // The default is `SIGHASH_ALL` for ECDSA, but `SIGHASH_DEFAULT` for Taproot.
+    let tx = sign([UtxoProto::SighashType::UseDefault; 3]);
+    assert_eq!(tx.input[2].witness.nth(0).unwrap().len(), 64);
+    verify_tx(&tx, [EcdsaSighashType::All; 2], TapSighashType::Default);
+
+    // The explicit `SIGHASH_ALL` is appended to the Taproot signature.
+    let tx = sign([UtxoProto::SighashType::All; 3]);
+    assert_eq!(tx.input[2].witness.nth(0).unwrap().len(), 65);
+    assert_eq!(tx.input[2].witness.nth(0).unwrap()[64], 0x01);
+    verify_tx(&tx, [EcdsaSighashType::All; 2], TapSighashType::All);
+}
+
+#[test]
+fn sighash_types_none_single_anyone_can_pay() {
+    let tx = sign([
+        UtxoProto::SighashType::None_pb,
+        UtxoProto::SighashType::SinglePlusAnyoneCanPay,
+        UtxoProto::SighashType::AllPlusAnyoneCanPay,
+    ]);
+    verify_tx(
+        &tx,
+        [
+            EcdsaSighashType::None,
+            EcdsaSighashType::SinglePlusAnyoneCanPay,
+        ],
+        TapSighashType::AllPlusAnyoneCanPay,
+    );
+
+    let tx = sign([
+        UtxoProto::SighashType::NonePlusAnyoneCanPay,
+        UtxoProto::SighashType::Single,
+        UtxoProto::SighashType::NonePlusAnyoneCanPay,
+    ]);
+    let signature = tx.input[2].witness.nth(0).unwrap();
+    assert_eq!(signature[64], 0x82);
+    verify_tx(
+        &tx,
+        [
+            EcdsaSighashType::NonePlusAnyoneCanPay,
+            EcdsaSighashType::Single,
+        ],
+        TapSighashType::NonePlusAnyoneCanPay,
+    );
+
+    // The third input has no output of the same index.
+    for sighash_type in [
+        UtxoProto::SighashType::Single,
+        UtxoProto::SighashType::SinglePlusAnyoneCanPay,
+    ] {
+        let input = signing_input([
+            UtxoProto::SighashType::All,
+            UtxoProto::SighashType::All,
+            sighash_type,
+        ]);
+        let signed = BitcoinEntry.sign(&TestCoinContext::default(), input);
+        assert_eq!(signed.error, Proto::Error::Error_utxo_invalid_sighash_type);
+    }
+}
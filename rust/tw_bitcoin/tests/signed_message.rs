@@ -0,0 +1,159 @@
+mod common;
+
+use bitcoin::{Address, Network, PublicKey};
+use common::hex;
+use std::str::FromStr;
+use tw_bitcoin::modules::signed_message::{
+    message_hash, recover_address, sign_message, verify_message,
+};
+use tw_proto::BitcoinV2::Proto;
+
+const PRIVATE_KEY: &str = "afeefca74d9a325cf1d6b6911d61a65c32afa8e02bd5e78e2e4ac2910bab45f5";
+const PUBLIC_KEY: &str = "0399c6f51ad6f98c9c583f8e92bb7758ab2ca9a04110c0a1126ec43e5453d196c1";
+const P2PKH_ADDRESS: &str = "19cAJn4Ms8jodBBGtroBNNpCZiHAWGAq7X";
+const P2PKH_UNCOMPRESSED_ADDRESS: &str = "1E4T9JZ3mq6cdgiRJEWzHqDXb9t322fE6d";
+
+fn address(address: &str) -> Address {
+    Address::from_str(address)
+        .unwrap()
+        .require_network(Network::Bitcoin)
+        .unwrap()
+}
+
+fn public_key() -> PublicKey {
+    PublicKey::from_slice(&hex(PUBLIC_KEY)).unwrap()
+}
+
+#[test]
+fn signed_message_hash() {
+    assert_eq!(
+        message_hash(b"Hello, world!"),
+        hex("02d6c0643e40b0db549cbbd7eb47dcab71a59d7017199ebde6b272f28fbbf95f").as_slice()
+    );
+    assert_eq!(
+        message_hash(b"test signature"),
+        hex("8e81cc5bca9862d8b7f22be1f7cb762b49121cf4e1611c27906a041f9a9eb21f").as_slice()
+    );
+}
+
+#[test]
+fn signed_message_verify_vectors() {
+    for (address_str, message, signature) in [
+        (
+            "1B8Qea79tsxmn4dTiKKRVvsJpHwL2fMQnr",
+            "test signature",
+            "H+3L5IbSVcejp4S2VwLXCxLEMQAWDvKbE8lQyq0ocdvyM1aoEudkzN/S/qLI3vnNOFY6V13BXWSFrPr3OjGa5Dk=",
+        ),
+        (
+            "1HZwkjkeaoZfTSaJxDw6aKkxp45agDiEzN",
+            "This is an example of a signed message.",
+            "G39Qf0XrZHICWbz3r5gOkcgTRw3vM4leGjiR3refr/K1OezcKmmXaLn4zc8ji2rjbBUIMrIhH/jc5Z2qEEz7qVk=",
+        ),
+        (
+            "1H8X4u6CVZRTLLNbUQTKAnc5vCkqWMpwfF",
+            "compressed key",
+            "IKUI9v2xbHogJe8HKXI2M5KEhMKaW6fjNxtyEy27Mf+3/e1ht4jZoc85e4F8stPsxt4Xcg8Yr42S28O6L/Qx9fE=",
+        ),
+        (
+            P2PKH_ADDRESS,
+            "another text",
+            "H7vrF2C+TlFiHyegAw3QLv6SK0myuEEXUOgfx0+Qio1YVDuSa6p/OHpoQVlUt3F8QJdbdZN9M1h/fYEAnEz16V0=",
+        ),
+        (
+            P2PKH_UNCOMPRESSED_ADDRESS,
+            "test signature",
+            "HLH5K7JQLaRGaKGXXH5mYM6FIIy9IWyY4JUPI+PHYY4WaupxUbg+zy0bhBCrDuehy9x4WidwjkRR1GSLnWvOXBo=",
+        ),
+    ] {
+        let address = address(address_str);
+        verify_message(&address, message.as_bytes(), signature).unwrap();
+        assert_eq!(
+            recover_address(message.as_bytes(), signature, Network::Bitcoin).unwrap(),
+            address
+        );
+        assert_eq!(
+            Proto::Error::from(verify_message(&address, b"Hello", signature).unwrap_err()),
+            Proto::Error::Error_invalid_message_signature
+        );
+    }
+
+    // The signature is of another address, or is too short.
+    let signature =
+        "H+3L5IbSVcejp4S2VwLXCxLEMQAWDvKbE8lQyq0ocdvyM1aoEudkzN/S/qLI3vnNOFY6V13BXWSFrPr3OjGa5Dk=";
+    let short =
+        "H+3L5IbSVcejp4S2VwLXCxLEMQAWDvKbE8lQyq0ocdvyM1aoEudkzN/S/qLI3vnNOFY6V13BXWSFrPr3OjGa";
+    for (address_str, signature) in [(P2PKH_ADDRESS, signature), (P2PKH_ADDRESS, short)] {
+        assert_eq!(
+            Proto::Error::from(
+                verify_message(&address(address_str), b"test signature", signature).unwrap_err()
+            ),
+            Proto::Error::Error_invalid_message_signature
+        );
+    }
+}
+
+#[test]
+fn signed_message_sign() {
+    let private_key = hex(PRIVATE_KEY);
+    let key = public_key();
+    assert_eq!(
+        Address::p2pkh(&key, Network::Bitcoin),
+        address(P2PKH_ADDRESS)
+    );
+
+    // The header specifies the address type, while the signature is the same.
+    for (address, expected) in [
+        (
+            address(P2PKH_ADDRESS),
+            "ILH5K7JQLaRGaKGXXH5mYM6FIIy9IWyY4JUPI+PHYY4WaupxUbg+zy0bhBCrDuehy9x4WidwjkRR1GSLnWvOXBo=",
+        ),
+        (
+            address(P2PKH_UNCOMPRESSED_ADDRESS),
+            "HLH5K7JQLaRGaKGXXH5mYM6FIIy9IWyY4JUPI+PHYY4WaupxUbg+zy0bhBCrDuehy9x4WidwjkRR1GSLnWvOXBo=",
+        ),
+        (
+            Address::p2shwpkh(&key, Network::Bitcoin).unwrap(),
+            "JLH5K7JQLaRGaKGXXH5mYM6FIIy9IWyY4JUPI+PHYY4WaupxUbg+zy0bhBCrDuehy9x4WidwjkRR1GSLnWvOXBo=",
+        ),
+        (
+            Address::p2wpkh(&key, Network::Bitcoin).unwrap(),
+            "KLH5K7JQLaRGaKGXXH5mYM6FIIy9IWyY4JUPI+PHYY4WaupxUbg+zy0bhBCrDuehy9x4WidwjkRR1GSLnWvOXBo=",
+        ),
+    ] {
+        let signature = sign_message(&private_key, &address, b"test signature").unwrap();
+        assert_eq!(signature, expected, "{address}");
+        verify_message(&address, b"test signature", &signature).unwrap();
+        assert_eq!(
+            recover_address(b"test signature", &signature, Network::Bitcoin).unwrap(),
+            address
+        );
+    }
+
+    // The P2PKH header is accepted for the segwit addresses of the compressed public key.
+    let p2pkh_signature =
+        "ILH5K7JQLaRGaKGXXH5mYM6FIIy9IWyY4JUPI+PHYY4WaupxUbg+zy0bhBCrDuehy9x4WidwjkRR1GSLnWvOXBo=";
+    let p2wpkh = Address::p2wpkh(&key, Network::Bitcoin).unwrap();
+    verify_message(&p2wpkh, b"test signature", p2pkh_signature).unwrap();
+    for (address, signature) in [
+        (&p2wpkh, "HLH5K7JQLaRGaKGXXH5mYM6FIIy9IWyY4JUPI+PHYY4WaupxUbg+zy0bhBCrDuehy9x4WidwjkRR1GSLnWvOXBo="),
+        (&address(P2PKH_UNCOMPRESSED_ADDRESS), p2pkh_signature),
+    ] {
+        assert_eq!(
+            Proto::Error::from(verify_message(address, b"test signature", signature).unwrap_err()),
+            Proto::Error::Error_invalid_message_signature
+        );
+    }
+
+    // The key doesn't control the address.
+    assert_eq!(
+        Proto::Error::from(
+            sign_message(
+                &private_key,
+                &address("1B8Qea79tsxmn4dTiKKRVvsJpHwL2fMQnr"),
+                b"test signature"
+            )
+            .unwrap_err()
+        ),
+        Proto::Error::Error_unsupported_message_address
+    );
+}
@@ -0,0 +1,236 @@
+mod common;
+
+use bitcoin::hashes::Hash;
+use bitcoin::key::TapTweak;
+use bitcoin::{Network, OutPoint, PublicKey, ScriptBuf, Transaction, TxOut, Txid};
+use common::{hex, ONE_BTC};
+use secp256k1::{KeyPair, Secp256k1, SecretKey, XOnlyPublicKey};
+use std::str::FromStr;
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::silent_payments::{
+    derive_outputs, scan, spend_private_key, InputKey, SilentPaymentAddress,
+};
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+const SCAN_PRIVATE_KEY: &str = "59ad1b2fc74287ded1bba7af67765d23ad4a49f1ae51902cc2ed3f8ebee96cfa";
+const SPEND_PRIVATE_KEY: &str = "f64a33ff88c38111769d86b2679168f7cdabcaa7c9c20cbb51aa0a3a506a8717";
+const SCAN_PUBKEY: &str = "02cd3b2e33f0c7234c54d817f1f533dd1e8b87f284f90a80948b4dbe9314b22a7c";
+const SPEND_PUBKEY: &str = "0342e2a8b5d5ee4262426b1ec4540d1f33866e4ba6329c1956632d96bc661073d7";
+const ADDRESS: &str = "sp1qqtxnkt3n7rrjxnz5mqtlrafnm50ghpljsnus4qy53dxmayc5kg48cq6zu25tt40wgf3yy6c7c32q68ensehyhf3jnsv4vcedj67xvyrn6u6c65yc";
+
+/// The P2WPKH input and the P2TR key path input of the sender.
+const P2WPKH_PRIVATE_KEY: &str = "1ea06586b18e8fce1b923eff26fd8252f617f0efd4e49820e8e9bee0614e5792";
+const P2WPKH_PUBKEY: &str = "0347782ff78d78035da91db62bf53fef3d9c5fa600d678b5bc1e92226640045183";
+const P2TR_PRIVATE_KEY: &str = "124d8541ff3d7a18b95432bdfbecd86816b86c8265bff44ef629765afb25f06b";
+const P2TR_PUBKEY: &str = "03b5a08f1be6dc3136a7a172a84d6eddb48e4200fc6756051fa1cf3d4ba2aa7cc4";
+
+/// The first and the second outputs to the address.
+const OUTPUT_KEY_0: &str = "c71e3ea27fa29ed776a3ba215f30a769d991de3b03641d7d8a37f7bac05b0580";
+const OUTPUT_KEY_1: &str = "5cbbe9771d4382c1ba0086c016112bff3409702f24a5b59f4df0c6f955efb85f";
+const TWEAK_0: &str = "30a1212c6ce9436b1559a42b0af25ac54c409bc23104a323b4fc93a2a7b84d36";
+
+fn secret_key(key: &str) -> SecretKey {
+    SecretKey::from_slice(&hex(key)).unwrap()
+}
+
+fn xonly(key: &str) -> XOnlyPublicKey {
+    XOnlyPublicKey::from_slice(&hex(key)).unwrap()
+}
+
+fn outpoints() -> Vec<OutPoint> {
+    vec![
+        OutPoint {
+            txid: Txid::from_str(&"aa".repeat(32)).unwrap(),
+            vout: 1,
+        },
+        OutPoint {
+            txid: Txid::from_str(&"11".repeat(32)).unwrap(),
+            vout: 0,
+        },
+    ]
+}
+
+#[test]
+fn silent_payments_address() {
+    // The address of BIP352.
+    let bip_address = "sp1qqgste7k9hx0qftg6qmwlkqtwuy6cycyavzmzj85c6qdfhjdpdjtdgqjuexzk6murw56suy3e0rd2cgqvycxttddwsvgxe2usfpxumr70xc9pkqwv";
+    let address = SilentPaymentAddress::from_str(bip_address).unwrap();
+    assert_eq!(
+        address.scan_key().serialize().as_slice(),
+        hex("0220bcfac5b99e04ad1a06ddfb016ee13582609d60b6291e98d01a9bc9a16c96d4")
+    );
+    assert_eq!(
+        address.spend_key().serialize().as_slice(),
+        hex("025cc9856d6f8375350e123978daac200c260cb5b5ae83106cab90484dcd8fcf36")
+    );
+    assert_eq!(address.network(), Network::Bitcoin);
+    assert_eq!(address.to_string(), bip_address);
+
+    let secp = Secp256k1::new();
+    let scan_key = secret_key(SCAN_PRIVATE_KEY).public_key(&secp);
+    let spend_key = secret_key(SPEND_PRIVATE_KEY).public_key(&secp);
+    assert_eq!(scan_key.serialize().as_slice(), hex(SCAN_PUBKEY));
+    assert_eq!(spend_key.serialize().as_slice(), hex(SPEND_PUBKEY));
+
+    let address = SilentPaymentAddress::new(scan_key, spend_key, Network::Bitcoin);
+    assert_eq!(address.to_string(), ADDRESS);
+    assert_eq!(SilentPaymentAddress::from_str(ADDRESS).unwrap(), address);
+
+    let testnet = SilentPaymentAddress::new(scan_key, spend_key, Network::Testnet);
+    assert_eq!(testnet.to_string(), "tsp1qqtxnkt3n7rrjxnz5mqtlrafnm50ghpljsnus4qy53dxmayc5kg48cq6zu25tt40wgf3yy6c7c32q68ensehyhf3jnsv4vcedj67xvyrn6uwh6pwr");
+    assert!(testnet.is_valid_for_network(Network::Signet));
+    assert!(!testnet.is_valid_for_network(Network::Regtest));
+    assert!(!address.is_valid_for_network(Network::Testnet));
+
+    // The future versions may append data.
+    let v1 = "sp1pqtxnkt3n7rrjxnz5mqtlrafnm50ghpljsnus4qy53dxmayc5kg48cq6zu25tt40wgf3yy6c7c32q68ensehyhf3jnsv4vcedj67xvyrn6uqq8dxm2r";
+    assert_eq!(SilentPaymentAddress::from_str(v1).unwrap(), address);
+
+    // A segwit address, an invalid checksum, a truncated payload, or the version 31.
+    for invalid in [
+        "bc1q9vza2e8x573nczrlzms0wvx3gsqjx7vavgkx0l",
+        &ADDRESS.replace("c65yc", "c65yd"),
+        "sp1qqtxnkt3n7rrjxnz5mqtlrafnm50ghpljsnus4qy53dxmayc5kg48ckgmr2u",
+        "sp1lqtxnkt3n7rrjxnz5mqtlrafnm50ghpljsnus4qy53dxmayc5kg48cq6zu25tt40wgf3yy6c7c32q68ensehyhf3jnsv4vcedj67xvyrn6u2hle6e",
+    ] {
+        assert_eq!(
+            Proto::Error::from(SilentPaymentAddress::from_str(invalid).unwrap_err()),
+            Proto::Error::Error_invalid_silent_payment,
+            "{invalid}"
+        );
+    }
+}
+
+#[test]
+fn silent_payments_derive_and_scan() {
+    let secp = Secp256k1::new();
+    let address = SilentPaymentAddress::from_str(ADDRESS).unwrap();
+
+    // The P2TR key path is signed by the BIP86 tweaked key.
+    let tweaked = KeyPair::from_secret_key(&secp, &secret_key(P2TR_PRIVATE_KEY))
+        .tap_tweak(&secp, None)
+        .to_inner();
+    let keys = [
+        InputKey::Ecdsa(secret_key(P2WPKH_PRIVATE_KEY)),
+        InputKey::Taproot(SecretKey::from_keypair(&tweaked)),
+    ];
+
+    // The outputs of the same address get different keys.
+    let output_keys = derive_outputs(&outpoints(), &keys, &[address, address]).unwrap();
+    assert_eq!(output_keys, vec![xonly(OUTPUT_KEY_0), xonly(OUTPUT_KEY_1)]);
+
+    // The order of the inputs doesn't matter.
+    let mut reversed = outpoints();
+    reversed.reverse();
+    let mut reversed_keys = keys;
+    reversed_keys.reverse();
+    assert_eq!(
+        derive_outputs(&reversed, &reversed_keys, &[address]).unwrap(),
+        vec![xonly(OUTPUT_KEY_0)]
+    );
+
+    // The recipient spends the output with the tweaked spend key.
+    let private_key = spend_private_key(
+        &secret_key(SPEND_PRIVATE_KEY),
+        &hex(TWEAK_0).try_into().unwrap(),
+    )
+    .unwrap();
+    assert_eq!(private_key.x_only_public_key(&secp).0, xonly(OUTPUT_KEY_0));
+
+    assert_eq!(
+        Proto::Error::from(derive_outputs(&outpoints(), &[], &[address]).unwrap_err()),
+        Proto::Error::Error_invalid_silent_payment
+    );
+}
+
+fn signing_input(input_selector: UtxoProto::InputSelector) -> Proto::SigningInput<'static> {
+    let outpoints = outpoints();
+    let p2wpkh = Proto::Input {
+        txid: outpoints[0].txid.to_byte_array().to_vec().into(),
+        vout: outpoints[0].vout,
+        value: ONE_BTC,
+        private_key: hex(P2WPKH_PRIVATE_KEY).into(),
+        sighash_type: UtxoProto::SighashType::All,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2wpkh(hex(P2WPKH_PUBKEY).into()),
+        }),
+        ..Default::default()
+    };
+    let p2tr = Proto::Input {
+        txid: outpoints[1].txid.to_byte_array().to_vec().into(),
+        vout: outpoints[1].vout,
+        value: ONE_BTC,
+        private_key: hex(P2TR_PRIVATE_KEY).into(),
+        sighash_type: UtxoProto::SighashType::UseDefault,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2tr_key_path(Proto::mod_Input::InputTaprootKeyPath {
+                public_key: hex(P2TR_PUBKEY).into(),
+                one_prevout: false,
+            }),
+        }),
+        ..Default::default()
+    };
+
+    let out1 = Proto::Output {
+        value: ONE_BTC,
+        to_recipient: ProtoOutputRecipient::from_address(ADDRESS.into()),
+    };
+
+    Proto::SigningInput {
+        inputs: vec![p2wpkh, p2tr],
+        outputs: vec![out1],
+        input_selector,
+        disable_change_output: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn silent_payments_sign() {
+    let coin = TestCoinContext::default();
+    let secp = Secp256k1::new();
+
+    let signed = BitcoinEntry.sign(&coin, signing_input(UtxoProto::InputSelector::UseAll));
+    assert_eq!(signed.error, Proto::Error::OK);
+    let tx: Transaction = bitcoin::consensus::deserialize(&signed.encoded).unwrap();
+    assert_eq!(tx.output.len(), 1);
+    assert_eq!(
+        tx.output[0].script_pubkey.as_bytes(),
+        hex(&format!("5120{OUTPUT_KEY_0}"))
+    );
+
+    // The recipient finds the output in the signed transaction.
+    let p2wpkh = PublicKey::from_slice(&hex(P2WPKH_PUBKEY)).unwrap();
+    let p2tr = PublicKey::from_slice(&hex(P2TR_PUBKEY)).unwrap();
+    let prevouts = [
+        TxOut {
+            value: ONE_BTC,
+            script_pubkey: ScriptBuf::new_v0_p2wpkh(&p2wpkh.wpubkey_hash().unwrap()),
+        },
+        TxOut {
+            value: ONE_BTC,
+            script_pubkey: ScriptBuf::new_v1_p2tr(&secp, p2tr.inner.x_only_public_key().0, None),
+        },
+    ];
+    let spend_key = secret_key(SPEND_PRIVATE_KEY).public_key(&secp);
+    let found = scan(&secret_key(SCAN_PRIVATE_KEY), &spend_key, &tx, &prevouts).unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].vout, 0);
+    assert_eq!(found[0].output_key, xonly(OUTPUT_KEY_0));
+    assert_eq!(found[0].tweak.as_slice(), hex(TWEAK_0));
+
+    // Another recipient finds nothing.
+    let other = secret_key(P2WPKH_PRIVATE_KEY);
+    assert!(scan(&other, &spend_key, &tx, &prevouts).unwrap().is_empty());
+
+    // The outputs commit to all the inputs, so they can't be selected.
+    let signed = BitcoinEntry.sign(
+        &coin,
+        signing_input(UtxoProto::InputSelector::SelectInOrder),
+    );
+    assert_eq!(signed.error, Proto::Error::Error_invalid_silent_payment);
+}
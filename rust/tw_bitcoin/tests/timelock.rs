@@ -0,0 +1,191 @@
+mod common;
+
+use bitcoin::blockdata::script::Instruction;
+use bitcoin::hashes::Hash;
+use bitcoin::sighash::{EcdsaSighashType, SighashCache};
+use bitcoin::{PublicKey, Transaction};
+use common::{hex, MINER_FEE, ONE_BTC};
+use secp256k1::{ecdsa, Message, Secp256k1};
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::transactions::{Timelock, TimelockScript};
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+const ALICE_PRIVATE_KEY: &str = "56429688a1a6b00b90ccd22a0de0a376b6569d8684022ae92229a28478bfb657";
+const ALICE_PUBKEY: &str = "036666dd712e05a487916384bfcd5973eb53e8038eccbbf97f7eed775b87389536";
+const INPUT_VALUE: u64 = 50 * ONE_BTC - 3 * MINER_FEE;
+
+type ProtoTimelock = Proto::mod_Input::mod_InputTimelock::OneOftimelock;
+
+fn public_key() -> PublicKey {
+    PublicKey::from_slice(&hex(ALICE_PUBKEY)).unwrap()
+}
+
+#[test]
+fn timelock_script() {
+    // `<800000> OP_CLTV OP_DROP <alice> OP_CHECKSIG`.
+    let cltv = TimelockScript::new(Timelock::Absolute(800_000), public_key()).unwrap();
+    assert_eq!(
+        cltv.script().as_bytes(),
+        hex(&format!("0300350cb17521{ALICE_PUBKEY}ac"))
+    );
+
+    // `<144> OP_CSV OP_DROP <alice> OP_CHECKSIG`.
+    let csv = TimelockScript::new(Timelock::Relative(144), public_key()).unwrap();
+    assert_eq!(
+        csv.script().as_bytes(),
+        hex(&format!("029000b27521{ALICE_PUBKEY}ac"))
+    );
+
+    // Zero, disabled, or with the reserved bits set.
+    for timelock in [
+        Timelock::Absolute(0),
+        Timelock::Relative(0),
+        Timelock::Relative(1 << 31 | 10),
+        Timelock::Relative(1 << 20),
+    ] {
+        assert_eq!(
+            Proto::Error::from(TimelockScript::new(timelock, public_key()).unwrap_err()),
+            Proto::Error::Error_invalid_timelock,
+            "{timelock:?}"
+        );
+    }
+}
+
+fn signing_input(
+    timelock: ProtoTimelock,
+    witness: bool,
+    sequence: u32,
+    lock_time: Option<UtxoProto::LockTime>,
+) -> Proto::SigningInput<'static> {
+    let txid: Vec<u8> = hex("dd9d4ca23532f5c89d016e1aacef1210ab5b9d00527c633969841daca7dd17c7")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let timelock = Proto::mod_Input::InputTimelock {
+        public_key: hex(ALICE_PUBKEY).into(),
+        timelock,
+    };
+    let variant = if witness {
+        ProtoInputBuilder::p2wsh_timelock(timelock)
+    } else {
+        ProtoInputBuilder::p2sh_timelock(timelock)
+    };
+    let tx1 = Proto::Input {
+        txid: txid.into(),
+        vout: 0,
+        sequence,
+        value: INPUT_VALUE,
+        sighash_type: UtxoProto::SighashType::All,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder { variant }),
+        ..Default::default()
+    };
+
+    let out1 = Proto::Output {
+        value: 50 * ONE_BTC - 4 * MINER_FEE,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(hex(ALICE_PUBKEY).into()),
+            }),
+        }),
+    };
+
+    Proto::SigningInput {
+        private_key: hex(ALICE_PRIVATE_KEY).into(),
+        inputs: vec![tx1],
+        outputs: vec![out1],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        disable_change_output: true,
+        lock_time,
+        ..Default::default()
+    }
+}
+
+fn verify(sighash: &[u8], signature: &[u8]) {
+    // The last byte is the sighash type.
+    let signature = ecdsa::Signature::from_der(&signature[..signature.len() - 1]).unwrap();
+    Secp256k1::new()
+        .verify_ecdsa(
+            &Message::from_slice(sighash).unwrap(),
+            &signature,
+            &public_key().inner,
+        )
+        .unwrap();
+}
+
+#[test]
+fn timelock_p2wsh_cltv_sign() {
+    let coin = TestCoinContext::default();
+    let script = TimelockScript::new(Timelock::Absolute(800_000), public_key())
+        .unwrap()
+        .script();
+
+    // The lock time and the sequence are set to enable the timelock.
+    let input = signing_input(ProtoTimelock::lock_time(800_000), true, 0, None);
+    let signed = BitcoinEntry.sign(&coin, input);
+    assert_eq!(signed.error, Proto::Error::OK);
+    let tx: Transaction = bitcoin::consensus::deserialize(&signed.encoded).unwrap();
+    assert_eq!(tx.lock_time.to_consensus_u32(), 800_000);
+    assert_eq!(tx.input[0].sequence.0, 0xffff_fffe);
+    assert!(tx.input[0].script_sig.is_empty());
+    let witness: Vec<Vec<u8>> = tx.input[0].witness.iter().map(|e| e.to_vec()).collect();
+    assert_eq!(witness.len(), 2);
+    assert_eq!(witness[1], script.to_bytes());
+    let sighash = SighashCache::new(&tx)
+        .segwit_signature_hash(0, &script, INPUT_VALUE, EcdsaSighashType::All)
+        .unwrap();
+    verify(sighash.as_byte_array(), &witness[0]);
+
+    // A lower height, or a timestamp.
+    for variant in [
+        UtxoProto::mod_LockTime::OneOfvariant::blocks(799_999),
+        UtxoProto::mod_LockTime::OneOfvariant::seconds(1_700_000_000),
+    ] {
+        let lock_time = Some(UtxoProto::LockTime { variant });
+        let input = signing_input(ProtoTimelock::lock_time(800_000), true, 0, lock_time);
+        let signed = BitcoinEntry.sign(&coin, input);
+        assert_eq!(signed.error, Proto::Error::Error_unsatisfied_timelock);
+    }
+}
+
+#[test]
+fn timelock_p2sh_csv_sign() {
+    let coin = TestCoinContext::default();
+    let script = TimelockScript::new(Timelock::Relative(144), public_key())
+        .unwrap()
+        .script();
+
+    // The sequence is set to enable the timelock.
+    let input = signing_input(ProtoTimelock::sequence(144), false, 0, None);
+    let signed = BitcoinEntry.sign(&coin, input);
+    assert_eq!(signed.error, Proto::Error::OK);
+    let tx: Transaction = bitcoin::consensus::deserialize(&signed.encoded).unwrap();
+    assert_eq!(tx.lock_time.to_consensus_u32(), 0);
+    assert_eq!(tx.input[0].sequence.0, 144);
+    assert!(tx.input[0].witness.is_empty());
+
+    // The signature and the redeem script.
+    let pushes: Vec<Vec<u8>> = tx.input[0]
+        .script_sig
+        .instructions()
+        .map(|instruction| match instruction.unwrap() {
+            Instruction::PushBytes(push) => push.as_bytes().to_vec(),
+            Instruction::Op(op) => panic!("unexpected {op:?}"),
+        })
+        .collect();
+    assert_eq!(pushes.len(), 2);
+    assert_eq!(pushes[1], script.to_bytes());
+    let sighash = SighashCache::new(&tx)
+        .legacy_signature_hash(0, &script, EcdsaSighashType::All.to_u32())
+        .unwrap();
+    verify(sighash.as_byte_array(), &pushes[0]);
+
+    // The timelock is not reached.
+    let input = signing_input(ProtoTimelock::sequence(144), false, 100, None);
+    let signed = BitcoinEntry.sign(&coin, input);
+    assert_eq!(signed.error, Proto::Error::Error_unsatisfied_timelock);
+}
@@ -0,0 +1,302 @@
+mod common;
+use common::{hex, ONE_BTC};
+
+use bitcoin::hashes::Hash;
+use bitcoin::{
+    Address, Network, OutPoint, PublicKey, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness,
+};
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::transaction_decoder::BitcoinTransactionDecoder;
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::modules::transaction_decoder::TransactionDecoder;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+const SAT_VBYTE: u64 = 10;
+
+const ALICE_PRIVATE_KEY: &str = "57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a";
+const ALICE_PUBKEY: &str = "028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f";
+const BOB_PUBKEY: &str = "025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f";
+
+fn p2wpkh_script(pubkey: &str) -> ScriptBuf {
+    let pubkey = PublicKey::from_slice(&hex(pubkey)).unwrap();
+    ScriptBuf::new_v0_p2wpkh(&pubkey.wpubkey_hash().unwrap())
+}
+
+fn p2wpkh_address(pubkey: &str) -> String {
+    Address::from_script(&p2wpkh_script(pubkey), Network::Bitcoin)
+        .unwrap()
+        .to_string()
+}
+
+fn p2wpkh(pubkey: &str) -> ProtoOutputRecipient<'static> {
+    ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+        variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+            to_address: ProtoPubkeyOrHash::pubkey(hex(pubkey).into()),
+        }),
+    })
+}
+
+/// Signs a transaction that spends one P2WPKH input of Alice to Bob, an `OP_RETURN` and the change.
+fn signed_tx() -> Proto::SigningOutput<'static> {
+    let tx1 = Proto::Input {
+        txid: vec![1; 32].into(),
+        vout: 0,
+        value: ONE_BTC,
+        sighash_type: UtxoProto::SighashType::All,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2wpkh(hex(ALICE_PUBKEY).into()),
+        }),
+        ..Default::default()
+    };
+    let signing = Proto::SigningInput {
+        private_key: hex(ALICE_PRIVATE_KEY).into(),
+        inputs: vec![tx1],
+        outputs: vec![
+            Proto::Output {
+                value: ONE_BTC / 2,
+                to_recipient: p2wpkh(BOB_PUBKEY),
+            },
+            Proto::Output {
+                value: 0,
+                to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+                    variant: ProtoOutputBuilder::op_return(hex("68656c6c6f").into()),
+                }),
+            },
+        ],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        fee_per_vb: SAT_VBYTE,
+        change_output: Some(Proto::Output {
+            value: 0,
+            to_recipient: p2wpkh(ALICE_PUBKEY),
+        }),
+        ..Default::default()
+    };
+
+    let signed = BitcoinEntry.sign(&TestCoinContext::default(), signing);
+    assert_eq!(signed.error, Proto::Error::OK);
+    signed
+}
+
+fn alice_prevout() -> TxOut {
+    TxOut {
+        value: ONE_BTC,
+        script_pubkey: p2wpkh_script(ALICE_PUBKEY),
+    }
+}
+
+#[test]
+fn decode_signed_transaction() {
+    let signed = signed_tx();
+    let decoded =
+        BitcoinTransactionDecoder::decode(&signed.encoded, &[alice_prevout()], Network::Bitcoin)
+            .unwrap();
+
+    // The weight reported by the signer is an estimate, so compare with the actual weight.
+    let tx: Transaction = bitcoin::consensus::deserialize(&signed.encoded).unwrap();
+    let weight = tx.weight().to_wu();
+
+    assert_eq!(decoded.txid, signed.txid);
+    assert_eq!(decoded.version, 2);
+    assert_eq!(decoded.weight, weight);
+    assert_eq!(decoded.vsize, (weight + 3) / 4);
+    assert_eq!(decoded.fee, signed.fee);
+    assert_eq!(
+        decoded.lock_time,
+        Some(UtxoProto::LockTime {
+            variant: UtxoProto::mod_LockTime::OneOfvariant::blocks(0),
+        })
+    );
+
+    let [input] = decoded.inputs.as_slice() else {
+        panic!("expected one input");
+    };
+    // In the internal byte order, as specified in the signing input.
+    assert_eq!(input.txid.as_ref(), [1; 32]);
+    assert_eq!(input.vout, 0);
+    assert_eq!(input.script_type, Proto::ScriptType::P2wpkh);
+    assert!(input.is_signed);
+    assert!(input.script_sig.is_empty());
+    assert_eq!(input.witness_items.len(), 2);
+    assert_eq!(input.witness_items[1].as_ref(), hex(ALICE_PUBKEY));
+    assert_eq!(input.value, ONE_BTC);
+    assert_eq!(input.address, p2wpkh_address(ALICE_PUBKEY));
+
+    let outputs = &decoded.outputs;
+    assert_eq!(outputs.len(), 3);
+    assert_eq!(outputs[0].script_type, Proto::ScriptType::P2wpkh);
+    assert_eq!(outputs[0].value, ONE_BTC / 2);
+    assert_eq!(outputs[0].address, p2wpkh_address(BOB_PUBKEY));
+    assert_eq!(outputs[1].script_type, Proto::ScriptType::OpReturn);
+    assert_eq!(outputs[1].script_pubkey.as_ref(), hex("6a0568656c6c6f"));
+    assert_eq!(outputs[1].address, "");
+    assert_eq!(outputs[2].script_type, Proto::ScriptType::P2wpkh);
+    assert_eq!(outputs[2].value, ONE_BTC / 2 - signed.fee);
+    assert_eq!(outputs[2].address, p2wpkh_address(ALICE_PUBKEY));
+}
+
+#[test]
+fn decode_without_prevouts() {
+    let signed = signed_tx();
+    let output = BitcoinEntry
+        .transaction_decoder()
+        .unwrap()
+        .decode_transaction(&TestCoinContext::default(), &signed.encoded);
+    assert_eq!(output.error, Proto::Error::OK);
+
+    let decoded = output.transaction.unwrap();
+    assert_eq!(decoded.txid, signed.txid);
+    // The fee and the spent values are unknown.
+    assert_eq!(decoded.fee, 0);
+    let input = &decoded.inputs[0];
+    assert_eq!(input.value, 0);
+    assert_eq!(input.address, "");
+    // Detected from the witness.
+    assert_eq!(input.script_type, Proto::ScriptType::P2wpkh);
+
+    // Without the witness, the input of an unsigned transaction can't be detected but from its prevout.
+    let mut tx: Transaction = bitcoin::consensus::deserialize(&signed.encoded).unwrap();
+    tx.input[0].witness = Witness::new();
+    let unsigned = bitcoin::consensus::serialize(&tx);
+
+    let decoded = BitcoinTransactionDecoder::decode(&unsigned, &[], Network::Bitcoin).unwrap();
+    assert!(!decoded.inputs[0].is_signed);
+    assert_eq!(
+        decoded.inputs[0].script_type,
+        Proto::ScriptType::UnknownScript
+    );
+    let decoded =
+        BitcoinTransactionDecoder::decode(&unsigned, &[alice_prevout()], Network::Bitcoin).unwrap();
+    assert!(!decoded.inputs[0].is_signed);
+    assert_eq!(decoded.inputs[0].script_type, Proto::ScriptType::P2wpkh);
+    assert_eq!(decoded.fee, signed.fee);
+}
+
+#[test]
+fn decode_input_script_types() {
+    let signature = vec![0x30; 71];
+    let pubkey = hex(ALICE_PUBKEY);
+    let wpubkey_hash = PublicKey::from_slice(&pubkey)
+        .unwrap()
+        .wpubkey_hash()
+        .unwrap();
+    let nested = ScriptBuf::new_v0_p2wpkh(&wpubkey_hash);
+    // The control block of a leaf at depth 1.
+    let mut control_block = vec![0xc0; 1];
+    control_block.extend_from_slice(&[2; 64]);
+
+    let txin = |script_sig: ScriptBuf, witness: Vec<Vec<u8>>| TxIn {
+        previous_output: OutPoint::null(),
+        script_sig,
+        sequence: Sequence::MAX,
+        witness: Witness::from_slice(&witness),
+    };
+    let push = |items: &[&[u8]]| {
+        items
+            .iter()
+            .fold(bitcoin::script::Builder::new(), |builder, item| {
+                builder.push_slice(<&bitcoin::script::PushBytes>::try_from(*item).unwrap())
+            })
+            .into_script()
+    };
+    let tx = Transaction {
+        version: 2,
+        lock_time: bitcoin::absolute::LockTime::from_consensus(600_000_000),
+        input: vec![
+            txin(push(&[&signature, &pubkey]), vec![]),
+            txin(push(&[&signature, &[0x51, 0xae]]), vec![]),
+            txin(
+                push(&[nested.as_bytes()]),
+                vec![signature.clone(), pubkey.clone()],
+            ),
+            txin(ScriptBuf::new(), vec![vec![1; 64]]),
+            // With an annex.
+            txin(ScriptBuf::new(), vec![vec![1; 64], vec![0x50, 1]]),
+            txin(
+                ScriptBuf::new(),
+                vec![vec![1; 64], vec![0x51], control_block],
+            ),
+            txin(
+                ScriptBuf::new(),
+                vec![vec![], signature.clone(), vec![0x51]],
+            ),
+        ],
+        output: vec![TxOut {
+            value: 1_000,
+            script_pubkey: ScriptBuf::new_p2sh(&nested.script_hash()),
+        }],
+    };
+
+    let decoded = BitcoinTransactionDecoder::decode(
+        &bitcoin::consensus::serialize(&tx),
+        &[],
+        Network::Testnet,
+    )
+    .unwrap();
+    let script_types: Vec<_> = decoded
+        .inputs
+        .iter()
+        .map(|input| input.script_type)
+        .collect();
+    assert_eq!(
+        script_types,
+        vec![
+            Proto::ScriptType::P2pkh,
+            Proto::ScriptType::P2sh,
+            Proto::ScriptType::P2shP2wpkh,
+            Proto::ScriptType::P2trKeyPath,
+            Proto::ScriptType::P2trKeyPath,
+            Proto::ScriptType::P2trScriptPath,
+            Proto::ScriptType::P2wsh,
+        ]
+    );
+    assert_eq!(
+        decoded.lock_time,
+        Some(UtxoProto::LockTime {
+            variant: UtxoProto::mod_LockTime::OneOfvariant::seconds(600_000_000),
+        })
+    );
+    assert_eq!(decoded.outputs[0].script_type, Proto::ScriptType::P2sh);
+    assert!(decoded.outputs[0].address.starts_with('2'));
+    assert_eq!(
+        decoded.inputs[0].txid.as_ref(),
+        bitcoin::Txid::all_zeros().as_byte_array()
+    );
+}
+
+#[test]
+fn decode_invalid() {
+    let output = BitcoinEntry
+        .transaction_decoder()
+        .unwrap()
+        .decode_transaction(&TestCoinContext::default(), &hex("0200000001"));
+    assert_eq!(output.error, Proto::Error::Error_invalid_raw_transaction);
+    assert!(output.transaction.is_none());
+
+    let signed = signed_tx();
+    // One prevout for each input.
+    let error = BitcoinTransactionDecoder::decode(
+        &signed.encoded,
+        &[alice_prevout(), alice_prevout()],
+        Network::Bitcoin,
+    )
+    .unwrap_err();
+    assert_eq!(
+        Proto::Error::from(error),
+        Proto::Error::Error_invalid_prevouts
+    );
+
+    // The prevout doesn't fund the outputs.
+    let prevout = TxOut {
+        value: ONE_BTC / 2,
+        ..alice_prevout()
+    };
+    let error = BitcoinTransactionDecoder::decode(&signed.encoded, &[prevout], Network::Bitcoin)
+        .unwrap_err();
+    assert_eq!(
+        Proto::Error::from(error),
+        Proto::Error::Error_invalid_prevouts
+    );
+}
@@ -0,0 +1,299 @@
+mod common;
+
+use bitcoin::hashes::Hash;
+use bitcoin::sighash::EcdsaSighashType;
+use bitcoin::{Network, OutPoint, PublicKey, ScriptBuf, Sequence, TxIn, TxOut, Txid, Witness};
+use common::hex;
+use secp256k1::{Secp256k1, SecretKey};
+use std::str::FromStr;
+use tw_bitcoin::modules::zcash::{
+    TAddress, ZcashTransaction, ZcashTxVersion, BLOSSOM_BRANCH_ID, NU5_BRANCH_ID, NU6_BRANCH_ID,
+    SAPLING_BRANCH_ID,
+};
+use tw_encoding::hex::ToHex;
+use tw_proto::BitcoinV2::Proto;
+
+const PRIVATE_KEY: &str = "4646464646464646464646464646464646464646464646464646464646464646";
+const RECIPIENT: &str = "t1biXYN8wJahR76SqZTe1LBzTLf3JAsmT93";
+
+fn input(txid: Txid, vout: u32, sequence: u32) -> TxIn {
+    TxIn {
+        previous_output: OutPoint { txid, vout },
+        script_sig: ScriptBuf::new(),
+        sequence: Sequence(sequence),
+        witness: Witness::new(),
+    }
+}
+
+fn output(value: u64, script_pubkey: &str) -> TxOut {
+    TxOut {
+        value,
+        script_pubkey: ScriptBuf::from_bytes(hex(script_pubkey)),
+    }
+}
+
+fn own_script_pubkey() -> ScriptBuf {
+    let secret = SecretKey::from_slice(&hex(PRIVATE_KEY)).unwrap();
+    let public_key = PublicKey::new(secret.public_key(&Secp256k1::new()));
+    TAddress::p2pkh(&public_key, Network::Bitcoin).script_pubkey()
+}
+
+#[test]
+fn zcash_t_address() {
+    let secp = Secp256k1::new();
+    for (private_key, expected) in [
+        (
+            "2d8f68944bdbfbc0769542fba8fc2d2a3de67393334471624364c7006da2aa54",
+            "t1Wg9uPPAfwhBWeRjtDPa5ZHNzyBx9rJVKY",
+        ),
+        (
+            "be88df1d0bf30a923cb39c3bb953178baaf3726e8d3ce81e7c8462e046e0d835",
+            "t1gaySCXCYtXE3ygP38YuWtVZczsEbdjG49",
+        ),
+    ] {
+        let secret = SecretKey::from_slice(&hex(private_key)).unwrap();
+        let public_key = PublicKey::new(secret.public_key(&secp));
+        let address = TAddress::p2pkh(&public_key, Network::Bitcoin);
+        assert_eq!(address.to_string(), expected);
+        assert_eq!(TAddress::from_str(expected).unwrap(), address);
+    }
+
+    for address in [
+        "t1RygJmrLdNGgi98gUgEJDTVaELTAYWoMBy",
+        "t3RD6RFKhWSotNbPEY4Vw7Ku9QCfKkzrbBL",
+        "tmSzhz1ET9EKaYzFJPWX8gnfjwjP5pTxZwr",
+        "t2SRyAR26tXTnZHfpa3jPqeyYmxCbAZxUnh",
+    ] {
+        assert_eq!(TAddress::from_str(address).unwrap().to_string(), address);
+    }
+    let address = TAddress::from_str("t3RD6RFKhWSotNbPEY4Vw7Ku9QCfKkzrbBL").unwrap();
+    assert!(address.script_pubkey().is_p2sh());
+
+    for invalid in [
+        // Invalid base58.
+        "t1RygJmrLdNGgi98+UgEJDTVaELTAYWoMBy",
+        // Too short.
+        "t1RygJmrLdNGgi98gUgEJDTVaELTAYW",
+        // Bad checksum.
+        "t1RygJmrLdNGgi98gUgEJDTVaELTAYWoMBz",
+        // A Bitcoin address.
+        "1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2",
+    ] {
+        let error = TAddress::from_str(invalid).unwrap_err();
+        assert_eq!(
+            Proto::Error::from(error),
+            Proto::Error::Error_bad_address_recipient
+        );
+    }
+}
+
+#[test]
+fn zcash_sapling_signature_hash() {
+    // The test vector 3 of ZIP-243.
+    let mut tx = ZcashTransaction::new(SAPLING_BRANCH_ID);
+    assert_eq!(tx.version, ZcashTxVersion::V4);
+    tx.lock_time = 0x0004_b029;
+    tx.expiry_height = 0x0004_b048;
+    let mut txin = input(
+        Txid::from_slice(&hex(
+            "a8c685478265f4c14dada651969c45a65e1aeb8cd6791f2f5bb6a1d9952104d9",
+        ))
+        .unwrap(),
+        1,
+        0xffff_fffe,
+    );
+    txin.script_sig = ScriptBuf::from_bytes(hex("483045022100a61e5d557568c2ddc1d9b03a7173c6ce7c996c4daecab007ac8f34bee01e6b9702204d38fdc0bcf2728a69fde78462a10fb45a9baa27873e6a5fc45fb5c76764202a01210365ffea3efa3908918a8b8627724af852fc9b86d7375b103ab0543cf418bcaa7f"));
+    tx.inputs.push(txin);
+    tx.outputs.push(output(
+        0x0262_5a00,
+        "76a9148132712c3ff19f3a151234616777420a6d7ef22688ac",
+    ));
+    tx.outputs.push(output(
+        0x0098_958b,
+        "76a9145453e4698f02a38abdaa521cd1ff2dee6fac187188ac",
+    ));
+
+    assert_eq!(
+        tx.encode().to_hex(),
+        "0400008085202f8901a8c685478265f4c14dada651969c45a65e1aeb8cd6791f2f5bb6a1d9952104d9010000006b483045022100a61e5d557568c2ddc1d9b03a7173c6ce7c996c4daecab007ac8f34bee01e6b9702204d38fdc0bcf2728a69fde78462a10fb45a9baa27873e6a5fc45fb5c76764202a01210365ffea3efa3908918a8b8627724af852fc9b86d7375b103ab0543cf418bcaa7ffeffffff02005a6202000000001976a9148132712c3ff19f3a151234616777420a6d7ef22688ac8b959800000000001976a9145453e4698f02a38abdaa521cd1ff2dee6fac187188ac29b0040048b004000000000000000000000000"
+    );
+
+    let prevouts = [output(
+        0x02fa_f080,
+        "76a914507173527b4c3318a2aecd793bf1cfed705950cf88ac",
+    )];
+    let sighash = tx
+        .signature_hash(0, &prevouts, EcdsaSighashType::All)
+        .unwrap();
+    assert_eq!(
+        sighash.to_hex(),
+        "f3148f80dfab5e573d5edfe7a850f5fd39234f80b5429d3a57edcc11e34c585b"
+    );
+
+    // The sighash commits to the consensus branch ID.
+    tx.consensus_branch_id = BLOSSOM_BRANCH_ID;
+    let sighash = tx
+        .signature_hash(0, &prevouts, EcdsaSighashType::All)
+        .unwrap();
+    assert_ne!(
+        sighash.to_hex(),
+        "f3148f80dfab5e573d5edfe7a850f5fd39234f80b5429d3a57edcc11e34c585b"
+    );
+}
+
+#[test]
+fn zcash_blossom_sign() {
+    // https://explorer.zcha.in/transactions/387939ff8eb07dd264376eeef2e126394ab139802b1d80e92b21c1a2ae54fe92
+    let mut tx = ZcashTransaction::new(BLOSSOM_BRANCH_ID);
+    tx.inputs.push(input(
+        Txid::from_str("2381825cd9069a200944996257e25b9403ba3e296bbc1dd98b01019cc7028cde").unwrap(),
+        0,
+        u32::MAX,
+    ));
+    tx.outputs.push(TxOut {
+        value: 17_615,
+        script_pubkey: TAddress::from_str(RECIPIENT).unwrap().script_pubkey(),
+    });
+    let prevouts = [TxOut {
+        value: 27_615,
+        script_pubkey: own_script_pubkey(),
+    }];
+
+    tx.sign_p2pkh(&hex(PRIVATE_KEY), &prevouts, EcdsaSighashType::All)
+        .unwrap();
+    assert_eq!(
+        tx.encode().to_hex(),
+        "0400008085202f8901de8c02c79c01018bd91dbc6b293eba03945be25762994409209a06d95c828123000000006b483045022100e6e5071811c08d0c2e81cb8682ee36a8c6b645f5c08747acd3e828de2a4d8a9602200b13b36a838c7e8af81f2d6e7e694ede28833a480cfbaaa68a47187655298a7f0121024bc2a31265153f07e70e0bab08724e6b85e217f8cd628ceb62974247bb493382ffffffff01cf440000000000001976a914c3bacb129d85288a3deb5890ca9b711f7f71392688ac00000000000000000000000000000000000000"
+    );
+}
+
+fn nu5_transaction() -> (ZcashTransaction, Vec<TxOut>) {
+    let mut tx = ZcashTransaction::new(NU5_BRANCH_ID);
+    tx.expiry_height = 2_500_000;
+    tx.inputs
+        .push(input(Txid::from_byte_array([0x11; 32]), 0, u32::MAX));
+    tx.inputs
+        .push(input(Txid::from_byte_array([0x22; 32]), 1, 0xffff_fffd));
+    tx.outputs.push(TxOut {
+        value: 50_000,
+        script_pubkey: TAddress::from_str(RECIPIENT).unwrap().script_pubkey(),
+    });
+    tx.outputs.push(TxOut {
+        value: 24_000,
+        script_pubkey: own_script_pubkey(),
+    });
+
+    let prevouts = [40_000, 44_000]
+        .into_iter()
+        .map(|value| TxOut {
+            value,
+            script_pubkey: own_script_pubkey(),
+        })
+        .collect();
+    (tx, prevouts)
+}
+
+#[test]
+fn zcash_nu5_sign() {
+    let (mut tx, prevouts) = nu5_transaction();
+    assert_eq!(tx.version, ZcashTxVersion::V5);
+
+    for (index, sighash_type, expected) in [
+        (
+            0,
+            EcdsaSighashType::All,
+            "9c0a585b5a2f689e94eece90bb9e0ba6934187ce75f24715751af52eafbfe643",
+        ),
+        (
+            1,
+            EcdsaSighashType::SinglePlusAnyoneCanPay,
+            "9dc03fd9dd39491c0fe09ebff388d13511a753f81d563415742c50a8feb8934b",
+        ),
+        (
+            1,
+            EcdsaSighashType::None,
+            "e655965cff6d79eb66290ff8acbf5ffc581267a2aa413ce1dca3654adaf01009",
+        ),
+    ] {
+        let sighash = tx.signature_hash(index, &prevouts, sighash_type).unwrap();
+        assert_eq!(sighash.to_hex(), expected);
+    }
+
+    // The ID doesn't commit to the script signatures.
+    let txid = "f63fe5bfa7a6507d6290bc2b90832de167b2f5de5a0e542bb52e80216e51fb29";
+    assert_eq!(tx.txid().to_string(), txid);
+
+    tx.sign_p2pkh(&hex(PRIVATE_KEY), &prevouts, EcdsaSighashType::All)
+        .unwrap();
+    assert_eq!(tx.txid().to_string(), txid);
+    assert_eq!(
+        tx.encode().to_hex(),
+        "050000800a27a726b4d0d6c200000000a0252600021111111111111111111111111111111111111111111111111111111111111111000000006b483045022100c38c9d234488d593954b157df46e9d17dcedaeba5430dee9210c40471768b87e02206bb1683bd925567cfd22b3d247c682eb88a212301f6e62dee7271fa8daffb96f0121024bc2a31265153f07e70e0bab08724e6b85e217f8cd628ceb62974247bb493382ffffffff2222222222222222222222222222222222222222222222222222222222222222010000006a47304402205eab70461b2bf7dde7c9fbec9e6ad8c9ec84880581ae61914d4032ed0e6a7a9d02205b46a9de570bc3db527001e93c88f573ab0464932a41b524c4ba294a1540f72b0121024bc2a31265153f07e70e0bab08724e6b85e217f8cd628ceb62974247bb493382fdffffff0250c30000000000001976a914c3bacb129d85288a3deb5890ca9b711f7f71392688acc05d0000000000001976a914bd92088bb7e82d611a9b94fbb74a0908152b784f88ac000000"
+    );
+}
+
+#[test]
+fn zcash_future_branch_id() {
+    // An upgrade after NU5 is signed with the same sighash, but commits to its own branch ID.
+    let (nu5, prevouts) = nu5_transaction();
+    let mut nu6 = nu5.clone();
+    nu6.consensus_branch_id = NU6_BRANCH_ID;
+    assert_eq!(
+        ZcashTransaction::new(NU6_BRANCH_ID).version,
+        ZcashTxVersion::V5
+    );
+    assert_eq!(
+        ZcashTxVersion::for_branch_id(0x1234_5678),
+        ZcashTxVersion::V5
+    );
+
+    let nu5_sighash = nu5
+        .signature_hash(0, &prevouts, EcdsaSighashType::All)
+        .unwrap();
+    let nu6_sighash = nu6
+        .signature_hash(0, &prevouts, EcdsaSighashType::All)
+        .unwrap();
+    assert_ne!(nu5_sighash, nu6_sighash);
+    assert_ne!(nu5.txid(), nu6.txid());
+}
+
+#[test]
+fn zcash_invalid_signing() {
+    let (mut tx, prevouts) = nu5_transaction();
+
+    // The prevouts of all the inputs are required.
+    let error = tx
+        .signature_hash(0, &prevouts[..1], EcdsaSighashType::All)
+        .unwrap_err();
+    assert_eq!(
+        Proto::Error::from(error),
+        Proto::Error::Error_invalid_zcash_transaction
+    );
+    let error = tx
+        .signature_hash(2, &prevouts, EcdsaSighashType::All)
+        .unwrap_err();
+    assert_eq!(
+        Proto::Error::from(error),
+        Proto::Error::Error_invalid_zcash_transaction
+    );
+
+    // No input spends the outputs of the key.
+    let other_key = [0x01; 32];
+    let error = tx
+        .sign_p2pkh(&other_key, &prevouts, EcdsaSighashType::All)
+        .unwrap_err();
+    assert_eq!(
+        Proto::Error::from(error),
+        Proto::Error::Error_invalid_zcash_transaction
+    );
+
+    // The second input has no output of the same index.
+    tx.outputs.truncate(1);
+    let error = tx
+        .sign_p2pkh(&hex(PRIVATE_KEY), &prevouts, EcdsaSighashType::Single)
+        .unwrap_err();
+    assert_eq!(
+        Proto::Error::from(error),
+        Proto::Error::Error_utxo_invalid_sighash_type
+    );
+}
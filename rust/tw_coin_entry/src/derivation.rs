@@ -3,12 +3,16 @@
 // Copyright © 2017 Trust Wallet.
 
 /// Extend this enum.
-#[derive(Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 #[repr(u32)]
 pub enum Derivation {
     /// Default derivation.
     #[default]
     Default = 0,
+    /// Bitcoin P2WPKH addresses.
+    BitcoinSegwit = 2,
+    /// Bitcoin P2PKH addresses.
+    BitcoinLegacy = 3,
     Solana = 6,
 }
 
@@ -17,6 +21,8 @@ impl Derivation {
     pub fn from_raw(derivation: u32) -> Option<Derivation> {
         match derivation {
             0 => Some(Derivation::Default),
+            2 => Some(Derivation::BitcoinSegwit),
+            3 => Some(Derivation::BitcoinLegacy),
             6 => Some(Derivation::Solana),
             _ => None,
         }
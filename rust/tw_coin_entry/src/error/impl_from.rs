@@ -44,6 +44,9 @@ impl From<KeyPairError> for SigningError {
             KeyPairError::InvalidPublicKey
             | KeyPairError::InvalidSignature
             | KeyPairError::InvalidSignMessage
+            | KeyPairError::InvalidMessageLength
+            | KeyPairError::InvalidMnemonic
+            | KeyPairError::InvalidTweak
             | KeyPairError::SignatureVerifyError => {
                 TWError::new(SigningErrorType::Error_invalid_params)
             },
@@ -23,6 +23,63 @@ pub fn decode(input: &str, alphabet: &Alphabet) -> EncodingResult<Vec<u8>> {
         .map_err(EncodingError::from)
 }
 
+/// Monero uses the Bitcoin alphabet.
+const MONERO_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+/// The size of a full Monero block.
+const MONERO_BLOCK_SIZE: usize = 8;
+/// The encoded sizes of Monero blocks of `0..=8` bytes.
+const MONERO_ENCODED_BLOCK_SIZES: [usize; MONERO_BLOCK_SIZE + 1] = [0, 2, 3, 5, 6, 7, 9, 10, 11];
+
+/// Encodes the `input` in the Monero variant of base58, which encodes every 8 byte block separately
+/// into exactly 11 characters, so the encoded length depends on the input length only.
+pub fn encode_monero(input: &[u8]) -> String {
+    let mut encoded = String::new();
+    for block in input.chunks(MONERO_BLOCK_SIZE) {
+        let mut buf = [0; MONERO_BLOCK_SIZE];
+        buf[MONERO_BLOCK_SIZE - block.len()..].copy_from_slice(block);
+        let mut num = u64::from_be_bytes(buf);
+
+        let mut chars = vec![MONERO_ALPHABET[0]; MONERO_ENCODED_BLOCK_SIZES[block.len()]];
+        for c in chars.iter_mut().rev() {
+            *c = MONERO_ALPHABET[(num % 58) as usize];
+            num /= 58;
+        }
+        encoded.extend(chars.into_iter().map(char::from));
+    }
+    encoded
+}
+
+/// Decodes the `input` encoded in the Monero variant of base58.
+pub fn decode_monero(input: &str) -> EncodingResult<Vec<u8>> {
+    let full_encoded_size = MONERO_ENCODED_BLOCK_SIZES[MONERO_BLOCK_SIZE];
+
+    let mut decoded = Vec::new();
+    for block in input.as_bytes().chunks(full_encoded_size) {
+        let size = MONERO_ENCODED_BLOCK_SIZES
+            .iter()
+            .position(|encoded_size| *encoded_size == block.len())
+            .ok_or(EncodingError::InvalidInput)?;
+
+        let mut num: u64 = 0;
+        for c in block {
+            let digit = MONERO_ALPHABET
+                .iter()
+                .position(|digit| digit == c)
+                .ok_or(EncodingError::InvalidInput)?;
+            num = num
+                .checked_mul(58)
+                .and_then(|num| num.checked_add(digit as u64))
+                .ok_or(EncodingError::InvalidInput)?;
+        }
+        // The value must fit into the block.
+        if size < MONERO_BLOCK_SIZE && num >> (size * 8) != 0 {
+            return Err(EncodingError::InvalidInput);
+        }
+        decoded.extend_from_slice(&num.to_be_bytes()[MONERO_BLOCK_SIZE - size..]);
+    }
+    Ok(decoded)
+}
+
 pub mod as_base58_bitcoin {
     use super::*;
     use serde::de::Error;
@@ -70,4 +127,21 @@ mod tests {
         let result = decode(data, Alphabet::BITCOIN).unwrap();
         assert_eq!(result, expected.to_vec());
     }
+
+    #[test]
+    fn test_base58_monero() {
+        let data = (1..=11).collect::<Vec<u8>>();
+        let expected = "1An6UebxCZd1436i";
+
+        assert_eq!(encode_monero(&data), expected);
+        assert_eq!(decode_monero(expected).unwrap(), data);
+        assert_eq!(encode_monero(&[0]), "11");
+        assert_eq!(decode_monero("").unwrap(), Vec::<u8>::new());
+
+        // Invalid length of the last block.
+        decode_monero("1An6UebxCZd1").unwrap_err();
+        // The value overflows a one byte block.
+        decode_monero("zz").unwrap_err();
+        decode_monero("1An6UebxCZd14I6i").unwrap_err();
+    }
 }
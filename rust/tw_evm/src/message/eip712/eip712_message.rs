@@ -73,28 +73,45 @@ impl Eip712Message {
     }
 }
 
-impl EthMessage for Eip712Message {
-    fn hash(&self) -> MessageSigningResult<H256> {
+impl Eip712Message {
+    /// Returns the `hashStruct` of the `EIP712Domain`, i.e. the domain separator of the verifying contract.
+    pub fn domain_separator(&self) -> MessageSigningResult<H256> {
         let domain_hash = encode_data(
             &self.types,
             PropertyType::Custom(EIP712_DOMAIN.to_string()),
             &self.domain,
         )
         .context("Error encoding EIP712Domain")?;
+        Ok(H256::try_from(domain_hash.as_slice()).expect("Expected 32-byte hash"))
+    }
 
+    /// Returns the `hashStruct` of the message, or `None` if the primary type is `EIP712Domain`,
+    /// in which case the domain is signed alone.
+    pub fn message_hash(&self) -> MessageSigningResult<Option<H256>> {
+        if self.primary_type == EIP712_DOMAIN {
+            return Ok(None);
+        }
         let primary_data_hash = encode_data(
             &self.types,
             PropertyType::Custom(self.primary_type.clone()),
             &self.message,
         )
         .context("Error encoding primary type")?;
+        Ok(Some(
+            H256::try_from(primary_data_hash.as_slice()).expect("Expected 32-byte hash"),
+        ))
+    }
+}
+
+impl EthMessage for Eip712Message {
+    fn hash(&self) -> MessageSigningResult<H256> {
+        let domain_hash = self.domain_separator()?;
+        let primary_data_hash = self.message_hash()?;
 
-        let concat = [
-            PREFIX.as_slice(),
-            domain_hash.as_slice(),
-            primary_data_hash.as_slice(),
-        ]
-        .concat();
+        let mut concat = [PREFIX.as_slice(), domain_hash.as_slice()].concat();
+        if let Some(primary_data_hash) = primary_data_hash {
+            concat.extend_from_slice(primary_data_hash.as_slice());
+        }
 
         let hash_data = keccak256(&concat);
         Ok(H256::try_from(hash_data.as_slice()).expect("Expected 32-byte hash"))
@@ -321,7 +338,7 @@ mod encode_custom_type {
                         &field.property_type
                     };
                     // Seen this type before? or not a custom type - skip
-                    if !deps.contains(field_type) || custom_types.contains_key(field_type) {
+                    if !deps.contains(field_type) && custom_types.contains_key(field_type) {
                         types_stack.push(field_type);
                     }
                 }
@@ -402,6 +419,73 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_encode_recursive_type() {
+        let custom_types = r#"{
+			"Tree": [
+				{ "name": "root", "type": "Node" },
+				{ "name": "name", "type": "string" }
+			],
+			"Node": [
+				{ "name": "value", "type": "uint256" },
+				{ "name": "children", "type": "Node[]" }
+			]
+		}"#;
+
+        let custom_types: CustomTypes = serde_json::from_str(custom_types).unwrap();
+        assert_eq!(
+            encode_custom_type::encode_type(&custom_types, "Tree").unwrap(),
+            "Tree(Node root,string name)Node(uint256 value,Node[] children)"
+        );
+        assert_eq!(
+            encode_custom_type::encode_type(&custom_types, "Node").unwrap(),
+            "Node(uint256 value,Node[] children)"
+        );
+
+        let tree = serde_json::json!({
+            "root": {
+                "value": 1,
+                "children": [
+                    { "value": 2, "children": [] },
+                    { "value": 3, "children": [{ "value": 4, "children": [] }] }
+                ]
+            },
+            "name": "Tree"
+        });
+        let hash = encode_data(
+            &custom_types,
+            PropertyType::Custom("Tree".to_string()),
+            &tree,
+        )
+        .unwrap();
+        assert_eq!(
+            hash.to_hex(),
+            "f85793fd71aba2716adc4fdcc2d23ae022df823364cd7be7e769613f64f4255c"
+        );
+    }
+
+    #[test]
+    fn test_hash_domain_only() {
+        let message = r#"{
+			"types": {
+				"EIP712Domain": [
+					{ "name": "name", "type": "string" },
+					{ "name": "chainId", "type": "uint256" }
+				]
+			},
+			"domain": { "name": "Ether Mail", "chainId": 1 },
+			"primaryType": "EIP712Domain",
+			"message": {}
+		}"#;
+
+        let message = Eip712Message::new(message).unwrap();
+        assert_eq!(message.message_hash().unwrap(), None);
+        assert_eq!(
+            message.hash().unwrap().to_hex(),
+            "39e325d6ec0ba0f1a0c70e23b32f92398d853e0be0961cb07c6d47affcf9cf2d"
+        );
+    }
+
     #[test]
     fn test_encode_type_hash() {
         let custom_types = r#"{
@@ -4,12 +4,14 @@
 
 use crate::evm_context::EvmContext;
 use crate::modules::tx_builder::TxBuilder;
+use crate::transaction::UnsignedTransactionBox;
 use std::borrow::Cow;
 use std::marker::PhantomData;
 use tw_coin_entry::error::prelude::*;
 use tw_coin_entry::signing_output_error;
+use tw_hash::H256;
 use tw_keypair::ecdsa::secp256k1;
-use tw_keypair::traits::SigningKeyTrait;
+use tw_keypair::traits::{AsyncSigner, SigningKeyTrait};
 use tw_number::U256;
 use tw_proto::Ethereum::Proto;
 
@@ -26,12 +28,24 @@ impl<Context: EvmContext> Signer<Context> {
             .unwrap_or_else(|e| signing_output_error!(Proto::SigningOutput, e))
     }
 
+    /// Signs the transaction with the given external `signer` instead of `input.private_key`,
+    /// e.g. an HSM, a remote KMS or a hardware wallet.
+    pub async fn sign_proto_with_signer<S>(
+        input: Proto::SigningInput<'_>,
+        signer: &S,
+    ) -> Proto::SigningOutput<'static>
+    where
+        S: AsyncSigner<SigningMessage = H256, Signature = secp256k1::Signature>,
+    {
+        Self::sign_proto_with_signer_impl(input, signer)
+            .await
+            .unwrap_or_else(|e| signing_output_error!(Proto::SigningOutput, e))
+    }
+
     fn sign_proto_impl(
         input: Proto::SigningInput<'_>,
     ) -> SigningResult<Proto::SigningOutput<'static>> {
-        let chain_id = U256::from_big_endian_slice(&input.chain_id)
-            .into_tw()
-            .context("Invalid chain ID")?;
+        let chain_id = Self::chain_id(&input)?;
         let private_key = secp256k1::PrivateKey::try_from(input.private_key.as_ref())?;
 
         let unsigned = TxBuilder::<Context>::tx_from_proto(&input)?;
@@ -39,6 +53,38 @@ impl<Context: EvmContext> Signer<Context> {
         let pre_hash = unsigned.pre_hash(chain_id);
         let signature = private_key.sign(pre_hash)?;
 
+        Self::signing_output(unsigned, signature, chain_id, pre_hash)
+    }
+
+    async fn sign_proto_with_signer_impl<S>(
+        input: Proto::SigningInput<'_>,
+        signer: &S,
+    ) -> SigningResult<Proto::SigningOutput<'static>>
+    where
+        S: AsyncSigner<SigningMessage = H256, Signature = secp256k1::Signature>,
+    {
+        let chain_id = Self::chain_id(&input)?;
+
+        let unsigned = TxBuilder::<Context>::tx_from_proto(&input)?;
+
+        let pre_hash = unsigned.pre_hash(chain_id);
+        let signature = signer.sign(pre_hash).await?;
+
+        Self::signing_output(unsigned, signature, chain_id, pre_hash)
+    }
+
+    fn chain_id(input: &Proto::SigningInput<'_>) -> SigningResult<U256> {
+        U256::from_big_endian_slice(&input.chain_id)
+            .into_tw()
+            .context("Invalid chain ID")
+    }
+
+    fn signing_output(
+        unsigned: Box<dyn UnsignedTransactionBox>,
+        signature: secp256k1::Signature,
+        chain_id: U256,
+        pre_hash: H256,
+    ) -> SigningResult<Proto::SigningOutput<'static>> {
         let signed = unsigned.try_into_signed(signature, chain_id)?;
 
         let eth_signature = signed.signature();
@@ -54,6 +100,7 @@ impl<Context: EvmContext> Signer<Context> {
             s: Cow::from(eth_signature.s().to_big_endian().to_vec()),
             data: Cow::from(signed.payload()),
             pre_hash: Cow::from(pre_hash.to_vec()),
+            encoded_network: Cow::from(signed.encode_network().unwrap_or_default()),
             ..Proto::SigningOutput::default()
         })
     }
@@ -9,13 +9,22 @@ use crate::abi::prebuild::erc4337::{Erc4337SimpleAccount, ExecuteArgs};
 use crate::abi::prebuild::erc721::Erc721;
 use crate::address::{Address, EvmAddress};
 use crate::evm_context::EvmContext;
+use crate::transaction::access_list::{Access, AccessList};
 use crate::transaction::transaction_eip1559::TransactionEip1559;
+use crate::transaction::transaction_eip2930::TransactionEip2930;
+use crate::transaction::transaction_eip4844::{
+    kzg_to_versioned_hash, BlobSidecar, TransactionEip4844, BLOB_SIZE, KZG_COMMITMENT_SIZE,
+    KZG_PROOF_SIZE, VERSIONED_HASH_VERSION_KZG,
+};
 use crate::transaction::transaction_non_typed::TransactionNonTyped;
 use crate::transaction::user_operation::UserOperation;
+use crate::transaction::user_operation_v0_7::UserOperationV0_7;
 use crate::transaction::UnsignedTransactionBox;
+use std::borrow::Cow;
 use std::marker::PhantomData;
 use std::str::FromStr;
 use tw_coin_entry::error::prelude::*;
+use tw_hash::H256;
 use tw_memory::Data;
 use tw_number::U256;
 use tw_proto::Common::Proto::SigningError as CommonError;
@@ -146,8 +155,7 @@ impl<Context: EvmContext> TxBuilder<Context> {
                 let payload = Erc4337SimpleAccount::encode_execute_batch(calls)
                     .map_err(abi_to_signing_error)?;
 
-                return Self::user_operation_from_proto(input, payload)
-                    .map(UserOperation::into_boxed);
+                return Self::user_operation_from_proto(input, payload);
             },
             Tx::None => {
                 return SigningError::err(SigningErrorType::Error_invalid_params)
@@ -162,6 +170,12 @@ impl<Context: EvmContext> TxBuilder<Context> {
             TxMode::Enveloped => {
                 Self::transaction_eip1559_from_proto(input, eth_amount, payload, to)?.into_boxed()
             },
+            TxMode::AccessList => {
+                Self::transaction_eip2930_from_proto(input, eth_amount, payload, to)?.into_boxed()
+            },
+            TxMode::Blob => {
+                Self::transaction_eip4844_from_proto(input, eth_amount, payload, to)?.into_boxed()
+            },
             TxMode::UserOp => {
                 let to = to
                     .or_tw_err(SigningErrorType::Error_invalid_address)
@@ -174,7 +188,7 @@ impl<Context: EvmContext> TxBuilder<Context> {
                 })
                 .map_err(abi_to_signing_error)?;
 
-                Self::user_operation_from_proto(input, payload)?.into_boxed()
+                Self::user_operation_from_proto(input, payload)?
             },
         };
         Ok(tx)
@@ -249,6 +263,14 @@ impl<Context: EvmContext> TxBuilder<Context> {
             .into_tw()
             .context("Invalid max fee per gas")?;
 
+        // Such a transaction is rejected by the nodes, as the priority fee is a part of the max fee.
+        if max_inclusion_fee_per_gas > max_fee_per_gas {
+            return SigningError::err(CommonError::Error_invalid_params)
+                .context("Max inclusion fee per gas cannot exceed the max fee per gas");
+        }
+
+        let access_list = Self::access_list_from_proto(input)?;
+
         Ok(TransactionEip1559 {
             nonce,
             max_inclusion_fee_per_gas,
@@ -257,18 +279,206 @@ impl<Context: EvmContext> TxBuilder<Context> {
             to: to_address,
             amount: eth_amount,
             payload,
+            access_list,
+        })
+    }
+
+    #[inline]
+    fn transaction_eip2930_from_proto(
+        input: &Proto::SigningInput,
+        eth_amount: U256,
+        payload: Data,
+        to_address: Option<Address>,
+    ) -> SigningResult<TransactionEip2930> {
+        let nonce = U256::from_big_endian_slice(&input.nonce)
+            .into_tw()
+            .context("Invalid nonce")?;
+
+        let gas_price = U256::from_big_endian_slice(&input.gas_price)
+            .into_tw()
+            .context("Invalid gas price")?;
+
+        let gas_limit = U256::from_big_endian_slice(&input.gas_limit)
+            .into_tw()
+            .context("Invalid gas limit")?;
+
+        let access_list = Self::access_list_from_proto(input)?;
+
+        Ok(TransactionEip2930 {
+            nonce,
+            gas_price,
+            gas_limit,
+            to: to_address,
+            amount: eth_amount,
+            payload,
+            access_list,
         })
     }
 
+    fn transaction_eip4844_from_proto(
+        input: &Proto::SigningInput,
+        eth_amount: U256,
+        payload: Data,
+        to_address: Option<Address>,
+    ) -> SigningResult<TransactionEip4844> {
+        let to = to_address
+            .or_tw_err(SigningErrorType::Error_invalid_address)
+            .context("Blob transactions cannot create contracts")?;
+
+        // The blob transaction is paid the same way as the EIP1559 one, plus the blob gas.
+        let TransactionEip1559 {
+            nonce,
+            max_inclusion_fee_per_gas,
+            max_fee_per_gas,
+            gas_limit,
+            access_list,
+            ..
+        } = Self::transaction_eip1559_from_proto(input, eth_amount, Data::default(), None)?;
+
+        let max_fee_per_blob_gas = U256::from_big_endian_slice(&input.max_fee_per_blob_gas)
+            .into_tw()
+            .context("Invalid max fee per blob gas")?;
+
+        let sidecar = input
+            .blob_sidecar
+            .as_ref()
+            .map(Self::blob_sidecar_from_proto)
+            .transpose()?;
+
+        let mut blob_versioned_hashes = input
+            .blob_versioned_hashes
+            .iter()
+            .map(|hash| {
+                H256::try_from(hash.as_ref())
+                    .tw_err(|_| SigningErrorType::Error_invalid_params)
+                    .context("Invalid blob versioned hash")
+            })
+            .collect::<SigningResult<Vec<_>>>()?;
+
+        if let Some(ref sidecar) = sidecar {
+            let expected_hashes: Vec<_> = sidecar
+                .commitments
+                .iter()
+                .map(|commitment| kzg_to_versioned_hash(commitment))
+                .collect();
+
+            if blob_versioned_hashes.is_empty() {
+                blob_versioned_hashes = expected_hashes;
+            } else if blob_versioned_hashes != expected_hashes {
+                return SigningError::err(CommonError::Error_invalid_params)
+                    .context("Blob versioned hashes do not match the sidecar commitments");
+            }
+        }
+
+        if blob_versioned_hashes.is_empty() {
+            return SigningError::err(CommonError::Error_invalid_params)
+                .context("Blob transactions must carry at least one blob");
+        }
+        if blob_versioned_hashes
+            .iter()
+            .any(|hash| hash[0] != VERSIONED_HASH_VERSION_KZG)
+        {
+            return SigningError::err(CommonError::Error_invalid_params)
+                .context("Unsupported blob versioned hash version");
+        }
+
+        Ok(TransactionEip4844 {
+            nonce,
+            max_inclusion_fee_per_gas,
+            max_fee_per_gas,
+            gas_limit,
+            to,
+            amount: eth_amount,
+            payload,
+            access_list,
+            max_fee_per_blob_gas,
+            blob_versioned_hashes,
+            sidecar,
+        })
+    }
+
+    fn blob_sidecar_from_proto(sidecar: &Proto::BlobSidecar) -> SigningResult<BlobSidecar> {
+        if sidecar.blobs.len() != sidecar.commitments.len()
+            || sidecar.blobs.len() != sidecar.proofs.len()
+        {
+            return SigningError::err(CommonError::Error_invalid_params)
+                .context("Every blob must have exactly one commitment and one proof");
+        }
+
+        fn items_of_size(items: &[Cow<[u8]>], size: usize, name: &str) -> SigningResult<Vec<Data>> {
+            items
+                .iter()
+                .map(|item| {
+                    if item.len() != size {
+                        return SigningError::err(CommonError::Error_invalid_params)
+                            .with_context(|| format!("Invalid {name} size: {}", item.len()));
+                    }
+                    Ok(item.to_vec())
+                })
+                .collect()
+        }
+
+        Ok(BlobSidecar {
+            blobs: items_of_size(&sidecar.blobs, BLOB_SIZE, "blob")?,
+            commitments: items_of_size(
+                &sidecar.commitments,
+                KZG_COMMITMENT_SIZE,
+                "KZG commitment",
+            )?,
+            proofs: items_of_size(&sidecar.proofs, KZG_PROOF_SIZE, "KZG proof")?,
+        })
+    }
+
+    fn access_list_from_proto(input: &Proto::SigningInput) -> SigningResult<AccessList> {
+        let access_list = input
+            .access_list
+            .iter()
+            .map(|access| {
+                let address =
+                    Self::parse_address(&access.address).context("Invalid access list address")?;
+                let storage_keys = access
+                    .stored_keys
+                    .iter()
+                    .map(|key| {
+                        H256::try_from(key.as_ref())
+                            .tw_err(|_| SigningErrorType::Error_invalid_params)
+                            .context("Invalid access list storage key")
+                    })
+                    .collect::<SigningResult<Vec<_>>>()?;
+                Ok(Access {
+                    address,
+                    storage_keys,
+                })
+            })
+            .collect::<SigningResult<Vec<_>>>()?;
+        Ok(AccessList(access_list))
+    }
+
     fn user_operation_from_proto(
         input: &Proto::SigningInput,
         erc4337_payload: Data,
-    ) -> SigningResult<UserOperation> {
-        let Some(ref user_op) = input.user_operation else {
-            return SigningError::err(CommonError::Error_invalid_params)
-                .context("No user operation specified");
-        };
+    ) -> SigningResult<Box<dyn UnsignedTransactionBox>> {
+        match (&input.user_operation, &input.user_operation_v0_7) {
+            (Some(user_op), None) => {
+                Self::user_operation_v0_6_from_proto(input, user_op, erc4337_payload)
+                    .map(UserOperation::into_boxed)
+            },
+            (None, Some(user_op)) => {
+                Self::user_operation_v0_7_from_proto(input, user_op, erc4337_payload)
+                    .map(UserOperationV0_7::into_boxed)
+            },
+            (Some(_), Some(_)) => SigningError::err(CommonError::Error_invalid_params)
+                .context("Either v0.6 or v0.7 user operation must be specified, not both"),
+            (None, None) => SigningError::err(CommonError::Error_invalid_params)
+                .context("No user operation specified"),
+        }
+    }
 
+    fn user_operation_v0_6_from_proto(
+        input: &Proto::SigningInput,
+        user_op: &Proto::UserOperation,
+        erc4337_payload: Data,
+    ) -> SigningResult<UserOperation> {
         let nonce = U256::from_big_endian_slice(&input.nonce)
             .into_tw()
             .context("Invalid nonce")?;
@@ -315,6 +525,75 @@ impl<Context: EvmContext> TxBuilder<Context> {
         })
     }
 
+    fn user_operation_v0_7_from_proto(
+        input: &Proto::SigningInput,
+        user_op: &Proto::UserOperationV0_7,
+        erc4337_payload: Data,
+    ) -> SigningResult<UserOperationV0_7> {
+        let nonce = U256::from_big_endian_slice(&input.nonce)
+            .into_tw()
+            .context("Invalid nonce")?;
+
+        // The entry point v0.7 packs the gas limits and fees as `uint128` pairs.
+        let call_gas_limit =
+            Self::parse_u128(&input.gas_limit).context("Invalid call gas limit")?;
+
+        let max_inclusion_fee_per_gas = Self::parse_u128(&input.max_inclusion_fee_per_gas)
+            .context("Invalid max inclusion fee per gas")?;
+
+        let max_fee_per_gas =
+            Self::parse_u128(&input.max_fee_per_gas).context("Invalid max fee per gas")?;
+
+        let entry_point =
+            Self::parse_address(user_op.entry_point.as_ref()).context("Invalid entry point")?;
+
+        let sender = Self::parse_address(user_op.sender.as_ref())
+            .context("Invalid User Operation sender")?;
+
+        let factory = Self::parse_address_optional(user_op.factory.as_ref())
+            .context("Invalid User Operation factory")?;
+
+        let verification_gas_limit = Self::parse_u128(&user_op.verification_gas_limit)
+            .context("Invalid verification gas limit")?;
+
+        let pre_verification_gas = U256::from_big_endian_slice(&user_op.pre_verification_gas)
+            .into_tw()
+            .context("Invalid pre-verification gas")?;
+
+        let paymaster = Self::parse_address_optional(user_op.paymaster.as_ref())
+            .context("Invalid paymaster")?;
+
+        let paymaster_verification_gas_limit =
+            Self::parse_u128(&user_op.paymaster_verification_gas_limit)
+                .context("Invalid paymaster verification gas limit")?;
+
+        let paymaster_post_op_gas_limit = Self::parse_u128(&user_op.paymaster_post_op_gas_limit)
+            .context("Invalid paymaster post-op gas limit")?;
+
+        Ok(UserOperationV0_7 {
+            nonce,
+            entry_point,
+            sender,
+            factory,
+            factory_data: user_op.factory_data.to_vec(),
+            call_gas_limit,
+            verification_gas_limit,
+            pre_verification_gas,
+            max_fee_per_gas,
+            max_inclusion_fee_per_gas,
+            paymaster,
+            paymaster_verification_gas_limit,
+            paymaster_post_op_gas_limit,
+            paymaster_data: user_op.paymaster_data.to_vec(),
+            payload: erc4337_payload,
+        })
+    }
+
+    fn parse_u128(data: &[u8]) -> SigningResult<u128> {
+        let value = U256::from_big_endian_slice(data)?;
+        Ok(u128::try_from(value)?)
+    }
+
     #[inline]
     fn parse_address(addr: &str) -> SigningResult<Address> {
         Context::Address::from_str(addr)
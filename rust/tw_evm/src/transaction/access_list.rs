@@ -0,0 +1,71 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::address::Address;
+use crate::rlp::buffer::RlpBuffer;
+use crate::rlp::list::RlpList;
+use crate::rlp::RlpEncode;
+use tw_hash::H256;
+
+/// The storage keys of a contract that a transaction accesses.
+pub struct Access {
+    pub address: Address,
+    pub storage_keys: Vec<H256>,
+}
+
+/// EIP2930 access list. The listed addresses and storage keys are charged in advance,
+/// and then are accessed by the transaction at a discount.
+#[derive(Default)]
+pub struct AccessList(pub Vec<Access>);
+
+impl<'a> RlpEncode for &'a AccessList {
+    fn rlp_append(&self, buf: &mut RlpBuffer) {
+        // `[[address, [storage_key, ...]], ...]`
+        let mut list = RlpList::new();
+        for access in self.0.iter() {
+            let mut storage_keys = RlpList::new();
+            for key in access.storage_keys.iter() {
+                storage_keys.append(key.as_slice());
+            }
+
+            let mut item = RlpList::new();
+            item.append(access.address)
+                .append_raw_encoded(storage_keys.finish().as_slice());
+            list.append_raw_encoded(item.finish().as_slice());
+        }
+        buf.append_raw_encoded(list.finish().as_slice());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evm_context::StandardEvmContext;
+    use crate::modules::rlp_encoder::RlpEncoder;
+    use tw_encoding::hex;
+
+    #[test]
+    fn test_encode_access_list() {
+        let access_list = AccessList(vec![
+            Access {
+                address: Address::from("0xde0b295669a9fd93d5f28d9ec85e40f4cb697bae"),
+                storage_keys: vec![
+                    H256::from("0000000000000000000000000000000000000000000000000000000000000003"),
+                    H256::from("0000000000000000000000000000000000000000000000000000000000000007"),
+                ],
+            },
+            Access {
+                address: Address::from("0xbb9bc244d798123fde783fcc1c72d3bb8c189413"),
+                storage_keys: Vec::new(),
+            },
+        ]);
+
+        let actual = RlpEncoder::<StandardEvmContext>::encode(&access_list);
+        let expected = "f872f85994de0b295669a9fd93d5f28d9ec85e40f4cb697baef842a00000000000000000000000000000000000000000000000000000000000000003a00000000000000000000000000000000000000000000000000000000000000007d694bb9bc244d798123fde783fcc1c72d3bb8c189413c0";
+        assert_eq!(hex::encode(actual, false), expected);
+
+        let empty = RlpEncoder::<StandardEvmContext>::encode(&AccessList::default());
+        assert_eq!(hex::encode(empty, false), "c0");
+    }
+}
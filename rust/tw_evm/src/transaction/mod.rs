@@ -7,7 +7,10 @@
 //!  -- simple ETH transfer
 //!  -- others with payload, function call, e.g. ERC20 transfer
 //! - Typed transactions (enveloped, EIP2718), with specific type and transaction payload
-//! - User operations (EIP4337)
+//!  -- EIP2930 transactions with an access list
+//!  -- EIP1559 transactions with the dynamic fee
+//!  -- EIP4844 transactions that carry blobs
+//! - User operations (EIP4337), of the entry point v0.6 and v0.7 layouts
 
 use crate::transaction::signature::EthSignature;
 use tw_coin_entry::error::prelude::*;
@@ -16,10 +19,14 @@ use tw_keypair::ecdsa::secp256k1;
 use tw_memory::Data;
 use tw_number::U256;
 
+pub mod access_list;
 pub mod signature;
 pub mod transaction_eip1559;
+pub mod transaction_eip2930;
+pub mod transaction_eip4844;
 pub mod transaction_non_typed;
 pub mod user_operation;
+pub mod user_operation_v0_7;
 
 pub trait TransactionCommon {
     fn payload(&self) -> Data;
@@ -47,6 +54,12 @@ pub trait SignedTransaction: TransactionCommon {
 
     fn encode(&self) -> Data;
 
+    /// Returns the serialization that is broadcast instead of [`SignedTransaction::encode`], if it differs,
+    /// e.g. of a blob transaction along with its blobs.
+    fn encode_network(&self) -> Option<Data> {
+        None
+    }
+
     fn signature(&self) -> &Self::Signature;
 }
 
@@ -94,6 +107,8 @@ where
 pub trait SignedTransactionBox: TransactionCommon {
     fn encode(&self) -> Data;
 
+    fn encode_network(&self) -> Option<Data>;
+
     fn signature(&self) -> &dyn EthSignature;
 }
 
@@ -105,6 +120,10 @@ where
         <Self as SignedTransaction>::encode(self)
     }
 
+    fn encode_network(&self) -> Option<Data> {
+        <Self as SignedTransaction>::encode_network(self)
+    }
+
     fn signature(&self) -> &dyn EthSignature {
         <Self as SignedTransaction>::signature(self)
     }
@@ -4,6 +4,7 @@
 
 use crate::address::Address;
 use crate::rlp::list::RlpList;
+use crate::transaction::access_list::AccessList;
 use crate::transaction::signature::{EthSignature, Signature};
 use crate::transaction::{SignedTransaction, TransactionCommon, UnsignedTransaction};
 use tw_coin_entry::error::prelude::*;
@@ -22,6 +23,7 @@ pub struct TransactionEip1559 {
     pub to: Option<Address>,
     pub amount: U256,
     pub payload: Data,
+    pub access_list: AccessList,
 }
 
 impl TransactionCommon for TransactionEip1559 {
@@ -94,8 +96,7 @@ fn encode_transaction(
         .append(tx.to)
         .append(tx.amount)
         .append(tx.payload.as_slice())
-        // empty `access_list`.
-        .append_empty_list();
+        .append(&tx.access_list);
 
     if let Some(signature) = signature {
         list.append(signature.v());
@@ -126,6 +127,7 @@ mod tests {
             to: Some(Address::from("0x6b175474e89094c44da98b954eedeac495271d0f")),
             amount: U256::zero(),
             payload: hex::decode("a9059cbb0000000000000000000000005322b34c88ed0691971bf52a7047448f0f4efc840000000000000000000000000000000000000000000000000001ee0c29f50cb1").unwrap(),
+            access_list: AccessList::default(),
         };
         let chain_id = U256::from(10u64);
         let actual = tx.encode(chain_id);
@@ -0,0 +1,142 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::address::Address;
+use crate::rlp::list::RlpList;
+use crate::transaction::access_list::AccessList;
+use crate::transaction::signature::{EthSignature, Signature};
+use crate::transaction::{SignedTransaction, TransactionCommon, UnsignedTransaction};
+use tw_coin_entry::error::prelude::*;
+use tw_keypair::ecdsa::secp256k1;
+use tw_memory::Data;
+use tw_number::U256;
+
+const EIP2930_TX_TYPE: u8 = 0x01;
+
+/// EIP2930 transaction, with the gas price of a legacy transaction and an access list.
+pub struct TransactionEip2930 {
+    pub nonce: U256,
+    pub gas_price: U256,
+    pub gas_limit: U256,
+    pub to: Option<Address>,
+    pub amount: U256,
+    pub payload: Data,
+    pub access_list: AccessList,
+}
+
+impl TransactionCommon for TransactionEip2930 {
+    #[inline]
+    fn payload(&self) -> Data {
+        self.payload.clone()
+    }
+}
+
+impl UnsignedTransaction for TransactionEip2930 {
+    type SignedTransaction = SignedTransactionEip2930;
+
+    #[inline]
+    fn encode(&self, chain_id: U256) -> Data {
+        encode_transaction(self, chain_id, None)
+    }
+
+    #[inline]
+    fn try_into_signed(
+        self,
+        signature: secp256k1::Signature,
+        chain_id: U256,
+    ) -> SigningResult<Self::SignedTransaction> {
+        Ok(SignedTransactionEip2930 {
+            unsigned: self,
+            signature: Signature::new(signature),
+            chain_id,
+        })
+    }
+}
+
+pub struct SignedTransactionEip2930 {
+    unsigned: TransactionEip2930,
+    signature: Signature,
+    chain_id: U256,
+}
+
+impl TransactionCommon for SignedTransactionEip2930 {
+    #[inline]
+    fn payload(&self) -> Data {
+        self.unsigned.payload.clone()
+    }
+}
+
+impl SignedTransaction for SignedTransactionEip2930 {
+    type Signature = Signature;
+
+    #[inline]
+    fn encode(&self) -> Data {
+        encode_transaction(&self.unsigned, self.chain_id, Some(&self.signature))
+    }
+
+    #[inline]
+    fn signature(&self) -> &Self::Signature {
+        &self.signature
+    }
+}
+
+fn encode_transaction(
+    tx: &TransactionEip2930,
+    chain_id: U256,
+    signature: Option<&Signature>,
+) -> Data {
+    let mut list = RlpList::new();
+    list.append(chain_id)
+        .append(tx.nonce)
+        .append(tx.gas_price)
+        .append(tx.gas_limit)
+        .append(tx.to)
+        .append(tx.amount)
+        .append(tx.payload.as_slice())
+        .append(&tx.access_list);
+
+    if let Some(signature) = signature {
+        list.append(signature.v());
+        list.append(signature.r());
+        list.append(signature.s());
+    }
+
+    let tx_encoded = list.finish();
+
+    let mut envelope = Vec::with_capacity(tx_encoded.len() + 1);
+    envelope.push(EIP2930_TX_TYPE);
+    envelope.extend_from_slice(tx_encoded.as_slice());
+    envelope
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::access_list::Access;
+    use tw_encoding::hex;
+    use tw_hash::H256;
+
+    #[test]
+    fn test_encode_transaction_eip2930() {
+        let tx = TransactionEip2930 {
+            nonce: U256::from(6u64),
+            gas_price: U256::from(3_000_000_000u64),
+            gas_limit: U256::from(30_000u32),
+            to: Some(Address::from("0x6b175474e89094c44da98b954eedeac495271d0f")),
+            amount: U256::zero(),
+            payload: hex::decode("a9059cbb0000000000000000000000005322b34c88ed0691971bf52a7047448f0f4efc840000000000000000000000000000000000000000000000000001ee0c29f50cb1").unwrap(),
+            access_list: AccessList(vec![Access {
+                address: Address::from("0x6b175474e89094c44da98b954eedeac495271d0f"),
+                storage_keys: vec![H256::from(
+                    "0000000000000000000000000000000000000000000000000000000000000002",
+                )],
+            }]),
+        };
+        let chain_id = U256::from(10u64);
+        let actual = tx.encode(chain_id);
+
+        let expected = "01f8a00a0684b2d05e00827530946b175474e89094c44da98b954eedeac495271d0f80b844a9059cbb0000000000000000000000005322b34c88ed0691971bf52a7047448f0f4efc840000000000000000000000000000000000000000000000000001ee0c29f50cb1f838f7946b175474e89094c44da98b954eedeac495271d0fe1a00000000000000000000000000000000000000000000000000000000000000002";
+        assert_eq!(hex::encode(actual, false), expected);
+    }
+}
@@ -0,0 +1,221 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::address::Address;
+use crate::rlp::list::RlpList;
+use crate::transaction::access_list::AccessList;
+use crate::transaction::signature::{EthSignature, Signature};
+use crate::transaction::{SignedTransaction, TransactionCommon, UnsignedTransaction};
+use tw_coin_entry::error::prelude::*;
+use tw_hash::sha2::sha256;
+use tw_hash::H256;
+use tw_keypair::ecdsa::secp256k1;
+use tw_memory::Data;
+use tw_number::U256;
+
+const EIP4844_TX_TYPE: u8 = 0x03;
+/// cbindgen:ignore
+pub const BLOB_SIZE: usize = 131_072;
+/// cbindgen:ignore
+pub const KZG_COMMITMENT_SIZE: usize = 48;
+/// cbindgen:ignore
+pub const KZG_PROOF_SIZE: usize = 48;
+/// cbindgen:ignore
+pub const VERSIONED_HASH_VERSION_KZG: u8 = 0x01;
+
+/// The blobs with their KZG commitments and proofs, which are sent along with the transaction,
+/// while the transaction itself commits to the versioned hashes of the commitments only.
+pub struct BlobSidecar {
+    pub blobs: Vec<Data>,
+    pub commitments: Vec<Data>,
+    pub proofs: Vec<Data>,
+}
+
+/// EIP4844 transaction that carries blobs, with the fee of an EIP1559 transaction and the blob fee.
+pub struct TransactionEip4844 {
+    pub nonce: U256,
+    pub max_inclusion_fee_per_gas: U256,
+    pub max_fee_per_gas: U256,
+    pub gas_limit: U256,
+    /// Blob transactions cannot create contracts.
+    pub to: Address,
+    pub amount: U256,
+    pub payload: Data,
+    pub access_list: AccessList,
+    pub max_fee_per_blob_gas: U256,
+    pub blob_versioned_hashes: Vec<H256>,
+    /// Required for the network serialization only.
+    pub sidecar: Option<BlobSidecar>,
+}
+
+impl TransactionCommon for TransactionEip4844 {
+    #[inline]
+    fn payload(&self) -> Data {
+        self.payload.clone()
+    }
+}
+
+impl UnsignedTransaction for TransactionEip4844 {
+    type SignedTransaction = SignedTransactionEip4844;
+
+    #[inline]
+    fn encode(&self, chain_id: U256) -> Data {
+        envelope(&encode_transaction(self, chain_id, None))
+    }
+
+    #[inline]
+    fn try_into_signed(
+        self,
+        signature: secp256k1::Signature,
+        chain_id: U256,
+    ) -> SigningResult<Self::SignedTransaction> {
+        Ok(SignedTransactionEip4844 {
+            unsigned: self,
+            signature: Signature::new(signature),
+            chain_id,
+        })
+    }
+}
+
+pub struct SignedTransactionEip4844 {
+    unsigned: TransactionEip4844,
+    signature: Signature,
+    chain_id: U256,
+}
+
+impl TransactionCommon for SignedTransactionEip4844 {
+    #[inline]
+    fn payload(&self) -> Data {
+        self.unsigned.payload.clone()
+    }
+}
+
+impl SignedTransaction for SignedTransactionEip4844 {
+    type Signature = Signature;
+
+    #[inline]
+    fn encode(&self) -> Data {
+        envelope(&encode_transaction(
+            &self.unsigned,
+            self.chain_id,
+            Some(&self.signature),
+        ))
+    }
+
+    /// Returns `0x03 || rlp([tx_payload_body, blobs, commitments, proofs])`.
+    fn encode_network(&self) -> Option<Data> {
+        let sidecar = self.unsigned.sidecar.as_ref()?;
+        let tx_payload_body =
+            encode_transaction(&self.unsigned, self.chain_id, Some(&self.signature));
+
+        let mut list = RlpList::new();
+        list.append_raw_encoded(tx_payload_body.as_slice())
+            .append_raw_encoded(encode_items(&sidecar.blobs).as_slice())
+            .append_raw_encoded(encode_items(&sidecar.commitments).as_slice())
+            .append_raw_encoded(encode_items(&sidecar.proofs).as_slice());
+        Some(envelope(&list.finish()))
+    }
+
+    #[inline]
+    fn signature(&self) -> &Self::Signature {
+        &self.signature
+    }
+}
+
+/// Returns the versioned hash of a KZG commitment, i.e. its SHA256 hash with the first byte replaced by the version.
+pub fn kzg_to_versioned_hash(commitment: &[u8]) -> H256 {
+    let mut hash = H256::try_from(sha256(commitment).as_slice()).expect("sha256 returns 32 bytes");
+    hash[0] = VERSIONED_HASH_VERSION_KZG;
+    hash
+}
+
+/// Returns `rlp([chain_id, nonce, max_priority_fee_per_gas, max_fee_per_gas, gas_limit, to, value, data,
+/// access_list, max_fee_per_blob_gas, blob_versioned_hashes, y_parity, r, s])` without the transaction type.
+fn encode_transaction(
+    tx: &TransactionEip4844,
+    chain_id: U256,
+    signature: Option<&Signature>,
+) -> Data {
+    let mut blob_versioned_hashes = RlpList::new();
+    for hash in tx.blob_versioned_hashes.iter() {
+        blob_versioned_hashes.append(hash.as_slice());
+    }
+
+    let mut list = RlpList::new();
+    list.append(chain_id)
+        .append(tx.nonce)
+        .append(tx.max_inclusion_fee_per_gas)
+        .append(tx.max_fee_per_gas)
+        .append(tx.gas_limit)
+        .append(tx.to)
+        .append(tx.amount)
+        .append(tx.payload.as_slice())
+        .append(&tx.access_list)
+        .append(tx.max_fee_per_blob_gas)
+        .append_raw_encoded(blob_versioned_hashes.finish().as_slice());
+
+    if let Some(signature) = signature {
+        list.append(signature.v());
+        list.append(signature.r());
+        list.append(signature.s());
+    }
+
+    list.finish()
+}
+
+fn encode_items(items: &[Data]) -> Data {
+    let mut list = RlpList::new();
+    for item in items.iter() {
+        list.append(item.as_slice());
+    }
+    list.finish()
+}
+
+fn envelope(tx_encoded: &[u8]) -> Data {
+    let mut envelope = Vec::with_capacity(tx_encoded.len() + 1);
+    envelope.push(EIP4844_TX_TYPE);
+    envelope.extend_from_slice(tx_encoded);
+    envelope
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tw_encoding::hex::{self, ToHex};
+
+    #[test]
+    fn test_encode_transaction_eip4844() {
+        let tx = TransactionEip4844 {
+            nonce: U256::from(6u64),
+            max_inclusion_fee_per_gas: U256::from(2_000_000_000u64),
+            max_fee_per_gas: U256::from(3_000_000_000u64),
+            gas_limit: U256::from(21100u32),
+            to: Address::from("0x6b175474e89094c44da98b954eedeac495271d0f"),
+            amount: U256::zero(),
+            payload: Data::default(),
+            access_list: AccessList::default(),
+            max_fee_per_blob_gas: U256::from(1u64),
+            blob_versioned_hashes: vec![H256::from(
+                "0111111111111111111111111111111111111111111111111111111111111111",
+            )],
+            sidecar: None,
+        };
+        let chain_id = U256::from(10u64);
+        let actual = tx.encode(chain_id);
+
+        let expected = "03f84a0a06847735940084b2d05e0082526c946b175474e89094c44da98b954eedeac495271d0f8080c001e1a00111111111111111111111111111111111111111111111111111111111111111";
+        assert_eq!(hex::encode(actual, false), expected);
+    }
+
+    #[test]
+    fn test_kzg_to_versioned_hash() {
+        // The commitment of the zero blob is the point at infinity.
+        let mut commitment = vec![0; KZG_COMMITMENT_SIZE];
+        commitment[0] = 0xc0;
+        assert_eq!(
+            kzg_to_versioned_hash(&commitment).to_hex(),
+            "010657f37554c781402a22917dee2f75def7ab966d7b770905398eba3c444014"
+        );
+    }
+}
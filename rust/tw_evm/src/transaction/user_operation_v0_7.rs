@@ -0,0 +1,276 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::abi::encode::encode_tokens;
+use crate::abi::non_empty_array::NonEmptyBytes;
+use crate::abi::token::Token;
+use crate::address::Address;
+use crate::transaction::signature::Signature;
+use crate::transaction::{SignedTransaction, TransactionCommon, UnsignedTransaction};
+use serde::Serialize;
+use tw_coin_entry::error::prelude::*;
+use tw_encoding::hex;
+use tw_hash::sha3::keccak256;
+use tw_hash::H256;
+use tw_memory::Data;
+use tw_number::U256;
+
+/// EIP4337 UserOperation of the entry point v0.7, which is packed before hashing.
+/// https://github.com/eth-infinitism/account-abstraction/blob/v0.7.0/contracts/interfaces/PackedUserOperation.sol
+pub struct UserOperationV0_7 {
+    pub nonce: U256,
+    pub entry_point: Address,
+    pub sender: Address,
+    /// The account factory, if the account is not deployed yet.
+    pub factory: Option<Address>,
+    pub factory_data: Data,
+    pub call_gas_limit: u128,
+    pub verification_gas_limit: u128,
+    pub pre_verification_gas: U256,
+    pub max_fee_per_gas: u128,
+    pub max_inclusion_fee_per_gas: u128,
+    /// The paymaster sponsoring the transaction, if any.
+    pub paymaster: Option<Address>,
+    pub paymaster_verification_gas_limit: u128,
+    pub paymaster_post_op_gas_limit: u128,
+    pub paymaster_data: Data,
+    pub payload: Data,
+}
+
+impl UserOperationV0_7 {
+    /// Returns `factory || factory_data`, or empty bytes if there is no factory.
+    pub fn init_code(&self) -> Data {
+        match self.factory {
+            Some(factory) => [factory.as_slice(), self.factory_data.as_slice()].concat(),
+            None => Data::default(),
+        }
+    }
+
+    /// Returns `paymaster || paymaster_verification_gas_limit || paymaster_post_op_gas_limit || paymaster_data`,
+    /// or empty bytes if there is no paymaster.
+    pub fn paymaster_and_data(&self) -> Data {
+        match self.paymaster {
+            Some(paymaster) => [
+                paymaster.as_slice(),
+                self.paymaster_verification_gas_limit
+                    .to_be_bytes()
+                    .as_slice(),
+                self.paymaster_post_op_gas_limit.to_be_bytes().as_slice(),
+                self.paymaster_data.as_slice(),
+            ]
+            .concat(),
+            None => Data::default(),
+        }
+    }
+}
+
+impl TransactionCommon for UserOperationV0_7 {
+    #[inline]
+    fn payload(&self) -> Data {
+        self.payload.clone()
+    }
+}
+
+impl UnsignedTransaction for UserOperationV0_7 {
+    type SignedTransaction = SignedUserOperationV0_7;
+
+    fn pre_hash(&self, chain_id: U256) -> H256 {
+        let encode_hash = keccak256(&self.encode(chain_id));
+        let encode_hash =
+            NonEmptyBytes::new(encode_hash).expect("keccak256 must not return an empty hash");
+
+        let tokens = [
+            Token::FixedBytes(encode_hash),
+            Token::Address(self.entry_point),
+            Token::u256(chain_id),
+        ];
+        let encoded = encode_tokens(&tokens);
+        let pre_hash = keccak256(&encoded);
+        H256::try_from(pre_hash.as_slice()).expect("keccak256 returns 32 bytes")
+    }
+
+    fn encode(&self, _chain_id: U256) -> Data {
+        let init_code_hash = keccak256(&self.init_code());
+        let init_code_hash =
+            NonEmptyBytes::new(init_code_hash).expect("keccak256 must not return an empty hash");
+
+        let payload_hash = keccak256(&self.payload);
+        let payload_hash =
+            NonEmptyBytes::new(payload_hash).expect("keccak256 must not return an empty hash");
+
+        let account_gas_limits = concat_u128_be(self.verification_gas_limit, self.call_gas_limit);
+        let gas_fees = concat_u128_be(self.max_inclusion_fee_per_gas, self.max_fee_per_gas);
+
+        let paymaster_and_data_hash = keccak256(&self.paymaster_and_data());
+        let paymaster_and_data_hash = NonEmptyBytes::new(paymaster_and_data_hash)
+            .expect("keccak256 must not return an empty hash");
+
+        let tokens = [
+            Token::Address(self.sender),
+            Token::u256(self.nonce),
+            Token::FixedBytes(init_code_hash),
+            Token::FixedBytes(payload_hash),
+            Token::FixedBytes(account_gas_limits),
+            Token::u256(self.pre_verification_gas),
+            Token::FixedBytes(gas_fees),
+            Token::FixedBytes(paymaster_and_data_hash),
+        ];
+
+        encode_tokens(&tokens)
+    }
+
+    #[inline]
+    fn try_into_signed(
+        self,
+        signature: tw_keypair::ecdsa::secp256k1::Signature,
+        _chain_id: U256,
+    ) -> SigningResult<Self::SignedTransaction> {
+        Ok(SignedUserOperationV0_7 {
+            unsigned: self,
+            signature: Signature::new(signature),
+        })
+    }
+}
+
+pub struct SignedUserOperationV0_7 {
+    unsigned: UserOperationV0_7,
+    signature: Signature,
+}
+
+impl TransactionCommon for SignedUserOperationV0_7 {
+    #[inline]
+    fn payload(&self) -> Data {
+        self.unsigned.payload.clone()
+    }
+}
+
+impl SignedTransaction for SignedUserOperationV0_7 {
+    type Signature = Signature;
+
+    fn encode(&self) -> Data {
+        let mut signature = self.signature.to_rsv_bytes();
+        signature[64] += 27;
+
+        let prefix = true;
+        let tx = &self.unsigned;
+        // The factory and paymaster fields are omitted altogether if there is no factory or paymaster.
+        let factory = tx.factory.map(|factory| FactorySerde {
+            factory: factory.to_string(),
+            factory_data: hex::encode(&tx.factory_data, prefix),
+        });
+        let paymaster = tx.paymaster.map(|paymaster| PaymasterSerde {
+            paymaster: paymaster.to_string(),
+            paymaster_verification_gas_limit: tx.paymaster_verification_gas_limit.to_string(),
+            paymaster_post_op_gas_limit: tx.paymaster_post_op_gas_limit.to_string(),
+            paymaster_data: hex::encode(&tx.paymaster_data, prefix),
+        });
+
+        let tx = SignedUserOperationV0_7Serde {
+            sender: tx.sender.to_string(),
+            nonce: tx.nonce.to_string(),
+            factory,
+            call_data: hex::encode(&tx.payload, prefix),
+            call_gas_limit: tx.call_gas_limit.to_string(),
+            verification_gas_limit: tx.verification_gas_limit.to_string(),
+            pre_verification_gas: tx.pre_verification_gas.to_string(),
+            max_fee_per_gas: tx.max_fee_per_gas.to_string(),
+            max_priority_fee_per_gas: tx.max_inclusion_fee_per_gas.to_string(),
+            paymaster,
+            signature: hex::encode(signature.as_slice(), prefix),
+        };
+        serde_json::to_string(&tx)
+            .expect("Simple structure should never fail on serialization")
+            .into_bytes()
+    }
+
+    #[inline]
+    fn signature(&self) -> &Self::Signature {
+        &self.signature
+    }
+}
+
+/// Packs two `uint128` values into a `bytes32`, the first one in the high half.
+fn concat_u128_be(high: u128, low: u128) -> NonEmptyBytes {
+    let packed = [high.to_be_bytes(), low.to_be_bytes()].concat();
+    NonEmptyBytes::new(packed).expect("Packed values must not be empty")
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SignedUserOperationV0_7Serde {
+    sender: String,
+    nonce: String,
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    factory: Option<FactorySerde>,
+    call_data: String,
+    call_gas_limit: String,
+    verification_gas_limit: String,
+    pre_verification_gas: String,
+    max_fee_per_gas: String,
+    max_priority_fee_per_gas: String,
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    paymaster: Option<PaymasterSerde>,
+    signature: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FactorySerde {
+    factory: String,
+    factory_data: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PaymasterSerde {
+    paymaster: String,
+    paymaster_verification_gas_limit: String,
+    paymaster_post_op_gas_limit: String,
+    paymaster_data: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abi::prebuild::erc4337::{Erc4337SimpleAccount, ExecuteArgs};
+
+    #[test]
+    fn test_encode_user_operation_v0_7() {
+        let chain_id = U256::from(31337u64);
+
+        let execute_args = ExecuteArgs {
+            to: Address::from("0x61061fCAE11fD5461535e134EfF67A98CFFF44E9"),
+            value: U256::from(0x2_386f_26fc_10000u64),
+            data: Vec::default(),
+        };
+        let payload = Erc4337SimpleAccount::encode_execute(execute_args).unwrap();
+
+        let user_op = UserOperationV0_7 {
+            nonce: U256::zero(),
+            entry_point: Address::from("0x0000000071727De22E5E9d8BAf0edAc6f37da032"),
+            sender: Address::from("0x174a240e5147D02dE4d7724D5D3E1c1bF11cE029"),
+            factory: Some(Address::from("0xf471789937856d80e589f5996cf8b0511ddd9de4")),
+            factory_data: hex::decode("5fbfb9cf0000000000000000000000009d8a62f656a8d1615c1294fd71e9cfb3e4855a4f0000000000000000000000000000000000000000000000000000000000000000").unwrap(),
+            call_gas_limit: 100_000,
+            verification_gas_limit: 100_000,
+            pre_verification_gas: U256::from(46_856u64),
+            max_fee_per_gas: 7_033_440_745,
+            max_inclusion_fee_per_gas: 1_000_000_000,
+            paymaster: Some(Address::from("0xf62849f9a0b5bf2913b396098f7c7019b51a820a")),
+            paymaster_verification_gas_limit: 99_999,
+            paymaster_post_op_gas_limit: 88_888,
+            paymaster_data: hex::decode("00000000000b0000000000002e234dae75c793f67a35089c9d99245e1c58470b00000000000000000000000000000000000000000000000000000000000186a0072f35038bcacc31bcdeda87c1d9857703a26fb70a053f6e87da5a4e7a1e1f3c4b09fbe2dbff98e7a87ebb45a635234f4b79eff3225d07560039c7764291c97e1b").unwrap(),
+            payload,
+        };
+
+        let encoded = hex::encode(user_op.encode(chain_id), false);
+        let expected = "000000000000000000000000174a240e5147d02de4d7724d5d3e1c1bf11ce0290000000000000000000000000000000000000000000000000000000000000000152c4492466f5aa62b5437fa2ec0d1085d6364ee6909cafff18cd2e5247d58b3fbec3c1db0378685d954edd265aa6eb11e8474d828e6bda151810263838e4570000000000000000000000000000186a0000000000000000000000000000186a0000000000000000000000000000000000000000000000000000000000000b7080000000000000000000000003b9aca00000000000000000000000001a339c9e9cbf78d5fc1c6b4940bb3c30a719925e5c857af96c041fd67e75a6de6d2a10afb";
+        assert_eq!(encoded, expected);
+
+        let pre_hash = user_op.pre_hash(chain_id);
+        let expected_pre_hash =
+            H256::from("33185436db44c3721533fa617828a5e2a18c3786811b8c913343018afe01ff83");
+        assert_eq!(pre_hash, expected_pre_hash);
+    }
+}
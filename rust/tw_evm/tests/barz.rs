@@ -45,6 +45,7 @@ fn test_barz_transfer_account_deployed() {
             transaction_oneof: Proto::mod_Transaction::OneOftransaction_oneof::transfer(transfer),
         }),
         user_operation: Some(user_op),
+        ..Proto::SigningInput::default()
     };
 
     let output = Signer::<StandardEvmContext>::sign_proto(input);
@@ -98,6 +99,7 @@ fn test_barz_transfer_account_not_deployed() {
             transaction_oneof: Proto::mod_Transaction::OneOftransaction_oneof::transfer(transfer),
         }),
         user_operation: Some(user_op),
+        ..Proto::SigningInput::default()
     };
 
     let output = Signer::<StandardEvmContext>::sign_proto(input);
@@ -175,6 +177,7 @@ fn test_barz_batched_account_deployed() {
             ),
         }),
         user_operation: Some(user_op),
+        ..Proto::SigningInput::default()
     };
 
     let output = Signer::<StandardEvmContext>::sign_proto(input);
@@ -7,6 +7,9 @@ use tw_coin_entry::error::prelude::*;
 use tw_encoding::hex::{self, ToHex};
 use tw_evm::evm_context::StandardEvmContext;
 use tw_evm::modules::signer::Signer;
+use tw_keypair::async_signer::InMemorySigner;
+use tw_keypair::ecdsa::secp256k1;
+use tw_misc::test_utils::future::block_on;
 use tw_number::U256;
 use tw_proto::Ethereum::Proto;
 use tw_proto::Ethereum::Proto::TransactionMode;
@@ -52,6 +55,45 @@ fn test_sign_transaction_non_typed_erc20_transfer() {
     );
 }
 
+#[test]
+fn test_sign_transaction_with_async_signer() {
+    let private =
+        hex::decode("0x4646464646464646464646464646464646464646464646464646464646464646").unwrap();
+
+    let erc20_transfer = Proto::mod_Transaction::ERC20Transfer {
+        to: "0x5322b34c88ed0691971bf52a7047448f0f4efc84".into(),
+        amount: U256::encode_be_compact(2_000_000_000_000_000_000),
+    };
+
+    // The private key is not passed in the input, so it's held by the signer only.
+    let input = Proto::SigningInput {
+        chain_id: U256::encode_be_compact(0x34),
+        tx_mode: TransactionMode::Legacy,
+        // 42000000000
+        gas_price: U256::encode_be_compact(0x09_c765_2400),
+        // 78009
+        gas_limit: U256::encode_be_compact(0x01_30B9),
+        // DAI
+        to_address: "0x6b175474e89094c44da98b954eedeac495271d0f".into(),
+        transaction: Some(Proto::Transaction {
+            transaction_oneof: Proto::mod_Transaction::OneOftransaction_oneof::erc20_transfer(
+                erc20_transfer,
+            ),
+        }),
+        ..Proto::SigningInput::default()
+    };
+
+    let signer = InMemorySigner::new(secp256k1::PrivateKey::try_from(private.as_slice()).unwrap());
+    let output = block_on(Signer::<StandardEvmContext>::sign_proto_with_signer(
+        input, &signer,
+    ));
+    assert_eq!(output.error, SigningErrorType::OK);
+    assert!(output.error_message.is_empty());
+
+    let expected = "f8ab808509c7652400830130b9946b175474e89094c44da98b954eedeac495271d0f80b844a9059cbb0000000000000000000000005322b34c88ed0691971bf52a7047448f0f4efc840000000000000000000000000000000000000000000000001bc16d674ec80000818ba0c34040ff76f6d5e397b54b47f7fa2b3a7213f3c2a39a750260211fa15249ae8aa01ac5061e9bcf05aebef461864662652f25c45ee99240e3bb91b31f456208a6cd";
+    assert_eq!(hex::encode(output.encoded, false), expected);
+}
+
 #[test]
 fn test_sign_transaction_non_typed_native() {
     let private =
@@ -417,6 +459,298 @@ fn test_sign_transaction_eip1559_erc1155_transfer() {
     );
 }
 
+fn access_list() -> Vec<Proto::Access<'static>> {
+    vec![Proto::Access {
+        address: "0xB9F5771C27664bF2282D98E09D7F50cEc7cB01a7".into(),
+        stored_keys: vec![
+            hex::decode("0000000000000000000000000000000000000000000000000000000000000001")
+                .unwrap()
+                .into(),
+            hex::decode("c2575a0e9e593c00f959f8c92f12db2869c3395a3b0502d05e2516446f71f85b")
+                .unwrap()
+                .into(),
+        ],
+    }]
+}
+
+#[test]
+fn test_sign_transaction_eip2930_native_transfer() {
+    let private =
+        hex::decode("4f96ed80e9a7555a6f74b3d658afdd9c756b0a40d4ca30c42c2039eb449bb904").unwrap();
+
+    let transfer = Proto::mod_Transaction::Transfer {
+        amount: U256::encode_be_compact(543_210_987_654_321),
+        data: Cow::default(),
+    };
+
+    let input = Proto::SigningInput {
+        chain_id: U256::encode_be_compact(3),
+        nonce: U256::encode_be_compact(6),
+        tx_mode: TransactionMode::AccessList,
+        gas_price: U256::encode_be_compact(3_000_000_000),
+        gas_limit: U256::encode_be_compact(30_000),
+        to_address: "0xB9F5771C27664bF2282D98E09D7F50cEc7cB01a7".into(),
+        transaction: Some(Proto::Transaction {
+            transaction_oneof: Proto::mod_Transaction::OneOftransaction_oneof::transfer(transfer),
+        }),
+        access_list: access_list(),
+        private_key: private.into(),
+        ..Proto::SigningInput::default()
+    };
+
+    let output = Signer::<StandardEvmContext>::sign_proto(input);
+    assert_eq!(output.error, SigningErrorType::OK);
+    assert!(output.error_message.is_empty());
+
+    let expected = "01f8c8030684b2d05e0082753094b9f5771c27664bf2282d98e09d7f50cec7cb01a78701ee0c29f50cb180f85bf85994b9f5771c27664bf2282d98e09d7f50cec7cb01a7f842a00000000000000000000000000000000000000000000000000000000000000001a0c2575a0e9e593c00f959f8c92f12db2869c3395a3b0502d05e2516446f71f85b01a0f9e30c26c5509774a1eb180c4834ce4d40d6340f138c4542136d2ee749a09498a025a906127c77a187b9d390c86058c4dbf8cd200eb6405beffc9dfd0db3eab99e";
+    assert_eq!(hex::encode(output.encoded, false), expected);
+
+    assert_eq!(
+        output.r.to_hex(),
+        "f9e30c26c5509774a1eb180c4834ce4d40d6340f138c4542136d2ee749a09498"
+    );
+    assert_eq!(
+        output.s.to_hex(),
+        "25a906127c77a187b9d390c86058c4dbf8cd200eb6405beffc9dfd0db3eab99e"
+    );
+    // The y-parity of the typed transactions, with no chain ID.
+    assert_eq!(output.v.to_hex(), "01");
+
+    assert_eq!(
+        output.pre_hash.to_hex(),
+        "1dfb05f45f33ba9bb6378d7867bf97823809e9bbe1dd758207e3c555f499867b"
+    );
+}
+
+#[test]
+fn test_sign_transaction_eip1559_with_access_list() {
+    let private =
+        hex::decode("4f96ed80e9a7555a6f74b3d658afdd9c756b0a40d4ca30c42c2039eb449bb904").unwrap();
+
+    let transfer = Proto::mod_Transaction::Transfer {
+        amount: U256::encode_be_compact(543_210_987_654_321),
+        data: Cow::default(),
+    };
+
+    let input = Proto::SigningInput {
+        chain_id: U256::encode_be_compact(3),
+        nonce: U256::encode_be_compact(6),
+        tx_mode: TransactionMode::Enveloped,
+        gas_limit: U256::encode_be_compact(30_000),
+        max_inclusion_fee_per_gas: U256::encode_be_compact(2_000_000_000),
+        max_fee_per_gas: U256::encode_be_compact(3_000_000_000),
+        to_address: "0xB9F5771C27664bF2282D98E09D7F50cEc7cB01a7".into(),
+        transaction: Some(Proto::Transaction {
+            transaction_oneof: Proto::mod_Transaction::OneOftransaction_oneof::transfer(transfer),
+        }),
+        access_list: access_list(),
+        private_key: private.into(),
+        ..Proto::SigningInput::default()
+    };
+
+    let output = Signer::<StandardEvmContext>::sign_proto(input);
+    assert_eq!(output.error, SigningErrorType::OK);
+    assert!(output.error_message.is_empty());
+
+    let expected = "02f8cd0306847735940084b2d05e0082753094b9f5771c27664bf2282d98e09d7f50cec7cb01a78701ee0c29f50cb180f85bf85994b9f5771c27664bf2282d98e09d7f50cec7cb01a7f842a00000000000000000000000000000000000000000000000000000000000000001a0c2575a0e9e593c00f959f8c92f12db2869c3395a3b0502d05e2516446f71f85b80a0ed577d0109a53f5189c2afca608189430182fe63ecb9dbbd1e579670fc851ed7a01263f2bf5eb1aa674ca749e232c3f4c5cbe6feeb7c7687086a5f510d8d1c4ef0";
+    assert_eq!(hex::encode(output.encoded, false), expected);
+
+    assert_eq!(
+        output.pre_hash.to_hex(),
+        "cfba4561243472fd6479017958049d371b1ef23d249c6b79f05cdf8bc5fc717f"
+    );
+}
+
+#[test]
+fn test_sign_transaction_eip2930_invalid_storage_key() {
+    let private =
+        hex::decode("4f96ed80e9a7555a6f74b3d658afdd9c756b0a40d4ca30c42c2039eb449bb904").unwrap();
+
+    let transfer = Proto::mod_Transaction::Transfer {
+        amount: U256::encode_be_compact(543_210_987_654_321),
+        data: Cow::default(),
+    };
+
+    let input = Proto::SigningInput {
+        chain_id: U256::encode_be_compact(3),
+        nonce: U256::encode_be_compact(6),
+        tx_mode: TransactionMode::AccessList,
+        gas_price: U256::encode_be_compact(3_000_000_000),
+        gas_limit: U256::encode_be_compact(30_000),
+        to_address: "0xB9F5771C27664bF2282D98E09D7F50cEc7cB01a7".into(),
+        transaction: Some(Proto::Transaction {
+            transaction_oneof: Proto::mod_Transaction::OneOftransaction_oneof::transfer(transfer),
+        }),
+        access_list: vec![Proto::Access {
+            address: "0xB9F5771C27664bF2282D98E09D7F50cEc7cB01a7".into(),
+            stored_keys: vec![hex::decode("01").unwrap().into()],
+        }],
+        private_key: private.into(),
+        ..Proto::SigningInput::default()
+    };
+
+    let output = Signer::<StandardEvmContext>::sign_proto(input);
+    assert_eq!(output.error, SigningErrorType::Error_invalid_params);
+    assert!(!output.error_message.is_empty());
+}
+
+#[test]
+fn test_sign_transaction_eip1559_inclusion_fee_exceeds_max_fee() {
+    let private =
+        hex::decode("4f96ed80e9a7555a6f74b3d658afdd9c756b0a40d4ca30c42c2039eb449bb904").unwrap();
+
+    let transfer = Proto::mod_Transaction::Transfer {
+        amount: U256::encode_be_compact(543_210_987_654_321),
+        data: Cow::default(),
+    };
+
+    let input = Proto::SigningInput {
+        chain_id: U256::encode_be_compact(3),
+        nonce: U256::encode_be_compact(6),
+        tx_mode: TransactionMode::Enveloped,
+        gas_limit: U256::encode_be_compact(21_100),
+        max_inclusion_fee_per_gas: U256::encode_be_compact(3_000_000_001),
+        max_fee_per_gas: U256::encode_be_compact(3_000_000_000),
+        to_address: "0xB9F5771C27664bF2282D98E09D7F50cEc7cB01a7".into(),
+        transaction: Some(Proto::Transaction {
+            transaction_oneof: Proto::mod_Transaction::OneOftransaction_oneof::transfer(transfer),
+        }),
+        private_key: private.into(),
+        ..Proto::SigningInput::default()
+    };
+
+    let output = Signer::<StandardEvmContext>::sign_proto(input);
+    assert_eq!(output.error, SigningErrorType::Error_invalid_params);
+    assert!(!output.error_message.is_empty());
+}
+
+const BLOB_VERSIONED_HASH: &str =
+    "010657f37554c781402a22917dee2f75def7ab966d7b770905398eba3c444014";
+
+fn blob_input(
+    blob_versioned_hashes: Vec<Cow<'static, [u8]>>,
+    blob_sidecar: Option<Proto::BlobSidecar<'static>>,
+) -> Proto::SigningInput<'static> {
+    let private =
+        hex::decode("4f96ed80e9a7555a6f74b3d658afdd9c756b0a40d4ca30c42c2039eb449bb904").unwrap();
+
+    let transfer = Proto::mod_Transaction::Transfer {
+        amount: U256::encode_be_compact(543_210_987_654_321),
+        data: Cow::default(),
+    };
+
+    Proto::SigningInput {
+        chain_id: U256::encode_be_compact(1),
+        nonce: U256::encode_be_compact(6),
+        tx_mode: TransactionMode::Blob,
+        gas_limit: U256::encode_be_compact(21_000),
+        max_inclusion_fee_per_gas: U256::encode_be_compact(2_000_000_000),
+        max_fee_per_gas: U256::encode_be_compact(3_000_000_000),
+        max_fee_per_blob_gas: U256::encode_be_compact(1_000_000_000),
+        blob_versioned_hashes,
+        blob_sidecar,
+        to_address: "0xB9F5771C27664bF2282D98E09D7F50cEc7cB01a7".into(),
+        transaction: Some(Proto::Transaction {
+            transaction_oneof: Proto::mod_Transaction::OneOftransaction_oneof::transfer(transfer),
+        }),
+        private_key: private.into(),
+        ..Proto::SigningInput::default()
+    }
+}
+
+/// The sidecar of a single zero blob, whose commitment and proof are the point at infinity.
+fn zero_blob_sidecar() -> Proto::BlobSidecar<'static> {
+    let mut point_at_infinity = vec![0; 48];
+    point_at_infinity[0] = 0xc0;
+
+    Proto::BlobSidecar {
+        blobs: vec![vec![0; 131_072].into()],
+        commitments: vec![point_at_infinity.clone().into()],
+        proofs: vec![point_at_infinity.into()],
+    }
+}
+
+#[test]
+fn test_sign_transaction_eip4844_native_transfer() {
+    let hashes = vec![hex::decode(BLOB_VERSIONED_HASH).unwrap().into()];
+    let output = Signer::<StandardEvmContext>::sign_proto(blob_input(hashes, None));
+    assert_eq!(output.error, SigningErrorType::OK);
+    assert!(output.error_message.is_empty());
+
+    let expected = "03f8980106847735940084b2d05e0082520894b9f5771c27664bf2282d98e09d7f50cec7cb01a78701ee0c29f50cb180c0843b9aca00e1a0010657f37554c781402a22917dee2f75def7ab966d7b770905398eba3c44401480a0f6d6dc2d07ff1d9f51eb13255f827632ae0457cecb8d04522640580ad5fc2e73a07a53044a5c7f5e2b6a82560dbc39609e592969aed34147db160e43ff1eafbcad";
+    assert_eq!(hex::encode(output.encoded, false), expected);
+    // There are no blobs to broadcast along with the transaction.
+    assert!(output.encoded_network.is_empty());
+
+    assert_eq!(
+        output.r.to_hex(),
+        "f6d6dc2d07ff1d9f51eb13255f827632ae0457cecb8d04522640580ad5fc2e73"
+    );
+    assert_eq!(
+        output.s.to_hex(),
+        "7a53044a5c7f5e2b6a82560dbc39609e592969aed34147db160e43ff1eafbcad"
+    );
+    assert_eq!(output.v.to_hex(), "00");
+
+    assert_eq!(
+        output.pre_hash.to_hex(),
+        "6b32ea1f0b16fbf93f31162dd25ad336293b8b7b3505968a1848a501afc9e0d8"
+    );
+}
+
+#[test]
+fn test_sign_transaction_eip4844_with_blob_sidecar() {
+    // The versioned hashes are derived from the commitments.
+    let output =
+        Signer::<StandardEvmContext>::sign_proto(blob_input(Vec::new(), Some(zero_blob_sidecar())));
+    assert_eq!(output.error, SigningErrorType::OK);
+    assert!(output.error_message.is_empty());
+
+    let expected = "03f8980106847735940084b2d05e0082520894b9f5771c27664bf2282d98e09d7f50cec7cb01a78701ee0c29f50cb180c0843b9aca00e1a0010657f37554c781402a22917dee2f75def7ab966d7b770905398eba3c44401480a0f6d6dc2d07ff1d9f51eb13255f827632ae0457cecb8d04522640580ad5fc2e73a07a53044a5c7f5e2b6a82560dbc39609e592969aed34147db160e43ff1eafbcad";
+    assert_eq!(hex::encode(output.encoded.as_ref(), false), expected);
+
+    // `0x03 || rlp([tx_payload_body, blobs, commitments, proofs])`
+    let network = output.encoded_network.as_ref();
+    assert_eq!(network[0], 0x03);
+    let items = rlp::Rlp::new(&network[1..]);
+    assert_eq!(items.item_count().unwrap(), 4);
+    assert_eq!(items.at(0).unwrap().as_raw(), &output.encoded[1..]);
+    let blobs = items.at(1).unwrap();
+    assert_eq!(blobs.item_count().unwrap(), 1);
+    assert_eq!(blobs.at(0).unwrap().data().unwrap(), vec![0; 131_072]);
+}
+
+#[test]
+fn test_sign_transaction_eip4844_mismatched_versioned_hashes() {
+    let mut hash = hex::decode(BLOB_VERSIONED_HASH).unwrap();
+    hash[31] ^= 1;
+
+    let input = blob_input(vec![hash.into()], Some(zero_blob_sidecar()));
+    let output = Signer::<StandardEvmContext>::sign_proto(input);
+    assert_eq!(output.error, SigningErrorType::Error_invalid_params);
+    assert!(!output.error_message.is_empty());
+}
+
+#[test]
+fn test_sign_transaction_eip4844_no_blobs() {
+    let output = Signer::<StandardEvmContext>::sign_proto(blob_input(Vec::new(), None));
+    assert_eq!(output.error, SigningErrorType::Error_invalid_params);
+    assert!(!output.error_message.is_empty());
+}
+
+#[test]
+fn test_sign_transaction_eip4844_contract_creation() {
+    let hashes = vec![hex::decode(BLOB_VERSIONED_HASH).unwrap().into()];
+    let input = Proto::SigningInput {
+        to_address: Cow::default(),
+        ..blob_input(hashes, None)
+    };
+
+    let output = Signer::<StandardEvmContext>::sign_proto(input);
+    assert_eq!(output.error, SigningErrorType::Error_invalid_address);
+    assert!(!output.error_message.is_empty());
+}
+
 #[test]
 fn test_sign_transaction_non_typed_erc20_transfer_as_contract_generic() {
     let private =
@@ -582,3 +916,97 @@ fn test_sign_transaction_non_typed_erc1155_transfer() {
     let expected_data = "f242432a000000000000000000000000718046867b5b1782379a14ea4fc0c9b724da94fc0000000000000000000000005322b34c88ed0691971bf52a7047448f0f4efc840000000000000000000000000000000000000000000000000000000023c47ee50000000000000000000000000000000000000000000000001bc16d674ec8000000000000000000000000000000000000000000000000000000000000000000a000000000000000000000000000000000000000000000000000000000000000040102030400000000000000000000000000000000000000000000000000000000";
     assert_eq!(hex::encode(output.data, false), expected_data);
 }
+
+fn user_operation_v0_7_input(
+    user_op: Proto::UserOperationV0_7<'static>,
+) -> Proto::SigningInput<'static> {
+    let private_key =
+        hex::decode("3c90badc15c4d35733769093d3733501e92e7f16e101df284cee9a310d36c483").unwrap();
+
+    let transfer = Proto::mod_Transaction::Transfer {
+        amount: U256::encode_be_compact(0x23_86f2_6fc1_0000),
+        data: Cow::default(),
+    };
+
+    Proto::SigningInput {
+        chain_id: U256::encode_be_compact(31337),
+        nonce: U256::encode_be_compact(0),
+        tx_mode: TransactionMode::UserOp,
+        gas_limit: U256::encode_be_compact(100_000),
+        max_fee_per_gas: U256::encode_be_compact(7_033_440_745),
+        max_inclusion_fee_per_gas: U256::encode_be_compact(1_000_000_000),
+        to_address: "0x61061fCAE11fD5461535e134EfF67A98CFFF44E9".into(),
+        private_key: private_key.into(),
+        transaction: Some(Proto::Transaction {
+            transaction_oneof: Proto::mod_Transaction::OneOftransaction_oneof::transfer(transfer),
+        }),
+        user_operation_v0_7: Some(user_op),
+        ..Proto::SigningInput::default()
+    }
+}
+
+#[test]
+fn test_sign_user_operation_v0_7_with_factory_and_paymaster() {
+    let user_op = Proto::UserOperationV0_7 {
+        entry_point: "0x0000000071727De22E5E9d8BAf0edAc6f37da032".into(),
+        sender: "0x174a240e5147D02dE4d7724D5D3E1c1bF11cE029".into(),
+        factory: "0xf471789937856d80e589f5996cf8b0511ddd9de4".into(),
+        factory_data: hex::decode("5fbfb9cf0000000000000000000000009d8a62f656a8d1615c1294fd71e9cfb3e4855a4f0000000000000000000000000000000000000000000000000000000000000000").unwrap().into(),
+        pre_verification_gas: U256::encode_be_compact(46_856),
+        verification_gas_limit: U256::encode_be_compact(100_000),
+        paymaster: "0xf62849f9a0b5bf2913b396098f7c7019b51a820a".into(),
+        paymaster_verification_gas_limit: U256::encode_be_compact(99_999),
+        paymaster_post_op_gas_limit: U256::encode_be_compact(88_888),
+        paymaster_data: hex::decode("00000000000b0000000000002e234dae75c793f67a35089c9d99245e1c58470b00000000000000000000000000000000000000000000000000000000000186a0072f35038bcacc31bcdeda87c1d9857703a26fb70a053f6e87da5a4e7a1e1f3c4b09fbe2dbff98e7a87ebb45a635234f4b79eff3225d07560039c7764291c97e1b").unwrap().into(),
+    };
+
+    let output = Signer::<StandardEvmContext>::sign_proto(user_operation_v0_7_input(user_op));
+    assert_eq!(output.error, SigningErrorType::OK);
+    assert!(output.error_message.is_empty());
+
+    let expected = r#"{"sender":"0x174a240e5147D02dE4d7724D5D3E1c1bF11cE029","nonce":"0","factory":"0xf471789937856D80e589F5996cf8b0511DDD9de4","factoryData":"0x5fbfb9cf0000000000000000000000009d8a62f656a8d1615c1294fd71e9cfb3e4855a4f0000000000000000000000000000000000000000000000000000000000000000","callData":"0xb61d27f600000000000000000000000061061fcae11fd5461535e134eff67a98cfff44e9000000000000000000000000000000000000000000000000002386f26fc1000000000000000000000000000000000000000000000000000000000000000000600000000000000000000000000000000000000000000000000000000000000000","callGasLimit":"100000","verificationGasLimit":"100000","preVerificationGas":"46856","maxFeePerGas":"7033440745","maxPriorityFeePerGas":"1000000000","paymaster":"0xF62849F9A0B5Bf2913b396098F7c7019b51A820a","paymasterVerificationGasLimit":"99999","paymasterPostOpGasLimit":"88888","paymasterData":"0x00000000000b0000000000002e234dae75c793f67a35089c9d99245e1c58470b00000000000000000000000000000000000000000000000000000000000186a0072f35038bcacc31bcdeda87c1d9857703a26fb70a053f6e87da5a4e7a1e1f3c4b09fbe2dbff98e7a87ebb45a635234f4b79eff3225d07560039c7764291c97e1b","signature":"0xfa25fddcc4fb5cbb31f32e72b990d8f7e42a2a58f789293da9564bdb5df9e6182de9cb1cd474e20291b1ccbed7cea1b89a9e8d015194818bd41e65f76cf784ef1b"}"#;
+    let actual = String::from_utf8(output.encoded.to_vec()).unwrap();
+    assert_eq!(actual, expected);
+
+    assert_eq!(
+        output.pre_hash.to_hex(),
+        "33185436db44c3721533fa617828a5e2a18c3786811b8c913343018afe01ff83"
+    );
+}
+
+#[test]
+fn test_sign_user_operation_v0_7_gas_limit_overflow() {
+    let user_op = Proto::UserOperationV0_7 {
+        entry_point: "0x0000000071727De22E5E9d8BAf0edAc6f37da032".into(),
+        sender: "0x174a240e5147D02dE4d7724D5D3E1c1bF11cE029".into(),
+        pre_verification_gas: U256::encode_be_compact(46_856),
+        // 2^128 does not fit into the packed `uint128`.
+        verification_gas_limit: [&[1][..], &[0; 16][..]].concat().into(),
+        ..Proto::UserOperationV0_7::default()
+    };
+
+    let output = Signer::<StandardEvmContext>::sign_proto(user_operation_v0_7_input(user_op));
+    assert_eq!(output.error, SigningErrorType::Error_invalid_params);
+    assert!(!output.error_message.is_empty());
+}
+
+#[test]
+fn test_sign_user_operation_v0_6_and_v0_7() {
+    let user_op = Proto::UserOperationV0_7 {
+        entry_point: "0x0000000071727De22E5E9d8BAf0edAc6f37da032".into(),
+        sender: "0x174a240e5147D02dE4d7724D5D3E1c1bF11cE029".into(),
+        ..Proto::UserOperationV0_7::default()
+    };
+    let input = Proto::SigningInput {
+        user_operation: Some(Proto::UserOperation {
+            entry_point: "0x5FF137D4b0FDCD49DcA30c7CF57E578a026d2789".into(),
+            sender: "0x174a240e5147D02dE4d7724D5D3E1c1bF11cE029".into(),
+            ..Proto::UserOperation::default()
+        }),
+        ..user_operation_v0_7_input(user_op)
+    };
+
+    let output = Signer::<StandardEvmContext>::sign_proto(input);
+    assert_eq!(output.error, SigningErrorType::Error_invalid_params);
+    assert!(!output.error_message.is_empty());
+}
@@ -0,0 +1,35 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::traits::{AsyncSigner, SigningKeyTrait};
+use crate::KeyPairResult;
+use std::future::{ready, Future};
+
+/// Adapts an in-memory private key to the [`AsyncSigner`] interface.
+/// The resulting future is always ready.
+pub struct InMemorySigner<Key> {
+    key: Key,
+}
+
+impl<Key> InMemorySigner<Key> {
+    pub fn new(key: Key) -> Self {
+        InMemorySigner { key }
+    }
+}
+
+impl<Key> AsyncSigner for InMemorySigner<Key>
+where
+    Key: SigningKeyTrait,
+    Key::Signature: Send,
+{
+    type SigningMessage = Key::SigningMessage;
+    type Signature = Key::Signature;
+
+    fn sign(
+        &self,
+        message: Self::SigningMessage,
+    ) -> impl Future<Output = KeyPairResult<Self::Signature>> + Send {
+        ready(self.key.sign(message))
+    }
+}
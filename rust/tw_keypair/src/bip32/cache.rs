@@ -0,0 +1,165 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::bip32::{Bip32Curve, ExtendedPrivateKey};
+use crate::mnemonic::{Mnemonic, Passphrase};
+use crate::KeyPairResult;
+use std::collections::BTreeMap;
+
+/// Keeps the master key and the intermediate nodes derived from it,
+/// so deriving many keys, e.g. the addresses of an account, doesn't run PBKDF2
+/// and derive the same parents again for every key:
+///
+/// ```rust,ignore
+/// let mut cache = HdNodeCache::<Secp256k1>::from_mnemonic(&mnemonic, &passphrase)?;
+/// for index in 0..1000 {
+///     let key = cache.derive_path(DerivationPath::bip84(0, 0, 0, index)?.indices())?;
+/// }
+/// ```
+///
+/// The private keys of the cached nodes are zeroized on drop or [`HdNodeCache::clear`].
+pub struct HdNodeCache<C: Bip32Curve> {
+    master: ExtendedPrivateKey<C>,
+    /// The nodes by their path relative to the master key.
+    nodes: BTreeMap<Vec<u32>, ExtendedPrivateKey<C>>,
+}
+
+impl<C: Bip32Curve> HdNodeCache<C> {
+    pub fn new(master: ExtendedPrivateKey<C>) -> Self {
+        HdNodeCache {
+            master,
+            nodes: BTreeMap::new(),
+        }
+    }
+
+    /// Creates a cache of the master key of the given `seed`.
+    pub fn from_seed(seed: &[u8]) -> KeyPairResult<Self> {
+        ExtendedPrivateKey::from_seed(seed).map(Self::new)
+    }
+
+    /// Creates a cache of the master key of the given `mnemonic` and optional `passphrase`.
+    pub fn from_mnemonic(mnemonic: &Mnemonic, passphrase: &Passphrase) -> KeyPairResult<Self> {
+        ExtendedPrivateKey::from_mnemonic(mnemonic, passphrase).map(Self::new)
+    }
+
+    /// Returns the master key.
+    pub fn master(&self) -> &ExtendedPrivateKey<C> {
+        &self.master
+    }
+
+    /// Derives the key at the given `path` relative to the master key.
+    /// Every parent of the key is cached, while the key itself is not.
+    pub fn derive_path(&mut self, path: &[u32]) -> KeyPairResult<ExtendedPrivateKey<C>> {
+        match path.split_last() {
+            Some((index, parent_path)) => self.node(parent_path)?.derive_child(*index),
+            None => self.master.derive_path(&[]),
+        }
+    }
+
+    /// Returns the number of the cached nodes besides the master key.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Removes the cached nodes except for the master key.
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+    }
+
+    /// Returns the cached node at `path`, deriving and caching it and its parents if needed.
+    fn node(&mut self, path: &[u32]) -> KeyPairResult<&ExtendedPrivateKey<C>> {
+        let Some((index, parent_path)) = path.split_last() else {
+            return Ok(&self.master);
+        };
+        if !self.nodes.contains_key(path) {
+            let node = self.node(parent_path)?.derive_child(*index)?;
+            self.nodes.insert(path.to_vec(), node);
+        }
+        Ok(&self.nodes[path])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bip32::{DerivationPath, Secp256k1, HARDENED_OFFSET, XPRV_VERSION};
+    use crate::mnemonic::Language;
+    use crate::KeyPairError;
+    use tw_encoding::hex;
+
+    #[test]
+    fn test_hd_node_cache_derive() {
+        let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+        let mut cache = HdNodeCache::<Secp256k1>::from_seed(&seed).unwrap();
+        let master = ExtendedPrivateKey::<Secp256k1>::from_seed(&seed).unwrap();
+
+        // https://github.com/bitcoin/bips/blob/master/bip-0032.mediawiki#test-vector-1
+        let path = [HARDENED_OFFSET, 1, 2 + HARDENED_OFFSET, 2, 1_000_000_000];
+        let key = cache.derive_path(&path).unwrap();
+        assert_eq!(
+            key.to_xprv(XPRV_VERSION).as_str(),
+            "xprvA41z7zogVVwxVSgdKUHDy1SKmdb533PjDz7J6N6mV6uS3ze1ai8FHa8kmHScGpWmj4WggLyQjgPie1rFSruoUihUZREPSL39UNdE3BBDu76"
+        );
+        // The parents are cached.
+        assert_eq!(cache.len(), 4);
+
+        let key = cache.derive_path(&path[..2]).unwrap();
+        assert_eq!(
+            key.to_xprv(XPRV_VERSION).as_str(),
+            "xprv9wTYmMFdV23N2TdNG573QoEsfRrWKQgWeibmLntzniatZvR9BmLnvSxqu53Kw1UmYPxLgboyZQaXwTCg8MSY3H2EU4pWcQDnRnrVA1xe8fs"
+        );
+        assert_eq!(cache.len(), 4);
+
+        let key = cache.derive_path(&[]).unwrap();
+        assert_eq!(key.to_xprv(XPRV_VERSION), master.to_xprv(XPRV_VERSION));
+
+        cache.clear();
+        assert!(cache.is_empty());
+        assert_eq!(
+            cache.master().to_xprv(XPRV_VERSION),
+            master.to_xprv(XPRV_VERSION)
+        );
+    }
+
+    #[test]
+    fn test_hd_node_cache_addresses() {
+        let mnemonic = Mnemonic::parse(
+            Language::English,
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+        )
+        .unwrap();
+        let passphrase = Passphrase::default();
+        let mut cache = HdNodeCache::<Secp256k1>::from_mnemonic(&mnemonic, &passphrase).unwrap();
+        let master =
+            ExtendedPrivateKey::<Secp256k1>::from_mnemonic(&mnemonic, &passphrase).unwrap();
+
+        for change in 0..2 {
+            for index in 0..10 {
+                let path = DerivationPath::bip84(0, 0, change, index).unwrap();
+                let expected = master.derive_path(path.indices()).unwrap();
+                let key = cache.derive_path(path.indices()).unwrap();
+                assert_eq!(key.to_xprv(XPRV_VERSION), expected.to_xprv(XPRV_VERSION));
+            }
+        }
+        // `m/84'`, `m/84'/0'`, `m/84'/0'/0'`, `m/84'/0'/0'/0` and `m/84'/0'/0'/1`.
+        assert_eq!(cache.len(), 5);
+    }
+
+    #[test]
+    fn test_hd_node_cache_invalid() {
+        let mut cache = HdNodeCache::<crate::bip32::Ed25519>::from_seed(&[1; 32]).unwrap();
+        // Ed25519 supports hardened derivation only.
+        assert_eq!(
+            cache.derive_path(&[HARDENED_OFFSET, 0, 1]).unwrap_err(),
+            KeyPairError::InvalidSecretKey
+        );
+        assert_eq!(cache.len(), 1);
+
+        assert!(HdNodeCache::<Secp256k1>::from_seed(&[1; 8]).is_err());
+    }
+}
@@ -0,0 +1,211 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::ecdsa::{nist256p1, secp256k1};
+use crate::ed25519;
+use crate::schnorr::scalar_from_canonical_bytes;
+use crate::{KeyPairError, KeyPairResult};
+use p256::elliptic_curve::PrimeField;
+use tw_hash::{H256, H264};
+use tw_misc::traits::ToBytesZeroizing;
+use zeroize::Zeroizing;
+
+/// An elliptic curve that keys can be derived on according to BIP32 or SLIP-10.
+pub trait Bip32Curve {
+    type PrivateKey;
+    type PublicKey: Clone;
+
+    /// The HMAC key of the master key derivation.
+    const MASTER_HMAC_KEY: &'static [u8];
+    /// Whether non-hardened child keys can be derived.
+    const NON_HARDENED: bool;
+    /// Whether an invalid key is derived again as specified in SLIP-10,
+    /// or an error is returned as specified in BIP32.
+    const RETRY_INVALID_KEY: bool;
+
+    /// Returns the child private key, or `None` if the key is invalid for the given `tweak`, i.e. `I_L`.
+    fn child_private(parent: &Self::PrivateKey, tweak: &H256) -> Option<Self::PrivateKey>;
+
+    /// Returns the child public key, or `None` if the key is invalid for the given `tweak`, i.e. `I_L`.
+    fn child_public(parent: &Self::PublicKey, tweak: &H256) -> Option<Self::PublicKey>;
+
+    fn public(private: &Self::PrivateKey) -> Self::PublicKey;
+
+    fn private_to_bytes(private: &Self::PrivateKey) -> Zeroizing<H256>;
+
+    fn private_from_bytes(bytes: &[u8]) -> KeyPairResult<Self::PrivateKey>;
+
+    /// Returns the 33 byte public key as it's serialized in the extended key.
+    fn public_to_bytes(public: &Self::PublicKey) -> H264;
+
+    fn public_from_bytes(bytes: &[u8]) -> KeyPairResult<Self::PublicKey>;
+}
+
+/// `secp256k1` derivation as specified in [BIP32](https://github.com/bitcoin/bips/blob/master/bip-0032.mediawiki).
+pub struct Secp256k1;
+
+impl Bip32Curve for Secp256k1 {
+    type PrivateKey = secp256k1::PrivateKey;
+    type PublicKey = secp256k1::PublicKey;
+
+    const MASTER_HMAC_KEY: &'static [u8] = b"Bitcoin seed";
+    const NON_HARDENED: bool = true;
+    const RETRY_INVALID_KEY: bool = false;
+
+    fn child_private(parent: &Self::PrivateKey, tweak: &H256) -> Option<Self::PrivateKey> {
+        let tweak = scalar_from_canonical_bytes(tweak)?;
+        let scalar = *parent.secret.as_nonzero_scalar().as_ref() + tweak;
+        let scalar = Option::<k256::NonZeroScalar>::from(k256::NonZeroScalar::new(scalar))?;
+        Some(secp256k1::PrivateKey {
+            secret: k256::ecdsa::SigningKey::from(scalar),
+        })
+    }
+
+    fn child_public(parent: &Self::PublicKey, tweak: &H256) -> Option<Self::PublicKey> {
+        parent.tweak_add(tweak).ok()
+    }
+
+    fn public(private: &Self::PrivateKey) -> Self::PublicKey {
+        private.public()
+    }
+
+    fn private_to_bytes(private: &Self::PrivateKey) -> Zeroizing<H256> {
+        private_to_bytes(private)
+    }
+
+    fn private_from_bytes(bytes: &[u8]) -> KeyPairResult<Self::PrivateKey> {
+        secp256k1::PrivateKey::try_from(bytes)
+    }
+
+    fn public_to_bytes(public: &Self::PublicKey) -> H264 {
+        public.compressed()
+    }
+
+    fn public_from_bytes(bytes: &[u8]) -> KeyPairResult<Self::PublicKey> {
+        secp256k1::PublicKey::from_compressed(bytes)
+    }
+}
+
+/// `nist256p1` derivation as specified in [SLIP-10](https://github.com/satoshilabs/slips/blob/master/slip-0010.md).
+pub struct Nist256p1;
+
+impl Bip32Curve for Nist256p1 {
+    type PrivateKey = nist256p1::PrivateKey;
+    type PublicKey = nist256p1::PublicKey;
+
+    const MASTER_HMAC_KEY: &'static [u8] = b"Nist256p1 seed";
+    const NON_HARDENED: bool = true;
+    const RETRY_INVALID_KEY: bool = true;
+
+    fn child_private(parent: &Self::PrivateKey, tweak: &H256) -> Option<Self::PrivateKey> {
+        let tweak = nist256p1_scalar(tweak)?;
+        let scalar = *parent.secret.as_nonzero_scalar().as_ref() + tweak;
+        let scalar = Option::<p256::NonZeroScalar>::from(p256::NonZeroScalar::new(scalar))?;
+        let secret = Zeroizing::new(scalar.to_repr());
+        nist256p1::PrivateKey::try_from(secret.as_slice()).ok()
+    }
+
+    fn child_public(parent: &Self::PublicKey, tweak: &H256) -> Option<Self::PublicKey> {
+        let tweak = nist256p1_scalar(tweak)?;
+        let point = p256::ProjectivePoint::from(*parent.public.as_affine())
+            + p256::ProjectivePoint::GENERATOR * tweak;
+        // Rejects the point at infinity.
+        p256::ecdsa::VerifyingKey::from_affine(point.to_affine())
+            .ok()
+            .map(nist256p1::PublicKey::new)
+    }
+
+    fn public(private: &Self::PrivateKey) -> Self::PublicKey {
+        private.public()
+    }
+
+    fn private_to_bytes(private: &Self::PrivateKey) -> Zeroizing<H256> {
+        private_to_bytes(private)
+    }
+
+    fn private_from_bytes(bytes: &[u8]) -> KeyPairResult<Self::PrivateKey> {
+        nist256p1::PrivateKey::try_from(bytes)
+    }
+
+    fn public_to_bytes(public: &Self::PublicKey) -> H264 {
+        public.compressed()
+    }
+
+    fn public_from_bytes(bytes: &[u8]) -> KeyPairResult<Self::PublicKey> {
+        if bytes.len() != nist256p1::PublicKey::COMPRESSED {
+            return Err(KeyPairError::InvalidPublicKey);
+        }
+        nist256p1::PublicKey::try_from(bytes)
+    }
+}
+
+/// `ed25519` derivation as specified in [SLIP-10](https://github.com/satoshilabs/slips/blob/master/slip-0010.md).
+/// Only hardened child keys can be derived.
+pub struct Ed25519;
+
+/// cbindgen:ignore
+impl Ed25519 {
+    /// The prefix of a public key in the serialized extended key.
+    const PUBLIC_KEY_PREFIX: u8 = 0x00;
+}
+
+impl Bip32Curve for Ed25519 {
+    type PrivateKey = ed25519::sha512::PrivateKey;
+    type PublicKey = ed25519::sha512::PublicKey;
+
+    const MASTER_HMAC_KEY: &'static [u8] = b"ed25519 seed";
+    const NON_HARDENED: bool = false;
+    const RETRY_INVALID_KEY: bool = false;
+
+    /// The child private key is `I_L` itself.
+    fn child_private(_parent: &Self::PrivateKey, tweak: &H256) -> Option<Self::PrivateKey> {
+        ed25519::sha512::PrivateKey::try_from(tweak.as_slice()).ok()
+    }
+
+    fn child_public(_parent: &Self::PublicKey, _tweak: &H256) -> Option<Self::PublicKey> {
+        None
+    }
+
+    fn public(private: &Self::PrivateKey) -> Self::PublicKey {
+        private.public()
+    }
+
+    fn private_to_bytes(private: &Self::PrivateKey) -> Zeroizing<H256> {
+        private_to_bytes(private)
+    }
+
+    fn private_from_bytes(bytes: &[u8]) -> KeyPairResult<Self::PrivateKey> {
+        ed25519::sha512::PrivateKey::try_from(bytes)
+    }
+
+    /// Returns `0x00 || public`.
+    fn public_to_bytes(public: &Self::PublicKey) -> H264 {
+        let mut bytes = H264::default();
+        bytes[0] = Self::PUBLIC_KEY_PREFIX;
+        bytes[1..].copy_from_slice(public.as_slice());
+        bytes
+    }
+
+    fn public_from_bytes(bytes: &[u8]) -> KeyPairResult<Self::PublicKey> {
+        match bytes.split_first() {
+            Some((prefix, public))
+                if *prefix == Self::PUBLIC_KEY_PREFIX && bytes.len() == H264::LEN =>
+            {
+                ed25519::sha512::PublicKey::try_from(public)
+            },
+            _ => Err(KeyPairError::InvalidPublicKey),
+        }
+    }
+}
+
+fn private_to_bytes(private: &impl ToBytesZeroizing) -> Zeroizing<H256> {
+    let bytes = private.to_zeroizing_vec();
+    Zeroizing::new(H256::try_from(bytes.as_slice()).expect("Expected a 32 byte private key"))
+}
+
+/// Interprets the given 32 bytes as a big-endian integer.
+/// Returns `None` if the integer is not less than the `nist256p1` curve order.
+fn nist256p1_scalar(bytes: &H256) -> Option<p256::Scalar> {
+    p256::Scalar::from_repr(p256::FieldBytes::clone_from_slice(bytes.as_slice())).into()
+}
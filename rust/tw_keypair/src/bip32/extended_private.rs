@@ -0,0 +1,203 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::bip32::extended_public::ExtendedPublicKey;
+use crate::bip32::slip132::KeyVersion;
+use crate::bip32::{
+    decode_payload, decode_version, encode_payload, fingerprint, hmac_split, is_hardened,
+    retry_data, Bip32Curve, ExtendedKeyAttrs,
+};
+use crate::mnemonic::{Mnemonic, Passphrase};
+use crate::{KeyPairError, KeyPairResult};
+use std::fmt;
+use tw_encoding::base58::{self, Alphabet};
+use tw_hash::{H256, H264, H32};
+use zeroize::Zeroizing;
+
+/// The seed length must be between 128 and 512 bits.
+const MIN_SEED_LEN: usize = 16;
+const MAX_SEED_LEN: usize = 64;
+/// The prefix of a private key in the serialized extended key.
+const PRIVATE_KEY_PREFIX: u8 = 0x00;
+
+/// Represents an extended private key on the curve `C`.
+pub struct ExtendedPrivateKey<C: Bip32Curve> {
+    private: C::PrivateKey,
+    attrs: ExtendedKeyAttrs,
+}
+
+impl<C: Bip32Curve> ExtendedPrivateKey<C> {
+    /// Creates a master key from the given `seed` (16 to 64 bytes).
+    pub fn from_seed(seed: &[u8]) -> KeyPairResult<Self> {
+        if !(MIN_SEED_LEN..=MAX_SEED_LEN).contains(&seed.len()) {
+            return Err(KeyPairError::InvalidSecretKey);
+        }
+
+        let (mut secret, mut chain_code) = hmac_split(C::MASTER_HMAC_KEY, seed);
+        let private = loop {
+            match C::private_from_bytes(secret.as_slice()) {
+                Ok(private) => break private,
+                // SLIP-10: `I = HMAC-SHA512(Key = curve, Data = I)`.
+                Err(_) if C::RETRY_INVALID_KEY => {
+                    let mut data = Zeroizing::new(secret.to_vec());
+                    data.extend_from_slice(chain_code.as_slice());
+                    (secret, chain_code) = hmac_split(C::MASTER_HMAC_KEY, data.as_slice());
+                },
+                Err(e) => return Err(e),
+            }
+        };
+
+        Ok(Self::new_master(private, chain_code))
+    }
+
+    /// Creates a master key from the given `private` key and `chain_code`.
+    pub fn new_master(private: C::PrivateKey, chain_code: H256) -> Self {
+        ExtendedPrivateKey {
+            private,
+            attrs: ExtendedKeyAttrs::master(chain_code),
+        }
+    }
+
+    /// Creates a master key from the seed of the given `mnemonic` and optional `passphrase`.
+    pub fn from_mnemonic(mnemonic: &Mnemonic, passphrase: &Passphrase) -> KeyPairResult<Self> {
+        Self::from_seed(mnemonic.to_seed(passphrase).as_slice())
+    }
+
+    /// Derives a child key at the given `index`.
+    /// Returns an error if `index` is not hardened while the curve supports hardened derivation only,
+    /// or in the unlikely case the child key is invalid for BIP32, then the next index should be used.
+    pub fn derive_child(&self, index: u32) -> KeyPairResult<Self> {
+        if !is_hardened(index) && !C::NON_HARDENED {
+            return Err(KeyPairError::InvalidSecretKey);
+        }
+        let public = C::public(&self.private);
+
+        let mut data = Zeroizing::new(Vec::with_capacity(H264::LEN + 4));
+        if is_hardened(index) {
+            data.push(PRIVATE_KEY_PREFIX);
+            data.extend_from_slice(C::private_to_bytes(&self.private).as_slice());
+        } else {
+            data.extend_from_slice(C::public_to_bytes(&public).as_slice());
+        }
+        data.extend_from_slice(&index.to_be_bytes());
+
+        let parent_chain_code = self.attrs.chain_code.as_slice();
+        let (mut tweak, mut chain_code) = hmac_split(parent_chain_code, data.as_slice());
+        let private = loop {
+            match C::child_private(&self.private, &tweak) {
+                Some(private) => break private,
+                None if C::RETRY_INVALID_KEY => {
+                    (tweak, chain_code) =
+                        hmac_split(parent_chain_code, &retry_data(&chain_code, index));
+                },
+                None => return Err(KeyPairError::InvalidSecretKey),
+            }
+        };
+
+        Ok(ExtendedPrivateKey {
+            private,
+            attrs: self
+                .attrs
+                .child(fingerprint::<C>(&public), index, chain_code)
+                .ok_or(KeyPairError::InvalidSecretKey)?,
+        })
+    }
+
+    /// Derives a descendant key at the given `path` relative to this key.
+    pub fn derive_path(&self, path: &[u32]) -> KeyPairResult<Self> {
+        let key = ExtendedPrivateKey {
+            private: C::private_from_bytes(C::private_to_bytes(&self.private).as_slice())?,
+            attrs: self.attrs.clone(),
+        };
+        path.iter()
+            .try_fold(key, |key, index| key.derive_child(*index))
+    }
+
+    /// Returns an associated extended public key.
+    pub fn public(&self) -> ExtendedPublicKey<C> {
+        ExtendedPublicKey::new(C::public(&self.private), self.attrs.clone())
+    }
+
+    /// Returns a reference to the private key.
+    pub fn private(&self) -> &C::PrivateKey {
+        &self.private
+    }
+
+    /// Returns the chain code.
+    pub fn chain_code(&self) -> H256 {
+        self.attrs.chain_code
+    }
+
+    /// Returns the depth of the key, i.e. `0` for the master key.
+    pub fn depth(&self) -> u8 {
+        self.attrs.depth
+    }
+
+    /// Returns the fingerprint of the parent key, i.e. zeros for the master key.
+    pub fn parent_fingerprint(&self) -> H32 {
+        self.attrs.parent_fingerprint
+    }
+
+    /// Returns the index of the key in the parent's children, i.e. `0` for the master key.
+    pub fn child_number(&self) -> u32 {
+        self.attrs.child_number
+    }
+
+    /// Returns the fingerprint of this key, i.e. the first 4 bytes of `hash160(public)`.
+    pub fn fingerprint(&self) -> H32 {
+        fingerprint::<C>(&C::public(&self.private))
+    }
+
+    /// Encodes the key in base58 with the given `version` bytes, e.g. [`super::XPRV_VERSION`].
+    pub fn to_xprv(&self, version: u32) -> Zeroizing<String> {
+        let mut key = Zeroizing::new([0; H264::LEN]);
+        key[0] = PRIVATE_KEY_PREFIX;
+        key[1..].copy_from_slice(C::private_to_bytes(&self.private).as_slice());
+
+        let payload = encode_payload(version, &self.attrs, key.as_slice());
+        Zeroizing::new(base58::encode(payload.as_slice(), Alphabet::BITCOIN))
+    }
+
+    /// Decodes a base58 extended private key with the given expected `version` bytes, e.g. [`super::XPRV_VERSION`].
+    pub fn from_xprv(xprv: &str, version: u32) -> KeyPairResult<Self> {
+        let payload = decode_payload(xprv, version).ok_or(KeyPairError::InvalidSecretKey)?;
+        let (attrs, key) =
+            ExtendedKeyAttrs::decode(&payload).ok_or(KeyPairError::InvalidSecretKey)?;
+        let (prefix, secret) = key.split_first().ok_or(KeyPairError::InvalidSecretKey)?;
+        if *prefix != PRIVATE_KEY_PREFIX {
+            return Err(KeyPairError::InvalidSecretKey);
+        }
+        Ok(ExtendedPrivateKey {
+            private: C::private_from_bytes(secret)?,
+            attrs,
+        })
+    }
+
+    /// Encodes the key in base58 with the SLIP-132 version bytes of the given `version`, e.g. as a `zprv`.
+    pub fn to_slip132(&self, version: KeyVersion) -> Zeroizing<String> {
+        self.to_xprv(version.private())
+    }
+
+    /// Decodes a base58 extended private key with any of the SLIP-132 version bytes,
+    /// e.g. `xprv`, `yprv` or `zprv`, and returns the key version that it was encoded with.
+    pub fn from_slip132(xprv: &str) -> KeyPairResult<(Self, KeyVersion)> {
+        let version = decode_version(xprv)
+            .and_then(KeyVersion::from_private)
+            .ok_or(KeyPairError::InvalidSecretKey)?;
+        let key = Self::from_xprv(xprv, version.private())?;
+        Ok((key, version))
+    }
+}
+
+/// Prints the position of the key in the tree, but not the key itself.
+impl<C: Bip32Curve> fmt::Debug for ExtendedPrivateKey<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExtendedPrivateKey")
+            .field("depth", &self.attrs.depth)
+            .field("parent_fingerprint", &self.attrs.parent_fingerprint)
+            .field("child_number", &self.attrs.child_number)
+            .field("fingerprint", &fingerprint::<C>(&C::public(&self.private)))
+            .finish()
+    }
+}
@@ -0,0 +1,149 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::bip32::slip132::KeyVersion;
+use crate::bip32::{
+    decode_payload, decode_version, encode_payload, fingerprint, hmac_split, is_hardened,
+    retry_data, Bip32Curve, ExtendedKeyAttrs,
+};
+use crate::{KeyPairError, KeyPairResult};
+use std::fmt;
+use tw_encoding::base58::{self, Alphabet};
+use tw_hash::{H256, H264, H32};
+
+/// Represents an extended public key on the curve `C`.
+pub struct ExtendedPublicKey<C: Bip32Curve> {
+    public: C::PublicKey,
+    attrs: ExtendedKeyAttrs,
+}
+
+impl<C: Bip32Curve> ExtendedPublicKey<C> {
+    pub(super) fn new(public: C::PublicKey, attrs: ExtendedKeyAttrs) -> Self {
+        ExtendedPublicKey { public, attrs }
+    }
+
+    /// Derives a non-hardened child key at the given `index`.
+    /// Returns an error if `index` is hardened or the curve supports hardened derivation only,
+    /// or in the unlikely case the child key is invalid for BIP32, then the next index should be used.
+    pub fn derive_child(&self, index: u32) -> KeyPairResult<Self> {
+        if is_hardened(index) || !C::NON_HARDENED {
+            return Err(KeyPairError::InvalidPublicKey);
+        }
+
+        let mut data = Vec::with_capacity(H264::LEN + 4);
+        data.extend_from_slice(C::public_to_bytes(&self.public).as_slice());
+        data.extend_from_slice(&index.to_be_bytes());
+
+        let parent_chain_code = self.attrs.chain_code.as_slice();
+        let (mut tweak, mut chain_code) = hmac_split(parent_chain_code, &data);
+        let public = loop {
+            match C::child_public(&self.public, &tweak) {
+                Some(public) => break public,
+                None if C::RETRY_INVALID_KEY => {
+                    (tweak, chain_code) =
+                        hmac_split(parent_chain_code, &retry_data(&chain_code, index));
+                },
+                None => return Err(KeyPairError::InvalidPublicKey),
+            }
+        };
+
+        Ok(ExtendedPublicKey {
+            public,
+            attrs: self
+                .attrs
+                .child(fingerprint::<C>(&self.public), index, chain_code)
+                .ok_or(KeyPairError::InvalidPublicKey)?,
+        })
+    }
+
+    /// Derives a descendant key at the given non-hardened `path` relative to this key.
+    pub fn derive_path(&self, path: &[u32]) -> KeyPairResult<Self> {
+        path.iter()
+            .try_fold(self.clone(), |key, index| key.derive_child(*index))
+    }
+
+    /// Returns a reference to the public key.
+    pub fn public(&self) -> &C::PublicKey {
+        &self.public
+    }
+
+    /// Returns the chain code.
+    pub fn chain_code(&self) -> H256 {
+        self.attrs.chain_code
+    }
+
+    /// Returns the depth of the key, i.e. `0` for the master key.
+    pub fn depth(&self) -> u8 {
+        self.attrs.depth
+    }
+
+    /// Returns the fingerprint of the parent key, i.e. zeros for the master key.
+    pub fn parent_fingerprint(&self) -> H32 {
+        self.attrs.parent_fingerprint
+    }
+
+    /// Returns the index of the key in the parent's children, i.e. `0` for the master key.
+    pub fn child_number(&self) -> u32 {
+        self.attrs.child_number
+    }
+
+    /// Returns the fingerprint of this key, i.e. the first 4 bytes of `hash160(public)`.
+    pub fn fingerprint(&self) -> H32 {
+        fingerprint::<C>(&self.public)
+    }
+
+    /// Encodes the key in base58 with the given `version` bytes, e.g. [`super::XPUB_VERSION`].
+    pub fn to_xpub(&self, version: u32) -> String {
+        let key = C::public_to_bytes(&self.public);
+        let payload = encode_payload(version, &self.attrs, key.as_slice());
+        base58::encode(payload.as_slice(), Alphabet::BITCOIN)
+    }
+
+    /// Decodes a base58 extended public key with the given expected `version` bytes, e.g. [`super::XPUB_VERSION`].
+    pub fn from_xpub(xpub: &str, version: u32) -> KeyPairResult<Self> {
+        let payload = decode_payload(xpub, version).ok_or(KeyPairError::InvalidPublicKey)?;
+        let (attrs, key) =
+            ExtendedKeyAttrs::decode(&payload).ok_or(KeyPairError::InvalidPublicKey)?;
+        Ok(ExtendedPublicKey {
+            public: C::public_from_bytes(key)?,
+            attrs,
+        })
+    }
+
+    /// Encodes the key in base58 with the SLIP-132 version bytes of the given `version`, e.g. as a `zpub`.
+    pub fn to_slip132(&self, version: KeyVersion) -> String {
+        self.to_xpub(version.public())
+    }
+
+    /// Decodes a base58 extended public key with any of the SLIP-132 version bytes,
+    /// e.g. `xpub`, `ypub` or `zpub`, and returns the key version that it was encoded with.
+    pub fn from_slip132(xpub: &str) -> KeyPairResult<(Self, KeyVersion)> {
+        let version = decode_version(xpub)
+            .and_then(KeyVersion::from_public)
+            .ok_or(KeyPairError::InvalidPublicKey)?;
+        let key = Self::from_xpub(xpub, version.public())?;
+        Ok((key, version))
+    }
+}
+
+impl<C: Bip32Curve> Clone for ExtendedPublicKey<C> {
+    fn clone(&self) -> Self {
+        ExtendedPublicKey {
+            public: self.public.clone(),
+            attrs: self.attrs.clone(),
+        }
+    }
+}
+
+/// Prints the position of the key in the tree, but not the key itself.
+impl<C: Bip32Curve> fmt::Debug for ExtendedPublicKey<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExtendedPublicKey")
+            .field("depth", &self.attrs.depth)
+            .field("parent_fingerprint", &self.attrs.parent_fingerprint)
+            .field("child_number", &self.attrs.child_number)
+            .field("fingerprint", &fingerprint::<C>(&self.public))
+            .finish()
+    }
+}
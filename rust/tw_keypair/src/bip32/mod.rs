@@ -0,0 +1,724 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! [BIP32](https://github.com/bitcoin/bips/blob/master/bip-0032.mediawiki) hierarchical deterministic key derivation,
+//! and its [SLIP-10](https://github.com/satoshilabs/slips/blob/master/slip-0010.md) generalization
+//! to the `nist256p1` and `ed25519` curves, so keys of every curve are derived from the same seed with the same API:
+//!
+//! ```rust,ignore
+//! use tw_keypair::bip32::{DerivationPath, Ed25519, ExtendedPrivateKey, Secp256k1};
+//!
+//! let bitcoin_path: DerivationPath = "m/84'/0'/0'/0/0".parse()?;
+//! let bitcoin_key = ExtendedPrivateKey::<Secp256k1>::from_seed(SEED)?.derive_path(bitcoin_path.indices())?;
+//!
+//! let solana_path: DerivationPath = "m/44'/501'/0'/0'".parse()?;
+//! let solana_key = ExtendedPrivateKey::<Ed25519>::from_seed(SEED)?.derive_path(solana_path.indices())?;
+//! ```
+//!
+//! Extended keys are serialized as `base58(version || depth || parent_fingerprint || child_number || chain_code || key || checksum)`.
+
+use crate::ecdsa::secp256k1::wif::checksum;
+use tw_encoding::base58::{self, Alphabet};
+use tw_hash::hmac::hmac_sha512;
+use tw_hash::ripemd::ripemd_160;
+use tw_hash::sha2::sha256;
+use tw_hash::{H256, H264, H32};
+use zeroize::Zeroizing;
+
+mod cache;
+mod curve;
+mod extended_private;
+mod extended_public;
+mod path;
+pub mod slip132;
+pub mod slip44;
+
+pub use cache::HdNodeCache;
+pub use curve::{Bip32Curve, Ed25519, Nist256p1, Secp256k1};
+pub use extended_private::ExtendedPrivateKey;
+pub use extended_public::ExtendedPublicKey;
+pub use path::{DerivationPath, DerivationPathError, HardenedNotation, Purpose};
+
+/// Indices starting from this value are hardened.
+pub const HARDENED_OFFSET: u32 = 0x8000_0000;
+
+/// Version bytes of a mainnet extended private key (`xprv`).
+pub const XPRV_VERSION: u32 = 0x0488_ade4;
+/// Version bytes of a mainnet extended public key (`xpub`).
+pub const XPUB_VERSION: u32 = 0x0488_b21e;
+/// Version bytes of a testnet extended private key (`tprv`).
+pub const TPRV_VERSION: u32 = 0x0435_8394;
+/// Version bytes of a testnet extended public key (`tpub`).
+pub const TPUB_VERSION: u32 = 0x0435_87cf;
+
+/// `version (4) || depth (1) || parent_fingerprint (4) || child_number (4) || chain_code (32) || key (33)`.
+const SERIALIZED_LEN: usize = 4 + 1 + H32::LEN + 4 + H256::LEN + H264::LEN;
+const CHECKSUM_LEN: usize = 4;
+/// The prefix of the SLIP-10 retry data: `0x01 || I_R || ser32(i)`.
+const RETRY_PREFIX: u8 = 0x01;
+
+/// Returns whether the child `index` should be derived from the parent private key.
+pub fn is_hardened(index: u32) -> bool {
+    index >= HARDENED_OFFSET
+}
+
+/// The attributes shared by the extended private and public keys.
+#[derive(Clone)]
+struct ExtendedKeyAttrs {
+    depth: u8,
+    parent_fingerprint: H32,
+    child_number: u32,
+    chain_code: H256,
+}
+
+impl ExtendedKeyAttrs {
+    fn master(chain_code: H256) -> ExtendedKeyAttrs {
+        ExtendedKeyAttrs {
+            depth: 0,
+            parent_fingerprint: H32::default(),
+            child_number: 0,
+            chain_code,
+        }
+    }
+
+    /// Returns the attributes of a child, or `None` if the depth exceeds 255.
+    fn child(
+        &self,
+        parent_fingerprint: H32,
+        index: u32,
+        chain_code: H256,
+    ) -> Option<ExtendedKeyAttrs> {
+        Some(ExtendedKeyAttrs {
+            depth: self.depth.checked_add(1)?,
+            parent_fingerprint,
+            child_number: index,
+            chain_code,
+        })
+    }
+
+    /// Decodes the attributes and the 33 byte key from the payload without the version and checksum.
+    /// Returns `None` if the master key has a non-zero parent fingerprint or child number.
+    fn decode(payload: &[u8]) -> Option<(ExtendedKeyAttrs, &[u8])> {
+        let (depth, rest) = payload.split_first()?;
+        let (parent_fingerprint, rest) = rest.split_at(H32::LEN);
+        let (child_number, rest) = rest.split_at(4);
+        let (chain_code, key) = rest.split_at(H256::LEN);
+
+        let attrs = ExtendedKeyAttrs {
+            depth: *depth,
+            parent_fingerprint: H32::try_from(parent_fingerprint).ok()?,
+            child_number: u32::from_be_bytes(child_number.try_into().ok()?),
+            chain_code: H256::try_from(chain_code).ok()?,
+        };
+        if attrs.depth == 0 && (!attrs.parent_fingerprint.is_zero() || attrs.child_number != 0) {
+            return None;
+        }
+        Some((attrs, key))
+    }
+}
+
+/// Computes `HMAC-SHA512(key, data)` and splits it into the left and right 32 byte halves.
+fn hmac_split(key: &[u8], data: &[u8]) -> (Zeroizing<H256>, H256) {
+    let hash = Zeroizing::new(hmac_sha512(key, data));
+    let left = H256::try_from(&hash[..H256::LEN]).expect("HMAC-SHA512 returns 64 bytes");
+    let right = H256::try_from(&hash[H256::LEN..]).expect("HMAC-SHA512 returns 64 bytes");
+    (Zeroizing::new(left), right)
+}
+
+/// Returns the SLIP-10 data `0x01 || I_R || ser32(i)` to derive the child key again.
+fn retry_data(chain_code: &H256, index: u32) -> Vec<u8> {
+    let mut data = Vec::with_capacity(1 + H256::LEN + 4);
+    data.push(RETRY_PREFIX);
+    data.extend_from_slice(chain_code.as_slice());
+    data.extend_from_slice(&index.to_be_bytes());
+    data
+}
+
+/// Returns the first 4 bytes of `ripemd160(sha256(public))`.
+fn fingerprint<C: Bip32Curve>(public: &C::PublicKey) -> H32 {
+    let hash = ripemd_160(&sha256(C::public_to_bytes(public).as_slice()));
+    H32::try_from(&hash[..H32::LEN]).expect("RIPEMD-160 returns 20 bytes")
+}
+
+/// Returns `version || attrs || key || checksum`.
+fn encode_payload(version: u32, attrs: &ExtendedKeyAttrs, key: &[u8]) -> Zeroizing<Vec<u8>> {
+    let mut payload = Zeroizing::new(Vec::with_capacity(SERIALIZED_LEN + CHECKSUM_LEN));
+    payload.extend_from_slice(&version.to_be_bytes());
+    payload.push(attrs.depth);
+    payload.extend_from_slice(attrs.parent_fingerprint.as_slice());
+    payload.extend_from_slice(&attrs.child_number.to_be_bytes());
+    payload.extend_from_slice(attrs.chain_code.as_slice());
+    payload.extend_from_slice(key);
+
+    let checksum = checksum(payload.as_slice());
+    payload.extend_from_slice(&checksum);
+    payload
+}
+
+/// Returns the version bytes of a base58 extended key without verifying its checksum.
+fn decode_version(encoded: &str) -> Option<u32> {
+    let payload = Zeroizing::new(base58::decode(encoded, Alphabet::BITCOIN).ok()?);
+    if payload.len() != SERIALIZED_LEN + CHECKSUM_LEN {
+        return None;
+    }
+    Some(u32::from_be_bytes(payload[..4].try_into().ok()?))
+}
+
+/// Decodes the base58 payload, verifies its checksum and `version`,
+/// and returns the payload without the version and checksum.
+fn decode_payload(encoded: &str, version: u32) -> Option<Zeroizing<Vec<u8>>> {
+    let payload = Zeroizing::new(base58::decode(encoded, Alphabet::BITCOIN).ok()?);
+    if payload.len() != SERIALIZED_LEN + CHECKSUM_LEN {
+        return None;
+    }
+
+    let (data, actual_checksum) = payload.split_at(SERIALIZED_LEN);
+    if checksum(data) != actual_checksum || data[..4] != version.to_be_bytes() {
+        return None;
+    }
+    Some(Zeroizing::new(data[4..].to_vec()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::KeyPairError;
+    use tw_encoding::hex;
+    use tw_misc::traits::ToBytesZeroizing;
+
+    /// https://github.com/bitcoin/bips/blob/master/bip-0032.mediawiki#test-vector-1
+    #[test]
+    fn test_bip32_derive_test_vector_1() {
+        let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+        let master = ExtendedPrivateKey::<Secp256k1>::from_seed(&seed).unwrap();
+        assert_eq!(
+            master.to_xprv(XPRV_VERSION).as_str(),
+            "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi"
+        );
+        assert_eq!(
+            master.public().to_xpub(XPUB_VERSION),
+            "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8"
+        );
+        assert_eq!(master.fingerprint(), H32::from("3442193e"));
+        assert_eq!(master.depth(), 0);
+
+        let tests = [
+            (
+                HARDENED_OFFSET,
+                "xprv9uHRZZhk6KAJC1avXpDAp4MDc3sQKNxDiPvvkX8Br5ngLNv1TxvUxt4cV1rGL5hj6KCesnDYUhd7oWgT11eZG7XnxHrnYeSvkzY7d2bhkJ7",
+                "xpub68Gmy5EdvgibQVfPdqkBBCHxA5htiqg55crXYuXoQRKfDBFA1WEjWgP6LHhwBZeNK1VTsfTFUHCdrfp1bgwQ9xv5ski8PX9rL2dZXvgGDnw",
+            ),
+            (
+                1,
+                "xprv9wTYmMFdV23N2TdNG573QoEsfRrWKQgWeibmLntzniatZvR9BmLnvSxqu53Kw1UmYPxLgboyZQaXwTCg8MSY3H2EU4pWcQDnRnrVA1xe8fs",
+                "xpub6ASuArnXKPbfEwhqN6e3mwBcDTgzisQN1wXN9BJcM47sSikHjJf3UFHKkNAWbWMiGj7Wf5uMash7SyYq527Hqck2AxYysAA7xmALppuCkwQ",
+            ),
+            (
+                2 + HARDENED_OFFSET,
+                "xprv9z4pot5VBttmtdRTWfWQmoH1taj2axGVzFqSb8C9xaxKymcFzXBDptWmT7FwuEzG3ryjH4ktypQSAewRiNMjANTtpgP4mLTj34bhnZX7UiM",
+                "xpub6D4BDPcP2GT577Vvch3R8wDkScZWzQzMMUm3PWbmWvVJrZwQY4VUNgqFJPMM3No2dFDFGTsxxpG5uJh7n7epu4trkrX7x7DogT5Uv6fcLW5",
+            ),
+            (
+                2,
+                "xprvA2JDeKCSNNZky6uBCviVfJSKyQ1mDYahRjijr5idH2WwLsEd4Hsb2Tyh8RfQMuPh7f7RtyzTtdrbdqqsunu5Mm3wDvUAKRHSC34sJ7in334",
+                "xpub6FHa3pjLCk84BayeJxFW2SP4XRrFd1JYnxeLeU8EqN3vDfZmbqBqaGJAyiLjTAwm6ZLRQUMv1ZACTj37sR62cfN7fe5JnJ7dh8zL4fiyLHV",
+            ),
+            (
+                1_000_000_000,
+                "xprvA41z7zogVVwxVSgdKUHDy1SKmdb533PjDz7J6N6mV6uS3ze1ai8FHa8kmHScGpWmj4WggLyQjgPie1rFSruoUihUZREPSL39UNdE3BBDu76",
+                "xpub6H1LXWLaKsWFhvm6RVpEL9P4KfRZSW7abD2ttkWP3SSQvnyA8FSVqNTEcYFgJS2UaFcxupHiYkro49S8yGasTvXEYBVPamhGW6cFJodrTHy",
+            ),
+        ];
+
+        let mut key = master;
+        for (depth, (index, xprv, xpub)) in tests.into_iter().enumerate() {
+            let parent_fingerprint = key.fingerprint();
+            key = key.derive_child(index).unwrap();
+
+            assert_eq!(key.to_xprv(XPRV_VERSION).as_str(), xprv);
+            assert_eq!(key.public().to_xpub(XPUB_VERSION), xpub);
+            assert_eq!(key.depth() as usize, depth + 1);
+            assert_eq!(key.child_number(), index);
+            assert_eq!(key.parent_fingerprint(), parent_fingerprint);
+        }
+
+        let path: DerivationPath = "m/0'/1/2'/2/1000000000".parse().unwrap();
+        let key = ExtendedPrivateKey::<Secp256k1>::from_seed(&seed)
+            .unwrap()
+            .derive_path(path.indices())
+            .unwrap();
+        assert_eq!(
+            key.public().to_xpub(XPUB_VERSION),
+            "xpub6H1LXWLaKsWFhvm6RVpEL9P4KfRZSW7abD2ttkWP3SSQvnyA8FSVqNTEcYFgJS2UaFcxupHiYkro49S8yGasTvXEYBVPamhGW6cFJodrTHy"
+        );
+    }
+
+    #[test]
+    fn test_bip32_derive_public() {
+        // https://github.com/bitcoin/bips/blob/master/bip-0032.mediawiki#test-vector-2
+        let seed = hex::decode("fffcf9f6f3f0edeae7e4e1dedbd8d5d2cfccc9c6c3c0bdbab7b4b1aeaba8a5a29f9c999693908d8a8784817e7b7875726f6c696663605d5a5754514e4b484542").unwrap();
+        let master = ExtendedPrivateKey::<Secp256k1>::from_seed(&seed).unwrap();
+        let expected = "xpub69H7F5d8KSRgmmdJg2KhpAK8SR3DjMwAdkxj3ZuxV27CprR9LgpeyGmXUbC6wb7ERfvrnKZjXoUmmDznezpbZb7ap6r1D3tgFxHmwMkQTPH";
+
+        let child = master.derive_child(0).unwrap();
+        assert_eq!(child.public().to_xpub(XPUB_VERSION), expected);
+        let child = master.public().derive_child(0).unwrap();
+        assert_eq!(child.to_xpub(XPUB_VERSION), expected);
+
+        // Public derivation of the test vector 1 `m/0'/1/2'` -> `m/0'/1/2'/2/1000000000`.
+        let xpub = "xpub6D4BDPcP2GT577Vvch3R8wDkScZWzQzMMUm3PWbmWvVJrZwQY4VUNgqFJPMM3No2dFDFGTsxxpG5uJh7n7epu4trkrX7x7DogT5Uv6fcLW5";
+        let key = ExtendedPublicKey::<Secp256k1>::from_xpub(xpub, XPUB_VERSION).unwrap();
+        assert_eq!(key.depth(), 3);
+        assert_eq!(key.child_number(), 2 + HARDENED_OFFSET);
+        assert_eq!(
+            key.derive_path(&[2, 1_000_000_000]).unwrap().to_xpub(XPUB_VERSION),
+            "xpub6H1LXWLaKsWFhvm6RVpEL9P4KfRZSW7abD2ttkWP3SSQvnyA8FSVqNTEcYFgJS2UaFcxupHiYkro49S8yGasTvXEYBVPamhGW6cFJodrTHy"
+        );
+
+        // Hardened keys can't be derived from the public key.
+        assert_eq!(
+            key.derive_child(HARDENED_OFFSET).unwrap_err(),
+            KeyPairError::InvalidPublicKey
+        );
+    }
+
+    #[test]
+    fn test_bip32_xprv_xpub_parse() {
+        let xprv = "xprv9wTYmMFdV23N2TdNG573QoEsfRrWKQgWeibmLntzniatZvR9BmLnvSxqu53Kw1UmYPxLgboyZQaXwTCg8MSY3H2EU4pWcQDnRnrVA1xe8fs";
+        let key = ExtendedPrivateKey::<Secp256k1>::from_xprv(xprv, XPRV_VERSION).unwrap();
+        assert_eq!(key.to_xprv(XPRV_VERSION).as_str(), xprv);
+        assert_eq!(key.depth(), 2);
+        assert_eq!(key.child_number(), 1);
+        assert_eq!(key.parent_fingerprint(), H32::from("5c1bd648"));
+
+        let tprv = "tprv8ZgxMBicQKsPeDgjzdC36fs6bMjGApWDNLR9erAXMs5skhMv36j9MV5ecvfavji5khqjWaWSFhN3YcCUUdiKH6isR4Pwy3U5y5egddBr16m";
+        let key = ExtendedPrivateKey::<Secp256k1>::from_xprv(tprv, TPRV_VERSION).unwrap();
+        assert_eq!(key.fingerprint(), H32::from("3442193e"));
+        // The version doesn't match.
+        ExtendedPrivateKey::<Secp256k1>::from_xprv(tprv, XPRV_VERSION).unwrap_err();
+
+        let invalid_xprvs = [
+            // Zero depth with a non-zero parent fingerprint.
+            "xprv9s2SVEMYPrA5zFr9cMZoqCQE6996p9PcDSAJdygf2wXW35yPEq4R8WjZcNDGuQFXjzJuMEWuHjMBXPKa4QGPyjiiAZJYQvsRPTuqBWKvEZh",
+            // Zero depth with a non-zero child number.
+            "xprv9s21ZrQH143K5xHBs26cwZK5DysagCJvyKkvGxYZfF4mZAqjPTNZDYRPyzMWuZqh2Ah4465C1KR38McHpLVffLbyzqfTkrY5tYLVhTL5ye4",
+            // Zero private key.
+            "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChijLXZSun8bsGj49MuvWWsqL9fqS5fhiDUkRQvq8cj8L42RGwHP",
+            // The private key is equal to the curve order.
+            "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkg5hntwdZH6QYdrGVYWUCS2Xv6FCMHoYQZYQDohv67LnGTwiNd",
+            // Invalid checksum.
+            "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHj",
+            // Extended public key.
+            "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8",
+            "",
+        ];
+        for xprv in invalid_xprvs {
+            ExtendedPrivateKey::<Secp256k1>::from_xprv(xprv, XPRV_VERSION).unwrap_err();
+        }
+
+        // Invalid public key prefix.
+        let xpub = "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ45ycVBsADt89FVXeDkYqbSeZmpjjnJETkyyiMwXokWPisrtUjm";
+        ExtendedPublicKey::<Secp256k1>::from_xpub(xpub, XPUB_VERSION).unwrap_err();
+        ExtendedPublicKey::<Secp256k1>::from_xpub(xprv, XPUB_VERSION).unwrap_err();
+    }
+
+    #[test]
+    fn test_bip32_derivation_path() {
+        let path: DerivationPath = "m/44'/0h/0H/0/2147483647".parse().unwrap();
+        assert_eq!(
+            path.indices(),
+            &[
+                44 + HARDENED_OFFSET,
+                HARDENED_OFFSET,
+                HARDENED_OFFSET,
+                0,
+                2_147_483_647
+            ]
+        );
+        assert_eq!(path.to_string(), "m/44'/0'/0'/0/2147483647");
+        assert_eq!(
+            path.to_string_with(HardenedNotation::LowerH),
+            "m/44h/0h/0h/0/2147483647"
+        );
+        assert_eq!(
+            path.to_string_with(HardenedNotation::UpperH),
+            "m/44H/0H/0H/0/2147483647"
+        );
+        assert_eq!(
+            "44h/0'/0H/0/2147483647".parse::<DerivationPath>().unwrap(),
+            path
+        );
+        assert_eq!(
+            "m".parse::<DerivationPath>().unwrap(),
+            DerivationPath::default()
+        );
+        assert_eq!("0".parse::<DerivationPath>().unwrap().indices(), &[0]);
+
+        assert_eq!(
+            "".parse::<DerivationPath>().unwrap_err(),
+            DerivationPathError::Empty
+        );
+        assert_eq!(
+            "m/44'/0'/x/0".parse::<DerivationPath>().unwrap_err(),
+            DerivationPathError::InvalidComponent {
+                position: 2,
+                component: "x".to_string(),
+            }
+        );
+        assert_eq!(
+            KeyPairError::from(DerivationPathError::Empty),
+            KeyPairError::InvalidSecretKey
+        );
+
+        let invalid_paths = [
+            "/44'/0'",
+            "M/44'",
+            "m/",
+            "m//0",
+            "m/x",
+            "m/-1",
+            "m/+1",
+            "m/2147483648",
+            "m/0''",
+            "m/0'h",
+            "m/0hh",
+            "m/0'/",
+        ];
+        for path in invalid_paths {
+            path.parse::<DerivationPath>().unwrap_err();
+        }
+
+        // The seed must be between 16 and 64 bytes.
+        ExtendedPrivateKey::<Secp256k1>::from_seed(&[1; 15]).unwrap_err();
+        ExtendedPrivateKey::<Secp256k1>::from_seed(&[1; 65]).unwrap_err();
+    }
+
+    #[test]
+    fn test_bip32_standard_derivation_path() {
+        let path = DerivationPath::bip44(slip44::ETHEREUM, 0, 0, 0).unwrap();
+        assert_eq!(path.to_string(), "m/44'/60'/0'/0/0");
+        let path = DerivationPath::bip49(slip44::LITECOIN, 1, 0, 5).unwrap();
+        assert_eq!(path.to_string(), "m/49'/2'/1'/0/5");
+        let path = DerivationPath::bip84(slip44::TESTNET, 0, 1, 0).unwrap();
+        assert_eq!(path.to_string(), "m/84'/1'/0'/1/0");
+        let path = DerivationPath::bip86(slip44::BITCOIN, 0, 0, 2).unwrap();
+        assert_eq!(path.to_string(), "m/86'/0'/0'/0/2");
+        assert_eq!(path, "m/86'/0'/0'/0/2".parse().unwrap());
+
+        assert_eq!(path.purpose(), Some(Purpose::Bip86));
+        assert_eq!(path.coin(), Some(slip44::BITCOIN));
+        assert_eq!(path.account(), Some(0));
+        assert_eq!(path.change(), Some(0));
+        assert_eq!(path.address_index(), Some(2));
+
+        // The indices are hardened by the constructor.
+        let err = DerivationPath::bip44(HARDENED_OFFSET, 0, 0, 0).unwrap_err();
+        assert_eq!(err, KeyPairError::InvalidSecretKey);
+        let err = DerivationPath::bip84(slip44::BITCOIN, 0, 0, HARDENED_OFFSET).unwrap_err();
+        assert_eq!(err, KeyPairError::InvalidSecretKey);
+
+        // Non-standard paths.
+        let paths = [
+            "m/44'/501'/0'/0'",
+            "m/44'/0'/0'/0'/0",
+            "m/44/0'/0'/0/0",
+            "m/1852'/1815'/0'/0/0",
+        ];
+        for path in paths {
+            let path: DerivationPath = path.parse().unwrap();
+            assert_eq!(path.purpose(), None);
+        }
+        let path: DerivationPath = "m/44'/501'/0'/0'".parse().unwrap();
+        assert_eq!(path.coin(), None);
+    }
+
+    /// Checks the chain code, private and public keys at `m` and every prefix of the `path`.
+    fn check_slip10_vector<C>(
+        seed: &str,
+        path: &[u32],
+        expected: &[(&'static str, &str, &'static str)],
+    ) where
+        C: Bip32Curve,
+        C::PrivateKey: ToBytesZeroizing,
+    {
+        let seed = hex::decode(seed).unwrap();
+        let master = ExtendedPrivateKey::<C>::from_seed(&seed).unwrap();
+
+        for (depth, (chain_code, private, public)) in expected.iter().enumerate() {
+            let key = master.derive_path(&path[..depth]).unwrap();
+            assert_eq!(key.depth() as usize, depth);
+            assert_eq!(key.chain_code(), H256::from(*chain_code));
+            assert_eq!(
+                hex::encode(key.private().to_zeroizing_vec().as_slice(), false),
+                *private
+            );
+            assert_eq!(
+                C::public_to_bytes(key.public().public()),
+                H264::from(*public)
+            );
+        }
+    }
+
+    /// https://github.com/satoshilabs/slips/blob/master/slip-0010.md#test-vector-1-for-ed25519
+    #[test]
+    fn test_slip10_ed25519_test_vector_1() {
+        let path = [
+            HARDENED_OFFSET,
+            1 + HARDENED_OFFSET,
+            2 + HARDENED_OFFSET,
+            2 + HARDENED_OFFSET,
+            1_000_000_000 + HARDENED_OFFSET,
+        ];
+        let expected = [
+            (
+                "90046a93de5380a72b5e45010748567d5ea02bbf6522f979e05c0d8d8ca9fffb",
+                "2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7",
+                "00a4b2856bfec510abab89753fac1ac0e1112364e7d250545963f135f2a33188ed",
+            ),
+            (
+                "8b59aa11380b624e81507a27fedda59fea6d0b779a778918a2fd3590e16e9c69",
+                "68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3",
+                "008c8a13df77a28f3445213a0f432fde644acaa215fc72dcdf300d5efaa85d350c",
+            ),
+            (
+                "a320425f77d1b5c2505a6b1b27382b37368ee640e3557c315416801243552f14",
+                "b1d0bad404bf35da785a64ca1ac54b2617211d2777696fbffaf208f746ae84f2",
+                "001932a5270f335bed617d5b935c80aedb1a35bd9fc1e31acafd5372c30f5c1187",
+            ),
+            (
+                "2e69929e00b5ab250f49c3fb1c12f252de4fed2c1db88387094a0f8c4c9ccd6c",
+                "92a5b23c0b8a99e37d07df3fb9966917f5d06e02ddbd909c7e184371463e9fc9",
+                "00ae98736566d30ed0e9d2f4486a64bc95740d89c7db33f52121f8ea8f76ff0fc1",
+            ),
+            (
+                "8f6d87f93d750e0efccda017d662a1b31a266e4a6f5993b15f5c1f07f74dd5cc",
+                "30d1dc7e5fc04c31219ab25a27ae00b50f6fd66622f6e9c913253d6511d1e662",
+                "008abae2d66361c879b900d204ad2cc4984fa2aa344dd7ddc46007329ac76c429c",
+            ),
+            (
+                "68789923a0cac2cd5a29172a475fe9e0fb14cd6adb5ad98a3fa70333e7afa230",
+                "8f94d394a8e8fd6b1bc2f3f49f5c47e385281d5c17e65324b0f62483e37e8793",
+                "003c24da049451555d51a7014a37337aa4e12d41e485abccfa46b47dfb2af54b7a",
+            ),
+        ];
+        check_slip10_vector::<Ed25519>("000102030405060708090a0b0c0d0e0f", &path, &expected);
+    }
+
+    /// https://github.com/satoshilabs/slips/blob/master/slip-0010.md#test-vector-1-for-nist256p1
+    #[test]
+    fn test_slip10_nist256p1_test_vector_1() {
+        let path = [HARDENED_OFFSET, 1, 2 + HARDENED_OFFSET, 2, 1_000_000_000];
+        let expected = [
+            (
+                "beeb672fe4621673f722f38529c07392fecaa61015c80c34f29ce8b41b3cb6ea",
+                "612091aaa12e22dd2abef664f8a01a82cae99ad7441b7ef8110424915c268bc2",
+                "0266874dc6ade47b3ecd096745ca09bcd29638dd52c2c12117b11ed3e458cfa9e8",
+            ),
+            (
+                "3460cea53e6a6bb5fb391eeef3237ffd8724bf0a40e94943c98b83825342ee11",
+                "6939694369114c67917a182c59ddb8cafc3004e63ca5d3b84403ba8613debc0c",
+                "0384610f5ecffe8fda089363a41f56a5c7ffc1d81b59a612d0d649b2d22355590c",
+            ),
+            (
+                "4187afff1aafa8445010097fb99d23aee9f599450c7bd140b6826ac22ba21d0c",
+                "284e9d38d07d21e4e281b645089a94f4cf5a5a81369acf151a1c3a57f18b2129",
+                "03526c63f8d0b4bbbf9c80df553fe66742df4676b241dabefdef67733e070f6844",
+            ),
+            (
+                "98c7514f562e64e74170cc3cf304ee1ce54d6b6da4f880f313e8204c2a185318",
+                "694596e8a54f252c960eb771a3c41e7e32496d03b954aeb90f61635b8e092aa7",
+                "0359cf160040778a4b14c5f4d7b76e327ccc8c4a6086dd9451b7482b5a4972dda0",
+            ),
+            (
+                "ba96f776a5c3907d7fd48bde5620ee374d4acfd540378476019eab70790c63a0",
+                "5996c37fd3dd2679039b23ed6f70b506c6b56b3cb5e424681fb0fa64caf82aaa",
+                "029f871f4cb9e1c97f9f4de9ccd0d4a2f2a171110c61178f84430062230833ff20",
+            ),
+            (
+                "b9b7b82d326bb9cb5b5b121066feea4eb93d5241103c9e7a18aad40f1dde8059",
+                "21c4f269ef0a5fd1badf47eeacebeeaa3de22eb8e5b0adcd0f27dd99d34d0119",
+                "02216cd26d31147f72427a453c443ed2cde8a1e53c9cc44e5ddf739725413fe3f4",
+            ),
+        ];
+        check_slip10_vector::<Nist256p1>("000102030405060708090a0b0c0d0e0f", &path, &expected);
+
+        // The non-hardened children can be derived from the extended public key too.
+        let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+        let key = ExtendedPrivateKey::<Nist256p1>::from_seed(&seed)
+            .unwrap()
+            .derive_path(&path[..3])
+            .unwrap();
+        let public = key.public().derive_path(&path[3..]).unwrap();
+        assert_eq!(
+            Nist256p1::public_to_bytes(public.public()),
+            H264::from("02216cd26d31147f72427a453c443ed2cde8a1e53c9cc44e5ddf739725413fe3f4")
+        );
+        assert_eq!(
+            public.chain_code(),
+            H256::from("b9b7b82d326bb9cb5b5b121066feea4eb93d5241103c9e7a18aad40f1dde8059")
+        );
+    }
+
+    /// https://github.com/satoshilabs/slips/blob/master/slip-0010.md#test-derivation-retry-for-nist256p1
+    #[test]
+    fn test_slip10_nist256p1_derivation_retry() {
+        let expected = [
+            (
+                "beeb672fe4621673f722f38529c07392fecaa61015c80c34f29ce8b41b3cb6ea",
+                "612091aaa12e22dd2abef664f8a01a82cae99ad7441b7ef8110424915c268bc2",
+                "0266874dc6ade47b3ecd096745ca09bcd29638dd52c2c12117b11ed3e458cfa9e8",
+            ),
+            (
+                "e94c8ebe30c2250a14713212f6449b20f3329105ea15b652ca5bdfc68f6c65c2",
+                "06f0db126f023755d0b8d86d4591718a5210dd8d024e3e14b6159d63f53aa669",
+                "02519b5554a4872e8c9c1c847115363051ec43e93400e030ba3c36b52a3e70a5b7",
+            ),
+            (
+                "9e87fe95031f14736774cd82f25fd885065cb7c358c1edf813c72af535e83071",
+                "092154eed4af83e078ff9b84322015aefe5769e31270f62c3f66c33888335f3a",
+                "0235bfee614c0d5b2cae260000bb1d0d84b270099ad790022c1ae0b2e782efe120",
+            ),
+        ];
+        check_slip10_vector::<Nist256p1>(
+            "000102030405060708090a0b0c0d0e0f",
+            &[28578 + HARDENED_OFFSET, 33941],
+            &expected,
+        );
+
+        // The non-hardened child is derived from the extended public key with the same retry.
+        let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+        let key = ExtendedPrivateKey::<Nist256p1>::from_seed(&seed)
+            .unwrap()
+            .derive_child(28578 + HARDENED_OFFSET)
+            .unwrap();
+        let public = key.public().derive_child(33941).unwrap();
+        assert_eq!(
+            Nist256p1::public_to_bytes(public.public()),
+            H264::from("0235bfee614c0d5b2cae260000bb1d0d84b270099ad790022c1ae0b2e782efe120")
+        );
+    }
+
+    /// https://github.com/satoshilabs/slips/blob/master/slip-0010.md#test-seed-retry-for-nist256p1
+    #[test]
+    fn test_slip10_nist256p1_seed_retry() {
+        let expected = [(
+            "7762f9729fed06121fd13f326884c82f59aa95c57ac492ce8c9654e60efd130c",
+            "3b8c18469a4634517d6d0b65448f8e6c62091b45540a1743c5846be55d47d88f",
+            "0383619fadcde31063d8c5cb00dbfe1713f3e6fa169d8541a798752a1c1ca0cb20",
+        )];
+        check_slip10_vector::<Nist256p1>(
+            "a7305bc8df8d0951f0cb224c0e95d7707cbdf2c6ce7e8d481fec69c7ff5e9446",
+            &[],
+            &expected,
+        );
+    }
+
+    #[test]
+    fn test_slip10_ed25519_hardened_only() {
+        let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+        let master = ExtendedPrivateKey::<Ed25519>::from_seed(&seed).unwrap();
+        assert_eq!(
+            master.derive_child(0).unwrap_err(),
+            KeyPairError::InvalidSecretKey
+        );
+        assert_eq!(
+            master.public().derive_child(0).unwrap_err(),
+            KeyPairError::InvalidPublicKey
+        );
+
+        let path: DerivationPath = "m/44'/501'/0'/0'".parse().unwrap();
+        let key = master.derive_path(path.indices()).unwrap();
+        let xpub = key.public().to_xpub(XPUB_VERSION);
+        let public = ExtendedPublicKey::<Ed25519>::from_xpub(&xpub, XPUB_VERSION).unwrap();
+        assert_eq!(public.public().to_bytes(), key.public().public().to_bytes());
+        assert_eq!(public.fingerprint(), key.fingerprint());
+        assert_eq!(public.parent_fingerprint(), key.parent_fingerprint());
+
+        let xprv = key.to_xprv(XPRV_VERSION);
+        let private = ExtendedPrivateKey::<Ed25519>::from_xprv(&xprv, XPRV_VERSION).unwrap();
+        assert_eq!(
+            private.private().public().to_bytes(),
+            key.private().public().to_bytes()
+        );
+    }
+
+    /// https://github.com/bitcoin/bips/blob/master/bip-0049.mediawiki#test-vectors
+    /// https://github.com/bitcoin/bips/blob/master/bip-0084.mediawiki#test-vectors
+    #[test]
+    fn test_slip132_versions() {
+        use slip132::{KeyVersion, Network, ScriptType};
+
+        let seed = hex::decode("5eb00bbddcf069084889a8ab9155568165f5c453ccb85e70811aaed6f6da5fc19a5ac40b389cd370d086206dec8aa6c43daea6690f20ad3d8d48b2d2ce9e38e4").unwrap();
+        let master = ExtendedPrivateKey::<Secp256k1>::from_seed(&seed).unwrap();
+        let tests = [
+            (
+                "m/49'/0'/0'",
+                KeyVersion::new(ScriptType::P2shP2wpkh, Network::Mainnet),
+                "ypub6Ww3ibxVfGzLrAH1PNcjyAWenMTbbAosGNB6VvmSEgytSER9azLDWCxoJwW7Ke7icmizBMXrzBx9979FfaHxHcrArf3zbeJJJUZPf663zsP",
+                Some("yprvAHwhK6RbpuS3dgCYHM5jc2ZvEKd7Bi61u9FVhYMpgMSuZS613T1xxQeKTffhrHY79hZ5PsskBjcc6C2V7DrnsMsNaGDaWev3GLRQRgV7hxF"),
+            ),
+            (
+                "m/84'/0'/0'",
+                KeyVersion::new(ScriptType::P2wpkh, Network::Mainnet),
+                "zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs",
+                Some("zprvAdG4iTXWBoARxkkzNpNh8r6Qag3irQB8PzEMkAFeTRXxHpbF9z4QgEvBRmfvqWvGp42t42nvgGpNgYSJA9iefm1yYNZKEm7z6qUWCroSQnE"),
+            ),
+            (
+                "m/49'/1'/0'",
+                KeyVersion::new(ScriptType::P2shP2wpkh, Network::Testnet),
+                "upub5EFU65HtV5TeiSHmZZm7FUffBGy8UKeqp7vw43jYbvZPpoVsgU93oac7Wk3u6moKegAEWtGNF8DehrnHtv21XXEMYRUocHqguyjknFHYfgY",
+                None,
+            ),
+            (
+                "m/84'/1'/0'",
+                KeyVersion::new(ScriptType::P2wpkh, Network::Testnet),
+                "vpub5Y6cjg78GGuNLsaPhmYsiw4gYX3HoQiRBiSwDaBXKUafCt9bNwWQiitDk5VZ5BVxYnQdwoTyXSs2JHRPAgjAvtbBrf8ZhDYe2jWAqvZVnsc",
+                None,
+            ),
+        ];
+        for (path, version, expected_xpub, expected_xprv) in tests {
+            let path: DerivationPath = path.parse().unwrap();
+            let key = master.derive_path(path.indices()).unwrap();
+            assert_eq!(
+                version.script_type.purpose() as u32 + HARDENED_OFFSET,
+                path.indices()[0]
+            );
+
+            let xpub = key.public().to_slip132(version);
+            assert_eq!(xpub, expected_xpub);
+            let (public, actual) = ExtendedPublicKey::<Secp256k1>::from_slip132(&xpub).unwrap();
+            assert_eq!(actual, version);
+            assert!(public.public() == key.public().public());
+            // The version bytes are verified.
+            assert!(ExtendedPublicKey::<Secp256k1>::from_xpub(&xpub, XPUB_VERSION).is_err());
+
+            let xprv = key.to_slip132(version);
+            if let Some(expected_xprv) = expected_xprv {
+                assert_eq!(xprv.as_str(), expected_xprv);
+            }
+            let (private, actual) = ExtendedPrivateKey::<Secp256k1>::from_slip132(&xprv).unwrap();
+            assert_eq!(actual, version);
+            assert_eq!(private.fingerprint(), key.fingerprint());
+        }
+
+        let xpub = master.public().to_xpub(XPUB_VERSION);
+        let (_, version) = ExtendedPublicKey::<Secp256k1>::from_slip132(&xpub).unwrap();
+        assert_eq!(
+            version,
+            KeyVersion::new(ScriptType::P2pkh, Network::Mainnet)
+        );
+        assert_eq!(
+            ExtendedPrivateKey::<Secp256k1>::from_slip132(&xpub)
+                .err()
+                .unwrap(),
+            KeyPairError::InvalidSecretKey
+        );
+        assert_eq!(
+            ExtendedPublicKey::<Secp256k1>::from_slip132(&xpub[1..])
+                .err()
+                .unwrap(),
+            KeyPairError::InvalidPublicKey
+        );
+    }
+}
@@ -0,0 +1,280 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::bip32::{is_hardened, HARDENED_OFFSET};
+use crate::{KeyPairError, KeyPairResult};
+use std::fmt;
+use std::str::FromStr;
+
+/// The number of levels of a standard path `m/purpose'/coin'/account'/change/address_index`.
+const STANDARD_DEPTH: usize = 5;
+/// The number of hardened levels of a standard path, i.e. `purpose`, `coin` and `account`.
+const HARDENED_LEVELS: usize = 3;
+/// The prefix of a path relative to the master key.
+const MASTER_PREFIX: &str = "m";
+const SEPARATOR: char = '/';
+
+/// The purpose of a standard derivation path, i.e. the type of addresses that it derives keys for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Purpose {
+    /// [BIP44](https://github.com/bitcoin/bips/blob/master/bip-0044.mediawiki): P2PKH and account-based coins.
+    Bip44 = 44,
+    /// [BIP49](https://github.com/bitcoin/bips/blob/master/bip-0049.mediawiki): P2WPKH nested in P2SH.
+    Bip49 = 49,
+    /// [BIP84](https://github.com/bitcoin/bips/blob/master/bip-0084.mediawiki): P2WPKH.
+    Bip84 = 84,
+    /// [BIP86](https://github.com/bitcoin/bips/blob/master/bip-0086.mediawiki): single key P2TR.
+    Bip86 = 86,
+}
+
+impl Purpose {
+    fn from_index(index: u32) -> Option<Purpose> {
+        match index {
+            44 => Some(Purpose::Bip44),
+            49 => Some(Purpose::Bip49),
+            84 => Some(Purpose::Bip84),
+            86 => Some(Purpose::Bip86),
+            _ => None,
+        }
+    }
+}
+
+/// The suffix that marks the hardened indices of a serialized path.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HardenedNotation {
+    /// `m/44'/0'`, the most common notation.
+    #[default]
+    Apostrophe,
+    /// `m/44h/0h`, e.g. to avoid quoting the path in a shell or a descriptor.
+    LowerH,
+    /// `m/44H/0H`.
+    UpperH,
+}
+
+impl HardenedNotation {
+    fn suffix(self) -> char {
+        match self {
+            HardenedNotation::Apostrophe => '\'',
+            HardenedNotation::LowerH => 'h',
+            HardenedNotation::UpperH => 'H',
+        }
+    }
+}
+
+/// The reason why a derivation path can't be parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DerivationPathError {
+    /// The path is an empty string.
+    Empty,
+    /// The component is not a child index, e.g. it's empty, not a number, or exceeds `2^31 - 1`.
+    InvalidComponent {
+        /// The zero-based position of the component, not counting the `m` prefix.
+        position: usize,
+        component: String,
+    },
+}
+
+impl From<DerivationPathError> for KeyPairError {
+    fn from(_: DerivationPathError) -> Self {
+        KeyPairError::InvalidSecretKey
+    }
+}
+
+/// Represents a BIP32 derivation path, e.g. `m/44'/0'/0'/0/0`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DerivationPath {
+    indices: Vec<u32>,
+}
+
+impl DerivationPath {
+    /// Creates a derivation path from the given child indices,
+    /// where hardened indices are offset by [`HARDENED_OFFSET`].
+    pub fn new(indices: Vec<u32>) -> DerivationPath {
+        DerivationPath { indices }
+    }
+
+    /// Creates a standard path `m/purpose'/coin'/account'/change/address_index`,
+    /// where `coin` is the [SLIP-44](https://github.com/satoshilabs/slips/blob/master/slip-0044.md) coin type,
+    /// e.g. [`super::slip44::BITCOIN`].
+    ///
+    /// The indices must not exceed [`HARDENED_OFFSET`] as `purpose`, `coin` and `account` are hardened here.
+    pub fn standard(
+        purpose: Purpose,
+        coin: u32,
+        account: u32,
+        change: u32,
+        address_index: u32,
+    ) -> KeyPairResult<DerivationPath> {
+        let levels = [purpose as u32, coin, account, change, address_index];
+        if levels.iter().any(|index| is_hardened(*index)) {
+            return Err(KeyPairError::InvalidSecretKey);
+        }
+        Ok(DerivationPath {
+            indices: vec![
+                purpose as u32 + HARDENED_OFFSET,
+                coin + HARDENED_OFFSET,
+                account + HARDENED_OFFSET,
+                change,
+                address_index,
+            ],
+        })
+    }
+
+    /// Creates a BIP44 path `m/44'/coin'/account'/change/address_index`.
+    pub fn bip44(
+        coin: u32,
+        account: u32,
+        change: u32,
+        address_index: u32,
+    ) -> KeyPairResult<DerivationPath> {
+        Self::standard(Purpose::Bip44, coin, account, change, address_index)
+    }
+
+    /// Creates a BIP49 path `m/49'/coin'/account'/change/address_index`.
+    pub fn bip49(
+        coin: u32,
+        account: u32,
+        change: u32,
+        address_index: u32,
+    ) -> KeyPairResult<DerivationPath> {
+        Self::standard(Purpose::Bip49, coin, account, change, address_index)
+    }
+
+    /// Creates a BIP84 path `m/84'/coin'/account'/change/address_index`.
+    pub fn bip84(
+        coin: u32,
+        account: u32,
+        change: u32,
+        address_index: u32,
+    ) -> KeyPairResult<DerivationPath> {
+        Self::standard(Purpose::Bip84, coin, account, change, address_index)
+    }
+
+    /// Creates a BIP86 path `m/86'/coin'/account'/change/address_index`.
+    pub fn bip86(
+        coin: u32,
+        account: u32,
+        change: u32,
+        address_index: u32,
+    ) -> KeyPairResult<DerivationPath> {
+        Self::standard(Purpose::Bip86, coin, account, change, address_index)
+    }
+
+    /// Returns the child indices.
+    pub fn indices(&self) -> &[u32] {
+        &self.indices
+    }
+
+    /// Returns the purpose if this is a standard path.
+    pub fn purpose(&self) -> Option<Purpose> {
+        self.standard_levels()
+            .and_then(|levels| Purpose::from_index(levels[0]))
+    }
+
+    /// Returns the SLIP-44 coin type if this is a standard path.
+    pub fn coin(&self) -> Option<u32> {
+        self.standard_levels().map(|levels| levels[1])
+    }
+
+    /// Returns the account if this is a standard path.
+    pub fn account(&self) -> Option<u32> {
+        self.standard_levels().map(|levels| levels[2])
+    }
+
+    /// Returns the change, i.e. `0` for receiving and `1` for change addresses, if this is a standard path.
+    pub fn change(&self) -> Option<u32> {
+        self.standard_levels().map(|levels| levels[3])
+    }
+
+    /// Returns the address index if this is a standard path.
+    pub fn address_index(&self) -> Option<u32> {
+        self.standard_levels().map(|levels| levels[4])
+    }
+
+    /// Serializes the path with the `m` prefix and the given hardened `notation`.
+    /// The [`fmt::Display`] implementation uses [`HardenedNotation::Apostrophe`].
+    pub fn to_string_with(&self, notation: HardenedNotation) -> String {
+        let mut path = String::from(MASTER_PREFIX);
+        for index in self.indices.iter() {
+            path.push(SEPARATOR);
+            if is_hardened(*index) {
+                path.push_str(&(index - HARDENED_OFFSET).to_string());
+                path.push(notation.suffix());
+            } else {
+                path.push_str(&index.to_string());
+            }
+        }
+        path
+    }
+
+    /// Returns the levels without the hardened offset if the path is `m/a'/b'/c'/d/e`.
+    fn standard_levels(&self) -> Option<[u32; STANDARD_DEPTH]> {
+        let mut levels: [u32; STANDARD_DEPTH] = self.indices.as_slice().try_into().ok()?;
+        for level in levels.iter_mut().take(HARDENED_LEVELS) {
+            if !is_hardened(*level) {
+                return None;
+            }
+            *level -= HARDENED_OFFSET;
+        }
+        if levels[HARDENED_LEVELS..]
+            .iter()
+            .any(|level| is_hardened(*level))
+        {
+            return None;
+        }
+        Some(levels)
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = DerivationPathError;
+
+    /// Parses a path like `m/44'/0'/0'/0/0`, or a bare path like `44'/0'/0'/0/0`.
+    /// Hardened indices are marked with either `'`, `h` or `H`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(DerivationPathError::Empty);
+        }
+        if s == MASTER_PREFIX {
+            return Ok(DerivationPath::default());
+        }
+        let components = s
+            .strip_prefix(MASTER_PREFIX)
+            .and_then(|rest| rest.strip_prefix(SEPARATOR))
+            .unwrap_or(s);
+
+        let indices = components
+            .split(SEPARATOR)
+            .enumerate()
+            .map(|(position, component)| {
+                parse_index(component).ok_or_else(|| DerivationPathError::InvalidComponent {
+                    position,
+                    component: component.to_string(),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(DerivationPath { indices })
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_string_with(HardenedNotation::Apostrophe))
+    }
+}
+
+/// Parses a child index like `0`, `0'`, `0h` or `0H`.
+fn parse_index(component: &str) -> Option<u32> {
+    let (number, offset) = match component.strip_suffix(['\'', 'h', 'H']) {
+        Some(number) => (number, HARDENED_OFFSET),
+        None => (component, 0),
+    };
+    if number.is_empty() || !number.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    match number.parse::<u32>() {
+        Ok(index) if !is_hardened(index) => Some(index + offset),
+        _ => None,
+    }
+}
@@ -0,0 +1,116 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! [SLIP-132](https://github.com/satoshilabs/slips/blob/master/slip-0132.md) version bytes of extended keys,
+//! which also tell the script type of the addresses derived from the key, e.g. `zpub` for P2WPKH.
+
+use crate::bip32::{Purpose, TPRV_VERSION, TPUB_VERSION, XPRV_VERSION, XPUB_VERSION};
+
+/// Version bytes of a mainnet P2SH-P2WPKH extended private key (`yprv`).
+pub const YPRV_VERSION: u32 = 0x049d_7878;
+/// Version bytes of a mainnet P2SH-P2WPKH extended public key (`ypub`).
+pub const YPUB_VERSION: u32 = 0x049d_7cb2;
+/// Version bytes of a mainnet P2WPKH extended private key (`zprv`).
+pub const ZPRV_VERSION: u32 = 0x04b2_430c;
+/// Version bytes of a mainnet P2WPKH extended public key (`zpub`).
+pub const ZPUB_VERSION: u32 = 0x04b2_4746;
+/// Version bytes of a testnet P2SH-P2WPKH extended private key (`uprv`).
+pub const UPRV_VERSION: u32 = 0x044a_4e28;
+/// Version bytes of a testnet P2SH-P2WPKH extended public key (`upub`).
+pub const UPUB_VERSION: u32 = 0x044a_5262;
+/// Version bytes of a testnet P2WPKH extended private key (`vprv`).
+pub const VPRV_VERSION: u32 = 0x045f_18bc;
+/// Version bytes of a testnet P2WPKH extended public key (`vpub`).
+pub const VPUB_VERSION: u32 = 0x045f_1cf6;
+
+/// The script type of the addresses derived from an extended key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScriptType {
+    P2pkh,
+    /// P2WPKH nested in P2SH.
+    P2shP2wpkh,
+    P2wpkh,
+}
+
+impl ScriptType {
+    /// Returns the purpose of the standard derivation paths of the script type.
+    pub fn purpose(self) -> Purpose {
+        match self {
+            ScriptType::P2pkh => Purpose::Bip44,
+            ScriptType::P2shP2wpkh => Purpose::Bip49,
+            ScriptType::P2wpkh => Purpose::Bip84,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Network {
+    Mainnet,
+    Testnet,
+}
+
+/// The version bytes of an extended key pair.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeyVersion {
+    pub script_type: ScriptType,
+    pub network: Network,
+}
+
+/// cbindgen:ignore
+impl KeyVersion {
+    /// All the supported key versions.
+    const ALL: [KeyVersion; 6] = [
+        KeyVersion::new(ScriptType::P2pkh, Network::Mainnet),
+        KeyVersion::new(ScriptType::P2pkh, Network::Testnet),
+        KeyVersion::new(ScriptType::P2shP2wpkh, Network::Mainnet),
+        KeyVersion::new(ScriptType::P2shP2wpkh, Network::Testnet),
+        KeyVersion::new(ScriptType::P2wpkh, Network::Mainnet),
+        KeyVersion::new(ScriptType::P2wpkh, Network::Testnet),
+    ];
+
+    pub const fn new(script_type: ScriptType, network: Network) -> KeyVersion {
+        KeyVersion {
+            script_type,
+            network,
+        }
+    }
+
+    /// Returns the version bytes of the extended private key.
+    pub fn private(&self) -> u32 {
+        match (self.script_type, self.network) {
+            (ScriptType::P2pkh, Network::Mainnet) => XPRV_VERSION,
+            (ScriptType::P2pkh, Network::Testnet) => TPRV_VERSION,
+            (ScriptType::P2shP2wpkh, Network::Mainnet) => YPRV_VERSION,
+            (ScriptType::P2shP2wpkh, Network::Testnet) => UPRV_VERSION,
+            (ScriptType::P2wpkh, Network::Mainnet) => ZPRV_VERSION,
+            (ScriptType::P2wpkh, Network::Testnet) => VPRV_VERSION,
+        }
+    }
+
+    /// Returns the version bytes of the extended public key.
+    pub fn public(&self) -> u32 {
+        match (self.script_type, self.network) {
+            (ScriptType::P2pkh, Network::Mainnet) => XPUB_VERSION,
+            (ScriptType::P2pkh, Network::Testnet) => TPUB_VERSION,
+            (ScriptType::P2shP2wpkh, Network::Mainnet) => YPUB_VERSION,
+            (ScriptType::P2shP2wpkh, Network::Testnet) => UPUB_VERSION,
+            (ScriptType::P2wpkh, Network::Mainnet) => ZPUB_VERSION,
+            (ScriptType::P2wpkh, Network::Testnet) => VPUB_VERSION,
+        }
+    }
+
+    /// Returns the key version of the given extended private key `version` bytes.
+    pub fn from_private(version: u32) -> Option<KeyVersion> {
+        KeyVersion::ALL
+            .into_iter()
+            .find(|candidate| candidate.private() == version)
+    }
+
+    /// Returns the key version of the given extended public key `version` bytes.
+    pub fn from_public(version: u32) -> Option<KeyVersion> {
+        KeyVersion::ALL
+            .into_iter()
+            .find(|candidate| candidate.public() == version)
+    }
+}
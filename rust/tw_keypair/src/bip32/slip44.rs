@@ -0,0 +1,21 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! [SLIP-44](https://github.com/satoshilabs/slips/blob/master/slip-0044.md) coin types of the standard derivation paths.
+
+pub const BITCOIN: u32 = 0;
+/// The coin type of all testnets.
+pub const TESTNET: u32 = 1;
+pub const LITECOIN: u32 = 2;
+pub const DOGECOIN: u32 = 3;
+pub const DASH: u32 = 5;
+pub const ETHEREUM: u32 = 60;
+pub const COSMOS: u32 = 118;
+pub const ZCASH: u32 = 133;
+pub const RIPPLE: u32 = 144;
+pub const BITCOIN_CASH: u32 = 145;
+pub const TRON: u32 = 195;
+pub const POLKADOT: u32 = 354;
+pub const SOLANA: u32 = 501;
+pub const CARDANO: u32 = 1815;
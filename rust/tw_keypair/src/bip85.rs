@@ -0,0 +1,226 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! [BIP85](https://github.com/bitcoin/bips/blob/master/bip-0085.mediawiki) deterministic entropy
+//! derived from a root extended private key, so a single backup spawns independent child wallets:
+//!
+//! ```rust,ignore
+//! use tw_keypair::bip32::{ExtendedPrivateKey, Secp256k1, XPRV_VERSION};
+//! use tw_keypair::bip85;
+//! use tw_keypair::mnemonic::Language;
+//!
+//! let root = ExtendedPrivateKey::<Secp256k1>::from_xprv(ROOT_XPRV, XPRV_VERSION)?;
+//! let app_wallet = bip85::derive_mnemonic(&root, Language::English, 24, APP_INDEX)?;
+//! ```
+//!
+//! The entropy of the application path `m/83696968'/app'/...'` is `HMAC-SHA512(key = "bip-entropy-from-k", data = k)`,
+//! where `k` is the private key derived at that path.
+
+use crate::bip32::{is_hardened, Bip32Curve, ExtendedPrivateKey, Secp256k1, HARDENED_OFFSET};
+use crate::ecdsa::secp256k1::{self, WifInfo, WifNetwork};
+use crate::mnemonic::{Language, Mnemonic};
+use crate::{KeyPairError, KeyPairResult};
+use tw_hash::hmac::hmac_sha512;
+use tw_hash::{H256, H512};
+use zeroize::Zeroizing;
+
+/// The purpose index of the BIP85 paths.
+const PURPOSE: u32 = 83696968;
+const HMAC_KEY: &[u8] = b"bip-entropy-from-k";
+
+/// The application indices.
+const APP_BIP39: u32 = 39;
+const APP_WIF: u32 = 2;
+const APP_XPRV: u32 = 32;
+const APP_HEX: u32 = 128169;
+
+/// The length range of the HEX application entropy.
+const MIN_HEX_LEN: usize = 16;
+const MAX_HEX_LEN: usize = 64;
+
+/// Derives the 64 byte entropy at the path `m/83696968'/app_path'`.
+/// The `app_path` indices are hardened, so they must not exceed [`HARDENED_OFFSET`].
+pub fn derive_entropy(
+    root: &ExtendedPrivateKey<Secp256k1>,
+    app_path: &[u32],
+) -> KeyPairResult<Zeroizing<H512>> {
+    let path = std::iter::once(&PURPOSE)
+        .chain(app_path)
+        .map(|index| {
+            if is_hardened(*index) {
+                return Err(KeyPairError::InvalidSecretKey);
+            }
+            Ok(index + HARDENED_OFFSET)
+        })
+        .collect::<KeyPairResult<Vec<_>>>()?;
+
+    let key = root.derive_path(&path)?;
+    let k = Secp256k1::private_to_bytes(key.private());
+    let entropy = Zeroizing::new(hmac_sha512(HMAC_KEY, k.as_slice()));
+    Ok(Zeroizing::new(
+        H512::try_from(entropy.as_slice()).expect("Expected a 64 byte HMAC-SHA512"),
+    ))
+}
+
+/// Derives a child mnemonic of `word_count` words, i.e. `12`, `18` or `24`,
+/// at the path `m/83696968'/39'/language'/word_count'/index'`.
+pub fn derive_mnemonic(
+    root: &ExtendedPrivateKey<Secp256k1>,
+    language: Language,
+    word_count: usize,
+    index: u32,
+) -> KeyPairResult<Mnemonic> {
+    let entropy_len = match word_count {
+        12 => 16,
+        18 => 24,
+        24 => 32,
+        _ => return Err(KeyPairError::InvalidMnemonic),
+    };
+    let path = [APP_BIP39, language_code(language), word_count as u32, index];
+    let entropy = derive_entropy(root, &path)?;
+    Mnemonic::from_entropy(language, &entropy[..entropy_len])
+}
+
+/// Derives a child `secp256k1` private key encoded as a compressed mainnet WIF,
+/// at the path `m/83696968'/2'/index'`.
+pub fn derive_wif(
+    root: &ExtendedPrivateKey<Secp256k1>,
+    index: u32,
+) -> KeyPairResult<Zeroizing<String>> {
+    let entropy = derive_entropy(root, &[APP_WIF, index])?;
+    let private = secp256k1::PrivateKey::try_from(&entropy[..H256::LEN])?;
+    Ok(private.to_wif(WifInfo {
+        network: WifNetwork::Mainnet,
+        compressed: true,
+    }))
+}
+
+/// Derives a child root key at the path `m/83696968'/32'/index'`,
+/// where the chain code is the first and the private key the second half of the entropy.
+pub fn derive_xprv(
+    root: &ExtendedPrivateKey<Secp256k1>,
+    index: u32,
+) -> KeyPairResult<ExtendedPrivateKey<Secp256k1>> {
+    let entropy = derive_entropy(root, &[APP_XPRV, index])?;
+    let (chain_code, private) = entropy.split_at(H256::LEN);
+    let chain_code = H256::try_from(chain_code).expect("Expected a 32 byte chain code");
+    let private = Secp256k1::private_from_bytes(private)?;
+    Ok(ExtendedPrivateKey::new_master(private, chain_code))
+}
+
+/// Derives `len` bytes (16 to 64) of raw entropy at the path `m/83696968'/128169'/len'/index'`.
+pub fn derive_hex(
+    root: &ExtendedPrivateKey<Secp256k1>,
+    len: usize,
+    index: u32,
+) -> KeyPairResult<Zeroizing<Vec<u8>>> {
+    if !(MIN_HEX_LEN..=MAX_HEX_LEN).contains(&len) {
+        return Err(KeyPairError::InvalidSecretKey);
+    }
+    let entropy = derive_entropy(root, &[APP_HEX, len as u32, index])?;
+    Ok(Zeroizing::new(entropy[..len].to_vec()))
+}
+
+/// Returns the BIP85 code of the wordlist `language`.
+fn language_code(language: Language) -> u32 {
+    match language {
+        Language::English => 0,
+        Language::Japanese => 1,
+        Language::Korean => 2,
+        Language::Spanish => 3,
+        Language::ChineseSimplified => 4,
+        Language::ChineseTraditional => 5,
+        Language::French => 6,
+        Language::Italian => 7,
+        Language::Czech => 8,
+        Language::Portuguese => 9,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bip32::XPRV_VERSION;
+    use tw_encoding::hex;
+
+    const ROOT: &str = "xprv9s21ZrQH143K2LBWUUQRFXhucrQqBpKdRRxNVq2zBqsx8HVqFk2uYo8kmbaLLHRdqtQpUm98uKfu3vca1LqdGhUtyoFnCNkfmXRyPXLjbKb";
+
+    fn root() -> ExtendedPrivateKey<Secp256k1> {
+        ExtendedPrivateKey::from_xprv(ROOT, XPRV_VERSION).unwrap()
+    }
+
+    /// https://github.com/bitcoin/bips/blob/master/bip-0085.mediawiki#test-vectors
+    #[test]
+    fn test_derive_entropy() {
+        let entropy = derive_entropy(&root(), &[0, 0]).unwrap();
+        let expected = "efecfbccffea313214232d29e71563d941229afb4338c21f9517c41aaa0d16f00b83d2a09ef747e7a64e8e2bd5a14869e693da66ce94ac2da570ab7ee48618f7";
+        assert_eq!(hex::encode(entropy.as_slice(), false), expected);
+
+        let entropy = derive_entropy(&root(), &[0, 1]).unwrap();
+        let expected = "70c6e3e8ebee8dc4c0dbba66076819bb8c09672527c4277ca8729532ad711872218f826919f6b67218adde99018a6df9095ab2b58d803b5b93ec9802085a690e";
+        assert_eq!(hex::encode(entropy.as_slice(), false), expected);
+
+        let err = derive_entropy(&root(), &[HARDENED_OFFSET]).unwrap_err();
+        assert_eq!(err, KeyPairError::InvalidSecretKey);
+    }
+
+    #[test]
+    fn test_derive_mnemonic() {
+        let vectors = [
+            (12, "girl mad pet galaxy egg matter matrix prison refuse sense ordinary nose"),
+            (18, "near account window bike charge season chef number sketch tomorrow excuse sniff circle vital hockey outdoor supply token"),
+            (24, "puppy ocean match cereal symbol another shed magic wrap hammer bulb intact gadget divorce twin tonight reason outdoor destroy simple truth cigar social volcano"),
+        ];
+        for (word_count, expected) in vectors {
+            let mnemonic = derive_mnemonic(&root(), Language::English, word_count, 0).unwrap();
+            assert_eq!(mnemonic.phrase().as_str(), expected);
+        }
+
+        // Different indices and languages derive independent mnemonics.
+        let first = derive_mnemonic(&root(), Language::English, 12, 0).unwrap();
+        let second = derive_mnemonic(&root(), Language::English, 12, 1).unwrap();
+        assert_ne!(first.to_entropy(), second.to_entropy());
+        let spanish = derive_mnemonic(&root(), Language::Spanish, 12, 0).unwrap();
+        assert_eq!(spanish.language(), Language::Spanish);
+        assert_ne!(first.to_entropy(), spanish.to_entropy());
+
+        let err = derive_mnemonic(&root(), Language::English, 15, 0).err();
+        assert_eq!(err, Some(KeyPairError::InvalidMnemonic));
+    }
+
+    #[test]
+    fn test_derive_wif() {
+        let wif = derive_wif(&root(), 0).unwrap();
+        assert_eq!(
+            wif.as_str(),
+            "Kzyv4uF39d4Jrw2W7UryTHwZr1zQVNk4dAFyqE6BuMrMh1Za7uhp"
+        );
+    }
+
+    #[test]
+    fn test_derive_xprv() {
+        let xprv = derive_xprv(&root(), 0).unwrap();
+        assert_eq!(
+            xprv.to_xprv(XPRV_VERSION).as_str(),
+            "xprv9s21ZrQH143K2srSbCSg4m4kLvPMzcWydgmKEnMmoZUurYuBuYG46c6P71UGXMzmriLzCCBvKQWBUv3vPB3m1SATMhp3uEjXHJ42jFg7myX"
+        );
+    }
+
+    #[test]
+    fn test_derive_hex() {
+        let entropy = derive_hex(&root(), 64, 0).unwrap();
+        let expected = "492db4698cf3b73a5a24998aa3e9d7fa96275d85724a91e71aa2d645442f878555d078fd1f1f67e368976f04137b1f7a0d19232136ca50c44614af72b5582a5c";
+        assert_eq!(hex::encode(entropy.as_slice(), false), expected);
+
+        assert_eq!(derive_hex(&root(), 16, 0).unwrap().len(), 16);
+        assert_eq!(
+            derive_hex(&root(), 15, 0).unwrap_err(),
+            KeyPairError::InvalidSecretKey
+        );
+        assert_eq!(
+            derive_hex(&root(), 65, 0).unwrap_err(),
+            KeyPairError::InvalidSecretKey
+        );
+    }
+}
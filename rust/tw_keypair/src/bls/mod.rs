@@ -0,0 +1,220 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! BLS12-381 signatures as specified in [draft-irtf-cfrg-bls-signature](https://datatracker.ietf.org/doc/html/draft-irtf-cfrg-bls-signature-05).
+//!
+//! There are two variants that differ in the groups public keys and signatures belong to:
+//! * [`min_pk`] - 48 byte public keys in G1 and 96 byte signatures in G2 (Ethereum, Chia).
+//! * [`min_sig`] - 96 byte public keys in G2 and 48 byte signatures in G1.
+
+#[macro_use]
+mod variant;
+
+/// Minimal-pubkey-size variant: public keys in G1, signatures in G2.
+pub mod min_pk {
+    /// The proof-of-possession scheme domain separation tag (Ethereum).
+    pub const POP_DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+    /// The message augmentation scheme domain separation tag (Chia).
+    pub const AUG_DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_AUG_";
+
+    bls_variant!(min_pk, public_len = 48, signature_len = 96);
+}
+
+/// Minimal-signature-size variant: public keys in G2, signatures in G1.
+pub mod min_sig {
+    /// The proof-of-possession scheme domain separation tag.
+    pub const POP_DST: &[u8] = b"BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_POP_";
+    /// The message augmentation scheme domain separation tag.
+    pub const AUG_DST: &[u8] = b"BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_AUG_";
+
+    bls_variant!(min_sig, public_len = 96, signature_len = 48);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::{KeyPairTrait, SigningKeyTrait, VerifyingKeyTrait};
+    use rand_core::OsRng;
+    use tw_encoding::hex;
+    use tw_misc::traits::{ToBytesVec, ToBytesZeroizing};
+
+    /// Taken from the Ethereum consensus spec BLS test generator.
+    const SECRETS: [&str; 3] = [
+        "263dbd792f5b1be47ed85f8938c0f29586af0d3ac7b977f21c278fe1462040e3",
+        "47b8192d77bf871b62e87859d653922725724a5c031afeabc60bcef5ff665138",
+        "328388aff0d4a5b7dc9205abd374e7e98f3cd9f3418edb4eafda5fb16473d216",
+    ];
+
+    #[test]
+    fn test_min_pk_private_to_public() {
+        let expected = [
+            "a491d1b0ecd9bb917989f0e74f0dea0422eac4a873e5e2644f368dffb9a6e20fd6e10c1b77654d067c0618f6e5a7f79a",
+            "b301803f8b5ac4a1133581fc676dfedc60d891dd5fa99028805e5ea5b08d3491af75d0707adab3b70c6a6a580217bf81",
+            "b53d21a4cfd562c469cc81514d4ce5a6b577d8403d32a394dc265dd190b47fa9f829fdd7963afdf972e5e77854051f6f",
+        ];
+        for (secret, expected) in SECRETS.iter().zip(expected) {
+            let private = min_pk::PrivateKey::try_from(*secret).unwrap();
+            assert_eq!(
+                private.to_zeroizing_vec().as_slice(),
+                hex::decode(secret).unwrap()
+            );
+            assert_eq!(hex::encode(private.public().to_vec(), false), expected);
+        }
+    }
+
+    #[test]
+    fn test_min_sig_private_to_public() {
+        let private = min_sig::PrivateKey::try_from(SECRETS[0]).unwrap();
+        let expected = "ac400b70f6f8cd35648f5c126cce5417f3be4d8eefbd42ceb4286a14df7e03135313fe5845e3a575faab3e8b949d248814856c22d8cdb2967c720e963eedc999e738373b14172f06fc915769d3cc5ab7ae0a1b9c38f48b5585fb09d4bd2733bb";
+        assert_eq!(hex::encode(private.public().to_vec(), false), expected);
+    }
+
+    #[test]
+    fn test_min_pk_sign_verify() {
+        let keypair = min_pk::KeyPair::try_from(SECRETS[0]).unwrap();
+        let message = b"Hello".to_vec();
+
+        let signature = keypair.sign(message.clone()).unwrap();
+        assert_eq!(signature.to_vec().len(), min_pk::Signature::LEN);
+        // BLS signatures are deterministic.
+        assert_eq!(
+            signature.to_vec(),
+            keypair.sign(message.clone()).unwrap().to_vec()
+        );
+
+        assert!(keypair.verify(signature.clone(), message.clone()));
+        assert!(!keypair.verify(signature.clone(), b"Hello!".to_vec()));
+        assert!(!keypair
+            .public()
+            .verify_with_dst(&signature, &message, min_pk::AUG_DST));
+    }
+
+    #[test]
+    fn test_min_sig_sign_verify() {
+        let keypair = min_sig::KeyPair::try_from(SECRETS[1]).unwrap();
+        let message = b"Hello".to_vec();
+
+        let signature = keypair.private().sign_with_dst(&message, min_sig::AUG_DST);
+        assert_eq!(signature.to_vec().len(), min_sig::Signature::LEN);
+        assert!(keypair
+            .public()
+            .verify_with_dst(&signature, &message, min_sig::AUG_DST));
+        assert!(!keypair.verify(signature, message));
+    }
+
+    #[test]
+    fn test_min_pk_aggregate_same_message() {
+        let message = b"Hello";
+        let keys: Vec<_> = SECRETS
+            .iter()
+            .map(|secret| min_pk::PrivateKey::try_from(*secret).unwrap())
+            .collect();
+        let publics: Vec<_> = keys.iter().map(|key| key.public()).collect();
+        let signatures: Vec<_> = keys
+            .iter()
+            .map(|key| key.sign_with_dst(message, min_pk::POP_DST))
+            .collect();
+
+        let aggregated = min_pk::aggregate_signatures(&signatures).unwrap();
+        assert!(min_pk::fast_aggregate_verify(
+            &aggregated,
+            message,
+            &publics,
+            min_pk::POP_DST
+        ));
+        assert!(!min_pk::fast_aggregate_verify(
+            &aggregated,
+            message,
+            &publics[1..],
+            min_pk::POP_DST
+        ));
+
+        // The aggregated signature is valid for the aggregated public key.
+        let aggregated_public = min_pk::aggregate_public_keys(&publics).unwrap();
+        assert!(aggregated_public.verify_with_dst(&aggregated, message, min_pk::POP_DST));
+    }
+
+    #[test]
+    fn test_min_sig_aggregate_distinct_messages() {
+        let messages: [&[u8]; 3] = [b"first", b"second", b"third"];
+        let keys: Vec<_> = SECRETS
+            .iter()
+            .map(|secret| min_sig::PrivateKey::try_from(*secret).unwrap())
+            .collect();
+        let publics: Vec<_> = keys.iter().map(|key| key.public()).collect();
+        let signatures: Vec<_> = keys
+            .iter()
+            .zip(messages)
+            .map(|(key, message)| key.sign_with_dst(message, min_sig::POP_DST))
+            .collect();
+
+        let aggregated = min_sig::aggregate_signatures(&signatures).unwrap();
+        assert!(min_sig::aggregate_verify(
+            &aggregated,
+            &messages,
+            &publics,
+            min_sig::POP_DST
+        ));
+
+        let reordered: [&[u8]; 3] = [b"second", b"first", b"third"];
+        assert!(!min_sig::aggregate_verify(
+            &aggregated,
+            &reordered,
+            &publics,
+            min_sig::POP_DST
+        ));
+        assert!(!min_sig::aggregate_verify(
+            &aggregated,
+            &messages[1..],
+            &publics,
+            min_sig::POP_DST
+        ));
+    }
+
+    #[test]
+    fn test_aggregate_empty() {
+        min_pk::aggregate_signatures(&[]).unwrap_err();
+        min_pk::aggregate_public_keys(&[]).unwrap_err();
+        min_sig::aggregate_signatures(&[]).unwrap_err();
+        min_sig::aggregate_public_keys(&[]).unwrap_err();
+    }
+
+    #[test]
+    fn test_signature_to_from_bytes() {
+        let private = min_pk::PrivateKey::try_from(SECRETS[2]).unwrap();
+        let signature = private.sign(b"Hello".to_vec()).unwrap();
+
+        let bytes = signature.to_vec();
+        let actual = min_pk::Signature::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(actual.to_vec(), bytes);
+
+        min_pk::Signature::try_from(&bytes[1..]).unwrap_err();
+    }
+
+    #[test]
+    fn test_from_invalid_bytes() {
+        min_pk::PrivateKey::try_from(&[0; 32][..]).unwrap_err();
+        min_pk::PrivateKey::try_from(&[1; 31][..]).unwrap_err();
+        // Greater than the group order.
+        min_sig::PrivateKey::try_from(&[0xFF; 32][..]).unwrap_err();
+
+        // The point at infinity.
+        let mut infinity = [0; 48];
+        infinity[0] = 0xC0;
+        min_pk::PublicKey::try_from(&infinity[..]).unwrap_err();
+        min_pk::PublicKey::try_from(&[0; 47][..]).unwrap_err();
+        min_sig::PublicKey::try_from(&[0; 96][..]).unwrap_err();
+    }
+
+    #[test]
+    fn test_private_key_generate() {
+        let private = min_pk::PrivateKey::generate(&mut OsRng);
+        let another = min_pk::PrivateKey::generate(&mut OsRng);
+        assert_ne!(private.public(), another.public());
+
+        let message = b"hello".to_vec();
+        let signature = private.sign(message.clone()).unwrap();
+        assert!(private.public().verify(signature, message));
+    }
+}
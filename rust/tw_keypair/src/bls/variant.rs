@@ -0,0 +1,327 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+/// Implements BLS key types, signing, verification and aggregation
+/// on top of the given `blst` variant module (`min_pk` or `min_sig`).
+///
+/// The calling module must define `POP_DST` that is used by `SigningKeyTrait` and `VerifyingKeyTrait`.
+macro_rules! bls_variant {
+    ($variant:ident, public_len = $public_len:literal, signature_len = $signature_len:literal) => {
+        use crate::traits::{KeyPairTrait, SigningKeyTrait, VerifyingKeyTrait};
+        use crate::{KeyPairError, KeyPairResult};
+        use blst::$variant as variant;
+        use blst::BLST_ERROR;
+        use rand_core::{CryptoRng, RngCore};
+        use std::fmt;
+        use tw_encoding::hex;
+        use tw_hash::Hash;
+        use tw_misc::traits::{ToBytesVec, ToBytesZeroizing};
+        use zeroize::{ZeroizeOnDrop, Zeroizing};
+
+        /// Represents a BLS12-381 private key, i.e. a 32 byte big-endian scalar.
+        #[derive(ZeroizeOnDrop)]
+        pub struct PrivateKey {
+            secret: variant::SecretKey,
+        }
+
+        impl fmt::Debug for PrivateKey {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_struct("PrivateKey")
+                    .field("public", &self.public())
+                    .finish()
+            }
+        }
+
+        impl PrivateKey {
+            /// Generates a random private key using the given `rng`.
+            pub fn generate(rng: &mut (impl CryptoRng + RngCore)) -> PrivateKey {
+                let mut ikm = Zeroizing::new([0_u8; 32]);
+                rng.fill_bytes(ikm.as_mut_slice());
+                let secret = variant::SecretKey::key_gen(ikm.as_slice(), &[])
+                    .expect("32 byte IKM is always enough");
+                PrivateKey { secret }
+            }
+
+            /// Returns an associated public key.
+            pub fn public(&self) -> PublicKey {
+                PublicKey {
+                    public: self.secret.sk_to_pk(),
+                }
+            }
+
+            /// Signs the given `message` with the domain separation tag `dst`.
+            pub fn sign_with_dst(&self, message: &[u8], dst: &[u8]) -> Signature {
+                Signature {
+                    signature: self.secret.sign(message, dst, &[]),
+                }
+            }
+        }
+
+        impl SigningKeyTrait for PrivateKey {
+            type SigningMessage = Vec<u8>;
+            type Signature = Signature;
+
+            fn sign(&self, message: Self::SigningMessage) -> KeyPairResult<Self::Signature> {
+                Ok(self.sign_with_dst(&message, POP_DST))
+            }
+        }
+
+        impl<'a> TryFrom<&'a [u8]> for PrivateKey {
+            type Error = KeyPairError;
+
+            fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+                let secret = variant::SecretKey::from_bytes(data)
+                    .map_err(|_| KeyPairError::InvalidSecretKey)?;
+                Ok(PrivateKey { secret })
+            }
+        }
+
+        impl<'a> TryFrom<&'a str> for PrivateKey {
+            type Error = KeyPairError;
+
+            fn try_from(hex: &'a str) -> Result<Self, Self::Error> {
+                let bytes =
+                    Zeroizing::new(hex::decode(hex).map_err(|_| KeyPairError::InvalidSecretKey)?);
+                Self::try_from(bytes.as_slice())
+            }
+        }
+
+        impl ToBytesZeroizing for PrivateKey {
+            fn to_zeroizing_vec(&self) -> Zeroizing<Vec<u8>> {
+                let secret = Zeroizing::new(self.secret.to_bytes());
+                Zeroizing::new(secret.to_vec())
+            }
+        }
+
+        crate::ct::impl_ct_eq!(PrivateKey);
+
+        /// Represents a BLS12-381 public key.
+        #[derive(Clone, Debug, PartialEq)]
+        pub struct PublicKey {
+            public: variant::PublicKey,
+        }
+
+        /// cbindgen:ignore
+        impl PublicKey {
+            /// The number of bytes in a compressed public key.
+            pub const LEN: usize = $public_len;
+
+            /// Returns the compressed public key.
+            pub fn to_bytes(&self) -> Hash<$public_len> {
+                Hash::from(self.public.compress())
+            }
+
+            /// Verifies if the given `message` was signed with the domain separation tag `dst`.
+            pub fn verify_with_dst(
+                &self,
+                signature: &Signature,
+                message: &[u8],
+                dst: &[u8],
+            ) -> bool {
+                // Both public key and signature are validated on deserialization.
+                let result =
+                    signature
+                        .signature
+                        .verify(false, message, dst, &[], &self.public, false);
+                result == BLST_ERROR::BLST_SUCCESS
+            }
+        }
+
+        impl VerifyingKeyTrait for PublicKey {
+            type SigningMessage = Vec<u8>;
+            type VerifySignature = Signature;
+
+            fn verify(
+                &self,
+                signature: Self::VerifySignature,
+                message: Self::SigningMessage,
+            ) -> bool {
+                self.verify_with_dst(&signature, &message, POP_DST)
+            }
+        }
+
+        impl ToBytesVec for PublicKey {
+            fn to_vec(&self) -> Vec<u8> {
+                self.to_bytes().into_vec()
+            }
+        }
+
+        impl<'a> TryFrom<&'a [u8]> for PublicKey {
+            type Error = KeyPairError;
+
+            /// Accepts both compressed and uncompressed public keys.
+            /// Rejects the point at infinity and points that are not in the prime order subgroup.
+            fn try_from(pubkey: &'a [u8]) -> Result<Self, Self::Error> {
+                let public = variant::PublicKey::key_validate(pubkey)
+                    .map_err(|_| KeyPairError::InvalidPublicKey)?;
+                Ok(PublicKey { public })
+            }
+        }
+
+        impl<'a> TryFrom<&'a str> for PublicKey {
+            type Error = KeyPairError;
+
+            fn try_from(hex: &'a str) -> Result<Self, Self::Error> {
+                let bytes = hex::decode(hex).map_err(|_| KeyPairError::InvalidPublicKey)?;
+                Self::try_from(bytes.as_slice())
+            }
+        }
+
+        /// Represents a BLS12-381 signature.
+        #[derive(Clone, Debug)]
+        pub struct Signature {
+            signature: variant::Signature,
+        }
+
+        impl Signature {
+            /// cbindgen:ignore
+            pub const LEN: usize = $signature_len;
+
+            /// Returns the compressed signature.
+            pub fn to_bytes(&self) -> Hash<$signature_len> {
+                Hash::from(self.signature.compress())
+            }
+        }
+
+        impl ToBytesVec for Signature {
+            fn to_vec(&self) -> Vec<u8> {
+                self.to_bytes().into_vec()
+            }
+        }
+
+        impl<'a> TryFrom<&'a [u8]> for Signature {
+            type Error = KeyPairError;
+
+            /// Rejects points that are not in the prime order subgroup.
+            fn try_from(sign: &'a [u8]) -> Result<Self, Self::Error> {
+                let signature = variant::Signature::sig_validate(sign, true)
+                    .map_err(|_| KeyPairError::InvalidSignature)?;
+                Ok(Signature { signature })
+            }
+        }
+
+        /// Represents a pair of BLS12-381 private and public keys.
+        #[derive(Debug)]
+        pub struct KeyPair {
+            private: PrivateKey,
+            public: PublicKey,
+        }
+
+        impl KeyPairTrait for KeyPair {
+            type Private = PrivateKey;
+            type Public = PublicKey;
+
+            fn public(&self) -> &Self::Public {
+                &self.public
+            }
+
+            fn private(&self) -> &Self::Private {
+                &self.private
+            }
+        }
+
+        impl SigningKeyTrait for KeyPair {
+            type SigningMessage = Vec<u8>;
+            type Signature = Signature;
+
+            fn sign(&self, message: Self::SigningMessage) -> KeyPairResult<Self::Signature> {
+                self.private().sign(message)
+            }
+        }
+
+        impl VerifyingKeyTrait for KeyPair {
+            type SigningMessage = Vec<u8>;
+            type VerifySignature = Signature;
+
+            fn verify(
+                &self,
+                signature: Self::VerifySignature,
+                message: Self::SigningMessage,
+            ) -> bool {
+                self.public().verify(signature, message)
+            }
+        }
+
+        impl<'a> TryFrom<&'a [u8]> for KeyPair {
+            type Error = KeyPairError;
+
+            fn try_from(bytes: &'a [u8]) -> Result<Self, Self::Error> {
+                let private = PrivateKey::try_from(bytes)?;
+                let public = private.public();
+                Ok(KeyPair { private, public })
+            }
+        }
+
+        impl<'a> TryFrom<&'a str> for KeyPair {
+            type Error = KeyPairError;
+
+            fn try_from(hex: &'a str) -> Result<Self, Self::Error> {
+                let bytes =
+                    Zeroizing::new(hex::decode(hex).map_err(|_| KeyPairError::InvalidSecretKey)?);
+                Self::try_from(bytes.as_slice())
+            }
+        }
+
+        /// Aggregates the given signatures into one.
+        /// Returns an error if `signatures` is empty.
+        pub fn aggregate_signatures(signatures: &[Signature]) -> KeyPairResult<Signature> {
+            let signatures: Vec<_> = signatures.iter().map(|sig| &sig.signature).collect();
+            let aggregated = variant::AggregateSignature::aggregate(&signatures, false)
+                .map_err(|_| KeyPairError::InvalidSignature)?;
+            Ok(Signature {
+                signature: aggregated.to_signature(),
+            })
+        }
+
+        /// Aggregates the given public keys into one.
+        /// Returns an error if `public_keys` is empty.
+        ///
+        /// # Warning
+        ///
+        /// The aggregated public key is prone to the rogue key attack
+        /// unless every public key comes with a proof of possession.
+        pub fn aggregate_public_keys(public_keys: &[PublicKey]) -> KeyPairResult<PublicKey> {
+            let public_keys: Vec<_> = public_keys.iter().map(|public| &public.public).collect();
+            let aggregated = variant::AggregatePublicKey::aggregate(&public_keys, false)
+                .map_err(|_| KeyPairError::InvalidPublicKey)?;
+            Ok(PublicKey {
+                public: aggregated.to_public_key(),
+            })
+        }
+
+        /// Verifies the aggregated `signature` of the same `message` signed by every public key.
+        pub fn fast_aggregate_verify(
+            signature: &Signature,
+            message: &[u8],
+            public_keys: &[PublicKey],
+            dst: &[u8],
+        ) -> bool {
+            let public_keys: Vec<_> = public_keys.iter().map(|public| &public.public).collect();
+            let result =
+                signature
+                    .signature
+                    .fast_aggregate_verify(false, message, dst, &public_keys);
+            result == BLST_ERROR::BLST_SUCCESS
+        }
+
+        /// Verifies the aggregated `signature` of distinct `messages`,
+        /// where `messages[i]` is signed by `public_keys[i]`.
+        pub fn aggregate_verify(
+            signature: &Signature,
+            messages: &[&[u8]],
+            public_keys: &[PublicKey],
+            dst: &[u8],
+        ) -> bool {
+            if messages.len() != public_keys.len() {
+                return false;
+            }
+            let public_keys: Vec<_> = public_keys.iter().map(|public| &public.public).collect();
+            let result =
+                signature
+                    .signature
+                    .aggregate_verify(false, messages, dst, &public_keys, false);
+            result == BLST_ERROR::BLST_SUCCESS
+        }
+    };
+}
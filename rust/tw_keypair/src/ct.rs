@@ -0,0 +1,76 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Constant-time comparison of secret data.
+//!
+//! Private keys and seeds implement [`PartialEq`] via [`ConstantTimeEq::ct_eq`],
+//! so `==` on secrets never short-circuits on the first mismatching byte.
+//!
+//! Enable the `ct-audit` feature to panic on comparisons that can't be done in constant time,
+//! i.e. when the secrets have different lengths.
+
+pub use subtle::{Choice, ConstantTimeEq};
+
+/// Compares the given `lhs` and `rhs` secrets in constant time.
+///
+/// The comparison is only constant time if the secrets have the same length.
+/// Panics on different lengths if the `ct-audit` feature is enabled.
+pub fn ct_eq_secrets(lhs: &[u8], rhs: &[u8]) -> Choice {
+    #[cfg(feature = "ct-audit")]
+    assert_eq!(
+        lhs.len(),
+        rhs.len(),
+        "Non-constant-time comparison of secrets with different lengths"
+    );
+
+    lhs.ct_eq(rhs)
+}
+
+/// Implements [`ConstantTimeEq`], [`PartialEq`] and [`Eq`] for the given secret type
+/// by comparing its [`tw_misc::traits::ToBytesZeroizing::to_zeroizing_vec`] bytes in constant time.
+macro_rules! impl_ct_eq {
+    ($ty:ident $(<$generic:ident: $bound:path>)?) => {
+        impl$(<$generic: $bound>)? $crate::ct::ConstantTimeEq for $ty$(<$generic>)? {
+            fn ct_eq(&self, other: &Self) -> $crate::ct::Choice {
+                use tw_misc::traits::ToBytesZeroizing;
+
+                $crate::ct::ct_eq_secrets(&self.to_zeroizing_vec(), &other.to_zeroizing_vec())
+            }
+        }
+
+        impl$(<$generic: $bound>)? PartialEq for $ty$(<$generic>)? {
+            fn eq(&self, other: &Self) -> bool {
+                $crate::ct::ConstantTimeEq::ct_eq(self, other).into()
+            }
+        }
+
+        impl$(<$generic: $bound>)? Eq for $ty$(<$generic>)? {}
+    };
+}
+
+pub(crate) use impl_ct_eq;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ct_eq_secrets() {
+        assert!(bool::from(ct_eq_secrets(&[1, 2, 3], &[1, 2, 3])));
+        assert!(!bool::from(ct_eq_secrets(&[1, 2, 3], &[1, 2, 4])));
+    }
+
+    #[cfg(not(feature = "ct-audit"))]
+    #[test]
+    fn test_ct_eq_secrets_different_len() {
+        assert!(!bool::from(ct_eq_secrets(&[1, 2, 3], &[1, 2])));
+    }
+
+    #[cfg(feature = "ct-audit")]
+    #[test]
+    #[should_panic(expected = "Non-constant-time comparison")]
+    fn test_ct_eq_secrets_different_len_audit() {
+        let _ = ct_eq_secrets(&[1, 2, 3], &[1, 2]);
+    }
+}
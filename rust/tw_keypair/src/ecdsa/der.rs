@@ -11,6 +11,10 @@ use der::{Decode, Reader};
 const SIGNATURE_LENGTH: usize = 64;
 const R_LENGTH: usize = 32;
 const S_LENGTH: usize = 32;
+/// ASN.1 `SEQUENCE` tag.
+const SEQUENCE_TAG: u8 = 0x30;
+/// ASN.1 `INTEGER` tag.
+const INTEGER_TAG: u8 = 0x02;
 
 pub type SignatureBytes = [u8; SIGNATURE_LENGTH];
 
@@ -31,6 +35,11 @@ pub struct Signature {
 }
 
 impl Signature {
+    /// Creates a signature from the big-endian `r` and `s` components.
+    pub fn new(r: [u8; R_LENGTH], s: [u8; S_LENGTH]) -> Signature {
+        Signature { r, s }
+    }
+
     /// Parses signature from DER-encoded bytes.
     /// Source: https://github.com/RustCrypto/signatures/blob/ecb112aa25ac210d86946089cddf0a62a672ce14/ecdsa/src/der.rs#L83-L108
     pub fn from_bytes(input: &[u8]) -> KeyPairResult<Signature> {
@@ -79,6 +88,42 @@ impl Signature {
     pub fn to_vec(&self) -> Vec<u8> {
         self.to_bytes().to_vec()
     }
+
+    /// Returns the ASN.1 DER-encoded signature.
+    pub fn to_der(&self) -> Vec<u8> {
+        let r = encode_der_integer(&self.r);
+        let s = encode_der_integer(&self.s);
+
+        // The content length never exceeds 127 bytes, so it's encoded in the short form.
+        let content_len = r.len() + s.len();
+        let mut der = Vec::with_capacity(2 + content_len);
+        der.push(SEQUENCE_TAG);
+        der.push(content_len as u8);
+        der.extend_from_slice(&r);
+        der.extend_from_slice(&s);
+        der
+    }
+}
+
+/// Encodes the given big-endian unsigned integer as an ASN.1 `INTEGER`.
+/// Leading zeros are stripped, and a zero byte is prepended if the most significant bit is set.
+fn encode_der_integer(bytes: &[u8]) -> Vec<u8> {
+    let first_non_zero = bytes
+        .iter()
+        .position(|byte| *byte != 0)
+        .unwrap_or(bytes.len() - 1);
+    let value = &bytes[first_non_zero..];
+    let needs_padding = value[0] & 0x80 != 0;
+
+    let value_len = value.len() + usize::from(needs_padding);
+    let mut encoded = Vec::with_capacity(2 + value_len);
+    encoded.push(INTEGER_TAG);
+    encoded.push(value_len as u8);
+    if needs_padding {
+        encoded.push(0);
+    }
+    encoded.extend_from_slice(value);
+    encoded
 }
 
 /// Decode the `r` and `s` components of a DER-encoded ECDSA signature.
@@ -143,6 +188,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_encode_ecdsa_signature() {
+        #[track_caller]
+        fn test_impl(encoded: &str) {
+            let encoded_bytes = hex::decode(encoded).unwrap();
+            let sign = Signature::from_bytes(&encoded_bytes).unwrap();
+            assert_eq!(sign.to_der(), encoded_bytes);
+        }
+
+        test_impl("3045022100B2B31575F8536B284410D01217F688BE3A9FAF4BA0BA3A9093F983E40D630EC7022022A7A25B01403CFF0D00B3B853D230F8E96FF832B15D4CCC75203CB65896A2D5");
+        test_impl("3046022100db421231f23d0320dbb8f1284b600cd34b8e9218628139539ff4f1f6c05495da022100ff715aab70d5317dbf8ee224eb18bec3120cfb9db1000dbb31eadaf96c71c1b1");
+        test_impl("303d021d00f23d0320dbb8f1284b600cd34b8e9218628139539ff4f1f600000000021c70d5317dbf8ee224eb18bec3120cfb9db1000dbb31eadaf900000000");
+        test_impl("3006020110020110");
+
+        let zero = Signature::new([0; R_LENGTH], [0; S_LENGTH]);
+        assert_eq!(zero.to_der(), hex::decode("3006020100020100").unwrap());
+    }
+
     #[test]
     fn test_parse_ecdsa_signature_invalid() {
         #[track_caller]
@@ -5,7 +5,7 @@
 use crate::ecdsa::nist256p1::private::PrivateKey;
 use crate::ecdsa::nist256p1::public::PublicKey;
 use crate::ecdsa::nist256p1::{Signature, VerifySignature};
-use crate::traits::{KeyPairTrait, SigningKeyTrait, VerifyingKeyTrait};
+use crate::traits::{KeyPairTrait, SigningKeyTrait, VerifyMode, VerifyingKeyTrait};
 use crate::{KeyPairError, KeyPairResult};
 use tw_encoding::hex;
 use tw_hash::H256;
@@ -49,6 +49,15 @@ impl VerifyingKeyTrait for KeyPair {
     fn verify(&self, signature: Self::VerifySignature, message: Self::SigningMessage) -> bool {
         self.public.verify(signature, message)
     }
+
+    fn verify_with_mode(
+        &self,
+        signature: Self::VerifySignature,
+        message: Self::SigningMessage,
+        mode: VerifyMode,
+    ) -> bool {
+        self.public.verify_with_mode(signature, message, mode)
+    }
 }
 
 impl<'a> TryFrom<&'a [u8]> for KeyPair {
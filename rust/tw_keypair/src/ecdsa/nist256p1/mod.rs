@@ -18,10 +18,10 @@ pub type VerifySignature = crate::ecdsa::signature::VerifySignature<NistP256>;
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::traits::{KeyPairTrait, SigningKeyTrait, VerifyingKeyTrait};
+    use crate::traits::{KeyPairTrait, SigningKeyTrait, VerifyMode, VerifyingKeyTrait};
     use tw_encoding::hex;
     use tw_hash::sha3::keccak256;
-    use tw_hash::{H256, H264, H520};
+    use tw_hash::{H256, H264, H512, H520};
     use tw_misc::traits::{ToBytesVec, ToBytesZeroizing};
 
     #[test]
@@ -71,6 +71,77 @@ mod tests {
         assert!(keypair.verify(verify_signature, hash_to_sign));
     }
 
+    #[test]
+    fn test_private_key_sign_der() {
+        let secret = "afeefca74d9a325cf1d6b6911d61a65c32afa8e02bd5e78e2e4ac2910bab45f5";
+        let private = PrivateKey::try_from(secret).unwrap();
+
+        let hash_to_sign = keccak256(b"hello");
+        let hash_to_sign = H256::try_from(hash_to_sign.as_slice()).unwrap();
+        let signature = private.sign(hash_to_sign).unwrap();
+
+        let expected_der = "30450221008859e63a0c0cc2fc7f788d7e78406157b288faa6f76f76d37c4cd1534e8d83c4022068f9fd6ca7dde378df594625dcde98559389569e039282275e3d87c26e364474";
+        assert_eq!(hex::encode(signature.to_der(), false), expected_der);
+
+        let verify_signature =
+            VerifySignature::from_der(&hex::decode(expected_der).unwrap()).unwrap();
+        assert_eq!(verify_signature.to_der(), signature.to_der());
+        assert!(private.public().verify(verify_signature, hash_to_sign));
+    }
+
+    #[test]
+    fn test_private_key_sign_low_s() {
+        let secret = "afeefca74d9a325cf1d6b6911d61a65c32afa8e02bd5e78e2e4ac2910bab45f5";
+        let hash_to_sign = keccak256(b"hello world");
+        let hash_to_sign = H256::try_from(hash_to_sign.as_slice()).unwrap();
+
+        let private = PrivateKey::try_from(secret).unwrap();
+        let signature = private.sign(hash_to_sign).unwrap();
+        let expected = H520::from("81442fd9a55c2f748f292b91b835b6714dafbeed8ea126387757d2df31ca1422dfce17e86a75b56c0039afc50d43f030e335af8075ea2fa82d2b318cd9c398c200");
+        assert_eq!(signature.to_bytes(), expected);
+        assert!(!signature.is_low_s());
+
+        let private = private.low_s();
+        let signature = private.sign(hash_to_sign).unwrap();
+        let expected = H520::from("81442fd9a55c2f748f292b91b835b6714dafbeed8ea126387757d2df31ca14222031e816958a4a94ffc6503af2bc0fced9b14b2d312d6edcc68e9936229f8c8f01");
+        assert_eq!(signature.to_bytes(), expected);
+        assert!(signature.is_low_s());
+
+        let recovered = PublicKey::recover(signature.clone(), hash_to_sign).unwrap();
+        assert_eq!(recovered.compressed(), private.public().compressed());
+        let verify_signature = VerifySignature::from(signature);
+        assert!(private.public().verify_with_mode(
+            verify_signature,
+            hash_to_sign,
+            VerifyMode::Strict
+        ));
+    }
+
+    #[test]
+    fn test_verify_signature_from_invalid_der() {
+        assert!(VerifySignature::from_der(&[]).is_err());
+        // `r` is zero.
+        let zero_r = hex::decode("3006020100020110").unwrap();
+        assert!(VerifySignature::from_der(&zero_r).is_err());
+    }
+
+    #[test]
+    fn test_verify_with_mode_high_s() {
+        let secret = "afeefca74d9a325cf1d6b6911d61a65c32afa8e02bd5e78e2e4ac2910bab45f5";
+        let public = PrivateKey::try_from(secret).unwrap().public();
+
+        let hash_to_sign = keccak256(b"hello");
+        let hash_to_sign = H256::try_from(hash_to_sign.as_slice()).unwrap();
+
+        // `s` is replaced with `n - s` of the signature from `test_private_key_sign_verify`.
+        let high_s = H512::from("8859e63a0c0cc2fc7f788d7e78406157b288faa6f76f76d37c4cd1534e8d83c49706029258221c8820a6b9da232167aa295da40fa3851c5d957c43008e2ce0dd");
+        let verify_sig = || VerifySignature::try_from(high_s.as_slice()).unwrap();
+
+        assert!(public.verify_with_mode(verify_sig(), hash_to_sign, VerifyMode::Lax));
+        assert!(!public.verify_with_mode(verify_sig(), hash_to_sign, VerifyMode::Strict));
+        assert!(public.verify(verify_sig(), hash_to_sign));
+    }
+
     #[test]
     fn test_public_key_from() {
         let compressed = "026d786ab8fda678cf50f71d13641049a393b325063b8c0d4e5070de48a2caf9ab";
@@ -104,4 +175,28 @@ mod tests {
             H264::from("026d786ab8fda678cf50f71d13641049a393b325063b8c0d4e5070de48a2caf9ab");
         assert_eq!(actual.compressed(), expected_compressed);
     }
+
+    #[test]
+    fn test_public_key_recoverable_signature() {
+        let secret = "afeefca74d9a325cf1d6b6911d61a65c32afa8e02bd5e78e2e4ac2910bab45f5";
+        let public = PrivateKey::try_from(secret).unwrap().public();
+        let sign_bytes = H520::from("8859e63a0c0cc2fc7f788d7e78406157b288faa6f76f76d37c4cd1534e8d83c468f9fd6ca7dde378df594625dcde98559389569e039282275e3d87c26e36447401");
+
+        let signed_hash = keccak256(b"hello");
+        let signed_hash = H256::try_from(signed_hash.as_slice()).unwrap();
+
+        // Drop the recovery ID.
+        let verify_sign = VerifySignature::try_from(&sign_bytes[..64]).unwrap();
+        let actual = public
+            .recoverable_signature(verify_sign, signed_hash)
+            .unwrap();
+        assert_eq!(actual.to_bytes(), sign_bytes);
+        assert_eq!(actual.v(), 1);
+
+        let another_hash = H256::from([1; 32]);
+        let verify_sign = VerifySignature::try_from(&sign_bytes[..64]).unwrap();
+        public
+            .recoverable_signature(verify_sign, another_hash)
+            .unwrap_err();
+    }
 }
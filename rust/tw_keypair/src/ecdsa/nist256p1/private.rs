@@ -7,6 +7,7 @@ use crate::ecdsa::nist256p1::Signature;
 use crate::traits::SigningKeyTrait;
 use crate::{KeyPairError, KeyPairResult};
 use p256::ecdsa::SigningKey;
+use rand_core::{CryptoRng, RngCore};
 use tw_encoding::hex;
 use tw_hash::H256;
 use tw_misc::traits::ToBytesZeroizing;
@@ -16,13 +17,30 @@ use zeroize::{ZeroizeOnDrop, Zeroizing};
 #[derive(ZeroizeOnDrop)]
 pub struct PrivateKey {
     pub(crate) secret: SigningKey,
+    /// Whether the signatures should be normalized to low-S.
+    low_s: bool,
 }
 
 impl PrivateKey {
+    /// Generates a random private key using the given `rng`.
+    pub fn generate(rng: &mut (impl CryptoRng + RngCore)) -> PrivateKey {
+        PrivateKey {
+            secret: SigningKey::random(rng),
+            low_s: false,
+        }
+    }
+
     /// Returns an associated `nist256p1` public key.
     pub fn public(&self) -> PublicKey {
         PublicKey::new(*self.secret.verifying_key())
     }
+
+    /// Makes the private key always produce low-S signatures, see [`Signature::normalize_s`].
+    /// Unlike `secp256k1`, `nist256p1` signatures are not normalized by default.
+    pub fn low_s(mut self) -> PrivateKey {
+        self.low_s = true;
+        self
+    }
 }
 
 impl SigningKeyTrait for PrivateKey {
@@ -34,7 +52,11 @@ impl SigningKeyTrait for PrivateKey {
             .secret
             .sign_prehash_recoverable(message.as_slice())
             .map_err(|_| KeyPairError::SigningError)?;
-        Ok(Signature::new(signature, recovery_id))
+        let signature = Signature::new(signature, recovery_id);
+        if self.low_s {
+            return Ok(signature.normalize_s());
+        }
+        Ok(signature)
     }
 }
 
@@ -43,7 +65,10 @@ impl<'a> TryFrom<&'a [u8]> for PrivateKey {
 
     fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
         let secret = SigningKey::from_slice(data).map_err(|_| KeyPairError::InvalidSecretKey)?;
-        Ok(PrivateKey { secret })
+        Ok(PrivateKey {
+            secret,
+            low_s: false,
+        })
     }
 }
 
@@ -62,3 +87,5 @@ impl ToBytesZeroizing for PrivateKey {
         Zeroizing::new(secret.as_slice().to_vec())
     }
 }
+
+crate::ct::impl_ct_eq!(PrivateKey);
@@ -3,8 +3,9 @@
 // Copyright © 2017 Trust Wallet.
 
 use crate::ecdsa::nist256p1::{Signature, VerifySignature};
-use crate::traits::VerifyingKeyTrait;
+use crate::traits::{VerifyMode, VerifyingKeyTrait};
 use crate::{KeyPairError, KeyPairResult};
+use ecdsa::RecoveryId;
 use p256::ecdsa::signature::hazmat::PrehashVerifier;
 use p256::ecdsa::VerifyingKey;
 use tw_encoding::hex;
@@ -31,6 +32,22 @@ impl PublicKey {
             .map_err(|_| KeyPairError::InvalidSignature)
     }
 
+    /// Finds the recovery ID of the (r, s) `sign` over the `message` that is produced with this public key.
+    /// Returns a recoverable [`Signature`], or an error if the signature is not valid for this public key.
+    pub fn recoverable_signature(
+        &self,
+        sign: VerifySignature,
+        message: H256,
+    ) -> KeyPairResult<Signature> {
+        let recovery_id = RecoveryId::trial_recovery_from_prehash(
+            &self.public,
+            message.as_slice(),
+            &sign.signature,
+        )
+        .map_err(|_| KeyPairError::SignatureVerifyError)?;
+        Ok(Signature::new(sign.signature, recovery_id))
+    }
+
     /// Creates a public key from the given [`VerifyingKey`].
     pub(crate) fn new(public: VerifyingKey) -> PublicKey {
         PublicKey { public }
@@ -55,11 +72,26 @@ impl VerifyingKeyTrait for PublicKey {
     type SigningMessage = H256;
     type VerifySignature = VerifySignature;
 
+    /// Accepts high-S signatures, i.e. equals to [`VerifyMode::Lax`].
     fn verify(&self, sign: Self::VerifySignature, message: Self::SigningMessage) -> bool {
         self.public
             .verify_prehash(message.as_slice(), &sign.signature)
             .is_ok()
     }
+
+    fn verify_with_mode(
+        &self,
+        sign: Self::VerifySignature,
+        message: Self::SigningMessage,
+        mode: VerifyMode,
+    ) -> bool {
+        // `normalize_s` returns `Some` if the signature is high-S.
+        let is_high_s = sign.signature.normalize_s().is_some();
+        if mode == VerifyMode::Strict && is_high_s {
+            return false;
+        }
+        self.verify(sign, message)
+    }
 }
 
 impl<'a> TryFrom<&'a str> for PublicKey {
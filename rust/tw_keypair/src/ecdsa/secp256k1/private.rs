@@ -3,12 +3,17 @@
 // Copyright © 2017 Trust Wallet.
 
 use crate::ecdsa::secp256k1::public::PublicKey;
+use crate::ecdsa::secp256k1::wif::{self, WifInfo};
 use crate::ecdsa::secp256k1::Signature;
+use crate::pem;
 use crate::traits::SigningKeyTrait;
 use crate::{KeyPairError, KeyPairResult};
 use k256::ecdsa::{SigningKey, VerifyingKey};
+use k256::elliptic_curve::point::AffineCoordinates;
 use k256::elliptic_curve::sec1::ToEncodedPoint;
+use k256::pkcs8::{DecodePrivateKey, EncodePrivateKey};
 use k256::{AffinePoint, ProjectivePoint};
+use rand_core::{CryptoRng, RngCore};
 use tw_encoding::hex;
 use tw_hash::H256;
 use tw_misc::traits::ToBytesZeroizing;
@@ -21,6 +26,13 @@ pub struct PrivateKey {
 }
 
 impl PrivateKey {
+    /// Generates a random private key using the given `rng`.
+    pub fn generate(rng: &mut (impl CryptoRng + RngCore)) -> PrivateKey {
+        PrivateKey {
+            secret: SigningKey::random(rng),
+        }
+    }
+
     /// Returns an associated `secp256k1` public key.
     pub fn public(&self) -> PublicKey {
         PublicKey::new(*self.secret.verifying_key())
@@ -38,6 +50,53 @@ impl PrivateKey {
         let shared_secret_hash = tw_hash::sha2::sha256(shared_secret_compressed.as_bytes());
         H256::try_from(shared_secret_hash.as_slice()).expect("Expected 32 byte array sha256 hash")
     }
+
+    /// Computes an EC Diffie-Hellman secret in constant time.
+    /// Returns the raw X coordinate of the shared point (32 bytes) as specified in
+    /// [SEC 1](https://www.secg.org/sec1-v2.pdf) section 3.3.1,
+    /// so the caller can derive an encryption key with a KDF of its choice.
+    pub fn shared_key(&self, pubkey: &PublicKey) -> H256 {
+        let shared_secret = diffie_hellman(&self.secret, &pubkey.public);
+        H256::try_from(shared_secret.x().as_slice()).expect("Expected 32 byte X coordinate")
+    }
+
+    /// Decodes a private key from the Wallet Import Format.
+    /// Returns the key along with the network and the compressed flag.
+    pub fn from_wif(wif: &str) -> KeyPairResult<(PrivateKey, WifInfo)> {
+        let (secret, info) = wif::decode(wif)?;
+        Ok((PrivateKey::try_from(secret.as_slice())?, info))
+    }
+
+    /// Encodes the private key in the Wallet Import Format.
+    pub fn to_wif(&self, info: WifInfo) -> Zeroizing<String> {
+        wif::encode(self.to_zeroizing_vec().as_slice(), info)
+    }
+
+    /// Decodes a private key from the PKCS#8 DER document, e.g. produced by `openssl genpkey`.
+    pub fn from_pkcs8_der(der: &[u8]) -> KeyPairResult<PrivateKey> {
+        let secret = SigningKey::from_pkcs8_der(der).map_err(|_| KeyPairError::InvalidSecretKey)?;
+        Ok(PrivateKey { secret })
+    }
+
+    /// Encodes the private key as a PKCS#8 DER document.
+    pub fn to_pkcs8_der(&self) -> Zeroizing<Vec<u8>> {
+        let doc = self
+            .secret
+            .to_pkcs8_der()
+            .expect("Expected a valid secp256k1 private key");
+        Zeroizing::new(doc.as_bytes().to_vec())
+    }
+
+    /// Decodes a private key from the PKCS#8 `PRIVATE KEY` PEM document.
+    pub fn from_pkcs8_pem(pem: &str) -> KeyPairResult<PrivateKey> {
+        let doc = pem::decode_private_key(pem)?;
+        PrivateKey::from_pkcs8_der(doc.as_bytes())
+    }
+
+    /// Encodes the private key as a PKCS#8 `PRIVATE KEY` PEM document.
+    pub fn to_pkcs8_pem(&self) -> Zeroizing<String> {
+        pem::encode_private_key(&self.to_pkcs8_der())
+    }
 }
 
 /// This method is inspired by [elliptic_curve::ecdh::diffie_hellman](https://github.com/RustCrypto/traits/blob/f0dbe44fea56d4c17e625ababacb580fec842137/elliptic-curve/src/ecdh.rs#L60-L70)
@@ -52,6 +111,7 @@ impl SigningKeyTrait for PrivateKey {
     type SigningMessage = H256;
     type Signature = Signature;
 
+    /// Always produces low-S signatures, see [`Signature::is_low_s`].
     fn sign(&self, message: Self::SigningMessage) -> KeyPairResult<Self::Signature> {
         let (signature, recovery_id) = self
             .secret
@@ -85,3 +145,5 @@ impl ToBytesZeroizing for PrivateKey {
         Zeroizing::new(secret.as_slice().to_vec())
     }
 }
+
+crate::ct::impl_ct_eq!(PrivateKey);
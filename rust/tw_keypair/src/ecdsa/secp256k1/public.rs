@@ -3,11 +3,15 @@
 // Copyright © 2017 Trust Wallet.
 
 use crate::ecdsa::secp256k1::{Signature, VerifySignature};
-use crate::traits::VerifyingKeyTrait;
+use crate::pem;
+use crate::schnorr::{lift_x, scalar_from_canonical_bytes};
+use crate::traits::{VerifyMode, VerifyingKeyTrait};
 use crate::{KeyPairError, KeyPairResult};
 use der::Document;
 use k256::ecdsa::signature::hazmat::PrehashVerifier;
-use k256::ecdsa::VerifyingKey;
+use k256::ecdsa::{RecoveryId, VerifyingKey};
+use k256::pkcs8::DecodePublicKey;
+use k256::ProjectivePoint;
 use tw_encoding::hex;
 use tw_hash::{Hash, H256, H264, H512, H520};
 use tw_misc::traits::ToBytesVec;
@@ -32,11 +36,66 @@ impl PublicKey {
             .map_err(|_| KeyPairError::InvalidSignature)
     }
 
+    /// Finds the recovery ID of the (r, s) `sign` over the `message` that is produced with this public key.
+    /// Returns a recoverable [`Signature`], or an error if the signature is not valid for this public key.
+    pub fn recoverable_signature(
+        &self,
+        sign: VerifySignature,
+        message: H256,
+    ) -> KeyPairResult<Signature> {
+        let recovery_id = RecoveryId::trial_recovery_from_prehash(
+            &self.public,
+            message.as_slice(),
+            &sign.signature,
+        )
+        .map_err(|_| KeyPairError::SignatureVerifyError)?;
+        Ok(Signature::new(sign.signature, recovery_id))
+    }
+
     /// Creates a public key from the given [`VerifyingKey`].
     pub(crate) fn new(public: VerifyingKey) -> PublicKey {
         PublicKey { public }
     }
 
+    /// Returns `P + t * G`, where `P` is the public key and `t` is the given `tweak` scalar.
+    /// Returns an error if `tweak` is not less than the curve order or the result is at infinity.
+    pub fn tweak_add(&self, tweak: &H256) -> KeyPairResult<PublicKey> {
+        let tweak = scalar_from_canonical_bytes(tweak).ok_or(KeyPairError::InvalidTweak)?;
+        let point = self.projective_point() + ProjectivePoint::GENERATOR * tweak;
+        PublicKey::from_projective_point(point)
+    }
+
+    /// Returns `t * P`, where `P` is the public key and `t` is the given `tweak` scalar.
+    /// Returns an error if `tweak` is zero or not less than the curve order.
+    pub fn tweak_mul(&self, tweak: &H256) -> KeyPairResult<PublicKey> {
+        let tweak = scalar_from_canonical_bytes(tweak).ok_or(KeyPairError::InvalidTweak)?;
+        PublicKey::from_projective_point(self.projective_point() * tweak)
+    }
+
+    /// Returns the sum of the given public keys.
+    /// Returns an error if `public_keys` is empty or the sum is at infinity.
+    pub fn combine(public_keys: &[PublicKey]) -> KeyPairResult<PublicKey> {
+        if public_keys.is_empty() {
+            return Err(KeyPairError::InvalidPublicKey);
+        }
+        let sum = public_keys
+            .iter()
+            .map(PublicKey::projective_point)
+            .fold(ProjectivePoint::IDENTITY, |sum, point| sum + point);
+        PublicKey::from_projective_point(sum)
+    }
+
+    fn projective_point(&self) -> ProjectivePoint {
+        ProjectivePoint::from(*self.public.as_affine())
+    }
+
+    fn from_projective_point(point: ProjectivePoint) -> KeyPairResult<PublicKey> {
+        // Rejects the point at infinity.
+        VerifyingKey::from_affine(point.to_affine())
+            .map(PublicKey::new)
+            .map_err(|_| KeyPairError::InvalidPublicKey)
+    }
+
     /// Returns the raw data of the compressed public key (33 bytes).
     pub fn compressed(&self) -> H264 {
         let compressed = true;
@@ -57,6 +116,36 @@ impl PublicKey {
         public
     }
 
+    /// Returns the X coordinate of the public key (32 bytes).
+    /// Consider using [`crate::schnorr::PublicKey`] for BIP-340 x-only public keys.
+    pub fn x_only(&self) -> H256 {
+        let (_prefix, x): (Hash<1>, H256) = self.compressed().split();
+        x
+    }
+
+    /// Parses a compressed public key (33 bytes).
+    pub fn from_compressed(bytes: &[u8]) -> KeyPairResult<PublicKey> {
+        if bytes.len() != PublicKey::COMPRESSED {
+            return Err(KeyPairError::InvalidPublicKey);
+        }
+        PublicKey::try_from(bytes)
+    }
+
+    /// Parses an uncompressed public key (65 bytes).
+    pub fn from_uncompressed(bytes: &[u8]) -> KeyPairResult<PublicKey> {
+        if bytes.len() != PublicKey::UNCOMPRESSED {
+            return Err(KeyPairError::InvalidPublicKey);
+        }
+        PublicKey::try_from(bytes)
+    }
+
+    /// Parses an x-only public key (32 bytes), i.e. the point with an even Y coordinate.
+    pub fn from_x_only(bytes: &[u8]) -> KeyPairResult<PublicKey> {
+        let x = H256::try_from(bytes).map_err(|_| KeyPairError::InvalidPublicKey)?;
+        let point = lift_x(&x).ok_or(KeyPairError::InvalidPublicKey)?;
+        PublicKey::from_projective_point(ProjectivePoint::from(point))
+    }
+
     /// Returns the public key as DER-encoded bytes.
     pub fn der_encoded(&self) -> Vec<u8> {
         let compressed = false;
@@ -78,17 +167,50 @@ impl PublicKey {
         let doc = Document::try_from(&spki).unwrap();
         doc.into_vec()
     }
+
+    /// Parses a public key from the DER-encoded `SubjectPublicKeyInfo`, see [`PublicKey::der_encoded`].
+    pub fn from_der_encoded(der: &[u8]) -> KeyPairResult<PublicKey> {
+        let public =
+            VerifyingKey::from_public_key_der(der).map_err(|_| KeyPairError::InvalidPublicKey)?;
+        Ok(PublicKey { public })
+    }
+
+    /// Returns the public key as a `PUBLIC KEY` PEM document.
+    pub fn pem_encoded(&self) -> String {
+        pem::encode_public_key(&self.der_encoded())
+    }
+
+    /// Parses a public key from the `PUBLIC KEY` PEM document.
+    pub fn from_pem_encoded(pem: &str) -> KeyPairResult<PublicKey> {
+        let doc = pem::decode_public_key(pem)?;
+        PublicKey::from_der_encoded(doc.as_bytes())
+    }
 }
 
 impl VerifyingKeyTrait for PublicKey {
     type SigningMessage = H256;
     type VerifySignature = VerifySignature;
 
+    /// Accepts high-S signatures, i.e. equals to [`VerifyMode::Lax`].
     fn verify(&self, sign: Self::VerifySignature, message: Self::SigningMessage) -> bool {
         self.public
             .verify_prehash(message.as_slice(), &sign.signature)
             .is_ok()
     }
+
+    fn verify_with_mode(
+        &self,
+        sign: Self::VerifySignature,
+        message: Self::SigningMessage,
+        mode: VerifyMode,
+    ) -> bool {
+        // `normalize_s` returns `Some` if the signature is high-S.
+        let is_high_s = sign.signature.normalize_s().is_some();
+        if mode == VerifyMode::Strict && is_high_s {
+            return false;
+        }
+        self.verify(sign, message)
+    }
 }
 
 impl<'a> TryFrom<&'a str> for PublicKey {
@@ -0,0 +1,106 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! [Wallet Import Format](https://en.bitcoin.it/wiki/Wallet_import_format) of `secp256k1` private keys:
+//! `base58(prefix || secret || [0x01] || checksum)`.
+
+use crate::{KeyPairError, KeyPairResult};
+use tw_encoding::base58::{self, Alphabet};
+use tw_hash::sha2::sha256;
+use tw_hash::H256;
+use zeroize::Zeroizing;
+
+const MAINNET_PREFIX: u8 = 0x80;
+const TESTNET_PREFIX: u8 = 0xef;
+const COMPRESSED_FLAG: u8 = 0x01;
+const CHECKSUM_LEN: usize = 4;
+
+/// Network that a WIF private key belongs to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WifNetwork {
+    Mainnet,
+    Testnet,
+}
+
+impl WifNetwork {
+    fn prefix(self) -> u8 {
+        match self {
+            WifNetwork::Mainnet => MAINNET_PREFIX,
+            WifNetwork::Testnet => TESTNET_PREFIX,
+        }
+    }
+
+    fn from_prefix(prefix: u8) -> Option<WifNetwork> {
+        match prefix {
+            MAINNET_PREFIX => Some(WifNetwork::Mainnet),
+            TESTNET_PREFIX => Some(WifNetwork::Testnet),
+            _ => None,
+        }
+    }
+}
+
+/// Describes how a private key is encoded in WIF.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WifInfo {
+    pub network: WifNetwork,
+    /// Whether the key corresponds to a compressed public key.
+    pub compressed: bool,
+}
+
+/// Encodes the 32 byte `secret` in WIF.
+pub(crate) fn encode(secret: &[u8], info: WifInfo) -> Zeroizing<String> {
+    let mut payload = Zeroizing::new(Vec::with_capacity(1 + H256::LEN + 1 + CHECKSUM_LEN));
+    payload.push(info.network.prefix());
+    payload.extend_from_slice(secret);
+    if info.compressed {
+        payload.push(COMPRESSED_FLAG);
+    }
+
+    let checksum = checksum(payload.as_slice());
+    payload.extend_from_slice(&checksum);
+    Zeroizing::new(base58::encode(payload.as_slice(), Alphabet::BITCOIN))
+}
+
+/// Decodes a WIF string into the 32 byte secret and the [`WifInfo`].
+pub(crate) fn decode(wif: &str) -> KeyPairResult<(Zeroizing<H256>, WifInfo)> {
+    let payload = Zeroizing::new(
+        base58::decode(wif, Alphabet::BITCOIN).map_err(|_| KeyPairError::InvalidSecretKey)?,
+    );
+    if payload.len() < CHECKSUM_LEN {
+        return Err(KeyPairError::InvalidSecretKey);
+    }
+
+    let (data, actual_checksum) = payload.split_at(payload.len() - CHECKSUM_LEN);
+    if checksum(data) != actual_checksum {
+        return Err(KeyPairError::InvalidSecretKey);
+    }
+
+    let (prefix, data) = data.split_first().ok_or(KeyPairError::InvalidSecretKey)?;
+    let network = WifNetwork::from_prefix(*prefix).ok_or(KeyPairError::InvalidSecretKey)?;
+
+    let (secret, compressed) = match data.len() {
+        len if len == H256::LEN => (data, false),
+        len if len == H256::LEN + 1 && data[H256::LEN] == COMPRESSED_FLAG => {
+            (&data[..H256::LEN], true)
+        },
+        _ => return Err(KeyPairError::InvalidSecretKey),
+    };
+
+    let secret = H256::try_from(secret).map_err(|_| KeyPairError::InvalidSecretKey)?;
+    Ok((
+        Zeroizing::new(secret),
+        WifInfo {
+            network,
+            compressed,
+        },
+    ))
+}
+
+/// Returns the first 4 bytes of `sha256(sha256(data))`.
+pub(crate) fn checksum(data: &[u8]) -> [u8; CHECKSUM_LEN] {
+    let hash = sha256(&sha256(data));
+    let mut checksum = [0; CHECKSUM_LEN];
+    checksum.copy_from_slice(&hash[..CHECKSUM_LEN]);
+    checksum
+}
@@ -2,8 +2,9 @@
 //
 // Copyright © 2017 Trust Wallet.
 
-use crate::ecdsa::EcdsaCurve;
+use crate::ecdsa::{der, EcdsaCurve};
 use crate::{KeyPairError, KeyPairResult};
+use ecdsa::elliptic_curve::scalar::IsHigh;
 use ecdsa::elliptic_curve::FieldBytes;
 use std::ops::{Range, RangeInclusive};
 use tw_hash::{H256, H512, H520};
@@ -54,6 +55,24 @@ impl<C: EcdsaCurve> Signature<C> {
         self.v.to_byte()
     }
 
+    /// Whether `s` is in the lower half of the curve order, i.e. the signature is not malleable.
+    /// Required by the Bitcoin standardness rules and Ethereum [EIP-2](https://eips.ethereum.org/EIPS/eip-2).
+    pub fn is_low_s(&self) -> bool {
+        is_low_s(&self.signature)
+    }
+
+    /// Normalizes `s` to the lower half of the curve order, i.e. replaces `s` with `n - s` if `s` is high.
+    /// The recovery ID is flipped accordingly, so the signature recovers the same public key.
+    pub fn normalize_s(self) -> Self {
+        match self.signature.normalize_s() {
+            Some(signature) => Signature {
+                signature,
+                v: ecdsa::RecoveryId::new(!self.v.is_y_odd(), self.v.is_x_reduced()),
+            },
+            None => self,
+        }
+    }
+
     /// Tries to create a Signature from the serialized representation.
     pub fn from_bytes(sig: &[u8]) -> KeyPairResult<Self> {
         if sig.len() != Self::len() {
@@ -89,6 +108,20 @@ impl<C: EcdsaCurve> Signature<C> {
         dest
     }
 
+    /// Returns the ASN.1 DER-encoded signature without the recovery ID.
+    pub fn to_der(&self) -> Vec<u8> {
+        der::Signature::new(self.r().take(), self.s().take()).to_der()
+    }
+
+    /// Tries to create a Signature from the ASN.1 DER-encoded signature and the recovery ID `v`.
+    pub fn from_der(der_sig: &[u8], v: u8) -> KeyPairResult<Self> {
+        let sig = VerifySignature::<C>::from_der(der_sig)?;
+        Ok(Signature {
+            signature: sig.signature,
+            v: ecdsa::RecoveryId::from_byte(v).ok_or(KeyPairError::InvalidSignature)?,
+        })
+    }
+
     /// # Panic
     ///
     /// `r` and `s` must be 32 byte arrays, otherwise the function panics.
@@ -130,6 +163,48 @@ impl<C: EcdsaCurve> VerifySignature<C> {
         dest[Signature::<C>::S_RANGE].copy_from_slice(s.as_slice());
         dest
     }
+
+    /// Whether `s` is in the lower half of the curve order, see [`Signature::is_low_s`].
+    pub fn is_low_s(&self) -> bool {
+        is_low_s(&self.signature)
+    }
+
+    /// Normalizes `s` to the lower half of the curve order, i.e. replaces `s` with `n - s` if `s` is high.
+    pub fn normalize_s(self) -> Self {
+        match self.signature.normalize_s() {
+            Some(signature) => VerifySignature { signature },
+            None => self,
+        }
+    }
+
+    /// Tries to create a signature from the ASN.1 DER-encoded bytes.
+    pub fn from_der(der_sig: &[u8]) -> KeyPairResult<Self> {
+        let sig = der::Signature::from_bytes(der_sig)?;
+        Ok(VerifySignature {
+            signature: Signature::signature_from_slices(sig.r(), sig.s())?,
+        })
+    }
+
+    /// Returns the ASN.1 DER-encoded signature.
+    pub fn to_der(&self) -> Vec<u8> {
+        let (r, s) = self.signature.split_bytes();
+        let r = H256::try_from(r.as_slice()).expect("Expected 'r' 32 byte length array");
+        let s = H256::try_from(s.as_slice()).expect("Expected 's' 32 byte length array");
+        der::Signature::new(r.take(), s.take()).to_der()
+    }
+
+    /// Converts the compact RS or RSV signature into the ASN.1 DER encoding.
+    /// The recovery ID is dropped if present.
+    /// Returns an error if `r` or `s` is zero or not less than the curve order.
+    pub fn compact_to_der(compact: &[u8]) -> KeyPairResult<Vec<u8>> {
+        Ok(VerifySignature::<C>::try_from(compact)?.to_der())
+    }
+
+    /// Converts the ASN.1 DER-encoded signature into the compact RS representation.
+    /// Returns an error if `r` or `s` is zero or not less than the curve order.
+    pub fn der_to_compact(der_sig: &[u8]) -> KeyPairResult<H512> {
+        Ok(VerifySignature::<C>::from_der(der_sig)?.to_bytes())
+    }
 }
 
 impl<C: EcdsaCurve> ToBytesVec for VerifySignature<C> {
@@ -163,10 +238,15 @@ impl<C: EcdsaCurve> From<Signature<C>> for VerifySignature<C> {
     }
 }
 
+fn is_low_s<C: EcdsaCurve>(signature: &ecdsa::Signature<C>) -> bool {
+    !bool::from(signature.s().as_ref().is_high())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use k256::Secp256k1;
+    use tw_encoding::hex;
 
     #[test]
     fn test_signature() {
@@ -184,6 +264,63 @@ mod tests {
         assert_eq!(sign.to_bytes(), sign_bytes);
     }
 
+    #[test]
+    fn test_compact_der_conversion() {
+        let compact = H520::from("d93fc9ae934d4f72db91cb149e7e84b50ca83b5a8a7b873b0fdb009546e3af47786bfaf31af61eea6471dbb1bec7d94f73fb90887e4f04d0e9b85676c47ab02a00");
+        let der_sig = hex::decode("3045022100d93fc9ae934d4f72db91cb149e7e84b50ca83b5a8a7b873b0fdb009546e3af470220786bfaf31af61eea6471dbb1bec7d94f73fb90887e4f04d0e9b85676c47ab02a").unwrap();
+
+        // Both RSV and RS signatures are accepted.
+        let actual = VerifySignature::<Secp256k1>::compact_to_der(compact.as_slice()).unwrap();
+        assert_eq!(actual, der_sig);
+        let actual = VerifySignature::<Secp256k1>::compact_to_der(&compact[..64]).unwrap();
+        assert_eq!(actual, der_sig);
+
+        let actual = VerifySignature::<Secp256k1>::der_to_compact(&der_sig).unwrap();
+        assert_eq!(actual.as_slice(), &compact[..64]);
+
+        let sign = Signature::<Secp256k1>::from_der(&der_sig, 0).unwrap();
+        assert_eq!(sign.to_bytes(), compact);
+    }
+
+    #[test]
+    fn test_compact_der_conversion_invalid() {
+        // `s` is not less than the curve order.
+        let invalid_s = H512::from("d93fc9ae934d4f72db91cb149e7e84b50ca83b5a8a7b873b0fdb009546e3af47ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff");
+        VerifySignature::<Secp256k1>::compact_to_der(invalid_s.as_slice()).unwrap_err();
+        VerifySignature::<Secp256k1>::compact_to_der(&invalid_s[..63]).unwrap_err();
+
+        // `r` is zero.
+        let zero_r = hex::decode("3006020100020110").unwrap();
+        VerifySignature::<Secp256k1>::der_to_compact(&zero_r).unwrap_err();
+        // Trailing data.
+        let trailing = hex::decode("3006020101020110ff").unwrap();
+        VerifySignature::<Secp256k1>::der_to_compact(&trailing).unwrap_err();
+
+        let der_sig = hex::decode("3006020101020110").unwrap();
+        Signature::<Secp256k1>::from_der(&der_sig, 4).unwrap_err();
+    }
+
+    #[test]
+    fn test_signature_normalize_s() {
+        // `s` is replaced with `n - s` of the signature from `test_signature`.
+        let high_s = H520::from("d93fc9ae934d4f72db91cb149e7e84b50ca83b5a8a7b873b0fdb009546e3af478794050ce509e1159b8e244e413826af46b34c5e30f99b6ad61a08160bbb911701");
+        let low_s = H520::from("d93fc9ae934d4f72db91cb149e7e84b50ca83b5a8a7b873b0fdb009546e3af47786bfaf31af61eea6471dbb1bec7d94f73fb90887e4f04d0e9b85676c47ab02a00");
+
+        let sign = Signature::<Secp256k1>::from_bytes(high_s.as_slice()).unwrap();
+        assert!(!sign.is_low_s());
+        let normalized = sign.normalize_s();
+        assert!(normalized.is_low_s());
+        assert_eq!(normalized.to_bytes(), low_s);
+        // Normalizing a low-S signature is a no-op.
+        assert_eq!(normalized.normalize_s().to_bytes(), low_s);
+
+        let verify_sign = VerifySignature::<Secp256k1>::try_from(&high_s[..64]).unwrap();
+        assert!(!verify_sign.is_low_s());
+        let normalized = verify_sign.normalize_s();
+        assert!(normalized.is_low_s());
+        assert_eq!(normalized.to_bytes().as_slice(), &low_s[..64]);
+    }
+
     #[test]
     fn test_signature_from_invalid_bytes() {
         Signature::<Secp256k1>::from_bytes(b"123").unwrap_err();
@@ -0,0 +1,92 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! BIP32-Ed25519 child key derivation scheme (V2) that is used in Cardano blockchain.
+//! Source: https://input-output-hk.github.io/adrestia/static/Ed25519_BIP.pdf
+//! Ported: https://github.com/trustwallet/wallet-core/blob/423f0e34725f69c0a9d535e1a32534c99682edea/trezor-crypto/crypto/bip32.c#L196-L253
+
+use tw_hash::hmac::hmac_sha512;
+use tw_hash::{H256, H512};
+use zeroize::Zeroizing;
+
+/// Indices starting from this value are hardened.
+pub const HARDENED_OFFSET: u32 = 0x8000_0000;
+
+/// HMAC prefixes of a hardened derivation that is computed from the parent private key.
+const HARDENED_PREFIXES: DerivationPrefixes = DerivationPrefixes {
+    z: 0x00,
+    chain_code: 0x01,
+};
+/// HMAC prefixes of a soft derivation that is computed from the parent public key.
+const SOFT_PREFIXES: DerivationPrefixes = DerivationPrefixes {
+    z: 0x02,
+    chain_code: 0x03,
+};
+
+struct DerivationPrefixes {
+    z: u8,
+    chain_code: u8,
+}
+
+/// Returns whether the child `index` should be derived from the parent private key.
+pub(crate) fn is_hardened(index: u32) -> bool {
+    index >= HARDENED_OFFSET
+}
+
+/// Computes the `Z` digest and the child chain code of the given `index`.
+/// `parent_data` should be either `kL || kR` of the parent private key if the index is hardened,
+/// or the parent public key otherwise.
+pub(crate) fn child_digest(
+    chain_code: &H256,
+    parent_data: &[u8],
+    index: u32,
+) -> (Zeroizing<H512>, H256) {
+    let prefixes = if is_hardened(index) {
+        HARDENED_PREFIXES
+    } else {
+        SOFT_PREFIXES
+    };
+
+    let mut input = Zeroizing::new(Vec::with_capacity(1 + parent_data.len() + 4));
+    input.push(prefixes.z);
+    input.extend_from_slice(parent_data);
+    input.extend_from_slice(&index.to_le_bytes());
+
+    let z = Zeroizing::new(hmac_sha512(chain_code.as_slice(), input.as_slice()));
+    let z = Zeroizing::new(H512::try_from(z.as_slice()).expect("HMAC-SHA512 returns 64 bytes"));
+
+    input[0] = prefixes.chain_code;
+    let chain_code_digest = hmac_sha512(chain_code.as_slice(), input.as_slice());
+    let child_chain_code =
+        H256::try_from(&chain_code_digest[32..64]).expect("HMAC-SHA512 returns 64 bytes");
+
+    (z, child_chain_code)
+}
+
+/// Returns `x + 8 * y[0..28]`, where both numbers are little-endian integers.
+/// The result is taken modulo `2^256`.
+pub(crate) fn add_28_mul8(x: &H256, y: &H512) -> H256 {
+    let mut res = H256::default();
+    let mut carry: u16 = 0;
+    for (i, res_byte) in res.iter_mut().enumerate() {
+        let y_byte = if i < 28 { (y[i] as u16) << 3 } else { 0 };
+        let r = x[i] as u16 + y_byte + carry;
+        *res_byte = (r & 0xff) as u8;
+        carry = r >> 8;
+    }
+    res
+}
+
+/// Returns `x + y[32..64]`, where both numbers are little-endian integers.
+/// The result is taken modulo `2^256`.
+pub(crate) fn add_256bits(x: &H256, y: &H512) -> H256 {
+    let mut res = H256::default();
+    let mut carry: u16 = 0;
+    for (i, res_byte) in res.iter_mut().enumerate() {
+        let r = x[i] as u16 + y[32 + i] as u16 + carry;
+        *res_byte = (r & 0xff) as u8;
+        carry = r >> 8;
+    }
+    res
+}
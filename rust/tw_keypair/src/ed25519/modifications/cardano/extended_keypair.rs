@@ -6,7 +6,7 @@ use crate::ed25519::modifications::cardano::{
     extended_private::ExtendedPrivateKey, extended_public::ExtendedPublicKey,
 };
 use crate::ed25519::{signature::Signature, Hasher512};
-use crate::traits::{KeyPairTrait, SigningKeyTrait, VerifyingKeyTrait};
+use crate::traits::{KeyPairTrait, SigningKeyTrait, VerifyMode, VerifyingKeyTrait};
 use crate::{KeyPairError, KeyPairResult};
 use tw_encoding::hex;
 use zeroize::Zeroizing;
@@ -47,6 +47,15 @@ impl<H: Hasher512> VerifyingKeyTrait for ExtendedKeyPair<H> {
     fn verify(&self, signature: Self::VerifySignature, message: Self::SigningMessage) -> bool {
         self.public().verify(signature, message)
     }
+
+    fn verify_with_mode(
+        &self,
+        signature: Self::VerifySignature,
+        message: Self::SigningMessage,
+        mode: VerifyMode,
+    ) -> bool {
+        self.public().verify_with_mode(signature, message, mode)
+    }
 }
 
 impl<'a, H: Hasher512> TryFrom<&'a [u8]> for ExtendedKeyPair<H> {
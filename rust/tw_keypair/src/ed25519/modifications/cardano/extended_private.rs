@@ -2,18 +2,23 @@
 //
 // Copyright © 2017 Trust Wallet.
 
+use crate::ed25519::modifications::cardano::derivation::{
+    add_256bits, add_28_mul8, child_digest, is_hardened,
+};
 use crate::ed25519::modifications::cardano::extended_public::{
     ExtendedPublicKey, ExtendedPublicPart,
 };
+use crate::ed25519::modifications::cardano::master::{master_key, MasterKeyScheme};
 use crate::ed25519::public::PublicKey;
 use crate::ed25519::secret::ExpandedSecretKey;
 use crate::ed25519::signature::Signature;
 use crate::ed25519::Hasher512;
+use crate::mnemonic::{Mnemonic, Passphrase};
 use crate::traits::SigningKeyTrait;
 use crate::{KeyPairError, KeyPairResult};
 use std::ops::Range;
 use tw_encoding::hex;
-use tw_hash::H256;
+use tw_hash::{concat, H256, H512};
 use tw_misc::traits::ToBytesZeroizing;
 use zeroize::{ZeroizeOnDrop, Zeroizing};
 
@@ -31,6 +36,52 @@ impl<H: Hasher512> ExtendedPrivateKey<H> {
     pub const LEN: usize = ExtendedSecretPart::<H>::LEN * 2;
     const KEY_RANGE: Range<usize> = 0..ExtendedSecretPart::<H>::LEN;
     const SECOND_KEY_RANGE: Range<usize> = ExtendedSecretPart::<H>::LEN..Self::LEN;
+    /// The number of bytes in a serialized master key (96 bytes).
+    pub const MASTER_LEN: usize = ExtendedSecretPart::<H>::LEN;
+    /// The number of indices in a Cardano Shelley derivation path:
+    /// `m / purpose' / coin_type' / account' / role / index`.
+    const PATH_LEN: usize = 5;
+    const ROLE_INDEX: usize = 3;
+    const ADDRESS_INDEX: usize = 4;
+    /// External and internal chains are the only roles allowed to be derived.
+    const MAX_ROLE: u32 = 1;
+    const STAKING_ROLE: u32 = 2;
+
+    /// Derives an extended private key from the 96 byte `master` key
+    /// (`secret || extension || chain_code`) according to the BIP32-Ed25519 V2 scheme.
+    ///
+    /// The first half is derived at the given `path`, and the second half is derived
+    /// at the staking path that has the same account, but `2 / 0` role and index.
+    /// `path` must be a Cardano Shelley path, e.g. `m/1852'/1815'/0'/0/0`,
+    /// where hardened indices are offset by [`super::HARDENED_OFFSET`].
+    pub fn derive_from_master(master: &[u8], path: &[u32]) -> KeyPairResult<Self> {
+        if path.len() != Self::PATH_LEN || path[Self::ROLE_INDEX] > Self::MAX_ROLE {
+            return Err(KeyPairError::InvalidSecretKey);
+        }
+        let master = ExtendedSecretPart::<H>::try_from(master)?;
+
+        let mut staking_path = path.to_vec();
+        staking_path[Self::ROLE_INDEX] = Self::STAKING_ROLE;
+        staking_path[Self::ADDRESS_INDEX] = 0;
+
+        Ok(ExtendedPrivateKey {
+            key: master.derive_path(path),
+            second_key: master.derive_path(&staking_path),
+        })
+    }
+
+    /// Derives an extended private key from the BIP39 `mnemonic` at the given Shelley `path`,
+    /// where the master key is generated according to the `scheme` of the wallet that the mnemonic comes from.
+    /// See [`ExtendedPrivateKey::derive_from_master`].
+    pub fn from_mnemonic(
+        mnemonic: &Mnemonic,
+        passphrase: &Passphrase,
+        scheme: MasterKeyScheme,
+        path: &[u32],
+    ) -> KeyPairResult<Self> {
+        let master = master_key(scheme, mnemonic, passphrase);
+        Self::derive_from_master(&master, path)
+    }
 
     /// Returns an associated Cardano extended `ed25519` public key.
     pub fn public(&self) -> ExtendedPublicKey<H> {
@@ -63,6 +114,15 @@ impl<H: Hasher512> SigningKeyTrait for ExtendedPrivateKey<H> {
     fn sign(&self, message: Self::SigningMessage) -> KeyPairResult<Self::Signature> {
         self.sign_with_public_key(&self.public(), message.as_slice())
     }
+
+    /// The public key is computed once for all messages.
+    fn sign_batch(&self, messages: &[Self::SigningMessage]) -> KeyPairResult<Vec<Self::Signature>> {
+        let public = self.public();
+        messages
+            .iter()
+            .map(|message| self.sign_with_public_key(&public, message.as_slice()))
+            .collect()
+    }
 }
 
 impl<H: Hasher512> ToBytesZeroizing for ExtendedPrivateKey<H> {
@@ -73,6 +133,8 @@ impl<H: Hasher512> ToBytesZeroizing for ExtendedPrivateKey<H> {
     }
 }
 
+crate::ct::impl_ct_eq!(ExtendedPrivateKey<H: Hasher512>);
+
 impl<'a, H: Hasher512> TryFrom<&'a [u8]> for ExtendedPrivateKey<H> {
     type Error = KeyPairError;
 
@@ -113,6 +175,40 @@ impl<H: Hasher512> ExtendedSecretPart<H> {
     const SECRET_RANGE: Range<usize> = 0..32;
     const EXTENSION_RANGE: Range<usize> = 32..64;
     const CHAIN_CODE_RANGE: Range<usize> = 64..96;
+
+    fn new(secret: H256, extension: H256, chain_code: H256) -> Self {
+        let expanded_key = ExpandedSecretKey::with_extended_secret(secret, extension);
+        ExtendedSecretPart {
+            secret,
+            extension,
+            chain_code,
+            expanded_key,
+        }
+    }
+
+    /// Derives a descendant key by applying [`ExtendedSecretPart::derive_child`] for each index.
+    fn derive_path(&self, path: &[u32]) -> Self {
+        let mut key = self.derive_child(path[0]);
+        for index in &path[1..] {
+            key = key.derive_child(*index);
+        }
+        key
+    }
+
+    /// Derives a child key according to the BIP32-Ed25519 V2 scheme.
+    fn derive_child(&self, index: u32) -> Self {
+        let (z, chain_code) = if is_hardened(index) {
+            let secret: Zeroizing<H512> = Zeroizing::new(concat(self.secret, self.extension));
+            child_digest(&self.chain_code, secret.as_slice(), index)
+        } else {
+            let public = PublicKey::with_expanded_secret_no_mangle(&self.expanded_key);
+            child_digest(&self.chain_code, public.as_slice(), index)
+        };
+
+        let secret = add_28_mul8(&self.secret, &z);
+        let extension = add_256bits(&self.extension, &z);
+        ExtendedSecretPart::new(secret, extension, chain_code)
+    }
 }
 
 impl<H: Hasher512> ToBytesZeroizing for ExtendedSecretPart<H> {
@@ -139,12 +235,6 @@ impl<'a, H: Hasher512> TryFrom<&'a [u8]> for ExtendedSecretPart<H> {
         let chain_code = H256::try_from(&bytes[Self::CHAIN_CODE_RANGE])
             .map_err(|_| KeyPairError::InvalidSecretKey)?;
 
-        let expanded_key = ExpandedSecretKey::with_extended_secret(secret, extension);
-        Ok(ExtendedSecretPart {
-            secret,
-            extension,
-            chain_code,
-            expanded_key,
-        })
+        Ok(ExtendedSecretPart::new(secret, extension, chain_code))
     }
 }
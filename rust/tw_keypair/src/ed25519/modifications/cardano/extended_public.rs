@@ -5,7 +5,7 @@
 use crate::ed25519::public::PublicKey;
 use crate::ed25519::signature::Signature;
 use crate::ed25519::Hasher512;
-use crate::traits::VerifyingKeyTrait;
+use crate::traits::{VerifyMode, VerifyingKeyTrait};
 use crate::KeyPairError;
 use std::ops::Range;
 use tw_encoding::hex;
@@ -46,6 +46,15 @@ impl<H: Hasher512> VerifyingKeyTrait for ExtendedPublicKey<H> {
     fn verify(&self, signature: Self::VerifySignature, message: Self::SigningMessage) -> bool {
         self.key.public.verify(signature, message)
     }
+
+    fn verify_with_mode(
+        &self,
+        signature: Self::VerifySignature,
+        message: Self::SigningMessage,
+        mode: VerifyMode,
+    ) -> bool {
+        self.key.public.verify_with_mode(signature, message, mode)
+    }
 }
 
 impl<H: Hasher512> ToBytesVec for ExtendedPublicKey<H> {
@@ -0,0 +1,81 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Generation of the 96 byte master key (`kL || kR || chain_code`) from a BIP39 mnemonic.
+//! Source: https://github.com/cardano-foundation/CIPs/blob/master/CIP-0003/CIP-0003.md
+
+use crate::mnemonic::{Mnemonic, Passphrase};
+use sha2::Sha512;
+use tw_hash::hmac::{hmac_sha256, hmac_sha512};
+use tw_hash::{H256, H512};
+use zeroize::Zeroizing;
+
+const MASTER_LEN: usize = 96;
+const ICARUS_ROUNDS: u32 = 4096;
+const LEDGER_HMAC_KEY: &[u8] = b"ed25519 seed";
+const LEDGER_CHAIN_CODE_PREFIX: u8 = 0x01;
+
+/// The scheme that a wallet generated its master key with.
+/// The same mnemonic restores different keys depending on the scheme.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MasterKeyScheme {
+    /// Icarus, used by Daedalus, Yoroi and most software wallets:
+    /// `PBKDF2-HMAC-SHA512(passphrase, salt = entropy, 4096)` of the mnemonic entropy.
+    #[default]
+    Icarus,
+    /// Ledger hardware wallets: `HMAC-SHA512(key = "ed25519 seed", data = seed)` of the BIP39 seed,
+    /// repeated until the key is valid.
+    Ledger,
+}
+
+/// Generates the master key of the given `mnemonic` according to the `scheme`.
+pub fn master_key(
+    scheme: MasterKeyScheme,
+    mnemonic: &Mnemonic,
+    passphrase: &Passphrase,
+) -> Zeroizing<Vec<u8>> {
+    match scheme {
+        MasterKeyScheme::Icarus => icarus_master_key(mnemonic, passphrase),
+        MasterKeyScheme::Ledger => ledger_master_key(mnemonic, passphrase),
+    }
+}
+
+fn icarus_master_key(mnemonic: &Mnemonic, passphrase: &Passphrase) -> Zeroizing<Vec<u8>> {
+    let entropy = mnemonic.to_entropy();
+    let mut key = Zeroizing::new(vec![0; MASTER_LEN]);
+    pbkdf2::pbkdf2_hmac::<Sha512>(
+        passphrase.as_str().as_bytes(),
+        &entropy,
+        ICARUS_ROUNDS,
+        &mut key,
+    );
+
+    key[0] &= 0b1111_1000;
+    key[31] &= 0b0001_1111;
+    key[31] |= 0b0100_0000;
+    key
+}
+
+fn ledger_master_key(mnemonic: &Mnemonic, passphrase: &Passphrase) -> Zeroizing<Vec<u8>> {
+    let seed = mnemonic.to_seed(passphrase);
+
+    // The third highest bit of `kL` must be cleared.
+    let mut digest = Zeroizing::new(hmac_sha512(LEDGER_HMAC_KEY, seed.as_slice()));
+    while digest[31] & 0b0010_0000 != 0 {
+        digest = Zeroizing::new(hmac_sha512(LEDGER_HMAC_KEY, &digest));
+    }
+
+    let mut key = Zeroizing::new(Vec::with_capacity(MASTER_LEN));
+    key.extend_from_slice(&digest);
+    key[0] &= 0b1111_1000;
+    key[31] &= 0b0111_1111;
+    key[31] |= 0b0100_0000;
+
+    let mut chain_code_data = Zeroizing::new(Vec::with_capacity(1 + H512::LEN));
+    chain_code_data.push(LEDGER_CHAIN_CODE_PREFIX);
+    chain_code_data.extend_from_slice(seed.as_slice());
+    let chain_code = Zeroizing::new(hmac_sha256(LEDGER_HMAC_KEY, &chain_code_data));
+    key.extend_from_slice(&chain_code[..H256::LEN]);
+    key
+}
@@ -4,9 +4,14 @@
 
 use sha2::Sha512;
 
+mod derivation;
 mod extended_keypair;
 mod extended_private;
 mod extended_public;
+mod master;
+
+pub use derivation::HARDENED_OFFSET;
+pub use master::{master_key, MasterKeyScheme};
 
 pub type ExtendedKeyPair = extended_keypair::ExtendedKeyPair<Sha512>;
 pub type ExtendedPrivateKey = extended_private::ExtendedPrivateKey<Sha512>;
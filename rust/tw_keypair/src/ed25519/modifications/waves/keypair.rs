@@ -6,7 +6,7 @@ use crate::ed25519::modifications::waves::private::PrivateKey;
 use crate::ed25519::modifications::waves::public::PublicKey;
 use crate::ed25519::modifications::waves::Signature;
 use crate::ed25519::Hasher512;
-use crate::traits::{KeyPairTrait, SigningKeyTrait, VerifyingKeyTrait};
+use crate::traits::{KeyPairTrait, SigningKeyTrait, VerifyMode, VerifyingKeyTrait};
 use crate::{KeyPairError, KeyPairResult};
 use tw_encoding::hex;
 use zeroize::Zeroizing;
@@ -46,6 +46,15 @@ impl<H: Hasher512> VerifyingKeyTrait for KeyPair<H> {
     fn verify(&self, signature: Self::VerifySignature, message: Self::SigningMessage) -> bool {
         self.public.verify(signature, message)
     }
+
+    fn verify_with_mode(
+        &self,
+        signature: Self::VerifySignature,
+        message: Self::SigningMessage,
+        mode: VerifyMode,
+    ) -> bool {
+        self.public.verify_with_mode(signature, message, mode)
+    }
 }
 
 impl<'a, H: Hasher512> TryFrom<&'a [u8]> for KeyPair<H> {
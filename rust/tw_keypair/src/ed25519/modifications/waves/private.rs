@@ -57,3 +57,5 @@ impl<H: Hasher512> ToBytesZeroizing for PrivateKey<H> {
         self.standard_key.to_zeroizing_vec()
     }
 }
+
+crate::ct::impl_ct_eq!(PrivateKey<H: Hasher512>);
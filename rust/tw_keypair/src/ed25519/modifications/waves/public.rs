@@ -5,7 +5,7 @@
 use crate::ed25519::modifications::waves::signature::Signature;
 use crate::ed25519::public::PublicKey as StandardPublicKey;
 use crate::ed25519::Hasher512;
-use crate::traits::VerifyingKeyTrait;
+use crate::traits::{VerifyMode, VerifyingKeyTrait};
 use crate::KeyPairError;
 use curve25519_dalek::montgomery::MontgomeryPoint;
 use std::marker::PhantomData;
@@ -47,6 +47,15 @@ impl<H: Hasher512> VerifyingKeyTrait for PublicKey<H> {
     type VerifySignature = Signature;
 
     fn verify(&self, signature: Self::VerifySignature, message: Self::SigningMessage) -> bool {
+        self.verify_with_mode(signature, message, VerifyMode::Lax)
+    }
+
+    fn verify_with_mode(
+        &self,
+        signature: Self::VerifySignature,
+        message: Self::SigningMessage,
+        mode: VerifyMode,
+    ) -> bool {
         let Ok(standard_signature) = signature.to_standard_signature() else {
             return false;
         };
@@ -59,7 +68,7 @@ impl<H: Hasher512> VerifyingKeyTrait for PublicKey<H> {
         };
         let standard_public = StandardPublicKey::<H>::with_edwards_point(ed25519_pk);
 
-        standard_public.verify(standard_signature, message)
+        standard_public.verify_with_mode(standard_signature, message, mode)
     }
 }
 
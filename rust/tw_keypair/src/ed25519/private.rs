@@ -5,12 +5,17 @@
 use crate::ed25519::public::PublicKey;
 use crate::ed25519::secret::ExpandedSecretKey;
 use crate::ed25519::signature::Signature;
-use crate::ed25519::Hasher512;
+use crate::ed25519::{dom2, Hasher512, ED25519_ALGORITHM};
+use crate::pem;
 use crate::traits::SigningKeyTrait;
 use crate::{KeyPairError, KeyPairResult};
+use der::asn1::OctetStringRef;
+use der::{Decode, Encode};
+use rand_core::{CryptoRng, RngCore};
+use sha2::Sha512;
 use std::fmt;
 use tw_encoding::hex;
-use tw_hash::H256;
+use tw_hash::{H256, H512};
 use tw_misc::traits::ToBytesZeroizing;
 use zeroize::{ZeroizeOnDrop, Zeroizing};
 
@@ -32,11 +37,58 @@ impl<H: Hasher512> fmt::Debug for PrivateKey<H> {
 }
 
 impl<H: Hasher512> PrivateKey<H> {
+    /// Generates a random private key using the given `rng`.
+    pub fn generate(rng: &mut (impl CryptoRng + RngCore)) -> Self {
+        let mut secret = H256::default();
+        rng.fill_bytes(secret.as_mut_slice());
+        let expanded_key = ExpandedSecretKey::<H>::with_secret(secret);
+        PrivateKey {
+            secret,
+            expanded_key,
+        }
+    }
+
     /// Returns an associated `ed25519` public key.
     pub fn public(&self) -> PublicKey<H> {
         PublicKey::with_expanded_secret(&self.expanded_key)
     }
 
+    /// Computes an X25519 Diffie-Hellman secret with the given `ed25519` public key.
+    ///
+    /// Both keys are converted to their Montgomery (Curve25519) form,
+    /// so the result equals `X25519(clamped_scalar, u)` as specified in [RFC 7748](https://www.rfc-editor.org/rfc/rfc7748#section-6.1).
+    /// Returns an error if the public key is of a small order, i.e. the shared secret is all zeros.
+    pub fn shared_key(&self, public: &PublicKey<H>) -> KeyPairResult<H256> {
+        let shared_point = public.edwards_point() * self.expanded_key.key;
+        let shared_secret = H256::from(shared_point.to_montgomery().to_bytes());
+        if shared_secret.is_zero() {
+            return Err(KeyPairError::InvalidPublicKey);
+        }
+        Ok(shared_secret)
+    }
+
+    /// Signs the given `prehash` according to Ed25519ph as specified in [RFC 8032](https://www.rfc-editor.org/rfc/rfc8032#section-5.1).
+    /// `prehash` is expected to be `SHA-512(message)`, `context` may be empty.
+    pub fn sign_prehashed(&self, prehash: &H512, context: &[u8]) -> KeyPairResult<Signature> {
+        let domain = dom2(true, context)?;
+        self.expanded_key.sign_with_pubkey_and_domain(
+            self.public().to_bytes(),
+            &domain,
+            prehash.as_slice(),
+        )
+    }
+
+    /// Signs the given `message` according to Ed25519ctx as specified in [RFC 8032](https://www.rfc-editor.org/rfc/rfc8032#section-5.1).
+    /// Returns an error if `context` is empty or longer than 255 bytes.
+    pub fn sign_with_context(&self, message: &[u8], context: &[u8]) -> KeyPairResult<Signature> {
+        if context.is_empty() {
+            return Err(KeyPairError::InvalidSignMessage);
+        }
+        let domain = dom2(false, context)?;
+        self.expanded_key
+            .sign_with_pubkey_and_domain(self.public().to_bytes(), &domain, message)
+    }
+
     /// `ed25519` signing uses a public key associated with the private key.
     pub(crate) fn sign_with_public_key(
         &self,
@@ -48,6 +100,53 @@ impl<H: Hasher512> PrivateKey<H> {
     }
 }
 
+/// PKCS#8 encoding as specified in [RFC 8410](https://www.rfc-editor.org/rfc/rfc8410#section-7).
+/// Supported by the standard `ed25519` only.
+impl PrivateKey<Sha512> {
+    /// Decodes a private key from the PKCS#8 DER document, e.g. produced by `openssl genpkey`.
+    pub fn from_pkcs8_der(der: &[u8]) -> KeyPairResult<Self> {
+        let info =
+            pkcs8::PrivateKeyInfo::try_from(der).map_err(|_| KeyPairError::InvalidSecretKey)?;
+        if info.algorithm.oid != ED25519_ALGORITHM.oid || info.algorithm.parameters.is_some() {
+            return Err(KeyPairError::InvalidSecretKey);
+        }
+
+        // The secret is an `OCTET STRING` wrapped into the `PrivateKeyInfo` octet string.
+        let secret = OctetStringRef::from_der(info.private_key)
+            .map_err(|_| KeyPairError::InvalidSecretKey)?;
+        let private = PrivateKey::try_from(secret.as_bytes())?;
+
+        // PKCS#8 v2 document may contain the public key that must match the secret.
+        if let Some(public) = info.public_key {
+            if public != private.public().as_slice() {
+                return Err(KeyPairError::InvalidSecretKey);
+            }
+        }
+        Ok(private)
+    }
+
+    /// Encodes the private key as a PKCS#8 v1 DER document.
+    pub fn to_pkcs8_der(&self) -> Zeroizing<Vec<u8>> {
+        let secret = OctetStringRef::new(self.secret.as_slice())
+            .and_then(|secret| secret.to_der())
+            .map(Zeroizing::new)
+            .expect("Expected a valid 32 byte secret");
+        let info = pkcs8::PrivateKeyInfo::new(ED25519_ALGORITHM, &secret);
+        Zeroizing::new(info.to_der().expect("Expected a valid PKCS#8 document"))
+    }
+
+    /// Decodes a private key from the PKCS#8 `PRIVATE KEY` PEM document.
+    pub fn from_pkcs8_pem(pem: &str) -> KeyPairResult<Self> {
+        let doc = pem::decode_private_key(pem)?;
+        PrivateKey::from_pkcs8_der(doc.as_bytes())
+    }
+
+    /// Encodes the private key as a PKCS#8 `PRIVATE KEY` PEM document.
+    pub fn to_pkcs8_pem(&self) -> Zeroizing<String> {
+        pem::encode_private_key(&self.to_pkcs8_der())
+    }
+}
+
 impl<H: Hasher512> SigningKeyTrait for PrivateKey<H> {
     type SigningMessage = Vec<u8>;
     type Signature = Signature;
@@ -55,6 +154,15 @@ impl<H: Hasher512> SigningKeyTrait for PrivateKey<H> {
     fn sign(&self, message: Self::SigningMessage) -> KeyPairResult<Self::Signature> {
         self.sign_with_public_key(&self.public(), &message)
     }
+
+    /// The public key is computed once for all messages.
+    fn sign_batch(&self, messages: &[Self::SigningMessage]) -> KeyPairResult<Vec<Self::Signature>> {
+        let public = self.public();
+        messages
+            .iter()
+            .map(|message| self.sign_with_public_key(&public, message))
+            .collect()
+    }
 }
 
 impl<H: Hasher512> TryFrom<&[u8]> for PrivateKey<H> {
@@ -84,3 +192,5 @@ impl<H: Hasher512> ToBytesZeroizing for PrivateKey<H> {
         Zeroizing::new(self.secret.to_vec())
     }
 }
+
+crate::ct::impl_ct_eq!(PrivateKey<H: Hasher512>);
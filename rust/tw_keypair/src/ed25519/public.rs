@@ -5,16 +5,20 @@
 use crate::ed25519::mangle::mangle_scalar;
 use crate::ed25519::secret::ExpandedSecretKey;
 use crate::ed25519::signature::Signature;
-use crate::ed25519::Hasher512;
-use crate::traits::VerifyingKeyTrait;
-use crate::KeyPairError;
+use crate::ed25519::{dom2, Hasher512, ED25519_ALGORITHM};
+use crate::pem;
+use crate::traits::{VerifyMode, VerifyingKeyTrait};
+use crate::{KeyPairError, KeyPairResult};
 use curve25519_dalek::constants;
 use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
 use curve25519_dalek::scalar::Scalar;
+use der::asn1::BitStringRef;
+use der::Encode;
+use sha2::Sha512;
 use std::fmt;
 use std::marker::PhantomData;
 use tw_encoding::hex;
-use tw_hash::H256;
+use tw_hash::{H256, H512};
 use tw_misc::traits::ToBytesVec;
 use tw_misc::try_or_false;
 
@@ -76,11 +80,77 @@ impl<H: Hasher512> PublicKey<H> {
         self.compressed.as_bytes()
     }
 
+    /// Returns the X25519 public key, i.e. the Montgomery `u` coordinate of the point (32 bytes).
+    pub fn to_x25519(&self) -> H256 {
+        H256::from(self.point.to_montgomery().to_bytes())
+    }
+
+    /// Verifies the given Ed25519ph `signature` of the `prehash`, i.e. `SHA-512(message)`.
+    /// See `PrivateKey::sign_prehashed`.
+    pub fn verify_prehashed(&self, signature: Signature, prehash: &H512, context: &[u8]) -> bool {
+        let domain = try_or_false!(dom2(true, context));
+        self.verify_with_domain(signature, &domain, prehash.as_slice(), VerifyMode::Lax)
+    }
+
+    /// Verifies the given Ed25519ctx `signature` of the `message` with the non-empty `context`.
+    /// See `PrivateKey::sign_with_context`.
+    pub fn verify_with_context(
+        &self,
+        signature: Signature,
+        message: &[u8],
+        context: &[u8],
+    ) -> bool {
+        if context.is_empty() {
+            return false;
+        }
+        let domain = try_or_false!(dom2(false, context));
+        self.verify_with_domain(signature, &domain, message, VerifyMode::Lax)
+    }
+
     /// Returns a reference to the [`EdwardsPoint`].
     pub(crate) fn edwards_point(&self) -> &EdwardsPoint {
         &self.point
     }
 
+    /// Verifies the `signature` of the `message`, prepending the `domain` to the hashed data.
+    /// Source: https://github.com/dalek-cryptography/ed25519-dalek/blob/1.0.1/src/public.rs#L220-L319
+    #[allow(non_snake_case)]
+    fn verify_with_domain(
+        &self,
+        signature: Signature,
+        domain: &[u8],
+        message: &[u8],
+        mode: VerifyMode,
+    ) -> bool {
+        let mut h = H::new();
+        let minus_A: EdwardsPoint = -self.point;
+
+        let signature_R = try_or_false!(signature.R.decompress());
+
+        // Logical OR is fine here as we're not trying to be constant time.
+        if signature_R.is_small_order() || self.point.is_small_order() {
+            return false;
+        }
+
+        // Reject non-canonical encodings and points that are not in the prime order subgroup.
+        if mode == VerifyMode::Strict
+            && !(is_canonical_point(&signature.R, &signature_R)
+                && is_canonical_point(&self.compressed, &self.point))
+        {
+            return false;
+        }
+
+        h.update(domain);
+        h.update(signature.R.as_bytes());
+        h.update(self.as_slice());
+        h.update(message);
+
+        let k = Scalar::from_hash(h);
+        let R = EdwardsPoint::vartime_double_scalar_mul_basepoint(&k, &(minus_A), &signature.s);
+
+        R == signature_R
+    }
+
     /// Internal utility function for mangling the bits of a (formerly
     /// mathematically well-defined) "scalar" and multiplying it to produce a
     /// public key.
@@ -108,27 +178,63 @@ impl<H: Hasher512> VerifyingKeyTrait for PublicKey<H> {
     type SigningMessage = Vec<u8>;
     type VerifySignature = Signature;
 
-    /// Source: https://github.com/dalek-cryptography/ed25519-dalek/blob/1.0.1/src/public.rs#L220-L319
-    #[allow(non_snake_case)]
+    /// Accepts non-canonical points, i.e. equals to [`VerifyMode::Lax`].
     fn verify(&self, signature: Self::VerifySignature, message: Self::SigningMessage) -> bool {
-        let mut h = H::new();
-        let minus_A: EdwardsPoint = -self.point;
+        self.verify_with_mode(signature, message, VerifyMode::Lax)
+    }
 
-        let signature_R = try_or_false!(signature.R.decompress());
+    fn verify_with_mode(
+        &self,
+        signature: Self::VerifySignature,
+        message: Self::SigningMessage,
+        mode: VerifyMode,
+    ) -> bool {
+        self.verify_with_domain(signature, &[], &message, mode)
+    }
+}
 
-        // Logical OR is fine here as we're not trying to be constant time.
-        if signature_R.is_small_order() || self.point.is_small_order() {
-            return false;
+/// Checks if the `point` is encoded canonically and belongs to the prime order subgroup.
+fn is_canonical_point(compressed: &CompressedEdwardsY, point: &EdwardsPoint) -> bool {
+    point.compress() == *compressed && point.is_torsion_free()
+}
+
+/// `SubjectPublicKeyInfo` encoding as specified in [RFC 8410](https://www.rfc-editor.org/rfc/rfc8410#section-4).
+/// Supported by the standard `ed25519` only.
+impl PublicKey<Sha512> {
+    /// Returns the public key as DER-encoded `SubjectPublicKeyInfo` bytes.
+    pub fn der_encoded(&self) -> Vec<u8> {
+        let spki = pkcs8::SubjectPublicKeyInfoRef {
+            algorithm: ED25519_ALGORITHM,
+            subject_public_key: BitStringRef::new(0, self.as_slice())
+                .expect("Expected a valid 32 byte public key"),
+        };
+        spki.to_der().expect("Expected a valid SPKI document")
+    }
+
+    /// Parses a public key from the DER-encoded `SubjectPublicKeyInfo`, see [`PublicKey::der_encoded`].
+    pub fn from_der_encoded(der: &[u8]) -> KeyPairResult<Self> {
+        let spki = pkcs8::SubjectPublicKeyInfoRef::try_from(der)
+            .map_err(|_| KeyPairError::InvalidPublicKey)?;
+        if spki.algorithm.oid != ED25519_ALGORITHM.oid || spki.algorithm.parameters.is_some() {
+            return Err(KeyPairError::InvalidPublicKey);
         }
 
-        h.update(signature.R.as_bytes());
-        h.update(self.as_slice());
-        h.update(&message);
+        let public = spki
+            .subject_public_key
+            .as_bytes()
+            .ok_or(KeyPairError::InvalidPublicKey)?;
+        PublicKey::try_from(public)
+    }
 
-        let k = Scalar::from_hash(h);
-        let R = EdwardsPoint::vartime_double_scalar_mul_basepoint(&k, &(minus_A), &signature.s);
+    /// Returns the public key as a `PUBLIC KEY` PEM document.
+    pub fn pem_encoded(&self) -> String {
+        pem::encode_public_key(&self.der_encoded())
+    }
 
-        R == signature_R
+    /// Parses a public key from the `PUBLIC KEY` PEM document.
+    pub fn from_pem_encoded(pem: &str) -> KeyPairResult<Self> {
+        let doc = pem::decode_public_key(pem)?;
+        PublicKey::from_der_encoded(doc.as_bytes())
     }
 }
 
@@ -69,14 +69,26 @@ impl<H: Hasher512> ExpandedSecretKey<H> {
     /// Signs a message with this `ExpandedSecretKey`.
     /// Source: https://github.com/dalek-cryptography/ed25519-dalek/blob/1.0.1/src/secret.rs#L389-L412
     /// Ported: https://github.com/trustwallet/wallet-core/blob/423f0e34725f69c0a9d535e1a32534c99682edea/trezor-crypto/crypto/ed25519-donna/ed25519.c#L97-L130
-    #[allow(non_snake_case)]
     pub(crate) fn sign_with_pubkey(
         &self,
         pubkey: H256,
         message: &[u8],
+    ) -> KeyPairResult<Signature> {
+        self.sign_with_pubkey_and_domain(pubkey, &[], message)
+    }
+
+    /// Signs a message with this `ExpandedSecretKey`,
+    /// prepending the `domain` to the hashed data, e.g. [`crate::ed25519::dom2`].
+    #[allow(non_snake_case)]
+    pub(crate) fn sign_with_pubkey_and_domain(
+        &self,
+        pubkey: H256,
+        domain: &[u8],
+        message: &[u8],
     ) -> KeyPairResult<Signature> {
         let mut h = H::new();
 
+        h.update(domain);
         h.update(self.nonce.as_slice());
         h.update(message);
 
@@ -84,6 +96,7 @@ impl<H: Hasher512> ExpandedSecretKey<H> {
         let R = (&r * constants::ED25519_BASEPOINT_TABLE).compress();
 
         h = H::new();
+        h.update(domain);
         h.update(R.as_bytes());
         h.update(pubkey);
         h.update(message);
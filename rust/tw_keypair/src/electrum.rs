@@ -0,0 +1,317 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! [Electrum](https://electrum.readthedocs.io/en/latest/seedphrase.html) seed phrases, so wallets created
+//! in Electrum can be restored with the same keys and addresses:
+//!
+//! ```rust,ignore
+//! use tw_keypair::electrum::ElectrumSeed;
+//!
+//! let seed = ElectrumSeed::parse(PHRASE)?;
+//! let account = seed.account_key(PASSPHRASE)?;
+//! let receive = account.derive_path(&[0, 0])?;
+//! ```
+//!
+//! Unlike BIP39, the phrase has no checksum. Its version is the prefix of `HMAC-SHA512(key = "Seed version", data = phrase)`,
+//! and it's stretched to a seed with `PBKDF2-HMAC-SHA512(phrase, salt = "electrum" || passphrase, 2048)`.
+//! The old, pre-2.0 seeds and the two-factor seeds are not supported.
+
+use crate::bip32::slip132::{KeyVersion, Network, ScriptType};
+use crate::bip32::{ExtendedPrivateKey, Secp256k1, HARDENED_OFFSET};
+use crate::mnemonic::Language;
+use crate::{KeyPairError, KeyPairResult};
+use rand_core::{CryptoRng, RngCore};
+use sha2::Sha512;
+use tw_encoding::hex;
+use tw_hash::hmac::hmac_sha512;
+use tw_hash::H512;
+use unicode_normalization::char::canonical_combining_class;
+use unicode_normalization::UnicodeNormalization;
+use zeroize::Zeroizing;
+
+const VERSION_KEY: &[u8] = b"Seed version";
+const SALT_PREFIX: &str = "electrum";
+const PBKDF2_ROUNDS: u32 = 2048;
+/// The generated phrases encode 132 bits.
+const WORD_COUNT: usize = 12;
+
+/// The CJK ranges as defined by Electrum. Whitespaces between CJK characters are removed on normalization.
+const CJK_INTERVALS: [(u32, u32); 29] = [
+    (0x4E00, 0x9FFF),
+    (0x3400, 0x4DBF),
+    (0x20000, 0x2A6DF),
+    (0x2A700, 0x2B73F),
+    (0x2B740, 0x2B81F),
+    (0xF900, 0xFAFF),
+    (0x2F800, 0x2FA1D),
+    (0x3190, 0x319F),
+    (0x2E80, 0x2EFF),
+    (0x2F00, 0x2FDF),
+    (0x31C0, 0x31EF),
+    (0x2FF0, 0x2FFF),
+    (0xE0100, 0xE01EF),
+    (0x3100, 0x312F),
+    (0x31A0, 0x31BF),
+    (0xFF00, 0xFFEF),
+    (0x3040, 0x309F),
+    (0x30A0, 0x30FF),
+    (0x31F0, 0x31FF),
+    (0x1B000, 0x1B0FF),
+    (0xAC00, 0xD7AF),
+    (0x1100, 0x11FF),
+    (0xA960, 0xA97F),
+    (0xD7B0, 0xD7FF),
+    (0x3130, 0x318F),
+    (0xA4D0, 0xA4FF),
+    (0x16F00, 0x16F9F),
+    (0xA000, 0xA48F),
+    (0xA490, 0xA4CF),
+];
+
+/// The type of an Electrum wallet, which determines its derivation scheme.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SeedType {
+    /// P2PKH addresses derived at `m/change/index`.
+    Standard,
+    /// P2WPKH addresses derived at `m/0'/change/index`.
+    Segwit,
+}
+
+impl SeedType {
+    /// Returns the hex prefix of the seed version.
+    fn prefix(self) -> &'static str {
+        match self {
+            SeedType::Standard => "01",
+            SeedType::Segwit => "100",
+        }
+    }
+
+    /// Returns the path of the account key relative to the master key.
+    pub fn account_path(self) -> &'static [u32] {
+        match self {
+            SeedType::Standard => &[],
+            SeedType::Segwit => &[HARDENED_OFFSET],
+        }
+    }
+
+    /// Returns the version of the account's extended keys, i.e. `xpub` or `zpub`.
+    pub fn key_version(self) -> KeyVersion {
+        let script_type = match self {
+            SeedType::Standard => ScriptType::P2pkh,
+            SeedType::Segwit => ScriptType::P2wpkh,
+        };
+        KeyVersion::new(script_type, Network::Mainnet)
+    }
+}
+
+/// Represents a valid Electrum seed phrase. The phrase is zeroized on drop.
+pub struct ElectrumSeed {
+    phrase: Zeroizing<String>,
+    seed_type: SeedType,
+}
+
+/// cbindgen:ignore
+impl ElectrumSeed {
+    /// Generates a new 12 word English phrase of the given type.
+    pub fn generate(seed_type: SeedType, rng: &mut (impl CryptoRng + RngCore)) -> ElectrumSeed {
+        let words = Language::English.word_list();
+        loop {
+            // Electrum increments a nonce until the version matches. Drawing new random words is equivalent,
+            // as the phrase is valid with the same probability.
+            let mut phrase = Zeroizing::new(String::new());
+            for i in 0..WORD_COUNT {
+                if i > 0 {
+                    phrase.push(' ');
+                }
+                // 2048 divides 2^32, so the indices are uniform.
+                phrase.push_str(words[rng.next_u32() as usize % words.len()]);
+            }
+            if version_of(&phrase).starts_with(seed_type.prefix()) {
+                return ElectrumSeed { phrase, seed_type };
+            }
+        }
+    }
+
+    /// Parses a phrase of any language and detects its seed type.
+    pub fn parse(phrase: &str) -> KeyPairResult<ElectrumSeed> {
+        let phrase = normalize(phrase);
+        if phrase.is_empty() {
+            return Err(KeyPairError::InvalidMnemonic);
+        }
+        let version = version_of(&phrase);
+        let seed_type = [SeedType::Standard, SeedType::Segwit]
+            .into_iter()
+            .find(|seed_type| version.starts_with(seed_type.prefix()))
+            .ok_or(KeyPairError::InvalidMnemonic)?;
+        Ok(ElectrumSeed { phrase, seed_type })
+    }
+
+    /// Returns whether the phrase is a valid Electrum seed of a supported type.
+    pub fn is_valid(phrase: &str) -> bool {
+        ElectrumSeed::parse(phrase).is_ok()
+    }
+
+    pub fn seed_type(&self) -> SeedType {
+        self.seed_type
+    }
+
+    /// Returns the normalized phrase.
+    pub fn phrase(&self) -> &str {
+        &self.phrase
+    }
+
+    /// Stretches the phrase with an optional `passphrase` to the 64 byte BIP32 seed.
+    pub fn to_seed(&self, passphrase: &str) -> Zeroizing<H512> {
+        let mut salt = normalize(passphrase);
+        salt.insert_str(0, SALT_PREFIX);
+
+        let mut seed = Zeroizing::new(H512::default());
+        pbkdf2::pbkdf2_hmac::<Sha512>(
+            self.phrase.as_bytes(),
+            salt.as_bytes(),
+            PBKDF2_ROUNDS,
+            seed.as_mut_slice(),
+        );
+        seed
+    }
+
+    /// Derives the account key of the wallet, whose `0/index` and `1/index` children are
+    /// the receive and change keys respectively.
+    pub fn account_key(&self, passphrase: &str) -> KeyPairResult<ExtendedPrivateKey<Secp256k1>> {
+        let seed = self.to_seed(passphrase);
+        ExtendedPrivateKey::from_seed(seed.as_slice())?.derive_path(self.seed_type.account_path())
+    }
+}
+
+/// Returns the hex encoded seed version of the normalized `phrase`.
+fn version_of(phrase: &str) -> String {
+    let hash = hmac_sha512(VERSION_KEY, phrase.as_bytes());
+    // Only the first 2 bytes are needed to match the prefixes.
+    hex::encode(&hash[..2], false)
+}
+
+/// Normalizes the phrase as Electrum does: NFKD, lowercase, no accents,
+/// single spaces between words, and no spaces between CJK characters.
+fn normalize(s: &str) -> Zeroizing<String> {
+    let decomposed = Zeroizing::new(s.nfkd().collect::<String>());
+    let mut stripped = Zeroizing::new(decomposed.to_lowercase());
+    stripped.retain(|c| canonical_combining_class(c) == 0);
+
+    let mut words = Zeroizing::new(String::with_capacity(stripped.len()));
+    for word in stripped.split_whitespace() {
+        if !words.is_empty() {
+            words.push(' ');
+        }
+        words.push_str(word);
+    }
+
+    let chars = Zeroizing::new(words.chars().collect::<Vec<_>>());
+    let mut normalized = Zeroizing::new(String::with_capacity(words.len()));
+    for (i, c) in chars.iter().enumerate() {
+        // Words are single space separated, so a space has characters on both sides.
+        let between_cjk = *c == ' ' && is_cjk(chars[i - 1]) && is_cjk(chars[i + 1]);
+        if !between_cjk {
+            normalized.push(*c);
+        }
+    }
+    normalized
+}
+
+fn is_cjk(c: char) -> bool {
+    let c = c as u32;
+    CJK_INTERVALS
+        .iter()
+        .any(|(start, end)| (*start..=*end).contains(&c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_core::OsRng;
+
+    const SEGWIT_PHRASE: &str =
+        "wild father tree among universe such mobile favorite target dynamic credit identify";
+
+    /// https://github.com/spesmilo/electrum/blob/master/tests/test_mnemonic.py
+    #[test]
+    fn test_electrum_seed() {
+        let seed = ElectrumSeed::parse(SEGWIT_PHRASE).unwrap();
+        assert_eq!(seed.seed_type(), SeedType::Segwit);
+        assert_eq!(
+            hex::encode(seed.to_seed("").as_slice(), false),
+            "aac2a6302e48577ab4b46f23dbae0774e2e62c796f797d0a1b5faeb528301e3064342dafb79069e7c4c6b8c38ae11d7a973bec0d4f70626f8cc5184a8d0b0756"
+        );
+        assert_eq!(
+            hex::encode(
+                seed.to_seed("Did you ever hear the tragedy of Darth Plagueis the Wise?")
+                    .as_slice(),
+                false
+            ),
+            "4aa29f2aeb0127efb55138ab9e7be83b36750358751906f86c662b21a1ea1370f949e6d1a12fa56d3d93cadda93038c76ac8118597364e46f5156fde6183c82f"
+        );
+
+        // The phrase is normalized.
+        let seed = ElectrumSeed::parse(
+            " Wild  father tree among universe such mobile favorite target dynamic credit IDENTIFY\n",
+        )
+        .unwrap();
+        assert_eq!(seed.phrase(), SEGWIT_PHRASE);
+    }
+
+    #[test]
+    fn test_electrum_account_key() {
+        let seed = ElectrumSeed::parse(
+            "cycle rocket west magnet parrot shuffle foot correct salt library feed song",
+        )
+        .unwrap();
+        assert_eq!(seed.seed_type(), SeedType::Standard);
+        let account = seed.account_key("").unwrap();
+        assert_eq!(
+            account
+                .public()
+                .to_slip132(seed.seed_type().key_version()),
+            "xpub661MyMwAqRbcFWohJWt7PHsFEJfZAvw9ZxwQoDa4SoMgsDDM1T7WK3u9E4edkC4ugRnZ8E4xDZRpk8Rnts3Nbt97dPwT52CwBdDWroaZf8U"
+        );
+
+        let seed = ElectrumSeed::parse(
+            "bitter grass shiver impose acquire brush forget axis eager alone wine silver",
+        )
+        .unwrap();
+        assert_eq!(seed.seed_type(), SeedType::Segwit);
+        let account = seed.account_key("").unwrap();
+        assert_eq!(account.depth(), 1);
+        assert_eq!(
+            account
+                .public()
+                .to_slip132(seed.seed_type().key_version()),
+            "zpub6nsHdRuY92FsMKdbn9BfjBCG6X8pyhCibNP6uDvpnw2cyrVhecvHRMa3Ne8kdJZxjxgwnpbHLkcR4bfnhHy6auHPJyDTQ3kianeuVLdkCYQ"
+        );
+    }
+
+    #[test]
+    fn test_electrum_generate() {
+        for seed_type in [SeedType::Standard, SeedType::Segwit] {
+            let seed = ElectrumSeed::generate(seed_type, &mut OsRng);
+            assert_eq!(seed.phrase().split(' ').count(), WORD_COUNT);
+            let parsed = ElectrumSeed::parse(seed.phrase()).unwrap();
+            assert_eq!(parsed.seed_type(), seed_type);
+        }
+    }
+
+    #[test]
+    fn test_electrum_invalid() {
+        // A BIP39 mnemonic is not a valid Electrum seed.
+        assert!(!ElectrumSeed::is_valid(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about"
+        ));
+        assert!(!ElectrumSeed::is_valid(""));
+        assert!(!ElectrumSeed::is_valid("  "));
+    }
+
+    #[test]
+    fn test_electrum_normalize_cjk() {
+        assert_eq!(normalize("あ い　う").as_str(), "あいう");
+        assert_eq!(normalize("Héllo  wörld").as_str(), "hello world");
+    }
+}
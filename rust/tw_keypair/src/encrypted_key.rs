@@ -0,0 +1,385 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Password-based encryption of private keys.
+//!
+//! The encryption key is derived from the password via `scrypt` or `argon2id`,
+//! and the private key is encrypted with `AES-256-GCM`.
+//! The KDF parameters and the salt are authenticated as the associated data.
+//!
+//! # Binary format
+//!
+//! ```text
+//! version (1) | kdf (1) | kdf params (3 * 4, big-endian) | salt (16) | nonce (12) | ciphertext + tag
+//! ```
+
+use crate::{KeyPairError, KeyPairResult};
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use rand_core::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+use tw_encoding::hex::as_hex;
+use tw_misc::traits::ToBytesZeroizing;
+use zeroize::Zeroizing;
+
+const VERSION: u8 = 1;
+const KEY_LEN: usize = 32;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+const HEADER_LEN: usize = 2 + KdfParams::PARAMS_LEN + SALT_LEN;
+
+const KDF_SCRYPT: u8 = 0;
+const KDF_ARGON2ID: u8 = 1;
+
+/// Parameters of the key derivation function.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "kdf", rename_all = "camelCase")]
+pub enum KdfParams {
+    #[serde(rename_all = "camelCase")]
+    Scrypt { log_n: u8, r: u32, p: u32 },
+    #[serde(rename_all = "camelCase")]
+    Argon2id {
+        m_cost: u32,
+        t_cost: u32,
+        p_cost: u32,
+    },
+}
+
+/// cbindgen:ignore
+impl KdfParams {
+    const PARAMS_LEN: usize = 3 * 4;
+
+    /// The recommended `scrypt` parameters: `N = 2^17, r = 8, p = 1`.
+    pub const fn scrypt() -> KdfParams {
+        KdfParams::Scrypt {
+            log_n: 17,
+            r: 8,
+            p: 1,
+        }
+    }
+
+    /// The recommended `argon2id` parameters: `m = 19 MiB, t = 2, p = 1`.
+    pub const fn argon2id() -> KdfParams {
+        KdfParams::Argon2id {
+            m_cost: 19 * 1024,
+            t_cost: 2,
+            p_cost: 1,
+        }
+    }
+
+    /// Derives a 32 byte encryption key from the given `password` and `salt`.
+    fn derive_key(&self, password: &[u8], salt: &[u8]) -> KeyPairResult<Zeroizing<[u8; KEY_LEN]>> {
+        let mut key = Zeroizing::new([0; KEY_LEN]);
+        match *self {
+            KdfParams::Scrypt { log_n, r, p } => {
+                let params = scrypt::Params::new(log_n, r, p, KEY_LEN)
+                    .map_err(|_| KeyPairError::InvalidSecretKey)?;
+                scrypt::scrypt(password, salt, &params, key.as_mut_slice())
+                    .map_err(|_| KeyPairError::InvalidSecretKey)?;
+            },
+            KdfParams::Argon2id {
+                m_cost,
+                t_cost,
+                p_cost,
+            } => {
+                let params = argon2::Params::new(m_cost, t_cost, p_cost, Some(KEY_LEN))
+                    .map_err(|_| KeyPairError::InvalidSecretKey)?;
+                argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params)
+                    .hash_password_into(password, salt, key.as_mut_slice())
+                    .map_err(|_| KeyPairError::InvalidSecretKey)?;
+            },
+        }
+        Ok(key)
+    }
+
+    fn to_bytes(self) -> [u8; 1 + Self::PARAMS_LEN] {
+        let (kdf, params) = match self {
+            KdfParams::Scrypt { log_n, r, p } => (KDF_SCRYPT, [log_n as u32, r, p]),
+            KdfParams::Argon2id {
+                m_cost,
+                t_cost,
+                p_cost,
+            } => (KDF_ARGON2ID, [m_cost, t_cost, p_cost]),
+        };
+
+        let mut res = [0; 1 + Self::PARAMS_LEN];
+        res[0] = kdf;
+        for (chunk, param) in res[1..].chunks_exact_mut(4).zip(params) {
+            chunk.copy_from_slice(&param.to_be_bytes());
+        }
+        res
+    }
+
+    fn from_bytes(kdf: u8, bytes: &[u8]) -> KeyPairResult<KdfParams> {
+        let params: Vec<u32> = bytes
+            .chunks_exact(4)
+            .map(|chunk| u32::from_be_bytes(chunk.try_into().expect("Expected 4 byte chunk")))
+            .collect();
+        let &[a, b, c] = params.as_slice() else {
+            return Err(KeyPairError::InvalidSecretKey);
+        };
+
+        match kdf {
+            KDF_SCRYPT => Ok(KdfParams::Scrypt {
+                log_n: u8::try_from(a).map_err(|_| KeyPairError::InvalidSecretKey)?,
+                r: b,
+                p: c,
+            }),
+            KDF_ARGON2ID => Ok(KdfParams::Argon2id {
+                m_cost: a,
+                t_cost: b,
+                p_cost: c,
+            }),
+            _ => Err(KeyPairError::InvalidSecretKey),
+        }
+    }
+}
+
+/// Represents a private key encrypted with a password.
+///
+/// Can be serialized to bytes via [`EncryptedKey::to_bytes`] and [`EncryptedKey::try_from`],
+/// or to JSON via `serde`.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptedKey {
+    #[serde(flatten)]
+    kdf: KdfParams,
+    #[serde(with = "as_hex")]
+    salt: Vec<u8>,
+    #[serde(with = "as_hex")]
+    nonce: Vec<u8>,
+    #[serde(with = "as_hex")]
+    ciphertext: Vec<u8>,
+}
+
+impl EncryptedKey {
+    /// Encrypts the given `private` key with the `password`.
+    pub fn encrypt<K: ToBytesZeroizing>(
+        private: &K,
+        password: &[u8],
+        kdf: KdfParams,
+        rng: &mut (impl CryptoRng + RngCore),
+    ) -> KeyPairResult<EncryptedKey> {
+        EncryptedKey::encrypt_bytes(&private.to_zeroizing_vec(), password, kdf, rng)
+    }
+
+    /// Encrypts the given `secret` bytes with the `password`.
+    pub fn encrypt_bytes(
+        secret: &[u8],
+        password: &[u8],
+        kdf: KdfParams,
+        rng: &mut (impl CryptoRng + RngCore),
+    ) -> KeyPairResult<EncryptedKey> {
+        let mut salt = vec![0; SALT_LEN];
+        rng.fill_bytes(&mut salt);
+        let mut nonce = vec![0; NONCE_LEN];
+        rng.fill_bytes(&mut nonce);
+
+        let key = kdf.derive_key(password, &salt)?;
+        let aad = header(kdf, &salt);
+        let ciphertext = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key.as_slice()))
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: secret,
+                    aad: &aad,
+                },
+            )
+            .map_err(|_| KeyPairError::InvalidSecretKey)?;
+
+        Ok(EncryptedKey {
+            kdf,
+            salt,
+            nonce,
+            ciphertext,
+        })
+    }
+
+    /// Decrypts the private key with the `password`.
+    ///
+    /// Returns [`KeyPairError::InvalidSecretKey`] if the password is wrong or the data is corrupted.
+    pub fn decrypt<K>(&self, password: &[u8]) -> KeyPairResult<K>
+    where
+        K: for<'a> TryFrom<&'a [u8], Error = KeyPairError>,
+    {
+        K::try_from(self.decrypt_bytes(password)?.as_slice())
+    }
+
+    /// Decrypts the secret bytes with the `password`.
+    ///
+    /// Returns [`KeyPairError::InvalidSecretKey`] if the password is wrong or the data is corrupted.
+    pub fn decrypt_bytes(&self, password: &[u8]) -> KeyPairResult<Zeroizing<Vec<u8>>> {
+        if self.salt.len() != SALT_LEN || self.nonce.len() != NONCE_LEN {
+            return Err(KeyPairError::InvalidSecretKey);
+        }
+
+        let key = self.kdf.derive_key(password, &self.salt)?;
+        let aad = header(self.kdf, &self.salt);
+        Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key.as_slice()))
+            .decrypt(
+                Nonce::from_slice(&self.nonce),
+                Payload {
+                    msg: &self.ciphertext,
+                    aad: &aad,
+                },
+            )
+            .map(Zeroizing::new)
+            .map_err(|_| KeyPairError::InvalidSecretKey)
+    }
+
+    /// Returns the KDF parameters the key is encrypted with.
+    pub fn kdf(&self) -> KdfParams {
+        self.kdf
+    }
+
+    /// Serializes the encrypted key to bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut res = header(self.kdf, &self.salt);
+        res.extend_from_slice(&self.nonce);
+        res.extend_from_slice(&self.ciphertext);
+        res
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for EncryptedKey {
+    type Error = KeyPairError;
+
+    fn try_from(bytes: &'a [u8]) -> Result<Self, Self::Error> {
+        if bytes.len() < HEADER_LEN + NONCE_LEN + TAG_LEN || bytes[0] != VERSION {
+            return Err(KeyPairError::InvalidSecretKey);
+        }
+
+        let (params, rest) = bytes[2..].split_at(KdfParams::PARAMS_LEN);
+        let (salt, rest) = rest.split_at(SALT_LEN);
+        let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+
+        Ok(EncryptedKey {
+            kdf: KdfParams::from_bytes(bytes[1], params)?,
+            salt: salt.to_vec(),
+            nonce: nonce.to_vec(),
+            ciphertext: ciphertext.to_vec(),
+        })
+    }
+}
+
+/// Returns `version | kdf | kdf params | salt` that is authenticated as the associated data.
+fn header(kdf: KdfParams, salt: &[u8]) -> Vec<u8> {
+    let mut res = Vec::with_capacity(HEADER_LEN);
+    res.push(VERSION);
+    res.extend_from_slice(&kdf.to_bytes());
+    res.extend_from_slice(salt);
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecdsa::secp256k1;
+    use rand_core::OsRng;
+    use tw_encoding::hex;
+
+    const SECRET: &str = "afeefca74d9a325cf1d6b6911d61a65c32afa8e02bd5e78e2e4ac2910bab45f5";
+    /// Weak parameters to keep the tests fast.
+    const TEST_SCRYPT: KdfParams = KdfParams::Scrypt {
+        log_n: 4,
+        r: 8,
+        p: 1,
+    };
+
+    #[test]
+    fn test_decrypt_scrypt() {
+        let encrypted = hex::decode("0100000000040000000800000001010101010101010101010101010101010202020202020202020202024c288a44233f00e877d88f25a57cf966d516a1588b1b41c43d58ae6850801598d945e5b45eda3b4582a2808f8684c41b").unwrap();
+        let encrypted = EncryptedKey::try_from(encrypted.as_slice()).unwrap();
+        assert_eq!(encrypted.kdf(), TEST_SCRYPT);
+
+        let private: secp256k1::PrivateKey = encrypted.decrypt(b"password").unwrap();
+        assert_eq!(
+            private.to_zeroizing_vec().as_slice(),
+            hex::decode(SECRET).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let private = secp256k1::PrivateKey::try_from(SECRET).unwrap();
+        let argon2id = KdfParams::Argon2id {
+            m_cost: 64,
+            t_cost: 1,
+            p_cost: 1,
+        };
+
+        for kdf in [TEST_SCRYPT, argon2id] {
+            let encrypted = EncryptedKey::encrypt(&private, b"password", kdf, &mut OsRng).unwrap();
+
+            let encrypted = EncryptedKey::try_from(encrypted.to_bytes().as_slice()).unwrap();
+            let decrypted: secp256k1::PrivateKey = encrypted.decrypt(b"password").unwrap();
+            assert!(decrypted == private);
+        }
+    }
+
+    #[test]
+    fn test_decrypt_wrong_password() {
+        let encrypted = EncryptedKey::encrypt_bytes(
+            &hex::decode(SECRET).unwrap(),
+            b"password",
+            TEST_SCRYPT,
+            &mut OsRng,
+        )
+        .unwrap();
+        assert_eq!(
+            encrypted.decrypt_bytes(b"passw0rd").unwrap_err(),
+            KeyPairError::InvalidSecretKey
+        );
+    }
+
+    #[test]
+    fn test_decrypt_tampered_params() {
+        let encrypted =
+            EncryptedKey::encrypt_bytes(&[1; 32], b"password", TEST_SCRYPT, &mut OsRng).unwrap();
+
+        let mut bytes = encrypted.to_bytes();
+        // Change `r` from 8 to 9.
+        bytes[9] = 9;
+        let tampered = EncryptedKey::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(
+            tampered.decrypt_bytes(b"password").unwrap_err(),
+            KeyPairError::InvalidSecretKey
+        );
+    }
+
+    #[test]
+    fn test_encrypted_key_from_invalid_bytes() {
+        assert!(EncryptedKey::try_from([1; 40].as_slice()).is_err());
+
+        let mut bytes = vec![0; HEADER_LEN + NONCE_LEN + TAG_LEN];
+        // Invalid version.
+        bytes[0] = 2;
+        assert!(EncryptedKey::try_from(bytes.as_slice()).is_err());
+        // Unknown KDF.
+        bytes[0] = VERSION;
+        bytes[1] = 2;
+        assert!(EncryptedKey::try_from(bytes.as_slice()).is_err());
+    }
+
+    #[test]
+    fn test_encrypted_key_json() {
+        let encrypted = hex::decode("0100000000040000000800000001010101010101010101010101010101010202020202020202020202024c288a44233f00e877d88f25a57cf966d516a1588b1b41c43d58ae6850801598d945e5b45eda3b4582a2808f8684c41b").unwrap();
+        let encrypted = EncryptedKey::try_from(encrypted.as_slice()).unwrap();
+
+        let expected = serde_json::json!({
+            "kdf": "scrypt",
+            "logN": 4,
+            "r": 8,
+            "p": 1,
+            "salt": "01010101010101010101010101010101",
+            "nonce": "020202020202020202020202",
+            "ciphertext": "4c288a44233f00e877d88f25a57cf966d516a1588b1b41c43d58ae6850801598d945e5b45eda3b4582a2808f8684c41b",
+        });
+        assert_eq!(serde_json::to_value(&encrypted).unwrap(), expected);
+
+        let actual: EncryptedKey = serde_json::from_value(expected).unwrap();
+        assert_eq!(actual, encrypted);
+    }
+}
@@ -0,0 +1,161 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Deterministic derivation of private keys from arbitrary seed material
+//! via [HKDF](https://www.rfc-editor.org/rfc/rfc5869).
+//!
+//! The `info` string separates the domains of the derived keys,
+//! so the same seed can be used to derive independent application-specific keys:
+//!
+//! ```rust,ignore
+//! use tw_keypair::ecdsa::secp256k1;
+//! use tw_keypair::key_derivation::{derive_private_key, HkdfHash};
+//!
+//! let session_key: secp256k1::PrivateKey =
+//!     derive_private_key(HkdfHash::Sha256, SEED, &[], b"wallet-core/session/dapp.example")?;
+//! ```
+
+use crate::{KeyPairError, KeyPairResult};
+use hkdf::Hkdf;
+use sha2::{Sha256, Sha512};
+use zeroize::Zeroizing;
+
+/// The length of the key material that is tried as a private key.
+const PRIVATE_KEY_LEN: usize = 32;
+
+/// The hash function used by HKDF.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HkdfHash {
+    Sha256,
+    Sha512,
+}
+
+/// Derives `len` bytes of key material from the given `seed` via HKDF.
+/// An empty `salt` is equivalent to the zero-filled salt of the hash length.
+///
+/// Returns an error if `seed` is empty or `len` exceeds `255` times the hash length.
+pub fn derive_key_material(
+    hash: HkdfHash,
+    seed: &[u8],
+    salt: &[u8],
+    info: &[u8],
+    len: usize,
+) -> KeyPairResult<Zeroizing<Vec<u8>>> {
+    if seed.is_empty() {
+        return Err(KeyPairError::InvalidSecretKey);
+    }
+
+    let salt = (!salt.is_empty()).then_some(salt);
+    let mut okm = Zeroizing::new(vec![0; len]);
+    let res = match hash {
+        HkdfHash::Sha256 => Hkdf::<Sha256>::new(salt, seed).expand(info, &mut okm),
+        HkdfHash::Sha512 => Hkdf::<Sha512>::new(salt, seed).expand(info, &mut okm),
+    };
+    res.map_err(|_| KeyPairError::InvalidSecretKey)?;
+    Ok(okm)
+}
+
+/// Derives a private key from the given `seed` via HKDF.
+///
+/// The 32 byte key material is derived with `info || counter` as the HKDF info,
+/// where the `counter` byte starts from `0` and is incremented until the key material is a valid private key.
+/// Returns an error if `seed` is empty or none of the candidates is a valid private key.
+pub fn derive_private_key<Key>(
+    hash: HkdfHash,
+    seed: &[u8],
+    salt: &[u8],
+    info: &[u8],
+) -> KeyPairResult<Key>
+where
+    Key: for<'a> TryFrom<&'a [u8], Error = KeyPairError>,
+{
+    let mut info_with_counter = info.to_vec();
+    info_with_counter.push(0);
+
+    for counter in 0..=u8::MAX {
+        *info_with_counter
+            .last_mut()
+            .expect("`info_with_counter` is not empty") = counter;
+
+        let candidate = derive_key_material(hash, seed, salt, &info_with_counter, PRIVATE_KEY_LEN)?;
+        if let Ok(private) = Key::try_from(candidate.as_slice()) {
+            return Ok(private);
+        }
+    }
+    Err(KeyPairError::InvalidSecretKey)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecdsa::secp256k1;
+    use crate::{ed25519, starkex};
+    use tw_encoding::hex;
+    use tw_misc::traits::ToBytesZeroizing;
+
+    const SEED: [u8; 32] = [1; 32];
+    const INFO: &[u8] = b"wallet-core/session/dapp.example";
+
+    /// https://www.rfc-editor.org/rfc/rfc5869#appendix-A.1
+    #[test]
+    fn test_derive_key_material_rfc5869() {
+        let ikm = [0x0b; 22];
+        let salt = hex::decode("000102030405060708090a0b0c").unwrap();
+        let info = hex::decode("f0f1f2f3f4f5f6f7f8f9").unwrap();
+
+        let okm = derive_key_material(HkdfHash::Sha256, &ikm, &salt, &info, 42).unwrap();
+        assert_eq!(
+            hex::encode(okm.as_slice(), false),
+            "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865"
+        );
+    }
+
+    #[test]
+    fn test_derive_key_material_invalid() {
+        derive_key_material(HkdfHash::Sha256, &[], &[], INFO, 32).unwrap_err();
+        derive_key_material(HkdfHash::Sha256, &SEED, &[], INFO, 255 * 32 + 1).unwrap_err();
+        derive_key_material(HkdfHash::Sha512, &SEED, &[], INFO, 255 * 64).unwrap();
+    }
+
+    #[test]
+    fn test_derive_private_key() {
+        let private: secp256k1::PrivateKey =
+            derive_private_key(HkdfHash::Sha256, &SEED, &[], INFO).unwrap();
+        assert_eq!(
+            hex::encode(private.to_zeroizing_vec().as_slice(), false),
+            "523bac46a4df1953b60407f6beb6e1679fa181c4cb67c76dbcc11ea5073be271"
+        );
+
+        let private: ed25519::sha512::PrivateKey =
+            derive_private_key(HkdfHash::Sha512, &SEED, b"salt", INFO).unwrap();
+        assert_eq!(
+            hex::encode(private.to_zeroizing_vec().as_slice(), false),
+            "83b8ee09988d4ca0ff812ed657d4e7203d6edeb87555f9685e8798cdf3cf331b"
+        );
+    }
+
+    #[test]
+    fn test_derive_private_key_retries() {
+        // The first 5 candidates are not less than the STARK field prime.
+        let private: starkex::PrivateKey =
+            derive_private_key(HkdfHash::Sha512, &SEED, &[], INFO).unwrap();
+        assert_eq!(
+            hex::encode(private.to_zeroizing_vec().as_slice(), false),
+            "061ade7bac2123ba87ffb6f379b372acbff3adc04b4f5eeef0205622b24e3b26"
+        );
+    }
+
+    #[test]
+    fn test_derive_private_key_domain_separation() {
+        let first: secp256k1::PrivateKey =
+            derive_private_key(HkdfHash::Sha256, &SEED, &[], b"first").unwrap();
+        let second: secp256k1::PrivateKey =
+            derive_private_key(HkdfHash::Sha256, &SEED, &[], b"second").unwrap();
+        assert_ne!(first.to_zeroizing_vec(), second.to_zeroizing_vec());
+
+        assert!(
+            derive_private_key::<secp256k1::PrivateKey>(HkdfHash::Sha256, &[], &[], INFO).is_err()
+        );
+    }
+}
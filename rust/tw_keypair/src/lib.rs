@@ -42,12 +42,32 @@
 //! assert_eq(eth_signature.v, H256::from(EXPECTED_V));
 //! ```
 
+pub mod async_signer;
+pub mod bip32;
+pub mod bip85;
+pub mod bls;
+pub mod ct;
 pub mod ecdsa;
 pub mod ed25519;
+pub mod electrum;
+pub mod encrypted_key;
 pub mod ffi;
+pub mod key_derivation;
+pub mod mnemonic;
+pub mod monero;
+mod pem;
+pub mod ristretto255;
+pub mod schnorr;
+pub mod secret_bytes;
+pub mod serialization;
+pub mod slip39;
+pub mod sr25519;
 pub mod starkex;
+pub mod suri;
+pub mod ton;
 pub mod traits;
 pub mod tw;
+pub mod zilliqa_schnorr;
 
 #[cfg(feature = "test-utils")]
 pub mod test_utils;
@@ -62,4 +82,7 @@ pub enum KeyPairError {
     InvalidSignMessage,
     SignatureVerifyError,
     SigningError,
+    InvalidTweak,
+    InvalidMessageLength,
+    InvalidMnemonic,
 }
@@ -0,0 +1,631 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! [BIP39](https://github.com/bitcoin/bips/blob/master/bip-0039.mediawiki) mnemonic phrases.
+//!
+//! A mnemonic encodes 128 to 256 bits of entropy with a checksum as 12 to 24 words
+//! of one of the standard wordlists, and is stretched to a 64 byte seed via PBKDF2:
+//!
+//! ```rust,ignore
+//! use tw_keypair::bip32::{ExtendedPrivateKey, Secp256k1};
+//! use tw_keypair::mnemonic::{Language, Mnemonic, Passphrase};
+//!
+//! let mnemonic = Mnemonic::parse(Language::English, PHRASE)?;
+//! let master = ExtendedPrivateKey::<Secp256k1>::from_mnemonic(&mnemonic, &Passphrase::default())?;
+//! ```
+//!
+//! An optional passphrase, also known as the 25th word, derives a different seed from the same mnemonic,
+//! e.g. for a hidden wallet:
+//!
+//! ```rust,ignore
+//! let passphrase = Passphrase::new(USER_PASSPHRASE);
+//! let hidden = ExtendedPrivateKey::<Secp256k1>::from_mnemonic(&mnemonic, &passphrase)?;
+//! ```
+
+use crate::{KeyPairError, KeyPairResult};
+use rand_core::{CryptoRng, RngCore};
+use tw_hash::H512;
+use unicode_normalization::UnicodeNormalization;
+use zeroize::{Zeroize, Zeroizing};
+
+/// The words of a Japanese mnemonic are separated by the ideographic space.
+const JAPANESE_SEPARATOR: &str = "\u{3000}";
+const SEPARATOR: &str = " ";
+/// The maximum number of the suggested words for an unknown word.
+const MAX_SUGGESTIONS: usize = 5;
+/// The maximum edit distance of a suggested word that the unknown word is not a prefix of.
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+
+/// The language of a BIP39 wordlist.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Language {
+    English,
+    ChineseSimplified,
+    ChineseTraditional,
+    Czech,
+    French,
+    Italian,
+    Japanese,
+    Korean,
+    Portuguese,
+    Spanish,
+}
+
+impl Language {
+    /// Returns the 2048 words of the wordlist.
+    pub fn word_list(self) -> &'static [&'static str; 2048] {
+        bip39::Language::from(self).word_list()
+    }
+}
+
+impl From<Language> for bip39::Language {
+    fn from(language: Language) -> Self {
+        match language {
+            Language::English => bip39::Language::English,
+            Language::ChineseSimplified => bip39::Language::SimplifiedChinese,
+            Language::ChineseTraditional => bip39::Language::TraditionalChinese,
+            Language::Czech => bip39::Language::Czech,
+            Language::French => bip39::Language::French,
+            Language::Italian => bip39::Language::Italian,
+            Language::Japanese => bip39::Language::Japanese,
+            Language::Korean => bip39::Language::Korean,
+            Language::Portuguese => bip39::Language::Portuguese,
+            Language::Spanish => bip39::Language::Spanish,
+        }
+    }
+}
+
+/// A BIP39 passphrase normalized to NFKD. Empty by default.
+/// The passphrase is zeroized on drop.
+#[derive(Default)]
+pub struct Passphrase(Zeroizing<String>);
+
+impl Passphrase {
+    pub fn new(passphrase: &str) -> Passphrase {
+        Passphrase(normalize(passphrase))
+    }
+
+    /// Returns the normalized passphrase.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl From<&str> for Passphrase {
+    fn from(passphrase: &str) -> Self {
+        Passphrase::new(passphrase)
+    }
+}
+
+/// The reason why a phrase is not a valid mnemonic, so the offending words can be highlighted.
+#[derive(Debug, PartialEq, Eq)]
+pub enum MnemonicError {
+    /// Some words are not in the wordlist.
+    UnknownWords(Vec<UnknownWord>),
+    /// The number of words is not `12`, `15`, `18`, `21` or `24`.
+    InvalidWordCount(usize),
+    /// All the words are known, but the checksum doesn't match, e.g. the words are in a wrong order.
+    InvalidChecksum,
+}
+
+impl From<MnemonicError> for KeyPairError {
+    fn from(_: MnemonicError) -> Self {
+        KeyPairError::InvalidMnemonic
+    }
+}
+
+/// A word of a phrase that is not in the wordlist.
+#[derive(Debug, PartialEq, Eq)]
+pub struct UnknownWord {
+    /// The zero-based index of the word in the phrase.
+    pub index: usize,
+    /// The closest words of the wordlist, the best match first.
+    pub suggestions: Vec<&'static str>,
+}
+
+/// Represents a valid mnemonic phrase. The words are zeroized on drop.
+pub struct Mnemonic {
+    inner: bip39::Mnemonic,
+    language: Language,
+}
+
+/// cbindgen:ignore
+impl Mnemonic {
+    pub const MIN_WORDS: usize = 12;
+    pub const MAX_WORDS: usize = 24;
+
+    /// Generates a new mnemonic of `word_count` words, i.e. `12`, `15`, `18`, `21` or `24`.
+    pub fn generate(
+        language: Language,
+        word_count: usize,
+        rng: &mut (impl CryptoRng + RngCore),
+    ) -> KeyPairResult<Mnemonic> {
+        if !(Self::MIN_WORDS..=Self::MAX_WORDS).contains(&word_count) || word_count % 3 != 0 {
+            return Err(KeyPairError::InvalidMnemonic);
+        }
+
+        // Every 3 words encode 32 bits of entropy and 1 checksum bit.
+        let mut entropy = Zeroizing::new(vec![0; word_count / 3 * 4]);
+        rng.fill_bytes(&mut entropy);
+        Self::from_entropy(language, &entropy)
+    }
+
+    /// Encodes the given `entropy` of 16, 20, 24, 28 or 32 bytes as a mnemonic.
+    pub fn from_entropy(language: Language, entropy: &[u8]) -> KeyPairResult<Mnemonic> {
+        let inner = bip39::Mnemonic::from_entropy_in(language.into(), entropy)
+            .map_err(|_| KeyPairError::InvalidMnemonic)?;
+        Ok(Mnemonic { inner, language })
+    }
+
+    /// Parses the given `phrase` after normalizing it to NFKD.
+    /// Returns an error if the word count is invalid, a word is not in the wordlist of `language`,
+    /// or the checksum doesn't match.
+    pub fn parse(language: Language, phrase: &str) -> KeyPairResult<Mnemonic> {
+        let normalized = normalize(phrase);
+        let inner = bip39::Mnemonic::parse_in_normalized(language.into(), &normalized)
+            .map_err(|_| KeyPairError::InvalidMnemonic)?;
+        Ok(Mnemonic { inner, language })
+    }
+
+    /// Validates the given `phrase` like [`Mnemonic::parse`], but reports why it's invalid.
+    /// The unknown words take precedence over the word count, and the checksum is checked last.
+    pub fn validate(language: Language, phrase: &str) -> Result<(), MnemonicError> {
+        let normalized = normalize(phrase);
+        let words: Vec<&str> = normalized.split_whitespace().collect();
+        let unknown: Vec<UnknownWord> = words
+            .iter()
+            .enumerate()
+            .filter(|(_, word)| !language.word_list().contains(word))
+            .map(|(index, word)| UnknownWord {
+                index,
+                suggestions: suggest(language, word),
+            })
+            .collect();
+        if !unknown.is_empty() {
+            return Err(MnemonicError::UnknownWords(unknown));
+        }
+
+        let word_count = words.len();
+        if !(Self::MIN_WORDS..=Self::MAX_WORDS).contains(&word_count) || word_count % 3 != 0 {
+            return Err(MnemonicError::InvalidWordCount(word_count));
+        }
+
+        let mut inner = bip39::Mnemonic::parse_in_normalized(language.into(), &normalized)
+            .map_err(|_| MnemonicError::InvalidChecksum)?;
+        inner.zeroize();
+        Ok(())
+    }
+
+    /// Returns the closest words of the wordlist of `language` to the given `word`, the best match first:
+    /// the words that start with `word`, then the words within a small edit distance.
+    /// Can be used to autocomplete or correct a word as it's typed.
+    pub fn suggest_words(language: Language, word: &str) -> Vec<&'static str> {
+        suggest(language, &normalize(word))
+    }
+
+    /// Checks whether the given `phrase` is a valid mnemonic in `language`.
+    pub fn is_valid(language: Language, phrase: &str) -> bool {
+        Self::parse(language, phrase).is_ok()
+    }
+
+    /// Returns the language of the wordlist.
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    /// Returns the number of words.
+    pub fn word_count(&self) -> usize {
+        self.inner.word_count()
+    }
+
+    /// Returns the phrase with the words separated by a space,
+    /// or by the ideographic space for [`Language::Japanese`].
+    pub fn phrase(&self) -> Zeroizing<String> {
+        let separator = match self.language {
+            Language::Japanese => JAPANESE_SEPARATOR,
+            _ => SEPARATOR,
+        };
+
+        let mut phrase = Zeroizing::new(String::new());
+        for (i, word) in self.inner.words().enumerate() {
+            if i > 0 {
+                phrase.push_str(separator);
+            }
+            phrase.push_str(word);
+        }
+        phrase
+    }
+
+    /// Returns the encoded entropy.
+    pub fn to_entropy(&self) -> Zeroizing<Vec<u8>> {
+        Zeroizing::new(self.inner.to_entropy())
+    }
+
+    /// Derives the 64 byte seed via PBKDF2-HMAC-SHA512 with the salt `"mnemonic" || passphrase`.
+    pub fn to_seed(&self, passphrase: &Passphrase) -> Zeroizing<H512> {
+        let mut seed = self.inner.to_seed_normalized(passphrase.as_str());
+        let res = Zeroizing::new(H512::from(seed));
+        seed.zeroize();
+        res
+    }
+}
+
+impl Drop for Mnemonic {
+    fn drop(&mut self) {
+        self.inner.zeroize();
+    }
+}
+
+/// Decodes the entropy of the given `phrase`, verifying its checksum,
+/// e.g. to back up the entropy of a mnemonic in another format.
+pub fn mnemonic_to_entropy(language: Language, phrase: &str) -> KeyPairResult<Zeroizing<Vec<u8>>> {
+    Mnemonic::parse(language, phrase).map(|mnemonic| mnemonic.to_entropy())
+}
+
+/// Encodes the given `entropy` of 16, 20, 24, 28 or 32 bytes as a phrase with its checksum,
+/// e.g. the entropy of dice rolls or a hardware random number generator.
+pub fn entropy_to_mnemonic(language: Language, entropy: &[u8]) -> KeyPairResult<Zeroizing<String>> {
+    Mnemonic::from_entropy(language, entropy).map(|mnemonic| mnemonic.phrase())
+}
+
+/// Returns the suggested words for an already normalized `word`.
+fn suggest(language: Language, word: &str) -> Vec<&'static str> {
+    if word.is_empty() {
+        return Vec::new();
+    }
+
+    let mut candidates: Vec<(usize, &'static str)> = language
+        .word_list()
+        .iter()
+        .filter_map(|candidate| {
+            if candidate.starts_with(word) {
+                return Some((0, *candidate));
+            }
+            let distance = edit_distance(word, candidate);
+            (distance <= MAX_SUGGESTION_DISTANCE).then_some((distance, *candidate))
+        })
+        .collect();
+    candidates.sort();
+    candidates
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+/// Returns the Levenshtein distance of the given strings in characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+fn normalize(s: &str) -> Zeroizing<String> {
+    let mut normalized = Zeroizing::new(String::with_capacity(s.len()));
+    normalized.extend(s.nfkd());
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bip32::{ExtendedPrivateKey, Secp256k1, XPRV_VERSION};
+    use rand_core::OsRng;
+    use tw_encoding::hex;
+
+    /// https://github.com/trezor/python-mnemonic/blob/master/vectors.json
+    const VECTORS: [(&str, &str, &str, &str); 4] = [
+        (
+            "00000000000000000000000000000000",
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04",
+            "xprv9s21ZrQH143K3h3fDYiay8mocZ3afhfULfb5GX8kCBdno77K4HiA15Tg23wpbeF1pLfs1c5SPmYHrEpTuuRhxMwvKDwqdKiGJS9XFKzUsAF",
+        ),
+        (
+            "ffffffffffffffffffffffffffffffff",
+            "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo wrong",
+            "ac27495480225222079d7be181583751e86f571027b0497b5b5d11218e0a8a13332572917f0f8e5a589620c6f15b11c61dee327651a14c34e18231052e48c069",
+            "xprv9s21ZrQH143K2V4oox4M8Zmhi2Fjx5XK4Lf7GKRvPSgydU3mjZuKGCTg7UPiBUD7ydVPvSLtg9hjp7MQTYsW67rZHAXeccqYqrsx8LcXnyd",
+        ),
+        (
+            "000000000000000000000000000000000000000000000000",
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon agent",
+            "035895f2f481b1b0f01fcf8c289c794660b289981a78f8106447707fdd9666ca06da5a9a565181599b79f53b844d8a71dd9f439c52a3d7b3e8a79c906ac845fa",
+            "xprv9s21ZrQH143K3mEDrypcZ2usWqFgzKB6jBBx9B6GfC7fu26X6hPRzVjzkqkPvDqp6g5eypdk6cyhGnBngbjeHTe4LsuLG1cCmKJka5SMkmU",
+        ),
+        (
+            "f585c11aec520db57dd353c69554b21a89b20fb0650966fa0a9d6f74fd989d8f",
+            "void come effort suffer camp survey warrior heavy shoot primary clutch crush open amazing screen patrol group space point ten exist slush involve unfold",
+            "01f5bced59dec48e362f2c45b5de68b9fd6c92c6634f44d6d40aab69056506f0e35524a518034ddc1192e1dacd32c1ed3eaa3c3b131c88ed8e7e54c49a5d0998",
+            "xprv9s21ZrQH143K39rnQJknpH1WEPFJrzmAqqasiDcVrNuk926oizzJDDQkdiTvNPr2FYDYzWgiMiC63YmfPAa2oPyNB23r2g7d1yiK6WpqaQS",
+        ),
+    ];
+    const PASSPHRASE: &str = "TREZOR";
+    /// The seed of `VECTORS[0]` with an empty passphrase.
+    const EMPTY_PASSPHRASE_SEED: &str = "5eb00bbddcf069084889a8ab9155568165f5c453ccb85e70811aaed6f6da5fc19a5ac40b389cd370d086206dec8aa6c43daea6690f20ad3d8d48b2d2ce9e38e4";
+
+    #[test]
+    fn test_mnemonic_vectors() {
+        assert_eq!(Language::English.word_list()[0], "abandon");
+
+        for (entropy, phrase, seed, xprv) in VECTORS {
+            let entropy = hex::decode(entropy).unwrap();
+            let mnemonic = Mnemonic::from_entropy(Language::English, &entropy).unwrap();
+            assert_eq!(mnemonic.phrase().as_str(), phrase);
+
+            let mnemonic = Mnemonic::parse(Language::English, phrase).unwrap();
+            assert_eq!(mnemonic.to_entropy().as_slice(), entropy.as_slice());
+            assert_eq!(mnemonic.word_count(), phrase.split(' ').count());
+
+            let actual_seed = mnemonic.to_seed(&Passphrase::new(PASSPHRASE));
+            assert_eq!(hex::encode(actual_seed.as_slice(), false), seed);
+
+            let master =
+                ExtendedPrivateKey::<Secp256k1>::from_seed(actual_seed.as_slice()).unwrap();
+            assert_eq!(master.to_xprv(XPRV_VERSION).as_str(), xprv);
+        }
+    }
+
+    #[test]
+    fn test_mnemonic_entropy_round_trip() {
+        for (entropy, phrase, _, _) in VECTORS {
+            let entropy = hex::decode(entropy).unwrap();
+            let actual = entropy_to_mnemonic(Language::English, &entropy).unwrap();
+            assert_eq!(actual.as_str(), phrase);
+            let actual = mnemonic_to_entropy(Language::English, phrase).unwrap();
+            assert_eq!(actual.as_slice(), entropy.as_slice());
+        }
+
+        for len in [16, 20, 24, 28, 32] {
+            let entropy = vec![0xa5; len];
+            let phrase = entropy_to_mnemonic(Language::Japanese, &entropy).unwrap();
+            assert_eq!(
+                mnemonic_to_entropy(Language::Japanese, &phrase)
+                    .unwrap()
+                    .as_slice(),
+                entropy.as_slice()
+            );
+        }
+
+        assert_eq!(
+            entropy_to_mnemonic(Language::English, &[0; 17]).unwrap_err(),
+            KeyPairError::InvalidMnemonic
+        );
+        // Invalid checksum.
+        let phrase = "abandon ".repeat(11) + "abandon";
+        assert_eq!(
+            mnemonic_to_entropy(Language::English, &phrase).unwrap_err(),
+            KeyPairError::InvalidMnemonic
+        );
+    }
+
+    #[test]
+    fn test_mnemonic_invalid() {
+        // Invalid checksum.
+        let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon";
+        assert_eq!(
+            Mnemonic::parse(Language::English, phrase).err(),
+            Some(KeyPairError::InvalidMnemonic)
+        );
+        // Unknown word.
+        let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abou";
+        assert!(!Mnemonic::is_valid(Language::English, phrase));
+        // Invalid word count.
+        let phrase =
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        assert!(!Mnemonic::is_valid(Language::English, phrase));
+        // Valid in another language only.
+        let phrase = VECTORS[0].1;
+        assert!(!Mnemonic::is_valid(Language::Spanish, phrase));
+
+        assert_eq!(
+            Mnemonic::from_entropy(Language::English, &[0; 15]).err(),
+            Some(KeyPairError::InvalidMnemonic)
+        );
+        assert_eq!(
+            Mnemonic::from_entropy(Language::English, &[0; 33]).err(),
+            Some(KeyPairError::InvalidMnemonic)
+        );
+    }
+
+    #[test]
+    fn test_mnemonic_validate() {
+        let abandon = "abandon ".repeat(11);
+        assert_eq!(
+            Mnemonic::validate(Language::English, &format!("{abandon}about")),
+            Ok(())
+        );
+        assert_eq!(
+            Mnemonic::validate(Language::English, &format!("{abandon}abandon")),
+            Err(MnemonicError::InvalidChecksum)
+        );
+        assert_eq!(
+            Mnemonic::validate(Language::English, "abandon about"),
+            Err(MnemonicError::InvalidWordCount(2))
+        );
+
+        // The unknown words are reported even if the word count is invalid too.
+        let phrase = format!("abandom {abandon}abou zzzzzz");
+        assert_eq!(
+            Mnemonic::validate(Language::English, &phrase),
+            Err(MnemonicError::UnknownWords(vec![
+                UnknownWord {
+                    index: 0,
+                    suggestions: vec!["abandon", "random"],
+                },
+                UnknownWord {
+                    index: 12,
+                    suggestions: vec!["about", "able", "above", "atom", "box"],
+                },
+                UnknownWord {
+                    index: 13,
+                    suggestions: vec![],
+                },
+            ]))
+        );
+        assert_eq!(
+            KeyPairError::from(MnemonicError::InvalidChecksum),
+            KeyPairError::InvalidMnemonic
+        );
+
+        // Valid in another language only.
+        let err = Mnemonic::validate(Language::Spanish, VECTORS[0].1).unwrap_err();
+        assert!(matches!(err, MnemonicError::UnknownWords(words) if words.len() == 12));
+    }
+
+    #[test]
+    fn test_mnemonic_suggest_words() {
+        assert_eq!(
+            Mnemonic::suggest_words(Language::English, "ab"),
+            ["abandon", "ability", "able", "about", "above"]
+        );
+        assert_eq!(
+            Mnemonic::suggest_words(Language::English, "qwerty"),
+            ["twenty"]
+        );
+        assert!(Mnemonic::suggest_words(Language::English, "").is_empty());
+        // The input is normalized.
+        let (word, composed) = Language::French
+            .word_list()
+            .iter()
+            .map(|word| (*word, word.nfc().collect::<String>()))
+            .find(|(word, composed)| word != composed)
+            .unwrap();
+        assert_eq!(
+            Mnemonic::suggest_words(Language::French, &composed)[0],
+            word
+        );
+    }
+
+    #[test]
+    fn test_mnemonic_generate() {
+        for word_count in [12, 15, 18, 21, 24] {
+            let mnemonic = Mnemonic::generate(Language::English, word_count, &mut OsRng).unwrap();
+            assert_eq!(mnemonic.word_count(), word_count);
+            assert_eq!(mnemonic.to_entropy().len(), word_count / 3 * 4);
+            assert!(Mnemonic::is_valid(Language::English, &mnemonic.phrase()));
+        }
+
+        for word_count in [0, 11, 13, 25, 27] {
+            assert_eq!(
+                Mnemonic::generate(Language::English, word_count, &mut OsRng).err(),
+                Some(KeyPairError::InvalidMnemonic)
+            );
+        }
+    }
+
+    #[test]
+    fn test_mnemonic_languages() {
+        let entropy = [0x7f; 32];
+        for language in [
+            Language::English,
+            Language::ChineseSimplified,
+            Language::ChineseTraditional,
+            Language::Czech,
+            Language::French,
+            Language::Italian,
+            Language::Japanese,
+            Language::Korean,
+            Language::Portuguese,
+            Language::Spanish,
+        ] {
+            let mnemonic = Mnemonic::from_entropy(language, &entropy).unwrap();
+            assert_eq!(mnemonic.language(), language);
+            assert_eq!(mnemonic.word_count(), Mnemonic::MAX_WORDS);
+
+            let parsed = Mnemonic::parse(language, &mnemonic.phrase()).unwrap();
+            assert_eq!(parsed.to_entropy().as_slice(), entropy.as_slice());
+        }
+    }
+
+    #[test]
+    fn test_mnemonic_japanese_separator() {
+        let mnemonic = Mnemonic::from_entropy(Language::Japanese, &[0; 16]).unwrap();
+        let phrase = mnemonic.phrase();
+        assert_eq!(phrase.matches(JAPANESE_SEPARATOR).count(), 11);
+        assert!(!phrase.contains(SEPARATOR));
+    }
+
+    #[test]
+    fn test_mnemonic_normalize_input() {
+        for language in [Language::Japanese, Language::Spanish, Language::French] {
+            let mnemonic = Mnemonic::from_entropy(language, &[0x80; 16]).unwrap();
+            let phrase = mnemonic.phrase();
+
+            // Composed characters and the ideographic space are normalized.
+            let composed: String = phrase.nfc().collect();
+            let parsed = Mnemonic::parse(language, &composed).unwrap();
+            assert_eq!(parsed.to_entropy(), mnemonic.to_entropy());
+            let passphrase = Passphrase::default();
+            assert_eq!(
+                parsed.to_seed(&passphrase).as_slice(),
+                mnemonic.to_seed(&passphrase).as_slice()
+            );
+        }
+    }
+
+    #[test]
+    fn test_mnemonic_normalize_passphrase() {
+        let mnemonic = Mnemonic::parse(Language::English, VECTORS[0].1).unwrap();
+        // "é" composed and decomposed.
+        let composed = Passphrase::new("caf\u{e9}");
+        let decomposed = Passphrase::new("cafe\u{301}");
+        assert_eq!(composed.as_str(), decomposed.as_str());
+        assert_eq!(
+            mnemonic.to_seed(&composed).as_slice(),
+            mnemonic.to_seed(&decomposed).as_slice()
+        );
+        assert_ne!(
+            mnemonic.to_seed(&composed).as_slice(),
+            mnemonic.to_seed(&Passphrase::new("cafe")).as_slice()
+        );
+    }
+
+    #[test]
+    fn test_mnemonic_passphrase_seed() {
+        let mnemonic = Mnemonic::parse(Language::English, VECTORS[0].1).unwrap();
+
+        let passphrase = Passphrase::default();
+        assert!(passphrase.is_empty());
+        let seed = mnemonic.to_seed(&passphrase);
+        assert_eq!(hex::encode(seed.as_slice(), false), EMPTY_PASSPHRASE_SEED);
+
+        // A hidden wallet.
+        let seed = mnemonic.to_seed(&Passphrase::from("hidden"));
+        let expected = "0838cc4b3dbbfb77cf72baf1c1d3270415229428a287c05131d815df9fa0b989461187c877df41912d4a8c49dc9999ec4488342cb2b246169d188a7aca989937";
+        assert_eq!(hex::encode(seed.as_slice(), false), expected);
+    }
+
+    #[test]
+    fn test_extended_key_from_mnemonic() {
+        let (_, phrase, _, xprv) = VECTORS[0];
+        let mnemonic = Mnemonic::parse(Language::English, phrase).unwrap();
+        let passphrase = Passphrase::new(PASSPHRASE);
+
+        let master =
+            ExtendedPrivateKey::<Secp256k1>::from_mnemonic(&mnemonic, &passphrase).unwrap();
+        assert_eq!(master.to_xprv(XPRV_VERSION).as_str(), xprv);
+
+        let visible =
+            ExtendedPrivateKey::<Secp256k1>::from_mnemonic(&mnemonic, &Passphrase::default())
+                .unwrap();
+        assert_ne!(
+            visible.to_xprv(XPRV_VERSION).as_str(),
+            master.to_xprv(XPRV_VERSION).as_str()
+        );
+    }
+}
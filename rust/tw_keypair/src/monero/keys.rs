@@ -0,0 +1,157 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::{KeyPairError, KeyPairResult};
+use curve25519_dalek::constants::ED25519_BASEPOINT_TABLE;
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use curve25519_dalek::scalar::Scalar;
+use std::fmt;
+use tw_encoding::base58;
+use tw_hash::sha3::keccak256;
+use tw_hash::H256;
+use zeroize::{ZeroizeOnDrop, Zeroizing};
+
+const CHECKSUM_LEN: usize = 4;
+
+/// The network of a Monero address.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Network {
+    Mainnet,
+    Testnet,
+    Stagenet,
+}
+
+impl Network {
+    /// Returns the prefix of the standard addresses of the network.
+    pub fn address_prefix(self) -> u8 {
+        match self {
+            Network::Mainnet => 18,
+            Network::Testnet => 53,
+            Network::Stagenet => 24,
+        }
+    }
+}
+
+/// The private spend and view keys of a Monero wallet.
+#[derive(ZeroizeOnDrop)]
+pub struct PrivateKeys {
+    spend: Scalar,
+    view: Scalar,
+}
+
+impl fmt::Debug for PrivateKeys {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PrivateKeys")
+            .field("public_spend_key", &self.public_spend_key())
+            .finish()
+    }
+}
+
+impl PrivateKeys {
+    /// Creates the keys of the given private spend key,
+    /// where the private view key is `keccak256(spend) mod l` as in the reference wallet.
+    /// Returns an error if the spend key is not a canonical scalar.
+    pub fn with_spend_key(spend_key: &[u8]) -> KeyPairResult<PrivateKeys> {
+        canonical_scalar(spend_key).map(PrivateKeys::with_spend_scalar)
+    }
+
+    pub(crate) fn with_spend_scalar(spend: Scalar) -> PrivateKeys {
+        let hash = Zeroizing::new(keccak256(spend.as_bytes()));
+        let hash =
+            Zeroizing::new(H256::try_from(hash.as_slice()).expect("Expected a 32 byte hash"));
+        let view = Scalar::from_bytes_mod_order(hash.take());
+        PrivateKeys { spend, view }
+    }
+
+    pub fn spend_key(&self) -> Zeroizing<H256> {
+        Zeroizing::new(H256::from(self.spend.to_bytes()))
+    }
+
+    pub fn view_key(&self) -> Zeroizing<H256> {
+        Zeroizing::new(H256::from(self.view.to_bytes()))
+    }
+
+    pub fn public_spend_key(&self) -> H256 {
+        public_key(&self.spend)
+    }
+
+    pub fn public_view_key(&self) -> H256 {
+        public_key(&self.view)
+    }
+
+    /// Returns the keys of a view-only wallet, which can scan for the incoming outputs but can't spend them.
+    pub fn view_keys(&self) -> ViewKeys {
+        ViewKeys {
+            view: self.view,
+            public_spend: self.public_spend_key(),
+        }
+    }
+
+    /// Returns the primary address of the wallet.
+    pub fn address(&self, network: Network) -> String {
+        self.view_keys().address(network)
+    }
+}
+
+/// The private view key and the public spend key of a view-only Monero wallet.
+#[derive(ZeroizeOnDrop)]
+pub struct ViewKeys {
+    view: Scalar,
+    public_spend: H256,
+}
+
+impl fmt::Debug for ViewKeys {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ViewKeys")
+            .field("public_spend_key", &self.public_spend)
+            .finish()
+    }
+}
+
+impl ViewKeys {
+    /// Creates the view-only keys, e.g. exported from another wallet.
+    /// Returns an error if the view key is not a canonical scalar, or the spend key is not a valid point.
+    pub fn new(view_key: &[u8], public_spend_key: &[u8]) -> KeyPairResult<ViewKeys> {
+        let view = canonical_scalar(view_key)?;
+        let public_spend =
+            H256::try_from(public_spend_key).map_err(|_| KeyPairError::InvalidPublicKey)?;
+        CompressedEdwardsY(public_spend.take())
+            .decompress()
+            .ok_or(KeyPairError::InvalidPublicKey)?;
+        Ok(ViewKeys { view, public_spend })
+    }
+
+    pub fn view_key(&self) -> Zeroizing<H256> {
+        Zeroizing::new(H256::from(self.view.to_bytes()))
+    }
+
+    pub fn public_spend_key(&self) -> H256 {
+        self.public_spend
+    }
+
+    pub fn public_view_key(&self) -> H256 {
+        public_key(&self.view)
+    }
+
+    /// Returns the primary address, i.e. `base58(prefix || spend || view || checksum)`,
+    /// where the checksum is the first 4 bytes of `keccak256` of the preceding data.
+    pub fn address(&self, network: Network) -> String {
+        let mut data = Vec::with_capacity(1 + H256::LEN * 2 + CHECKSUM_LEN);
+        data.push(network.address_prefix());
+        data.extend_from_slice(self.public_spend.as_slice());
+        data.extend_from_slice(self.public_view_key().as_slice());
+        let checksum = keccak256(&data);
+        data.extend_from_slice(&checksum[..CHECKSUM_LEN]);
+        base58::encode_monero(&data)
+    }
+}
+
+fn canonical_scalar(bytes: &[u8]) -> KeyPairResult<Scalar> {
+    let bytes = Zeroizing::new(H256::try_from(bytes).map_err(|_| KeyPairError::InvalidSecretKey)?);
+    Option::from(Scalar::from_canonical_bytes(bytes.take())).ok_or(KeyPairError::InvalidSecretKey)
+}
+
+fn public_key(scalar: &Scalar) -> H256 {
+    H256::from((scalar * ED25519_BASEPOINT_TABLE).compress().to_bytes())
+}
@@ -0,0 +1,212 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Monero 25 word seeds, so the addresses and the view-only keys of a Monero wallet
+//! can be produced from its seed:
+//!
+//! ```rust,ignore
+//! use tw_keypair::monero::{MoneroSeed, Network};
+//!
+//! let keys = MoneroSeed::parse(PHRASE)?.keys();
+//! let address = keys.address(Network::Mainnet);
+//! let view_keys = keys.view_keys();
+//! ```
+//!
+//! The first 24 words encode the 32 byte private spend key, 4 bytes per 3 words,
+//! and the last word repeats one of them as a checksum, chosen by the `CRC-32` of their first 3 letters.
+//! The private view key is `keccak256(spend) mod l`. Only the English wordlist is supported.
+
+use crate::{KeyPairError, KeyPairResult};
+use curve25519_dalek::scalar::Scalar;
+use rand_core::{CryptoRng, RngCore};
+use tw_hash::crc32::crc32;
+use tw_hash::H256;
+use zeroize::Zeroizing;
+
+mod keys;
+mod wordlist;
+
+pub use keys::{Network, PrivateKeys, ViewKeys};
+
+use wordlist::WORDLIST;
+
+/// The number of words without the checksum word.
+const DATA_WORD_COUNT: usize = 24;
+/// The number of letters of a word that the checksum is computed of.
+const CHECKSUM_PREFIX_LEN: usize = 3;
+const SEPARATOR: &str = " ";
+
+/// A Monero 25 word seed.
+pub struct MoneroSeed {
+    /// The encoded bytes. The private spend key is the bytes reduced modulo the group order.
+    secret: Zeroizing<H256>,
+}
+
+impl MoneroSeed {
+    /// Generates a random seed using the given `rng`.
+    pub fn generate(rng: &mut (impl CryptoRng + RngCore)) -> MoneroSeed {
+        let mut secret = Zeroizing::new(H256::default());
+        rng.fill_bytes(secret.as_mut_slice());
+        // The reference wallet encodes the reduced key.
+        let spend = Scalar::from_bytes_mod_order(secret.take());
+        MoneroSeed {
+            secret: Zeroizing::new(H256::from(spend.to_bytes())),
+        }
+    }
+
+    /// Parses the 25 words of the seed phrase.
+    pub fn parse(phrase: &str) -> KeyPairResult<MoneroSeed> {
+        let words: Vec<&str> = phrase.split_whitespace().collect();
+        if words.len() != DATA_WORD_COUNT + 1 {
+            return Err(KeyPairError::InvalidMnemonic);
+        }
+        if checksum_word(&words[..DATA_WORD_COUNT]) != words[DATA_WORD_COUNT] {
+            return Err(KeyPairError::InvalidMnemonic);
+        }
+
+        let n = WORDLIST.len() as u64;
+        let mut secret = Zeroizing::new(H256::default());
+        for (chunk, triple) in secret.chunks_mut(4).zip(words[..DATA_WORD_COUNT].chunks(3)) {
+            let mut indices = [0; 3];
+            for (index, word) in indices.iter_mut().zip(triple) {
+                *index = WORDLIST
+                    .binary_search(word)
+                    .map_err(|_| KeyPairError::InvalidMnemonic)? as u64;
+            }
+            let [w1, w2, w3] = indices;
+            let value = w1 + n * ((n - w1 + w2) % n) + n * n * ((n - w2 + w3) % n);
+            let value = u32::try_from(value).map_err(|_| KeyPairError::InvalidMnemonic)?;
+            chunk.copy_from_slice(&value.to_le_bytes());
+        }
+        Ok(MoneroSeed { secret })
+    }
+
+    pub fn is_valid(phrase: &str) -> bool {
+        MoneroSeed::parse(phrase).is_ok()
+    }
+
+    /// Returns the 25 words of the seed phrase.
+    pub fn phrase(&self) -> Zeroizing<String> {
+        let n = WORDLIST.len() as u32;
+        let mut words = Vec::with_capacity(DATA_WORD_COUNT + 1);
+        for chunk in self.secret.chunks(4) {
+            let value = u32::from_le_bytes(chunk.try_into().expect("Expected a 4 byte chunk"));
+            let w1 = value % n;
+            let w2 = (value / n + w1) % n;
+            let w3 = (value / n / n + w2) % n;
+            words.extend([w1, w2, w3].map(|index| WORDLIST[index as usize]));
+        }
+        words.push(checksum_word(&words));
+        Zeroizing::new(words.join(SEPARATOR))
+    }
+
+    /// Returns the private spend and view keys.
+    pub fn keys(&self) -> PrivateKeys {
+        PrivateKeys::with_spend_scalar(Scalar::from_bytes_mod_order(self.secret.take()))
+    }
+}
+
+/// Returns the word of the `words` that is chosen by their checksum.
+fn checksum_word<'a>(words: &[&'a str]) -> &'a str {
+    let prefixes: Zeroizing<String> = Zeroizing::new(
+        words
+            .iter()
+            .flat_map(|word| word.chars().take(CHECKSUM_PREFIX_LEN))
+            .collect(),
+    );
+    words[crc32(prefixes.as_bytes()) as usize % words.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_core::OsRng;
+    use tw_encoding::hex;
+
+    const PHRASE: &str = "sequence atlas unveil summon pebbles tuesday beer rudely snake rockets different fuselage woven tagged bested dented vegan hover rapid fawns obvious muppet randomly seasons randomly";
+    const ADDRESS: &str = "46PAiPrNjr2XS82k2ovp5EUYLzBt9pYNW2LXUFsZiv8S3Mt21FZ5qQaAroko1enzw3eGr9qC7X1D7Geoo2RrAotYPvzt9vB";
+
+    #[test]
+    fn test_monero_seed_keys() {
+        let seed = MoneroSeed::parse(PHRASE).unwrap();
+        assert_eq!(seed.phrase().as_str(), PHRASE);
+
+        let keys = seed.keys();
+        assert_eq!(
+            hex::encode(keys.spend_key().as_slice(), false),
+            "b0ef6bd527b9b23b9ceef70dc8b4cd1ee83ca14541964e764ad23f5151204f0f"
+        );
+        assert_eq!(
+            hex::encode(keys.view_key().as_slice(), false),
+            "42ba20adb337e5eca797565be11c9adb0a8bef8c830bccc2df712535d3b8f608"
+        );
+        assert_eq!(
+            keys.public_spend_key(),
+            H256::from("7d996b0f2db6dbb5f2a086211f2399a4a7479b2c911af307fdc3f7f61a88cb0e")
+        );
+        assert_eq!(
+            keys.public_view_key(),
+            H256::from("1c06bcac7082f73af10460b5f2849aded79374b2fbdaae5d9384b9b6514fddcb")
+        );
+        assert_eq!(keys.address(Network::Mainnet), ADDRESS);
+        assert_eq!(
+            keys.address(Network::Testnet),
+            "9wviCeWe2D8XS82k2ovp5EUYLzBt9pYNW2LXUFsZiv8S3Mt21FZ5qQaAroko1enzw3eGr9qC7X1D7Geoo2RrAotYPwq9Gm8"
+        );
+        assert_eq!(
+            keys.address(Network::Stagenet),
+            "56bCoEmLPT8XS82k2ovp5EUYLzBt9pYNW2LXUFsZiv8S3Mt21FZ5qQaAroko1enzw3eGr9qC7X1D7Geoo2RrAotYPx1iovY"
+        );
+    }
+
+    #[test]
+    fn test_monero_view_keys() {
+        let keys = MoneroSeed::parse(PHRASE).unwrap().keys();
+        let view_keys = keys.view_keys();
+        assert_eq!(view_keys.view_key(), keys.view_key());
+        assert_eq!(view_keys.public_view_key(), keys.public_view_key());
+        assert_eq!(view_keys.address(Network::Mainnet), ADDRESS);
+
+        let imported = ViewKeys::new(
+            keys.view_key().as_slice(),
+            keys.public_spend_key().as_slice(),
+        )
+        .unwrap();
+        assert_eq!(imported.public_spend_key(), keys.public_spend_key());
+        assert_eq!(imported.address(Network::Mainnet), ADDRESS);
+
+        let restored = PrivateKeys::with_spend_key(keys.spend_key().as_slice()).unwrap();
+        assert_eq!(restored.view_key(), keys.view_key());
+
+        // Not a canonical scalar.
+        PrivateKeys::with_spend_key(&[0xff; 32]).unwrap_err();
+        ViewKeys::new(&[0xff; 32], keys.public_spend_key().as_slice()).unwrap_err();
+        // Not a point of the curve.
+        let not_a_point =
+            H256::from("0200000000000000000000000000000000000000000000000000000000000000");
+        ViewKeys::new(keys.view_key().as_slice(), not_a_point.as_slice()).unwrap_err();
+    }
+
+    #[test]
+    fn test_monero_generate() {
+        let seed = MoneroSeed::generate(&mut OsRng);
+        let restored = MoneroSeed::parse(&seed.phrase()).unwrap();
+        assert_eq!(restored.keys().spend_key(), seed.keys().spend_key());
+        // The phrase encodes the private spend key as is.
+        assert_eq!(restored.secret, seed.keys().spend_key());
+    }
+
+    #[test]
+    fn test_monero_seed_invalid() {
+        // Invalid checksum word.
+        let phrase = PHRASE.replace("seasons randomly", "seasons sequence");
+        assert!(!MoneroSeed::is_valid(&phrase));
+        // 24 words.
+        let phrase = PHRASE.trim_end_matches(" randomly");
+        assert!(!MoneroSeed::is_valid(phrase));
+        // Not a word of the wordlist.
+        let phrase = PHRASE.replace("atlas", "atlass");
+        assert!(!MoneroSeed::is_valid(&phrase));
+    }
+}
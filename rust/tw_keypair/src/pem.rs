@@ -0,0 +1,46 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! PEM encoding of PKCS#8 private keys and SPKI public keys as specified in
+//! [RFC 7468](https://www.rfc-editor.org/rfc/rfc7468).
+
+use crate::{KeyPairError, KeyPairResult};
+use der::pem::LineEnding;
+use der::{Document, SecretDocument};
+use zeroize::Zeroizing;
+
+const PRIVATE_KEY_LABEL: &str = "PRIVATE KEY";
+const PUBLIC_KEY_LABEL: &str = "PUBLIC KEY";
+
+/// Decodes a PKCS#8 `PRIVATE KEY` PEM document into DER bytes.
+pub(crate) fn decode_private_key(pem: &str) -> KeyPairResult<SecretDocument> {
+    let (label, doc) = SecretDocument::from_pem(pem).map_err(|_| KeyPairError::InvalidSecretKey)?;
+    if label != PRIVATE_KEY_LABEL {
+        return Err(KeyPairError::InvalidSecretKey);
+    }
+    Ok(doc)
+}
+
+/// Encodes the given PKCS#8 DER bytes as a `PRIVATE KEY` PEM document.
+pub(crate) fn encode_private_key(der: &[u8]) -> Zeroizing<String> {
+    SecretDocument::try_from(der)
+        .and_then(|doc| doc.to_pem(PRIVATE_KEY_LABEL, LineEnding::LF))
+        .expect("Expected a valid DER document")
+}
+
+/// Decodes an SPKI `PUBLIC KEY` PEM document into DER bytes.
+pub(crate) fn decode_public_key(pem: &str) -> KeyPairResult<Document> {
+    let (label, doc) = Document::from_pem(pem).map_err(|_| KeyPairError::InvalidPublicKey)?;
+    if label != PUBLIC_KEY_LABEL {
+        return Err(KeyPairError::InvalidPublicKey);
+    }
+    Ok(doc)
+}
+
+/// Encodes the given SPKI DER bytes as a `PUBLIC KEY` PEM document.
+pub(crate) fn encode_public_key(der: &[u8]) -> String {
+    Document::try_from(der)
+        .and_then(|doc| doc.to_pem(PUBLIC_KEY_LABEL, LineEnding::LF))
+        .expect("Expected a valid DER document")
+}
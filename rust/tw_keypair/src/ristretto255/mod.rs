@@ -0,0 +1,93 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Schnorr signatures over the [Ristretto255](https://www.rfc-editor.org/rfc/rfc9496) group.
+//!
+//! The signatures are constructed the same way as `ed25519` signatures specified in
+//! [RFC 8032](https://www.rfc-editor.org/rfc/rfc8032#section-5.1.6), but:
+//! * the points are encoded as Ristretto255 elements;
+//! * the secret scalar is the lower half of `SHA-512(secret)` reduced modulo the group order,
+//!   i.e. it's not clamped, as the Ristretto255 group has a prime order.
+
+mod keypair;
+mod private;
+mod public;
+mod signature;
+
+pub use keypair::KeyPair;
+pub use private::PrivateKey;
+pub use public::PublicKey;
+pub use signature::Signature;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::{SigningKeyTrait, VerifyingKeyTrait};
+    use rand_core::OsRng;
+    use tw_hash::{H256, H512};
+    use tw_misc::traits::{ToBytesVec, ToBytesZeroizing};
+
+    const SECRET: &str = "afeefca74d9a325cf1d6b6911d61a65c32afa8e02bd5e78e2e4ac2910bab45f5";
+    const PUBLIC: &str = "2084a35eb41fc3a732bffebda8d12e207b318080481e6a4542e2232d2818db2b";
+
+    #[test]
+    fn test_private_from_bytes() {
+        let private = PrivateKey::try_from(SECRET).unwrap();
+        let actual = private.to_zeroizing_vec();
+        assert_eq!(actual.as_slice(), H256::from(SECRET).as_slice());
+    }
+
+    #[test]
+    fn test_private_to_public() {
+        let private = PrivateKey::try_from(SECRET).unwrap();
+        assert_eq!(private.public().to_bytes(), H256::from(PUBLIC));
+    }
+
+    #[test]
+    fn test_keypair_sign_verify() {
+        let keypair = KeyPair::try_from(SECRET).unwrap();
+        let message = b"Hello".to_vec();
+
+        let actual = keypair.sign(message.clone()).unwrap();
+        let expected = H512::from("f8491b0551416c1465a7eeb0b9c76554f65d46898f2551ce1a326cb5cf68a25149034756a9547bb4e7a892aaac4655e711449b6776c6cdae16043805d607380d");
+        assert_eq!(actual.to_bytes(), expected);
+
+        assert!(keypair.verify(actual.clone(), message));
+        assert!(!keypair.verify(actual, b"Hello!".to_vec()));
+    }
+
+    #[test]
+    fn test_generate_sign_verify() {
+        let private = PrivateKey::generate(&mut OsRng);
+        let public = private.public();
+        let message = b"Hello".to_vec();
+
+        let signature = private.sign(message.clone()).unwrap();
+        let signature = Signature::try_from(signature.to_vec().as_slice()).unwrap();
+        assert!(public.verify(signature, message));
+    }
+
+    #[test]
+    fn test_public_from_bytes() {
+        let public = PublicKey::try_from(PUBLIC).unwrap();
+        assert_eq!(public.to_vec(), H256::from(PUBLIC).into_vec());
+
+        // Non-canonical encoding, i.e. a negative field element.
+        PublicKey::try_from("0100000000000000000000000000000000000000000000000000000000000000")
+            .unwrap_err();
+        // The identity element.
+        PublicKey::try_from("0000000000000000000000000000000000000000000000000000000000000000")
+            .unwrap_err();
+        PublicKey::try_from(&[1; 31][..]).unwrap_err();
+    }
+
+    #[test]
+    fn test_signature_from_invalid_bytes() {
+        // `s` is not less than the group order.
+        let mut bytes = [0; 64];
+        bytes[32..].copy_from_slice(&[0xff; 32]);
+        Signature::try_from(bytes.as_slice()).unwrap_err();
+        Signature::try_from(&[0; 63][..]).unwrap_err();
+    }
+}
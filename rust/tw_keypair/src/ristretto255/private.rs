@@ -0,0 +1,123 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::ristretto255::public::PublicKey;
+use crate::ristretto255::signature::Signature;
+use crate::traits::SigningKeyTrait;
+use crate::{KeyPairError, KeyPairResult};
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_TABLE;
+use curve25519_dalek::scalar::Scalar;
+use rand_core::{CryptoRng, RngCore};
+use sha2::{Digest, Sha512};
+use std::fmt;
+use tw_encoding::hex;
+use tw_hash::{H256, H512};
+use tw_misc::traits::ToBytesZeroizing;
+use zeroize::{ZeroizeOnDrop, Zeroizing};
+
+/// Represents a Ristretto255 private key.
+///
+/// The key is a 32 byte secret that is expanded via `SHA-512` into a secret scalar and a nonce prefix.
+#[derive(ZeroizeOnDrop)]
+pub struct PrivateKey {
+    secret: H256,
+    /// The secret scalar `a`, i.e. the lower half of `SHA-512(secret)` reduced modulo the group order.
+    key: Scalar,
+    /// The upper half of `SHA-512(secret)` that is used to derive the signing nonce.
+    nonce: H256,
+}
+
+impl fmt::Debug for PrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PrivateKey")
+            .field("public", &self.public())
+            .finish()
+    }
+}
+
+impl PrivateKey {
+    /// Generates a random private key using the given `rng`.
+    pub fn generate(rng: &mut (impl CryptoRng + RngCore)) -> PrivateKey {
+        loop {
+            let mut secret = Zeroizing::new(H256::default());
+            rng.fill_bytes(secret.as_mut_slice());
+            if let Ok(private) = PrivateKey::try_from(secret.as_slice()) {
+                return private;
+            }
+        }
+    }
+
+    /// Returns an associated Ristretto255 public key.
+    pub fn public(&self) -> PublicKey {
+        PublicKey::with_point(&self.key * RISTRETTO_BASEPOINT_TABLE)
+    }
+
+    /// Signs the `message` with the associated `public` key.
+    #[allow(non_snake_case)]
+    pub(crate) fn sign_with_public_key(&self, public: &PublicKey, message: &[u8]) -> Signature {
+        let r = Scalar::from_hash(
+            Sha512::new()
+                .chain_update(self.nonce.as_slice())
+                .chain_update(message),
+        );
+        let R = (&r * RISTRETTO_BASEPOINT_TABLE).compress();
+
+        let k = Scalar::from_hash(
+            Sha512::new()
+                .chain_update(R.as_bytes())
+                .chain_update(public.as_slice())
+                .chain_update(message),
+        );
+        let s = k * self.key + r;
+
+        Signature { R, s }
+    }
+}
+
+impl SigningKeyTrait for PrivateKey {
+    type SigningMessage = Vec<u8>;
+    type Signature = Signature;
+
+    fn sign(&self, message: Self::SigningMessage) -> KeyPairResult<Self::Signature> {
+        Ok(self.sign_with_public_key(&self.public(), &message))
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for PrivateKey {
+    type Error = KeyPairError;
+
+    /// Returns an error if the secret scalar is zero.
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let secret = H256::try_from(data).map_err(|_| KeyPairError::InvalidSecretKey)?;
+
+        let hash = Zeroizing::new(
+            H512::try_from(Sha512::digest(secret.as_slice()).as_slice())
+                .expect("Expected 64 byte hash"),
+        );
+        let (lower, nonce): (H256, H256) = hash.split();
+        let key = Scalar::from_bytes_mod_order(lower.take());
+        if key == Scalar::ZERO {
+            return Err(KeyPairError::InvalidSecretKey);
+        }
+
+        Ok(PrivateKey { secret, key, nonce })
+    }
+}
+
+impl<'a> TryFrom<&'a str> for PrivateKey {
+    type Error = KeyPairError;
+
+    fn try_from(hex: &'a str) -> Result<Self, Self::Error> {
+        let bytes = Zeroizing::new(hex::decode(hex).map_err(|_| KeyPairError::InvalidSecretKey)?);
+        Self::try_from(bytes.as_slice())
+    }
+}
+
+impl ToBytesZeroizing for PrivateKey {
+    fn to_zeroizing_vec(&self) -> Zeroizing<Vec<u8>> {
+        Zeroizing::new(self.secret.to_vec())
+    }
+}
+
+crate::ct::impl_ct_eq!(PrivateKey);
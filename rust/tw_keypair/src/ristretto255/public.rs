@@ -0,0 +1,97 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::ristretto255::signature::Signature;
+use crate::traits::VerifyingKeyTrait;
+use crate::KeyPairError;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::IsIdentity;
+use sha2::{Digest, Sha512};
+use tw_encoding::hex;
+use tw_hash::H256;
+use tw_misc::traits::ToBytesVec;
+
+/// Represents a Ristretto255 public key, i.e. a compressed Ristretto point.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PublicKey {
+    compressed: CompressedRistretto,
+    point: RistrettoPoint,
+}
+
+/// cbindgen:ignore
+impl PublicKey {
+    /// The number of bytes in a serialized public key.
+    pub const LEN: usize = H256::LEN;
+
+    pub(crate) fn with_point(point: RistrettoPoint) -> PublicKey {
+        PublicKey {
+            compressed: point.compress(),
+            point,
+        }
+    }
+
+    /// Returns the raw data of the public key (32 bytes).
+    pub fn to_bytes(&self) -> H256 {
+        H256::from(self.compressed.to_bytes())
+    }
+
+    /// Returns the raw data of the public key.
+    pub fn as_slice(&self) -> &[u8] {
+        self.compressed.as_bytes()
+    }
+}
+
+impl VerifyingKeyTrait for PublicKey {
+    type SigningMessage = Vec<u8>;
+    type VerifySignature = Signature;
+
+    /// Checks if `s * B == R + k * A`, where `k = SHA-512(R || A || message)`.
+    #[allow(non_snake_case)]
+    fn verify(&self, signature: Self::VerifySignature, message: Self::SigningMessage) -> bool {
+        let k = Scalar::from_hash(
+            Sha512::new()
+                .chain_update(signature.R.as_bytes())
+                .chain_update(self.as_slice())
+                .chain_update(&message),
+        );
+
+        let R = RistrettoPoint::vartime_double_scalar_mul_basepoint(&k, &-self.point, &signature.s);
+        R.compress() == signature.R
+    }
+}
+
+impl ToBytesVec for PublicKey {
+    fn to_vec(&self) -> Vec<u8> {
+        self.as_slice().to_vec()
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for PublicKey {
+    type Error = KeyPairError;
+
+    /// Expects a canonical encoding of a non-identity element.
+    fn try_from(pubkey: &'a [u8]) -> Result<Self, Self::Error> {
+        let pubkey = H256::try_from(pubkey).map_err(|_| KeyPairError::InvalidPublicKey)?;
+
+        let compressed = CompressedRistretto(pubkey.take());
+        let point = compressed
+            .decompress()
+            .ok_or(KeyPairError::InvalidPublicKey)?;
+        if point.is_identity() {
+            return Err(KeyPairError::InvalidPublicKey);
+        }
+
+        Ok(PublicKey { compressed, point })
+    }
+}
+
+impl<'a> TryFrom<&'a str> for PublicKey {
+    type Error = KeyPairError;
+
+    fn try_from(hex: &'a str) -> Result<Self, Self::Error> {
+        let bytes = hex::decode(hex).map_err(|_| KeyPairError::InvalidPublicKey)?;
+        Self::try_from(bytes.as_slice())
+    }
+}
@@ -0,0 +1,52 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::KeyPairError;
+use curve25519_dalek::ristretto::CompressedRistretto;
+use curve25519_dalek::scalar::Scalar;
+use tw_hash::{concat, H256, H512};
+use tw_misc::traits::ToBytesVec;
+
+/// Represents a Ristretto255 Schnorr signature, i.e. `R || s`.
+#[allow(non_snake_case)]
+#[derive(Clone, Debug)]
+pub struct Signature {
+    pub(crate) R: CompressedRistretto,
+    pub(crate) s: Scalar,
+}
+
+impl Signature {
+    /// cbindgen:ignore
+    pub const LEN: usize = H512::LEN;
+
+    /// Returns the signature data (64 bytes).
+    pub fn to_bytes(&self) -> H512 {
+        let left = H256::from(self.R.to_bytes());
+        let right = H256::from(self.s.to_bytes());
+        concat(left, right)
+    }
+}
+
+impl ToBytesVec for Signature {
+    fn to_vec(&self) -> Vec<u8> {
+        self.to_bytes().into_vec()
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for Signature {
+    type Error = KeyPairError;
+
+    /// Expects `s` to be a canonical scalar, i.e. less than the group order.
+    fn try_from(sign: &'a [u8]) -> Result<Self, Self::Error> {
+        let bytes = H512::try_from(sign).map_err(|_| KeyPairError::InvalidSignature)?;
+        let (lower, upper): (H256, H256) = bytes.split();
+
+        let s = Option::from(Scalar::from_canonical_bytes(upper.take()))
+            .ok_or(KeyPairError::InvalidSignature)?;
+        Ok(Signature {
+            R: CompressedRistretto(lower.take()),
+            s,
+        })
+    }
+}
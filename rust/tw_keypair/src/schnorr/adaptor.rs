@@ -0,0 +1,182 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::ecdsa::secp256k1;
+use crate::schnorr::signature::Signature;
+use crate::schnorr::{
+    compressed_point, has_even_y, point_from_slice, scalar_from_canonical_bytes, scalar_to_bytes,
+    x_only,
+};
+use crate::{KeyPairError, KeyPairResult};
+use k256::{AffinePoint, ProjectivePoint, Scalar};
+use tw_hash::{H256, H264};
+use tw_misc::traits::ToBytesVec;
+
+/// Represents a Schnorr adaptor (pre-) signature that is encrypted with an adaptor point `T = t*G`.
+///
+/// The adaptor signature can be completed into a valid BIP-340 [`Signature`] by anyone who knows
+/// the adaptor secret `t`, and the secret can be extracted from the adaptor and completed signatures.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AdaptorSignature {
+    /// The nonce point `R' = R + T`.
+    /// Its Y coordinate parity determines how the signature should be completed.
+    pub(crate) r: AffinePoint,
+    pub(crate) s: Scalar,
+}
+
+/// cbindgen:ignore
+impl AdaptorSignature {
+    /// The number of bytes for a serialized adaptor signature representation.
+    pub const LEN: usize = H264::LEN + H256::LEN;
+
+    pub(crate) fn new(r: AffinePoint, s: Scalar) -> AdaptorSignature {
+        AdaptorSignature { r, s }
+    }
+
+    /// Completes the adaptor signature into a BIP-340 signature with the given `adaptor_secret`.
+    pub fn complete(&self, adaptor_secret: &secp256k1::PrivateKey) -> Signature {
+        let t = adaptor_secret.secret.as_nonzero_scalar().as_ref();
+        let s = if has_even_y(&self.r) {
+            self.s + t
+        } else {
+            self.s - t
+        };
+        Signature::new(x_only(&self.r), s)
+    }
+
+    /// Extracts the adaptor secret from the completed `signature`.
+    /// Returns an error if the `signature` is not a completion of the adaptor signature with the `adaptor` point.
+    pub fn extract_secret(
+        &self,
+        signature: &Signature,
+        adaptor: &secp256k1::PublicKey,
+    ) -> KeyPairResult<secp256k1::PrivateKey> {
+        if signature.r != x_only(&self.r) {
+            return Err(KeyPairError::InvalidSignature);
+        }
+
+        let t = if has_even_y(&self.r) {
+            signature.s - self.s
+        } else {
+            self.s - signature.s
+        };
+        if ProjectivePoint::GENERATOR * t != ProjectivePoint::from(*adaptor.public.as_affine()) {
+            return Err(KeyPairError::InvalidSignature);
+        }
+
+        secp256k1::PrivateKey::try_from(scalar_to_bytes(&t).as_slice())
+    }
+
+    /// Returns a binary adaptor signature representation:
+    /// RS, where R - 33 byte compressed nonce point, S - 32 byte array.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut res = Vec::with_capacity(Self::LEN);
+        res.extend_from_slice(compressed_point(&self.r).as_slice());
+        res.extend_from_slice(scalar_to_bytes(&self.s).as_slice());
+        res
+    }
+}
+
+impl ToBytesVec for AdaptorSignature {
+    fn to_vec(&self) -> Vec<u8> {
+        self.to_bytes()
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for AdaptorSignature {
+    type Error = KeyPairError;
+
+    fn try_from(sig: &'a [u8]) -> Result<Self, Self::Error> {
+        if sig.len() != Self::LEN {
+            return Err(KeyPairError::InvalidSignature);
+        }
+        let (r, s) = sig.split_at(H264::LEN);
+
+        let r = point_from_slice(r).map_err(|_| KeyPairError::InvalidSignature)?;
+        let s = H256::try_from(s).map_err(|_| KeyPairError::InvalidSignature)?;
+        let s = scalar_from_canonical_bytes(&s).ok_or(KeyPairError::InvalidSignature)?;
+        Ok(AdaptorSignature { r, s })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schnorr::PrivateKey;
+    use crate::traits::{SigningKeyTrait, VerifyingKeyTrait};
+    use tw_encoding::hex;
+
+    const SECRET: &str = "afeefca74d9a325cf1d6b6911d61a65c32afa8e02bd5e78e2e4ac2910bab45f5";
+    const ADAPTOR_SECRET: &str = "9cd3b16e10bd574fed3743d8e0de0b7b4e6c69f3245ab5a168ef010d22bfefa0";
+    const HASH_TO_SIGN: &str = "71b7098e8150cde90f3ec00280815d3069f81c7cdb6d83bbe2b897b1afbe7cd6";
+
+    #[test]
+    fn test_adaptor_sign_complete_extract() {
+        let private = PrivateKey::try_from(SECRET).unwrap().no_aux_rand();
+        let public = private.public();
+        let adaptor_secret = secp256k1::PrivateKey::try_from(ADAPTOR_SECRET).unwrap();
+        let adaptor = adaptor_secret.public();
+        let hash_to_sign = H256::from(HASH_TO_SIGN);
+
+        let adaptor_sig = private.sign_adaptor(hash_to_sign, &adaptor).unwrap();
+        assert_eq!(
+            hex::encode(adaptor_sig.to_bytes(), false),
+            "026b1a775a4828c1273752fb3a797eb2dd9a40bbf595d6a79ce33ba15d853e3320611149fb897854c057fb4bd91540ce3f068b13e3dfd969238f56197dd89fdfdd"
+        );
+        assert!(public.verify_adaptor(&adaptor_sig, hash_to_sign, &adaptor));
+        // The adaptor signature is not a valid BIP-340 signature.
+        let invalid = Signature::new(x_only(&adaptor_sig.r), adaptor_sig.s);
+        assert!(!public.verify(invalid, hash_to_sign));
+
+        let signature = adaptor_sig.complete(&adaptor_secret);
+        assert_eq!(
+            hex::encode(signature.to_vec(), false),
+            "6b1a775a4828c1273752fb3a797eb2dd9a40bbf595d6a79ce33ba15d853e3320fde4fb699a35ac1045328fb1f61ed9ba54f77dd704341ec4f8451a8afb5fcf7d"
+        );
+        assert!(public.verify(signature.clone(), hash_to_sign));
+
+        let extracted = adaptor_sig.extract_secret(&signature, &adaptor).unwrap();
+        assert_eq!(extracted.public().compressed(), adaptor.compressed());
+    }
+
+    #[test]
+    fn test_adaptor_verify_invalid() {
+        let private = PrivateKey::try_from(SECRET).unwrap();
+        let adaptor = secp256k1::PrivateKey::try_from(ADAPTOR_SECRET)
+            .unwrap()
+            .public();
+        let another_adaptor = secp256k1::PrivateKey::try_from(SECRET).unwrap().public();
+        let hash_to_sign = H256::from(HASH_TO_SIGN);
+
+        let adaptor_sig = private.sign_adaptor(hash_to_sign, &adaptor).unwrap();
+        assert!(!private
+            .public()
+            .verify_adaptor(&adaptor_sig, hash_to_sign, &another_adaptor));
+        assert!(!private
+            .public()
+            .verify_adaptor(&adaptor_sig, H256::default(), &adaptor));
+    }
+
+    #[test]
+    fn test_adaptor_extract_invalid() {
+        let private = PrivateKey::try_from(SECRET).unwrap();
+        let adaptor = secp256k1::PrivateKey::try_from(ADAPTOR_SECRET)
+            .unwrap()
+            .public();
+        let hash_to_sign = H256::from(HASH_TO_SIGN);
+
+        let adaptor_sig = private.sign_adaptor(hash_to_sign, &adaptor).unwrap();
+        let unrelated = private.sign(hash_to_sign).unwrap();
+        assert!(adaptor_sig.extract_secret(&unrelated, &adaptor).is_err());
+    }
+
+    #[test]
+    fn test_adaptor_signature_to_from_bytes() {
+        let bytes = hex::decode("026b1a775a4828c1273752fb3a797eb2dd9a40bbf595d6a79ce33ba15d853e3320611149fb897854c057fb4bd91540ce3f068b13e3dfd969238f56197dd89fdfdd").unwrap();
+        let adaptor_sig = AdaptorSignature::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(adaptor_sig.to_vec(), bytes);
+
+        AdaptorSignature::try_from(&bytes[1..]).unwrap_err();
+    }
+}
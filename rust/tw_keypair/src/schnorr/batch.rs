@@ -0,0 +1,112 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::schnorr::public::PublicKey;
+use crate::schnorr::signature::Signature;
+use crate::schnorr::{challenge, lift_x};
+use k256::elliptic_curve::ops::LinearCombination;
+use k256::elliptic_curve::Field;
+use k256::{ProjectivePoint, Scalar};
+use rand_core::OsRng;
+use tw_hash::H256;
+
+/// Verifies the given `(public, signature, message)` tuples at once.
+/// Returns `true` only if all signatures are valid. An empty batch is considered valid.
+///
+/// Checks that `(s1 + a2*s2 + ... + au*su) * G = R1 + e1*P1 + a2*(R2 + e2*P2) + ... + au*(Ru + eu*Pu)`,
+/// where `a2...au` are random coefficients.
+/// Source: https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki#batch-verification
+pub fn verify_batch(batch: &[(&PublicKey, &Signature, H256)]) -> bool {
+    let mut s_sum = Scalar::ZERO;
+    let mut point_sum = ProjectivePoint::IDENTITY;
+
+    for (i, (public, signature, message)) in batch.iter().enumerate() {
+        let r_point = match lift_x(&signature.r) {
+            Some(r_point) => ProjectivePoint::from(r_point),
+            None => return false,
+        };
+        let e = challenge(&signature.r, &public.x_only(), message.as_slice());
+
+        let a = if i == 0 {
+            Scalar::ONE
+        } else {
+            Scalar::random(&mut OsRng)
+        };
+
+        s_sum += a * signature.s;
+        point_sum += ProjectivePoint::lincomb(
+            &r_point,
+            &a,
+            &ProjectivePoint::from(*public.point()),
+            &(a * e),
+        );
+    }
+
+    ProjectivePoint::GENERATOR * s_sum == point_sum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schnorr::PrivateKey;
+    use crate::traits::SigningKeyTrait;
+
+    const SECRETS: [&str; 3] = [
+        "afeefca74d9a325cf1d6b6911d61a65c32afa8e02bd5e78e2e4ac2910bab45f5",
+        "9cd3b16e10bd574fed3743d8e0de0b7b4e6c69f3245ab5a168ef010d22bfefa0",
+        "b7e151628aed2a6abf7158809cf4f3c762e7160f38b4da56a784d9045190cfef",
+    ];
+
+    fn sign_all() -> Vec<(PublicKey, Signature, H256)> {
+        SECRETS
+            .iter()
+            .enumerate()
+            .map(|(i, secret)| {
+                let private = PrivateKey::try_from(*secret).unwrap();
+                let message = H256::from([i as u8; 32]);
+                let signature = private.sign(message).unwrap();
+                (private.public(), signature, message)
+            })
+            .collect()
+    }
+
+    fn as_batch(items: &[(PublicKey, Signature, H256)]) -> Vec<(&PublicKey, &Signature, H256)> {
+        items
+            .iter()
+            .map(|(public, signature, message)| (public, signature, *message))
+            .collect()
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        let items = sign_all();
+        assert!(verify_batch(&as_batch(&items)));
+        assert!(verify_batch(&as_batch(&items[..1])));
+        assert!(verify_batch(&[]));
+    }
+
+    #[test]
+    fn test_verify_batch_invalid() {
+        let mut items = sign_all();
+        // Sign the message of the second item with the first private key.
+        items[1].1 = PrivateKey::try_from(SECRETS[0])
+            .unwrap()
+            .sign(items[1].2)
+            .unwrap();
+        assert!(!verify_batch(&as_batch(&items)));
+
+        let mut items = sign_all();
+        items[2].2 = H256::default();
+        assert!(!verify_batch(&as_batch(&items)));
+    }
+
+    #[test]
+    fn test_verify_batch_invalid_r() {
+        let mut items = sign_all();
+        // There is no point with such X coordinate on the curve.
+        items[0].1.r =
+            H256::from("eefdea4cdb677750a420fee807eacf21eb9898ae79b9768766e4faa04a2d4a34");
+        assert!(!verify_batch(&as_batch(&items)));
+    }
+}
@@ -0,0 +1,837 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! FROST threshold Schnorr signatures ([RFC 9591](https://www.rfc-editor.org/rfc/rfc9591))
+//! adapted to produce BIP-340 signatures.
+//!
+//! 1. A trusted dealer splits a secret key into `max_signers` [`KeyShare`]s via [`trusted_dealer_keygen`],
+//!    so any `min_signers` of them can produce a signature valid for the group public key.
+//! 2. Round 1: every participating signer generates [`SigningNonces`] and [`SigningCommitments`] via [`commit`],
+//!    and sends the commitments to the coordinator.
+//! 3. Round 2: the coordinator composes a [`SigningPackage`] and sends it to the signers.
+//!    Every signer creates a [`SignatureShare`] via [`sign`].
+//! 4. The coordinator aggregates the signature shares into a BIP-340 [`Signature`] via [`aggregate`].
+
+use crate::schnorr::private::PrivateKey;
+use crate::schnorr::public::PublicKey;
+use crate::schnorr::signature::Signature;
+use crate::schnorr::{
+    challenge, compressed_point, has_even_y, point_from_slice, scalar_from_canonical_bytes,
+    scalar_from_hash, scalar_to_bytes, tagged_hash, x_only,
+};
+use crate::{KeyPairError, KeyPairResult};
+use k256::elliptic_curve::Field;
+use k256::{AffinePoint, ProjectivePoint, Scalar};
+use rand_core::{OsRng, RngCore};
+use std::ops::Range;
+use tw_hash::{H256, H264};
+use tw_misc::traits::{ToBytesVec, ToBytesZeroizing};
+use zeroize::{ZeroizeOnDrop, Zeroizing};
+
+const NONCE_TAG: &str = "FROST/nonce";
+const BINDING_TAG: &str = "FROST/binding";
+const MESSAGE_TAG: &str = "FROST/message";
+const COMMITMENTS_TAG: &str = "FROST/commitments";
+
+const IDENTIFIER_LEN: usize = 2;
+
+/// Represents a non-zero identifier of a participant.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Identifier(u16);
+
+impl Identifier {
+    pub fn new(id: u16) -> KeyPairResult<Identifier> {
+        if id == 0 {
+            return Err(KeyPairError::InvalidSecretKey);
+        }
+        Ok(Identifier(id))
+    }
+
+    pub fn value(&self) -> u16 {
+        self.0
+    }
+
+    fn to_scalar(self) -> Scalar {
+        Scalar::from(self.0 as u64)
+    }
+
+    fn from_slice(bytes: &[u8]) -> KeyPairResult<Identifier> {
+        let bytes: [u8; IDENTIFIER_LEN] = bytes
+            .try_into()
+            .map_err(|_| KeyPairError::InvalidSecretKey)?;
+        Identifier::new(u16::from_be_bytes(bytes))
+    }
+}
+
+/// Represents a secret share of a participant.
+#[derive(ZeroizeOnDrop)]
+pub struct KeyShare {
+    #[zeroize(skip)]
+    identifier: Identifier,
+    secret: Scalar,
+    #[zeroize(skip)]
+    group_public: PublicKey,
+}
+
+/// cbindgen:ignore
+impl KeyShare {
+    /// The number of bytes in a serialized key share:
+    /// 2 byte identifier, 32 byte secret share and 32 byte x-only group public key.
+    pub const LEN: usize = IDENTIFIER_LEN + H256::LEN + H256::LEN;
+
+    const SECRET_RANGE: Range<usize> = IDENTIFIER_LEN..(IDENTIFIER_LEN + H256::LEN);
+    const GROUP_PUBLIC_RANGE: Range<usize> = (IDENTIFIER_LEN + H256::LEN)..Self::LEN;
+
+    pub fn identifier(&self) -> Identifier {
+        self.identifier
+    }
+
+    /// Returns the x-only group public key.
+    pub fn group_public_key(&self) -> PublicKey {
+        self.group_public.clone()
+    }
+
+    /// Verifies the key share against the dealer's commitment.
+    pub fn verify(&self, public_package: &PublicKeyPackage) -> bool {
+        public_package.group_public_key() == self.group_public
+            && ProjectivePoint::GENERATOR * self.secret
+                == public_package.verifying_share(self.identifier)
+    }
+}
+
+impl ToBytesZeroizing for KeyShare {
+    fn to_zeroizing_vec(&self) -> Zeroizing<Vec<u8>> {
+        let secret = Zeroizing::new(scalar_to_bytes(&self.secret));
+
+        let mut res = Zeroizing::new(Vec::with_capacity(Self::LEN));
+        res.extend_from_slice(&self.identifier.0.to_be_bytes());
+        res.extend_from_slice(secret.as_slice());
+        res.extend_from_slice(self.group_public.x_only().as_slice());
+        res
+    }
+}
+
+crate::ct::impl_ct_eq!(KeyShare);
+
+impl<'a> TryFrom<&'a [u8]> for KeyShare {
+    type Error = KeyPairError;
+
+    fn try_from(bytes: &'a [u8]) -> Result<Self, Self::Error> {
+        if bytes.len() != Self::LEN {
+            return Err(KeyPairError::InvalidSecretKey);
+        }
+
+        let identifier = Identifier::from_slice(&bytes[..IDENTIFIER_LEN])?;
+        let secret = Zeroizing::new(
+            H256::try_from(&bytes[Self::SECRET_RANGE])
+                .map_err(|_| KeyPairError::InvalidSecretKey)?,
+        );
+        let secret = scalar_from_canonical_bytes(&secret)
+            .filter(|secret| !bool::from(secret.is_zero()))
+            .ok_or(KeyPairError::InvalidSecretKey)?;
+        let group_public = PublicKey::try_from(&bytes[Self::GROUP_PUBLIC_RANGE])?;
+
+        Ok(KeyShare {
+            identifier,
+            secret,
+            group_public,
+        })
+    }
+}
+
+/// Represents the dealer's commitment to the secret sharing polynomial,
+/// that is used to verify key shares and signature shares.
+#[derive(Clone, Debug)]
+pub struct PublicKeyPackage {
+    /// Commitments to the polynomial coefficients.
+    /// The first one is the group public key that always has an even Y coordinate.
+    commitment: Vec<ProjectivePoint>,
+}
+
+impl PublicKeyPackage {
+    /// Returns the x-only group public key.
+    pub fn group_public_key(&self) -> PublicKey {
+        PublicKey::with_point(&self.commitment[0].to_affine())
+    }
+
+    /// Returns the minimum number of signers required to produce a signature.
+    pub fn min_signers(&self) -> usize {
+        self.commitment.len()
+    }
+
+    /// Returns the public key `s_i * G` of the participant's secret share.
+    fn verifying_share(&self, identifier: Identifier) -> ProjectivePoint {
+        let x = identifier.to_scalar();
+        self.commitment
+            .iter()
+            .rev()
+            .fold(ProjectivePoint::IDENTITY, |acc, coefficient| {
+                acc * x + coefficient
+            })
+    }
+}
+
+impl ToBytesVec for PublicKeyPackage {
+    fn to_vec(&self) -> Vec<u8> {
+        let mut res = Vec::with_capacity(self.commitment.len() * H264::LEN);
+        for coefficient in self.commitment.iter() {
+            res.extend_from_slice(compressed_point(&coefficient.to_affine()).as_slice());
+        }
+        res
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for PublicKeyPackage {
+    type Error = KeyPairError;
+
+    fn try_from(bytes: &'a [u8]) -> Result<Self, Self::Error> {
+        if bytes.is_empty() || bytes.len() % H264::LEN != 0 {
+            return Err(KeyPairError::InvalidPublicKey);
+        }
+        let commitment = bytes
+            .chunks(H264::LEN)
+            .map(|chunk| point_from_slice(chunk).map(ProjectivePoint::from))
+            .collect::<KeyPairResult<Vec<_>>>()?;
+
+        if !has_even_y(&commitment[0].to_affine()) {
+            return Err(KeyPairError::InvalidPublicKey);
+        }
+        Ok(PublicKeyPackage { commitment })
+    }
+}
+
+/// Splits the `secret` key into `max_signers` shares, so any `min_signers` of them can sign.
+/// The group public key equals to [`PrivateKey::public`].
+pub fn trusted_dealer_keygen(
+    secret: &PrivateKey,
+    min_signers: u16,
+    max_signers: u16,
+) -> KeyPairResult<(Vec<KeyShare>, PublicKeyPackage)> {
+    if min_signers == 0 {
+        return Err(KeyPairError::InvalidSecretKey);
+    }
+
+    let mut coefficients = Zeroizing::new(vec![secret.even_y_scalar()]);
+    for _ in 1..min_signers {
+        coefficients.push(Scalar::random(&mut OsRng));
+    }
+    split_secret(&coefficients, max_signers)
+}
+
+/// Evaluates the secret sharing polynomial with the given `coefficients`
+/// at the points `1..=max_signers`.
+/// The first coefficient is the secret, so `min_signers = coefficients.len()`.
+fn split_secret(
+    coefficients: &[Scalar],
+    max_signers: u16,
+) -> KeyPairResult<(Vec<KeyShare>, PublicKeyPackage)> {
+    if coefficients.is_empty() || coefficients.len() > max_signers as usize {
+        return Err(KeyPairError::InvalidSecretKey);
+    }
+
+    let commitment: Vec<_> = coefficients
+        .iter()
+        .map(|coefficient| ProjectivePoint::GENERATOR * coefficient)
+        .collect();
+    let public_package = PublicKeyPackage { commitment };
+    let group_public = public_package.group_public_key();
+
+    let shares = (1..=max_signers)
+        .map(|id| {
+            let identifier = Identifier(id);
+            let x = identifier.to_scalar();
+            let secret = coefficients
+                .iter()
+                .rev()
+                .fold(Scalar::ZERO, |acc, coefficient| acc * x + coefficient);
+            if bool::from(secret.is_zero()) {
+                return Err(KeyPairError::InvalidSecretKey);
+            }
+            Ok(KeyShare {
+                identifier,
+                secret,
+                group_public: group_public.clone(),
+            })
+        })
+        .collect::<KeyPairResult<Vec<_>>>()?;
+
+    Ok((shares, public_package))
+}
+
+/// Represents the secret nonces of a signer.
+///
+/// # Warning
+///
+/// The nonces must never be reused, so [`sign`] takes them by value.
+#[derive(ZeroizeOnDrop)]
+pub struct SigningNonces {
+    hiding: Scalar,
+    binding: Scalar,
+    #[zeroize(skip)]
+    commitments: SigningCommitments,
+}
+
+/// Represents the public commitments to the signer's nonces that have to be sent to the coordinator.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SigningCommitments {
+    identifier: Identifier,
+    hiding: AffinePoint,
+    binding: AffinePoint,
+}
+
+/// cbindgen:ignore
+impl SigningCommitments {
+    /// The number of bytes in serialized signing commitments.
+    pub const LEN: usize = IDENTIFIER_LEN + H264::LEN * 2;
+
+    const HIDING_RANGE: Range<usize> = IDENTIFIER_LEN..(IDENTIFIER_LEN + H264::LEN);
+    const BINDING_RANGE: Range<usize> = (IDENTIFIER_LEN + H264::LEN)..Self::LEN;
+
+    pub fn identifier(&self) -> Identifier {
+        self.identifier
+    }
+}
+
+impl ToBytesVec for SigningCommitments {
+    fn to_vec(&self) -> Vec<u8> {
+        let mut res = Vec::with_capacity(Self::LEN);
+        res.extend_from_slice(&self.identifier.0.to_be_bytes());
+        res.extend_from_slice(compressed_point(&self.hiding).as_slice());
+        res.extend_from_slice(compressed_point(&self.binding).as_slice());
+        res
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for SigningCommitments {
+    type Error = KeyPairError;
+
+    fn try_from(bytes: &'a [u8]) -> Result<Self, Self::Error> {
+        if bytes.len() != Self::LEN {
+            return Err(KeyPairError::InvalidPublicKey);
+        }
+        Ok(SigningCommitments {
+            identifier: Identifier::from_slice(&bytes[..IDENTIFIER_LEN])?,
+            hiding: point_from_slice(&bytes[Self::HIDING_RANGE])?,
+            binding: point_from_slice(&bytes[Self::BINDING_RANGE])?,
+        })
+    }
+}
+
+/// Generates the signing nonces and their commitments (round 1).
+pub fn commit(key_share: &KeyShare) -> KeyPairResult<(SigningNonces, SigningCommitments)> {
+    let mut hiding_rand = Zeroizing::new([0_u8; H256::LEN]);
+    let mut binding_rand = Zeroizing::new([0_u8; H256::LEN]);
+    OsRng.fill_bytes(hiding_rand.as_mut_slice());
+    OsRng.fill_bytes(binding_rand.as_mut_slice());
+
+    commit_with_rand(
+        key_share,
+        &H256::from(*hiding_rand),
+        &H256::from(*binding_rand),
+    )
+}
+
+fn commit_with_rand(
+    key_share: &KeyShare,
+    hiding_rand: &H256,
+    binding_rand: &H256,
+) -> KeyPairResult<(SigningNonces, SigningCommitments)> {
+    let secret = Zeroizing::new(scalar_to_bytes(&key_share.secret));
+    let nonce = |rand: &H256| {
+        scalar_from_hash(&tagged_hash(
+            NONCE_TAG,
+            &[rand.as_slice(), secret.as_slice()],
+        ))
+    };
+
+    let hiding = nonce(hiding_rand);
+    let binding = nonce(binding_rand);
+    if bool::from(hiding.is_zero()) || bool::from(binding.is_zero()) {
+        return Err(KeyPairError::SigningError);
+    }
+
+    let commitments = SigningCommitments {
+        identifier: key_share.identifier,
+        hiding: (ProjectivePoint::GENERATOR * hiding).to_affine(),
+        binding: (ProjectivePoint::GENERATOR * binding).to_affine(),
+    };
+    let nonces = SigningNonces {
+        hiding,
+        binding,
+        commitments: commitments.clone(),
+    };
+    Ok((nonces, commitments))
+}
+
+/// Represents the signing commitments of the participating signers and the message to be signed.
+#[derive(Clone, Debug)]
+pub struct SigningPackage {
+    /// Sorted by the signer identifiers.
+    commitments: Vec<SigningCommitments>,
+    message: Vec<u8>,
+}
+
+/// Precomputed values of the signing package for the given group public key.
+struct SigningContext {
+    /// Binding factors in the order of [`SigningPackage::commitments`].
+    binding_factors: Vec<Scalar>,
+    /// The group commitment `R`.
+    r: AffinePoint,
+    /// The BIP-340 challenge `e`.
+    e: Scalar,
+}
+
+impl SigningPackage {
+    /// Creates a signing package of the given `message`.
+    /// Returns an error if there are no or duplicate signers.
+    pub fn new(
+        mut commitments: Vec<SigningCommitments>,
+        message: &[u8],
+    ) -> KeyPairResult<SigningPackage> {
+        commitments.sort_by_key(SigningCommitments::identifier);
+
+        let has_duplicates = commitments
+            .windows(2)
+            .any(|pair| pair[0].identifier == pair[1].identifier);
+        if commitments.is_empty() || has_duplicates {
+            return Err(KeyPairError::InvalidPublicKey);
+        }
+
+        Ok(SigningPackage {
+            commitments,
+            message: message.to_vec(),
+        })
+    }
+
+    fn index_of(&self, identifier: Identifier) -> Option<usize> {
+        self.commitments
+            .binary_search_by_key(&identifier, SigningCommitments::identifier)
+            .ok()
+    }
+
+    fn context(&self, group_public: &PublicKey) -> KeyPairResult<SigningContext> {
+        let group_public = group_public.x_only();
+
+        let mut encoded_commitments =
+            Vec::with_capacity(self.commitments.len() * (H256::LEN + H264::LEN * 2));
+        for commitments in self.commitments.iter() {
+            let identifier = scalar_to_bytes(&commitments.identifier.to_scalar());
+            encoded_commitments.extend_from_slice(identifier.as_slice());
+            encoded_commitments.extend_from_slice(compressed_point(&commitments.hiding).as_slice());
+            encoded_commitments
+                .extend_from_slice(compressed_point(&commitments.binding).as_slice());
+        }
+        let message_hash = tagged_hash(MESSAGE_TAG, &[self.message.as_slice()]);
+        let commitments_hash = tagged_hash(COMMITMENTS_TAG, &[encoded_commitments.as_slice()]);
+
+        let binding_factors: Vec<Scalar> = self
+            .commitments
+            .iter()
+            .map(|commitments| {
+                let identifier = scalar_to_bytes(&commitments.identifier.to_scalar());
+                scalar_from_hash(&tagged_hash(
+                    BINDING_TAG,
+                    &[
+                        group_public.as_slice(),
+                        message_hash.as_slice(),
+                        commitments_hash.as_slice(),
+                        identifier.as_slice(),
+                    ],
+                ))
+            })
+            .collect();
+
+        let r = self.commitments.iter().zip(binding_factors.iter()).fold(
+            ProjectivePoint::IDENTITY,
+            |acc, (commitments, rho)| {
+                acc + commitments.hiding + ProjectivePoint::from(commitments.binding) * rho
+            },
+        );
+        if r == ProjectivePoint::IDENTITY {
+            return Err(KeyPairError::SigningError);
+        }
+        let r = r.to_affine();
+
+        let e = challenge(&x_only(&r), &group_public, &self.message);
+        Ok(SigningContext {
+            binding_factors,
+            r,
+            e,
+        })
+    }
+
+    /// Returns the Lagrange coefficient of the signer at zero.
+    fn lagrange_coefficient(&self, identifier: Identifier) -> KeyPairResult<Scalar> {
+        let x_i = identifier.to_scalar();
+        let (numerator, denominator) = self
+            .commitments
+            .iter()
+            .filter(|commitments| commitments.identifier != identifier)
+            .fold((Scalar::ONE, Scalar::ONE), |(num, den), commitments| {
+                let x_j = commitments.identifier.to_scalar();
+                (num * x_j, den * (x_j - x_i))
+            });
+
+        Option::<Scalar>::from(denominator.invert())
+            .map(|inverted| numerator * inverted)
+            .ok_or(KeyPairError::SigningError)
+    }
+}
+
+/// Represents a signature share of a signer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignatureShare {
+    identifier: Identifier,
+    z: Scalar,
+}
+
+/// cbindgen:ignore
+impl SignatureShare {
+    /// The number of bytes in a serialized signature share.
+    pub const LEN: usize = IDENTIFIER_LEN + H256::LEN;
+
+    pub fn identifier(&self) -> Identifier {
+        self.identifier
+    }
+}
+
+impl ToBytesVec for SignatureShare {
+    fn to_vec(&self) -> Vec<u8> {
+        let mut res = Vec::with_capacity(Self::LEN);
+        res.extend_from_slice(&self.identifier.0.to_be_bytes());
+        res.extend_from_slice(scalar_to_bytes(&self.z).as_slice());
+        res
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for SignatureShare {
+    type Error = KeyPairError;
+
+    fn try_from(bytes: &'a [u8]) -> Result<Self, Self::Error> {
+        if bytes.len() != Self::LEN {
+            return Err(KeyPairError::InvalidSignature);
+        }
+        let identifier = Identifier::from_slice(&bytes[..IDENTIFIER_LEN])
+            .map_err(|_| KeyPairError::InvalidSignature)?;
+        let z =
+            H256::try_from(&bytes[IDENTIFIER_LEN..]).map_err(|_| KeyPairError::InvalidSignature)?;
+        let z = scalar_from_canonical_bytes(&z).ok_or(KeyPairError::InvalidSignature)?;
+        Ok(SignatureShare { identifier, z })
+    }
+}
+
+/// Creates a signature share with the given `nonces` and `key_share` (round 2).
+pub fn sign(
+    package: &SigningPackage,
+    nonces: SigningNonces,
+    key_share: &KeyShare,
+) -> KeyPairResult<SignatureShare> {
+    let identifier = key_share.identifier;
+    let index = package
+        .index_of(identifier)
+        .ok_or(KeyPairError::InvalidPublicKey)?;
+    if package.commitments[index] != nonces.commitments {
+        return Err(KeyPairError::InvalidSecretKey);
+    }
+
+    let ctx = package.context(&key_share.group_public)?;
+    let k = nonces.hiding + nonces.binding * ctx.binding_factors[index];
+    let k = if has_even_y(&ctx.r) { k } else { -k };
+
+    let lambda = package.lagrange_coefficient(identifier)?;
+    Ok(SignatureShare {
+        identifier,
+        z: k + lambda * key_share.secret * ctx.e,
+    })
+}
+
+/// Verifies the signature `share` of a signer.
+pub fn verify_share(
+    package: &SigningPackage,
+    share: &SignatureShare,
+    public_package: &PublicKeyPackage,
+) -> bool {
+    let index = match package.index_of(share.identifier) {
+        Some(index) => index,
+        None => return false,
+    };
+    let ctx = match package.context(&public_package.group_public_key()) {
+        Ok(ctx) => ctx,
+        Err(_) => return false,
+    };
+    let lambda = match package.lagrange_coefficient(share.identifier) {
+        Ok(lambda) => lambda,
+        Err(_) => return false,
+    };
+
+    let commitments = &package.commitments[index];
+    let r_share = ProjectivePoint::from(commitments.hiding)
+        + ProjectivePoint::from(commitments.binding) * ctx.binding_factors[index];
+    let r_share = if has_even_y(&ctx.r) {
+        r_share
+    } else {
+        -r_share
+    };
+
+    let verifying_share = public_package.verifying_share(share.identifier);
+    ProjectivePoint::GENERATOR * share.z == r_share + verifying_share * (lambda * ctx.e)
+}
+
+/// Aggregates the signature shares of all participating signers into a BIP-340 signature.
+/// Returns an error if any of the shares is invalid or there are not enough signers.
+pub fn aggregate(
+    package: &SigningPackage,
+    shares: &[SignatureShare],
+    public_package: &PublicKeyPackage,
+) -> KeyPairResult<Signature> {
+    if shares.len() != package.commitments.len() {
+        return Err(KeyPairError::InvalidSignature);
+    }
+    for share in shares {
+        if !verify_share(package, share, public_package) {
+            return Err(KeyPairError::InvalidSignature);
+        }
+    }
+    let mut identifiers: Vec<_> = shares.iter().map(SignatureShare::identifier).collect();
+    identifiers.sort();
+    identifiers.dedup();
+    if identifiers.len() != shares.len() {
+        return Err(KeyPairError::InvalidSignature);
+    }
+
+    let group_public = public_package.group_public_key();
+    let ctx = package.context(&group_public)?;
+    let z = shares.iter().fold(Scalar::ZERO, |acc, share| acc + share.z);
+
+    let signature = Signature::new(x_only(&ctx.r), z);
+    if !group_public.verify_message(&signature, &package.message) {
+        return Err(KeyPairError::SignatureVerifyError);
+    }
+    Ok(signature)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tw_encoding::hex;
+
+    const SECRET: &str = "afeefca74d9a325cf1d6b6911d61a65c32afa8e02bd5e78e2e4ac2910bab45f5";
+    const COEFFICIENT: &str = "9cd3b16e10bd574fed3743d8e0de0b7b4e6c69f3245ab5a168ef010d22bfefa0";
+    const GROUP_PUBLIC: &str = "99c6f51ad6f98c9c583f8e92bb7758ab2ca9a04110c0a1126ec43e5453d196c1";
+
+    /// Splits [`SECRET`] into 3 shares with the threshold of 2.
+    fn split_2_of_3() -> (Vec<KeyShare>, PublicKeyPackage) {
+        let secret = PrivateKey::try_from(SECRET).unwrap();
+        let coefficient = scalar_from_canonical_bytes(&H256::from(COEFFICIENT)).unwrap();
+        split_secret(&[secret.even_y_scalar(), coefficient], 3).unwrap()
+    }
+
+    struct ExpectedShare {
+        id: usize,
+        commitments: &'static str,
+        share: &'static str,
+    }
+
+    #[track_caller]
+    fn test_sign_impl(message: &str, expected_shares: &[ExpectedShare], expected_signature: &str) {
+        let (key_shares, public_package) = split_2_of_3();
+        let message = hex::decode(message).unwrap();
+
+        // Round 1.
+        let (nonces, commitments): (Vec<_>, Vec<_>) = expected_shares
+            .iter()
+            .map(|expected| {
+                let key_share = &key_shares[expected.id - 1];
+                let hiding_rand = H256::from([expected.id as u8; 32]);
+                let binding_rand = H256::from([expected.id as u8 + 16; 32]);
+                commit_with_rand(key_share, &hiding_rand, &binding_rand).unwrap()
+            })
+            .unzip();
+        for (commitments, expected) in commitments.iter().zip(expected_shares) {
+            assert_eq!(
+                hex::encode(commitments.to_vec(), false),
+                format!("{:04x}{}", expected.id, expected.commitments)
+            );
+        }
+
+        // Round 2.
+        let package = SigningPackage::new(commitments, &message).unwrap();
+        let shares: Vec<_> = nonces
+            .into_iter()
+            .zip(expected_shares)
+            .map(|(nonces, expected)| sign(&package, nonces, &key_shares[expected.id - 1]).unwrap())
+            .collect();
+        for (share, expected) in shares.iter().zip(expected_shares) {
+            assert_eq!(
+                hex::encode(share.to_vec(), false),
+                format!("{:04x}{}", expected.id, expected.share)
+            );
+            assert!(verify_share(&package, share, &public_package));
+        }
+
+        let signature = aggregate(&package, &shares, &public_package).unwrap();
+        assert_eq!(hex::encode(signature.to_vec(), false), expected_signature);
+        assert!(public_package
+            .group_public_key()
+            .verify_message(&signature, &message));
+    }
+
+    #[test]
+    fn test_split_secret() {
+        let (key_shares, public_package) = split_2_of_3();
+        assert_eq!(public_package.min_signers(), 2);
+        assert_eq!(
+            public_package.group_public_key().x_only(),
+            H256::from(GROUP_PUBLIC)
+        );
+
+        let expected = [
+            "ece4b4c6c32324f2fb608d47c37c651dd66b9df9a7cd6e4efa769d08e74aeaec",
+            "89b86634d3e07c42e897d120a45a709a6a292b061cdf83b4a3933f8939d4994b",
+            "268c17a2e49dd392d5cf14f985387c16fde6b81291f1991a4cafe2098c5e47aa",
+        ];
+        for (key_share, expected) in key_shares.iter().zip(expected) {
+            assert_eq!(scalar_to_bytes(&key_share.secret), H256::from(expected));
+            assert!(key_share.verify(&public_package));
+        }
+    }
+
+    #[test]
+    fn test_sign_even_nonce() {
+        test_sign_impl(
+            "71b7098e8150cde90f3ec00280815d3069f81c7cdb6d83bbe2b897b1afbe7cd6",
+            &[
+                ExpectedShare {
+                    id: 1,
+                    commitments: "02098016381b75d4e7959ffee79eb293886b5e941f00e85131190a605238856bd903c6c5ebc779891457e5ff618bd6ac920791beccb8ce58c3a234cd84154dc0aae6",
+                    share: "c0e8391f295b36ceff7422118031fb258c7656d54b1aa294d50e543b368852e3",
+                },
+                ExpectedShare {
+                    id: 3,
+                    commitments: "0265b6721680200eda6ae9bae40fd404b99dfc09b1253901e00ee25d80cda474b203a1277c377d4fed7613df4b10eb3d81ad6f96baebd9fc7062530abc7e46d49537",
+                    share: "37fbe839ce30022b86225dbd632abdd5325d5103138b8e1f3396b71f90407943",
+                },
+            ],
+            "10eb0b5c0e5f3c5e02b62ebf600dc6c2827b7341eb0533060310a41a1a05477ef8e42158f78b38fa85967fcee35cb8fabed3a7d85ea630b408a50b5ac6c8cc26",
+        );
+    }
+
+    #[test]
+    fn test_sign_odd_nonce() {
+        test_sign_impl(
+            "0101010101010101010101010101010101010101010101010101010101010101",
+            &[
+                ExpectedShare {
+                    id: 1,
+                    commitments: "02098016381b75d4e7959ffee79eb293886b5e941f00e85131190a605238856bd903c6c5ebc779891457e5ff618bd6ac920791beccb8ce58c3a234cd84154dc0aae6",
+                    share: "bff87cca47d493d5c7799cb1102111923bec012cda9a64e3d2fcc946e92a98a9",
+                },
+                ExpectedShare {
+                    id: 3,
+                    commitments: "0265b6721680200eda6ae9bae40fd404b99dfc09b1253901e00ee25d80cda474b203a1277c377d4fed7613df4b10eb3d81ad6f96baebd9fc7062530abc7e46d49537",
+                    share: "1822e140032bcbc6b2f679ce11011bf68fcd3a1e07d491483dcdb6953736027f",
+                },
+            ],
+            "1a1a35de64ca9a4fddfb18d38debea89181329522e1b019b50a54c3851ead958d81b5e0a4b005f9c7a70167f21222d88cbb93b4ae26ef62c10ca7fdc20609b28",
+        );
+    }
+
+    #[test]
+    fn test_trusted_dealer_keygen_sign() {
+        let secret = PrivateKey::try_from(SECRET).unwrap();
+        let (key_shares, public_package) = trusted_dealer_keygen(&secret, 3, 5).unwrap();
+        assert_eq!(public_package.group_public_key(), secret.public());
+        assert_eq!(key_shares.len(), 5);
+        assert!(trusted_dealer_keygen(&secret, 0, 5).is_err());
+        assert!(trusted_dealer_keygen(&secret, 6, 5).is_err());
+
+        let signers = [&key_shares[4], &key_shares[0], &key_shares[2]];
+        let (nonces, commitments): (Vec<_>, Vec<_>) = signers
+            .iter()
+            .map(|key_share| commit(key_share).unwrap())
+            .unzip();
+
+        let message = b"Hello, FROST!";
+        let package = SigningPackage::new(commitments, message).unwrap();
+        let shares: Vec<_> = nonces
+            .into_iter()
+            .zip(signers)
+            .map(|(nonces, key_share)| sign(&package, nonces, key_share).unwrap())
+            .collect();
+
+        let signature = aggregate(&package, &shares, &public_package).unwrap();
+        assert!(secret.public().verify_message(&signature, message));
+    }
+
+    #[test]
+    fn test_aggregate_not_enough_signers() {
+        let (key_shares, public_package) = split_2_of_3();
+        let (nonces, commitments) = commit(&key_shares[0]).unwrap();
+
+        let package = SigningPackage::new(vec![commitments], b"message").unwrap();
+        let share = sign(&package, nonces, &key_shares[0]).unwrap();
+        assert_eq!(
+            aggregate(&package, &[share], &public_package),
+            Err(KeyPairError::SignatureVerifyError)
+        );
+    }
+
+    #[test]
+    fn test_verify_share_invalid() {
+        let (key_shares, public_package) = split_2_of_3();
+        let (nonces_1, commitments_1) = commit(&key_shares[0]).unwrap();
+        let (nonces_2, commitments_2) = commit(&key_shares[1]).unwrap();
+
+        let package = SigningPackage::new(vec![commitments_1, commitments_2], b"message").unwrap();
+        let share_1 = sign(&package, nonces_1, &key_shares[0]).unwrap();
+        let share_2 = sign(&package, nonces_2, &key_shares[1]).unwrap();
+
+        let forged = SignatureShare {
+            identifier: share_2.identifier,
+            z: share_1.z,
+        };
+        assert!(!verify_share(&package, &forged, &public_package));
+        aggregate(&package, &[share_1.clone(), forged], &public_package).unwrap_err();
+        aggregate(&package, &[share_1.clone(), share_1], &public_package).unwrap_err();
+        aggregate(&package, &[share_2], &public_package).unwrap_err();
+    }
+
+    #[test]
+    fn test_sign_reused_commitments() {
+        let (key_shares, _) = split_2_of_3();
+        let (_, commitments_1) = commit(&key_shares[0]).unwrap();
+        let (nonces_2, commitments_2) = commit(&key_shares[1]).unwrap();
+
+        let package = SigningPackage::new(vec![commitments_1, commitments_2], b"message").unwrap();
+        // The nonces do not correspond to the first signer's commitments.
+        assert_eq!(
+            sign(&package, nonces_2, &key_shares[0]),
+            Err(KeyPairError::InvalidSecretKey)
+        );
+    }
+
+    #[test]
+    fn test_serialization() {
+        let (key_shares, public_package) = split_2_of_3();
+
+        let bytes = key_shares[0].to_zeroizing_vec();
+        let key_share = KeyShare::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(key_share.identifier(), Identifier::new(1).unwrap());
+        assert!(key_share.verify(&public_package));
+
+        let bytes = public_package.to_vec();
+        let decoded = PublicKeyPackage::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(decoded.to_vec(), bytes);
+
+        let (_, commitments) = commit(&key_share).unwrap();
+        let bytes = commitments.to_vec();
+        assert_eq!(
+            SigningCommitments::try_from(bytes.as_slice()).unwrap(),
+            commitments
+        );
+
+        SigningPackage::new(vec![commitments.clone(), commitments], b"message").unwrap_err();
+        Identifier::new(0).unwrap_err();
+    }
+}
@@ -0,0 +1,629 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! [BIP-340](https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki) Schnorr signatures
+//! over the `secp256k1` curve.
+
+use crate::{KeyPairError, KeyPairResult};
+use k256::elliptic_curve::bigint::U256;
+use k256::elliptic_curve::ops::Reduce;
+use k256::elliptic_curve::point::{AffineCoordinates, DecompressPoint};
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use k256::elliptic_curve::subtle::Choice;
+use k256::elliptic_curve::PrimeField;
+use k256::{AffinePoint, FieldBytes, Scalar};
+use sha2::{Digest, Sha256};
+use tw_hash::{H256, H264};
+
+mod adaptor;
+mod batch;
+pub mod frost;
+mod keypair;
+pub mod musig2;
+mod private;
+mod public;
+pub mod sign_to_contract;
+mod signature;
+mod taproot;
+
+pub use adaptor::AdaptorSignature;
+pub use batch::verify_batch;
+pub use keypair::KeyPair;
+pub use private::PrivateKey;
+pub use public::PublicKey;
+pub use sign_to_contract::SignToContractOpening;
+pub use signature::Signature;
+pub use taproot::{branch_hash, leaf_hash, TapTree, TaprootSpendPath, TAPSCRIPT_LEAF_VERSION};
+
+const AUX_TAG: &str = "BIP0340/aux";
+const NONCE_TAG: &str = "BIP0340/nonce";
+const CHALLENGE_TAG: &str = "BIP0340/challenge";
+const ADAPTOR_NONCE_TAG: &str = "SchnorrAdaptor/nonce";
+const TAP_TWEAK_TAG: &str = "TapTweak";
+
+/// Computes a tagged hash: `SHA256(SHA256(tag) || SHA256(tag) || chunks[0] || chunks[1] || ...)`.
+pub fn tagged_hash(tag: &str, chunks: &[&[u8]]) -> H256 {
+    let tag_hash = Sha256::digest(tag.as_bytes());
+
+    let mut hasher = Sha256::new();
+    hasher.update(tag_hash.as_slice());
+    hasher.update(tag_hash.as_slice());
+    for chunk in chunks {
+        hasher.update(chunk);
+    }
+
+    let mut hash = H256::default();
+    hash.copy_from_slice(hasher.finalize().as_slice());
+    hash
+}
+
+/// Computes the BIP-340 challenge `e = int(hash_BIP0340/challenge(r || P || m)) mod n`.
+pub(crate) fn challenge(r: &H256, public: &H256, message: &[u8]) -> Scalar {
+    let hash = tagged_hash(CHALLENGE_TAG, &[r.as_slice(), public.as_slice(), message]);
+    scalar_from_hash(&hash)
+}
+
+/// Computes the BIP-341 taproot tweak `t = int(hash_TapTweak(P || merkle_root))`.
+/// Returns an error if `t` is not less than the curve order.
+pub(crate) fn taproot_tweak(
+    internal: &PublicKey,
+    merkle_root: Option<&H256>,
+) -> KeyPairResult<Scalar> {
+    let internal = internal.x_only();
+    let hash = match merkle_root {
+        Some(merkle_root) => tagged_hash(
+            TAP_TWEAK_TAG,
+            &[internal.as_slice(), merkle_root.as_slice()],
+        ),
+        None => tagged_hash(TAP_TWEAK_TAG, &[internal.as_slice()]),
+    };
+    scalar_from_canonical_bytes(&hash).ok_or(KeyPairError::InvalidTweak)
+}
+
+/// Interprets the given 32 bytes as a big-endian integer reduced modulo the curve order.
+pub(crate) fn scalar_from_hash(hash: &H256) -> Scalar {
+    <Scalar as Reduce<U256>>::reduce_bytes(&FieldBytes::clone_from_slice(hash.as_slice()))
+}
+
+/// Interprets the given 32 bytes as a big-endian integer.
+/// Returns `None` if the integer is not less than the curve order.
+pub(crate) fn scalar_from_canonical_bytes(bytes: &H256) -> Option<Scalar> {
+    Scalar::from_repr(FieldBytes::clone_from_slice(bytes.as_slice())).into()
+}
+
+/// Returns the 32 byte big-endian representation of the given `scalar`.
+pub(crate) fn scalar_to_bytes(scalar: &Scalar) -> H256 {
+    let mut bytes = H256::default();
+    bytes.copy_from_slice(scalar.to_bytes().as_slice());
+    bytes
+}
+
+/// Returns the X coordinate of the given `point`.
+pub(crate) fn x_only(point: &AffinePoint) -> H256 {
+    let mut bytes = H256::default();
+    bytes.copy_from_slice(point.x().as_slice());
+    bytes
+}
+
+pub(crate) fn has_even_y(point: &AffinePoint) -> bool {
+    !bool::from(point.y_is_odd())
+}
+
+/// Returns the compressed representation of the given `point` (33 bytes).
+pub(crate) fn compressed_point(point: &AffinePoint) -> H264 {
+    let mut bytes = H264::default();
+    bytes.copy_from_slice(point.to_encoded_point(true).as_bytes());
+    bytes
+}
+
+/// Parses a compressed or uncompressed point that is not at infinity.
+pub(crate) fn point_from_slice(bytes: &[u8]) -> KeyPairResult<AffinePoint> {
+    let public =
+        k256::PublicKey::from_sec1_bytes(bytes).map_err(|_| KeyPairError::InvalidPublicKey)?;
+    Ok(*public.as_affine())
+}
+
+/// Returns the point with the given X coordinate and an even Y coordinate.
+/// Returns `None` if there is no such a point on the curve.
+pub(crate) fn lift_x(x: &H256) -> Option<AffinePoint> {
+    let x = FieldBytes::clone_from_slice(x.as_slice());
+    AffinePoint::decompress(&x, Choice::from(0)).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecdsa::secp256k1::{self, WifInfo, WifNetwork};
+    use crate::traits::{KeyPairTrait, SigningKeyTrait, VerifyingKeyTrait};
+    use tw_encoding::hex;
+    use tw_hash::{H512, H520};
+    use tw_misc::traits::{ToBytesVec, ToBytesZeroizing};
+
+    const SECRET: &str = "afeefca74d9a325cf1d6b6911d61a65c32afa8e02bd5e78e2e4ac2910bab45f5";
+    const HASH_TO_SIGN: &str = "71b7098e8150cde90f3ec00280815d3069f81c7cdb6d83bbe2b897b1afbe7cd6";
+
+    /// https://github.com/bitcoin/bips/blob/master/bip-0340/test-vectors.csv
+    #[test]
+    fn test_bip340_sign_vectors() {
+        let private = PrivateKey::try_from(
+            "0000000000000000000000000000000000000000000000000000000000000003",
+        )
+        .unwrap()
+        .no_aux_rand();
+        assert_eq!(
+            private.public().x_only(),
+            H256::from("f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9")
+        );
+        let actual = private.sign(H256::default()).unwrap();
+        let expected = H512::from("e907831f80848d1069a5371b402410364bdf1c5f8307b0084c55f1ce2dca821525f66a4a85ea8b71e482a74f382d2ce5ebeee8fdb2172f477df4900d310536c0");
+        assert_eq!(actual.to_bytes(), expected);
+
+        let private = PrivateKey::try_from(
+            "b7e151628aed2a6abf7158809cf4f3c762e7160f38b4da56a784d9045190cfef",
+        )
+        .unwrap();
+        let message =
+            H256::from("243f6a8885a308d313198a2e03707344a4093822299f31d0082efa98ec4e6c89");
+        let aux = H256::from("0000000000000000000000000000000000000000000000000000000000000001");
+        let actual = private.sign_with_aux(message.as_slice(), &aux).unwrap();
+        let expected = H512::from("6896bd60eeae296db48a229ff71dfe071bde413e6d43f917dc8dcf8c78de33418906d11ac976abccb20b091292bff4ea897efcb639ea871cfa95f6de339e4b0a");
+        assert_eq!(actual.to_bytes(), expected);
+        assert!(private.public().verify(actual, message));
+    }
+
+    #[test]
+    fn test_private_key_sign_with_aux_rand() {
+        // Source: https://github.com/bitcoin/bips/blob/master/bip-0340/test-vectors.csv
+        let private = PrivateKey::try_from(
+            "c90fdaa22168c234c4c6628b80dc1cd129024e088a67cc74020bbea63b14e5c9",
+        )
+        .unwrap();
+        let message =
+            H256::from("7e2d58d8b3bcdf1abadec7829054f90dda9805aab56c77333024b9d0a508b75c");
+        let aux = H256::from("c87aa53824b4d7ae2eb035a2b5bbbccc080e76cdc6d1692c4b0b62d798e6d906");
+
+        let actual = private.sign_with_aux_rand(message, aux).unwrap();
+        let expected = H512::from("5831aaeed7b44bb74e5eab94ba9d4294c49bcf2a60728d8b4c200f50dd313c1bab745879a5ad954a72c45a91c3a51d3c7adea98d82f8481e0e1e03674a6f3fb7");
+        assert_eq!(actual.to_bytes(), expected);
+        assert!(private.public().verify(actual, message));
+    }
+
+    #[test]
+    fn test_private_key_wif() {
+        let wif = "KwdMAjGmerYanjeui5SHS7JkmpZvVipYvB2LJGU1ZxJwYvP98617";
+        let (private, info) = PrivateKey::from_wif(wif).unwrap();
+        assert_eq!(
+            private.to_zeroizing_vec().as_slice(),
+            H256::from("0c28fca386c7a227600b2fe50b7cae11ec86d3bf1fbe471be89827e19d72aa1d")
+                .as_slice()
+        );
+        assert_eq!(
+            info,
+            WifInfo {
+                network: WifNetwork::Mainnet,
+                compressed: true,
+            }
+        );
+        assert_eq!(private.to_wif(info).as_str(), wif);
+    }
+
+    #[test]
+    fn test_key_pair_sign_verify() {
+        let keypair = KeyPair::try_from(SECRET).unwrap();
+        assert_eq!(
+            keypair.private().to_zeroizing_vec().as_slice(),
+            H256::from(SECRET).as_slice()
+        );
+        assert_eq!(
+            keypair.public().to_vec(),
+            H256::from("99c6f51ad6f98c9c583f8e92bb7758ab2ca9a04110c0a1126ec43e5453d196c1")
+                .into_vec()
+        );
+
+        let hash_to_sign = H256::from(HASH_TO_SIGN);
+        let signature = keypair.sign(hash_to_sign).unwrap();
+        assert!(keypair.verify(signature, hash_to_sign));
+    }
+
+    #[test]
+    fn test_private_key_sign_no_aux_rand() {
+        let private = PrivateKey::try_from(SECRET).unwrap().no_aux_rand();
+        let hash_to_sign = H256::from(HASH_TO_SIGN);
+
+        let actual = private.sign(hash_to_sign).unwrap();
+        let expected = H512::from("c11b9527ed9fe7cfc5ddce7e97283f7aaceba2d99cdfbb510b7e586428e93b28c867441346000c3e8b85af152ca4504e8bb924a418144ca69a4abd3e1eac5f62");
+        assert_eq!(actual.to_bytes(), expected);
+        assert!(private.public().verify(actual, hash_to_sign));
+    }
+
+    #[test]
+    fn test_private_key_tweak() {
+        let private = PrivateKey::try_from(SECRET).unwrap();
+        let tweaked = private.tweak(None).unwrap().no_aux_rand();
+        assert_eq!(
+            tweaked.public().x_only(),
+            H256::from("a900f5afa141ad06ac0face4c82b68c52087eb9cc0ada2435056d92b0f0c2364")
+        );
+
+        let hash_to_sign = H256::from(HASH_TO_SIGN);
+        let actual = tweaked.sign(hash_to_sign).unwrap();
+        let expected = H512::from("a2b3319ba2bfad113660bd2b6723e703ca7600dd563f4608408cea41485f38ff9cef739aad31f32efead59eb8b069ba5f0713bcae0133abbc170f705a160c224");
+        assert_eq!(actual.to_bytes(), expected);
+        assert!(tweaked.public().verify(actual, hash_to_sign));
+    }
+
+    #[test]
+    fn test_public_key_tweak() {
+        // Source: https://github.com/bitcoin/bips/blob/master/bip-0341/wallet-test-vectors.json
+        let public =
+            PublicKey::try_from("d6889cb081036e0faefa3a35157ad71086b123b2b144b649798b494c300a961d")
+                .unwrap();
+        assert_eq!(
+            public.tweak(None).unwrap().x_only(),
+            H256::from("53a1f6e454df1aa2776a2814a721372d6258050de330b3c6d10ee8f4e0dda343")
+        );
+
+        let public =
+            PublicKey::try_from("187791b6f712a8ea41c8ecdd0ee77fab3e85263b37e1ec18a3651926b3a6cf27")
+                .unwrap();
+        let merkle_root =
+            H256::from("5b75adecf53548f3ec6ad7d78383bf84cc57b55a3127c72b9a2481752dd88b21");
+        assert_eq!(
+            public.tweak(Some(merkle_root)).unwrap().x_only(),
+            H256::from("147c9c57132f6e7ecddba9800bb0c4449251c92a1e60371ee77557b6620f3ea3")
+        );
+    }
+
+    #[test]
+    fn test_public_key_tweak_matches_private() {
+        let private = PrivateKey::try_from(SECRET).unwrap();
+        let merkle_root = Some(H256::from([1; 32]));
+
+        for merkle_root in [None, merkle_root] {
+            let expected = private.tweak(merkle_root).unwrap().public();
+            let actual = private.public().tweak(merkle_root).unwrap();
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_public_key_formats() {
+        let secp256k1_public = secp256k1::PrivateKey::try_from(SECRET).unwrap().public();
+        let public = PublicKey::from(&secp256k1_public);
+        assert_eq!(public, PrivateKey::try_from(SECRET).unwrap().public());
+
+        // The `secp256k1` public key has an odd Y coordinate, so the x-only key is negated.
+        assert_eq!(
+            public.compressed(),
+            H264::from("0299c6f51ad6f98c9c583f8e92bb7758ab2ca9a04110c0a1126ec43e5453d196c1")
+        );
+        assert_eq!(
+            public.uncompressed(),
+            H520::from("0499c6f51ad6f98c9c583f8e92bb7758ab2ca9a04110c0a1126ec43e5453d196c1994b765b483b6e18977191415c58e03c5e5b729f0672a317b36c49a0bdc01d9e")
+        );
+    }
+
+    #[test]
+    fn test_public_key_point_arithmetic() {
+        let public = PrivateKey::try_from(SECRET).unwrap().public();
+        let another =
+            PublicKey::try_from("a18a98316b5f52596e75bfa5ca9fa9912edd0c989b86b73d41bb64c9c6adb992")
+                .unwrap();
+        let tweak = H256::from([1; 32]);
+
+        assert_eq!(
+            public.tweak_add(&tweak).unwrap().x_only(),
+            H256::from("ad12bc1d2ec95115dfb0fb944569175a3b5fe9118171081295b0f5a625d122cb")
+        );
+        assert_eq!(
+            public.tweak_mul(&tweak).unwrap().x_only(),
+            H256::from("3d4238fc7ff59ec617fb63da46bd9e67c693b5b4d840d38940ee6206a6c696ce")
+        );
+        assert_eq!(
+            PublicKey::combine(&[public.clone(), another])
+                .unwrap()
+                .x_only(),
+            H256::from("8804de76f5cba616b51dc43415d23ad8ab1c6866f93ce5ac6524b6bbd6350a1a")
+        );
+
+        // The tweak is not less than the curve order.
+        assert_eq!(
+            public.tweak_add(&H256::from([0xff; 32])).unwrap_err(),
+            KeyPairError::InvalidTweak
+        );
+        // `0 * P` is at infinity.
+        assert_eq!(
+            public.tweak_mul(&H256::default()).unwrap_err(),
+            KeyPairError::InvalidPublicKey
+        );
+        PublicKey::combine(&[]).unwrap_err();
+    }
+
+    #[test]
+    fn test_public_key_verify_taproot() {
+        let private = PrivateKey::try_from(SECRET).unwrap();
+        let internal_key = private.public();
+        let hash_to_sign = H256::from(HASH_TO_SIGN);
+        let merkle_root = Some(H256::from([1; 32]));
+
+        for merkle_root in [None, merkle_root] {
+            let key_path = TaprootSpendPath::KeyPath { merkle_root };
+            let signature = private.sign_taproot(hash_to_sign, &key_path).unwrap();
+            assert!(internal_key.verify_taproot(&signature, hash_to_sign, merkle_root));
+        }
+
+        // The output key is tweaked with another merkle root.
+        let key_path = TaprootSpendPath::KeyPath { merkle_root: None };
+        let signature = private.sign_taproot(hash_to_sign, &key_path).unwrap();
+        assert!(!internal_key.verify_taproot(&signature, hash_to_sign, merkle_root));
+
+        // The untweaked signature doesn't match the output key.
+        let signature = private
+            .sign_taproot(hash_to_sign, &TaprootSpendPath::ScriptPath)
+            .unwrap();
+        assert!(!internal_key.verify_taproot(&signature, hash_to_sign, None));
+    }
+
+    #[test]
+    fn test_private_key_sign_taproot() {
+        let private = PrivateKey::try_from(SECRET).unwrap().no_aux_rand();
+        let hash_to_sign = H256::from(HASH_TO_SIGN);
+
+        let key_path = TaprootSpendPath::KeyPath { merkle_root: None };
+        let actual = private.sign_taproot(hash_to_sign, &key_path).unwrap();
+        let tweaked_public = private.tweak(None).unwrap().public();
+        assert!(tweaked_public.verify(actual, hash_to_sign));
+
+        let actual = private
+            .sign_taproot(hash_to_sign, &TaprootSpendPath::ScriptPath)
+            .unwrap();
+        let expected = H512::from("c11b9527ed9fe7cfc5ddce7e97283f7aaceba2d99cdfbb510b7e586428e93b28c867441346000c3e8b85af152ca4504e8bb924a418144ca69a4abd3e1eac5f62");
+        assert_eq!(actual.to_bytes(), expected);
+    }
+
+    #[test]
+    fn test_verify_invalid() {
+        let private = PrivateKey::try_from(SECRET).unwrap();
+        let signature = private.sign(H256::from(HASH_TO_SIGN)).unwrap();
+
+        let another_hash =
+            H256::from("0000000000000000000000000000000000000000000000000000000000000001");
+        assert!(!private.public().verify(signature, another_hash));
+    }
+
+    #[test]
+    fn test_public_key_from_invalid() {
+        // There is no point with such X coordinate on the curve.
+        PublicKey::try_from("eefdea4cdb677750a420fee807eacf21eb9898ae79b9768766e4faa04a2d4a34")
+            .unwrap_err();
+        PublicKey::try_from("0399c6f51ad6f98c9c583f8e92bb7758ab2ca9a04110c0a1126ec43e5453d196c1")
+            .unwrap_err();
+    }
+
+    #[test]
+    fn test_signature_from_invalid() {
+        // `s` is equal to the curve order.
+        let sig_bytes = H512::from("6896bd60eeae296db48a229ff71dfe071bde413e6d43f917dc8dcf8c78de3341fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141");
+        Signature::try_from(sig_bytes.as_slice()).unwrap_err();
+    }
+
+    #[test]
+    fn test_private_key_sign_batch() {
+        let private = PrivateKey::try_from(SECRET).unwrap().no_aux_rand();
+        let messages = vec![H256::from(HASH_TO_SIGN), H256::from([1; 32])];
+
+        let actual = private.sign_batch(&messages).unwrap();
+        assert_eq!(actual.len(), messages.len());
+        for (signature, message) in actual.into_iter().zip(messages) {
+            let expected = private.sign(message).unwrap();
+            assert_eq!(signature.to_bytes(), expected.to_bytes());
+            assert!(private.public().verify(signature, message));
+        }
+    }
+
+    /// Source: https://github.com/bitcoin/bips/blob/master/bip-0340/test-vectors.csv (indexes 15-18).
+    #[test]
+    fn test_sign_verify_arbitrary_length_message() {
+        let private = PrivateKey::try_from(
+            "0340034003400340034003400340034003400340034003400340034003400340",
+        )
+        .unwrap();
+        let public = private.public();
+        assert_eq!(
+            public.x_only(),
+            H256::from("778caa53b4393ac467774d09497a87224bf9fab6f6e68b23086497324d6fd117")
+        );
+
+        let repeated = [0x99; 100];
+        let tests: [(&[u8], &str); 4] = [
+            (&[], "71535db165ecd9fbbc046e5ffaea61186bb6ad436732fccc25291a55895464cf6069ce26bf03466228f19a3a62db8a649f2d560fac652827d1af0574e427ab63"),
+            (&[0x11], "08a20a0afef64124649232e0693c583ab1b9934ae63b4c3511f3ae1134c6a303ea3173bfea6683bd101fa5aa5dbc1996fe7cacfc5a577d33ec14564cec2bacbf"),
+            (&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17], "5130f39a4059b43bc7cac09a19ece52b5d8699d1a71e3c52da9afdb6b50ac370c4a482b77bf960f8681540e25b6771ece1e5a37fd80e5a51897c5566a97ea5a5"),
+            (&repeated, "403b12b0d8555a344175ea7ec746566303321e5dbfa8be6f091635163eca79a8585ed3e3170807e7c03b720fc54c7b23897fcba0e9d0b4a06894cfd249f22367"),
+        ];
+
+        for (message, expected) in tests {
+            let signature = private
+                .sign_message_with_aux_rand(message, H256::default())
+                .unwrap();
+            assert_eq!(signature.to_bytes(), H512::from(expected));
+            assert!(public.verify_message(&signature, message));
+            assert!(!public.verify_message(&signature, b"another message"));
+
+            let signature = private.sign_message(message).unwrap();
+            assert!(public.verify_message(&signature, message));
+        }
+    }
+
+    #[test]
+    fn test_sign_verify_tagged() {
+        let private = PrivateKey::try_from(SECRET).unwrap().no_aux_rand();
+        let public = private.public();
+
+        let signature = private.sign_tagged("wallet/message", b"Hello").unwrap();
+        let expected = H512::from("d705e7e724b67d3413d2956cbaf23203471b8ffcd3402083bf99f23df1a623b92beebc0063ac88ccbf92d23ba51da2c06164faff44b0e4eb6a82de940421716e");
+        assert_eq!(signature.to_bytes(), expected);
+
+        assert!(public.verify_tagged(&signature, "wallet/message", b"Hello"));
+        assert!(!public.verify_tagged(&signature, "wallet/another", b"Hello"));
+        assert!(!public.verify_message(&signature, b"Hello"));
+
+        let hash = tagged_hash("wallet/message", &[b"Hello".as_slice()]);
+        assert!(public.verify(signature, hash));
+    }
+
+    /// Source: https://github.com/bitcoin/bips/blob/master/bip-0341/wallet-test-vectors.json
+    #[test]
+    fn test_tap_tree_single_leaf() {
+        let script =
+            hex::decode("20d85a959b0290bf19bb89ed43c916be835475d013da4b362117393e25a48229b8ac")
+                .unwrap();
+        let tree = TapTree::leaf(script);
+        let merkle_root = tree.merkle_root();
+        assert_eq!(
+            merkle_root,
+            H256::from("5b75adecf53548f3ec6ad7d78383bf84cc57b55a3127c72b9a2481752dd88b21")
+        );
+
+        let internal_key =
+            PublicKey::try_from("187791b6f712a8ea41c8ecdd0ee77fab3e85263b37e1ec18a3651926b3a6cf27")
+                .unwrap();
+        let output_key = internal_key.tweak(Some(merkle_root)).unwrap();
+        assert_eq!(
+            output_key.x_only(),
+            H256::from("147c9c57132f6e7ecddba9800bb0c4449251c92a1e60371ee77557b6620f3ea3")
+        );
+        assert_eq!(tree.merkle_path(&merkle_root), Some(Vec::new()));
+    }
+
+    #[test]
+    fn test_tap_tree_branch() {
+        let first = TapTree::leaf(
+            hex::decode("20b617298552a72ade070667e86ca63b8f5789a9fe8731ef91202a91c9f3459007ac")
+                .unwrap(),
+        );
+        let second = TapTree::Leaf {
+            leaf_version: 0xfa,
+            script: hex::decode("06424950333431").unwrap(),
+        };
+        let first_hash =
+            H256::from("c525714a7f49c28aedbbba78c005931a81c234b2f6c99a73e4d06082adc8bf2b");
+        let second_hash =
+            H256::from("f224a923cd0021ab202ab139cc56802ddb92dcfc172b9212261a539df79a112a");
+        assert_eq!(first.node_hash(), first_hash);
+        assert_eq!(second.node_hash(), second_hash);
+
+        let expected =
+            H256::from("6d617333d04089655dc10c5891e09d385e7480454ac783afa222ec9ae1485fe8");
+        let tree = TapTree::branch(first.clone(), second.clone());
+        assert_eq!(tree.merkle_root(), expected);
+        // The children are sorted on hashing.
+        assert_eq!(TapTree::branch(second, first).merkle_root(), expected);
+        assert_eq!(branch_hash(&second_hash, &first_hash), expected);
+
+        assert_eq!(tree.merkle_path(&first_hash), Some(vec![second_hash]));
+        assert_eq!(tree.merkle_path(&second_hash), Some(vec![first_hash]));
+        assert_eq!(tree.merkle_path(&H256::default()), None);
+    }
+
+    #[test]
+    fn test_tap_tree_huffman() {
+        let a = TapTree::leaf(vec![0x51]);
+        let b = TapTree::leaf(vec![0x52]);
+        let c = TapTree::leaf(vec![0x53]);
+
+        let actual =
+            TapTree::with_huffman(vec![(1, a.clone()), (2, c.clone()), (1, b.clone())]).unwrap();
+        let expected = TapTree::branch(c.clone(), TapTree::branch(a.clone(), b));
+        assert_eq!(actual.merkle_root(), expected.merkle_root());
+
+        let single = TapTree::with_huffman(vec![(5, a.clone())]).unwrap();
+        assert_eq!(single, a);
+
+        assert_eq!(TapTree::with_huffman(Vec::new()), None);
+    }
+
+    #[test]
+    fn test_tap_leaf_hash_long_script() {
+        // The script length is encoded as a 3 byte `CompactSize`.
+        let script = vec![0x51; 300];
+        assert_eq!(
+            leaf_hash(TAPSCRIPT_LEAF_VERSION, &script),
+            H256::from("14dbbf1ba8fbe4da51312d7f13eaa4178026942da2ce20cb4c9c8f0c1f538dfd")
+        );
+    }
+
+    #[test]
+    fn test_sign_to_contract() {
+        let private = PrivateKey::try_from(SECRET).unwrap().no_aux_rand();
+        let public = private.public();
+        let hash_to_sign = H256::from(HASH_TO_SIGN);
+
+        let (signature, opening) = private
+            .sign_to_contract(hash_to_sign, b"timestamp")
+            .unwrap();
+        let expected = H512::from("751b3ff219b35b25c6734e9b9e55e59c81387fda3845dbfd7a997dfb692b16bb1bce109d2f124feb8fb5bd2c137acfb78f656333719ab4d052103a76d873209f");
+        assert_eq!(signature.to_bytes(), expected);
+        assert_eq!(
+            opening.to_bytes(),
+            H264::from("03c11b9527ed9fe7cfc5ddce7e97283f7aaceba2d99cdfbb510b7e586428e93b28")
+        );
+
+        assert!(public.verify(signature.clone(), hash_to_sign));
+        assert!(opening.verify_commitment(&signature, b"timestamp"));
+        assert!(!opening.verify_commitment(&signature, b"another"));
+
+        let opening = SignToContractOpening::try_from(opening.to_vec().as_slice()).unwrap();
+        assert!(opening.verify_commitment(&signature, b"timestamp"));
+
+        // The commitment differs from a regular signature.
+        let regular = private.sign(hash_to_sign).unwrap();
+        assert!(!opening.verify_commitment(&regular, b"timestamp"));
+    }
+
+    #[test]
+    fn test_anti_exfil() {
+        let private = PrivateKey::try_from(SECRET).unwrap();
+        let public = private.public();
+        let hash_to_sign = H256::from(HASH_TO_SIGN);
+        let host_data = H256::from([7; 32]);
+
+        // 1. The host sends the commitment to its random data.
+        let host_commitment = sign_to_contract::anti_exfil_host_commitment(&host_data);
+        assert_eq!(
+            host_commitment,
+            H256::from("106f94cac9e61f52cc04ba5a27d4b47cf5213ac4855e153f2fae06176cf163c7")
+        );
+
+        // 2. The signer commits to its nonce.
+        let signer_commitment = private
+            .anti_exfil_signer_commit(hash_to_sign, &host_commitment)
+            .unwrap();
+        assert_eq!(
+            signer_commitment.to_bytes(),
+            H264::from("03f53a5805fbe0d98faeb79303fc3035918eba68fa064aab88898f3e04a6ee8bec")
+        );
+
+        // 3. The host reveals its data, and the signer signs the message.
+        let signature = private.anti_exfil_sign(hash_to_sign, &host_data).unwrap();
+        let expected = H512::from("cbcf0c99df3ddb23d08469c778c6d59a0bbb0ae2eafb1eaeaf77d33441fe69ba9bb1ab8d72bf608d2e37683ece47a58c03be286b215b5e3a56a5da70a08a66d1");
+        assert_eq!(signature.to_bytes(), expected);
+
+        // 4. The host verifies the signature.
+        assert!(public.verify_anti_exfil(&signature, hash_to_sign, &host_data, &signer_commitment));
+        let another_data = H256::from([8; 32]);
+        assert!(!public.verify_anti_exfil(
+            &signature,
+            hash_to_sign,
+            &another_data,
+            &signer_commitment
+        ));
+    }
+
+    #[test]
+    fn test_sign_to_contract_opening_from_invalid() {
+        SignToContractOpening::try_from([2; 32].as_slice()).unwrap_err();
+        SignToContractOpening::try_from([5; 33].as_slice()).unwrap_err();
+    }
+}
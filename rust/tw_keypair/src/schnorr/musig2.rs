@@ -0,0 +1,778 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! MuSig2 multi-signatures as specified in [BIP-327](https://github.com/bitcoin/bips/blob/master/bip-0327.mediawiki).
+//!
+//! The signing session consists of two rounds:
+//! 1. Every signer generates a [`SecretNonce`] and [`PublicNonce`] pair via [`nonce_gen`],
+//!    and sends the [`PublicNonce`] to the other signers.
+//! 2. Once all public nonces are collected and aggregated via [`nonce_agg`], every signer creates
+//!    a [`PartialSignature`] via [`Session::partial_sign`]. The partial signatures are combined
+//!    into an ordinary BIP-340 [`Signature`] via [`Session::aggregate`].
+//!
+//! The nonces can be generated before the message is final. In this case, the signers may
+//! exchange the [`PublicNonce::commitment`]s first and reveal the public nonces only after
+//! all the commitments are received, so no signer can choose its nonce depending on the others'.
+//! For two signers, see [`Session::with_counterparty`].
+
+use crate::ecdsa::secp256k1;
+use crate::schnorr::public::PublicKey;
+use crate::schnorr::signature::Signature;
+use crate::schnorr::{
+    challenge, compressed_point, has_even_y, point_from_slice, scalar_from_canonical_bytes,
+    scalar_from_hash, scalar_to_bytes, tagged_hash, taproot_tweak, x_only,
+};
+use crate::{KeyPairError, KeyPairResult};
+use k256::{AffinePoint, ProjectivePoint, Scalar};
+use std::ops::Range;
+use tw_hash::{H256, H264};
+use tw_misc::traits::ToBytesVec;
+use zeroize::{ZeroizeOnDrop, Zeroizing};
+
+const KEY_AGG_LIST_TAG: &str = "KeyAgg list";
+const KEY_AGG_COEFFICIENT_TAG: &str = "KeyAgg coefficient";
+const AUX_TAG: &str = "MuSig/aux";
+const NONCE_TAG: &str = "MuSig/nonce";
+const NONCE_COEFFICIENT_TAG: &str = "MuSig/noncecoef";
+const NONCE_COMMITMENT_TAG: &str = "MuSig/noncecommit";
+
+/// The number of bytes in a serialized compressed point.
+const POINT_LEN: usize = H264::LEN;
+const FIRST_POINT_RANGE: Range<usize> = 0..POINT_LEN;
+const SECOND_POINT_RANGE: Range<usize> = POINT_LEN..(POINT_LEN * 2);
+
+/// Sorts the given public keys in lexicographical order of their compressed representations.
+pub fn sort_public_keys(public_keys: &mut [secp256k1::PublicKey]) {
+    public_keys.sort_by_key(secp256k1::PublicKey::compressed);
+}
+
+/// Represents the result of the `KeyAgg` algorithm with the tweaks applied.
+#[derive(Clone, Debug)]
+pub struct KeyAggContext {
+    /// Compressed public keys of the signers in the aggregation order.
+    public_keys: Vec<H264>,
+    /// `hash_KeyAgg list(pk1 || pk2 || ... || pku)`.
+    public_keys_hash: H256,
+    /// The first public key that differs from the first one.
+    second_key: Option<H264>,
+    /// The aggregated, and possibly tweaked, point `Q`.
+    point: ProjectivePoint,
+    /// The accumulated sign of the tweaks (`gacc`).
+    gacc: Scalar,
+    /// The accumulated tweak (`tacc`).
+    tacc: Scalar,
+}
+
+impl KeyAggContext {
+    /// Aggregates the given public keys. The order of the keys matters.
+    /// Consider using [`sort_public_keys`] to make the aggregated key independent of the order.
+    pub fn new(public_keys: &[secp256k1::PublicKey]) -> KeyPairResult<KeyAggContext> {
+        let compressed: Vec<H264> = public_keys
+            .iter()
+            .map(secp256k1::PublicKey::compressed)
+            .collect();
+        let first = *compressed.first().ok_or(KeyPairError::InvalidPublicKey)?;
+
+        let chunks: Vec<&[u8]> = compressed.iter().map(H264::as_slice).collect();
+        let public_keys_hash = tagged_hash(KEY_AGG_LIST_TAG, &chunks);
+        let second_key = compressed.iter().find(|public| **public != first).copied();
+
+        let mut ctx = KeyAggContext {
+            public_keys: compressed,
+            public_keys_hash,
+            second_key,
+            point: ProjectivePoint::IDENTITY,
+            gacc: Scalar::ONE,
+            tacc: Scalar::ZERO,
+        };
+
+        let mut point = ProjectivePoint::IDENTITY;
+        for (public, compressed) in public_keys.iter().zip(ctx.public_keys.iter()) {
+            let public_point = ProjectivePoint::from(*public.public.as_affine());
+            point += public_point * ctx.coefficient(compressed);
+        }
+
+        if point == ProjectivePoint::IDENTITY {
+            return Err(KeyPairError::InvalidPublicKey);
+        }
+        ctx.point = point;
+        Ok(ctx)
+    }
+
+    /// Returns the aggregated x-only public key.
+    /// The BIP-340 signature produced by [`Session::aggregate`] is valid for this public key.
+    pub fn aggregated_public_key(&self) -> PublicKey {
+        PublicKey::with_point(&self.point.to_affine())
+    }
+
+    /// Applies the given `tweak` to the aggregated public key.
+    /// Set `is_xonly` to apply an x-only tweak (e.g. taproot), or a plain tweak (e.g. BIP32) otherwise.
+    pub fn apply_tweak(&mut self, tweak: H256, is_xonly: bool) -> KeyPairResult<()> {
+        let tweak = scalar_from_canonical_bytes(&tweak).ok_or(KeyPairError::InvalidTweak)?;
+
+        let g = if is_xonly {
+            self.parity_factor()
+        } else {
+            Scalar::ONE
+        };
+
+        let point = self.point * g + ProjectivePoint::GENERATOR * tweak;
+        if point == ProjectivePoint::IDENTITY {
+            return Err(KeyPairError::SigningError);
+        }
+
+        self.point = point;
+        self.gacc = g * self.gacc;
+        self.tacc = tweak + g * self.tacc;
+        Ok(())
+    }
+
+    /// Applies the BIP-341 taproot tweak to the aggregated public key,
+    /// so the signature is valid for the taproot output key.
+    /// `merkle_root` should be `None` if the output has no script path.
+    pub fn apply_taproot_tweak(&mut self, merkle_root: Option<H256>) -> KeyPairResult<()> {
+        let tweak = taproot_tweak(&self.aggregated_public_key(), merkle_root.as_ref())?;
+        self.apply_tweak(scalar_to_bytes(&tweak), true)
+    }
+
+    /// Returns the `KeyAgg` coefficient of the given public key.
+    fn coefficient(&self, public: &H264) -> Scalar {
+        if self.second_key.as_ref() == Some(public) {
+            return Scalar::ONE;
+        }
+        let hash = tagged_hash(
+            KEY_AGG_COEFFICIENT_TAG,
+            &[self.public_keys_hash.as_slice(), public.as_slice()],
+        );
+        scalar_from_hash(&hash)
+    }
+
+    /// Returns `1` if the aggregated point has an even Y coordinate, `-1` otherwise.
+    fn parity_factor(&self) -> Scalar {
+        if has_even_y(&self.point.to_affine()) {
+            Scalar::ONE
+        } else {
+            -Scalar::ONE
+        }
+    }
+}
+
+/// Represents a secret nonce of a signer.
+///
+/// # Warning
+///
+/// The nonce must never be reused, so [`Session::partial_sign`] takes it by value.
+#[derive(ZeroizeOnDrop)]
+pub struct SecretNonce {
+    k1: Scalar,
+    k2: Scalar,
+    /// The compressed public key of the signer.
+    public_key: H264,
+}
+
+/// Represents a public nonce of a signer that has to be sent to the other signers.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PublicNonce {
+    r1: AffinePoint,
+    r2: AffinePoint,
+}
+
+/// cbindgen:ignore
+impl PublicNonce {
+    /// The number of bytes in a serialized public nonce.
+    pub const LEN: usize = POINT_LEN * 2;
+
+    /// Returns the commitment to the public nonce `hash_MuSig/noncecommit(pubnonce)`
+    /// that can be sent to the other signers before the public nonce itself.
+    pub fn commitment(&self) -> H256 {
+        tagged_hash(NONCE_COMMITMENT_TAG, &[self.to_vec().as_slice()])
+    }
+
+    /// Checks if the public nonce matches the given `commitment`.
+    pub fn verify_commitment(&self, commitment: &H256) -> bool {
+        self.commitment() == *commitment
+    }
+}
+
+impl ToBytesVec for PublicNonce {
+    fn to_vec(&self) -> Vec<u8> {
+        let mut res = Vec::with_capacity(Self::LEN);
+        res.extend_from_slice(compressed_point(&self.r1).as_slice());
+        res.extend_from_slice(compressed_point(&self.r2).as_slice());
+        res
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for PublicNonce {
+    type Error = KeyPairError;
+
+    fn try_from(bytes: &'a [u8]) -> Result<Self, Self::Error> {
+        if bytes.len() != Self::LEN {
+            return Err(KeyPairError::InvalidPublicKey);
+        }
+        Ok(PublicNonce {
+            r1: point_from_slice(&bytes[FIRST_POINT_RANGE])?,
+            r2: point_from_slice(&bytes[SECOND_POINT_RANGE])?,
+        })
+    }
+}
+
+/// Represents the sum of the signers' public nonces.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AggregatedNonce {
+    /// The point may be at infinity.
+    r1: ProjectivePoint,
+    /// The point may be at infinity.
+    r2: ProjectivePoint,
+}
+
+/// cbindgen:ignore
+impl AggregatedNonce {
+    /// The number of bytes in a serialized aggregated nonce.
+    pub const LEN: usize = POINT_LEN * 2;
+}
+
+impl ToBytesVec for AggregatedNonce {
+    fn to_vec(&self) -> Vec<u8> {
+        let mut res = Vec::with_capacity(Self::LEN);
+        res.extend_from_slice(point_to_bytes_ext(&self.r1).as_slice());
+        res.extend_from_slice(point_to_bytes_ext(&self.r2).as_slice());
+        res
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for AggregatedNonce {
+    type Error = KeyPairError;
+
+    fn try_from(bytes: &'a [u8]) -> Result<Self, Self::Error> {
+        if bytes.len() != Self::LEN {
+            return Err(KeyPairError::InvalidPublicKey);
+        }
+        Ok(AggregatedNonce {
+            r1: point_from_slice_ext(&bytes[FIRST_POINT_RANGE])?,
+            r2: point_from_slice_ext(&bytes[SECOND_POINT_RANGE])?,
+        })
+    }
+}
+
+/// Represents a partial signature of a signer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PartialSignature {
+    s: Scalar,
+}
+
+/// cbindgen:ignore
+impl PartialSignature {
+    /// The number of bytes in a serialized partial signature.
+    pub const LEN: usize = H256::LEN;
+}
+
+impl ToBytesVec for PartialSignature {
+    fn to_vec(&self) -> Vec<u8> {
+        scalar_to_bytes(&self.s).into_vec()
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for PartialSignature {
+    type Error = KeyPairError;
+
+    fn try_from(bytes: &'a [u8]) -> Result<Self, Self::Error> {
+        let bytes = H256::try_from(bytes).map_err(|_| KeyPairError::InvalidSignature)?;
+        let s = scalar_from_canonical_bytes(&bytes).ok_or(KeyPairError::InvalidSignature)?;
+        Ok(PartialSignature { s })
+    }
+}
+
+/// Generates a nonce pair according to the `NonceGen` algorithm.
+///
+/// * `rand` - 32 bytes that must be freshly generated uniformly at random for each call.
+/// * `private` - the signer's private key. Optional, but strongly recommended.
+/// * `public` - the signer's public key.
+/// * `aggregated_public` - the aggregated public key, if already known.
+/// * `message` - the message to be signed, if already known.
+/// * `extra_in` - any additional data, if available.
+pub fn nonce_gen(
+    rand: H256,
+    private: Option<&secp256k1::PrivateKey>,
+    public: &secp256k1::PublicKey,
+    aggregated_public: Option<&PublicKey>,
+    message: Option<&[u8]>,
+    extra_in: Option<&[u8]>,
+) -> KeyPairResult<(SecretNonce, PublicNonce)> {
+    let mut rand = Zeroizing::new(rand);
+    if let Some(private) = private {
+        let aux_hash = tagged_hash(AUX_TAG, &[rand.as_slice()]);
+        let secret = Zeroizing::new(private.secret.to_bytes());
+        rand.iter_mut()
+            .zip(secret.iter().zip(aux_hash.iter()))
+            .for_each(|(rand, (secret, aux))| *rand = secret ^ aux);
+    }
+
+    let public_key = public.compressed();
+    let aggregated_public = aggregated_public.map(PublicKey::x_only);
+    let aggregated_public = aggregated_public
+        .as_ref()
+        .map(H256::as_slice)
+        .unwrap_or_default();
+
+    let message_prefixed = match message {
+        Some(message) => {
+            let mut prefixed = vec![1];
+            prefixed.extend_from_slice(&(message.len() as u64).to_be_bytes());
+            prefixed.extend_from_slice(message);
+            prefixed
+        },
+        None => vec![0],
+    };
+    let extra_in = extra_in.unwrap_or_default();
+
+    let public_key_len = [POINT_LEN as u8];
+    let aggregated_public_len = [aggregated_public.len() as u8];
+    let extra_in_len = (extra_in.len() as u32).to_be_bytes();
+
+    let nonce = |index: u8| {
+        let hash = tagged_hash(
+            NONCE_TAG,
+            &[
+                rand.as_slice(),
+                public_key_len.as_slice(),
+                public_key.as_slice(),
+                aggregated_public_len.as_slice(),
+                aggregated_public,
+                message_prefixed.as_slice(),
+                extra_in_len.as_slice(),
+                extra_in,
+                [index].as_slice(),
+            ],
+        );
+        scalar_from_hash(&hash)
+    };
+
+    let secret_nonce = SecretNonce {
+        k1: nonce(0),
+        k2: nonce(1),
+        public_key,
+    };
+    if bool::from(secret_nonce.k1.is_zero()) || bool::from(secret_nonce.k2.is_zero()) {
+        return Err(KeyPairError::SigningError);
+    }
+
+    let public_nonce = PublicNonce {
+        r1: (ProjectivePoint::GENERATOR * secret_nonce.k1).to_affine(),
+        r2: (ProjectivePoint::GENERATOR * secret_nonce.k2).to_affine(),
+    };
+    Ok((secret_nonce, public_nonce))
+}
+
+/// Aggregates the public nonces of all signers according to the `NonceAgg` algorithm.
+pub fn nonce_agg(nonces: &[PublicNonce]) -> AggregatedNonce {
+    let (r1, r2) = nonces.iter().fold(
+        (ProjectivePoint::IDENTITY, ProjectivePoint::IDENTITY),
+        |(r1, r2), nonce| (r1 + nonce.r1, r2 + nonce.r2),
+    );
+    AggregatedNonce { r1, r2 }
+}
+
+/// Represents a signing session of the given message.
+#[derive(Clone, Debug)]
+pub struct Session {
+    key_agg: KeyAggContext,
+    /// The nonce coefficient `b`.
+    b: Scalar,
+    /// The final nonce point `R`.
+    r: AffinePoint,
+    /// The BIP-340 challenge `e`.
+    e: Scalar,
+}
+
+impl Session {
+    /// Creates a signing session of the given `message`.
+    pub fn new(
+        key_agg: &KeyAggContext,
+        aggregated_nonce: &AggregatedNonce,
+        message: &[u8],
+    ) -> Session {
+        let aggregated_public = key_agg.aggregated_public_key().x_only();
+
+        let b_hash = tagged_hash(
+            NONCE_COEFFICIENT_TAG,
+            &[
+                aggregated_nonce.to_vec().as_slice(),
+                aggregated_public.as_slice(),
+                message,
+            ],
+        );
+        let b = scalar_from_hash(&b_hash);
+
+        let mut r = aggregated_nonce.r1 + aggregated_nonce.r2 * b;
+        if r == ProjectivePoint::IDENTITY {
+            r = ProjectivePoint::GENERATOR;
+        }
+        let r = r.to_affine();
+
+        let e = challenge(&x_only(&r), &aggregated_public, message);
+        Session {
+            key_agg: key_agg.clone(),
+            b,
+            r,
+            e,
+        }
+    }
+
+    /// Creates a two-party signing session of the given `message` with our `public_nonce`
+    /// and the `counterparty_nonce` revealed after its `counterparty_commitment` was received.
+    ///
+    /// Returns an error if the `counterparty_nonce` doesn't match the `counterparty_commitment`.
+    pub fn with_counterparty(
+        key_agg: &KeyAggContext,
+        public_nonce: &PublicNonce,
+        counterparty_nonce: &PublicNonce,
+        counterparty_commitment: &H256,
+        message: &[u8],
+    ) -> KeyPairResult<Session> {
+        if !counterparty_nonce.verify_commitment(counterparty_commitment) {
+            return Err(KeyPairError::InvalidPublicKey);
+        }
+
+        let aggregated_nonce = nonce_agg(&[public_nonce.clone(), counterparty_nonce.clone()]);
+        Ok(Session::new(key_agg, &aggregated_nonce, message))
+    }
+
+    /// Creates a partial signature with the given `secret_nonce` and `private` key.
+    pub fn partial_sign(
+        &self,
+        secret_nonce: SecretNonce,
+        private: &secp256k1::PrivateKey,
+    ) -> KeyPairResult<PartialSignature> {
+        let public_key = private.public().compressed();
+        if public_key != secret_nonce.public_key {
+            return Err(KeyPairError::InvalidSecretKey);
+        }
+        if !self.key_agg.public_keys.contains(&public_key) {
+            return Err(KeyPairError::InvalidPublicKey);
+        }
+
+        let (k1, k2) = if has_even_y(&self.r) {
+            (secret_nonce.k1, secret_nonce.k2)
+        } else {
+            (-secret_nonce.k1, -secret_nonce.k2)
+        };
+
+        let a = self.key_agg.coefficient(&public_key);
+        let g = self.key_agg.parity_factor();
+        let d = g * self.key_agg.gacc * private.secret.as_nonzero_scalar().as_ref();
+
+        let s = k1 + self.b * k2 + self.e * a * d;
+        Ok(PartialSignature { s })
+    }
+
+    /// Verifies the `partial` signature of a signer with the given `public_nonce` and `public` key.
+    pub fn partial_verify(
+        &self,
+        partial: &PartialSignature,
+        public_nonce: &PublicNonce,
+        public: &secp256k1::PublicKey,
+    ) -> bool {
+        let public_key = public.compressed();
+        if !self.key_agg.public_keys.contains(&public_key) {
+            return false;
+        }
+
+        let r = ProjectivePoint::from(public_nonce.r1) + public_nonce.r2 * self.b;
+        let r = if has_even_y(&self.r) { r } else { -r };
+
+        let a = self.key_agg.coefficient(&public_key);
+        let g = self.key_agg.parity_factor() * self.key_agg.gacc;
+        let public_point = ProjectivePoint::from(*public.public.as_affine());
+
+        ProjectivePoint::GENERATOR * partial.s == r + public_point * (self.e * a * g)
+    }
+
+    /// Aggregates the partial signatures of all signers into a BIP-340 signature.
+    pub fn aggregate(&self, partials: &[PartialSignature]) -> Signature {
+        let g = self.key_agg.parity_factor();
+        let s = partials
+            .iter()
+            .fold(Scalar::ZERO, |acc, partial| acc + partial.s);
+
+        Signature::new(x_only(&self.r), s + self.e * g * self.key_agg.tacc)
+    }
+}
+
+/// Parses a compressed point, where 33 zero bytes represent the point at infinity.
+fn point_from_slice_ext(bytes: &[u8]) -> KeyPairResult<ProjectivePoint> {
+    if bytes.iter().all(|byte| *byte == 0) {
+        return Ok(ProjectivePoint::IDENTITY);
+    }
+    point_from_slice(bytes).map(ProjectivePoint::from)
+}
+
+/// Serializes a compressed point, where the point at infinity is represented as 33 zero bytes.
+fn point_to_bytes_ext(point: &ProjectivePoint) -> H264 {
+    if *point == ProjectivePoint::IDENTITY {
+        return H264::default();
+    }
+    compressed_point(&point.to_affine())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::VerifyingKeyTrait;
+    use tw_encoding::hex;
+
+    const SECRET_1: &str = "afeefca74d9a325cf1d6b6911d61a65c32afa8e02bd5e78e2e4ac2910bab45f5";
+    const SECRET_2: &str = "9cd3b16e10bd574fed3743d8e0de0b7b4e6c69f3245ab5a168ef010d22bfefa0";
+    const MESSAGE: &str = "243f6a8885a308d313198a2e03707344a4093822299f31d0082efa98ec4e6c89";
+
+    struct ExpectedSession {
+        aggregated_public: &'static str,
+        public_nonce_1: &'static str,
+        public_nonce_2: &'static str,
+        aggregated_nonce: &'static str,
+        partial_1: &'static str,
+        partial_2: &'static str,
+        signature: &'static str,
+    }
+
+    #[track_caller]
+    fn test_two_signers_impl(key_agg: KeyAggContext, expected: ExpectedSession) {
+        let private_1 = secp256k1::PrivateKey::try_from(SECRET_1).unwrap();
+        let private_2 = secp256k1::PrivateKey::try_from(SECRET_2).unwrap();
+        let message = H256::from(MESSAGE);
+
+        let aggregated_public = key_agg.aggregated_public_key();
+        assert_eq!(
+            aggregated_public.x_only(),
+            H256::from(expected.aggregated_public)
+        );
+
+        // Round 1.
+        let (secret_nonce_1, public_nonce_1) = nonce_gen(
+            H256::from([1; 32]),
+            Some(&private_1),
+            &private_1.public(),
+            Some(&aggregated_public),
+            Some(message.as_slice()),
+            None,
+        )
+        .unwrap();
+        let (secret_nonce_2, public_nonce_2) = nonce_gen(
+            H256::from([2; 32]),
+            Some(&private_2),
+            &private_2.public(),
+            Some(&aggregated_public),
+            Some(message.as_slice()),
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            hex::encode(public_nonce_1.to_vec(), false),
+            expected.public_nonce_1
+        );
+        assert_eq!(
+            hex::encode(public_nonce_2.to_vec(), false),
+            expected.public_nonce_2
+        );
+
+        let aggregated_nonce = nonce_agg(&[public_nonce_1.clone(), public_nonce_2.clone()]);
+        assert_eq!(
+            hex::encode(aggregated_nonce.to_vec(), false),
+            expected.aggregated_nonce
+        );
+
+        // Round 2.
+        let session = Session::new(&key_agg, &aggregated_nonce, message.as_slice());
+        let partial_1 = session.partial_sign(secret_nonce_1, &private_1).unwrap();
+        let partial_2 = session.partial_sign(secret_nonce_2, &private_2).unwrap();
+        assert_eq!(hex::encode(partial_1.to_vec(), false), expected.partial_1);
+        assert_eq!(hex::encode(partial_2.to_vec(), false), expected.partial_2);
+
+        assert!(session.partial_verify(&partial_1, &public_nonce_1, &private_1.public()));
+        assert!(session.partial_verify(&partial_2, &public_nonce_2, &private_2.public()));
+        assert!(!session.partial_verify(&partial_1, &public_nonce_2, &private_2.public()));
+
+        let signature = session.aggregate(&[partial_1, partial_2]);
+        assert_eq!(hex::encode(signature.to_vec(), false), expected.signature);
+        assert!(aggregated_public.verify(signature, message));
+    }
+
+    fn public_keys() -> Vec<secp256k1::PublicKey> {
+        vec![
+            secp256k1::PrivateKey::try_from(SECRET_1).unwrap().public(),
+            secp256k1::PrivateKey::try_from(SECRET_2).unwrap().public(),
+        ]
+    }
+
+    /// https://github.com/bitcoin/bips/blob/master/bip-0327/vectors/key_agg_vectors.json
+    #[test]
+    fn test_key_agg() {
+        let public_keys: Vec<_> = [
+            "02f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9",
+            "03dff1d77f2a671c5f36183726db2341be58feae1da2deced843240f7b502ba659",
+            "023590a94e768f8e1815c2f24b4d80a8e3149316c3518ce7b7ad338368d038ca66",
+        ]
+        .into_iter()
+        .map(|public| secp256k1::PublicKey::try_from(public).unwrap())
+        .collect();
+
+        let key_agg = KeyAggContext::new(&public_keys).unwrap();
+        assert_eq!(
+            key_agg.aggregated_public_key().x_only(),
+            H256::from("90539eede565f5d054f32cc0c220126889ed1e5d193baf15aef344fe59d4610c")
+        );
+    }
+
+    #[test]
+    fn test_key_agg_empty() {
+        KeyAggContext::new(&[]).unwrap_err();
+    }
+
+    #[test]
+    fn test_two_signers() {
+        let key_agg = KeyAggContext::new(&public_keys()).unwrap();
+        test_two_signers_impl(key_agg, ExpectedSession {
+            aggregated_public: "d7bdffc808f84e42cf0dd9f22fc6c6da073b0cbf82b5ac28932e4abcb5fd3e1f",
+            public_nonce_1: "0291dcc15e964033ceeda02e41ed39768881f652b2f892afe0b2d9b7974aa88fbf0260eedae13670bfd27b8a9cadaa3cee07ffe3620a20a697652f379493229b1b69",
+            public_nonce_2: "02c98c2091b4bf04fd01327dac8bec0863190d9225229dfae474efa92a82051bc50375c93aada28bcceab234dc1fc145a013fea098498ed8fe0cbb8f3396f97349eb",
+            aggregated_nonce: "022e4702e5697f1574d87eeed5c80b327091cf6f7403a1df6e4458cc226f90863f02f0fc5a0bfd49ad88fda28dcd36b41a6e955d68054c140910d27b26017858948b",
+            partial_1: "d6998753e63aed1e466c798497d0d83a3c26993874193c847621fad141a465b2",
+            partial_2: "4e66291369cae6494e27d8e487baf602de07132295150fb179e23459ff9cf3c3",
+            signature: "9cab96677557fec576bdce8f210af41d04b7d085a37688b9ea9077324cccb7a024ffb0675005d367949452691f8bce3e5f7ecf7459e5abfa3031d09e710b1834",
+        });
+    }
+
+    #[test]
+    fn test_two_signers_taproot_tweak() {
+        let mut key_agg = KeyAggContext::new(&public_keys()).unwrap();
+        key_agg.apply_taproot_tweak(None).unwrap();
+        test_two_signers_impl(key_agg, ExpectedSession {
+            aggregated_public: "08269f93aa960c4e381fa6518055be43e0fa158d0649019d1e685654615a63ce",
+            public_nonce_1: "02a823d1bfd61440e18f2a4d80e0423713a8d8aaf00845917498fe9fda343e6ce3027638a964cb430ce0d005f38f9ad5b2d9252f79b042417724aa8e8266920e97f6",
+            public_nonce_2: "035ea82b2ee78b17d0e217e87cdcbdb5ad9e8512e4c3c97407ce2bb5142f97058a03348f32c31dd73d97654f8c61c1b11c76564f7c5a1d5c2b14102efab524cd7200",
+            aggregated_nonce: "021c74c41e735f6bf685efe54411d68f087c459fc391247135d67101902cc69e2403907a3a501c01682fbcaf2d49fb3a17d1bc603b57b94f310310c920292c74bf4d",
+            partial_1: "1afb6e89a6402bd4c905ae00bcfc2145d2097ca6ab0a272d80b72c705e5a6ef5",
+            partial_2: "b016159f0fb100e4552a545263b4786e468d6b9838b2c795a7d476be5b0f5ea8",
+            signature: "a47fc2430a05e4c52fbc95f65b9b3ef382ff1b16c6da721252a70e0d1bf9908d77cce2be2e8df11713979e3b6144de94ab02a34f3629ce80280c967259bdcadf",
+        });
+    }
+
+    #[test]
+    fn test_partial_sign_foreign_key() {
+        let key_agg = KeyAggContext::new(&public_keys()[0..1]).unwrap();
+        let private_2 = secp256k1::PrivateKey::try_from(SECRET_2).unwrap();
+
+        let (secret_nonce, public_nonce) =
+            nonce_gen(H256::default(), None, &private_2.public(), None, None, None).unwrap();
+        let session = Session::new(&key_agg, &nonce_agg(&[public_nonce]), &[]);
+        assert_eq!(
+            session.partial_sign(secret_nonce, &private_2),
+            Err(KeyPairError::InvalidPublicKey)
+        );
+    }
+
+    #[test]
+    fn test_two_signers_with_counterparty() {
+        let private_1 = secp256k1::PrivateKey::try_from(SECRET_1).unwrap();
+        let private_2 = secp256k1::PrivateKey::try_from(SECRET_2).unwrap();
+        let key_agg = KeyAggContext::new(&public_keys()).unwrap();
+        let aggregated_public = key_agg.aggregated_public_key();
+
+        // The nonces are generated and committed to before the message is known.
+        let (secret_nonce_1, public_nonce_1) = nonce_gen(
+            H256::from([1; 32]),
+            Some(&private_1),
+            &private_1.public(),
+            Some(&aggregated_public),
+            None,
+            None,
+        )
+        .unwrap();
+        let (secret_nonce_2, public_nonce_2) = nonce_gen(
+            H256::from([2; 32]),
+            Some(&private_2),
+            &private_2.public(),
+            Some(&aggregated_public),
+            None,
+            None,
+        )
+        .unwrap();
+        let commitment_1 = public_nonce_1.commitment();
+        let commitment_2 = public_nonce_2.commitment();
+
+        let message = H256::from(MESSAGE);
+        let session_1 = Session::with_counterparty(
+            &key_agg,
+            &public_nonce_1,
+            &public_nonce_2,
+            &commitment_2,
+            message.as_slice(),
+        )
+        .unwrap();
+        let session_2 = Session::with_counterparty(
+            &key_agg,
+            &public_nonce_2,
+            &public_nonce_1,
+            &commitment_1,
+            message.as_slice(),
+        )
+        .unwrap();
+
+        let partial_1 = session_1.partial_sign(secret_nonce_1, &private_1).unwrap();
+        let partial_2 = session_2.partial_sign(secret_nonce_2, &private_2).unwrap();
+        assert!(session_1.partial_verify(&partial_2, &public_nonce_2, &private_2.public()));
+        assert!(session_2.partial_verify(&partial_1, &public_nonce_1, &private_1.public()));
+
+        let signature = session_1.aggregate(&[partial_1, partial_2]);
+        assert!(aggregated_public.verify(signature, message));
+    }
+
+    #[test]
+    fn test_with_counterparty_invalid_commitment() {
+        let public_keys = public_keys();
+        let key_agg = KeyAggContext::new(&public_keys).unwrap();
+        let (_, public_nonce_1) =
+            nonce_gen(H256::from([1; 32]), None, &public_keys[0], None, None, None).unwrap();
+        let (_, public_nonce_2) =
+            nonce_gen(H256::from([2; 32]), None, &public_keys[1], None, None, None).unwrap();
+
+        // The counterparty reveals another nonce than it committed to.
+        let res = Session::with_counterparty(
+            &key_agg,
+            &public_nonce_1,
+            &public_nonce_2,
+            &public_nonce_1.commitment(),
+            &[],
+        );
+        assert_eq!(res.unwrap_err(), KeyPairError::InvalidPublicKey);
+    }
+
+    #[test]
+    fn test_public_nonce_commitment() {
+        let public_nonce = "0291dcc15e964033ceeda02e41ed39768881f652b2f892afe0b2d9b7974aa88fbf0260eedae13670bfd27b8a9cadaa3cee07ffe3620a20a697652f379493229b1b69";
+        let nonce = PublicNonce::try_from(hex::decode(public_nonce).unwrap().as_slice()).unwrap();
+
+        let expected =
+            H256::from("5b132015d24aa81ba519b64697312e6be14374785ed24ef8c1b7f17a5e11560d");
+        assert_eq!(nonce.commitment(), expected);
+        assert!(nonce.verify_commitment(&expected));
+        assert!(!nonce.verify_commitment(&H256::default()));
+    }
+
+    #[test]
+    fn test_nonces_to_from_bytes() {
+        let public_nonce = "0291dcc15e964033ceeda02e41ed39768881f652b2f892afe0b2d9b7974aa88fbf0260eedae13670bfd27b8a9cadaa3cee07ffe3620a20a697652f379493229b1b69";
+        let bytes = hex::decode(public_nonce).unwrap();
+        let nonce = PublicNonce::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(nonce.to_vec(), bytes);
+
+        // The first aggregated point is at infinity.
+        let aggregated = "0000000000000000000000000000000000000000000000000000000000000000000260eedae13670bfd27b8a9cadaa3cee07ffe3620a20a697652f379493229b1b69";
+        let bytes = hex::decode(aggregated).unwrap();
+        let nonce = AggregatedNonce::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(nonce.to_vec(), bytes);
+
+        PublicNonce::try_from(&bytes[..]).unwrap_err();
+    }
+}
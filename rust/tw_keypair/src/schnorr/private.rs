@@ -0,0 +1,370 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::ecdsa::secp256k1;
+use crate::ecdsa::secp256k1::wif::{self, WifInfo};
+use crate::schnorr::adaptor::AdaptorSignature;
+use crate::schnorr::public::PublicKey;
+use crate::schnorr::sign_to_contract::{anti_exfil_host_commitment, SignToContractOpening};
+use crate::schnorr::signature::Signature;
+use crate::schnorr::taproot::TaprootSpendPath;
+use crate::schnorr::{
+    challenge, has_even_y, scalar_from_hash, scalar_to_bytes, tagged_hash, taproot_tweak, x_only,
+    ADAPTOR_NONCE_TAG, AUX_TAG, NONCE_TAG,
+};
+use crate::traits::SigningKeyTrait;
+use crate::{KeyPairError, KeyPairResult};
+use k256::ecdsa::SigningKey;
+use k256::{ProjectivePoint, Scalar};
+use rand_core::{CryptoRng, OsRng, RngCore};
+use tw_encoding::hex;
+use tw_hash::H256;
+use tw_misc::traits::ToBytesZeroizing;
+use zeroize::{ZeroizeOnDrop, Zeroizing};
+
+/// Represents a `secp256k1` private key that is used to produce BIP-340 Schnorr signatures.
+#[derive(ZeroizeOnDrop)]
+pub struct PrivateKey {
+    pub(crate) secret: SigningKey,
+    /// Whether the auxiliary random data should be omitted on signing.
+    no_aux_rand: bool,
+}
+
+impl PrivateKey {
+    /// Generates a random private key using the given `rng`.
+    pub fn generate(rng: &mut (impl CryptoRng + RngCore)) -> PrivateKey {
+        PrivateKey {
+            secret: SigningKey::random(rng),
+            no_aux_rand: false,
+        }
+    }
+
+    /// Returns an associated x-only public key.
+    pub fn public(&self) -> PublicKey {
+        PublicKey::with_point(self.secret.verifying_key().as_affine())
+    }
+
+    /// Disables the auxiliary random data that is used to produce a signature,
+    /// so the signature is fully determined by the private key and the message.
+    ///
+    /// # Warning
+    ///
+    /// Should be used in tests only. Randomness should ALWAYS be used in production.
+    pub fn no_aux_rand(mut self) -> PrivateKey {
+        self.no_aux_rand = true;
+        self
+    }
+
+    /// Signs the given `message` with the caller-supplied `aux` random data
+    /// instead of the OS randomness.
+    ///
+    /// # Warning
+    ///
+    /// `aux` should be unique for every signature, e.g. derived from the transaction.
+    /// Otherwise, the signature is fully determined by the private key and the message.
+    pub fn sign_with_aux_rand(&self, message: H256, aux: H256) -> KeyPairResult<Signature> {
+        self.sign_with_aux(message.as_slice(), &aux)
+    }
+
+    /// Signs the given arbitrary-length `message` as permitted by BIP-340,
+    /// e.g. used by non-Bitcoin protocols that sign raw messages.
+    /// Consider using [`PrivateKey::sign_tagged`] to sign a domain-separated message hash instead.
+    pub fn sign_message(&self, message: &[u8]) -> KeyPairResult<Signature> {
+        self.sign_with_aux(message, &self.aux_rand())
+    }
+
+    /// Signs the given arbitrary-length `message` with the caller-supplied `aux` random data.
+    /// See [`PrivateKey::sign_with_aux_rand`].
+    pub fn sign_message_with_aux_rand(
+        &self,
+        message: &[u8],
+        aux: H256,
+    ) -> KeyPairResult<Signature> {
+        self.sign_with_aux(message, &aux)
+    }
+
+    /// Signs the tagged hash `hash_tag(message)` of the given `message`, see [`tagged_hash`].
+    pub fn sign_tagged(&self, tag: &str, message: &[u8]) -> KeyPairResult<Signature> {
+        self.sign(tagged_hash(tag, &[message]))
+    }
+
+    /// Decodes a private key from the Wallet Import Format.
+    /// Returns the key along with the network and the compressed flag.
+    pub fn from_wif(wif: &str) -> KeyPairResult<(PrivateKey, WifInfo)> {
+        let (secret, info) = wif::decode(wif)?;
+        Ok((PrivateKey::try_from(secret.as_slice())?, info))
+    }
+
+    /// Encodes the private key in the Wallet Import Format.
+    pub fn to_wif(&self, info: WifInfo) -> Zeroizing<String> {
+        wif::encode(self.to_zeroizing_vec().as_slice(), info)
+    }
+
+    /// Tweaks the private key according to [BIP-341](https://github.com/bitcoin/bips/blob/master/bip-0341.mediawiki#constructing-and-spending-taproot-outputs),
+    /// so the result key corresponds to the taproot output key.
+    /// `merkle_root` should be `None` if the output has no script path.
+    pub fn tweak(&self, merkle_root: Option<H256>) -> KeyPairResult<PrivateKey> {
+        let tweak = taproot_tweak(&self.public(), merkle_root.as_ref())?;
+        let mut tweaked = PrivateKey::with_scalar(self.even_y_scalar() + tweak)?;
+        tweaked.no_aux_rand = self.no_aux_rand;
+        Ok(tweaked)
+    }
+
+    /// Signs the taproot `sighash` according to the given spending `path`.
+    /// The key-path signature is produced with the tweaked key,
+    /// and the script-path signature is produced with the untweaked key.
+    pub fn sign_taproot(&self, sighash: H256, path: &TaprootSpendPath) -> KeyPairResult<Signature> {
+        match path {
+            TaprootSpendPath::KeyPath { merkle_root } => self.tweak(*merkle_root)?.sign(sighash),
+            TaprootSpendPath::ScriptPath => self.sign(sighash),
+        }
+    }
+
+    /// Creates a private key from the given non-zero `scalar`.
+    pub(crate) fn with_scalar(scalar: Scalar) -> KeyPairResult<PrivateKey> {
+        let secret = SigningKey::from_bytes(&scalar.to_bytes())
+            .map_err(|_| KeyPairError::InvalidSecretKey)?;
+        Ok(PrivateKey {
+            secret,
+            no_aux_rand: false,
+        })
+    }
+
+    /// Returns the secret scalar `d` such that `d * G` has an even Y coordinate.
+    pub(crate) fn even_y_scalar(&self) -> Scalar {
+        let scalar = *self.secret.as_nonzero_scalar().as_ref();
+        if has_even_y(self.secret.verifying_key().as_affine()) {
+            scalar
+        } else {
+            -scalar
+        }
+    }
+
+    /// Signs the given `message` with the `aux` random data.
+    /// Source: https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki#default-signing
+    pub(crate) fn sign_with_aux(&self, message: &[u8], aux: &H256) -> KeyPairResult<Signature> {
+        sign_with_secret(&self.even_y_scalar(), &self.public().x_only(), message, aux)
+    }
+
+    /// Creates an adaptor signature of the given `message` encrypted with the `adaptor` point.
+    /// The result can be completed into a valid signature with the adaptor secret
+    /// via [`AdaptorSignature::complete`].
+    pub fn sign_adaptor(
+        &self,
+        message: H256,
+        adaptor: &secp256k1::PublicKey,
+    ) -> KeyPairResult<AdaptorSignature> {
+        self.sign_adaptor_with_aux(message.as_slice(), adaptor, &self.aux_rand())
+    }
+
+    /// Creates an adaptor signature of the given `message` with the `aux` random data.
+    pub(crate) fn sign_adaptor_with_aux(
+        &self,
+        message: &[u8],
+        adaptor: &secp256k1::PublicKey,
+        aux: &H256,
+    ) -> KeyPairResult<AdaptorSignature> {
+        let d = self.even_y_scalar();
+        let public = self.public().x_only();
+        let adaptor_point = ProjectivePoint::from(*adaptor.public.as_affine());
+
+        let masked_key = masked_key(&d, aux);
+        let nonce_hash = tagged_hash(
+            ADAPTOR_NONCE_TAG,
+            &[
+                masked_key.as_slice(),
+                adaptor.compressed().as_slice(),
+                public.as_slice(),
+                message,
+            ],
+        );
+        let k = scalar_from_hash(&nonce_hash);
+        if bool::from(k.is_zero()) {
+            return Err(KeyPairError::SigningError);
+        }
+
+        let r_point = ProjectivePoint::GENERATOR * k + adaptor_point;
+        if r_point == ProjectivePoint::IDENTITY {
+            return Err(KeyPairError::SigningError);
+        }
+        let r_point = r_point.to_affine();
+        let k = if has_even_y(&r_point) { k } else { -k };
+
+        let e = challenge(&x_only(&r_point), &public, message);
+        Ok(AdaptorSignature::new(r_point, k + e * d))
+    }
+
+    /// Signs the given `message` with the nonce tweaked to commit to the given `data`.
+    /// Returns the signature along with the commitment opening, see [`SignToContractOpening`].
+    pub fn sign_to_contract(
+        &self,
+        message: H256,
+        data: &[u8],
+    ) -> KeyPairResult<(Signature, SignToContractOpening)> {
+        self.sign_to_contract_with_aux(message.as_slice(), data, &self.aux_rand())
+    }
+
+    /// Commits to the nonce on the second step of the anti-exfiltration protocol,
+    /// see [`crate::schnorr::sign_to_contract`].
+    /// The nonce is derived with the `host_commitment` used as the auxiliary random data.
+    pub fn anti_exfil_signer_commit(
+        &self,
+        message: H256,
+        host_commitment: &H256,
+    ) -> KeyPairResult<SignToContractOpening> {
+        let d = self.even_y_scalar();
+        let k = derive_nonce(
+            &d,
+            &self.public().x_only(),
+            message.as_slice(),
+            host_commitment,
+        )?;
+        Ok(SignToContractOpening::new(
+            (ProjectivePoint::GENERATOR * k).to_affine(),
+        ))
+    }
+
+    /// Signs the given `message` with the revealed `host_data`
+    /// on the third step of the anti-exfiltration protocol.
+    pub fn anti_exfil_sign(&self, message: H256, host_data: &H256) -> KeyPairResult<Signature> {
+        let host_commitment = anti_exfil_host_commitment(host_data);
+        let (signature, _opening) = self.sign_to_contract_with_aux(
+            message.as_slice(),
+            host_data.as_slice(),
+            &host_commitment,
+        )?;
+        Ok(signature)
+    }
+
+    /// Signs the given `message` with the nonce derived from the `aux` data and tweaked with the `data`.
+    fn sign_to_contract_with_aux(
+        &self,
+        message: &[u8],
+        data: &[u8],
+        aux: &H256,
+    ) -> KeyPairResult<(Signature, SignToContractOpening)> {
+        let d = self.even_y_scalar();
+        let public = self.public().x_only();
+
+        let k = derive_nonce(&d, &public, message, aux)?;
+        let opening = SignToContractOpening::new((ProjectivePoint::GENERATOR * k).to_affine());
+        let k = k + opening.tweak(data)?;
+
+        let r_point = ProjectivePoint::GENERATOR * k;
+        if r_point == ProjectivePoint::IDENTITY {
+            return Err(KeyPairError::SigningError);
+        }
+        let r_point = r_point.to_affine();
+        let k = if has_even_y(&r_point) { k } else { -k };
+        let r = x_only(&r_point);
+
+        let e = challenge(&r, &public, message);
+        Ok((Signature::new(r, k + e * d), opening))
+    }
+
+    /// Returns either random or zeroed auxiliary data depending on [`PrivateKey::no_aux_rand`].
+    fn aux_rand(&self) -> H256 {
+        if self.no_aux_rand {
+            return H256::default();
+        }
+
+        let mut aux = [0_u8; H256::LEN];
+        OsRng.fill_bytes(&mut aux);
+        H256::from(aux)
+    }
+}
+
+/// Signs the given `message` with the even-Y secret scalar `d`, its x-only `public` key
+/// and the `aux` random data.
+/// Source: https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki#default-signing
+fn sign_with_secret(
+    d: &Scalar,
+    public: &H256,
+    message: &[u8],
+    aux: &H256,
+) -> KeyPairResult<Signature> {
+    let k = derive_nonce(d, public, message, aux)?;
+
+    let r_point = (ProjectivePoint::GENERATOR * k).to_affine();
+    let k = if has_even_y(&r_point) { k } else { -k };
+    let r = x_only(&r_point);
+
+    let e = challenge(&r, public, message);
+    Ok(Signature::new(r, k + e * d))
+}
+
+/// Derives the BIP-340 nonce `k` from the even-Y secret scalar `d`, its x-only `public` key,
+/// the `message` and the `aux` random data.
+fn derive_nonce(d: &Scalar, public: &H256, message: &[u8], aux: &H256) -> KeyPairResult<Scalar> {
+    let masked_key = masked_key(d, aux);
+    let nonce_hash = tagged_hash(
+        NONCE_TAG,
+        &[masked_key.as_slice(), public.as_slice(), message],
+    );
+    let k = scalar_from_hash(&nonce_hash);
+    if bool::from(k.is_zero()) {
+        return Err(KeyPairError::SigningError);
+    }
+    Ok(k)
+}
+
+/// Returns the secret `d` masked with the auxiliary data: `bytes(d) xor hash_BIP0340/aux(aux)`.
+fn masked_key(d: &Scalar, aux: &H256) -> Zeroizing<H256> {
+    let aux_hash = tagged_hash(AUX_TAG, &[aux.as_slice()]);
+    let mut masked_key = Zeroizing::new(scalar_to_bytes(d));
+    masked_key
+        .iter_mut()
+        .zip(aux_hash.iter())
+        .for_each(|(key, aux)| *key ^= aux);
+    masked_key
+}
+
+impl SigningKeyTrait for PrivateKey {
+    type SigningMessage = H256;
+    type Signature = Signature;
+
+    fn sign(&self, message: Self::SigningMessage) -> KeyPairResult<Self::Signature> {
+        self.sign_with_aux(message.as_slice(), &self.aux_rand())
+    }
+
+    /// The secret scalar and the public key are computed once for all messages.
+    fn sign_batch(&self, messages: &[Self::SigningMessage]) -> KeyPairResult<Vec<Self::Signature>> {
+        let d = self.even_y_scalar();
+        let public = self.public().x_only();
+        messages
+            .iter()
+            .map(|message| sign_with_secret(&d, &public, message.as_slice(), &self.aux_rand()))
+            .collect()
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for PrivateKey {
+    type Error = KeyPairError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let secret = SigningKey::from_slice(data).map_err(|_| KeyPairError::InvalidSecretKey)?;
+        Ok(PrivateKey {
+            secret,
+            no_aux_rand: false,
+        })
+    }
+}
+
+impl<'a> TryFrom<&'a str> for PrivateKey {
+    type Error = KeyPairError;
+
+    fn try_from(hex: &'a str) -> Result<Self, Self::Error> {
+        let bytes = Zeroizing::new(hex::decode(hex).map_err(|_| KeyPairError::InvalidSecretKey)?);
+        Self::try_from(bytes.as_slice())
+    }
+}
+
+impl ToBytesZeroizing for PrivateKey {
+    fn to_zeroizing_vec(&self) -> Zeroizing<Vec<u8>> {
+        let secret = Zeroizing::new(self.secret.to_bytes());
+        Zeroizing::new(secret.as_slice().to_vec())
+    }
+}
+
+crate::ct::impl_ct_eq!(PrivateKey);
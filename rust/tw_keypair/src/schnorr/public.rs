@@ -0,0 +1,231 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::ecdsa::secp256k1;
+use crate::schnorr::adaptor::AdaptorSignature;
+use crate::schnorr::sign_to_contract::SignToContractOpening;
+use crate::schnorr::signature::Signature;
+use crate::schnorr::{
+    challenge, compressed_point, has_even_y, lift_x, scalar_from_canonical_bytes, tagged_hash,
+    taproot_tweak, x_only,
+};
+use crate::traits::VerifyingKeyTrait;
+use crate::{KeyPairError, KeyPairResult};
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use k256::{AffinePoint, ProjectivePoint};
+use tw_encoding::hex;
+use tw_hash::{H256, H264, H520};
+use tw_misc::traits::ToBytesVec;
+use tw_misc::try_or_false;
+
+/// Represents a BIP-340 x-only `secp256k1` public key.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PublicKey {
+    /// The point always has an even Y coordinate.
+    point: AffinePoint,
+}
+
+/// cbindgen:ignore
+impl PublicKey {
+    /// The number of bytes in a serialized x-only public key.
+    pub const LEN: usize = H256::len();
+
+    /// Creates an x-only public key from the given `point`,
+    /// i.e. negates the point if it has an odd Y coordinate.
+    pub(crate) fn with_point(point: &AffinePoint) -> PublicKey {
+        let point = if has_even_y(point) { *point } else { -*point };
+        PublicKey { point }
+    }
+
+    /// Returns the X coordinate of the public key (32 bytes).
+    pub fn x_only(&self) -> H256 {
+        x_only(&self.point)
+    }
+
+    /// Returns the compressed public key with an even Y coordinate (33 bytes).
+    pub fn compressed(&self) -> H264 {
+        compressed_point(&self.point)
+    }
+
+    /// Returns the uncompressed public key with an even Y coordinate (65 bytes).
+    pub fn uncompressed(&self) -> H520 {
+        H520::try_from(self.point.to_encoded_point(false).as_bytes())
+            .expect("Expected 65 byte array Public Key")
+    }
+
+    /// Tweaks the public key according to [BIP-341](https://github.com/bitcoin/bips/blob/master/bip-0341.mediawiki#constructing-and-spending-taproot-outputs),
+    /// so the result key is the taproot output key `Q = P + t * G`.
+    /// `merkle_root` should be `None` if the output has no script path.
+    ///
+    /// The result matches [`crate::schnorr::PrivateKey::tweak`], but doesn't require the private key.
+    pub fn tweak(&self, merkle_root: Option<H256>) -> KeyPairResult<PublicKey> {
+        let tweak = taproot_tweak(self, merkle_root.as_ref())?;
+
+        PublicKey::from_projective_point(
+            ProjectivePoint::from(self.point) + ProjectivePoint::GENERATOR * tweak,
+        )
+    }
+
+    /// Returns the x-only `P + t * G`, where `P` is the public key and `t` is the given `tweak` scalar.
+    /// Returns an error if `tweak` is not less than the curve order or the result is at infinity.
+    pub fn tweak_add(&self, tweak: &H256) -> KeyPairResult<PublicKey> {
+        let tweak = scalar_from_canonical_bytes(tweak).ok_or(KeyPairError::InvalidTweak)?;
+        PublicKey::from_projective_point(
+            ProjectivePoint::from(self.point) + ProjectivePoint::GENERATOR * tweak,
+        )
+    }
+
+    /// Returns the x-only `t * P`, where `P` is the public key and `t` is the given `tweak` scalar.
+    /// Returns an error if `tweak` is zero or not less than the curve order.
+    pub fn tweak_mul(&self, tweak: &H256) -> KeyPairResult<PublicKey> {
+        let tweak = scalar_from_canonical_bytes(tweak).ok_or(KeyPairError::InvalidTweak)?;
+        PublicKey::from_projective_point(ProjectivePoint::from(self.point) * tweak)
+    }
+
+    /// Returns the x-only sum of the given public keys, each taken with an even Y coordinate.
+    /// Returns an error if `public_keys` is empty or the sum is at infinity.
+    ///
+    /// # Warning
+    ///
+    /// The sum is prone to the rogue key attack, consider using [`crate::schnorr::musig2`]
+    /// to aggregate public keys of the signers.
+    pub fn combine(public_keys: &[PublicKey]) -> KeyPairResult<PublicKey> {
+        if public_keys.is_empty() {
+            return Err(KeyPairError::InvalidPublicKey);
+        }
+        let sum = public_keys
+            .iter()
+            .fold(ProjectivePoint::IDENTITY, |sum, public| {
+                sum + ProjectivePoint::from(public.point)
+            });
+        PublicKey::from_projective_point(sum)
+    }
+
+    /// Creates an x-only public key from the given `point`, rejecting the point at infinity.
+    fn from_projective_point(point: ProjectivePoint) -> KeyPairResult<PublicKey> {
+        if point == ProjectivePoint::IDENTITY {
+            return Err(KeyPairError::InvalidPublicKey);
+        }
+        Ok(PublicKey::with_point(&point.to_affine()))
+    }
+
+    /// Returns a reference to the [`AffinePoint`].
+    pub(crate) fn point(&self) -> &AffinePoint {
+        &self.point
+    }
+
+    /// Verifies the given `signature` of the arbitrary-length `message`.
+    /// See `PrivateKey::sign_message`.
+    /// Source: https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki#verification
+    pub fn verify_message(&self, signature: &Signature, message: &[u8]) -> bool {
+        let e = challenge(&signature.r, &self.x_only(), message);
+
+        let r_point =
+            ProjectivePoint::GENERATOR * signature.s - ProjectivePoint::from(self.point) * e;
+        if r_point == ProjectivePoint::IDENTITY {
+            return false;
+        }
+
+        let r_point = r_point.to_affine();
+        has_even_y(&r_point) && x_only(&r_point) == signature.r
+    }
+
+    /// Verifies the given `signature` of the tagged hash `hash_tag(message)`.
+    /// See `PrivateKey::sign_tagged`.
+    pub fn verify_tagged(&self, signature: &Signature, tag: &str, message: &[u8]) -> bool {
+        self.verify_message(signature, tagged_hash(tag, &[message]).as_slice())
+    }
+
+    /// Verifies the given anti-exfiltration `signature` of the `message` on the last step of the protocol,
+    /// i.e. the signature is valid and its nonce is the `signer_commitment` tweaked with the `host_data`.
+    /// See [`crate::schnorr::sign_to_contract`].
+    pub fn verify_anti_exfil(
+        &self,
+        signature: &Signature,
+        message: H256,
+        host_data: &H256,
+        signer_commitment: &SignToContractOpening,
+    ) -> bool {
+        self.verify_message(signature, message.as_slice())
+            && signer_commitment.verify_commitment(signature, host_data.as_slice())
+    }
+
+    /// Verifies the given adaptor `signature` of the `message` encrypted with the `adaptor` point.
+    pub fn verify_adaptor(
+        &self,
+        signature: &AdaptorSignature,
+        message: H256,
+        adaptor: &secp256k1::PublicKey,
+    ) -> bool {
+        let e = challenge(&x_only(&signature.r), &self.x_only(), message.as_slice());
+
+        // `R = R' - T`, negated if `R'` has an odd Y coordinate.
+        let r_point =
+            ProjectivePoint::from(signature.r) - ProjectivePoint::from(*adaptor.public.as_affine());
+        let r_point = if has_even_y(&signature.r) {
+            r_point
+        } else {
+            -r_point
+        };
+
+        ProjectivePoint::GENERATOR * signature.s == r_point + ProjectivePoint::from(self.point) * e
+    }
+
+    /// Verifies the given `signature` of the `message` against the taproot output key,
+    /// i.e. the internal key tweaked with the `merkle_root`, see [`PublicKey::tweak`].
+    /// `merkle_root` should be `None` if the output has no script path.
+    ///
+    /// Useful to validate the key-path signatures of the counterparties.
+    pub fn verify_taproot(
+        &self,
+        signature: &Signature,
+        message: H256,
+        merkle_root: Option<H256>,
+    ) -> bool {
+        let output_key = try_or_false!(self.tweak(merkle_root));
+        output_key.verify_message(signature, message.as_slice())
+    }
+}
+
+impl VerifyingKeyTrait for PublicKey {
+    type SigningMessage = H256;
+    type VerifySignature = Signature;
+
+    fn verify(&self, signature: Self::VerifySignature, message: Self::SigningMessage) -> bool {
+        self.verify_message(&signature, message.as_slice())
+    }
+}
+
+/// Drops the parity of the Y coordinate.
+impl From<&secp256k1::PublicKey> for PublicKey {
+    fn from(public: &secp256k1::PublicKey) -> Self {
+        PublicKey::with_point(public.public.as_affine())
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for PublicKey {
+    type Error = KeyPairError;
+
+    /// Expected an x-only `H256` slice.
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let x = H256::try_from(data).map_err(|_| KeyPairError::InvalidPublicKey)?;
+        let point = lift_x(&x).ok_or(KeyPairError::InvalidPublicKey)?;
+        Ok(PublicKey { point })
+    }
+}
+
+impl<'a> TryFrom<&'a str> for PublicKey {
+    type Error = KeyPairError;
+
+    fn try_from(hex: &'a str) -> Result<Self, Self::Error> {
+        let bytes = hex::decode(hex).map_err(|_| KeyPairError::InvalidPublicKey)?;
+        Self::try_from(bytes.as_slice())
+    }
+}
+
+impl ToBytesVec for PublicKey {
+    fn to_vec(&self) -> Vec<u8> {
+        self.x_only().to_vec()
+    }
+}
@@ -0,0 +1,95 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Sign-to-contract commitments in BIP-340 Schnorr signatures.
+//!
+//! The signing nonce `R` is tweaked with the caller-provided `data`:
+//! `R' = R + int(hash_SignToContract/commit(R || data)) * G`,
+//! so the signature commits to the `data` without changing its format.
+//! The commitment is revealed by the original nonce [`SignToContractOpening`].
+//!
+//! Sign-to-contract is also used by the anti-exfiltration protocol,
+//! that prevents a hardware wallet from leaking the secret through a biased nonce:
+//! 1. The host generates random `host_data` and sends [`anti_exfil_host_commitment`] to the signer.
+//! 2. The signer sends the nonce commitment produced by `PrivateKey::anti_exfil_signer_commit`.
+//! 3. The host reveals `host_data`, and the signer signs via `PrivateKey::anti_exfil_sign`.
+//! 4. The host verifies the signature via `PublicKey::verify_anti_exfil`.
+
+use crate::schnorr::signature::Signature;
+use crate::schnorr::{
+    compressed_point, point_from_slice, scalar_from_canonical_bytes, tagged_hash, x_only,
+};
+use crate::{KeyPairError, KeyPairResult};
+use k256::{AffinePoint, ProjectivePoint, Scalar};
+use tw_hash::{H256, H264};
+use tw_misc::traits::ToBytesVec;
+use tw_misc::try_or_false;
+
+const S2C_COMMIT_TAG: &str = "SignToContract/commit";
+const ANTI_EXFIL_HOST_TAG: &str = "SignToContract/host";
+
+/// Computes the commitment to the `host_data` that is sent to the signer
+/// on the first step of the anti-exfiltration protocol.
+pub fn anti_exfil_host_commitment(host_data: &H256) -> H256 {
+    tagged_hash(ANTI_EXFIL_HOST_TAG, &[host_data.as_slice()])
+}
+
+/// Represents an opening of the sign-to-contract commitment, i.e. the original nonce point `R`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignToContractOpening {
+    pub(crate) nonce: AffinePoint,
+}
+
+/// cbindgen:ignore
+impl SignToContractOpening {
+    /// The number of bytes for a serialized opening, i.e. the compressed nonce point.
+    pub const LEN: usize = H264::LEN;
+
+    pub(crate) fn new(nonce: AffinePoint) -> SignToContractOpening {
+        SignToContractOpening { nonce }
+    }
+
+    /// Verifies that the `signature` nonce commits to the given `data`.
+    /// Please note the signature itself should be verified separately.
+    pub fn verify_commitment(&self, signature: &Signature, data: &[u8]) -> bool {
+        let tweak = try_or_false!(self.tweak(data));
+        let nonce = ProjectivePoint::from(self.nonce) + ProjectivePoint::GENERATOR * tweak;
+        nonce != ProjectivePoint::IDENTITY && x_only(&nonce.to_affine()) == signature.r
+    }
+
+    /// Returns the compressed nonce point (33 bytes).
+    pub fn to_bytes(&self) -> H264 {
+        compressed_point(&self.nonce)
+    }
+
+    /// Computes the nonce tweak `t = int(hash_SignToContract/commit(R || data))`.
+    /// Returns an error if `t` is not less than the curve order.
+    pub(crate) fn tweak(&self, data: &[u8]) -> KeyPairResult<Scalar> {
+        let hash = tagged_hash(
+            S2C_COMMIT_TAG,
+            &[compressed_point(&self.nonce).as_slice(), data],
+        );
+        scalar_from_canonical_bytes(&hash).ok_or(KeyPairError::SigningError)
+    }
+}
+
+impl ToBytesVec for SignToContractOpening {
+    fn to_vec(&self) -> Vec<u8> {
+        self.to_bytes().to_vec()
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for SignToContractOpening {
+    type Error = KeyPairError;
+
+    /// Expected a compressed nonce point (33 bytes).
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != Self::LEN {
+            return Err(KeyPairError::InvalidPublicKey);
+        }
+        Ok(SignToContractOpening {
+            nonce: point_from_slice(data)?,
+        })
+    }
+}
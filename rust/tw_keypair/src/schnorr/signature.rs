@@ -0,0 +1,62 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::schnorr::{scalar_from_canonical_bytes, scalar_to_bytes};
+use crate::KeyPairError;
+use k256::Scalar;
+use tw_hash::{concat, H256, H512};
+use tw_misc::traits::ToBytesVec;
+
+/// Represents a BIP-340 Schnorr signature.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Signature {
+    /// The X coordinate of the nonce point `R`.
+    pub(crate) r: H256,
+    pub(crate) s: Scalar,
+}
+
+/// cbindgen:ignore
+impl Signature {
+    /// The number of bytes for a serialized signature representation.
+    pub const LEN: usize = H512::LEN;
+
+    /// Creates a signature from the nonce point X coordinate `r` and the `s` scalar.
+    pub(crate) fn new(r: H256, s: Scalar) -> Signature {
+        Signature { r, s }
+    }
+
+    /// Returns an r-coordinate as 32 byte array.
+    pub fn r(&self) -> H256 {
+        self.r
+    }
+
+    /// Returns an s-value as 32 byte array.
+    pub fn s(&self) -> H256 {
+        scalar_to_bytes(&self.s)
+    }
+
+    /// Returns a standard binary signature representation:
+    /// RS, where R - 32 byte array, S - 32 byte array.
+    pub fn to_bytes(&self) -> H512 {
+        concat(self.r(), self.s())
+    }
+}
+
+impl ToBytesVec for Signature {
+    fn to_vec(&self) -> Vec<u8> {
+        self.to_bytes().into_vec()
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for Signature {
+    type Error = KeyPairError;
+
+    fn try_from(sig: &'a [u8]) -> Result<Self, Self::Error> {
+        let sig = H512::try_from(sig).map_err(|_| KeyPairError::InvalidSignature)?;
+        let (r, s): (H256, H256) = sig.split();
+
+        let s = scalar_from_canonical_bytes(&s).ok_or(KeyPairError::InvalidSignature)?;
+        Ok(Signature { r, s })
+    }
+}
@@ -0,0 +1,137 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::schnorr::tagged_hash;
+use tw_hash::H256;
+
+const TAP_LEAF_TAG: &str = "TapLeaf";
+const TAP_BRANCH_TAG: &str = "TapBranch";
+
+/// The leaf version of the BIP-342 tapscript.
+pub const TAPSCRIPT_LEAF_VERSION: u8 = 0xc0;
+
+/// Describes how a taproot output is being spent,
+/// and therefore which key should produce the signature.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TaprootSpendPath {
+    /// P2TR key-path: the signature is produced by the internal key tweaked with the `merkle_root`.
+    /// `merkle_root` should be `None` if the output has no script path.
+    KeyPath { merkle_root: Option<H256> },
+    /// P2TR script-path: the signature is produced by the untweaked key
+    /// that is referenced in the tapscript leaf.
+    /// Note that the leaf hash is committed to by the sighash, so it's not required on signing.
+    ScriptPath,
+}
+
+/// Represents a taproot script tree, see [BIP-341](https://github.com/bitcoin/bips/blob/master/bip-0341.mediawiki#constructing-and-spending-taproot-outputs).
+#[derive(Clone, Debug, PartialEq)]
+pub enum TapTree {
+    /// A script leaf with the given leaf version.
+    Leaf { leaf_version: u8, script: Vec<u8> },
+    /// A branch node that combines two subtrees.
+    Branch(Box<TapTree>, Box<TapTree>),
+}
+
+impl TapTree {
+    /// Creates a tapscript leaf, i.e. with [`TAPSCRIPT_LEAF_VERSION`].
+    pub fn leaf(script: Vec<u8>) -> TapTree {
+        TapTree::Leaf {
+            leaf_version: TAPSCRIPT_LEAF_VERSION,
+            script,
+        }
+    }
+
+    /// Creates a branch node of the given `left` and `right` subtrees.
+    pub fn branch(left: TapTree, right: TapTree) -> TapTree {
+        TapTree::Branch(Box::new(left), Box::new(right))
+    }
+
+    /// Builds a Huffman tree from the given `(weight, subtree)` pairs,
+    /// so the subtrees with higher weights (i.e. more likely to be spent) are closer to the root.
+    /// Returns `None` if `leaves` is empty.
+    pub fn with_huffman(leaves: Vec<(u32, TapTree)>) -> Option<TapTree> {
+        let mut nodes: Vec<(u64, TapTree)> = leaves
+            .into_iter()
+            .map(|(weight, tree)| (weight as u64, tree))
+            .collect();
+
+        loop {
+            // Keep the nodes sorted by weight in descending order, so the lightest nodes are at the end.
+            nodes.sort_by(|(lhs, _), (rhs, _)| rhs.cmp(lhs));
+            let (weight, tree) = nodes.pop()?;
+            let Some((another_weight, another)) = nodes.pop() else {
+                return Some(tree);
+            };
+            nodes.push((weight + another_weight, TapTree::branch(tree, another)));
+        }
+    }
+
+    /// Computes the `TapLeaf` or the `TapBranch` hash of the tree root.
+    pub fn node_hash(&self) -> H256 {
+        match self {
+            TapTree::Leaf {
+                leaf_version,
+                script,
+            } => leaf_hash(*leaf_version, script),
+            TapTree::Branch(left, right) => branch_hash(&left.node_hash(), &right.node_hash()),
+        }
+    }
+
+    /// Computes the merkle root of the tree.
+    /// The result should be passed to `PrivateKey::tweak` or [`TaprootSpendPath::KeyPath`].
+    pub fn merkle_root(&self) -> H256 {
+        self.node_hash()
+    }
+
+    /// Returns the merkle path of the given `leaf_hash` from the leaf to the root,
+    /// i.e. the hashes to be included into the control block on script-path spending.
+    /// Returns `None` if the tree doesn't contain the leaf.
+    pub fn merkle_path(&self, leaf_hash: &H256) -> Option<Vec<H256>> {
+        match self {
+            TapTree::Leaf { .. } => (self.node_hash() == *leaf_hash).then(Vec::new),
+            TapTree::Branch(left, right) => {
+                if let Some(mut path) = left.merkle_path(leaf_hash) {
+                    path.push(right.node_hash());
+                    return Some(path);
+                }
+                let mut path = right.merkle_path(leaf_hash)?;
+                path.push(left.node_hash());
+                Some(path)
+            },
+        }
+    }
+}
+
+/// Computes the tapleaf hash `hash_TapLeaf(leaf_version || compact_size(script) || script)`.
+pub fn leaf_hash(leaf_version: u8, script: &[u8]) -> H256 {
+    let script_len = compact_size(script.len());
+    tagged_hash(
+        TAP_LEAF_TAG,
+        &[[leaf_version].as_slice(), script_len.as_slice(), script],
+    )
+}
+
+/// Computes the tapbranch hash `hash_TapBranch(min(lhs, rhs) || max(lhs, rhs))`.
+pub fn branch_hash(lhs: &H256, rhs: &H256) -> H256 {
+    let (first, second) = if lhs <= rhs { (lhs, rhs) } else { (rhs, lhs) };
+    tagged_hash(TAP_BRANCH_TAG, &[first.as_slice(), second.as_slice()])
+}
+
+/// Encodes the given `len` as the Bitcoin `CompactSize` integer.
+fn compact_size(len: usize) -> Vec<u8> {
+    let len = len as u64;
+    match len {
+        0..=0xfc => vec![len as u8],
+        0xfd..=0xffff => prefixed(0xfd, &(len as u16).to_le_bytes()),
+        0x10000..=0xffff_ffff => prefixed(0xfe, &(len as u32).to_le_bytes()),
+        _ => prefixed(0xff, &len.to_le_bytes()),
+    }
+}
+
+fn prefixed(prefix: u8, bytes: &[u8]) -> Vec<u8> {
+    let mut res = Vec::with_capacity(1 + bytes.len());
+    res.push(prefix);
+    res.extend_from_slice(bytes);
+    res
+}
@@ -0,0 +1,210 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! A fixed-size buffer for secret data, e.g. private keys and seeds.
+//!
+//! The buffer is zeroized on drop. If the `mlock` feature is enabled on a Unix target,
+//! the buffer is also allocated on its own memory pages that are locked via `mlock`,
+//! so the secret can't be swapped to disk, and excluded from core dumps on Linux.
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use zeroize::ZeroizeOnDrop;
+
+/// Represents a fixed-size secret buffer that is zeroized on drop.
+pub struct SecretBytes {
+    buffer: imp::Buffer,
+}
+
+impl SecretBytes {
+    /// Creates a buffer of the given `len` bytes filled with zeros.
+    pub fn zeroed(len: usize) -> SecretBytes {
+        SecretBytes {
+            buffer: imp::Buffer::zeroed(len),
+        }
+    }
+
+    /// Creates a buffer with a copy of the given `bytes`.
+    /// Please note the caller is responsible for zeroizing the original `bytes`.
+    pub fn from_slice(bytes: &[u8]) -> SecretBytes {
+        let mut secret = SecretBytes::zeroed(bytes.len());
+        secret.copy_from_slice(bytes);
+        secret
+    }
+
+    /// Whether the buffer memory is locked, i.e. can't be swapped to disk.
+    /// Always returns `false` if the `mlock` feature is disabled.
+    pub fn is_locked(&self) -> bool {
+        self.buffer.is_locked()
+    }
+}
+
+impl Deref for SecretBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        self.buffer.as_slice()
+    }
+}
+
+impl DerefMut for SecretBytes {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.buffer.as_mut_slice()
+    }
+}
+
+impl fmt::Debug for SecretBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SecretBytes")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+/// [`SecretBytes`] is zeroized by the underlying buffer on drop.
+impl ZeroizeOnDrop for SecretBytes {}
+
+#[cfg(all(feature = "mlock", unix))]
+mod imp {
+    use std::alloc::{alloc_zeroed, dealloc, handle_alloc_error, Layout};
+    use std::ptr::NonNull;
+    use std::slice;
+    use zeroize::Zeroize;
+
+    /// Buffer allocated on dedicated memory pages,
+    /// so unlocking it on drop doesn't unlock memory of another buffer.
+    pub struct Buffer {
+        ptr: NonNull<u8>,
+        len: usize,
+        layout: Layout,
+        locked: bool,
+    }
+
+    // `Buffer` owns its memory exclusively, the same way as `Vec<u8>` does.
+    unsafe impl Send for Buffer {}
+    unsafe impl Sync for Buffer {}
+
+    impl Buffer {
+        pub fn zeroed(len: usize) -> Buffer {
+            let page_size = page_size();
+            let size = len.max(1).next_multiple_of(page_size);
+            let layout =
+                Layout::from_size_align(size, page_size).expect("Page size must be a power of two");
+
+            // Safety: `layout` has a non-zero size.
+            let ptr = unsafe { alloc_zeroed(layout) };
+            let ptr = NonNull::new(ptr).unwrap_or_else(|| handle_alloc_error(layout));
+
+            // Locking may fail due to `RLIMIT_MEMLOCK`, then the secret is kept in regular memory.
+            // Safety: `ptr` points to `size` allocated bytes.
+            let locked = unsafe { libc::mlock(ptr.as_ptr().cast(), size) } == 0;
+            #[cfg(target_os = "linux")]
+            // Safety: `ptr` is page aligned and points to `size` allocated bytes.
+            unsafe {
+                libc::madvise(ptr.as_ptr().cast(), size, libc::MADV_DONTDUMP);
+            }
+
+            Buffer {
+                ptr,
+                len,
+                layout,
+                locked,
+            }
+        }
+
+        pub fn is_locked(&self) -> bool {
+            self.locked
+        }
+
+        pub fn as_slice(&self) -> &[u8] {
+            // Safety: `ptr` points to at least `len` initialized bytes.
+            unsafe { slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+        }
+
+        pub fn as_mut_slice(&mut self) -> &mut [u8] {
+            // Safety: `ptr` points to at least `len` initialized bytes.
+            unsafe { slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+        }
+    }
+
+    impl Drop for Buffer {
+        fn drop(&mut self) {
+            let size = self.layout.size();
+            // Safety: `ptr` points to `size` initialized bytes allocated with `layout`.
+            unsafe {
+                slice::from_raw_parts_mut(self.ptr.as_ptr(), size).zeroize();
+                if self.locked {
+                    libc::munlock(self.ptr.as_ptr().cast(), size);
+                }
+                dealloc(self.ptr.as_ptr(), self.layout);
+            }
+        }
+    }
+
+    fn page_size() -> usize {
+        // Safety: `sysconf` has no preconditions.
+        match unsafe { libc::sysconf(libc::_SC_PAGESIZE) } {
+            size if size > 0 => size as usize,
+            _ => 4096,
+        }
+    }
+}
+
+#[cfg(not(all(feature = "mlock", unix)))]
+mod imp {
+    use zeroize::Zeroizing;
+
+    pub struct Buffer {
+        bytes: Zeroizing<Vec<u8>>,
+    }
+
+    impl Buffer {
+        pub fn zeroed(len: usize) -> Buffer {
+            Buffer {
+                bytes: Zeroizing::new(vec![0; len]),
+            }
+        }
+
+        pub fn is_locked(&self) -> bool {
+            false
+        }
+
+        pub fn as_slice(&self) -> &[u8] {
+            self.bytes.as_slice()
+        }
+
+        pub fn as_mut_slice(&mut self) -> &mut [u8] {
+            self.bytes.as_mut_slice()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_bytes_from_slice() {
+        let secret = SecretBytes::from_slice(&[1, 2, 3]);
+        assert_eq!(&*secret, &[1, 2, 3]);
+
+        let empty = SecretBytes::from_slice(&[]);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_secret_bytes_mut() {
+        let mut secret = SecretBytes::zeroed(4);
+        assert_eq!(&*secret, &[0; 4]);
+
+        secret[3] = 0xff;
+        assert_eq!(&*secret, &[0, 0, 0, 0xff]);
+    }
+
+    #[cfg(not(all(feature = "mlock", unix)))]
+    #[test]
+    fn test_secret_bytes_not_locked() {
+        assert!(!SecretBytes::zeroed(32).is_locked());
+    }
+}
@@ -0,0 +1,187 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! `serde` support for the key and signature types.
+//!
+//! Public keys and signatures are serialized as hex strings without the `0x` prefix.
+//!
+//! Private keys don't implement `Serialize` and `Deserialize` deliberately,
+//! so they can't be persisted by accident. Use [`expose_private_key_hex`] explicitly instead:
+//!
+//! ```rust,ignore
+//! use tw_keypair::ecdsa::secp256k1;
+//!
+//! #[derive(Deserialize, Serialize)]
+//! struct Account {
+//!     #[serde(with = "tw_keypair::serialization::expose_private_key_hex")]
+//!     private: secp256k1::PrivateKey,
+//!     public: secp256k1::PublicKey,
+//! }
+//! ```
+
+use crate::{bls, ecdsa, ed25519, ristretto255, schnorr, sr25519, starkex, zilliqa_schnorr};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use tw_encoding::hex;
+use tw_misc::traits::ToBytesVec;
+
+/// Implements `Serialize` and `Deserialize` for the given types as hex strings.
+macro_rules! impl_serde_as_hex {
+    ($($ty:ty),+ $(,)?) => {
+        $(
+            impl Serialize for $ty {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serializer.serialize_str(&hex::encode(self.to_vec(), false))
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $ty {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    let hex = String::deserialize(deserializer)?;
+                    let bytes = hex::decode(&hex).map_err(|e| Error::custom(format!("{e:?}")))?;
+                    <$ty>::try_from(bytes.as_slice()).map_err(|e| Error::custom(format!("{e:?}")))
+                }
+            }
+        )+
+    };
+}
+
+impl_serde_as_hex!(
+    bls::min_pk::PublicKey,
+    bls::min_pk::Signature,
+    bls::min_sig::PublicKey,
+    bls::min_sig::Signature,
+    ecdsa::nist256p1::PublicKey,
+    ecdsa::nist256p1::Signature,
+    ecdsa::nist256p1::VerifySignature,
+    ecdsa::secp256k1::PublicKey,
+    ecdsa::secp256k1::Signature,
+    ecdsa::secp256k1::VerifySignature,
+    ed25519::Signature,
+    ed25519::blake2b::PublicKey,
+    ed25519::cardano::ExtendedPublicKey,
+    ed25519::keccak512::PublicKey,
+    ed25519::sha512::PublicKey,
+    ed25519::waves::PublicKey,
+    ed25519::waves::Signature,
+    ristretto255::PublicKey,
+    ristretto255::Signature,
+    schnorr::PublicKey,
+    schnorr::Signature,
+    sr25519::PublicKey,
+    sr25519::Signature,
+    starkex::PublicKey,
+    starkex::Signature,
+    zilliqa_schnorr::PublicKey,
+    zilliqa_schnorr::Signature,
+);
+
+/// Serializes a private key as a hex string, see the [module documentation](self).
+///
+/// The intermediate hex strings and byte buffers are zeroized.
+/// Please note the `serde` serializer and deserializer may still copy the secret into their own buffers.
+pub mod expose_private_key_hex {
+    use crate::KeyPairError;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use tw_encoding::hex;
+    use tw_misc::traits::ToBytesZeroizing;
+    use zeroize::Zeroizing;
+
+    pub fn serialize<K, S>(private: &K, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        K: ToBytesZeroizing,
+        S: Serializer,
+    {
+        let hex = Zeroizing::new(hex::encode(private.to_zeroizing_vec().as_slice(), false));
+        serializer.serialize_str(&hex)
+    }
+
+    pub fn deserialize<'de, D, K>(deserializer: D) -> Result<K, D::Error>
+    where
+        D: Deserializer<'de>,
+        K: for<'a> TryFrom<&'a [u8], Error = KeyPairError>,
+    {
+        let hex = Zeroizing::new(String::deserialize(deserializer)?);
+        // Don't include the decoding error as it may contain a part of the secret.
+        let bytes = Zeroizing::new(
+            hex::decode(&hex).map_err(|_| Error::custom("Invalid private key hex"))?,
+        );
+        K::try_from(bytes.as_slice()).map_err(|e| Error::custom(format!("{e:?}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::SigningKeyTrait;
+    use serde_json::json;
+    use tw_misc::traits::ToBytesZeroizing;
+
+    const SECRET: &str = "afeefca74d9a325cf1d6b6911d61a65c32afa8e02bd5e78e2e4ac2910bab45f5";
+
+    #[derive(Deserialize, Serialize)]
+    struct Account {
+        #[serde(with = "expose_private_key_hex")]
+        private: ecdsa::secp256k1::PrivateKey,
+        public: ecdsa::secp256k1::PublicKey,
+    }
+
+    #[test]
+    fn test_public_key_serde() {
+        let public = ed25519::sha512::PrivateKey::try_from(SECRET)
+            .unwrap()
+            .public();
+        let expected = json!("4870d56d074c50e891506d78faa4fb69ca039cc5f131eb491e166b975880e867");
+        assert_eq!(serde_json::to_value(&public).unwrap(), expected);
+
+        let actual: ed25519::sha512::PublicKey = serde_json::from_value(expected).unwrap();
+        assert_eq!(actual.to_vec(), public.to_vec());
+    }
+
+    #[test]
+    fn test_signature_serde() {
+        let private = ed25519::sha512::PrivateKey::try_from(SECRET).unwrap();
+        let signature = private.sign(b"Hello".to_vec()).unwrap();
+
+        let value = serde_json::to_value(&signature).unwrap();
+        assert_eq!(value, json!(hex::encode(signature.to_vec(), false)));
+
+        let actual: ed25519::Signature = serde_json::from_value(value).unwrap();
+        assert_eq!(actual.to_bytes(), signature.to_bytes());
+    }
+
+    #[test]
+    fn test_private_key_serde() {
+        let private = ecdsa::secp256k1::PrivateKey::try_from(SECRET).unwrap();
+        let account = Account {
+            public: private.public(),
+            private,
+        };
+
+        let expected = json!({
+            "private": SECRET,
+            "public": "0399c6f51ad6f98c9c583f8e92bb7758ab2ca9a04110c0a1126ec43e5453d196c1",
+        });
+        assert_eq!(serde_json::to_value(&account).unwrap(), expected);
+
+        let actual: Account = serde_json::from_value(expected).unwrap();
+        assert_eq!(
+            actual.private.to_zeroizing_vec(),
+            account.private.to_zeroizing_vec()
+        );
+        assert_eq!(actual.public.compressed(), account.public.compressed());
+    }
+
+    #[test]
+    fn test_deserialize_invalid() {
+        serde_json::from_value::<schnorr::PublicKey>(json!("0x00")).unwrap_err();
+        serde_json::from_value::<schnorr::Signature>(json!("00")).unwrap_err();
+        let account = serde_json::from_value::<Account>(json!({
+            "private": "zz",
+            "public": "0399c6f51ad6f98c9c583f8e92bb7758ab2ca9a04110c0a1126ec43e5453d196c1",
+        }));
+        assert!(account.is_err());
+    }
+}
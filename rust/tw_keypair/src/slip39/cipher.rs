@@ -0,0 +1,83 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! The 4-round Feistel network that encrypts the master secret with a passphrase,
+//! where the round function is PBKDF2-HMAC-SHA256.
+
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+const ROUND_COUNT: u8 = 4;
+/// The total number of PBKDF2 iterations is `BASE_ITERATION_COUNT << iteration_exponent`.
+const BASE_ITERATION_COUNT: u32 = 10_000;
+/// The salt prefix of non-extendable shares.
+const SALT_PREFIX: &[u8] = b"shamir";
+
+/// The parameters of the share set that the encryption depends on.
+pub(crate) struct CipherParams<'a> {
+    pub(crate) passphrase: &'a [u8],
+    pub(crate) iteration_exponent: u8,
+    pub(crate) identifier: u16,
+    pub(crate) extendable: bool,
+}
+
+pub(crate) fn encrypt(master_secret: &[u8], params: &CipherParams) -> Zeroizing<Vec<u8>> {
+    feistel(master_secret, params, 0..ROUND_COUNT)
+}
+
+pub(crate) fn decrypt(encrypted: &[u8], params: &CipherParams) -> Zeroizing<Vec<u8>> {
+    feistel(encrypted, params, (0..ROUND_COUNT).rev())
+}
+
+fn feistel(
+    data: &[u8],
+    params: &CipherParams,
+    rounds: impl Iterator<Item = u8>,
+) -> Zeroizing<Vec<u8>> {
+    let half = data.len() / 2;
+    let mut left = Zeroizing::new(data[..half].to_vec());
+    let mut right = Zeroizing::new(data[half..].to_vec());
+    let salt = salt(params);
+
+    for round in rounds {
+        let f = round_function(round, params, &salt, &right);
+        for (l, f) in left.iter_mut().zip(f.iter()) {
+            *l ^= *f;
+        }
+        std::mem::swap(&mut left, &mut right);
+    }
+
+    let mut result = right;
+    result.extend_from_slice(&left);
+    result
+}
+
+fn round_function(
+    round: u8,
+    params: &CipherParams,
+    salt: &[u8],
+    right: &[u8],
+) -> Zeroizing<Vec<u8>> {
+    let mut password = Zeroizing::new(Vec::with_capacity(1 + params.passphrase.len()));
+    password.push(round);
+    password.extend_from_slice(params.passphrase);
+
+    let mut round_salt = Zeroizing::new(salt.to_vec());
+    round_salt.extend_from_slice(right);
+
+    let iterations = (BASE_ITERATION_COUNT << params.iteration_exponent) / ROUND_COUNT as u32;
+    let mut output = Zeroizing::new(vec![0; right.len()]);
+    pbkdf2::pbkdf2_hmac::<Sha256>(&password, &round_salt, iterations, &mut output);
+    output
+}
+
+/// Extendable shares don't depend on the identifier, so new shares can be added to the set later.
+fn salt(params: &CipherParams) -> Vec<u8> {
+    if params.extendable {
+        return Vec::new();
+    }
+    let mut salt = SALT_PREFIX.to_vec();
+    salt.extend_from_slice(&params.identifier.to_be_bytes());
+    salt
+}
@@ -0,0 +1,462 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! [SLIP-39](https://github.com/satoshilabs/slips/blob/master/slip-0039.md) Shamir backups of master secrets,
+//! compatible with the Trezor implementation.
+//!
+//! The master secret is encrypted with a passphrase and split into groups,
+//! any `group_threshold` of which recover the secret. The secret of each group is split again
+//! into member shares, any `member_threshold` of which recover the group secret:
+//!
+//! ```rust,ignore
+//! use tw_keypair::slip39::{combine_mnemonics, generate_mnemonics, Group, DEFAULT_ITERATION_EXPONENT};
+//!
+//! // Two of the three groups are required: the owner's, and either the family's or the friends'.
+//! let groups = [
+//!     Group { member_threshold: 1, member_count: 1 },
+//!     Group { member_threshold: 2, member_count: 3 },
+//!     Group { member_threshold: 3, member_count: 5 },
+//! ];
+//! let mnemonics = generate_mnemonics(2, &groups, MASTER_SECRET, "", DEFAULT_ITERATION_EXPONENT, &mut OsRng)?;
+//!
+//! let master_secret = combine_mnemonics(&[&mnemonics[0][0], &mnemonics[1][0], &mnemonics[1][2]], "")?;
+//! ```
+//!
+//! Any passphrase recovers a valid master secret, so a wrong passphrase can't be detected.
+
+use crate::slip39::cipher::{decrypt, encrypt, CipherParams};
+use crate::slip39::shamir::{recover_secret, split_secret, RawShare};
+use crate::{KeyPairError, KeyPairResult};
+use rand_core::{CryptoRng, RngCore};
+use std::collections::BTreeMap;
+use zeroize::Zeroizing;
+
+mod cipher;
+mod rs1024;
+mod shamir;
+mod share;
+mod wordlist;
+
+pub use share::Share;
+
+/// The maximum number of groups, and of member shares in a group.
+pub const MAX_SHARE_COUNT: usize = 16;
+/// The iteration exponent that Trezor uses by default.
+pub const DEFAULT_ITERATION_EXPONENT: u8 = 1;
+pub const MAX_ITERATION_EXPONENT: u8 = 15;
+/// The master secret must be at least 128 bits long.
+pub const MIN_SECRET_LEN: usize = 16;
+
+/// The identifier is 15 bits long.
+const IDENTIFIER_MASK: u32 = 0x7FFF;
+
+/// The member shares of a group, any `member_threshold` of which recover the group secret.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Group {
+    pub member_threshold: u8,
+    pub member_count: u8,
+}
+
+/// Splits the given `master_secret` into extendable share mnemonics of the given `groups`,
+/// any `group_threshold` of which recover the master secret with the given `passphrase`.
+///
+/// The `passphrase` can be empty, and must consist of printable ASCII characters.
+/// Returns the member share mnemonics of each group.
+pub fn generate_mnemonics(
+    group_threshold: u8,
+    groups: &[Group],
+    master_secret: &[u8],
+    passphrase: &str,
+    iteration_exponent: u8,
+    rng: &mut (impl CryptoRng + RngCore),
+) -> KeyPairResult<Vec<Vec<Zeroizing<String>>>> {
+    if master_secret.len() < MIN_SECRET_LEN || master_secret.len() % 2 != 0 {
+        return Err(KeyPairError::InvalidSecretKey);
+    }
+    validate_passphrase(passphrase)?;
+    if iteration_exponent > MAX_ITERATION_EXPONENT
+        || groups.is_empty()
+        || groups.len() > MAX_SHARE_COUNT
+        || group_threshold == 0
+        || group_threshold as usize > groups.len()
+    {
+        return Err(KeyPairError::InvalidMnemonic);
+    }
+    // Multiple member shares with the threshold of 1 would be copies of the same share.
+    if groups
+        .iter()
+        .any(|group| group.member_threshold == 1 && group.member_count > 1)
+    {
+        return Err(KeyPairError::InvalidMnemonic);
+    }
+
+    let identifier = (rng.next_u32() & IDENTIFIER_MASK) as u16;
+    let params = CipherParams {
+        passphrase: passphrase.as_bytes(),
+        iteration_exponent,
+        identifier,
+        extendable: true,
+    };
+    let encrypted = encrypt(master_secret, &params);
+
+    let group_count = groups.len() as u8;
+    let group_shares = split_secret(group_threshold, group_count, &encrypted, rng)?;
+    groups
+        .iter()
+        .zip(group_shares)
+        .map(|(group, group_share)| {
+            let members = split_secret(
+                group.member_threshold,
+                group.member_count,
+                &group_share.y,
+                rng,
+            )?;
+            Ok(members
+                .into_iter()
+                .map(|member| {
+                    Share {
+                        identifier,
+                        extendable: params.extendable,
+                        iteration_exponent,
+                        group_index: group_share.x,
+                        group_threshold,
+                        group_count,
+                        member_index: member.x,
+                        member_threshold: group.member_threshold,
+                        value: member.y,
+                    }
+                    .to_mnemonic()
+                })
+                .collect())
+        })
+        .collect()
+}
+
+/// Recovers the master secret from the given share `mnemonics` and `passphrase`.
+///
+/// The `mnemonics` must contain exactly `member_threshold` shares of exactly `group_threshold` groups.
+pub fn combine_mnemonics(
+    mnemonics: &[&str],
+    passphrase: &str,
+) -> KeyPairResult<Zeroizing<Vec<u8>>> {
+    validate_passphrase(passphrase)?;
+    let shares = mnemonics
+        .iter()
+        .map(|mnemonic| Share::parse(mnemonic))
+        .collect::<KeyPairResult<Vec<_>>>()?;
+    let first = shares.first().ok_or(KeyPairError::InvalidMnemonic)?;
+
+    let mismatch = shares.iter().any(|share| {
+        share.identifier != first.identifier
+            || share.extendable != first.extendable
+            || share.iteration_exponent != first.iteration_exponent
+            || share.group_threshold != first.group_threshold
+            || share.group_count != first.group_count
+            || share.group_index >= share.group_count
+            || share.value.len() != first.value.len()
+    });
+    if mismatch {
+        return Err(KeyPairError::InvalidMnemonic);
+    }
+
+    let mut groups: BTreeMap<u8, Vec<&Share>> = BTreeMap::new();
+    for share in shares.iter() {
+        groups.entry(share.group_index).or_default().push(share);
+    }
+    if groups.len() != first.group_threshold as usize {
+        return Err(KeyPairError::InvalidMnemonic);
+    }
+
+    let group_shares = groups
+        .into_iter()
+        .map(|(group_index, members)| {
+            Ok(RawShare {
+                x: group_index,
+                y: recover_group_secret(&members)?,
+            })
+        })
+        .collect::<KeyPairResult<Vec<_>>>()?;
+    let encrypted = recover_secret(first.group_threshold, &group_shares)?;
+
+    let params = CipherParams {
+        passphrase: passphrase.as_bytes(),
+        iteration_exponent: first.iteration_exponent,
+        identifier: first.identifier,
+        extendable: first.extendable,
+    };
+    Ok(decrypt(&encrypted, &params))
+}
+
+fn recover_group_secret(members: &[&Share]) -> KeyPairResult<Zeroizing<Vec<u8>>> {
+    let member_threshold = members
+        .first()
+        .ok_or(KeyPairError::InvalidMnemonic)?
+        .member_threshold;
+    if members.len() != member_threshold as usize
+        || members
+            .iter()
+            .any(|member| member.member_threshold != member_threshold)
+    {
+        return Err(KeyPairError::InvalidMnemonic);
+    }
+
+    let mut indices: Vec<_> = members.iter().map(|member| member.member_index).collect();
+    indices.sort_unstable();
+    indices.dedup();
+    if indices.len() != members.len() {
+        return Err(KeyPairError::InvalidMnemonic);
+    }
+
+    let raw_shares: Vec<_> = members
+        .iter()
+        .map(|member| RawShare {
+            x: member.member_index,
+            y: member.value.clone(),
+        })
+        .collect();
+    recover_secret(member_threshold, &raw_shares)
+}
+
+fn validate_passphrase(passphrase: &str) -> KeyPairResult<()> {
+    if passphrase.bytes().all(|byte| (0x20..=0x7E).contains(&byte)) {
+        Ok(())
+    } else {
+        Err(KeyPairError::InvalidMnemonic)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_core::OsRng;
+    use tw_encoding::hex;
+
+    const PASSPHRASE: &str = "TREZOR";
+
+    /// https://github.com/trezor/python-shamir-mnemonic/blob/master/vectors.json
+    #[test]
+    fn test_combine_trezor_vectors() {
+        let mnemonic = "duckling enlarge academic academic agency result length solution fridge kidney coal piece deal husband erode duke ajar critical decision keyboard";
+        let secret = combine_mnemonics(&[mnemonic], PASSPHRASE).unwrap();
+        assert_eq!(
+            hex::encode(secret.as_slice(), false),
+            "bb54aac4b89dc868ba37d9cc21b2cece"
+        );
+
+        let mnemonics = [
+            "shadow pistol academic always adequate wildlife fancy gross oasis cylinder mustang wrist rescue view short owner flip making coding armed",
+            "shadow pistol academic acid actress prayer class unknown daughter sweater depict flip twice unkind craft early superior advocate guest smoking",
+        ];
+        let secret = combine_mnemonics(&mnemonics, PASSPHRASE).unwrap();
+        assert_eq!(
+            hex::encode(secret.as_slice(), false),
+            "b43ceb7e57a0ea8766221624d01b0864"
+        );
+    }
+
+    #[test]
+    fn test_combine_groups() {
+        // 2 of the groups `1-of-1`, `2-of-3` and `3-of-5`, iteration exponent `0`.
+        let owner = "party busy acrobat leader carbon glen aide bolt lunch rainbow smell medical slavery diagnose numb agency mailman focus pajamas spelling";
+        let family = [
+            "party busy beard leaf adapt pregnant fangs ting cinema charity deny timber fake estate glasses emperor hour regret puny cover",
+            "party busy beard lily amazing capacity acrobat upstairs prepare graduate strategy earth keyboard undergo pajamas hairy cinema penalty lilac actress",
+            "party busy beard lungs best spit pacific juice drift preach marathon usher march careful tendency retailer device freshman rapids ancestor",
+        ];
+        let friends = [
+            "party busy ceramic learn burden evaluate raspy cleanup victim medal silver voting theory quarter hand aquatic slush scout camera birthday",
+            "party busy ceramic lips cage escape briefing prospect result acquire judicial forecast sheriff smith walnut language extra ajar drink fitness",
+            "party busy ceramic luxury diminish rapids cradle upstairs spray dive brother guitar mobile tenant wrote include estate switch river lobe",
+            "party busy ceramic march debris receiver loud golden orbit receiver predator scholar relate orbit huge actress spend camera paces scholar",
+            "party busy ceramic method adjust junk visual fragment regular argue security dwarf edge machine mustang briefing pleasure lair uncover emphasis",
+        ];
+        let expected = "0102030405060708090a0b0c0d0e0f10";
+
+        let secret = combine_mnemonics(&[owner, family[0], family[2]], PASSPHRASE).unwrap();
+        assert_eq!(hex::encode(secret.as_slice(), false), expected);
+
+        let mnemonics = [family[1], friends[4], family[2], friends[0], friends[3]];
+        let secret = combine_mnemonics(&mnemonics, PASSPHRASE).unwrap();
+        assert_eq!(hex::encode(secret.as_slice(), false), expected);
+
+        // A wrong passphrase results in a different master secret.
+        let secret = combine_mnemonics(&[owner, family[0], family[2]], "").unwrap();
+        assert_eq!(
+            hex::encode(secret.as_slice(), false),
+            "1b10b737d22af6cddd43f6f1cda24b68"
+        );
+
+        // Not enough groups.
+        let err = combine_mnemonics(&[family[0], family[1]], PASSPHRASE).unwrap_err();
+        assert_eq!(err, KeyPairError::InvalidMnemonic);
+        // Not enough members.
+        let err = combine_mnemonics(&[owner, family[0]], PASSPHRASE).unwrap_err();
+        assert_eq!(err, KeyPairError::InvalidMnemonic);
+        // Too many members.
+        let mnemonics = [owner, family[0], family[1], family[2]];
+        let err = combine_mnemonics(&mnemonics, PASSPHRASE).unwrap_err();
+        assert_eq!(err, KeyPairError::InvalidMnemonic);
+        // The same member twice.
+        let err = combine_mnemonics(&[owner, family[0], family[0]], PASSPHRASE).unwrap_err();
+        assert_eq!(err, KeyPairError::InvalidMnemonic);
+        // Shares of different sets.
+        let other = "duckling enlarge academic academic agency result length solution fridge kidney coal piece deal husband erode duke ajar critical decision keyboard";
+        let err = combine_mnemonics(&[owner, family[0], other], PASSPHRASE).unwrap_err();
+        assert_eq!(err, KeyPairError::InvalidMnemonic);
+    }
+
+    #[test]
+    fn test_share_parse() {
+        let mnemonic = "party busy ceramic march debris receiver loud golden orbit receiver predator scholar relate orbit huge actress spend camera paces scholar";
+        let share = Share::parse(mnemonic).unwrap();
+        assert_eq!(share.identifier(), 20739);
+        assert!(share.extendable());
+        assert_eq!(share.iteration_exponent(), 0);
+        assert_eq!(share.group_index(), 2);
+        assert_eq!(share.group_threshold(), 2);
+        assert_eq!(share.group_count(), 3);
+        assert_eq!(share.member_index(), 3);
+        assert_eq!(share.member_threshold(), 3);
+        assert_eq!(share.to_mnemonic().as_str(), mnemonic);
+
+        // Upper case words are accepted.
+        let share = Share::parse(&mnemonic.to_uppercase()).unwrap();
+        assert_eq!(share.to_mnemonic().as_str(), mnemonic);
+    }
+
+    #[test]
+    fn test_share_parse_invalid() {
+        // Invalid checksum.
+        let mnemonic = "duckling enlarge academic academic agency result length solution fridge kidney coal piece deal husband erode duke ajar critical decision kidney";
+        assert_eq!(
+            Share::parse(mnemonic).err(),
+            Some(KeyPairError::InvalidMnemonic)
+        );
+        // Unknown word.
+        let mnemonic = "duckling enlarge academic academic agency result length solution fridge kidney coal piece deal husband erode duke ajar critical decision keyboards";
+        assert_eq!(
+            Share::parse(mnemonic).err(),
+            Some(KeyPairError::InvalidMnemonic)
+        );
+        // Too short.
+        let mnemonic = "duckling enlarge academic academic agency result length solution fridge kidney coal piece deal husband erode duke ajar critical decision";
+        assert_eq!(
+            Share::parse(mnemonic).err(),
+            Some(KeyPairError::InvalidMnemonic)
+        );
+        assert_eq!(Share::parse("").err(), Some(KeyPairError::InvalidMnemonic));
+    }
+
+    #[test]
+    fn test_generate_combine() {
+        let groups = [
+            Group {
+                member_threshold: 1,
+                member_count: 1,
+            },
+            Group {
+                member_threshold: 2,
+                member_count: 3,
+            },
+            Group {
+                member_threshold: 3,
+                member_count: 5,
+            },
+        ];
+
+        for secret_len in [16, 32] {
+            let master_secret: Vec<u8> = (0..secret_len).collect();
+            let mnemonics =
+                generate_mnemonics(2, &groups, &master_secret, PASSPHRASE, 0, &mut OsRng).unwrap();
+            assert_eq!(mnemonics.len(), 3);
+            for (group, members) in groups.iter().zip(mnemonics.iter()) {
+                assert_eq!(members.len(), group.member_count as usize);
+            }
+
+            let selected = [
+                mnemonics[1][1].as_str(),
+                mnemonics[2][4].as_str(),
+                mnemonics[2][0].as_str(),
+                mnemonics[1][2].as_str(),
+                mnemonics[2][2].as_str(),
+            ];
+            let secret = combine_mnemonics(&selected, PASSPHRASE).unwrap();
+            assert_eq!(secret.as_slice(), master_secret.as_slice());
+
+            let share = Share::parse(&mnemonics[0][0]).unwrap();
+            assert!(share.extendable());
+            assert_eq!(share.group_threshold(), 2);
+        }
+    }
+
+    #[test]
+    fn test_generate_invalid() {
+        let master_secret = [1; 16];
+        let single = [Group {
+            member_threshold: 1,
+            member_count: 1,
+        }];
+        let generate = |threshold, groups: &[Group], secret: &[u8], passphrase| {
+            generate_mnemonics(threshold, groups, secret, passphrase, 0, &mut OsRng).unwrap_err()
+        };
+
+        assert_eq!(
+            generate(1, &single, &[1; 15], ""),
+            KeyPairError::InvalidSecretKey
+        );
+        assert_eq!(
+            generate(1, &single, &[1; 17], ""),
+            KeyPairError::InvalidSecretKey
+        );
+        assert_eq!(
+            generate(0, &single, &master_secret, ""),
+            KeyPairError::InvalidMnemonic
+        );
+        assert_eq!(
+            generate(2, &single, &master_secret, ""),
+            KeyPairError::InvalidMnemonic
+        );
+        assert_eq!(
+            generate(1, &[], &master_secret, ""),
+            KeyPairError::InvalidMnemonic
+        );
+        // Non-printable or non-ASCII passphrase.
+        assert_eq!(
+            generate(1, &single, &master_secret, "caf\u{e9}"),
+            KeyPairError::InvalidMnemonic
+        );
+        assert_eq!(
+            generate(1, &single, &master_secret, "\n"),
+            KeyPairError::InvalidMnemonic
+        );
+
+        let copies = [Group {
+            member_threshold: 1,
+            member_count: 2,
+        }];
+        assert_eq!(
+            generate(1, &copies, &master_secret, ""),
+            KeyPairError::InvalidMnemonic
+        );
+        let too_many = [Group {
+            member_threshold: 2,
+            member_count: 17,
+        }];
+        assert_eq!(
+            generate(1, &too_many, &master_secret, ""),
+            KeyPairError::InvalidMnemonic
+        );
+        let unreachable = [Group {
+            member_threshold: 3,
+            member_count: 2,
+        }];
+        assert_eq!(
+            generate(1, &unreachable, &master_secret, ""),
+            KeyPairError::InvalidMnemonic
+        );
+
+        let err = generate_mnemonics(1, &single, &master_secret, "", 16, &mut OsRng).unwrap_err();
+        assert_eq!(err, KeyPairError::InvalidMnemonic);
+    }
+}
@@ -0,0 +1,50 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! The Reed-Solomon code over `GF(1024)` that protects a share mnemonic against up to 3 word errors.
+
+/// The length of the checksum in words.
+pub(crate) const CHECKSUM_WORDS: usize = 3;
+
+const GENERATOR: [u32; 10] = [
+    0xE0E040, 0x1C1C080, 0x3838100, 0x7070200, 0xE0E0009, 0x1C0C2412, 0x38086C24, 0x3090FC48,
+    0x21B1F890, 0x3F3F120,
+];
+
+/// Returns the checksum words of the given `words` with the given `customization` string.
+pub(crate) fn create_checksum(customization: &[u8], words: &[u16]) -> [u16; CHECKSUM_WORDS] {
+    let values = customization
+        .iter()
+        .map(|byte| *byte as u16)
+        .chain(words.iter().copied())
+        .chain([0; CHECKSUM_WORDS]);
+    let polymod = polymod(values) ^ 1;
+
+    let mut checksum = [0; CHECKSUM_WORDS];
+    for (i, word) in checksum.iter_mut().enumerate() {
+        *word = ((polymod >> (10 * (CHECKSUM_WORDS - 1 - i))) & 1023) as u16;
+    }
+    checksum
+}
+
+/// Checks whether the given `words` end with a valid checksum.
+pub(crate) fn verify_checksum(customization: &[u8], words: &[u16]) -> bool {
+    let values = customization
+        .iter()
+        .map(|byte| *byte as u16)
+        .chain(words.iter().copied());
+    polymod(values) == 1
+}
+
+fn polymod(values: impl Iterator<Item = u16>) -> u32 {
+    values.fold(1, |chk, value| {
+        let top = chk >> 20;
+        let chk = ((chk & 0xFFFFF) << 10) ^ value as u32;
+        GENERATOR
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| (top >> i) & 1 == 1)
+            .fold(chk, |chk, (_, generator)| chk ^ generator)
+    })
+}
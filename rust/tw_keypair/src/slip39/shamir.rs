@@ -0,0 +1,160 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Shamir's secret sharing over `GF(256)` with the Rijndael polynomial `x^8 + x^4 + x^3 + x + 1`.
+//!
+//! The field arithmetic is implemented without lookup tables, so it's constant time in the secret bytes.
+
+use crate::slip39::MAX_SHARE_COUNT;
+use crate::{KeyPairError, KeyPairResult};
+use rand_core::{CryptoRng, RngCore};
+use tw_hash::hmac::hmac_sha256;
+use zeroize::Zeroizing;
+
+/// The `x` coordinate of the share that holds the digest of the secret.
+const DIGEST_INDEX: u8 = 254;
+/// The `x` coordinate of the share that holds the secret itself.
+const SECRET_INDEX: u8 = 255;
+/// The length of the digest that verifies the recovered secret.
+const DIGEST_LEN: usize = 4;
+
+/// A point `(x, y)` of the polynomials that share the secret bytes.
+pub(crate) struct RawShare {
+    pub(crate) x: u8,
+    pub(crate) y: Zeroizing<Vec<u8>>,
+}
+
+/// Splits the given `secret` into `share_count` shares, any `threshold` of which recover the secret.
+pub(crate) fn split_secret(
+    threshold: u8,
+    share_count: u8,
+    secret: &[u8],
+    rng: &mut (impl CryptoRng + RngCore),
+) -> KeyPairResult<Vec<RawShare>> {
+    if threshold == 0 || threshold > share_count || share_count as usize > MAX_SHARE_COUNT {
+        return Err(KeyPairError::InvalidMnemonic);
+    }
+    if secret.len() <= DIGEST_LEN {
+        return Err(KeyPairError::InvalidSecretKey);
+    }
+
+    if threshold == 1 {
+        return Ok((0..share_count)
+            .map(|x| RawShare {
+                x,
+                y: Zeroizing::new(secret.to_vec()),
+            })
+            .collect());
+    }
+
+    // `threshold - 2` random shares, the digest share and the secret share define the polynomials.
+    let random_share_count = threshold - 2;
+    let mut shares: Vec<_> = (0..random_share_count)
+        .map(|x| {
+            let mut y = Zeroizing::new(vec![0; secret.len()]);
+            rng.fill_bytes(&mut y);
+            RawShare { x, y }
+        })
+        .collect();
+
+    let mut random_part = Zeroizing::new(vec![0; secret.len() - DIGEST_LEN]);
+    rng.fill_bytes(&mut random_part);
+    let mut digest_share = Zeroizing::new(digest(&random_part, secret).to_vec());
+    digest_share.extend_from_slice(&random_part);
+
+    let mut base_shares: Vec<_> = shares
+        .iter()
+        .map(|share| RawShare {
+            x: share.x,
+            y: share.y.clone(),
+        })
+        .collect();
+    base_shares.push(RawShare {
+        x: DIGEST_INDEX,
+        y: digest_share,
+    });
+    base_shares.push(RawShare {
+        x: SECRET_INDEX,
+        y: Zeroizing::new(secret.to_vec()),
+    });
+
+    for x in random_share_count..share_count {
+        shares.push(RawShare {
+            x,
+            y: interpolate(&base_shares, x),
+        });
+    }
+    Ok(shares)
+}
+
+/// Recovers the secret from exactly `threshold` shares with distinct `x` coordinates,
+/// and verifies it against the digest share.
+pub(crate) fn recover_secret(
+    threshold: u8,
+    shares: &[RawShare],
+) -> KeyPairResult<Zeroizing<Vec<u8>>> {
+    let first = shares.first().ok_or(KeyPairError::InvalidMnemonic)?;
+    if threshold == 1 {
+        return Ok(first.y.clone());
+    }
+
+    let secret = interpolate(shares, SECRET_INDEX);
+    let digest_share = interpolate(shares, DIGEST_INDEX);
+    let (expected, random_part) = digest_share.split_at(DIGEST_LEN);
+    if digest(random_part, &secret).as_slice() != expected {
+        return Err(KeyPairError::InvalidMnemonic);
+    }
+    Ok(secret)
+}
+
+fn digest(random_part: &[u8], secret: &[u8]) -> [u8; DIGEST_LEN] {
+    let mac = Zeroizing::new(hmac_sha256(random_part, secret));
+    let mut digest = [0; DIGEST_LEN];
+    digest.copy_from_slice(&mac[..DIGEST_LEN]);
+    digest
+}
+
+/// Evaluates the Lagrange polynomials through the given `shares` at `x`.
+/// The `x` coordinates are public, so only the multiplication by the `y` bytes needs to be constant time.
+fn interpolate(shares: &[RawShare], x: u8) -> Zeroizing<Vec<u8>> {
+    let len = shares.first().map_or(0, |share| share.y.len());
+    let mut result = Zeroizing::new(vec![0; len]);
+
+    for (i, share) in shares.iter().enumerate() {
+        // `basis = Π (x - x_j) / (x_i - x_j)` where the subtraction is XOR.
+        let basis = shares
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != i)
+            .fold(1, |basis, (_, other)| {
+                gf_mul(basis, gf_mul(x ^ other.x, gf_inv(share.x ^ other.x)))
+            });
+        for (res, y) in result.iter_mut().zip(share.y.iter()) {
+            *res ^= gf_mul(basis, *y);
+        }
+    }
+    result
+}
+
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut res = 0;
+    for _ in 0..8 {
+        res ^= a & 0u8.wrapping_sub(b & 1);
+        let carry = 0u8.wrapping_sub(a >> 7);
+        a = (a << 1) ^ (0x1b & carry);
+        b >>= 1;
+    }
+    res
+}
+
+/// Returns `a^254`, i.e. the multiplicative inverse of a non-zero `a`.
+fn gf_inv(a: u8) -> u8 {
+    let mut res = 1;
+    let mut power = a;
+    for _ in 0..7 {
+        power = gf_mul(power, power);
+        res = gf_mul(res, power);
+    }
+    res
+}
@@ -0,0 +1,212 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::slip39::rs1024::{create_checksum, verify_checksum, CHECKSUM_WORDS};
+use crate::slip39::wordlist::WORDLIST;
+use crate::{KeyPairError, KeyPairResult};
+use zeroize::Zeroizing;
+
+const RADIX_BITS: usize = 10;
+const RADIX_MASK: u32 = (1 << RADIX_BITS) - 1;
+/// `identifier (15 bits) || extendable (1 bit) || iteration_exponent (4 bits)`,
+/// then `group_index || group_threshold || group_count || member_index || member_threshold` (4 bits each).
+const HEADER_WORDS: usize = 4;
+/// The shortest mnemonic encodes a 128 bit share value.
+const MIN_MNEMONIC_WORDS: usize = HEADER_WORDS + 13 + CHECKSUM_WORDS;
+/// The checksum customization strings.
+const CUSTOMIZATION: &[u8] = b"shamir";
+const CUSTOMIZATION_EXTENDABLE: &[u8] = b"shamir_extendable";
+
+/// A single SLIP-39 share, i.e. a member share of a group.
+pub struct Share {
+    pub(crate) identifier: u16,
+    pub(crate) extendable: bool,
+    pub(crate) iteration_exponent: u8,
+    pub(crate) group_index: u8,
+    pub(crate) group_threshold: u8,
+    pub(crate) group_count: u8,
+    pub(crate) member_index: u8,
+    pub(crate) member_threshold: u8,
+    pub(crate) value: Zeroizing<Vec<u8>>,
+}
+
+impl Share {
+    /// Parses a share mnemonic and verifies its checksum.
+    pub fn parse(mnemonic: &str) -> KeyPairResult<Share> {
+        let words = mnemonic
+            .split_whitespace()
+            .map(word_index)
+            .collect::<KeyPairResult<Vec<u16>>>()
+            .map(Zeroizing::new)?;
+        if words.len() < MIN_MNEMONIC_WORDS {
+            return Err(KeyPairError::InvalidMnemonic);
+        }
+        // The share value is a whole number of 16 bit blocks.
+        let padding = (RADIX_BITS * (words.len() - HEADER_WORDS - CHECKSUM_WORDS)) % 16;
+        if padding > 8 {
+            return Err(KeyPairError::InvalidMnemonic);
+        }
+
+        let id_exp = (words[0] as u32) << RADIX_BITS | words[1] as u32;
+        let extendable = (id_exp >> 4) & 1 == 1;
+        if !verify_checksum(customization(extendable), &words) {
+            return Err(KeyPairError::InvalidMnemonic);
+        }
+
+        let params = (words[2] as u32) << RADIX_BITS | words[3] as u32;
+        let nibble = |shift: u32| ((params >> shift) & 0xF) as u8;
+        let share = Share {
+            identifier: (id_exp >> 5) as u16,
+            extendable,
+            iteration_exponent: (id_exp & 0xF) as u8,
+            group_index: nibble(16),
+            group_threshold: nibble(12) + 1,
+            group_count: nibble(8) + 1,
+            member_index: nibble(4),
+            member_threshold: nibble(0) + 1,
+            value: decode_value(&words[HEADER_WORDS..words.len() - CHECKSUM_WORDS], padding)?,
+        };
+        if share.group_threshold > share.group_count {
+            return Err(KeyPairError::InvalidMnemonic);
+        }
+        Ok(share)
+    }
+
+    /// Encodes the share as a mnemonic.
+    pub fn to_mnemonic(&self) -> Zeroizing<String> {
+        let id_exp = (self.identifier as u32) << 5
+            | (self.extendable as u32) << 4
+            | self.iteration_exponent as u32;
+        let params = (self.group_index as u32) << 16
+            | (self.group_threshold as u32 - 1) << 12
+            | (self.group_count as u32 - 1) << 8
+            | (self.member_index as u32) << 4
+            | (self.member_threshold as u32 - 1);
+
+        let mut words = Zeroizing::new(vec![
+            (id_exp >> RADIX_BITS) as u16,
+            (id_exp & RADIX_MASK) as u16,
+            (params >> RADIX_BITS) as u16,
+            (params & RADIX_MASK) as u16,
+        ]);
+        words.extend_from_slice(&encode_value(&self.value));
+        let checksum = create_checksum(customization(self.extendable), &words);
+        words.extend_from_slice(&checksum);
+
+        let mut mnemonic = Zeroizing::new(String::new());
+        for (i, word) in words.iter().enumerate() {
+            if i > 0 {
+                mnemonic.push(' ');
+            }
+            mnemonic.push_str(WORDLIST[*word as usize]);
+        }
+        mnemonic
+    }
+
+    /// Returns the random identifier that is common to all shares of the set.
+    pub fn identifier(&self) -> u16 {
+        self.identifier
+    }
+
+    /// Returns whether further shares can be added to the set without changing the identifier.
+    pub fn extendable(&self) -> bool {
+        self.extendable
+    }
+
+    /// Returns the exponent of the PBKDF2 iteration count of the encryption.
+    pub fn iteration_exponent(&self) -> u8 {
+        self.iteration_exponent
+    }
+
+    /// Returns the index of the share's group.
+    pub fn group_index(&self) -> u8 {
+        self.group_index
+    }
+
+    /// Returns the number of groups required to recover the master secret.
+    pub fn group_threshold(&self) -> u8 {
+        self.group_threshold
+    }
+
+    /// Returns the total number of groups.
+    pub fn group_count(&self) -> u8 {
+        self.group_count
+    }
+
+    /// Returns the index of the share in its group.
+    pub fn member_index(&self) -> u8 {
+        self.member_index
+    }
+
+    /// Returns the number of member shares required to recover the group secret.
+    pub fn member_threshold(&self) -> u8 {
+        self.member_threshold
+    }
+}
+
+fn customization(extendable: bool) -> &'static [u8] {
+    if extendable {
+        CUSTOMIZATION_EXTENDABLE
+    } else {
+        CUSTOMIZATION
+    }
+}
+
+/// Returns the index of the given case-insensitive `word` in the sorted wordlist.
+fn word_index(word: &str) -> KeyPairResult<u16> {
+    WORDLIST
+        .binary_search_by(|candidate| {
+            candidate
+                .bytes()
+                .cmp(word.bytes().map(|byte| byte.to_ascii_lowercase()))
+        })
+        .map(|index| index as u16)
+        .map_err(|_| KeyPairError::InvalidMnemonic)
+}
+
+/// Encodes the `value` bytes as 10 bit words, with leading zero bits as padding.
+fn encode_value(value: &[u8]) -> Zeroizing<Vec<u16>> {
+    let bits = value.len() * 8;
+    let padding = (RADIX_BITS - bits % RADIX_BITS) % RADIX_BITS;
+
+    let mut words = Zeroizing::new(Vec::with_capacity((bits + padding) / RADIX_BITS));
+    let mut acc = Zeroizing::new(0u32);
+    let mut acc_bits = padding;
+    for byte in value {
+        *acc = (*acc << 8) | *byte as u32;
+        acc_bits += 8;
+        if acc_bits >= RADIX_BITS {
+            acc_bits -= RADIX_BITS;
+            words.push(((*acc >> acc_bits) & RADIX_MASK) as u16);
+            *acc &= (1 << acc_bits) - 1;
+        }
+    }
+    words
+}
+
+/// Decodes the 10 bit `words` to bytes, skipping the given number of leading `padding` bits,
+/// which must be zero.
+fn decode_value(words: &[u16], padding: usize) -> KeyPairResult<Zeroizing<Vec<u8>>> {
+    let mut value = Zeroizing::new(Vec::with_capacity((words.len() * RADIX_BITS - padding) / 8));
+    let mut acc = Zeroizing::new(0u32);
+    let mut acc_bits = 0;
+    let mut skip = padding;
+    for word in words {
+        *acc = (*acc << RADIX_BITS) | *word as u32;
+        acc_bits += RADIX_BITS;
+        if skip > 0 {
+            if *acc >> (acc_bits - skip) != 0 {
+                return Err(KeyPairError::InvalidMnemonic);
+            }
+            acc_bits -= skip;
+            skip = 0;
+        }
+        while acc_bits >= 8 {
+            acc_bits -= 8;
+            value.push((*acc >> acc_bits) as u8);
+            *acc &= (1 << acc_bits) - 1;
+        }
+    }
+    Ok(value)
+}
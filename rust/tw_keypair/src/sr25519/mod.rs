@@ -0,0 +1,142 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! `sr25519` Schnorr signatures over the Ristretto group that are used by Substrate based chains.
+
+mod keypair;
+mod private;
+mod public;
+mod signature;
+
+pub use keypair::KeyPair;
+pub use private::PrivateKey;
+pub use public::PublicKey;
+pub use signature::Signature;
+
+/// The signing context that is used by Substrate based chains (Polkadot, Kusama etc).
+pub const SUBSTRATE_CONTEXT: &[u8] = b"substrate";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::{KeyPairTrait, SigningKeyTrait, VerifyingKeyTrait};
+    use rand_core::OsRng;
+    use tw_encoding::hex;
+    use tw_hash::H256;
+    use tw_misc::traits::{ToBytesVec, ToBytesZeroizing};
+
+    const SECRET: &str = "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60";
+    const PUBLIC: &str = "44a996beb1eef7bdcab976ab6d2ca26104834164ecf28fb375600576fcc6eb0f";
+
+    #[test]
+    fn test_private_from_bytes() {
+        let private = PrivateKey::try_from(SECRET).unwrap();
+        let actual = private.to_zeroizing_vec();
+        assert_eq!(actual.as_slice(), H256::from(SECRET).as_slice());
+    }
+
+    #[test]
+    fn test_private_to_public() {
+        let private = PrivateKey::try_from(SECRET).unwrap();
+        assert_eq!(private.public().to_bytes(), H256::from(PUBLIC));
+
+        // Taken from the `subkey` documentation.
+        let private = PrivateKey::try_from(
+            "fac7959dbfe72f052e5a0c3c8d6530f202b02fd8f9f5ca3580ec8deb7797479e",
+        )
+        .unwrap();
+        let expected =
+            H256::from("46ebddef8cd9bb167dc30878d7113b7e168e6f0646beffd77d69d39bad76b47a");
+        assert_eq!(private.public().to_vec(), expected.into_vec());
+    }
+
+    #[test]
+    fn test_keypair_sign_verify() {
+        let keypair = KeyPair::try_from(SECRET).unwrap();
+        let message = b"Hello".to_vec();
+
+        let signature = keypair.sign(message.clone()).unwrap();
+        assert!(keypair.verify(signature.clone(), message.clone()));
+        assert!(keypair.public().verify(signature.clone(), message));
+        assert!(!keypair.verify(signature, b"Hello!".to_vec()));
+    }
+
+    #[test]
+    fn test_sign_with_context() {
+        let private = PrivateKey::try_from(SECRET).unwrap();
+        let public = private.public();
+        let message = b"Hello";
+
+        let signature = private.sign_with_context(b"my-app", message);
+        assert!(public.verify_with_context(&signature, b"my-app", message));
+        // The signature is bound to the context.
+        assert!(!public.verify_with_context(&signature, SUBSTRATE_CONTEXT, message));
+        assert!(!public.verify(signature, message.to_vec()));
+    }
+
+    #[test]
+    fn test_sign_randomized() {
+        let public = PublicKey::try_from(PUBLIC).unwrap();
+        let private = PrivateKey::try_from(SECRET).unwrap();
+        let message = b"Hello".to_vec();
+
+        // `sr25519` signatures are randomized.
+        let first = private.sign(message.clone()).unwrap();
+        let second = private.sign(message.clone()).unwrap();
+        assert_ne!(first.to_bytes(), second.to_bytes());
+
+        assert!(public.verify(first, message.clone()));
+        assert!(public.verify(second, message));
+    }
+
+    #[test]
+    fn test_signature_to_from_bytes() {
+        let private = PrivateKey::try_from(SECRET).unwrap();
+        let signature = private.sign(b"Hello".to_vec()).unwrap();
+
+        let bytes = signature.to_vec();
+        let actual = Signature::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(actual.to_bytes(), signature.to_bytes());
+
+        // The last byte should have the `sr25519` marker bit.
+        let mut invalid = bytes;
+        invalid[63] &= 0x7F;
+        Signature::try_from(invalid.as_slice()).unwrap_err();
+        Signature::try_from(&[0; 63][..]).unwrap_err();
+    }
+
+    #[test]
+    fn test_keypair_from_invalid_bytes() {
+        let invalid = [0; 1];
+        let _ = KeyPair::try_from(&invalid[..]).unwrap_err();
+        let _ = PrivateKey::try_from(&invalid[..]).unwrap_err();
+        let _ = PublicKey::try_from(&invalid[..]).unwrap_err();
+        let _ = PublicKey::try_from(hex::encode([0xFF; 32], false).as_str()).unwrap_err();
+    }
+
+    #[test]
+    fn test_debug() {
+        let keypair = KeyPair::try_from(SECRET).unwrap();
+        let signature = keypair.sign(b"Hello".to_vec()).unwrap();
+
+        let _ = format!("{:?}", keypair);
+        let _ = format!("{:?}", keypair.private());
+        let _ = format!("{:?}", keypair.public());
+        let _ = format!("{:?}", signature);
+    }
+
+    #[test]
+    fn test_private_key_generate() {
+        let private = PrivateKey::generate(&mut OsRng);
+        let another = PrivateKey::generate(&mut OsRng);
+        assert_ne!(
+            private.to_zeroizing_vec().as_slice(),
+            another.to_zeroizing_vec().as_slice()
+        );
+
+        let message = b"hello".to_vec();
+        let signature = private.sign(message.clone()).unwrap();
+        assert!(private.public().verify(signature, message));
+    }
+}
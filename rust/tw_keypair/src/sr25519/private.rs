@@ -0,0 +1,146 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::sr25519::public::PublicKey;
+use crate::sr25519::signature::Signature;
+use crate::sr25519::SUBSTRATE_CONTEXT;
+use crate::traits::SigningKeyTrait;
+use crate::{KeyPairError, KeyPairResult};
+use rand_core::{CryptoRng, RngCore};
+use schnorrkel::derive::{ChainCode, Derivation};
+use schnorrkel::{ExpansionMode, MiniSecretKey, SecretKey, SECRET_KEY_LENGTH};
+use std::fmt;
+use tw_encoding::hex;
+use tw_hash::H256;
+use tw_misc::traits::ToBytesZeroizing;
+use zeroize::{ZeroizeOnDrop, Zeroizing};
+
+/// Represents an `sr25519` private key.
+///
+/// The key is usually a 32 byte "mini" secret (seed) that is expanded
+/// in the same way as Substrate does, i.e. using [`ExpansionMode::Ed25519`].
+/// Soft derivation yields a 64 byte expanded secret key that has no seed.
+#[derive(ZeroizeOnDrop)]
+pub struct PrivateKey {
+    secret: Secret,
+}
+
+#[derive(ZeroizeOnDrop)]
+enum Secret {
+    Mini(MiniSecretKey),
+    Expanded(SecretKey),
+}
+
+impl fmt::Debug for PrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PrivateKey")
+            .field("public", &self.public())
+            .finish()
+    }
+}
+
+impl PrivateKey {
+    /// Generates a random private key using the given `rng`.
+    pub fn generate(rng: &mut (impl CryptoRng + RngCore)) -> PrivateKey {
+        PrivateKey {
+            secret: Secret::Mini(MiniSecretKey::generate_with(rng)),
+        }
+    }
+
+    /// Returns an associated `sr25519` public key.
+    pub fn public(&self) -> PublicKey {
+        match self.secret {
+            Secret::Mini(ref mini) => PublicKey::new(mini.expand_to_public(ExpansionMode::Ed25519)),
+            Secret::Expanded(ref secret) => PublicKey::new(secret.to_public()),
+        }
+    }
+
+    /// Signs the given `message` within the signing `context`.
+    /// Substrate based chains use [`SUBSTRATE_CONTEXT`].
+    pub fn sign_with_context(&self, context: &[u8], message: &[u8]) -> Signature {
+        let keypair = self.expanded().to_keypair();
+        Signature::new(keypair.sign_simple(context, message))
+    }
+
+    /// Derives a hard child key with the given `chain_code`, i.e. a Substrate `//junction`.
+    /// The child key is a mini secret.
+    pub fn hard_derive(&self, chain_code: &H256) -> PrivateKey {
+        let (mini, _) = self
+            .expanded()
+            .hard_derive_mini_secret_key(Some(ChainCode(chain_code.take())), b"");
+        PrivateKey {
+            secret: Secret::Mini(mini),
+        }
+    }
+
+    /// Derives a soft child key with the given `chain_code`, i.e. a Substrate `/junction`.
+    /// Its public key can be derived from the parent public key with [`PublicKey::soft_derive`].
+    pub fn soft_derive(&self, chain_code: &H256) -> PrivateKey {
+        let (keypair, _) = self
+            .expanded()
+            .to_keypair()
+            .derived_key_simple(ChainCode(chain_code.take()), b"");
+        PrivateKey {
+            secret: Secret::Expanded(keypair.secret.clone()),
+        }
+    }
+
+    fn expanded(&self) -> SecretKey {
+        match self.secret {
+            Secret::Mini(ref mini) => mini.expand(ExpansionMode::Ed25519),
+            Secret::Expanded(ref secret) => secret.clone(),
+        }
+    }
+}
+
+impl SigningKeyTrait for PrivateKey {
+    type SigningMessage = Vec<u8>;
+    type Signature = Signature;
+
+    fn sign(&self, message: Self::SigningMessage) -> KeyPairResult<Self::Signature> {
+        Ok(self.sign_with_context(SUBSTRATE_CONTEXT, &message))
+    }
+}
+
+/// Accepts either a 32 byte mini secret or a 64 byte expanded secret key in the `ed25519` byte order,
+/// the same as Substrate does.
+impl<'a> TryFrom<&'a [u8]> for PrivateKey {
+    type Error = KeyPairError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let secret = if data.len() == SECRET_KEY_LENGTH {
+            SecretKey::from_ed25519_bytes(data).map(Secret::Expanded)
+        } else {
+            MiniSecretKey::from_bytes(data).map(Secret::Mini)
+        }
+        .map_err(|_| KeyPairError::InvalidSecretKey)?;
+        Ok(PrivateKey { secret })
+    }
+}
+
+impl<'a> TryFrom<&'a str> for PrivateKey {
+    type Error = KeyPairError;
+
+    fn try_from(hex: &'a str) -> Result<Self, Self::Error> {
+        let bytes = Zeroizing::new(hex::decode(hex).map_err(|_| KeyPairError::InvalidSecretKey)?);
+        Self::try_from(bytes.as_slice())
+    }
+}
+
+impl ToBytesZeroizing for PrivateKey {
+    fn to_zeroizing_vec(&self) -> Zeroizing<Vec<u8>> {
+        match self.secret {
+            Secret::Mini(ref mini) => {
+                let secret = Zeroizing::new(mini.to_bytes());
+                Zeroizing::new(secret.to_vec())
+            },
+            Secret::Expanded(ref secret) => {
+                let secret = Zeroizing::new(secret.to_ed25519_bytes());
+                Zeroizing::new(secret.to_vec())
+            },
+        }
+    }
+}
+
+crate::ct::impl_ct_eq!(PrivateKey);
@@ -0,0 +1,87 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::sr25519::signature::Signature;
+use crate::sr25519::SUBSTRATE_CONTEXT;
+use crate::traits::VerifyingKeyTrait;
+use crate::KeyPairError;
+use schnorrkel::derive::{ChainCode, Derivation};
+use tw_encoding::hex;
+use tw_hash::H256;
+use tw_misc::traits::ToBytesVec;
+
+/// Represents an `sr25519` public key, i.e. a compressed Ristretto point.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PublicKey {
+    public: schnorrkel::PublicKey,
+}
+
+/// cbindgen:ignore
+impl PublicKey {
+    /// The number of bytes in a serialized public key.
+    pub const LEN: usize = H256::LEN;
+
+    pub(crate) fn new(public: schnorrkel::PublicKey) -> PublicKey {
+        PublicKey { public }
+    }
+
+    /// Returns the raw data of the public key (32 bytes).
+    pub fn to_bytes(&self) -> H256 {
+        H256::from(self.public.to_bytes())
+    }
+
+    /// Derives a soft child key with the given `chain_code`, i.e. a Substrate `/junction`.
+    pub fn soft_derive(&self, chain_code: &H256) -> PublicKey {
+        let (public, _) = self
+            .public
+            .derived_key_simple(ChainCode(chain_code.take()), b"");
+        PublicKey { public }
+    }
+
+    /// Verifies if the given `message` was signed within the signing `context`.
+    pub fn verify_with_context(
+        &self,
+        signature: &Signature,
+        context: &[u8],
+        message: &[u8],
+    ) -> bool {
+        self.public
+            .verify_simple(context, message, &signature.signature)
+            .is_ok()
+    }
+}
+
+impl VerifyingKeyTrait for PublicKey {
+    type SigningMessage = Vec<u8>;
+    type VerifySignature = Signature;
+
+    fn verify(&self, signature: Self::VerifySignature, message: Self::SigningMessage) -> bool {
+        self.verify_with_context(&signature, SUBSTRATE_CONTEXT, &message)
+    }
+}
+
+impl ToBytesVec for PublicKey {
+    fn to_vec(&self) -> Vec<u8> {
+        self.to_bytes().into_vec()
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for PublicKey {
+    type Error = KeyPairError;
+
+    fn try_from(pubkey: &'a [u8]) -> Result<Self, Self::Error> {
+        let public = schnorrkel::PublicKey::from_bytes(pubkey)
+            .map_err(|_| KeyPairError::InvalidPublicKey)?;
+        Ok(PublicKey { public })
+    }
+}
+
+impl<'a> TryFrom<&'a str> for PublicKey {
+    type Error = KeyPairError;
+
+    fn try_from(hex: &'a str) -> Result<Self, Self::Error> {
+        let bytes = hex::decode(hex).map_err(|_| KeyPairError::InvalidPublicKey)?;
+        Self::try_from(bytes.as_slice())
+    }
+}
@@ -0,0 +1,44 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::KeyPairError;
+use tw_hash::H512;
+use tw_misc::traits::ToBytesVec;
+
+/// Represents an `sr25519` signature.
+#[derive(Clone, Debug)]
+pub struct Signature {
+    pub(crate) signature: schnorrkel::Signature,
+}
+
+impl Signature {
+    /// cbindgen:ignore
+    pub const LEN: usize = H512::LEN;
+
+    pub(crate) fn new(signature: schnorrkel::Signature) -> Signature {
+        Signature { signature }
+    }
+
+    /// Returns the signature data (64 bytes).
+    pub fn to_bytes(&self) -> H512 {
+        H512::from(self.signature.to_bytes())
+    }
+}
+
+impl ToBytesVec for Signature {
+    fn to_vec(&self) -> Vec<u8> {
+        self.to_bytes().into_vec()
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for Signature {
+    type Error = KeyPairError;
+
+    /// Note the signature must have the `sr25519` marker bit set in the last byte.
+    fn try_from(sign: &'a [u8]) -> Result<Self, Self::Error> {
+        let signature =
+            schnorrkel::Signature::from_bytes(sign).map_err(|_| KeyPairError::InvalidSignature)?;
+        Ok(Signature { signature })
+    }
+}
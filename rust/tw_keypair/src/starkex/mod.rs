@@ -3,11 +3,13 @@
 // Copyright © 2017 Trust Wallet.
 
 mod keypair;
+mod pedersen;
 mod private;
 mod public;
 mod signature;
 
 pub use keypair::KeyPair;
+pub use pedersen::{compute_hash_on_elements, pedersen_hash};
 pub use private::PrivateKey;
 pub use public::PublicKey;
 pub use signature::Signature;
@@ -28,6 +30,7 @@ fn field_element_from_bytes_be(bytes: &[u8]) -> Result<FieldElement, ()> {
 mod tests {
     use super::*;
     use crate::traits::{KeyPairTrait, SigningKeyTrait, VerifyingKeyTrait};
+    use rand_core::OsRng;
     use tw_encoding::hex;
     use tw_hash::{H256, H512};
     use tw_misc::traits::{ToBytesVec, ToBytesZeroizing};
@@ -119,4 +122,69 @@ mod tests {
 
         assert!(!public.verify(signature, hash));
     }
+
+    #[test]
+    fn test_private_key_grind() {
+        let seed = hex::decode("86f3e7293141f20a8baff320e8ee4accb9d4a4bf2b4d295e8cee784db46e0519")
+            .unwrap();
+        let private = PrivateKey::grind(&seed).unwrap();
+        assert_eq!(
+            private.to_zeroizing_vec().as_slice(),
+            H256::from("05c8c8683596c732541a59e03007b2d30dbbbb873556fe65b5fb63c16688f941")
+                .as_slice()
+        );
+    }
+
+    #[test]
+    fn test_pedersen_hash() {
+        let a = H256::from("03d937c035c878245caf64531a5756109c53068da139362728feb561405371cb");
+        let b = H256::from("0208a0a10250e382e1e4bbe2880906c2791bf6275695e02fbbc6aeff9cd8b31a");
+        let expected =
+            H256::from("030e480bed5fe53fa909cc0f8c4d99b8f9f2c016be4c41e13a4848797979c662");
+        assert_eq!(pedersen_hash(&a, &b).unwrap(), expected);
+
+        // The element is greater than the field prime.
+        let invalid = H256::from([0xff; 32]);
+        pedersen_hash(&a, &invalid).unwrap_err();
+    }
+
+    #[test]
+    fn test_compute_hash_on_elements() {
+        let a = H256::from("03d937c035c878245caf64531a5756109c53068da139362728feb561405371cb");
+        let b = H256::from("0208a0a10250e382e1e4bbe2880906c2791bf6275695e02fbbc6aeff9cd8b31a");
+        let mut len = H256::default();
+        len[31] = 2;
+
+        let hash_a = pedersen_hash(&H256::default(), &a).unwrap();
+        let hash_ab = pedersen_hash(&hash_a, &b).unwrap();
+        let expected = pedersen_hash(&hash_ab, &len).unwrap();
+        assert_eq!(compute_hash_on_elements(&[a, b]).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_private_key_sign_elements() {
+        let private = PrivateKey::try_from(
+            "0139fe4d6f02e666e86a6f58e65060f115cd3c185bd9e98bd829636931458f79",
+        )
+        .unwrap();
+        let elements = [H256::from([1; 32]), H256::from([2; 32])];
+
+        let signature = private.sign_elements(&elements).unwrap();
+        let hash = compute_hash_on_elements(&elements).unwrap();
+        assert!(private.public().verify(signature, hash.into_vec()));
+    }
+
+    #[test]
+    fn test_private_key_generate() {
+        let private = PrivateKey::generate(&mut OsRng);
+        let another = PrivateKey::generate(&mut OsRng);
+        assert_ne!(
+            private.to_zeroizing_vec().as_slice(),
+            another.to_zeroizing_vec().as_slice()
+        );
+
+        let hash_to_sign = H256::from([1; 32]).into_vec();
+        let signature = private.sign(hash_to_sign.clone()).unwrap();
+        assert!(private.public().verify(signature, hash_to_sign));
+    }
 }
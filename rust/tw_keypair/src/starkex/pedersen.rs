@@ -0,0 +1,37 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::starkex::field_element_from_bytes_be;
+use crate::{KeyPairError, KeyPairResult};
+use starknet_crypto::pedersen_hash as starknet_pedersen_hash;
+use starknet_ff::FieldElement;
+use tw_hash::H256;
+
+/// Computes the Pedersen hash of the two field elements `a` and `b`.
+/// Returns an error if any of the elements is not less than the field prime.
+pub fn pedersen_hash(a: &H256, b: &H256) -> KeyPairResult<H256> {
+    let a = field_element(a)?;
+    let b = field_element(b)?;
+    Ok(H256::from(starknet_pedersen_hash(&a, &b).to_bytes_be()))
+}
+
+/// Computes the Pedersen hash chain of the given `elements`:
+/// `h(h(h(h(0, e_1), e_2), ...), e_n), n)`.
+///
+/// This is how Starknet transactions and their calldata are hashed.
+pub fn compute_hash_on_elements(elements: &[H256]) -> KeyPairResult<H256> {
+    let mut hash = FieldElement::ZERO;
+    for element in elements {
+        hash = starknet_pedersen_hash(&hash, &field_element(element)?);
+    }
+
+    let len = FieldElement::from(elements.len() as u64);
+    Ok(H256::from(
+        starknet_pedersen_hash(&hash, &len).to_bytes_be(),
+    ))
+}
+
+fn field_element(element: &H256) -> KeyPairResult<FieldElement> {
+    field_element_from_bytes_be(element.as_slice()).map_err(|_| KeyPairError::InvalidSignMessage)
+}
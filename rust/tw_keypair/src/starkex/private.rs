@@ -2,16 +2,20 @@
 //
 // Copyright © 2017 Trust Wallet.
 
+use crate::secret_bytes::SecretBytes;
 use crate::starkex::field_element_from_bytes_be;
+use crate::starkex::pedersen::compute_hash_on_elements;
 use crate::starkex::public::PublicKey;
 use crate::starkex::signature::Signature;
 use crate::traits::SigningKeyTrait;
 use crate::{KeyPairError, KeyPairResult};
+use rand_core::{CryptoRng, RngCore};
 use starknet_crypto::{
     get_public_key, rfc6979_generate_k, sign, SignError, Signature as EcdsaSignature,
 };
 use starknet_ff::FieldElement;
 use tw_encoding::hex;
+use tw_hash::sha2::sha256;
 use tw_hash::H256;
 use tw_misc::traits::ToBytesZeroizing;
 use zeroize::Zeroizing;
@@ -20,12 +24,77 @@ use zeroize::Zeroizing;
 /// As the number is coming from `rfc6979_generate_k` so the probability is lower.
 const SIGN_RETRIES: usize = 5;
 
+/// The order of the STARK curve (big-endian).
+const EC_ORDER: [u8; 32] = [
+    0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xb7, 0x81, 0x12, 0x6d, 0xca, 0xe7, 0xb2, 0x32, 0x1e, 0x66, 0xa2, 0x41, 0xad, 0xc6, 0x4d, 0x2f,
+];
+
+/// The largest multiple of `EC_ORDER` that fits into 256 bits (big-endian),
+/// i.e. `2^256 - (2^256 mod EC_ORDER)`.
+/// A `sha256` digest that is not less than this value is biased when reduced modulo `EC_ORDER`.
+const GRIND_MAX_ALLOWED: [u8; 32] = [
+    0xf8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x0e, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xf7,
+    0x38, 0xa1, 0x3b, 0x4b, 0x92, 0x0e, 0x94, 0x11, 0xae, 0x6d, 0xa5, 0xf4, 0x0b, 0x03, 0x58, 0xb1,
+];
+
 /// Represents a private key that is used in `starknet` context.
 pub struct PrivateKey {
     secret: FieldElement,
 }
 
 impl PrivateKey {
+    /// Generates a random private key using the given `rng`.
+    /// The key is sampled uniformly from `[1, EC_ORDER)`.
+    pub fn generate(rng: &mut (impl CryptoRng + RngCore)) -> PrivateKey {
+        loop {
+            let mut bytes = Zeroizing::new([0_u8; 32]);
+            rng.fill_bytes(bytes.as_mut_slice());
+            // `EC_ORDER` is less than `2^252`, so clear the upper bits to reduce the number of retries.
+            bytes[0] &= 0x0f;
+
+            if *bytes >= EC_ORDER || bytes.iter().all(|byte| *byte == 0) {
+                continue;
+            }
+            if let Ok(secret) = FieldElement::from_bytes_be(&bytes) {
+                return PrivateKey { secret };
+            }
+        }
+    }
+
+    /// Derives a private key from the given `seed` using the StarkEx key grinding algorithm.
+    /// The `seed` is usually an Ethereum private key, e.g. derived from the user's signature.
+    ///
+    /// Ported: https://github.com/starkware-libs/starkware-crypto-utils/blob/dev/src/js/key_derivation.js
+    pub fn grind(seed: &[u8]) -> KeyPairResult<PrivateKey> {
+        let mut data = SecretBytes::zeroed(seed.len() + 1);
+        data[..seed.len()].copy_from_slice(seed);
+
+        for index in 0..=u8::MAX {
+            *data.last_mut().expect("`data` is not empty") = index;
+
+            let digest = Zeroizing::new(sha256(&data));
+            let mut key = Zeroizing::new(
+                H256::try_from(digest.as_slice())
+                    .expect("Expected 32 byte hash")
+                    .take(),
+            );
+            if *key >= GRIND_MAX_ALLOWED {
+                continue;
+            }
+
+            reduce_ec_order(&mut key);
+            return PrivateKey::try_from(key.as_slice());
+        }
+        Err(KeyPairError::InvalidSecretKey)
+    }
+
+    /// Signs the Pedersen hash of the given `elements`, see [`compute_hash_on_elements`].
+    pub fn sign_elements(&self, elements: &[H256]) -> KeyPairResult<Signature> {
+        let hash = compute_hash_on_elements(elements)?;
+        self.sign(hash.into_vec())
+    }
+
     /// Returns an associated `starknet` public key.
     pub fn public(&self) -> PublicKey {
         let public_scalar = get_public_key(&self.secret);
@@ -73,6 +142,21 @@ impl ToBytesZeroizing for PrivateKey {
     }
 }
 
+crate::ct::impl_ct_eq!(PrivateKey);
+
+/// Reduces the big-endian `key` modulo `EC_ORDER`.
+/// The `key` is expected to be less than `GRIND_MAX_ALLOWED`, so it takes a few subtractions only.
+fn reduce_ec_order(key: &mut [u8; 32]) {
+    while *key >= EC_ORDER {
+        let mut borrow = 0_i16;
+        for (key_byte, order_byte) in key.iter_mut().zip(EC_ORDER.iter()).rev() {
+            let diff = *key_byte as i16 - *order_byte as i16 - borrow;
+            borrow = i16::from(diff < 0);
+            *key_byte = diff.rem_euclid(256) as u8;
+        }
+    }
+}
+
 /// `starknet-core` depends on an out-dated `starknet-crypto` crate.
 /// We need to reimplement the same but using the latest `starknet-crypto` version.
 /// https://github.com/xJonathanLEI/starknet-rs/blob/0c78b365c2a7a7d4138553cba42fa69d695aa73d/starknet-core/src/crypto.rs#L34-L59
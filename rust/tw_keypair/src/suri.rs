@@ -0,0 +1,375 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Substrate secret URIs, i.e. `phrase//hard/soft///password`, as used by polkadot.js and `subkey`,
+//! so accounts created there are restored with the same keys:
+//!
+//! ```rust,ignore
+//! use tw_keypair::suri::SecretUri;
+//!
+//! let stash = SecretUri::parse("//Alice//stash")?.to_sr25519()?;
+//! let account = SecretUri::parse(&format!("{PHRASE}//polkadot/0///{PASSWORD}"))?.to_sr25519()?;
+//! ```
+//!
+//! The phrase is either a BIP39 English mnemonic or a `0x` prefixed 32 byte hex seed, and defaults to [`DEV_PHRASE`].
+//! The seed of a mnemonic is the first half of `PBKDF2-HMAC-SHA512(entropy, salt = "mnemonic" || password, 2048)`.
+//! Each junction is a chain code, which is the SCALE encoded junction, padded to 32 bytes or replaced with its
+//! `blake2b-256` hash if longer. `ed25519` keys support hard junctions only.
+
+use crate::ed25519::sha512::PrivateKey as Ed25519PrivateKey;
+use crate::mnemonic::{Language, Mnemonic};
+use crate::sr25519::PrivateKey as Sr25519PrivateKey;
+use crate::{KeyPairError, KeyPairResult};
+use sha2::Sha512;
+use tw_encoding::hex;
+use tw_hash::blake2::blake2_b;
+use tw_hash::{H256, H512};
+use zeroize::Zeroizing;
+
+/// The phrase of the well-known development accounts, e.g. `//Alice`.
+pub const DEV_PHRASE: &str =
+    "bottom drive obey lake curtain smoke basket hold race lonely fit walk";
+
+const HEX_PREFIX: &str = "0x";
+const PASSWORD_SEPARATOR: &str = "///";
+const SALT_PREFIX: &str = "mnemonic";
+const PBKDF2_ROUNDS: u32 = 2048;
+/// The SCALE encoded domain of the `ed25519` hard derivation.
+const ED25519_HDKD: &[u8] = b"\x2cEd25519HDKD";
+
+/// A single step of a derivation path.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeriveJunction {
+    /// `//junction`, derived from the parent private key.
+    Hard(H256),
+    /// `/junction`, derived from either the parent private or public key.
+    Soft(H256),
+}
+
+impl DeriveJunction {
+    /// Encodes the `code` of a junction as its chain code.
+    /// Numeric codes are encoded as `u64`, and any other codes as strings.
+    pub fn new(code: &str, hard: bool) -> DeriveJunction {
+        let encoded = match code.parse::<u64>() {
+            Ok(index) => index.to_le_bytes().to_vec(),
+            Err(_) => {
+                let mut encoded = compact_len(code.len());
+                encoded.extend_from_slice(code.as_bytes());
+                encoded
+            },
+        };
+
+        let chain_code = if encoded.len() > H256::LEN {
+            let hash = blake2_b(&encoded, H256::LEN).expect("Expected a valid hash size");
+            H256::try_from(hash.as_slice()).expect("Expected a 32 byte hash")
+        } else {
+            let mut chain_code = H256::default();
+            chain_code[..encoded.len()].copy_from_slice(&encoded);
+            chain_code
+        };
+
+        if hard {
+            DeriveJunction::Hard(chain_code)
+        } else {
+            DeriveJunction::Soft(chain_code)
+        }
+    }
+
+    pub fn chain_code(&self) -> &H256 {
+        match self {
+            DeriveJunction::Hard(chain_code) | DeriveJunction::Soft(chain_code) => chain_code,
+        }
+    }
+
+    pub fn is_hard(&self) -> bool {
+        matches!(self, DeriveJunction::Hard(_))
+    }
+}
+
+/// A parsed Substrate secret URI. The phrase and password are zeroized on drop.
+pub struct SecretUri {
+    phrase: Zeroizing<String>,
+    path: Vec<DeriveJunction>,
+    password: Zeroizing<String>,
+}
+
+impl SecretUri {
+    /// Parses a secret URI of the form `phrase//hard/soft///password`,
+    /// where every part is optional.
+    pub fn parse(suri: &str) -> KeyPairResult<SecretUri> {
+        // Junctions never contain `/`, so the first `///` starts the password.
+        let (rest, password) = suri.split_once(PASSWORD_SEPARATOR).unwrap_or((suri, ""));
+        let (phrase, mut path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+        let phrase = if phrase.is_empty() {
+            DEV_PHRASE
+        } else {
+            phrase
+        };
+
+        let mut junctions = Vec::new();
+        while let Some(junction) = path.strip_prefix('/') {
+            let (hard, junction) = match junction.strip_prefix('/') {
+                Some(junction) => (true, junction),
+                None => (false, junction),
+            };
+            let (code, rest) = junction.split_at(junction.find('/').unwrap_or(junction.len()));
+            if code.is_empty() {
+                return Err(KeyPairError::InvalidSecretKey);
+            }
+            junctions.push(DeriveJunction::new(code, hard));
+            path = rest;
+        }
+
+        Ok(SecretUri {
+            phrase: Zeroizing::new(phrase.to_string()),
+            path: junctions,
+            password: Zeroizing::new(password.to_string()),
+        })
+    }
+
+    pub fn path(&self) -> &[DeriveJunction] {
+        &self.path
+    }
+
+    /// Derives the `sr25519` private key.
+    pub fn to_sr25519(&self) -> KeyPairResult<Sr25519PrivateKey> {
+        let seed = self.seed()?;
+        let root = Sr25519PrivateKey::try_from(seed.as_slice())?;
+        Ok(self.path.iter().fold(root, |key, junction| match junction {
+            DeriveJunction::Hard(chain_code) => key.hard_derive(chain_code),
+            DeriveJunction::Soft(chain_code) => key.soft_derive(chain_code),
+        }))
+    }
+
+    /// Derives the `ed25519` private key.
+    /// Returns an error if the path has a soft junction.
+    pub fn to_ed25519(&self) -> KeyPairResult<Ed25519PrivateKey> {
+        let mut seed = self.seed()?;
+        for junction in self.path.iter() {
+            let DeriveJunction::Hard(chain_code) = junction else {
+                return Err(KeyPairError::InvalidSecretKey);
+            };
+            seed = ed25519_hard_derive(&seed, chain_code);
+        }
+        Ed25519PrivateKey::try_from(seed.as_slice())
+    }
+
+    /// Returns the 32 byte seed of the phrase.
+    /// The password is ignored for hex seeds, the same as in Substrate.
+    fn seed(&self) -> KeyPairResult<Zeroizing<H256>> {
+        if let Some(seed) = self.phrase.strip_prefix(HEX_PREFIX) {
+            let seed =
+                Zeroizing::new(hex::decode(seed).map_err(|_| KeyPairError::InvalidSecretKey)?);
+            let seed =
+                H256::try_from(seed.as_slice()).map_err(|_| KeyPairError::InvalidSecretKey)?;
+            return Ok(Zeroizing::new(seed));
+        }
+
+        let mnemonic = Mnemonic::parse(Language::English, &self.phrase)?;
+        let entropy = mnemonic.to_entropy();
+        let mut salt = Zeroizing::new(SALT_PREFIX.to_string());
+        salt.push_str(&self.password);
+
+        let mut seed = Zeroizing::new(H512::default());
+        pbkdf2::pbkdf2_hmac::<Sha512>(
+            &entropy,
+            salt.as_bytes(),
+            PBKDF2_ROUNDS,
+            seed.as_mut_slice(),
+        );
+        let seed = H256::try_from(&seed[..H256::LEN]).expect("Expected a 32 byte seed");
+        Ok(Zeroizing::new(seed))
+    }
+}
+
+/// Returns `blake2b-256(SCALE("Ed25519HDKD") || seed || chain_code)`.
+fn ed25519_hard_derive(seed: &H256, chain_code: &H256) -> Zeroizing<H256> {
+    let mut input = Zeroizing::new(Vec::with_capacity(ED25519_HDKD.len() + H256::LEN * 2));
+    input.extend_from_slice(ED25519_HDKD);
+    input.extend_from_slice(seed.as_slice());
+    input.extend_from_slice(chain_code.as_slice());
+
+    let hash = Zeroizing::new(blake2_b(&input, H256::LEN).expect("Expected a valid hash size"));
+    Zeroizing::new(H256::try_from(hash.as_slice()).expect("Expected a 32 byte hash"))
+}
+
+/// Returns the SCALE compact encoding of a length.
+fn compact_len(len: usize) -> Vec<u8> {
+    match len {
+        0..=0x3F => vec![(len as u8) << 2],
+        0x40..=0x3FFF => (((len as u16) << 2) | 0b01).to_le_bytes().to_vec(),
+        0x4000..=0x3FFF_FFFF => (((len as u32) << 2) | 0b10).to_le_bytes().to_vec(),
+        _ => {
+            let bytes = (len as u64).to_le_bytes();
+            let len_bytes = bytes.len() - bytes.iter().rev().take_while(|byte| **byte == 0).count();
+            let mut encoded = vec![(((len_bytes - 4) as u8) << 2) | 0b11];
+            encoded.extend_from_slice(&bytes[..len_bytes]);
+            encoded
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::{SigningKeyTrait, VerifyingKeyTrait};
+    use tw_misc::traits::{ToBytesVec, ToBytesZeroizing};
+
+    fn sr25519_public(suri: &str) -> String {
+        let private = SecretUri::parse(suri).unwrap().to_sr25519().unwrap();
+        hex::encode(private.public().to_vec(), false)
+    }
+
+    fn ed25519_public(suri: &str) -> String {
+        let private = SecretUri::parse(suri).unwrap().to_ed25519().unwrap();
+        hex::encode(private.public().to_vec(), false)
+    }
+
+    /// The well-known development accounts.
+    #[test]
+    fn test_suri_sr25519_hard() {
+        let alice = SecretUri::parse("//Alice").unwrap().to_sr25519().unwrap();
+        assert_eq!(
+            hex::encode(alice.to_zeroizing_vec().as_slice(), false),
+            "e5be9a5092b81bca64be81d212e7f2f9eba183bb7a90954f7b76361f6edb5c0a"
+        );
+        assert_eq!(
+            sr25519_public("//Alice"),
+            "d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d"
+        );
+        assert_eq!(
+            sr25519_public(&format!("{DEV_PHRASE}//Alice")),
+            "d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d"
+        );
+        assert_eq!(
+            sr25519_public("//Alice//stash"),
+            "be5ddb1579b72e84524fc29e78609e3caf42e85aa118ebfe0b0ad404b5bdd25f"
+        );
+        assert_eq!(
+            sr25519_public("//Bob"),
+            "8eaf04151687736326c9fea17e25fc5287613693c912909cb226aa4794f26a48"
+        );
+        assert_eq!(
+            sr25519_public(""),
+            "46ebddef8cd9bb167dc30878d7113b7e168e6f0646beffd77d69d39bad76b47a"
+        );
+    }
+
+    #[test]
+    fn test_suri_sr25519_soft() {
+        assert_eq!(
+            sr25519_public("/Alice"),
+            "d6c71059dbbe9ad2b0ed3f289738b800836eb425544ce694825285b958ca755e"
+        );
+        assert_eq!(
+            sr25519_public("//Alice//stash/0"),
+            "6ec1d52d09ea2136574aa8b32d6c2dc7cea3999ce6857ac7fe92bfbe8a163e15"
+        );
+        assert_eq!(
+            sr25519_public("//Alice/soft/1"),
+            "62fdaf0ce8389532e4d1c495297cb16633a2219c7c9985b636d690c698523f18"
+        );
+
+        // Soft junctions can be derived from the public key.
+        let stash = SecretUri::parse("//Alice//stash")
+            .unwrap()
+            .to_sr25519()
+            .unwrap();
+        let junction = DeriveJunction::new("0", false);
+        let public = stash.public().soft_derive(junction.chain_code());
+        assert_eq!(
+            hex::encode(public.to_vec(), false),
+            "6ec1d52d09ea2136574aa8b32d6c2dc7cea3999ce6857ac7fe92bfbe8a163e15"
+        );
+
+        // The soft derived key is an expanded secret key, which signs as any other key.
+        let private = stash.soft_derive(junction.chain_code());
+        let restored = Sr25519PrivateKey::try_from(private.to_zeroizing_vec().as_slice()).unwrap();
+        assert_eq!(restored.public(), public);
+        let signature = restored.sign(b"Hello".to_vec()).unwrap();
+        assert!(public.verify(signature, b"Hello".to_vec()));
+    }
+
+    #[test]
+    fn test_suri_password() {
+        assert_eq!(
+            sr25519_public("//Alice///secret"),
+            "08a5e583f74f54f3811cb5f7d74e686d473e3a466fd0e95738707a80c3183b15"
+        );
+        let suri = SecretUri::parse("//Alice///pass/word").unwrap();
+        assert_eq!(suri.path().len(), 1);
+        assert_eq!(suri.password.as_str(), "pass/word");
+
+        // The password is ignored for hex seeds.
+        let seed = "0xe5be9a5092b81bca64be81d212e7f2f9eba183bb7a90954f7b76361f6edb5c0a";
+        assert_eq!(
+            sr25519_public(&format!("{seed}///secret")),
+            "d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d"
+        );
+    }
+
+    #[test]
+    fn test_suri_ed25519() {
+        assert_eq!(
+            ed25519_public("//Alice"),
+            "88dc3417d5058ec4b4503e0c12ea1a0a89be200fe98922423d4334014fa6b0ee"
+        );
+        assert_eq!(
+            ed25519_public("//Alice//stash"),
+            "451781cd0c5504504f69ceec484cc66e4c22a2b6a9d20fb1a426d91ad074a2a8"
+        );
+        assert_eq!(
+            ed25519_public("//1"),
+            "bf3a763d817cee09bf785b9cc6118f58dab5c03f3ace6d524899bcb28ac74f27"
+        );
+        assert_eq!(
+            ed25519_public("//Alice///pw"),
+            "0837834cc62520d9f3a48cd0eeaf42e500740c55ec81efe8966bd42cc3ec2299"
+        );
+
+        let err = SecretUri::parse("//Alice/soft")
+            .unwrap()
+            .to_ed25519()
+            .unwrap_err();
+        assert_eq!(err, KeyPairError::InvalidSecretKey);
+    }
+
+    #[test]
+    fn test_suri_junctions() {
+        assert_eq!(
+            DeriveJunction::new("0", true),
+            DeriveJunction::Hard(H256::default())
+        );
+        assert_eq!(
+            DeriveJunction::new(&"x".repeat(40), false),
+            DeriveJunction::Soft(H256::from(
+                "c53b6f8b98325dfc51dd197eb366631c05f47ecf422da4b3bc1e43a1514a0f0c"
+            ))
+        );
+
+        let suri = SecretUri::parse("//Alice/0//1").unwrap();
+        let hard: Vec<_> = suri.path().iter().map(DeriveJunction::is_hard).collect();
+        assert_eq!(hard, [true, false, true]);
+
+        assert_eq!(compact_len(1), [0x04]);
+        assert_eq!(compact_len(0x40), [0x01, 0x01]);
+        assert_eq!(compact_len(0x4000), [0x02, 0x00, 0x01, 0x00]);
+        assert_eq!(compact_len(0x4000_0000), [0x03, 0x00, 0x00, 0x00, 0x40]);
+    }
+
+    #[test]
+    fn test_suri_invalid() {
+        for suri in ["//", "//Alice//", "//Alice/", "/"] {
+            let err = SecretUri::parse(suri).err();
+            assert_eq!(err, Some(KeyPairError::InvalidSecretKey), "{suri}");
+        }
+        let suri = SecretUri::parse("invalid phrase//Alice").unwrap();
+        assert_eq!(suri.to_sr25519().err(), Some(KeyPairError::InvalidMnemonic));
+        let suri = SecretUri::parse("0x1234//Alice").unwrap();
+        assert_eq!(
+            suri.to_sr25519().err(),
+            Some(KeyPairError::InvalidSecretKey)
+        );
+    }
+}
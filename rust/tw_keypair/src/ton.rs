@@ -0,0 +1,235 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! [TON](https://github.com/ton-org/ton-crypto/blob/master/src/mnemonic/mnemonic.ts) mnemonics,
+//! so wallets created in Tonkeeper and other TON wallets can be imported:
+//!
+//! ```rust,ignore
+//! use tw_keypair::ton::TonMnemonic;
+//!
+//! let mnemonic = TonMnemonic::parse(PHRASE, PASSWORD)?;
+//! let private = mnemonic.private_key()?;
+//! ```
+//!
+//! The words are from the BIP39 English wordlist, but there is no checksum. The entropy is
+//! `HMAC-SHA512(key = phrase, data = password)`, and the phrase is valid if
+//! `PBKDF2-HMAC-SHA512(entropy, "TON seed version", 390)` starts with a zero byte.
+//! A phrase that requires a password is additionally marked by
+//! `PBKDF2-HMAC-SHA512(entropy, "TON fast seed version", 1)` starting with `0x01` for the empty password.
+//! The `ed25519` key is the first 32 bytes of `PBKDF2-HMAC-SHA512(entropy, "TON default seed", 100000)`.
+
+use crate::ed25519::sha512::PrivateKey as Ed25519PrivateKey;
+use crate::mnemonic::Language;
+use crate::{KeyPairError, KeyPairResult};
+use rand_core::{CryptoRng, RngCore};
+use sha2::Sha512;
+use tw_hash::hmac::hmac_sha512;
+use tw_hash::{H256, H512};
+use zeroize::Zeroizing;
+
+const WORD_COUNT: usize = 24;
+const SEED_SALT: &[u8] = b"TON default seed";
+const SEED_ROUNDS: u32 = 100_000;
+const BASIC_SEED_SALT: &[u8] = b"TON seed version";
+/// `SEED_ROUNDS / 256`.
+const BASIC_SEED_ROUNDS: u32 = 390;
+const PASSWORD_SEED_SALT: &[u8] = b"TON fast seed version";
+const PASSWORD_SEED_ROUNDS: u32 = 1;
+
+/// Represents a valid TON mnemonic with its password. The phrase is zeroized on drop.
+pub struct TonMnemonic {
+    phrase: Zeroizing<String>,
+    entropy: Zeroizing<H512>,
+}
+
+/// cbindgen:ignore
+impl TonMnemonic {
+    /// Generates a new 24 word phrase. If `password` is not empty,
+    /// the phrase is valid with this password only, otherwise it's valid without a password.
+    pub fn generate(password: &str, rng: &mut (impl CryptoRng + RngCore)) -> TonMnemonic {
+        let words = Language::English.word_list();
+        loop {
+            // A random phrase is valid with the probability of 1/256, or 1/65536 with a password.
+            let mut phrase = Zeroizing::new(String::new());
+            for i in 0..WORD_COUNT {
+                if i > 0 {
+                    phrase.push(' ');
+                }
+                // 2048 divides 2^32, so the indices are uniform.
+                phrase.push_str(words[rng.next_u32() as usize % words.len()]);
+            }
+            if let Ok(mnemonic) = Self::from_normalized(phrase, password) {
+                return mnemonic;
+            }
+        }
+    }
+
+    /// Parses the 24 words of the phrase, lowercased and separated by any whitespaces,
+    /// and validates it with an optional `password`, i.e. `""` if none.
+    pub fn parse(phrase: &str, password: &str) -> KeyPairResult<TonMnemonic> {
+        let phrase = normalize(phrase);
+        let words = Language::English.word_list();
+        let mut word_count = 0;
+        for word in phrase.split(' ') {
+            words
+                .binary_search(&word)
+                .map_err(|_| KeyPairError::InvalidMnemonic)?;
+            word_count += 1;
+        }
+        if word_count != WORD_COUNT {
+            return Err(KeyPairError::InvalidMnemonic);
+        }
+        Self::from_normalized(phrase, password)
+    }
+
+    /// Returns whether the phrase is valid with the given `password`, i.e. `""` if none.
+    pub fn is_valid(phrase: &str, password: &str) -> bool {
+        TonMnemonic::parse(phrase, password).is_ok()
+    }
+
+    /// Returns whether the phrase can't be used without a password,
+    /// e.g. to ask the user for it on import.
+    pub fn is_password_needed(phrase: &str) -> bool {
+        let entropy = entropy(&normalize(phrase), "");
+        is_password_seed(&entropy) && !is_basic_seed(&entropy)
+    }
+
+    /// Returns the normalized phrase.
+    pub fn phrase(&self) -> &str {
+        &self.phrase
+    }
+
+    /// Stretches the entropy to the 64 byte seed.
+    pub fn to_seed(&self) -> Zeroizing<H512> {
+        pbkdf2_sha512(&self.entropy, SEED_SALT, SEED_ROUNDS)
+    }
+
+    /// Returns the `ed25519` private key of the wallet, i.e. the first 32 bytes of the seed.
+    pub fn private_key(&self) -> KeyPairResult<Ed25519PrivateKey> {
+        let seed = self.to_seed();
+        let secret =
+            Zeroizing::new(H256::try_from(&seed[..H256::LEN]).expect("Expected a 64 byte seed"));
+        Ed25519PrivateKey::try_from(secret.as_slice())
+    }
+
+    /// Validates the normalized `phrase` of known words.
+    fn from_normalized(phrase: Zeroizing<String>, password: &str) -> KeyPairResult<TonMnemonic> {
+        if !password.is_empty() && !Self::is_password_needed(&phrase) {
+            return Err(KeyPairError::InvalidMnemonic);
+        }
+        let entropy = entropy(&phrase, password);
+        if !is_basic_seed(&entropy) {
+            return Err(KeyPairError::InvalidMnemonic);
+        }
+        Ok(TonMnemonic { phrase, entropy })
+    }
+}
+
+/// Returns the lowercased words separated by a single space.
+fn normalize(phrase: &str) -> Zeroizing<String> {
+    let lowercase = Zeroizing::new(phrase.to_lowercase());
+    let mut normalized = Zeroizing::new(String::with_capacity(lowercase.len()));
+    for word in lowercase.split_whitespace() {
+        if !normalized.is_empty() {
+            normalized.push(' ');
+        }
+        normalized.push_str(word);
+    }
+    normalized
+}
+
+fn entropy(phrase: &str, password: &str) -> Zeroizing<H512> {
+    let hash = Zeroizing::new(hmac_sha512(phrase.as_bytes(), password.as_bytes()));
+    Zeroizing::new(H512::try_from(hash.as_slice()).expect("HMAC-SHA512 returns 64 bytes"))
+}
+
+fn is_basic_seed(entropy: &H512) -> bool {
+    pbkdf2_sha512(entropy, BASIC_SEED_SALT, BASIC_SEED_ROUNDS)[0] == 0
+}
+
+fn is_password_seed(entropy: &H512) -> bool {
+    pbkdf2_sha512(entropy, PASSWORD_SEED_SALT, PASSWORD_SEED_ROUNDS)[0] == 1
+}
+
+fn pbkdf2_sha512(entropy: &H512, salt: &[u8], rounds: u32) -> Zeroizing<H512> {
+    let mut output = Zeroizing::new(H512::default());
+    pbkdf2::pbkdf2_hmac::<Sha512>(entropy.as_slice(), salt, rounds, output.as_mut_slice());
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_core::OsRng;
+    use tw_encoding::hex;
+    use tw_misc::traits::ToBytesVec;
+
+    const PHRASE: &str = "long bomb own void gravity orphan social suspect picture breeze ranch exotic friend enable heavy squeeze practice shuffle cruel feel great confirm wagon write";
+    const PASSWORD_PHRASE: &str = "quantum whip jelly top congress miracle setup liberty park session describe hammer cluster early pudding canyon purity ugly arch abandon hospital era retire seek";
+
+    #[test]
+    fn test_ton_mnemonic() {
+        let mnemonic = TonMnemonic::parse(PHRASE, "").unwrap();
+        assert_eq!(mnemonic.phrase(), PHRASE);
+        assert_eq!(
+            hex::encode(mnemonic.to_seed().as_slice(), false),
+            "5fe53d4429a3efdf19d2d6e592c8188399fdccadebdf6b6a16a6508e8b3fa5bc0600d9b3d25fc5fa40ee907de50e81a7fc32628bd0c57086d734dc86b263b917"
+        );
+        let private = mnemonic.private_key().unwrap();
+        assert_eq!(
+            hex::encode(private.public().to_vec(), false),
+            "45f5593e6df0b494d32be82fc0b3f0bc2d8b1cddd17ab12ccb55d9f7061606d3"
+        );
+        assert!(!TonMnemonic::is_password_needed(PHRASE));
+        // A phrase without a password is not valid with one.
+        assert!(!TonMnemonic::is_valid(PHRASE, "secret"));
+
+        // The phrase is normalized.
+        let phrase = format!("  {}\n", PHRASE.to_uppercase().replace(' ', "\t"));
+        let mnemonic = TonMnemonic::parse(&phrase, "").unwrap();
+        assert_eq!(mnemonic.phrase(), PHRASE);
+    }
+
+    #[test]
+    fn test_ton_mnemonic_password() {
+        assert!(TonMnemonic::is_password_needed(PASSWORD_PHRASE));
+        assert!(!TonMnemonic::is_valid(PASSWORD_PHRASE, ""));
+        assert!(!TonMnemonic::is_valid(PASSWORD_PHRASE, "other"));
+
+        let mnemonic = TonMnemonic::parse(PASSWORD_PHRASE, "secret").unwrap();
+        assert_eq!(
+            hex::encode(mnemonic.to_seed().as_slice(), false),
+            "9fa3e207b893736bbbf8df3163cec9f15b91f9080c899d340b9cc377b8cfd02e2cee0e9485cc89156613ac3a26ba32041d55124a52a30bb60f68e2b512db14c9"
+        );
+        let private = mnemonic.private_key().unwrap();
+        assert_eq!(
+            hex::encode(private.public().to_vec(), false),
+            "f29dd7e508d63a16d60f6942ae1e52cc678b9aea7cb529d1fb9fb6715aba2499"
+        );
+    }
+
+    #[test]
+    fn test_ton_mnemonic_generate() {
+        let mnemonic = TonMnemonic::generate("", &mut OsRng);
+        assert_eq!(mnemonic.phrase().split(' ').count(), WORD_COUNT);
+        assert!(TonMnemonic::is_valid(mnemonic.phrase(), ""));
+        assert!(!TonMnemonic::is_password_needed(mnemonic.phrase()));
+    }
+
+    #[test]
+    fn test_ton_mnemonic_invalid() {
+        // 23 words.
+        let phrase = PHRASE.trim_end_matches(" write");
+        assert!(!TonMnemonic::is_valid(phrase, ""));
+        // Not a word of the wordlist.
+        let phrase = PHRASE.replace("bomb", "bombs");
+        assert!(!TonMnemonic::is_valid(&phrase, ""));
+        // A BIP39 mnemonic is not a valid TON mnemonic in most cases.
+        assert!(!TonMnemonic::is_valid(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art",
+            ""
+        ));
+        assert!(!TonMnemonic::is_valid("", ""));
+    }
+}
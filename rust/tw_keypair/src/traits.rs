@@ -3,6 +3,7 @@
 // Copyright © 2017 Trust Wallet.
 
 use crate::KeyPairResult;
+use std::future::Future;
 use tw_misc::traits::{FromSlice, ToBytesVec, ToBytesZeroizing};
 
 pub trait KeyPairTrait: FromSlice + SigningKeyTrait + VerifyingKeyTrait {
@@ -22,6 +23,34 @@ pub trait SigningKeyTrait {
 
     /// Signs the given `hash` using the private key.
     fn sign(&self, message: Self::SigningMessage) -> KeyPairResult<Self::Signature>;
+
+    /// Signs each of the given `messages` using the private key.
+    /// Returns an error if any of the messages cannot be signed.
+    fn sign_batch(&self, messages: &[Self::SigningMessage]) -> KeyPairResult<Vec<Self::Signature>>
+    where
+        Self::SigningMessage: Clone,
+    {
+        messages
+            .iter()
+            .map(|message| self.sign(message.clone()))
+            .collect()
+    }
+}
+
+/// An asynchronous counterpart of [`SigningKeyTrait`] for signers that don't keep
+/// the private key in memory, e.g. an HSM, a remote KMS or a hardware wallet.
+///
+/// Use [`crate::async_signer::InMemorySigner`] to pass an in-memory private key
+/// where an `AsyncSigner` is expected.
+pub trait AsyncSigner {
+    type SigningMessage;
+    type Signature;
+
+    /// Signs the given `message` using the private key held by the signer.
+    fn sign(
+        &self,
+        message: Self::SigningMessage,
+    ) -> impl Future<Output = KeyPairResult<Self::Signature>> + Send;
 }
 
 pub trait VerifyingKeyTrait {
@@ -30,4 +59,30 @@ pub trait VerifyingKeyTrait {
 
     /// Verifies if the given `hash` was signed using the private key.
     fn verify(&self, signature: Self::VerifySignature, message: Self::SigningMessage) -> bool;
+
+    /// Verifies if the given `hash` was signed using the private key
+    /// with the given [`VerifyMode`].
+    ///
+    /// The default implementation ignores `mode`, which is correct for the schemes
+    /// that produce non-malleable signatures only.
+    fn verify_with_mode(
+        &self,
+        signature: Self::VerifySignature,
+        message: Self::SigningMessage,
+        mode: VerifyMode,
+    ) -> bool {
+        let _ = mode;
+        self.verify(signature, message)
+    }
+}
+
+/// Defines how strictly [`VerifyingKeyTrait::verify_with_mode`] validates a signature.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VerifyMode {
+    /// Accepts any signature that satisfies the verification equation,
+    /// e.g. high-S ECDSA signatures.
+    Lax,
+    /// Additionally rejects malleable signatures and non-canonical encodings,
+    /// e.g. high-S ECDSA signatures or non-canonical ed25519 points.
+    Strict,
 }
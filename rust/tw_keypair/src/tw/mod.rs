@@ -126,4 +126,17 @@ mod tests {
             assert_eq!(PublicKeyType::from_raw(raw), expected);
         }
     }
+
+    #[test]
+    fn test_private_key_sign_invalid_message_length() {
+        let secret = tw_encoding::hex::decode(
+            "afeefca74d9a325cf1d6b6911d61a65c32afa8e02bd5e78e2e4ac2910bab45f5",
+        )
+        .unwrap();
+        let private = PrivateKey::new(secret).unwrap();
+        assert_eq!(
+            private.sign(&[1; 31], Curve::Secp256k1).unwrap_err(),
+            crate::KeyPairError::InvalidMessageLength
+        );
+    }
 }
@@ -2,21 +2,23 @@
 //
 // Copyright © 2017 Trust Wallet.
 
+use crate::ct::{ct_eq_secrets, Choice, ConstantTimeEq};
 use crate::ecdsa::{nist256p1, secp256k1};
+use crate::secret_bytes::SecretBytes;
 use crate::traits::SigningKeyTrait;
 use crate::tw::{Curve, PublicKey, PublicKeyType};
 use crate::{ed25519, starkex, KeyPairError, KeyPairResult};
 use std::ops::Range;
 use tw_hash::H256;
 use tw_misc::traits::ToBytesVec;
-use zeroize::ZeroizeOnDrop;
+use zeroize::{ZeroizeOnDrop, Zeroizing};
 
 /// Represents a private key that can be used to sign messages with different elliptic curves.
 ///
 /// TODO add `secp256k1: Once<each_curve::PrivateKey>` for each curve.
 #[derive(ZeroizeOnDrop)]
 pub struct PrivateKey {
-    bytes: Vec<u8>,
+    bytes: SecretBytes,
 }
 
 /// cbindgen:ignore
@@ -30,10 +32,13 @@ impl PrivateKey {
 
     /// Validates the given `bytes` secret and creates a private key.
     pub fn new(bytes: Vec<u8>) -> KeyPairResult<PrivateKey> {
+        let bytes = Zeroizing::new(bytes);
         if !Self::is_valid_general(&bytes) {
             return Err(KeyPairError::InvalidSecretKey);
         }
-        Ok(PrivateKey { bytes })
+        Ok(PrivateKey {
+            bytes: SecretBytes::from_slice(&bytes),
+        })
     }
 
     /// Returns the 32 byte array - the essential private key data.
@@ -95,7 +100,7 @@ impl PrivateKey {
             Key: SigningKeyTrait,
         {
             let hash_to_sign = <Key as SigningKeyTrait>::SigningMessage::try_from(message)
-                .map_err(|_| KeyPairError::InvalidSignMessage)?;
+                .map_err(|_| KeyPairError::InvalidMessageLength)?;
             signing_key.sign(hash_to_sign).map(|sig| sig.to_vec())
         }
 
@@ -191,3 +196,17 @@ impl PrivateKey {
         starkex::PrivateKey::try_from(self.key().as_slice())
     }
 }
+
+impl ConstantTimeEq for PrivateKey {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        ct_eq_secrets(&self.bytes, &other.bytes)
+    }
+}
+
+impl PartialEq for PrivateKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl Eq for PrivateKey {}
@@ -3,7 +3,7 @@
 // Copyright © 2017 Trust Wallet.
 
 use crate::ecdsa::{nist256p1, secp256k1};
-use crate::traits::VerifyingKeyTrait;
+use crate::traits::{VerifyMode, VerifyingKeyTrait};
 use crate::tw::PublicKeyType;
 use crate::{ed25519, starkex, KeyPairError, KeyPairResult};
 use tw_misc::traits::ToBytesVec;
@@ -80,7 +80,22 @@ impl PublicKey {
 
     /// Verifies if the given `message` was signed using a private key associated with the public key.
     pub fn verify(&self, sig: &[u8], message: &[u8]) -> bool {
-        fn verify_impl<Key>(verifying_key: &Key, sig: &[u8], message: &[u8]) -> bool
+        self.verify_impl(sig, message, None)
+    }
+
+    /// Verifies if the given `message` was signed using a private key associated with the public key
+    /// with the given [`VerifyMode`], regardless of the default behaviour of the curve.
+    pub fn verify_with_mode(&self, sig: &[u8], message: &[u8], mode: VerifyMode) -> bool {
+        self.verify_impl(sig, message, Some(mode))
+    }
+
+    fn verify_impl(&self, sig: &[u8], message: &[u8], mode: Option<VerifyMode>) -> bool {
+        fn verify_impl<Key>(
+            verifying_key: &Key,
+            sig: &[u8],
+            message: &[u8],
+            mode: Option<VerifyMode>,
+        ) -> bool
         where
             Key: VerifyingKeyTrait,
         {
@@ -89,23 +104,26 @@ impl PublicKey {
             let message = try_or_false!(<Key as VerifyingKeyTrait>::SigningMessage::try_from(
                 message
             ));
-            verifying_key.verify(verify_sig, message)
+            match mode {
+                Some(mode) => verifying_key.verify_with_mode(verify_sig, message, mode),
+                None => verifying_key.verify(verify_sig, message),
+            }
         }
 
         match self {
             PublicKey::Secp256k1(secp) | PublicKey::Secp256k1Extended(secp) => {
-                verify_impl(secp, sig, message)
+                verify_impl(secp, sig, message, mode)
             },
             PublicKey::Nist256p1(nist) | PublicKey::Nist256p1Extended(nist) => {
-                verify_impl(nist, sig, message)
+                verify_impl(nist, sig, message, mode)
             },
-            PublicKey::Ed25519(ed) => verify_impl(ed, sig, message),
-            PublicKey::Ed25519Blake2b(blake) => verify_impl(blake, sig, message),
-            PublicKey::Curve25519Waves(waves) => verify_impl(waves, sig, message),
+            PublicKey::Ed25519(ed) => verify_impl(ed, sig, message, mode),
+            PublicKey::Ed25519Blake2b(blake) => verify_impl(blake, sig, message, mode),
+            PublicKey::Curve25519Waves(waves) => verify_impl(waves, sig, message, mode),
             PublicKey::Ed25519ExtendedCardano(cardano) => {
-                verify_impl(cardano.as_ref(), sig, message)
+                verify_impl(cardano.as_ref(), sig, message, mode)
             },
-            PublicKey::Starkex(stark) => verify_impl(stark, sig, message),
+            PublicKey::Starkex(stark) => verify_impl(stark, sig, message, mode),
         }
     }
 
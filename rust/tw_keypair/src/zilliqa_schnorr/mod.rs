@@ -0,0 +1,161 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Zilliqa specific Schnorr signatures over `secp256k1`.
+//!
+//! Unlike BIP-340, the scheme commits to the compressed nonce point and public key,
+//! derives the nonce deterministically via RFC-6979 and produces `s = k - r*d`.
+//! Ported from [zilliqa.c](https://github.com/trustwallet/wallet-core/blob/d9e35ec485b1366dd10509192d02d9dbb6877ab3/trezor-crypto/crypto/zilliqa.c).
+
+use crate::schnorr::{compressed_point, scalar_from_hash};
+use k256::{AffinePoint, Scalar};
+use tw_hash::sha2::sha256;
+use tw_hash::{H256, H264};
+
+mod keypair;
+mod private;
+mod public;
+mod signature;
+
+pub use keypair::KeyPair;
+pub use private::PrivateKey;
+pub use public::PublicKey;
+pub use signature::Signature;
+
+/// Computes the challenge `r = H(Q, P, m) mod n`.
+fn challenge(q_point: &AffinePoint, public: &H264, message: &[u8]) -> Scalar {
+    let mut preimage = Vec::with_capacity(H264::LEN * 2 + message.len());
+    preimage.extend_from_slice(compressed_point(q_point).as_slice());
+    preimage.extend_from_slice(public.as_slice());
+    preimage.extend_from_slice(message);
+
+    let hash = H256::try_from(sha256(&preimage).as_slice()).expect("Expected 32 byte sha256 hash");
+    scalar_from_hash(&hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schnorr::scalar_from_canonical_bytes;
+    use crate::traits::{SigningKeyTrait, VerifyingKeyTrait};
+    use tw_encoding::hex;
+    use tw_hash::{concat, H512};
+    use tw_misc::traits::{ToBytesVec, ToBytesZeroizing};
+
+    #[test]
+    fn test_key_pair_sign_verify() {
+        let keypair =
+            KeyPair::try_from("afeefca74d9a325cf1d6b6911d61a65c32afa8e02bd5e78e2e4ac2910bab45f5")
+                .unwrap();
+        let message = b"hello schnorr".to_vec();
+
+        let signature = keypair.sign(message.clone()).unwrap();
+        let expected = H512::from("d166b1ae7892c5ef541461dc12a50214d0681b63d8037cda29a3fe6af8bb973e4ea94624d85bc0010bdc1b38d05198328fae21254adc2bf5feaf2804d54dba55");
+        assert_eq!(signature.to_bytes(), expected);
+
+        assert!(keypair.verify(signature.clone(), message));
+        assert!(!keypair.verify(signature, b"hello schnorr!".to_vec()));
+    }
+
+    /// Taken from `trezor-crypto/crypto/tests/test_check_zilliqa.h`.
+    #[test]
+    fn test_sign_with_nonce() {
+        #[track_caller]
+        fn test_impl(
+            message: &str,
+            secret: &'static str,
+            k: &'static str,
+            s: &'static str,
+            r: &'static str,
+        ) {
+            let private = PrivateKey::try_from(secret).unwrap();
+            let k = scalar_from_canonical_bytes(&H256::from(k)).unwrap();
+
+            let signature = private.sign_with_nonce(&k, message.as_bytes()).unwrap();
+            assert_eq!(signature.r(), H256::from(r));
+            assert_eq!(signature.s(), H256::from(s));
+            assert!(private
+                .public()
+                .verify(signature, message.as_bytes().to_vec()));
+        }
+
+        test_impl(
+            "123",
+            "3382266517e2ebe6df51faf4bfe612236ad46fb8bd59ac982a223b045e080ac6",
+            "669301f724c555d7bb1185c04909e9caca3ec7a292b3a1c92ddccd5a5a7dddd3",
+            "ffd72c290b98c93a4bcedc0edcdf040c35579be962fe83e6821d4f3cb4b795d2",
+            "74aae9c3e069e2806e1b0d890970be387aebed8040f37991aacad70b27895e39",
+        );
+        test_impl(
+            "1234",
+            "51a2758eed776c40b367364909c8a9c98cc969104f69ff316f7a287495c37c9b",
+            "a0a1a9b3570aae963535b8d4376c58a61646c18182c9fdda5fb13703f88d4d1e",
+            "99a0cb942c81571b77c682f79cd3cb663ce9e1c55bb425ba24b9f11a0de84fe2",
+            "c3c10363e38158bba20556a36de9358dfd81a31c180abc9e7617c1cc1caf03b3",
+        );
+        test_impl(
+            "What is great in man is that he is a bridge and not a goal",
+            "52c395a6d304de1a959e73e4604e32c5ad3f2bf01c8f730af426b38d7d5dd908",
+            "000000000000000000000000000000000000000000000000000000000000007b",
+            "546f70aa1fee3718c95508240cdc073b9fefed05959c5319dd8e2bf07a1dd028",
+            "b8667be5e10b113608bfe5327c44e9f0462be26f789177e10dce53019aa33daa",
+        );
+        test_impl(
+            "qwertyuiop[]asdfghjkl;'zxcvbnm,./1234567890-=",
+            "7457dc574d927e5dae84b05264a5b637b5a68e34a85b3965084ed6fed5b7f12d",
+            "000000000000000000000000000000000000000000000000000000000000007c",
+            "0aa595a649e517133d3448ca657424dd07bbed289030f0c0aa6738d26ab9a910",
+            "83812632f1443a70b198d112d075d886be7bbc6ec6275ae52661e52b7358bb8b",
+        );
+    }
+
+    #[test]
+    fn test_verify_invalid() {
+        let private = PrivateKey::try_from(
+            "3382266517e2ebe6df51faf4bfe612236ad46fb8bd59ac982a223b045e080ac6",
+        )
+        .unwrap();
+        let public = private.public();
+        let message = b"123".to_vec();
+        let signature = private.sign(message.clone()).unwrap();
+
+        // Empty message.
+        assert!(!public.verify(signature.clone(), Vec::new()));
+
+        // `r = 0` or `s = 0`.
+        let zero = H256::default();
+        let sign_bytes = signature.to_bytes();
+        let (r, s): (H256, H256) = sign_bytes.split();
+        let zero_r: H512 = concat(zero, s);
+        Signature::try_from(zero_r.as_slice()).unwrap_err();
+        let zero_s: H512 = concat(r, zero);
+        Signature::try_from(zero_s.as_slice()).unwrap_err();
+
+        // `s = n` is out of range.
+        let order = H256::from("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141");
+        let s_out_of_range: H512 = concat(r, order);
+        Signature::try_from(s_out_of_range.as_slice()).unwrap_err();
+    }
+
+    #[test]
+    fn test_public_key_from() {
+        let private = PrivateKey::try_from(
+            "afeefca74d9a325cf1d6b6911d61a65c32afa8e02bd5e78e2e4ac2910bab45f5",
+        )
+        .unwrap();
+        let expected =
+            H264::from("0399c6f51ad6f98c9c583f8e92bb7758ab2ca9a04110c0a1126ec43e5453d196c1");
+        assert_eq!(private.public().compressed(), expected);
+
+        let uncompressed = "0499c6f51ad6f98c9c583f8e92bb7758ab2ca9a04110c0a1126ec43e5453d196c166b489a4b7c491e7688e6ebea3a71fc3a1a48d60f98d5ce84c93b65e423fde91";
+        let public = PublicKey::try_from(uncompressed).unwrap();
+        assert_eq!(public.to_vec(), expected.into_vec());
+
+        assert_eq!(
+            private.to_zeroizing_vec().as_slice(),
+            hex::decode("afeefca74d9a325cf1d6b6911d61a65c32afa8e02bd5e78e2e4ac2910bab45f5")
+                .unwrap()
+        );
+    }
+}
@@ -0,0 +1,114 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::traits::SigningKeyTrait;
+use crate::zilliqa_schnorr::challenge;
+use crate::zilliqa_schnorr::public::PublicKey;
+use crate::zilliqa_schnorr::signature::Signature;
+use crate::{KeyPairError, KeyPairResult};
+use k256::ecdsa::SigningKey;
+use k256::elliptic_curve::PrimeField;
+use k256::{FieldBytes, ProjectivePoint, Scalar};
+use rand_core::{CryptoRng, RngCore};
+use rfc6979::HmacDrbg;
+use sha2::Sha256;
+use tw_encoding::hex;
+use tw_hash::sha2::sha256;
+use tw_misc::traits::ToBytesZeroizing;
+use zeroize::{ZeroizeOnDrop, Zeroizing};
+
+/// The maximum number of attempts to generate a valid nonce.
+const SIGN_RETRIES: usize = 10000;
+
+/// Represents a `secp256k1` private key that is used to produce Zilliqa Schnorr signatures.
+#[derive(ZeroizeOnDrop)]
+pub struct PrivateKey {
+    secret: SigningKey,
+}
+
+impl PrivateKey {
+    /// Generates a random private key using the given `rng`.
+    pub fn generate(rng: &mut (impl CryptoRng + RngCore)) -> PrivateKey {
+        PrivateKey {
+            secret: SigningKey::random(rng),
+        }
+    }
+
+    /// Returns an associated public key.
+    pub fn public(&self) -> PublicKey {
+        PublicKey::new(*self.secret.verifying_key())
+    }
+
+    /// Signs the `message` with the given nonce `k`.
+    /// Returns `None` if either `r` or `s` is zero, so another nonce should be used.
+    pub(crate) fn sign_with_nonce(&self, k: &Scalar, message: &[u8]) -> Option<Signature> {
+        let d = self.secret.as_nonzero_scalar().as_ref();
+        let q_point = (ProjectivePoint::GENERATOR * k).to_affine();
+
+        let r = challenge(&q_point, &self.public().compressed(), message);
+        let s = *k - r * d;
+        if bool::from(r.is_zero()) || bool::from(s.is_zero()) {
+            return None;
+        }
+        Some(Signature::new(r, s))
+    }
+}
+
+impl SigningKeyTrait for PrivateKey {
+    type SigningMessage = Vec<u8>;
+    type Signature = Signature;
+
+    /// The nonce is generated deterministically according to RFC-6979
+    /// from the private key and the `sha256` hash of the message.
+    fn sign(&self, message: Self::SigningMessage) -> KeyPairResult<Self::Signature> {
+        let hash = sha256(&message);
+        let secret = Zeroizing::new(self.secret.to_bytes());
+        let mut hmac_drbg = HmacDrbg::<Sha256>::new(secret.as_slice(), &hash, &[]);
+
+        for _ in 0..SIGN_RETRIES {
+            let mut k_bytes = Zeroizing::new([0_u8; 32]);
+            hmac_drbg.fill_bytes(k_bytes.as_mut_slice());
+
+            // Skip `k` if it's zero or not less than the curve order.
+            let k: Option<Scalar> = Scalar::from_repr(FieldBytes::from(*k_bytes)).into();
+            match k {
+                Some(k) if !bool::from(k.is_zero()) => {
+                    if let Some(signature) = self.sign_with_nonce(&k, &message) {
+                        return Ok(signature);
+                    }
+                },
+                _ => continue,
+            }
+        }
+
+        Err(KeyPairError::SigningError)
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for PrivateKey {
+    type Error = KeyPairError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let secret = SigningKey::from_slice(data).map_err(|_| KeyPairError::InvalidSecretKey)?;
+        Ok(PrivateKey { secret })
+    }
+}
+
+impl<'a> TryFrom<&'a str> for PrivateKey {
+    type Error = KeyPairError;
+
+    fn try_from(hex: &'a str) -> Result<Self, Self::Error> {
+        let bytes = Zeroizing::new(hex::decode(hex).map_err(|_| KeyPairError::InvalidSecretKey)?);
+        Self::try_from(bytes.as_slice())
+    }
+}
+
+impl ToBytesZeroizing for PrivateKey {
+    fn to_zeroizing_vec(&self) -> Zeroizing<Vec<u8>> {
+        let secret = Zeroizing::new(self.secret.to_bytes());
+        Zeroizing::new(secret.as_slice().to_vec())
+    }
+}
+
+crate::ct::impl_ct_eq!(PrivateKey);
@@ -0,0 +1,88 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::traits::VerifyingKeyTrait;
+use crate::zilliqa_schnorr::challenge;
+use crate::zilliqa_schnorr::signature::Signature;
+use crate::KeyPairError;
+use k256::ecdsa::VerifyingKey;
+use k256::elliptic_curve::ops::LinearCombination;
+use k256::ProjectivePoint;
+use tw_encoding::hex;
+use tw_hash::H264;
+use tw_misc::traits::ToBytesVec;
+
+/// Represents a `secp256k1` public key that is used to verify Zilliqa Schnorr signatures.
+#[derive(Clone, PartialEq)]
+pub struct PublicKey {
+    public: VerifyingKey,
+}
+
+/// cbindgen:ignore
+impl PublicKey {
+    /// The number of bytes in a compressed public key.
+    pub const COMPRESSED: usize = H264::len();
+
+    pub(crate) fn new(public: VerifyingKey) -> PublicKey {
+        PublicKey { public }
+    }
+
+    /// Returns the raw data of the compressed public key (33 bytes).
+    pub fn compressed(&self) -> H264 {
+        let compressed = true;
+        H264::try_from(self.public.to_encoded_point(compressed).as_bytes())
+            .expect("Expected 33 byte array Public Key")
+    }
+}
+
+impl VerifyingKeyTrait for PublicKey {
+    type SigningMessage = Vec<u8>;
+    type VerifySignature = Signature;
+
+    /// Checks that `r = H(s*G + r*P, P, m)`.
+    fn verify(&self, signature: Self::VerifySignature, message: Self::SigningMessage) -> bool {
+        if message.is_empty() {
+            return false;
+        }
+
+        let q_point = ProjectivePoint::lincomb(
+            &ProjectivePoint::GENERATOR,
+            &signature.s,
+            &ProjectivePoint::from(*self.public.as_affine()),
+            &signature.r,
+        );
+        if q_point == ProjectivePoint::IDENTITY {
+            return false;
+        }
+
+        challenge(&q_point.to_affine(), &self.compressed(), &message) == signature.r
+    }
+}
+
+impl<'a> TryFrom<&'a str> for PublicKey {
+    type Error = KeyPairError;
+
+    fn try_from(hex: &'a str) -> Result<Self, Self::Error> {
+        let bytes = hex::decode(hex).map_err(|_| KeyPairError::InvalidPublicKey)?;
+        Self::try_from(bytes.as_slice())
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for PublicKey {
+    type Error = KeyPairError;
+
+    /// Expected either `H264` or `H520` slice.
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        Ok(PublicKey {
+            public: VerifyingKey::from_sec1_bytes(data)
+                .map_err(|_| KeyPairError::InvalidPublicKey)?,
+        })
+    }
+}
+
+impl ToBytesVec for PublicKey {
+    fn to_vec(&self) -> Vec<u8> {
+        self.compressed().to_vec()
+    }
+}
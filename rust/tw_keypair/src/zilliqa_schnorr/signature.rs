@@ -0,0 +1,66 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::schnorr::{scalar_from_canonical_bytes, scalar_to_bytes};
+use crate::KeyPairError;
+use k256::Scalar;
+use tw_hash::{concat, H256, H512};
+use tw_misc::traits::ToBytesVec;
+
+/// Represents a Zilliqa Schnorr signature.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Signature {
+    /// The challenge `r = H(Q, P, m)`.
+    pub(crate) r: Scalar,
+    /// `s = k - r*d`.
+    pub(crate) s: Scalar,
+}
+
+impl Signature {
+    /// cbindgen:ignore
+    pub const LEN: usize = H512::LEN;
+
+    pub(crate) fn new(r: Scalar, s: Scalar) -> Signature {
+        Signature { r, s }
+    }
+
+    /// Returns the `r` challenge as 32 byte array.
+    pub fn r(&self) -> H256 {
+        scalar_to_bytes(&self.r)
+    }
+
+    /// Returns the `s` value as 32 byte array.
+    pub fn s(&self) -> H256 {
+        scalar_to_bytes(&self.s)
+    }
+
+    /// Returns a binary signature representation:
+    /// RS, where R - 32 byte array, S - 32 byte array.
+    pub fn to_bytes(&self) -> H512 {
+        concat(self.r(), self.s())
+    }
+}
+
+impl ToBytesVec for Signature {
+    fn to_vec(&self) -> Vec<u8> {
+        self.to_bytes().into_vec()
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for Signature {
+    type Error = KeyPairError;
+
+    /// Both `r` and `s` must be in range `[1, n-1]`.
+    fn try_from(sig: &'a [u8]) -> Result<Self, Self::Error> {
+        let bytes = H512::try_from(sig).map_err(|_| KeyPairError::InvalidSignature)?;
+        let (r, s): (H256, H256) = bytes.split();
+
+        let r = scalar_from_canonical_bytes(&r).ok_or(KeyPairError::InvalidSignature)?;
+        let s = scalar_from_canonical_bytes(&s).ok_or(KeyPairError::InvalidSignature)?;
+        if bool::from(r.is_zero()) || bool::from(s.is_zero()) {
+            return Err(KeyPairError::InvalidSignature);
+        }
+        Ok(Signature { r, s })
+    }
+}
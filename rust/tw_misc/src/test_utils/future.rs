@@ -0,0 +1,30 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use std::future::Future;
+use std::pin::pin;
+use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+const NOOP_WAKER_VTABLE: RawWakerVTable =
+    RawWakerVTable::new(|_| noop_raw_waker(), |_| {}, |_| {}, |_| {});
+
+fn noop_raw_waker() -> RawWaker {
+    RawWaker::new(std::ptr::null(), &NOOP_WAKER_VTABLE)
+}
+
+/// Runs the given `future` to completion on the current thread.
+/// Intended for tests of async APIs without pulling an async runtime in.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    // Safety: the vtable functions do nothing, so the null data pointer is never dereferenced.
+    let waker = unsafe { Waker::from_raw(noop_raw_waker()) };
+    let mut context = Context::from_waker(&waker);
+
+    let mut future = pin!(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+        std::thread::yield_now();
+    }
+}
@@ -2,4 +2,5 @@
 //
 // Copyright © 2017 Trust Wallet.
 
+pub mod future;
 pub mod json;
@@ -11,7 +11,7 @@ use std::str::FromStr;
 use tw_hash::H256;
 use tw_memory::Data;
 
-#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Ord, PartialOrd)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct U256(pub(crate) primitive_types::U256);
 
@@ -244,6 +244,7 @@ impl_map_from!(U256, u8);
 impl_map_from!(U256, u16);
 impl_map_from!(U256, u32);
 impl_map_from!(U256, u64);
+impl_map_from!(U256, u128);
 impl_map_from!(U256, usize);
 
 #[cfg(test)]
@@ -0,0 +1,95 @@
+/// The maximum number of the explored branches, the same as Bitcoin Core uses.
+const BNB_TOTAL_TRIES: usize = 100_000;
+
+/// Searches for the inputs whose total effective value (the value minus the
+/// fee of spending it) covers the `target` with an excess of at most
+/// `cost_of_change`, so that the transaction doesn't need a change output.
+///
+/// This is the Branch and Bound algorithm of Bitcoin Core: the inputs are
+/// explored in the descending order of the effective values and the selection
+/// with the least waste is returned. Since the current fee rate is also
+/// considered as the long-term one, the waste of a selection is its excess
+/// that is left to the miners.
+///
+/// Returns the indexes of the selected `effective_values`, or `None` if there
+/// is no such selection.
+pub fn branch_and_bound(
+    effective_values: &[u64],
+    target: u64,
+    cost_of_change: u64,
+) -> Option<Vec<usize>> {
+    // The inputs that cost more than their value are never spent.
+    let mut pool: Vec<usize> = (0..effective_values.len())
+        .filter(|index| effective_values[*index] > 0)
+        .collect();
+    pool.sort_by(|a, b| effective_values[*b].cmp(&effective_values[*a]));
+    let values: Vec<u64> = pool.iter().map(|index| effective_values[*index]).collect();
+
+    let mut current_value = 0;
+    let mut current_available: u64 = values.iter().sum();
+    if current_available < target {
+        return None;
+    }
+
+    // The positions in `values` of the current selection.
+    let mut selection: Vec<usize> = Vec::new();
+    let mut best: Option<(u64, Vec<usize>)> = None;
+
+    let mut position = 0;
+    for _ in 0..BNB_TOTAL_TRIES {
+        let mut backtrack = false;
+        if current_value + current_available < target || current_value > target + cost_of_change {
+            // The branch can't cover the target or exceeds it too much.
+            backtrack = true;
+        } else if current_value >= target {
+            let waste = current_value - target;
+            let improves = match &best {
+                Some((best_waste, _)) => waste <= *best_waste,
+                None => true,
+            };
+            if improves {
+                best = Some((waste, selection.clone()));
+            }
+            if waste == 0 {
+                break;
+            }
+            backtrack = true;
+        }
+
+        if backtrack {
+            let Some(last) = selection.last().copied() else {
+                // All the branches are explored.
+                break;
+            };
+            // Make the omitted inputs available again, and then omit the last
+            // selected one.
+            position -= 1;
+            while position > last {
+                current_available += values[position];
+                position -= 1;
+            }
+            current_value -= values[last];
+            selection.pop();
+        } else {
+            current_available -= values[position];
+            // Omitting an input is equivalent to omitting the previous one of the
+            // same value, so such a branch is explored once.
+            let previous_selected = position > 0 && selection.last() == Some(&(position - 1));
+            if selection.is_empty() || previous_selected || values[position] != values[position - 1]
+            {
+                selection.push(position);
+                current_value += values[position];
+            }
+        }
+        position += 1;
+    }
+
+    best.map(|(_, selection)| {
+        let mut selected: Vec<usize> = selection
+            .into_iter()
+            .map(|position| pool[position])
+            .collect();
+        selected.sort_unstable();
+        selected
+    })
+}
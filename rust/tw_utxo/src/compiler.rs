@@ -1,16 +1,24 @@
-use crate::{Error, Result};
+use crate::dust::DustThreshold;
+use crate::{coin_selection, Error, Result};
 use bitcoin::blockdata::locktime::absolute::{Height, LockTime, Time};
 use bitcoin::consensus::Encodable;
 use bitcoin::hashes::Hash;
-use bitcoin::sighash::{EcdsaSighashType, Prevouts, SighashCache, TapSighashType};
-use bitcoin::taproot::TapLeafHash;
+use bitcoin::sighash::{Annex, EcdsaSighashType, Prevouts, SighashCache, TapSighashType};
+use bitcoin::taproot::{TapLeafHash, TAPROOT_ANNEX_PREFIX};
 use bitcoin::{OutPoint, Script, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Txid, Witness};
+use std::cmp::Reverse;
 use std::marker::PhantomData;
 use tw_proto::Utxo::Proto::{self, SighashType};
 
 type ProtoLockTimeVariant = Proto::mod_LockTime::OneOfvariant;
 type ProtoSigningMethod = Proto::SigningMethod;
 
+/// The weight of the outpoint, the empty scriptSig and the sequence of an
+/// input, which is not included in its `weight_estimate`.
+const TXIN_BASE_WEIGHT: u64 = 4 * (32 + 4 + 1 + 4);
+/// The estimated weight of spending the change output, assuming it's P2WPKH.
+const CHANGE_SPEND_WEIGHT: u64 = TXIN_BASE_WEIGHT + 108;
+
 pub trait UtxoContext {
     type SigningInput<'a>;
     type SigningOutput;
@@ -57,6 +65,24 @@ impl Compiler<StandardBitcoinContext> {
     fn preimage_hashes_impl(
         mut proto: Proto::SigningInput<'_>,
     ) -> Result<Proto::PreSigningOutput<'static>> {
+        // The inputs that must never be spent are not available. With
+        // `UsePinned`, only the inputs that must be spent are.
+        proto.inputs.retain(|input| match input.coin_control {
+            Proto::CoinControl::CoinNeverSpend => false,
+            Proto::CoinControl::CoinSelectable => {
+                proto.input_selector != Proto::InputSelector::UsePinned
+            },
+            Proto::CoinControl::CoinMustSpend => true,
+        });
+        if let Proto::InputSelector::UsePinned = proto.input_selector {
+            proto.input_selector = Proto::InputSelector::UseAll;
+        }
+        let pinned_count = proto
+            .inputs
+            .iter()
+            .filter(|input| input.coin_control == Proto::CoinControl::CoinMustSpend)
+            .count();
+
         // Calculate total outputs amount, based on it we can determine how many inputs to select.
         let total_input_amount: u64 = proto.inputs.iter().map(|input| input.value).sum();
         let total_output_amount: u64 = proto.outputs.iter().map(|output| output.value).sum();
@@ -68,11 +94,22 @@ impl Compiler<StandardBitcoinContext> {
             return Err(Error::from(Proto::Error::Error_insufficient_inputs));
         }
 
-        // No ouputs specified.
-        if total_output_amount == 0 {
+        // No ouputs specified. The `OP_RETURN` outputs carry data instead of value.
+        if !has_outputs(&proto.outputs) {
             return Err(Error::from(Proto::Error::Error_no_outputs_specified));
         }
 
+        // The outputs must not be dust, unless the policy allows them.
+        let dust_threshold = DustThreshold::from_proto(&proto);
+        if proto.dust_policy != Proto::DustPolicy::DustInclude {
+            let has_dust = proto.outputs.iter().any(|output| {
+                dust_threshold.is_dust(output.value, Script::from_bytes(&output.script_pubkey))
+            });
+            if has_dust {
+                return Err(Error::from(Proto::Error::Error_dust_output));
+            }
+        }
+
         // Change scriptPubkey must be set if change output is enabled.
         if !proto.disable_change_output && proto.change_script_pubkey.is_empty() {
             return Err(Error::from(
@@ -87,8 +124,49 @@ impl Compiler<StandardBitcoinContext> {
             proto.inputs.sort_by(|a, b| b.value.cmp(&a.value));
         }
 
+        // The inputs that must be spent are selected first, keeping their order.
+        if pinned_count > 0 {
+            proto
+                .inputs
+                .sort_by_key(|input| input.coin_control != Proto::CoinControl::CoinMustSpend);
+        }
+
+        // If enabled, try to select the inputs that don't require a change
+        // output. Otherwise, fall back to selecting the largest inputs first.
+        let mut changeless = false;
+        if let Proto::InputSelector::SelectBranchAndBound = proto.input_selector {
+            // The selection can't be constrained to the inputs that must be spent.
+            let selected = if pinned_count > 0 {
+                None
+            } else {
+                select_changeless(&proto)?
+            };
+            match selected {
+                Some(selected) => {
+                    let available = std::mem::take(&mut proto.inputs);
+                    proto.inputs = available
+                        .into_iter()
+                        .enumerate()
+                        .filter(|(index, _)| selected.contains(index))
+                        .map(|(_, txin)| txin)
+                        .collect();
+                    // The selected inputs are used as they are.
+                    proto.input_selector = Proto::InputSelector::UseAll;
+                    changeless = true;
+                },
+                None => {
+                    // The same as `SelectDescending`, the inputs that must be spent first.
+                    proto.inputs.sort_by_key(|input| Reverse(input.value));
+                    proto.inputs.sort_by_key(|input| {
+                        input.coin_control != Proto::CoinControl::CoinMustSpend
+                    });
+                },
+            }
+        }
+        let with_change = !proto.disable_change_output && !changeless;
+
         // Add change output generation is enabled, push it to the proto structure.
-        if !proto.disable_change_output {
+        if with_change {
             proto.outputs.push(Proto::TxOut {
                 // We set the change value later.
                 value: 0,
@@ -119,7 +197,8 @@ impl Compiler<StandardBitcoinContext> {
         // Select the inputs accordingly by updating `proto.inputs`.
         let available = std::mem::take(&mut proto.inputs); // Drain `proto.inputs`
         match &proto.input_selector {
-            Proto::InputSelector::UseAll => {
+            // `UsePinned` has been replaced with `UseAll` above.
+            Proto::InputSelector::UseAll | Proto::InputSelector::UsePinned => {
                 // Simply add all inputs.
                 for txin in available {
                     let n_txin = convert_proto_to_txin(&txin)?;
@@ -131,7 +210,8 @@ impl Compiler<StandardBitcoinContext> {
             },
             Proto::InputSelector::SelectInOrder
             | Proto::InputSelector::SelectAscending
-            | Proto::InputSelector::SelectDescending => {
+            | Proto::InputSelector::SelectDescending
+            | Proto::InputSelector::SelectBranchAndBound => {
                 let mut total_input_amount = 0;
                 let mut total_input_weight = 0;
 
@@ -149,7 +229,7 @@ impl Compiler<StandardBitcoinContext> {
                     proto.inputs.push(txin);
 
                     // Update the change amount, if set.
-                    if !proto.disable_change_output {
+                    if with_change {
                         let change_output = tx.output.last_mut().expect("change output not set");
                         change_output.value =
                             total_input_amount.saturating_sub(total_output_amount);
@@ -161,7 +241,10 @@ impl Compiler<StandardBitcoinContext> {
                     let weight_estimate = tx.weight().to_wu() + total_input_weight;
                     let fee_estimate = (weight_estimate + 3) / 4 * proto.weight_base;
 
-                    if total_input_amount >= total_output_amount + fee_estimate {
+                    // All the inputs that must be spent are selected.
+                    if proto.inputs.len() >= pinned_count
+                        && total_input_amount >= total_output_amount + fee_estimate
+                    {
                         // Enough inputs to cover the output and fee estimate.
                         break;
                     }
@@ -179,7 +262,7 @@ impl Compiler<StandardBitcoinContext> {
         // weight). Note that the scriptSig/Witness fields are blanked inside
         // `tx`, hence we need to rely on the values passed on the proto
         // structure.
-        let weight_estimate = tx.weight().to_wu() + total_input_weight;
+        let mut weight_estimate = tx.weight().to_wu() + total_input_weight;
         let fee_estimate = (weight_estimate + 3) / 4 * proto.weight_base;
 
         // Check if there are enough inputs to cover the full output and fee estimate.
@@ -188,7 +271,8 @@ impl Compiler<StandardBitcoinContext> {
         }
 
         // Set the change output amount in the proto structure, if enabled.
-        if !proto.disable_change_output {
+        let mut absorbed_change = None;
+        if with_change {
             // Update the change amount in the proto list.
             let change_output = proto.outputs.last_mut().expect("change output not set");
             change_output.value = total_input_amount
@@ -198,6 +282,23 @@ impl Compiler<StandardBitcoinContext> {
             // Update the change amount in the `bitcoin` crate native transaction.
             // This is required for the sighash calculation.
             tx.output.last_mut().expect("change output not set").value = change_output.value;
+
+            let is_dust = dust_threshold.is_dust(
+                change_output.value,
+                Script::from_bytes(&change_output.script_pubkey),
+            );
+            // The change below the threshold is left to the miners, and so is not dust.
+            let below_threshold = change_output.value < proto.change_to_fee_threshold;
+            if is_dust && !below_threshold && proto.dust_policy == Proto::DustPolicy::DustReject {
+                return Err(Error::from(Proto::Error::Error_dust_output));
+            }
+            // The dust change is left to the miners.
+            let drop_dust = is_dust && proto.dust_policy == Proto::DustPolicy::DustDropChangeToFee;
+            if below_threshold || drop_dust {
+                absorbed_change = proto.outputs.pop().map(|output| output.value);
+                tx.output.pop();
+                weight_estimate = tx.weight().to_wu() + total_input_weight;
+            }
         }
 
         // Calculate the effective fee.
@@ -205,10 +306,28 @@ impl Compiler<StandardBitcoinContext> {
         let fee_estimate = total_input_amount - total_output_amount;
 
         // Calculate the sighashes.
+        let outputs_len = tx.output.len();
         let mut cache = SighashCache::new(&tx);
         let mut sighashes: Vec<(Vec<u8>, ProtoSigningMethod, Proto::SighashType)> = vec![];
 
         for (index, input) in proto.inputs.iter().enumerate() {
+            // `SIGHASH_SINGLE` requires the output of the same index. Otherwise, the legacy
+            // sighash is the constant `1` (the "SIGHASH_SINGLE bug"), so its signature could
+            // be replayed for any transaction that spends the same UTXO.
+            if is_sighash_single(input.sighash_type) && index >= outputs_len {
+                return Err(Error::from(Proto::Error::Error_invalid_sighash_type));
+            }
+
+            // Only the Taproot inputs can have an annex.
+            if !input.annex.is_empty()
+                && matches!(
+                    input.signing_method,
+                    ProtoSigningMethod::Legacy | ProtoSigningMethod::Segwit
+                )
+            {
+                return Err(Error::from(Proto::Error::Error_invalid_annex));
+            }
+
             match input.signing_method {
                 // Use the legacy hashing mechanism (e.g. P2SH, P2PK, P2PKH).
                 ProtoSigningMethod::Legacy => {
@@ -271,14 +390,14 @@ impl Compiler<StandardBitcoinContext> {
                         .iter()
                         .map(|i| TxOut {
                             value: i.value,
-                            script_pubkey: ScriptBuf::from_bytes(i.script_pubkey.to_vec()),
+                            script_pubkey: prevout_script_pubkey(i),
                         })
                         .collect::<Vec<TxOut>>();
 
                     let sighash = cache.taproot_signature_hash(
                         index,
                         &Prevouts::All(&prevouts),
-                        None,
+                        annex_from_proto(input)?,
                         leaf_hash,
                         sighash_type,
                     )?;
@@ -309,14 +428,14 @@ impl Compiler<StandardBitcoinContext> {
                         index,
                         TxOut {
                             value: input.value,
-                            script_pubkey: ScriptBuf::from_bytes(input.script_pubkey.to_vec()),
+                            script_pubkey: prevout_script_pubkey(input),
                         },
                     );
 
                     let sighash = cache.taproot_signature_hash(
                         index,
                         &prevouts,
-                        None,
+                        annex_from_proto(input)?,
                         leaf_hash,
                         sighash_type,
                     )?;
@@ -359,6 +478,9 @@ impl Compiler<StandardBitcoinContext> {
                     signing_method: input.signing_method,
                     sighash_type: input.sighash_type,
                     leaf_hash: input.leaf_hash.to_vec().into(),
+                    annex: input.annex.to_vec().into(),
+                    coin_control: input.coin_control,
+                    prevout_script_pubkey: input.prevout_script_pubkey.to_vec().into(),
                 })
                 .collect(),
             outputs: proto
@@ -371,6 +493,8 @@ impl Compiler<StandardBitcoinContext> {
                 .collect(),
             weight_estimate,
             fee_estimate,
+            change_absorbed: absorbed_change.is_some(),
+            absorbed_change: absorbed_change.unwrap_or_default(),
         })
     }
 
@@ -387,8 +511,8 @@ impl Compiler<StandardBitcoinContext> {
             return Err(Error::from(Proto::Error::Error_insufficient_inputs));
         }
 
-        // No ouputs specified.
-        if total_output_amount == 0 {
+        // No ouputs specified. The `OP_RETURN` outputs carry data instead of value.
+        if !has_outputs(&proto.outputs) {
             return Err(Error::from(Proto::Error::Error_no_outputs_specified));
         }
 
@@ -460,6 +584,101 @@ impl Compiler<StandardBitcoinContext> {
     }
 }
 
+// Convenience function to select the inputs that cover the outputs and the
+// fee without a change output, see `coin_selection::branch_and_bound`. Returns
+// the indexes of the selected inputs, if found.
+fn select_changeless(proto: &Proto::SigningInput<'_>) -> Result<Option<Vec<usize>>> {
+    let fee = |weight: u64| (weight + 3) / 4 * proto.weight_base;
+
+    // The transaction without the inputs and the change output.
+    let tx = Transaction {
+        version: proto.version,
+        lock_time: lock_time_from_proto(&proto.lock_time)?,
+        input: vec![],
+        output: proto
+            .outputs
+            .iter()
+            .map(|output| TxOut {
+                value: output.value,
+                script_pubkey: ScriptBuf::from_bytes(output.script_pubkey.to_vec()),
+            })
+            .collect(),
+    };
+    let total_output_amount: u64 = proto.outputs.iter().map(|output| output.value).sum();
+    let target = total_output_amount + fee(tx.weight().to_wu());
+
+    // The cost of creating the change output and spending it later, which is
+    // the maximum excess to be left to the miners instead.
+    let change_output_weight = 4
+        * (
+            // value + length + scriptPubkey.
+            8 + 1 + proto.change_script_pubkey.len() as u64
+        );
+    let cost_of_change = fee(change_output_weight) + fee(CHANGE_SPEND_WEIGHT);
+
+    // Note that the fee of each input is rounded up, so the selected inputs
+    // cover the fee of the whole transaction.
+    let effective_values: Vec<u64> = proto
+        .inputs
+        .iter()
+        .map(|input| {
+            input
+                .value
+                .saturating_sub(fee(TXIN_BASE_WEIGHT + input.weight_estimate))
+        })
+        .collect();
+
+    Ok(coin_selection::branch_and_bound(
+        &effective_values,
+        target,
+        cost_of_change,
+    ))
+}
+
+/// Whether any of the `outputs` has a value or is an `OP_RETURN` output.
+fn has_outputs(outputs: &[Proto::TxOut<'_>]) -> bool {
+    outputs
+        .iter()
+        .any(|output| output.value > 0 || Script::from_bytes(&output.script_pubkey).is_op_return())
+}
+
+/// Returns the annex of the Taproot witness, i.e. the last item if there are at least two and it starts with `0x50`.
+pub fn taproot_annex(witness: &Witness) -> Option<&[u8]> {
+    if witness.len() < 2 {
+        return None;
+    }
+    witness
+        .last()
+        .filter(|item| item.first() == Some(&TAPROOT_ANNEX_PREFIX))
+}
+
+/// Returns the annex of the Taproot input, `None` if it's empty.
+fn annex_from_proto<'a>(input: &'a Proto::TxIn<'_>) -> Result<Option<Annex<'a>>> {
+    if input.annex.is_empty() {
+        return Ok(None);
+    }
+    Annex::new(input.annex.as_ref())
+        .map(Some)
+        .map_err(|_| Error::from(Proto::Error::Error_invalid_annex))
+}
+
+/// Returns the script pubkey of the output spent by the `input`, which the Taproot sighashes commit to.
+fn prevout_script_pubkey(input: &Proto::TxIn<'_>) -> ScriptBuf {
+    if input.prevout_script_pubkey.is_empty() {
+        ScriptBuf::from_bytes(input.script_pubkey.to_vec())
+    } else {
+        ScriptBuf::from_bytes(input.prevout_script_pubkey.to_vec())
+    }
+}
+
+/// Whether the sighash type signs only the output of the same index as the input.
+fn is_sighash_single(sighash_type: SighashType) -> bool {
+    matches!(
+        sighash_type,
+        SighashType::Single | SighashType::SinglePlusAnyoneCanPay
+    )
+}
+
 fn convert_proto_to_txin<'a>(proto: &'a Proto::TxIn<'a>) -> Result<TxIn> {
     let txid = Txid::from_slice(proto.txid.as_ref())
         .map_err(|_| Error::from(Proto::Error::Error_invalid_txid))?;
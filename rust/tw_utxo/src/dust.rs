@@ -0,0 +1,87 @@
+use bitcoin::consensus::encode::VarInt;
+use bitcoin::Script;
+use tw_proto::Utxo::Proto;
+
+/// The dust relay fee of Bitcoin Core, in satoshis per 1000 vbytes.
+pub const BITCOIN_DUST_RELAY_FEE: u64 = 3_000;
+/// The dust relay fee of Litecoin Core, in litoshis per 1000 vbytes.
+pub const LITECOIN_DUST_RELAY_FEE: u64 = 30_000;
+/// The dust limit of Dogecoin Core, in koinus, regardless of the output script.
+pub const DOGECOIN_DUST_THRESHOLD: u64 = 1_000_000;
+
+/// The maximum length of a script that can be spent.
+const MAX_SCRIPT_SIZE: usize = 10_000;
+/// The pay-to-anchor (P2A) script `OP_1 <0x4e73>`.
+const P2A_SCRIPT: [u8; 4] = [0x51, 0x02, 0x4e, 0x73];
+/// The size of the outpoint, the script length, the sequence and the script signature
+/// `<signature> <compressed public key>` of the input that spends a legacy output.
+const LEGACY_SPEND_SIZE: u64 = 32 + 4 + 1 + 107 + 4;
+/// The same as [`LEGACY_SPEND_SIZE`], but the 107-byte witness is scaled down.
+const WITNESS_SPEND_SIZE: u64 = 32 + 4 + 1 + 107 / 4 + 4;
+
+/// How the dust threshold of an output is computed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DustThreshold {
+    /// The output is dust if spending it costs more than a third of its value,
+    /// i.e. the rule of Bitcoin Core and its forks, given the dust relay fee in satoshis per 1000 vbytes.
+    RelayFee(u64),
+    /// The same threshold for any output, e.g. for Dogecoin.
+    Fixed(u64),
+}
+
+impl DustThreshold {
+    pub fn bitcoin() -> Self {
+        DustThreshold::RelayFee(BITCOIN_DUST_RELAY_FEE)
+    }
+
+    pub fn litecoin() -> Self {
+        DustThreshold::RelayFee(LITECOIN_DUST_RELAY_FEE)
+    }
+
+    pub fn dogecoin() -> Self {
+        DustThreshold::Fixed(DOGECOIN_DUST_THRESHOLD)
+    }
+
+    /// Returns the threshold of the signing input, the fixed one if set, or the Bitcoin one by default.
+    pub fn from_proto(proto: &Proto::SigningInput<'_>) -> Self {
+        if proto.fixed_dust_threshold != 0 {
+            DustThreshold::Fixed(proto.fixed_dust_threshold)
+        } else if proto.dust_relay_fee != 0 {
+            DustThreshold::RelayFee(proto.dust_relay_fee)
+        } else {
+            DustThreshold::bitcoin()
+        }
+    }
+
+    /// Returns the minimum value of an output that pays to the `script_pubkey`.
+    /// The unspendable outputs, e.g. `OP_RETURN`, are never dust, and neither are the P2A anchors,
+    /// which are relayed with a zero value as ephemeral dust.
+    pub fn threshold(&self, script_pubkey: &Script) -> u64 {
+        if script_pubkey.is_op_return()
+            || script_pubkey.len() > MAX_SCRIPT_SIZE
+            || script_pubkey.as_bytes() == P2A_SCRIPT
+        {
+            return 0;
+        }
+
+        match *self {
+            DustThreshold::RelayFee(fee) => {
+                // The value, the script and the input that spends it.
+                let output_size = 8
+                    + VarInt(script_pubkey.len() as u64).len() as u64
+                    + script_pubkey.len() as u64;
+                let spend_size = if script_pubkey.is_witness_program() {
+                    WITNESS_SPEND_SIZE
+                } else {
+                    LEGACY_SPEND_SIZE
+                };
+                (output_size + spend_size) * fee / 1000
+            },
+            DustThreshold::Fixed(threshold) => threshold,
+        }
+    }
+
+    pub fn is_dust(&self, value: u64, script_pubkey: &Script) -> bool {
+        value < self.threshold(script_pubkey)
+    }
+}
@@ -1,6 +1,8 @@
 use tw_proto::Utxo::Proto;
 
+pub mod coin_selection;
 pub mod compiler;
+pub mod dust;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -0,0 +1,149 @@
+mod common;
+use common::{pubkey_hash_from_hex, txid_rev, witness_pubkey_hash};
+
+use bitcoin::blockdata::opcodes::all::OP_RETURN;
+use bitcoin::hashes::Hash;
+use bitcoin::{ScriptBuf, WScriptHash};
+use tw_proto::Utxo::Proto;
+use tw_utxo::compiler::{Compiler, StandardBitcoinContext};
+use tw_utxo::dust::DustThreshold;
+
+fn p2wpkh() -> ScriptBuf {
+    ScriptBuf::new_v0_p2wpkh(&witness_pubkey_hash(
+        "60cda7b50f14c152d7401c28ae773c698db92373",
+    ))
+}
+
+#[test]
+fn dust_thresholds() {
+    let p2pkh = ScriptBuf::new_p2pkh(&pubkey_hash_from_hex(
+        "aabbccddeeff00112233445566778899aabbccdd",
+    ));
+    let p2sh = ScriptBuf::new_p2sh(&ScriptBuf::new().script_hash());
+    let p2wsh = ScriptBuf::new_v0_p2wsh(&WScriptHash::all_zeros());
+    let op_return = ScriptBuf::builder()
+        .push_opcode(OP_RETURN)
+        .push_slice(b"hello")
+        .into_script();
+    let p2a = ScriptBuf::from_bytes(vec![0x51, 0x02, 0x4e, 0x73]);
+
+    // The same values as Bitcoin Core.
+    let bitcoin = DustThreshold::bitcoin();
+    for (script, threshold) in [
+        (&p2pkh, 546),
+        (&p2sh, 540),
+        (&p2wpkh(), 294),
+        (&p2wsh, 330),
+        (&op_return, 0),
+        (&p2a, 0),
+    ] {
+        assert_eq!(bitcoin.threshold(script), threshold, "{script}");
+    }
+    assert!(bitcoin.is_dust(293, &p2wpkh()));
+    assert!(!bitcoin.is_dust(294, &p2wpkh()));
+
+    let litecoin = DustThreshold::litecoin();
+    assert_eq!(litecoin.threshold(&p2pkh), 5_460);
+    assert_eq!(litecoin.threshold(&p2wpkh()), 2_940);
+
+    let dogecoin = DustThreshold::dogecoin();
+    assert_eq!(dogecoin.threshold(&p2pkh), 1_000_000);
+    assert_eq!(dogecoin.threshold(&p2wpkh()), 1_000_000);
+    assert_eq!(dogecoin.threshold(&op_return), 0);
+    assert_eq!(dogecoin.threshold(&p2a), 0);
+}
+
+fn signing_input(
+    output_value: u64,
+    dust_policy: Proto::DustPolicy,
+) -> Proto::SigningInput<'static> {
+    let txid = txid_rev("1e1cdc48aa990d7e154a161d5b5f1cad737742e97d2712ab188027bb42e6e47b");
+
+    Proto::SigningInput {
+        version: 2,
+        inputs: vec![Proto::TxIn {
+            txid: txid.into(),
+            value: 10_000,
+            sequence: u32::MAX,
+            ..Default::default()
+        }],
+        outputs: vec![Proto::TxOut {
+            value: output_value,
+            script_pubkey: p2wpkh().to_bytes().into(),
+        }],
+        input_selector: Proto::InputSelector::UseAll,
+        weight_base: 1,
+        change_script_pubkey: p2wpkh().to_bytes().into(),
+        dust_policy,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn dust_change_policies() {
+    // The fee of the 113-byte transaction leaves the change of 187, which is dust.
+    let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing_input(
+        9_700,
+        Proto::DustPolicy::DustInclude,
+    ));
+    assert_eq!(output.error, Proto::Error::OK);
+    assert_eq!(output.outputs.len(), 2);
+    assert_eq!(output.outputs[1].value, 187);
+    assert_eq!(output.fee_estimate, 113);
+
+    let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing_input(
+        9_700,
+        Proto::DustPolicy::DustReject,
+    ));
+    assert_eq!(output.error, Proto::Error::Error_dust_output);
+
+    // The change is left to the miners.
+    let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing_input(
+        9_700,
+        Proto::DustPolicy::DustDropChangeToFee,
+    ));
+    assert_eq!(output.error, Proto::Error::OK);
+    assert_eq!(output.outputs.len(), 1);
+    assert_eq!(output.fee_estimate, 300);
+    assert_eq!(output.weight_estimate, 4 * (113 - 31));
+
+    // The change of 1_887 is dust for Litecoin, but not for Bitcoin.
+    let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing_input(
+        8_000,
+        Proto::DustPolicy::DustDropChangeToFee,
+    ));
+    assert_eq!(output.outputs.len(), 2);
+    let mut litecoin = signing_input(8_000, Proto::DustPolicy::DustDropChangeToFee);
+    litecoin.dust_relay_fee = 30_000;
+    let output = Compiler::<StandardBitcoinContext>::preimage_hashes(litecoin);
+    assert_eq!(output.error, Proto::Error::OK);
+    assert_eq!(output.outputs.len(), 1);
+
+    // The fixed threshold overrides the relay fee.
+    let mut fixed = signing_input(8_000, Proto::DustPolicy::DustReject);
+    fixed.dust_relay_fee = 30_000;
+    fixed.fixed_dust_threshold = 1_000;
+    let output = Compiler::<StandardBitcoinContext>::preimage_hashes(fixed);
+    assert_eq!(output.error, Proto::Error::OK);
+    assert_eq!(output.outputs.len(), 2);
+}
+
+#[test]
+fn dust_recipient_output() {
+    // Only the change can be dropped.
+    for (dust_policy, error) in [
+        (Proto::DustPolicy::DustInclude, Proto::Error::OK),
+        (
+            Proto::DustPolicy::DustReject,
+            Proto::Error::Error_dust_output,
+        ),
+        (
+            Proto::DustPolicy::DustDropChangeToFee,
+            Proto::Error::Error_dust_output,
+        ),
+    ] {
+        let output =
+            Compiler::<StandardBitcoinContext>::preimage_hashes(signing_input(100, dust_policy));
+        assert_eq!(output.error, error, "{dust_policy:?}");
+    }
+}
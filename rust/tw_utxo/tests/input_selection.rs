@@ -3,6 +3,7 @@ use common::{pubkey_hash_from_hex, txid_rev};
 
 use bitcoin::ScriptBuf;
 use tw_proto::Utxo::Proto;
+use tw_utxo::coin_selection::branch_and_bound;
 use tw_utxo::compiler::{Compiler, StandardBitcoinContext};
 
 const WEIGHT_BASE: u64 = 2;
@@ -54,6 +55,7 @@ fn input_selector_all() {
         change_script_pubkey: Default::default(),
         // DISABLE change output.
         disable_change_output: true,
+        ..Default::default()
     };
 
     let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing);
@@ -83,6 +85,7 @@ fn input_selector_all() {
         change_script_pubkey: change_script.as_bytes().into(),
         // ENABLE change output.
         disable_change_output: false,
+        ..Default::default()
     };
 
     let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing);
@@ -146,6 +149,7 @@ fn input_selector_all_insufficient_inputs() {
         change_script_pubkey: Default::default(),
         // DISABLE change output.
         disable_change_output: true,
+        ..Default::default()
     };
 
     let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing);
@@ -169,6 +173,7 @@ fn input_selector_all_insufficient_inputs() {
         change_script_pubkey: change_script.as_bytes().into(),
         // ENABLE change output.
         disable_change_output: false,
+        ..Default::default()
     };
 
     let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing);
@@ -222,6 +227,7 @@ fn input_selector_one_input_required() {
         change_script_pubkey: Default::default(),
         // DISABLE change output.
         disable_change_output: true,
+        ..Default::default()
     };
 
     let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing);
@@ -248,6 +254,7 @@ fn input_selector_one_input_required() {
         change_script_pubkey: change_script.as_bytes().into(),
         // ENABLE change output.
         disable_change_output: false,
+        ..Default::default()
     };
 
     let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing);
@@ -316,6 +323,7 @@ fn input_selector_two_inputs_required() {
         change_script_pubkey: Default::default(),
         // DISABLE change output.
         disable_change_output: true,
+        ..Default::default()
     };
 
     let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing);
@@ -344,6 +352,7 @@ fn input_selector_two_inputs_required() {
         change_script_pubkey: change_script.as_bytes().into(),
         // ENABLE change output.
         disable_change_output: false,
+        ..Default::default()
     };
 
     let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing);
@@ -399,6 +408,7 @@ fn input_selector_one_input_cannot_cover_fees() {
         change_script_pubkey: Default::default(),
         // DISABLE change output.
         disable_change_output: true,
+        ..Default::default()
     };
 
     let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing);
@@ -423,6 +433,7 @@ fn input_selector_one_input_cannot_cover_fees() {
         change_script_pubkey: change_script.as_bytes().into(),
         // ENABLE change output.
         disable_change_output: false,
+        ..Default::default()
     };
 
     let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing);
@@ -466,6 +477,7 @@ fn input_selector_exact_balance_no_change() {
         change_script_pubkey: change_script.as_bytes().into(),
         // ENABLE change output.
         disable_change_output: false,
+        ..Default::default()
     };
 
     let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing);
@@ -516,6 +528,7 @@ fn input_selector_empty_script_bufs() {
         change_script_pubkey: Default::default(),
         // ENABLE change output.
         disable_change_output: false,
+        ..Default::default()
     };
 
     let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing);
@@ -573,6 +586,7 @@ fn input_selector_select_ascending() {
         change_script_pubkey: change_script.as_bytes().into(),
         // ENABLE change output.
         disable_change_output: false,
+        ..Default::default()
     };
 
     let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing);
@@ -587,3 +601,275 @@ fn input_selector_select_ascending() {
     assert_eq!(output.outputs.len(), 2);
     assert_eq!(output.outputs[0], out1);
 }
+
+#[test]
+fn input_selector_branch_and_bound() {
+    // Reusing the txid is fine here, although in production this would mark the transaction invalid.
+    let txid = txid_rev("1e1cdc48aa990d7e154a161d5b5f1cad737742e97d2712ab188027bb42e6e47b");
+
+    let tx1 = Proto::TxIn {
+        txid: txid.as_slice().into(),
+        value: 1_100,
+        sequence: u32::MAX,
+        ..Default::default()
+    };
+    let tx2 = Proto::TxIn {
+        txid: txid.as_slice().into(),
+        value: 2_000,
+        sequence: u32::MAX,
+        ..Default::default()
+    };
+    let tx3 = Proto::TxIn {
+        txid: txid.as_slice().into(),
+        value: 4_200,
+        sequence: u32::MAX,
+        ..Default::default()
+    };
+    let tx4 = Proto::TxIn {
+        txid: txid.as_slice().into(),
+        value: 10_000,
+        sequence: u32::MAX,
+        ..Default::default()
+    };
+
+    let out1 = Proto::TxOut {
+        value: 5_000,
+        script_pubkey: Default::default(),
+    };
+
+    let change_script = change_output();
+    let signing = Proto::SigningInput {
+        version: 2,
+        lock_time: Default::default(),
+        inputs: vec![tx1.clone(), tx2.clone(), tx3.clone(), tx4.clone()],
+        outputs: vec![out1.clone()],
+        input_selector: Proto::InputSelector::SelectBranchAndBound,
+        weight_base: WEIGHT_BASE,
+        change_script_pubkey: change_script.as_bytes().into(),
+        // ENABLE change output.
+        disable_change_output: false,
+        ..Default::default()
+    };
+
+    let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing);
+    assert_eq!(output.error, Proto::Error::OK);
+    assert_eq!(output.sighashes.len(), 2);
+
+    // The two inputs cover the output and the fee, with the excess lower than
+    // the cost of the change output, in the given order.
+    assert_eq!(output.inputs.len(), 2);
+    assert_eq!(output.inputs[0], tx1);
+    assert_eq!(output.inputs[1], tx3);
+
+    // No change output is created, the excess is left to the miners.
+    assert_eq!(output.outputs.len(), 1);
+    assert_eq!(output.outputs[0], out1);
+    assert_eq!(output.fee_estimate, 1_100 + 4_200 - 5_000);
+}
+
+#[test]
+fn input_selector_branch_and_bound_fallback() {
+    // Reusing the txid is fine here, although in production this would mark the transaction invalid.
+    let txid = txid_rev("1e1cdc48aa990d7e154a161d5b5f1cad737742e97d2712ab188027bb42e6e47b");
+
+    let tx1 = Proto::TxIn {
+        txid: txid.as_slice().into(),
+        value: 1_000,
+        sequence: u32::MAX,
+        ..Default::default()
+    };
+    let tx2 = Proto::TxIn {
+        txid: txid.as_slice().into(),
+        value: 3_000,
+        sequence: u32::MAX,
+        ..Default::default()
+    };
+    let tx3 = Proto::TxIn {
+        txid: txid.as_slice().into(),
+        value: 2_000,
+        sequence: u32::MAX,
+        ..Default::default()
+    };
+
+    let out1 = Proto::TxOut {
+        value: 500,
+        script_pubkey: Default::default(),
+    };
+
+    let change_script = change_output();
+    let signing = Proto::SigningInput {
+        version: 2,
+        lock_time: Default::default(),
+        inputs: vec![tx1.clone(), tx2.clone(), tx3.clone()],
+        outputs: vec![out1.clone()],
+        input_selector: Proto::InputSelector::SelectBranchAndBound,
+        weight_base: WEIGHT_BASE,
+        change_script_pubkey: change_script.as_bytes().into(),
+        // ENABLE change output.
+        disable_change_output: false,
+        ..Default::default()
+    };
+
+    let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing);
+    assert_eq!(output.error, Proto::Error::OK);
+
+    // Every input exceeds the output too much, so the largest input is selected.
+    assert_eq!(output.inputs.len(), 1);
+    assert_eq!(output.inputs[0], tx2);
+
+    let change_out = Proto::TxOut {
+        value: 3_000 - 500 - output.fee_estimate,
+        script_pubkey: change_script.as_bytes().into(),
+    };
+
+    assert_eq!(output.outputs.len(), 2);
+    assert_eq!(output.outputs[0], out1);
+    assert_eq!(output.outputs[1], change_out);
+}
+
+// Convenience function, creates the inputs of 1_000 (selectable), 2_000 (must
+// be spent), 3_000 (never spent) and 4_000 (must be spent).
+fn coin_control_inputs() -> Vec<Proto::TxIn<'static>> {
+    let txid = txid_rev("1e1cdc48aa990d7e154a161d5b5f1cad737742e97d2712ab188027bb42e6e47b");
+
+    [
+        (1_000, Proto::CoinControl::CoinSelectable),
+        (2_000, Proto::CoinControl::CoinMustSpend),
+        (3_000, Proto::CoinControl::CoinNeverSpend),
+        (4_000, Proto::CoinControl::CoinMustSpend),
+    ]
+    .into_iter()
+    .enumerate()
+    .map(|(vout, (value, coin_control))| Proto::TxIn {
+        txid: txid.clone().into(),
+        vout: vout as u32,
+        value,
+        sequence: u32::MAX,
+        coin_control,
+        ..Default::default()
+    })
+    .collect()
+}
+
+#[test]
+fn input_selector_use_pinned() {
+    let inputs = coin_control_inputs();
+
+    let out1 = Proto::TxOut {
+        value: 500,
+        script_pubkey: Default::default(),
+    };
+
+    let change_script = change_output();
+    let signing = Proto::SigningInput {
+        version: 2,
+        lock_time: Default::default(),
+        inputs: inputs.clone(),
+        outputs: vec![out1.clone()],
+        // Use only the inputs that must be spent.
+        input_selector: Proto::InputSelector::UsePinned,
+        weight_base: WEIGHT_BASE,
+        change_script_pubkey: change_script.as_bytes().into(),
+        disable_change_output: false,
+        ..Default::default()
+    };
+
+    let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing.clone());
+    assert_eq!(output.error, Proto::Error::OK);
+
+    // Both pinned inputs are used, although one of them covers the output.
+    assert_eq!(output.inputs.len(), 2);
+    assert_eq!(output.inputs[0], inputs[1]);
+    assert_eq!(output.inputs[1], inputs[3]);
+
+    let change_out = Proto::TxOut {
+        value: 6_000 - 500 - output.fee_estimate,
+        script_pubkey: change_script.as_bytes().into(),
+    };
+    assert_eq!(output.outputs.len(), 2);
+    assert_eq!(output.outputs[0], out1);
+    assert_eq!(output.outputs[1], change_out);
+
+    // The pinned inputs must cover the outputs on their own.
+    let mut insufficient = signing.clone();
+    insufficient.outputs[0].value = 6_500;
+    let output = Compiler::<StandardBitcoinContext>::preimage_hashes(insufficient);
+    assert_eq!(output.error, Proto::Error::Error_insufficient_inputs);
+
+    // No input is pinned.
+    let mut unpinned = signing;
+    for input in unpinned.inputs.iter_mut() {
+        input.coin_control = Proto::CoinControl::CoinSelectable;
+    }
+    let output = Compiler::<StandardBitcoinContext>::preimage_hashes(unpinned);
+    assert_eq!(output.error, Proto::Error::Error_insufficient_inputs);
+}
+
+#[test]
+fn input_selector_coin_control() {
+    let inputs = coin_control_inputs();
+
+    let out1 = Proto::TxOut {
+        value: 5_800,
+        script_pubkey: Default::default(),
+    };
+
+    let change_script = change_output();
+    let signing = Proto::SigningInput {
+        version: 2,
+        lock_time: Default::default(),
+        inputs: inputs.clone(),
+        outputs: vec![out1.clone()],
+        input_selector: Proto::InputSelector::SelectDescending,
+        weight_base: WEIGHT_BASE,
+        change_script_pubkey: change_script.as_bytes().into(),
+        disable_change_output: false,
+        ..Default::default()
+    };
+
+    let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing.clone());
+    assert_eq!(output.error, Proto::Error::OK);
+
+    // The inputs that must be spent are selected first, then the selectable
+    // one covers the fee. The input that must never be spent is skipped,
+    // although it's larger.
+    assert_eq!(output.inputs.len(), 3);
+    assert_eq!(output.inputs[0], inputs[3]);
+    assert_eq!(output.inputs[1], inputs[1]);
+    assert_eq!(output.inputs[2], inputs[0]);
+
+    // The same with Branch and Bound, which falls back to the descending order.
+    let mut bnb = signing.clone();
+    bnb.input_selector = Proto::InputSelector::SelectBranchAndBound;
+    let output = Compiler::<StandardBitcoinContext>::preimage_hashes(bnb);
+    assert_eq!(output.error, Proto::Error::OK);
+    assert_eq!(output.inputs.len(), 3);
+    assert_eq!(output.inputs[0], inputs[3]);
+    assert_eq!(output.inputs[1], inputs[1]);
+    assert_eq!(output.inputs[2], inputs[0]);
+
+    // The input that must never be spent isn't used, even if all the others
+    // don't cover the outputs.
+    let mut insufficient = signing;
+    insufficient.outputs[0].value = 6_800;
+    insufficient.input_selector = Proto::InputSelector::UseAll;
+    let output = Compiler::<StandardBitcoinContext>::preimage_hashes(insufficient);
+    assert_eq!(output.error, Proto::Error::Error_insufficient_inputs);
+}
+
+#[test]
+fn branch_and_bound_least_waste() {
+    // The exact match is preferred over the first found selection.
+    assert_eq!(
+        branch_and_bound(&[5_000, 3_000, 2_000, 1_500, 1_000], 4_500, 200),
+        Some(vec![1, 3])
+    );
+    // Of the equal values the first one is selected, and the zero value is never.
+    assert_eq!(
+        branch_and_bound(&[4_000, 1_000, 1_000, 0], 5_000, 100),
+        Some(vec![0, 1])
+    );
+    // The excess exceeds the cost of change or the value is insufficient.
+    assert_eq!(branch_and_bound(&[5_000, 3_000], 4_500, 200), None);
+    assert_eq!(branch_and_bound(&[5_000, 3_000], 9_000, 200), None);
+}